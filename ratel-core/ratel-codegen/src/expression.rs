@@ -47,6 +47,7 @@ impl<'ast, G: Generator> ToCode<G> for Literal<'ast> {
             False             => gen.write_bytes(b"false"),
             Binary(n)         => gen.write(&n),
             Number(ref val)   |
+            BigInt(ref val)   |
             String(ref val)   |
             RegEx(ref val)    => gen.write(val),
         }
@@ -363,6 +364,7 @@ mod test {
         assert_min("false", "false;");
         assert_min("42", "42;");
         assert_min("3.14", "3.14;");
+        assert_min("0x2An", "0x2An;");
         assert_min(r#" "foobar" "#, r#""foobar";"#);
         assert_min(r#" 'foobar' "#, r#"'foobar';"#);
     }
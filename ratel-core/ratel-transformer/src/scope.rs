@@ -1,7 +1,10 @@
+use std::collections::HashMap;
 use std::fmt::{self, Debug};
 
 use ratel::Module;
-use ratel::ast::{Identifier, ExpressionNode};
+use ratel::ast::{Identifier, ExpressionNode, StatementNode, Pattern};
+use ratel::ast::expression::{ArrowBody, ArrowExpression, FunctionExpression};
+use ratel::ast::statement::{FunctionStatement, TryStatement};
 use ratel_visitor::{Visitable, ScopeKind, Visitor};
 use toolshed::{Arena, CopyCell};
 use toolshed::list::GrowableList;
@@ -17,6 +20,66 @@ pub fn analyze<'ast>(module: &'ast Module<'ast>) -> &'ast Scope<'ast> {
     visitor.current.get()
 }
 
+/// What an identifier in a function refers to, when it can be found without looking it up by name
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binding {
+    /// A variable of the function, which no closure refers to, kept in the slot `index` of the call
+    Local(u32),
+    /// A variable closures refer to, kept in the slot `index` of the call that declared it, which
+    /// is `depth` calls with captured variables out from the running one
+    Captured { depth: u32, index: u32 },
+}
+
+/// The slots a call to a function needs for its variables
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layout {
+    /// The names of the variables only the function refers to
+    pub locals: Vec<String>,
+    /// The names of the variables closures refer to
+    pub captured: Vec<String>,
+}
+
+/// The bindings of the identifiers in a module, and the slots of the functions they're in
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    /// What each identifier refers to, keyed by the address of its text
+    pub bindings: HashMap<usize, Binding>,
+    /// The slots of each function whose variables are all resolved, keyed by where its body starts
+    pub functions: HashMap<u32, Layout>,
+}
+
+impl Resolution {
+    /// What an identifier refers to, if it was resolved
+    #[inline]
+    pub fn binding(&self, ident: &str) -> Option<Binding> {
+        self.bindings.get(&(ident.as_ptr() as usize)).cloned()
+    }
+
+    /// The slots of the function whose body starts at `body_start`, if its variables are all resolved
+    #[inline]
+    pub fn layout(&self, body_start: u32) -> Option<&Layout> {
+        self.functions.get(&body_start)
+    }
+}
+
+/// Resolve every identifier declaring or using a variable of a function to a slot.
+///
+/// Variables at the top level are properties of the global object, and those of a function
+/// that uses `eval` or `arguments`, or of a function around one, can be looked up by name at
+/// runtime, so identifiers referring to them aren't resolved. (`with` statements would be the
+/// same, but the parser rejects them.) Declarations in blocks other than `catch` clauses are
+/// bound in the function they're in.
+pub fn resolve<'ast>(module: &'ast Module<'ast>) -> Resolution {
+    let mut resolver = Resolver {
+        scopes: Vec::new(),
+        vars: Vec::new(),
+        identifiers: Vec::new(),
+    };
+
+    resolver.collect(analyze(module), None);
+    resolver.finish()
+}
+
 pub type ReferenceData = ();
 
 #[derive(Clone, Copy)]
@@ -41,6 +104,22 @@ pub struct Scope<'ast> {
 
     /// All children of the this scope
     pub children: GrowableList<'ast, &'ast Scope<'ast>>,
+
+    /// The variables bound in this scope, which for a function includes those declared in blocks
+    /// in it, and for a block is the parameter of a `catch` clause
+    pub variables: GrowableList<'ast, Identifier<'ast>>,
+
+    /// Every identifier declaring or using a variable written directly in this scope
+    pub identifiers: GrowableList<'ast, Identifier<'ast>>,
+
+    /// Where the body of the function starts, for the scope of a function or arrow function
+    pub body_start: Option<u32>,
+
+    /// The name of a function expression, which is in scope inside it
+    pub name: Option<&'ast str>,
+
+    /// Whether variables in this scope or one inside it may be looked up by name at runtime
+    pub dynamic: CopyCell<bool>,
 }
 
 impl<'ast> Scope<'ast> {
@@ -54,6 +133,34 @@ impl<'ast> Scope<'ast> {
             declared_refs: BloomMap::new(),
             parent,
             children: GrowableList::new(),
+            variables: GrowableList::new(),
+            identifiers: GrowableList::new(),
+            body_start: None,
+            name: None,
+            dynamic: CopyCell::new(false),
+        }
+    }
+
+    /// The innermost function scope this scope is in, which may be itself
+    #[inline]
+    pub fn function(&'ast self) -> &'ast Scope<'ast> {
+        let mut scope = self;
+
+        while scope.kind != ScopeKind::Function {
+            scope = scope.parent.expect("Block scopes are always inside a function");
+        }
+
+        scope
+    }
+
+    /// Mark this scope, and all scopes around it, as having variables looked up by name
+    #[inline]
+    pub fn deoptimize(&'ast self) {
+        let mut scope = Some(self);
+
+        while let Some(current) = scope {
+            current.dynamic.set(true);
+            scope = current.parent;
         }
     }
 
@@ -77,6 +184,9 @@ impl<'ast> Debug for Scope<'ast> {
             .field("used_this", &self.used_this)
             .field("used_refs", &self.used_refs)
             .field("declared_refs", &self.declared_refs)
+            .field("body_start", &self.body_start)
+            .field("name", &self.name)
+            .field("dynamic", &self.dynamic)
             .field("children", &self.children)
             .finish()
     }
@@ -93,18 +203,25 @@ impl<'ast> PartialEq for &'ast Scope<'ast> {
 struct ScopeAnalyzer<'ast> {
     arena: &'ast Arena,
     pub current: CopyCell<&'ast Scope<'ast>>,
+    /// Where the body of the function about to be entered starts, and its name if it's an expression
+    function: Option<(u32, Option<&'ast str>)>,
+    /// The addresses of the parameters of the `catch` clauses seen so far
+    catch_params: Vec<usize>,
 }
 
 impl<'ast> ScopeAnalyzer<'ast> {
     #[inline]
     fn new(arena: &'ast Arena) -> Self {
-        let current = CopyCell::new(
-            arena.alloc(Scope::new(ScopeKind::Function, None))
-        );
+        let root = arena.alloc(Scope::new(ScopeKind::Function, None));
+
+        // Top level variables are properties of the global object
+        root.dynamic.set(true);
 
         ScopeAnalyzer {
             arena,
-            current,
+            current: CopyCell::new(root),
+            function: None,
+            catch_params: Vec::new(),
         }
     }
 }
@@ -112,9 +229,26 @@ impl<'ast> ScopeAnalyzer<'ast> {
 impl<'ast> Visitor<'ast> for ScopeAnalyzer<'ast> {
     #[inline]
     fn on_enter_scope(&mut self, kind: ScopeKind) {
-        self.current.set(
-            self.arena.alloc(Scope::new(kind, Some(self.current.get())))
-        );
+        let mut scope = Scope::new(kind, Some(self.current.get()));
+
+        if kind == ScopeKind::Function {
+            match self.function.take() {
+                Some((body_start, name)) => {
+                    scope.body_start = Some(body_start);
+                    scope.name = name;
+                },
+                // Methods and class members are left to be looked up by name
+                None => scope.dynamic.set(true),
+            }
+        }
+
+        let scope = self.arena.alloc(scope);
+
+        if scope.dynamic.get() {
+            scope.deoptimize();
+        }
+
+        self.current.set(scope);
     }
 
     #[inline]
@@ -127,18 +261,215 @@ impl<'ast> Visitor<'ast> for ScopeAnalyzer<'ast> {
 
     #[inline]
     fn on_reference_use(&mut self, ident: &Identifier<'ast>) {
-        self.current.get().used_refs.insert(self.arena, *ident, ());
+        let current = self.current.get();
+
+        current.used_refs.insert(self.arena, *ident, ());
+        current.identifiers.push(self.arena, *ident);
+
+        // A direct `eval` can reach any variable by name, and so can `arguments`
+        if *ident == "eval" || *ident == "arguments" {
+            current.deoptimize();
+        }
     }
 
     #[inline]
     fn on_reference_declaration(&mut self, ident: &Identifier<'ast>) {
-        self.current.get().declared_refs.insert(self.arena, *ident, ());
+        let current = self.current.get();
+
+        current.declared_refs.insert(self.arena, *ident, ());
+        current.identifiers.push(self.arena, *ident);
+
+        if self.catch_params.contains(&(ident.as_ptr() as usize)) {
+            current.variables.push(self.arena, *ident);
+        } else {
+            current.function().variables.push(self.arena, *ident);
+        }
     }
 
     #[inline]
     fn on_this_expression(&mut self, _: &ExpressionNode<'ast>) {
         self.current.get().used_this.set(true);
     }
+
+    #[inline]
+    fn on_arrow_expression(&mut self, item: &ArrowExpression<'ast>, _: &'ast ExpressionNode<'ast>) {
+        let body_start = match item.body {
+            ArrowBody::Expression(ref expression) => expression.start,
+            ArrowBody::Block(ref block)           => block.start,
+        };
+
+        self.function = Some((body_start, None));
+    }
+
+    #[inline]
+    fn on_function_expression(&mut self, item: &FunctionExpression<'ast>, _: &'ast ExpressionNode<'ast>) {
+        self.function = Some((item.body.start, item.name.0.map(|name| name.item)));
+    }
+
+    #[inline]
+    fn on_function_statement(&mut self, item: &FunctionStatement<'ast>, _: &'ast StatementNode<'ast>) {
+        self.function = Some((item.body.start, None));
+    }
+
+    #[inline]
+    fn on_try_statement(&mut self, item: &TryStatement, _: &'ast StatementNode<'ast>) {
+        if let Some(handler) = item.handler {
+            match handler.param.item {
+                Pattern::Identifier(name) => self.catch_params.push(name.as_ptr() as usize),
+                // Destructured parameters are left to be looked up by name
+                _ => self.current.get().deoptimize(),
+            }
+        }
+    }
+}
+
+/// A scope, flattened out of the tree of `Scope`s
+struct ResolverScope<'ast> {
+    parent: Option<usize>,
+    /// The innermost function scope the scope is in, which may be itself
+    function: usize,
+    scope: &'ast Scope<'ast>,
+    vars: HashMap<&'ast str, usize>,
+}
+
+/// A variable bound in a scope
+struct ResolverVar<'ast> {
+    name: &'ast str,
+    /// The function scope the variable's slot is in
+    function: usize,
+    captured: bool,
+}
+
+struct Resolver<'ast> {
+    scopes: Vec<ResolverScope<'ast>>,
+    vars: Vec<ResolverVar<'ast>>,
+    /// Every identifier, with the scope it's written in
+    identifiers: Vec<(Identifier<'ast>, usize)>,
+}
+
+impl<'ast> Resolver<'ast> {
+    /// Flatten out a scope and the scopes inside it
+    fn collect(&mut self, scope: &'ast Scope<'ast>, parent: Option<usize>) {
+        let index = self.scopes.len();
+        let function = match (scope.kind, parent) {
+            (ScopeKind::Block, Some(parent)) => self.scopes[parent].function,
+            _ => index,
+        };
+        let mut vars = HashMap::new();
+
+        for &name in scope.variables.as_list().iter() {
+            if !vars.contains_key(name) {
+                vars.insert(name, self.vars.len());
+                self.vars.push(ResolverVar { name, function, captured: false });
+            }
+        }
+
+        self.scopes.push(ResolverScope { parent, function, scope, vars });
+
+        for &ident in scope.identifiers.as_list().iter() {
+            self.identifiers.push((ident, index));
+        }
+
+        for &child in scope.children.as_list().iter() {
+            self.collect(child, Some(index));
+        }
+    }
+
+    /// The variable a name written in a scope refers to, if any
+    fn lookup(&self, name: &str, mut index: usize) -> Option<usize> {
+        loop {
+            let scope = &self.scopes[index];
+
+            if let Some(&var) = scope.vars.get(name) {
+                return Some(var);
+            }
+
+            if scope.scope.name == Some(name) {
+                return None;
+            }
+
+            index = scope.parent?;
+        }
+    }
+
+    fn finish(mut self) -> Resolution {
+        let mut resolved = Vec::new();
+
+        for &(ident, index) in &self.identifiers {
+            let var = match self.lookup(ident, index) {
+                Some(var) => var,
+                None => continue,
+            };
+            let function = self.vars[var].function;
+
+            if self.scopes[function].scope.dynamic.get() {
+                continue;
+            }
+
+            let home = self.scopes[index].function;
+
+            if home != function {
+                self.vars[var].captured = true;
+            }
+
+            resolved.push((ident, var, home));
+        }
+
+        let mut layouts: HashMap<usize, Layout> = HashMap::new();
+        let mut slots = Vec::with_capacity(self.vars.len());
+
+        for var in &self.vars {
+            if self.scopes[var.function].scope.dynamic.get() {
+                slots.push(0);
+                continue;
+            }
+
+            let layout = layouts.entry(var.function).or_insert_with(Layout::default);
+            let list = if var.captured { &mut layout.captured } else { &mut layout.locals };
+
+            slots.push(list.len() as u32);
+            list.push(var.name.to_string());
+        }
+
+        for (index, scope) in self.scopes.iter().enumerate() {
+            if scope.function == index && !scope.scope.dynamic.get() {
+                layouts.entry(index).or_insert_with(Layout::default);
+            }
+        }
+
+        let mut bindings = HashMap::new();
+
+        for (ident, var, home) in resolved {
+            let binding = if self.vars[var].captured {
+                // Only calls that captured some variables have slots of their own to pass through
+                let mut depth = 0;
+                let mut function = home;
+
+                while function != self.vars[var].function {
+                    if !layouts[&function].captured.is_empty() {
+                        depth += 1;
+                    }
+
+                    let parent = self.scopes[function].parent.expect("A variable is bound in a scope around its uses");
+                    function = self.scopes[parent].function;
+                }
+
+                Binding::Captured { depth, index: slots[var] }
+            } else {
+                Binding::Local(slots[var])
+            };
+
+            bindings.insert(ident.as_ptr() as usize, binding);
+        }
+
+        let functions = layouts.into_iter()
+            .filter_map(|(index, layout)| {
+                self.scopes[index].scope.body_start.map(|start| (start, layout))
+            })
+            .collect();
+
+        Resolution { bindings, functions }
+    }
 }
 
 #[cfg(test)]
@@ -171,4 +502,74 @@ mod test {
         assert_eq!(moon.declared_refs.is_empty(), true);
         assert_eq!(moon.children.as_list().is_empty(), true);
     }
+
+    /// The identifiers a module declares or uses, in the order they're visited
+    struct Identifiers<'ast>(Vec<Identifier<'ast>>);
+
+    impl<'ast> Visitor<'ast> for Identifiers<'ast> {
+        fn on_reference_use(&mut self, ident: &Identifier<'ast>) {
+            self.0.push(*ident);
+        }
+
+        fn on_reference_declaration(&mut self, ident: &Identifier<'ast>) {
+            self.0.push(*ident);
+        }
+    }
+
+    /// The binding of the `nth` identifier called `name`, as identifiers are keyed by their text
+    /// in the module's copy of the source
+    fn binding<'ast>(module: &'ast Module<'ast>, resolution: &Resolution, name: &str, nth: usize) -> Option<Binding> {
+        let mut identifiers = Identifiers(Vec::new());
+
+        module.visit_with(&mut identifiers);
+
+        let ident = identifiers.0.into_iter().filter(|&ident| ident == name).nth(nth).unwrap();
+
+        resolution.binding(ident)
+    }
+
+    #[test]
+    fn resolution() {
+        let src = "var wow; function outer(doge, moon) { function inner() { return doge + wow; } try {} catch (moon) { moon; } return moon; }";
+        let module = parse(src).unwrap();
+        let resolution = resolve(&module);
+        let binding = |name: &str, nth: usize| binding(&module, &resolution, name, nth);
+
+        assert_eq!(binding("wow", 0), None);
+        assert_eq!(binding("wow", 1), None);
+        assert_eq!(binding("doge", 0), Some(Binding::Captured { depth: 0, index: 0 }));
+        assert_eq!(binding("doge", 1), Some(Binding::Captured { depth: 0, index: 0 }));
+        assert_eq!(binding("moon", 0), Some(Binding::Local(0)));
+        assert_eq!(binding("moon", 1), Some(Binding::Local(2)));
+        assert_eq!(binding("moon", 2), Some(Binding::Local(2)));
+        assert_eq!(binding("moon", 3), Some(Binding::Local(0)));
+
+        let outer = resolution.layout(src.find('{').unwrap() as u32).unwrap();
+
+        assert_eq!(outer.locals, &["moon", "inner", "moon"]);
+        assert_eq!(outer.captured, &["doge"]);
+        assert_eq!(resolution.functions.len(), 2);
+    }
+
+    #[test]
+    fn resolution_counts_calls_with_captured_variables() {
+        let src = "function to(doge) { return function the(moon) { return () => doge + moon; }; }";
+        let module = parse(src).unwrap();
+        let resolution = resolve(&module);
+        let binding = |name: &str, nth: usize| binding(&module, &resolution, name, nth);
+
+        assert_eq!(binding("doge", 1), Some(Binding::Captured { depth: 1, index: 0 }));
+        assert_eq!(binding("moon", 1), Some(Binding::Captured { depth: 0, index: 0 }));
+        assert_eq!(resolution.functions.len(), 3);
+    }
+
+    #[test]
+    fn resolution_leaves_eval_and_arguments_to_lookups() {
+        let src = "function to(doge) { return () => eval(doge); } function the(moon) { return arguments; }";
+        let module = parse(src).unwrap();
+        let resolution = resolve(&module);
+
+        assert_eq!(resolution.bindings.is_empty(), true);
+        assert_eq!(resolution.functions.is_empty(), true);
+    }
 }
@@ -504,6 +504,34 @@ mod test {
         );
     }
 
+    #[test]
+    fn arrow_functions_are_scopes() {
+        let module = parse("const doge = (to, the) => moon;").unwrap();
+        let mut visitor = ScopeTest::new();
+
+        module.visit_with(&mut visitor);
+
+        assert_eq!(visitor.scopes, &[Function]);
+        assert_eq!(visitor.depth, 0);
+        assert_eq!(visitor.max_depth, 1);
+        assert_eq!(visitor.used_vars, &[("moon", 1)]);
+        assert_eq!(visitor.declared_vars, &[("doge", 0), ("to", 1), ("the", 1)]);
+    }
+
+    #[test]
+    fn catch_clauses_are_scopes() {
+        let module = parse("try { doge; } catch (to) { the; }").unwrap();
+        let mut visitor = ScopeTest::new();
+
+        module.visit_with(&mut visitor);
+
+        assert_eq!(visitor.scopes, &[Block, Block]);
+        assert_eq!(visitor.depth, 0);
+        assert_eq!(visitor.max_depth, 1);
+        assert_eq!(visitor.used_vars, &[("doge", 1), ("the", 1)]);
+        assert_eq!(visitor.declared_vars, &[("to", 1)]);
+    }
+
     struct ParentsTest<'ast> {
         count: u32,
         parents: Vec<ParentNode<'ast>>,
@@ -3,6 +3,9 @@ use ast::{BlockNode, Statement, PatternList, PropertyKey};
 
 pub trait Name<'ast>: Copy {
     fn empty() -> Self;
+
+    /// The identifier of the name, if there is one
+    fn identifier(&self) -> Option<IdentifierNode<'ast>>;
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -20,6 +23,10 @@ impl<'ast> Name<'ast> for EmptyName {
     fn empty() -> Self {
         EmptyName
     }
+
+    fn identifier(&self) -> Option<IdentifierNode<'ast>> {
+        None
+    }
 }
 
 impl<'ast> Name<'ast> for MandatoryName<'ast> {
@@ -30,12 +37,20 @@ impl<'ast> Name<'ast> for MandatoryName<'ast> {
             item: ""
         }))
     }
+
+    fn identifier(&self) -> Option<IdentifierNode<'ast>> {
+        Some(self.0)
+    }
 }
 
 impl<'ast> Name<'ast> for OptionalName<'ast> {
     fn empty() -> Self {
         OptionalName(None)
     }
+
+    fn identifier(&self) -> Option<IdentifierNode<'ast>> {
+        self.0
+    }
 }
 
 #[cfg(test)]
@@ -6,6 +6,7 @@ pub enum Literal<'ast> {
     False,
     Number(&'ast str),
     Binary(&'ast str),
+    BigInt(&'ast str),
     String(&'ast str),
     RegEx(&'ast str),
 }
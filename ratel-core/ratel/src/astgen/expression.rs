@@ -1270,14 +1270,14 @@ mod test {
                             "start": 7,
                             "end": 8
                         },
-                        "start": 1,
+                        "start": 0,
                         "end": 8
                     },
-                    "start": 1,
+                    "start": 0,
                     "end": 8
                 }
               ],
-              "start": 1,
+              "start": 0,
               "end": 8,
         });
         expect_parse!("(b) => {}", {
@@ -1301,14 +1301,14 @@ mod test {
                             "start": 7,
                             "end": 9
                         },
-                        "start": 1,
+                        "start": 0,
                         "end": 9
                     },
-                    "start": 1,
+                    "start": 0,
                     "end": 9
                 }
               ],
-              "start": 1,
+              "start": 0,
               "end": 9,
         });
         expect_parse!("() => {}", {
@@ -42,7 +42,7 @@ impl<'ast, T: SerializeInLoc> Serialize for Node<'ast, T> {
     where
         S: Serializer
     {
-        Loc::<T>::serialize(&*self, serializer)
+        Loc::<T>::serialize(self, serializer)
     }
 }
 
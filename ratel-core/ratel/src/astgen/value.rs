@@ -10,7 +10,7 @@ pub struct RegExLiteral<'ast> {
 }
 
 #[inline]
-pub fn parse_regex(value: &str) -> RegExLiteral {
+pub fn parse_regex(value: &str) -> RegExLiteral<'_> {
     let mut end = value.len() - 1;
     for index in (0..value.len()).rev() {
         if "/" == &value[index..=index] {
@@ -68,7 +68,7 @@ impl<'ast> Serialize for TemplateQuasis<'ast> {
     }
 }
 
-impl<'ast> SerializeInLoc for &'ast str {
+impl SerializeInLoc for &str {
     fn serialize<S>(&self, serializer: S) -> Result<S::SerializeStruct, S::Error>
     where
         S: Serializer,
@@ -231,6 +231,12 @@ impl<'ast> SerializeInLoc for Literal<'ast> {
                     )?;
                     state.serialize_field("raw", &number)
                 }
+                BigInt(number) => {
+                    let digits = unsafe { number.get_unchecked(0..number.len() - 1) };
+                    state.serialize_field("value", &())?;
+                    state.serialize_field("raw", &number)?;
+                    state.serialize_field("bigint", &digits)
+                }
                 String(value) => {
                     let parsed_value = unsafe { value.get_unchecked(1..value.len() - 1) };
                     state.serialize_field("value", &parsed_value)?;
@@ -514,6 +520,27 @@ mod test {
             "end": 6,
         });
 
+        expect_parse!("0x1Fn", {
+            "type": "Program",
+            "body": [
+                {
+                    "type": "ExpressionStatement",
+                    "expression": {
+                        "type": "Literal",
+                        "value": null,
+                        "raw": "0x1Fn",
+                        "bigint": "0x1F",
+                        "start": 0,
+                        "end": 5
+                    },
+                    "start": 0,
+                    "end": 5,
+                }
+            ],
+            "start": 0,
+            "end": 5,
+        });
+
         expect_parse!("0o1111", {
             "type": "Program",
             "body": [
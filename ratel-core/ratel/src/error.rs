@@ -8,12 +8,18 @@ pub struct Error {
     pub raw: Box<str>,
     pub start: usize,
     pub end: usize,
+    /// What's wrong, for errors about code that reads fine but isn't allowed,
+    /// like the early errors of strict mode code
+    pub message: Option<&'static str>,
 }
 
 impl Debug for Error {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Unexpected {:?}({}) at {}:{}", &self.token, &*self.raw, self.start, self.end)
+        match self.message {
+            Some(message) => write!(f, "{} ({}) at {}:{}", message, &*self.raw, self.start, self.end),
+            None => write!(f, "Unexpected {:?}({}) at {}:{}", &self.token, &*self.raw, self.start, self.end),
+        }
     }
 }
 
@@ -41,7 +47,7 @@ impl Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             ParseError::UnexpectedEndOfProgram => {
-                try!(write!(f, "Unexpected end of program"))
+                write!(f, "Unexpected end of program")?
             },
 
             ParseError::UnexpectedToken {
@@ -58,7 +64,7 @@ impl Display for ParseError {
                 let colno = line.chars().count();
                 let token_len = source[start..end].chars().count();
 
-                try!(writeln!(f, "Unexpected token at {}:{}\n", lineno + 1, colno + 1));
+                writeln!(f, "Unexpected token at {}:{}\n", lineno + 1, colno + 1)?;
 
                 let iter = source
                             .lines()
@@ -70,25 +76,25 @@ impl Display for ParseError {
 
                 for (index, line) in iter {
                     if index == lineno {
-                        try!(writeln!(f, "> {0:1$} | {2}", index+1, width, line));
+                        writeln!(f, "> {0:1$} | {2}", index+1, width, line)?;
 
                         for _ in 0..width {
-                            try!(write!(f, " "));
+                            write!(f, " ")?;
                         }
 
-                        try!(write!(f, "   | "));
+                        write!(f, "   | ")?;
 
                         for _ in 0..colno {
-                            try!(write!(f, " "));
+                            write!(f, " ")?;
                         }
 
                         for _ in 0..token_len {
-                            try!(write!(f, "^"));
+                            write!(f, "^")?;
                         }
 
-                        try!(write!(f, "\n"));
+                        writeln!(f)?;
                     } else {
-                        try!(writeln!(f, "{0:1$} | {2}", index+1, width+2, line));
+                        writeln!(f, "{0:1$} | {2}", index+1, width+2, line)?;
                     }
                 }
 
@@ -501,7 +501,7 @@ const ZER: ByteHandler = Some(|lex| {
 
     loop {
         match lex.read_byte() {
-            b'0'...b'9' => {
+            b'0'..=b'9' => {
                 lex.bump();
             },
             b'.' => {
@@ -518,14 +518,14 @@ const ZER: ByteHandler = Some(|lex| {
         }
     }
 
-    lex.token = LiteralNumber;
+    lex.read_integer_end(LiteralNumber);
 });
 
 // 1 to 9
 const DIG: ByteHandler = Some(|lex| {
     unwind_loop!({
         match lex.next_byte() {
-            b'0'...b'9' => {},
+            b'0'..=b'9' => {},
             b'.' => {
                 lex.bump();
 
@@ -537,7 +537,7 @@ const DIG: ByteHandler = Some(|lex| {
                 return lex.read_scientific();
             },
             _ => {
-                return lex.token = LiteralNumber;
+                return lex.read_integer_end(LiteralNumber);
             },
         }
     });
@@ -546,7 +546,7 @@ const DIG: ByteHandler = Some(|lex| {
 // .
 const PRD: ByteHandler = Some(|lex| {
     match lex.next_byte() {
-        b'0'...b'9' => {
+        b'0'..=b'9' => {
             lex.bump();
 
             lex.read_float()
@@ -617,6 +617,9 @@ pub struct Lexer<'arena> {
     accessor_start: usize,
 
     pub quasi: &'arena str,
+
+    /// Whether the source being read is strict mode code, where more words are reserved
+    strict: bool,
 }
 
 
@@ -645,6 +648,7 @@ impl<'arena> Lexer<'arena> {
             token_start: 0,
             accessor_start: 0,
             quasi: "",
+            strict: false,
         };
 
         lexer.consume();
@@ -655,6 +659,43 @@ impl<'arena> Lexer<'arena> {
     /// Advances the lexer, produces a new `Token` and stores it on `self.token`.
     #[inline]
     pub fn consume(&mut self) {
+        self.read_token();
+
+        if !self.strict && self.token.is_strict_reserved() {
+            self.token = Identifier;
+        }
+    }
+
+    /// Switch between reading strict mode and sloppy mode code, re-reading
+    /// the current token if it's a word only reserved in strict mode code.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+
+        if !strict && self.token.is_strict_reserved() {
+            self.token = Identifier;
+        } else if strict && self.token == Identifier {
+            self.token = match self.token_as_str() {
+                "implements" => ReservedImplements,
+                "package"    => ReservedPackage,
+                "protected"  => ReservedProtected,
+                "interface"  => ReservedInterface,
+                "private"    => ReservedPrivate,
+                "public"     => ReservedPublic,
+                "static"     => Static,
+                "yield"      => Yield,
+                _            => Identifier,
+            };
+        }
+    }
+
+    /// Whether the source being read is strict mode code
+    #[inline]
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    #[inline]
+    fn read_token(&mut self) {
         self.asi = Asi::NoSemicolon;
 
         let mut ch;
@@ -799,7 +840,8 @@ impl<'arena> Lexer<'arena> {
             token,
             start,
             end,
-            raw: self.slice_source(start, end).to_owned().into_boxed_str()
+            raw: self.slice_source(start, end).to_owned().into_boxed_str(),
+            message: None,
         }
     }
 
@@ -841,7 +883,19 @@ impl<'arena> Lexer<'arena> {
             }
         }
 
-        self.token = LiteralBinary;
+        self.read_integer_end(LiteralBinary);
+    }
+
+    /// Finish an integer literal, which is a `BigInt` literal instead when
+    /// followed by the `n` suffix.
+    #[inline]
+    fn read_integer_end(&mut self, token: Token) {
+        if self.read_byte() == b'n' {
+            self.bump();
+            self.token = LiteralBigInt;
+        } else {
+            self.token = token;
+        }
     }
 
     /// This is a specialized method that expects the next token to be an identifier,
@@ -910,7 +964,7 @@ impl<'arena> Lexer<'arena> {
     }
 
     #[inline]
-    fn slice_source(&self, start: usize, end: usize) -> &'arena str {
+    pub fn slice_source(&self, start: usize, end: usize) -> &'arena str {
         use std::str::from_utf8_unchecked;
         use std::slice::from_raw_parts;
 
@@ -924,34 +978,34 @@ impl<'arena> Lexer<'arena> {
     #[inline]
     fn read_octal(&mut self) {
         while match self.read_byte() {
-            b'0'...b'7' => true,
+            b'0'..=b'7' => true,
             _ => false,
         } {
             self.bump();
         }
 
-        self.token = LiteralNumber;
+        self.read_integer_end(LiteralNumber);
     }
 
     #[inline]
     fn read_hexadec(&mut self) {
         while match self.read_byte() {
-            b'0'...b'9' |
-            b'a'...b'f' |
-            b'A'...b'F' => true,
+            b'0'..=b'9' |
+            b'a'..=b'f' |
+            b'A'..=b'F' => true,
             _ => false,
         } {
             self.bump();
         }
 
-        self.token = LiteralNumber;
+        self.read_integer_end(LiteralNumber);
     }
 
     #[inline]
     fn read_float(&mut self) {
         loop {
             match self.read_byte() {
-                b'0'...b'9'  => self.bump(),
+                b'0'..=b'9'  => self.bump(),
                 b'e' | b'E'  => {
                     self.bump();
                     return self.read_scientific();
@@ -971,7 +1025,7 @@ impl<'arena> Lexer<'arena> {
         }
 
         while match self.read_byte() {
-            b'0'...b'9' => true,
+            b'0'..=b'9' => true,
             _ => false,
         } {
             self.bump();
@@ -1039,8 +1093,17 @@ mod test {
     use super::*;
 
     fn assert_lex<T>(source: &str, tokens: T) where T: AsRef<[(Token, &'static str)]> {
+        assert_lex_in_mode(source, tokens, false)
+    }
+
+    fn assert_strict_lex<T>(source: &str, tokens: T) where T: AsRef<[(Token, &'static str)]> {
+        assert_lex_in_mode(source, tokens, true)
+    }
+
+    fn assert_lex_in_mode<T>(source: &str, tokens: T, strict: bool) where T: AsRef<[(Token, &'static str)]> {
         let arena = Arena::new();
         let mut lex = Lexer::new(&arena, source);
+        lex.set_strict(strict);
 
         for &(ref token, slice) in tokens.as_ref() {
             assert_eq!(lex.token, *token);
@@ -1130,6 +1193,22 @@ mod test {
         );
     }
 
+    #[test]
+    fn bigint_literals() {
+        assert_lex(
+            "0n 42n 0x1Fn 0o17n 0b101n 4.2 0b1",
+            [
+                (LiteralBigInt, "0n"),
+                (LiteralBigInt, "42n"),
+                (LiteralBigInt, "0x1Fn"),
+                (LiteralBigInt, "0o17n"),
+                (LiteralBigInt, "0b101n"),
+                (LiteralNumber, "4.2"),
+                (LiteralBinary, "0b1"),
+            ]
+        );
+    }
+
     #[test]
     fn function_statement() {
         assert_lex(
@@ -1160,7 +1239,7 @@ mod test {
 
     #[test]
     fn keywords() {
-        assert_lex(
+        assert_strict_lex(
             "
                 break case class const debugger default delete do else
                 export extends false finally for function if implements
@@ -1215,6 +1294,38 @@ mod test {
         );
     }
 
+    #[test]
+    fn strict_mode_reserved_words_in_sloppy_mode() {
+        assert_lex(
+            "implements interface package private protected public static yield let",
+            [
+                (Identifier, "implements"),
+                (Identifier, "interface"),
+                (Identifier, "package"),
+                (Identifier, "private"),
+                (Identifier, "protected"),
+                (Identifier, "public"),
+                (Identifier, "static"),
+                (Identifier, "yield"),
+                (DeclarationLet, "let"),
+            ]
+        );
+    }
+
+    #[test]
+    fn switching_to_strict_mode() {
+        let arena = Arena::new();
+        let mut lex = Lexer::new(&arena, "public public");
+
+        assert_eq!(lex.token, Identifier);
+        lex.set_strict(true);
+        assert_eq!(lex.token, ReservedPublic);
+        lex.consume();
+        assert_eq!(lex.token, ReservedPublic);
+        lex.set_strict(false);
+        assert_eq!(lex.token, Identifier);
+    }
+
     #[test]
     fn operators() {
         assert_lex(
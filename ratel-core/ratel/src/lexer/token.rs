@@ -10,6 +10,7 @@
 // SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 // IMPRT TRY   STATI TRUE  FALSE NULL  UNDEF STR   NUM   BIN   REGEX ENUM
 // IMPL  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E
+// BIGIN
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Token {
@@ -121,6 +122,7 @@ pub enum Token {
     TemplateClosed,
     UnexpectedToken,
     UnexpectedEndOfProgram,
+    LiteralBigInt,
 }
 
 impl Token {
@@ -156,7 +158,18 @@ impl Token {
             Import             |
             Try                |
             Static             |
+            DeclarationVar     |
+            DeclarationLet     |
+            DeclarationConst   |
+            ReservedEnum       |
+            ReservedImplements |
+            ReservedPackage    |
+            ReservedProtected  |
+            ReservedInterface  |
+            ReservedPrivate    |
+            ReservedPublic     |
             OperatorNew        |
+            OperatorIn         |
             OperatorTypeof     |
             OperatorVoid       |
             OperatorDelete     |
@@ -169,4 +182,23 @@ impl Token {
             _                  => false,
         }
     }
+
+    /// Words that are only reserved in strict mode code, and are identifiers otherwise
+    #[inline]
+    pub fn is_strict_reserved(&self) -> bool {
+        use self::Token::*;
+
+        match self {
+            ReservedImplements |
+            ReservedPackage    |
+            ReservedProtected  |
+            ReservedInterface  |
+            ReservedPrivate    |
+            ReservedPublic     |
+            Static             |
+            Yield              => true,
+
+            _                  => false,
+        }
+    }
 }
@@ -1,5 +1,5 @@
 #![recursion_limit="128"]
-#![cfg_attr(feature = "cargo-clippy", allow(trivially_copy_pass_by_ref))]
+#![allow(clippy::trivially_copy_pass_by_ref)]
 
 extern crate serde;
 extern crate toolshed;
@@ -23,5 +23,5 @@ mod module;
 mod parser;
 mod astgen;
 
-pub use parser::parse;
+pub use parser::{parse, parse_module, parse_strict};
 pub use module::Module;
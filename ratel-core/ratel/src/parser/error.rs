@@ -1,12 +1,6 @@
-use error::Error;
 
 use ast::{Node, Loc, NodeList, Pattern};
 use ast::{Name, ClassMember, Property, PropertyKey, MandatoryName, Block};
-use parser::Parser;
-
-pub trait Handle<'ast> {
-    fn handle_error(parser: &mut Parser<'ast>, err: Error) -> Self;
-}
 
 pub trait ToError {
     fn to_error() -> Self;
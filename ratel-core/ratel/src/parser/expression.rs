@@ -1,5 +1,5 @@
 use toolshed::list::ListBuilder;
-use parser::{Parser, Parse, BindingPower, ANY, B0, B15};
+use parser::{Parser, Parse, BindingPower, ANY, B0, B15, B16};
 use lexer::Token::*;
 use ast::{Node, NodeList, Expression, ExpressionNode, IdentifierNode, ExpressionList};
 use ast::{Property, PropertyKey, OperatorKind, Literal, Function, Class, StatementNode};
@@ -8,7 +8,7 @@ use ast::expression::*;
 
 type ExpressionHandler = for<'ast> fn(&mut Parser<'ast>) -> ExpressionNode<'ast>;
 
-pub type Context = &'static [ExpressionHandler; 108];
+pub type Context = &'static [ExpressionHandler; 109];
 
 static DEF_CONTEXT: Context = &[
     ____, ____, ____, ____, PRN,  ____, ARR,  ____, OBJ,  ____, ____, NEW,
@@ -37,6 +37,9 @@ static DEF_CONTEXT: Context = &[
 
     ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
 //  IMPL  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E
+
+    BIGN,
+//  BIGIN
 ];
 
 // Adds handlers for VoidExpression and SpreadExpression
@@ -50,6 +53,7 @@ pub static ARRAY_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
     ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
     ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
+    BIGN,
 ];
 
 // Adds handler for SpreadExpression
@@ -63,8 +67,37 @@ pub static CALL_CONTEXT: Context = &[
     ____, ____, ____, ____, ____, ____, ____, FUNC, THIS, ____, ____, ____,
     ____, ____, ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
     ____, ____, ____, ____, ____, ____, IDEN, ____, TPLE, TPLS, ____, ____,
+    BIGN,
 ];
 
+/// Whether a number is written with a leading zero, like the legacy octal `010`, or `09`
+fn is_legacy_octal_like(number: &str) -> bool {
+    let bytes = number.as_bytes();
+
+    bytes.len() > 1 && bytes[0] == b'0' && bytes[1].is_ascii_digit()
+}
+
+/// Whether a string literal has a legacy octal escape, like `\01`, or a `\8` or `\9` escape
+fn has_octal_escape(raw: &str) -> bool {
+    let mut bytes = raw.bytes().peekable();
+
+    while let Some(byte) = bytes.next() {
+        if byte != b'\\' {
+            continue;
+        }
+
+        match bytes.next() {
+            Some(b'0') => if let Some(b'0'..=b'9') = bytes.peek() {
+                return true;
+            },
+            Some(b'1'..=b'9') => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
+
 macro_rules! create_handlers {
     ($( const $name:ident = |$par:ident| $code:expr; )* $( pub const $pname:ident = |$ppar:ident| $pcode:expr; )*) => {
         $(
@@ -138,6 +171,19 @@ create_handlers! {
         par.lexer.consume();
         let expression = par.prefix_expression(op);
         let end = par.lexer.end();
+
+        match op {
+            OperatorKind::Increment | OperatorKind::Decrement => {
+                par.check_assignment_target(expression.operand);
+            },
+            OperatorKind::Delete if par.lexer.strict() => {
+                if let Expression::Identifier(_) = expression.operand.item {
+                    par.early_error(start, end, "Delete of an unqualified identifier in strict mode.");
+                }
+            },
+            _ => {}
+        }
+
         par.alloc_at_loc(start, end, expression)
     };
 
@@ -153,7 +199,10 @@ create_handlers! {
             par.lexer.consume();
             par.alloc_at_loc(start, end, expression)
         } else {
-            let expression = par.prefix_expression(OperatorKind::New);
+            let expression = PrefixExpression {
+                operator: OperatorKind::New,
+                operand: par.new_operand(),
+            };
             let end = par.lexer.end();
             par.alloc_at_loc(start, end, expression)
         }
@@ -199,6 +248,10 @@ create_handlers! {
         let value = par.lexer.token_as_str();
         let expr = par.alloc_in_loc(Literal::String(value));
 
+        if par.lexer.strict() && has_octal_escape(value) {
+            par.early_error(expr.start, expr.end, "Octal escape sequences are not allowed in strict mode.");
+        }
+
         par.lexer.consume();
         expr
     };
@@ -207,6 +260,10 @@ create_handlers! {
         let value = par.lexer.token_as_str();
         let expr = par.alloc_in_loc(Literal::Number(value));
 
+        if par.lexer.strict() && is_legacy_octal_like(value) {
+            par.early_error(expr.start, expr.end, "Octal literals are not allowed in strict mode.");
+        }
+
         par.lexer.consume();
         expr
     };
@@ -219,6 +276,14 @@ create_handlers! {
         expr
     };
 
+    pub const BIGN = |par| {
+        let value = par.lexer.token_as_str();
+        let expr = par.alloc_in_loc(Literal::BigInt(value));
+
+        par.lexer.consume();
+        expr
+    };
+
     pub const TPLS = |par| {
         let quasi = par.lexer.quasi;
         let quasi = par.alloc_in_loc(quasi);
@@ -266,18 +331,28 @@ impl<'ast> Parser<'ast> {
     }
 
     #[inline]
-    pub fn arrow_function_expression(&mut self, params: ExpressionList<'ast>) -> ArrowExpression<'ast> {
+    pub fn arrow_function_expression(&mut self, start: u32, params: ExpressionList<'ast>) -> ExpressionNode<'ast> {
         let params = self.params_from_expressions(params);
 
-        let body = match self.lexer.token {
-            BraceOpen => ArrowBody::Block(self.unchecked_block()),
-            _         => ArrowBody::Expression(self.expression::<B0>()),
+        let (body, end) = match self.lexer.token {
+            BraceOpen => {
+                let block = self.function_body(None, params);
+                (ArrowBody::Block(block), block.end)
+            },
+            _ => {
+                let expression = self.expression::<B0>();
+                (ArrowBody::Expression(expression), expression.end)
+            },
         };
 
-        ArrowExpression {
+        // The parameters were read as expressions, so their names haven't been checked yet
+        let strict = self.lexer.strict();
+        self.check_params(params, strict, true);
+
+        self.alloc_at_loc(start, end, ArrowExpression {
             params,
             body,
-        }
+        })
     }
 
     #[inline]
@@ -320,15 +395,25 @@ impl<'ast> Parser<'ast> {
             ParenClose => {
                 self.lexer.consume();
                 expect!(self, OperatorFatArrow);
-                let expression = self.arrow_function_expression(NodeList::empty());
-                let end = self.lexer.end();
-                self.alloc_at_loc(start, end, expression)
+                self.arrow_function_expression(start, NodeList::empty())
             },
             _ => {
                 let expression = self.expression::<ANY>();
 
                 expect!(self, ParenClose);
 
+                // The parameters of an arrow function start at the parenthesis
+                if self.lexer.token == OperatorFatArrow {
+                    self.lexer.consume();
+
+                    let params = match expression.item {
+                        Expression::Sequence(SequenceExpression { body }) => body,
+                        _ => NodeList::from(self.arena, expression)
+                    };
+
+                    return self.arrow_function_expression(start, params);
+                }
+
                 expression
             }
         }
@@ -344,6 +429,25 @@ impl<'ast> Parser<'ast> {
         }
     }
 
+    /// The constructor `new` is applied to, with the arguments right after it if there are any.
+    /// Calls and accesses after those arguments apply to the new object.
+    pub fn new_operand(&mut self) -> ExpressionNode<'ast> {
+        let callee = self.expression::<B16>();
+
+        if self.lexer.token != ParenOpen {
+            return callee;
+        }
+
+        let start = self.lexer.start_then_consume();
+        let arguments = self.call_arguments();
+        let end = self.lexer.end_then_consume();
+
+        self.alloc_at_loc(start, end, CallExpression {
+            callee,
+            arguments,
+        })
+    }
+
     #[inline]
     pub fn object_expression(&mut self) -> ExpressionNode<'ast> {
         let start = self.lexer.start_then_consume();
@@ -655,10 +759,12 @@ mod test {
         let expected_a = Literal::String(r#""foobar""#);
         let expected_b = Literal::Number("100");
         let expected_c = Literal::True;
+        let expected_d = Literal::BigInt("100n");
 
         assert_expr!(r#""foobar";"#, expected_a);
         assert_expr!("100;", expected_b);
         assert_expr!("true;", expected_c);
+        assert_expr!("100n;", expected_d);
     }
 
     #[test]
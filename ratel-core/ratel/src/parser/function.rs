@@ -1,8 +1,9 @@
 use toolshed::list::ListBuilder;
-use parser::{Parser, Parse, ANY, B0};
+use parser::{Parser, Parse, ANY, B0, has_use_strict};
 use lexer::Token::*;
 use ast::{Node, NodeList, EmptyName, OptionalName, MandatoryName, Name};
 use ast::{MethodKind, Pattern, Function, Class, ClassMember, PropertyKey};
+use ast::{Block, BlockNode, IdentifierNode, PatternList, Statement};
 
 impl<'ast> Parse<'ast> for EmptyName {
     type Output = Self;
@@ -74,12 +75,14 @@ impl<'ast, N> Parse<'ast> for Function<'ast, N> where
         };
 
         let name = N::parse(par);
+        let params = par.params();
+        let body = par.function_body(name.identifier(), params);
 
         Function {
             name,
             generator,
-            params: par.params(),
-            body: par.block(),
+            params,
+            body,
         }
     }
 }
@@ -206,8 +209,16 @@ impl<'ast, N> Parse<'ast> for Class<'ast, N> where
 
     #[inline]
     fn parse(par: &mut Parser<'ast>) -> Self::Output {
+        // All parts of a class are strict mode code
+        let outer = par.lexer.strict();
+        par.lexer.set_strict(true);
+
         let name = N::parse(par);
 
+        if let Some(name) = name.identifier() {
+            par.check_binding(name.start, name.end, name.item);
+        }
+
         let super_class = match par.lexer.token {
             Extends => {
                 par.lexer.consume();
@@ -217,14 +228,26 @@ impl<'ast, N> Parse<'ast> for Class<'ast, N> where
             _ => None
         };
 
+        let body = par.block();
+
+        par.lexer.set_strict(outer);
+
         Class {
             name,
             extends: super_class,
-            body: par.block(),
+            body,
         }
     }
 }
 
+/// Whether the parameters of a function are all plain identifiers, without defaults, rest or destructuring
+fn is_simple(params: PatternList) -> bool {
+    params.iter().all(|param| match param.item {
+        Pattern::Identifier(_) => true,
+        _ => false
+    })
+}
+
 impl<'ast> Parser<'ast> {
     #[inline]
     fn pattern_void(&mut self) -> Node<'ast, Pattern<'ast>> {
@@ -234,9 +257,11 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn pattern_identifier(&mut self) -> Node<'ast, Pattern<'ast>> {
-        let ident = Pattern::Identifier(self.lexer.token_as_str());
-        let ident = self.alloc_in_loc(ident);
+        let name = self.lexer.token_as_str();
+        let (start, end) = self.lexer.loc();
+        let ident = self.alloc_at_loc(start, end, Pattern::Identifier(name));
 
+        self.check_binding(start, end, name);
         self.lexer.consume();
 
         ident
@@ -314,6 +339,7 @@ impl<'ast> Parser<'ast> {
                 let ident = self.lexer.token_as_str();
                 let ident = self.alloc_in_loc(ident);
 
+                self.check_binding(ident.start, ident.end, ident.item);
                 self.lexer.consume();
 
                 ident
@@ -328,6 +354,84 @@ impl<'ast> Parser<'ast> {
         })
     }
 
+    /// Parse the body of a function, which is strict mode code if the code around it is
+    /// or if it starts with a `"use strict"` directive, then check the function's name
+    /// and parameters against the rules for the code it turned out to be
+    pub fn function_body(
+        &mut self,
+        name: Option<IdentifierNode<'ast>>,
+        params: PatternList<'ast>,
+    ) -> BlockNode<'ast, Statement<'ast>> {
+        let outer = self.lexer.strict();
+        let start = self.lexer.start();
+
+        match self.lexer.token {
+            BraceOpen => self.lexer.consume(),
+            _         => self.error::<()>(),
+        }
+
+        let body = self.body_statements(BraceClose);
+        let strict = self.lexer.strict();
+        let end = self.lexer.end_then_consume();
+
+        // The code after the function is back in the mode of the code around it
+        self.lexer.set_strict(outer);
+
+        let block = self.alloc_at_loc(start, end, Block { body });
+
+        if strict {
+            if let Some(name) = name {
+                self.check_strict_binding(name.start, name.end, name.item);
+            }
+        }
+
+        if has_use_strict(body) && !is_simple(params) {
+            self.early_error(
+                start,
+                end,
+                "Illegal 'use strict' directive in function with non-simple parameter list",
+            );
+        }
+
+        // Parameters read as strict mode code were checked as they were read
+        self.check_params(params, strict && !outer, strict);
+
+        block
+    }
+
+    /// Check the parameters of a function or arrow function, making sure their names
+    /// are allowed in strict mode code if `strict_names` is set, and that none of them
+    /// has the same name as another if the function is strict, an arrow function, or
+    /// has parameters that aren't plain identifiers, when `unique` is set.
+    pub fn check_params(&mut self, params: PatternList<'ast>, strict_names: bool, unique: bool) {
+        let unique = unique || !is_simple(params);
+        let mut seen: Vec<&str> = Vec::new();
+
+        for param in params.iter() {
+            let ident = match param.item {
+                Pattern::Identifier(name) => Some((param.start, param.end, name)),
+                Pattern::AssignmentPattern { left, .. } => match left.item {
+                    Pattern::Identifier(name) => Some((left.start, left.end, name)),
+                    _ => None
+                },
+                Pattern::RestElement { argument } => Some((argument.start, argument.end, argument.item)),
+                _ => None
+            };
+
+            if let Some((start, end, name)) = ident {
+                if strict_names {
+                    self.check_strict_binding(start, end, name);
+                }
+
+                if unique && seen.contains(&name) {
+                    self.early_error(start, end, "Duplicate parameter name not allowed in this context");
+                }
+
+                seen.push(name);
+            }
+        }
+    }
+
     #[inline]
     fn params(&mut self) -> NodeList<'ast, Pattern<'ast>> {
         expect!(self, ParenOpen);
@@ -14,17 +14,17 @@ use module::Module;
 use self::error::ToError;
 use self::nested::*;
 
-use ast::{Loc, Node, Statement, NodeList, Block, BlockNode};
-use ast::{Expression, ExpressionNode, ExpressionList, IdentifierNode};
+use ast::{Loc, Node, Statement, StatementNode, NodeList, Block, BlockNode};
+use ast::{Expression, ExpressionNode, ExpressionList, IdentifierNode, Literal};
 use ast::{OperatorKind, Pattern};
 use ast::expression::BinaryExpression;
-use lexer::{Lexer, Asi};
+use lexer::{Lexer, Asi, Token};
 use lexer::Token::*;
 
 pub trait Parse<'ast> {
     type Output;
 
-    fn parse(&mut Parser<'ast>) -> Self::Output;
+    fn parse(_: &mut Parser<'ast>) -> Self::Output;
 }
 
 pub struct Parser<'ast> {
@@ -51,13 +51,99 @@ impl<'ast> Parser<'ast> {
     }
 
     fn error<T: ToError>(&mut self) -> T {
-        let err = self.lexer.invalid_token();
+        let reserved = self.lexer.strict() && self.lexer.token.is_strict_reserved();
+        let mut err = self.lexer.invalid_token();
+
+        if reserved {
+            err.message = Some("Unexpected strict mode reserved word");
+        }
 
         self.errors.push(err);
 
         T::to_error()
     }
 
+    /// Record an error about code that reads fine, but isn't allowed where it is
+    fn early_error(&mut self, start: u32, end: u32, message: &'static str) {
+        let raw = self.lexer.slice_source(start as usize, end as usize);
+
+        self.errors.push(Error {
+            token: UnexpectedToken,
+            raw: raw.to_owned().into_boxed_str(),
+            start: start as usize,
+            end: end as usize,
+            message: Some(message),
+        });
+    }
+
+    /// Check whether a statement at the start of a script or function body is a directive,
+    /// switching to strict mode if it's `"use strict"`. The directives end at the first
+    /// statement that isn't one.
+    fn directive(&mut self, statement: StatementNode<'ast>) -> bool {
+        match directive_text(statement) {
+            Some(text) => {
+                if text == "use strict" {
+                    self.lexer.set_strict(true);
+                }
+
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Parse statements up to the end of a script or function body, which may start with directives
+    fn body_statements(&mut self, end: Token) -> NodeList<'ast, Statement<'ast>> {
+        if self.lexer.token == end || self.lexer.token == EndOfProgram {
+            return NodeList::empty();
+        }
+
+        let statement = self.statement();
+        let mut prologue = self.directive(statement);
+        let builder = ListBuilder::new(self.arena, statement);
+
+        while self.lexer.token != end && self.lexer.token != EndOfProgram {
+            let statement = self.statement();
+
+            if prologue {
+                prologue = self.directive(statement);
+            }
+
+            builder.push(self.arena, statement);
+        }
+
+        builder.as_list()
+    }
+
+    /// In strict mode code, `eval` and `arguments` can't be assigned to or declared
+    fn check_binding(&mut self, start: u32, end: u32, name: &str) {
+        if self.lexer.strict() {
+            self.check_strict_binding(start, end, name);
+        }
+    }
+
+    /// Check a name declared or assigned to by strict mode code, which may have been
+    /// read as sloppy mode code before a `"use strict"` directive was found
+    fn check_strict_binding(&mut self, start: u32, end: u32, name: &str) {
+        match name {
+            "eval" | "arguments" => {
+                self.early_error(start, end, "Unexpected eval or arguments in strict mode")
+            },
+            "implements" | "interface" | "package" | "private" |
+            "protected" | "public" | "static" | "yield" => {
+                self.early_error(start, end, "Unexpected strict mode reserved word")
+            },
+            _ => {}
+        }
+    }
+
+    /// Check the target of an assignment or an update
+    fn check_assignment_target(&mut self, target: ExpressionNode<'ast>) {
+        if let Expression::Identifier(name) = target.item {
+            self.check_binding(target.start, target.end, name);
+        }
+    }
+
     #[inline]
     fn asi(&mut self) -> Asi {
         self.lexer.asi()
@@ -101,18 +187,7 @@ impl<'ast> Parser<'ast> {
 
     #[inline]
     fn parse(&mut self) {
-        if self.lexer.token == EndOfProgram {
-            return;
-        }
-
-        let statement = self.statement();
-        let builder = ListBuilder::new(self.arena, statement);
-
-        while self.lexer.token != EndOfProgram {
-            builder.push(self.arena, self.statement());
-        }
-
-        self.body = builder.as_list()
+        self.body = self.body_statements(EndOfProgram);
     }
 
     #[inline]
@@ -132,18 +207,6 @@ impl<'ast> Parser<'ast> {
         self.alloc_at_loc(start, end, block)
     }
 
-    /// Same as above, but assumes that the opening brace has already been checked
-    #[inline]
-    fn unchecked_block<I>(&mut self) -> BlockNode<'ast, I> where
-        I: Parse<'ast, Output = Node<'ast, I>> + Copy
-    {
-        let start = self.lexer.start_then_consume();
-        let block = self.raw_block();
-        let end   = self.lexer.end_then_consume();
-
-        self.alloc_at_loc(start, end, block)
-    }
-
     #[inline]
     fn raw_block<I>(&mut self) -> Block<'ast, I> where
         I: Parse<'ast, Output = Node<'ast, I>> + Copy
@@ -218,13 +281,53 @@ impl<'ast> Parser<'ast> {
     }
 }
 
-/// Parse the JavaScript source `&str` and produce an Abstract Syntax Tree `Module`.
-pub fn parse<'src, 'ast>(source: &'src str) -> Result<Module<'ast>, Vec<Error>> {
+/// The text of a directive, as it's written between its quotes, if a statement can be one
+fn directive_text<'ast>(statement: StatementNode<'ast>) -> Option<&'ast str> {
+    match statement.item {
+        Statement::Expression(expression) => match expression.item {
+            Expression::Literal(Literal::String(raw)) => Some(&raw[1..raw.len() - 1]),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+/// Whether a script or function body starts with a `"use strict"` directive
+fn has_use_strict<'ast>(body: NodeList<'ast, Statement<'ast>>) -> bool {
+    body.iter()
+        .map(|&statement| directive_text(statement))
+        .take_while(Option::is_some)
+        .any(|text| text == Some("use strict"))
+}
+
+/// Parse the JavaScript source `&str` of a script and produce an Abstract Syntax Tree `Module`.
+///
+/// The script is sloppy mode code, unless it starts with a `"use strict"` directive.
+pub fn parse<'ast>(source: &str) -> Result<Module<'ast>, Vec<Error>> {
+    parse_in_mode(source, false)
+}
+
+/// Parse the JavaScript source `&str` of a script which is strict mode code from the start, like
+/// the code a direct `eval` in strict mode code runs, and produce an Abstract Syntax Tree `Module`.
+///
+/// Unlike `parse_module`, this parses a script, which can't import or export anything.
+pub fn parse_strict<'ast>(source: &str) -> Result<Module<'ast>, Vec<Error>> {
+    parse_in_mode(source, true)
+}
+
+/// Parse the JavaScript source `&str` of a module, which is always strict mode code,
+/// and produce an Abstract Syntax Tree `Module`.
+pub fn parse_module<'ast>(source: &str) -> Result<Module<'ast>, Vec<Error>> {
+    parse_in_mode(source, true)
+}
+
+fn parse_in_mode<'ast>(source: &str, strict: bool) -> Result<Module<'ast>, Vec<Error>> {
     let arena = Arena::new();
 
     let (body, errors) = {
         let mut parser = Parser::new(source, &arena);
 
+        parser.lexer.set_strict(strict);
         parser.parse();
 
         (parser.body.into_unsafe(), parser.errors)
@@ -315,4 +418,49 @@ mod test {
 
         assert_eq!(parse(";;;").unwrap().body(), expected);
     }
+
+    fn strict_error(source: &str) -> Option<&'static str> {
+        parse(source).unwrap_err()[0].message
+    }
+
+    #[test]
+    fn strict_mode_early_errors() {
+        assert_eq!(strict_error("'use strict'; with (a) {}"), Some("Strict mode code may not include a with statement"));
+        assert_eq!(strict_error("'use strict'; 010"), Some("Octal literals are not allowed in strict mode."));
+        assert_eq!(strict_error("'use strict'; '\\01'"), Some("Octal escape sequences are not allowed in strict mode."));
+        assert_eq!(strict_error("'use strict'; delete x"), Some("Delete of an unqualified identifier in strict mode."));
+        assert_eq!(strict_error("'use strict'; function f(a, a) {}"), Some("Duplicate parameter name not allowed in this context"));
+        assert_eq!(strict_error("'use strict'; var implements"), Some("Unexpected strict mode reserved word"));
+        assert_eq!(strict_error("function f() { 'use strict'; var yield; }"), Some("Unexpected strict mode reserved word"));
+        assert_eq!(strict_error("'use strict'; eval = 1"), Some("Unexpected eval or arguments in strict mode"));
+        assert_eq!(strict_error("'use strict'; arguments++"), Some("Unexpected eval or arguments in strict mode"));
+        assert_eq!(strict_error("function eval() { 'use strict'; }"), Some("Unexpected eval or arguments in strict mode"));
+        assert_eq!(strict_error("function f(a = 1) { 'use strict'; }"), Some("Illegal 'use strict' directive in function with non-simple parameter list"));
+        assert_eq!(strict_error("(a, a) => a"), Some("Duplicate parameter name not allowed in this context"));
+        assert_eq!(strict_error("class A { m(eval) {} }"), Some("Unexpected eval or arguments in strict mode"));
+    }
+
+    #[test]
+    fn sloppy_mode_accepts_strict_mode_errors() {
+        for source in &[
+            "010",
+            "'\\01'",
+            "delete x",
+            "function f(a, a) {}",
+            "var implements, yield = 1",
+            "eval = 1; arguments++",
+            "function eval() {}",
+            "x; 'use strict'; 010",
+            "function f() { 'use strict'; } 010",
+        ] {
+            assert!(parse(source).is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn modules_are_strict() {
+        assert!(parse_module("var implements").is_err());
+        assert!(parse_module("with (a) {}").is_err());
+        assert!(parse_module("var a = 1").is_ok());
+    }
 }
@@ -7,7 +7,7 @@ use ast::expression::*;
 use ast::OperatorKind::*;
 use lexer::Asi;
 
-const TOTAL_TOKENS: usize = 108;
+const TOTAL_TOKENS: usize = 109;
 
 type NestedHandler = Option<for<'ast> fn(&mut Parser<'ast>, ExpressionNode<'ast>) -> ExpressionNode<'ast>>;
 
@@ -38,7 +38,7 @@ macro_rules! bp {
     }
 }
 
-/// All potential tokens, including Comma for sequence expressions
+// All potential tokens, including Comma for sequence expressions
 bp!(ANY, [
     ____, ____, ____, SEQ,  CALL, ____, CMEM, ____, ____, ____, ARRW, ____,
 //  EOF   ;     :     ,     (     )     [     ]     {     }     =>    NEW
@@ -66,6 +66,9 @@ bp!(ANY, [
 
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
 //  IMPL  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E
+
+    ____,
+//  BIGIN
 ]);
 
 bp!(B0, [
@@ -78,6 +81,7 @@ bp!(B0, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B1, [
@@ -90,6 +94,7 @@ bp!(B1, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B5, [
@@ -102,6 +107,7 @@ bp!(B5, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B6, [
@@ -114,6 +120,7 @@ bp!(B6, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B7, [
@@ -126,6 +133,7 @@ bp!(B7, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B8, [
@@ -138,6 +146,7 @@ bp!(B8, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B9, [
@@ -150,6 +159,7 @@ bp!(B9, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B10, [
@@ -162,6 +172,7 @@ bp!(B10, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B11, [
@@ -174,6 +185,7 @@ bp!(B11, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B12, [
@@ -186,6 +198,7 @@ bp!(B12, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B13, [
@@ -198,6 +211,7 @@ bp!(B13, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B14, [
@@ -210,6 +224,7 @@ bp!(B14, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 bp!(B15, [
@@ -222,6 +237,21 @@ bp!(B15, [
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
     ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
+]);
+
+// Member access only, for the callee of `new`, which takes the first arguments after it
+bp!(B16, [
+    ____, ____, ____, ____, ____, ____, CMEM, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____, ____,
+    ____, ____, ____, ____, ____, ____, ____, ACCS, TPLE, TPLS, ____, ____,
+    ____,
 ]);
 
 const ____: NestedHandler = None;
@@ -265,10 +295,7 @@ const ARRW: NestedHandler = Some(|par, left| {
         _ => NodeList::from(par.arena, left)
     };
 
-    let expression = par.arrow_function_expression(params);
-    let start = left.start;
-    let end = par.lexer.end();
-    par.alloc_at_loc(start, end, expression)
+    par.arrow_function_expression(left.start, params)
 });
 
 const ACCS: NestedHandler = Some(|par, left| {
@@ -331,6 +358,8 @@ macro_rules! postfix {
                     par.error::<()>();
                 }
 
+                par.check_assignment_target(left);
+
                 par.alloc_at_loc(left.start, end, PostfixExpression {
                     operator: $op,
                     operand: left,
@@ -352,6 +381,8 @@ macro_rules! assign {
                     par.error::<()>();
                 }
 
+                par.check_assignment_target(left);
+
                 let right = par.expression::<B1>();
 
                 par.alloc_at_loc(left.start, right.end, BinaryExpression {
@@ -14,7 +14,7 @@ use ast::OperatorKind::*;
 
 type StatementHandler = for<'ast> fn(&mut Parser<'ast>) -> StatementNode<'ast>;
 
-static STMT_HANDLERS: [StatementHandler; 108] = [
+static STMT_HANDLERS: [StatementHandler; 109] = [
     ____, EMPT, ____, ____, PRN,  ____, ARR,  ____, BLCK, ____, ____, NEW,
 //  EOF   ;     :     ,     (     )     [     ]     {     }     =>    NEW
 
@@ -33,7 +33,7 @@ static STMT_HANDLERS: [StatementHandler; 108] = [
     CONS, BRK,  DO,   ____, ____, ____, ____, CLAS, ____, RET,  WHL,  ____,
 //  CONST BREAK DO    CASE  ELSE  CATCH EXPRT CLASS EXTND RET   WHILE FINLY
 
-    ____, ____, CONT, FOR,  SWCH, ____, DBGGR, FUNC, THIS, ____, IF,   THRW,
+    ____, WITH, CONT, FOR,  SWCH, ____, DBGGR, FUNC, THIS, ____, IF,   THRW,
 //  SUPER WITH  CONT  FOR   SWTCH YIELD DBGGR FUNCT THIS  DEFLT IF    THROW
 
     ____, TRY,  ____, TRUE, FALS, NULL, UNDE, STR,  NUM,  BIN,  ____, ____,
@@ -41,6 +41,9 @@ static STMT_HANDLERS: [StatementHandler; 108] = [
 
     ____, ____, ____, ____, ____, ____, LABL, ____, TPLE, TPLS, ____, ____,
 //  IMPL  PCKG  PROT  IFACE PRIV  PUBLI IDENT ACCSS TPL_O TPL_C ERR_T ERR_E
+
+    BIGN,
+//  BIGIN
 ];
 
 
@@ -57,7 +60,7 @@ macro_rules! create_handlers {
 
 /// Shared expression handlers that produce StatementNode<'ast>
 use parser::expression::handlers::{
-    PRN, ARR, OP, NEW, REG, THIS, TRUE, FALS, NULL, UNDE, STR, NUM, BIN, TPLS, TPLE
+    PRN, ARR, OP, NEW, REG, THIS, TRUE, FALS, NULL, UNDE, STR, NUM, BIN, BIGN, TPLS, TPLE
 };
 
 create_handlers! {
@@ -66,6 +69,18 @@ create_handlers! {
         par.error::<()>();
         par.alloc_at_loc(loc, loc, Statement::Empty)
     };
+    const WITH = |par| {
+        let (start, end) = par.lexer.loc();
+
+        if par.lexer.strict() {
+            par.early_error(start, end, "Strict mode code may not include a with statement");
+            par.lexer.consume();
+        } else {
+            par.error::<()>();
+        }
+
+        par.alloc_at_loc(start, end, Statement::Empty)
+    };
     const EMPT = |par| {
         let stmt = par.alloc_in_loc(Statement::Empty);
         par.lexer.consume();
@@ -1,18 +1,162 @@
 extern crate boa;
-use boa::exec::{Executor, Interpreter};
-use boa::syntax::lexer::Lexer;
-use boa::syntax::parser::Parser;
-use std::fs::read_to_string;
+use boa::exec_new::bytecode::disassemble;
+use boa::exec_new::event_loop::EventLoop;
+use boa::exec_new::host::{self, Permissions};
+use boa::exec_new::{self, Script, Termination};
+use boa::js::atom::atom_stats;
 use boa::repl::REPL;
 use std::env;
+use std::fs::read_to_string;
+use std::process;
 
 fn print_help() {
     println!("USAGE:");
     println!("\t ./bin <mode> [INPUT]");
+    println!("\t ./bin run --dump-bytecode [INPUT]");
+    println!("\t ./bin run --atom-stats [INPUT]");
+    println!("\t ./bin run --no-jit [INPUT]");
+    println!("\t ./bin run --jit-stats [INPUT]");
+    println!("\t ./bin run [PERMISSIONS] [INPUT] [ARGS]");
+    println!("PERMISSIONS, which scripts have none of unless given:");
+    println!("\t --allow-read[=PATH,...]  read files, all of them or the ones under the paths");
+    println!("\t --allow-write[=PATH,...] write files, all of them or the ones under the paths");
+    println!(
+        "\t --allow-env[=NAME,...]   read environment variables, all of them or the ones named"
+    );
+    println!("\t --allow-stdin            read the standard input stream");
+    println!("\t --allow-exit             stop with an exit code through process.exit");
+    println!("\t --allow-all              all of the above");
 }
 
-pub fn main() {
+/// Grant the permission a flag like `--allow-read=path` asks for, returning `false` if it isn't one
+fn allow_flag(permissions: &mut Permissions, flag: &str) -> bool {
+    let (name, list) = match flag.find('=') {
+        Some(i) => (&flag[..i], Some(&flag[i + 1..])),
+        None => (flag, None),
+    };
+    let items: Option<Vec<&str>> =
+        list.map(|list| list.split(',').filter(|s| !s.is_empty()).collect());
+    match (name, items) {
+        ("--allow-read", None) => {
+            permissions.allow_read_all();
+        }
+        ("--allow-read", Some(paths)) => {
+            for path in paths {
+                permissions.allow_read(path);
+            }
+        }
+        ("--allow-write", None) => {
+            permissions.allow_write_all();
+        }
+        ("--allow-write", Some(paths)) => {
+            for path in paths {
+                permissions.allow_write(path);
+            }
+        }
+        ("--allow-env", None) => {
+            permissions.allow_env_all();
+        }
+        ("--allow-env", Some(names)) => {
+            for name in names {
+                permissions.allow_env(name);
+            }
+        }
+        ("--allow-stdin", None) => {
+            permissions.allow_stdin();
+        }
+        ("--allow-exit", None) => {
+            permissions.allow_exit();
+        }
+        ("--allow-all", None) => *permissions = Permissions::all(),
+        _ => return false,
+    }
+    true
+}
+
+/// Leave with the exit code a script gave `process.exit`, if it called it
+fn exit_if_asked(engine: &exec_new::Interpreter) {
+    if let Some(Termination::Exit(code)) = exec_new::Executor::termination(engine) {
+        process::exit(code);
+    }
+}
+
+/// Run a script file, then the timers and microtasks it queues until there are none left
+fn run_file(engine: &mut exec_new::Interpreter, path: &str, argv: Vec<String>) {
+    let buffer = read_to_string(path).unwrap();
+    let realm = exec_new::Executor::realm(engine);
+    let event_loop = EventLoop::new();
+    event_loop.install(&realm);
+    host::install(&realm, argv);
+    let result = Script::parse_with_name(path, &buffer)
+        .and_then(|script| exec_new::Executor::run_script(engine, &script));
+    exit_if_asked(engine);
+    match result {
+        Ok(v) => println!("{}", v),
+        Err(v) => return println!("Error: {}", v),
+    }
+    let result = event_loop.run(engine);
+    exit_if_asked(engine);
+    if let Err(v) = result {
+        println!("Error: {}", v);
+    }
+}
 
+/// Run a script with the flags given to the `run` mode: one of the modes listed by `print_help`,
+/// and the permissions of the host functions, followed by the script and the arguments it gets
+fn run(args: &[String]) {
+    let mut mode = None;
+    let mut permissions = Permissions::new();
+    let mut i = 2;
+    while i < args.len() && args[i].starts_with("--") {
+        match args[i].as_ref() {
+            _ if allow_flag(&mut permissions, &args[i]) => (),
+            "--dump-bytecode" | "--atom-stats" | "--no-jit" | "--jit-stats" if mode.is_none() => {
+                mode = Some(args[i].as_str())
+            }
+            _ => return print_help(),
+        }
+        i += 1;
+    }
+    let path = match args.get(i) {
+        Some(path) => path,
+        None => return print_help(),
+    };
+    let mut argv = vec![args[0].clone(), path.clone()];
+    argv.extend(args[i + 1..].iter().cloned());
+    let mut engine: exec_new::Interpreter = exec_new::Executor::new();
+    exec_new::Executor::set_permissions(&mut engine, permissions);
+    match mode {
+        None => run_file(&mut engine, path, argv),
+        Some("--dump-bytecode") => {
+            let buffer = read_to_string(path).unwrap();
+            match Script::parse_with_name(path, &buffer) {
+                Ok(script) => print!("{}", disassemble(&script)),
+                Err(v) => print!("Error: {}", v),
+            }
+        }
+        Some("--atom-stats") => {
+            let buffer = read_to_string(path).unwrap();
+            let result = Script::parse_with_name(path, &buffer)
+                .and_then(|script| exec_new::Executor::run_script(&mut engine, &script));
+            match result {
+                Ok(v) => println!("{}", v),
+                Err(v) => println!("Error: {}", v),
+            }
+            eprintln!("{}", atom_stats());
+        }
+        Some(mode) => {
+            exec_new::Executor::set_jit(&mut engine, mode == "--jit-stats");
+            run_file(&mut engine, path, argv);
+            if mode == "--jit-stats" {
+                for stats in exec_new::Executor::function_stats(&engine) {
+                    eprintln!("{}", stats);
+                }
+            }
+        }
+    }
+}
+
+pub fn main() {
     let args: Vec<_> = env::args().collect();
 
     match args.len() {
@@ -21,31 +165,13 @@ pub fn main() {
             "repl" => {
                 let mut repl: REPL = REPL::new();
                 repl.run();
-            },
+            }
             "help" => {
                 print_help();
-            },
-            _ => print_help(),
-        },
-        3 => match args[1].as_ref() {
-            "run" => {
-                let buffer = read_to_string(&args[2]).unwrap();
-                let mut lexer = Lexer::new(&buffer);
-                lexer.lex().unwrap();
-                let tokens = lexer.tokens;
-
-                // Setup executor
-                let expr = Parser::new(tokens).parse_all().unwrap();
-
-                let mut engine: Interpreter = Executor::new();
-                let result = engine.run(&expr);
-                match result {
-                    Ok(v) => print!("{}", v),
-                    Err(v) => print!("Error: {}", v),
-                }
-            },
+            }
             _ => print_help(),
         },
+        _ if args[1] == "run" => run(&args),
         _ => print_help(),
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,26 @@
-use gc::{Gc, GcCell};
-use crate::js::function::{Function, RegularFunction};
-use crate::js::object::{INSTANCE_PROTOTYPE, PROTOTYPE};
+use crate::gc::GcCell;
+use crate::js::function::{
+    bound_target, make_arguments, Caller, Function, RegularFunction,
+};
+use crate::js::array::ARRAY_DATA;
+use crate::js::object::{ObjectData, PROTOTYPE};
 use crate::js::value::{from_value, to_value, ResultValue, Value, ValueData};
-use crate::js::{array, console, function, json, math, object, string};
+use crate::js::date::{self, TimeZone};
+use crate::js::array_buffer::SharedBytes;
+use crate::js::{
+    array, array_buffer, bigint, boolean, console, data_view, error, function, json, math, number,
+    object, string, typed_array,
+};
+#[cfg(feature = "web")]
+use crate::js::{encoding, uri, url};
+use std::cell::RefCell;
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::rc::Rc;
 use crate::syntax::ast::constant::Const;
 use crate::syntax::ast::expr::{Expr, ExprDef};
 use crate::syntax::ast::op::{BinOp, BitOp, CompOp, LogOp, NumOp, UnaryOp};
 /// A variable scope
-#[derive(Trace, Finalize, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Scope {
     /// The value of `this` in the scope
     pub this: Value,
@@ -28,6 +39,16 @@ pub trait Executor {
     fn make_scope(&mut self, this: Value) -> Scope;
     /// Destroy the current scope
     fn destroy_scope(&mut self) -> Scope;
+    /// Set the local time zone `Date` uses, in place of the host's
+    fn set_time_zone(&mut self, tz: TimeZone);
+    /// Make an `ArrayBuffer` holding a copy of some bytes
+    fn copy_array_buffer(&self, bytes: &[u8]) -> Value {
+        self.share_array_buffer(Rc::new(RefCell::new(bytes.to_vec())))
+    }
+    /// Make an `ArrayBuffer` over bytes the host keeps a handle to, so it sees what the script writes and the other way round
+    fn share_array_buffer(&self, bytes: SharedBytes) -> Value {
+        array_buffer::new_array_buffer(&self.get_global("ArrayBuffer".to_string()), bytes)
+    }
     /// Run an expression
     fn run(&mut self, expr: &Expr) -> ResultValue;
 }
@@ -38,13 +59,93 @@ pub struct Interpreter {
     global: Value,
     /// The scopes
     pub scopes: Vec<Scope>,
+    /// The local time zone `Date` uses
+    time_zone: Rc<TimeZone>,
 }
 
 impl Interpreter {
     #[inline(always)]
     /// Get the current scope
     pub fn scope(&self) -> &Scope {
-        self.scopes.get(self.scopes.len() - 1).unwrap()
+        self.scopes.last().unwrap()
+    }
+
+    /// Get a field of a value, looking the fields of number and boolean primitives up on their wrapper's prototype,
+    /// and those of functions on `Function.prototype`
+    fn get_field(&self, obj: &Value, field: String) -> Value {
+        let wrapper = match *obj.data() {
+            ValueData::Number(_) | ValueData::Integer(_) => "Number",
+            ValueData::Boolean(_) => "Boolean",
+            ValueData::BigInt(_) => "BigInt",
+            ValueData::Function(_) => match obj.get_prop(field.clone()) {
+                Some(prop) => return prop.value.clone(),
+                None => "Function",
+            },
+            _ => return obj.get_field(field),
+        };
+        self.get_global(wrapper.to_string())
+            .get_field_slice(PROTOTYPE)
+            .get_field(field)
+    }
+}
+
+impl Caller for Interpreter {
+    fn call(&mut self, func: &Value, this: Value, args: Vec<Value>) -> ResultValue {
+        let func_data = match *func.data() {
+            ValueData::Function(ref inner) => inner.borrow().clone(),
+            _ => return Err(Value::undefined()),
+        };
+        match func_data {
+            Function::NativeFunc(ref ntv) => {
+                ntv.data.call(this, func.clone(), args, self)
+            }
+            Function::RegularFunc(ref data) => {
+                let scope = self.make_scope(this);
+                let scope_vars_ptr = scope.vars.borrow();
+                for (i, name) in data.args.iter().enumerate() {
+                    let val = match args.get(i) {
+                        Some(val) => val.clone(),
+                        None => Value::undefined(),
+                    };
+                    scope_vars_ptr.set_field(name.clone(), val);
+                }
+                if !data.args.iter().any(|name| name == "arguments") {
+                    let arguments =
+                        make_arguments(&self.global, func, &args, Some((&data.args, &scope.vars)));
+                    scope_vars_ptr.set_field_slice("arguments", arguments);
+                }
+                let result = self.run(&data.expr);
+                self.destroy_scope();
+                result
+            }
+            Function::NewRegularFunc(_) => Err(to_value(
+                "Functions made by the new interpreter can't be called here",
+            )),
+        }
+    }
+
+    fn construct(&mut self, func: &Value, args: Vec<Value>) -> ResultValue {
+        if let Some((target, mut bound_args)) = bound_target(func) {
+            bound_args.extend(args);
+            return self.construct(&target, bound_args);
+        }
+        let this = Value::new(ValueData::Object(GcCell::new(ObjectData::new())));
+        this.borrow().set_prototype(func.get_field_slice(PROTOTYPE));
+        match *func.data() {
+            ValueData::Function(ref inner) => match inner.clone().into_inner() {
+                Function::NativeFunc(ref ntv) => ntv.data.call(this, func.clone(), args, self),
+                _ => self.call(func, this, args),
+            },
+            _ => Ok(Value::undefined()),
+        }
+    }
+
+    fn prototype(&self, constructor: &str) -> Value {
+        self.get_global(constructor.to_string()).get_field_slice(PROTOTYPE)
+    }
+
+    fn time_zone(&self) -> Rc<TimeZone> {
+        self.time_zone.clone()
     }
 }
 
@@ -52,11 +153,25 @@ impl Executor for Interpreter {
     fn new() -> Interpreter {
         let global = ValueData::new_obj(None);
         object::init(global.clone());
-        console::init(global.clone());
         math::init(global.clone());
+        number::init(global.clone());
         array::init(global.clone());
+        array_buffer::init(global.clone());
+        data_view::init(global.clone());
+        typed_array::init(global.clone());
+        bigint::init(global.clone());
+        boolean::init(global.clone());
+        date::init(global.clone());
+        error::init(global.clone());
         function::init(global.clone());
         json::init(global.clone());
+        #[cfg(feature = "web")]
+        {
+            encoding::init(global.clone());
+            uri::init(global.clone());
+            url::init(global.clone());
+        }
+        console::init(global.clone());
         string::init(global.clone());
         Interpreter {
             global: global.clone(),
@@ -64,6 +179,7 @@ impl Executor for Interpreter {
                 this: global.clone(),
                 vars: global.clone(),
             }],
+            time_zone: Rc::new(TimeZone::System),
         }
     }
 
@@ -71,13 +187,17 @@ impl Executor for Interpreter {
         self.global.borrow().set_field(name, val)
     }
 
+    fn set_time_zone(&mut self, tz: TimeZone) {
+        self.time_zone = Rc::new(tz);
+    }
+
     fn get_global(&self, name: String) -> Value {
         self.global.borrow().get_field(name)
     }
 
     fn make_scope(&mut self, this: Value) -> Scope {
         let scope = Scope {
-            this: this,
+            this,
             vars: ValueData::new_obj(None),
         };
         self.scopes.push(scope.clone());
@@ -91,7 +211,7 @@ impl Executor for Interpreter {
     fn run(&mut self, expr: &Expr) -> ResultValue {
         match expr.def {
             ExprDef::ConstExpr(Const::Null) => Ok(to_value(None::<()>)),
-            ExprDef::ConstExpr(Const::Undefined) => Ok(Gc::new(ValueData::Undefined)),
+            ExprDef::ConstExpr(Const::Undefined) => Ok(Value::undefined()),
             ExprDef::ConstExpr(Const::Num(num)) => Ok(to_value(num)),
             ExprDef::ConstExpr(Const::Int(num)) => Ok(to_value(num)),
             // we can't move String from Const into value, because const is a garbage collected value
@@ -111,18 +231,17 @@ impl Executor for Interpreter {
                 Ok(obj)
             }
             ExprDef::LocalExpr(ref name) => {
-                let mut val = Gc::new(ValueData::Undefined);
+                let mut val = Value::undefined();
                 for scope in self.scopes.iter().rev() {
                     let vars = scope.vars.clone();
                     let vars_ptr = vars.borrow();
-                    match *vars_ptr.clone() {
-                        ValueData::Object(ref obj) => match obj.borrow().get(name) {
-                            Some(v) => {
+                    match *vars_ptr.clone().data() {
+                        ValueData::Object(ref obj) => {
+                            if let Some(v) = obj.borrow().get(name) {
                                 val = v.value.clone();
                                 break;
                             }
-                            None => (),
-                        },
+                        }
                         _ => unreachable!(),
                     }
                 }
@@ -130,25 +249,25 @@ impl Executor for Interpreter {
             }
             ExprDef::GetConstFieldExpr(ref obj, ref field) => {
                 let val_obj = self.run(obj)?;
-                Ok(val_obj.borrow().get_field(field.clone()))
+                Ok(self.get_field(&val_obj, field.clone()))
             }
             ExprDef::GetFieldExpr(ref obj, ref field) => {
                 let val_obj = self.run(obj)?;
                 let val_field = self.run(field)?;
-                Ok(val_obj.borrow().get_field(val_field.borrow().to_string()))
+                Ok(self.get_field(&val_obj, val_field.borrow().to_string()))
             }
             ExprDef::CallExpr(ref callee, ref args) => {
                 let (this, func) = match callee.def {
                     ExprDef::GetConstFieldExpr(ref obj, ref field) => {
                         let obj = self.run(obj)?;
-                        (obj.clone(), obj.borrow().get_field(field.clone()))
+                        (obj.clone(), self.get_field(&obj, field.clone()))
                     }
                     ExprDef::GetFieldExpr(ref obj, ref field) => {
                         let obj = self.run(obj)?;
                         let field = self.run(field)?;
                         (
                             obj.clone(),
-                            obj.borrow().get_field(field.borrow().to_string()),
+                            self.get_field(&obj, field.borrow().to_string()),
                         )
                     }
                     _ => (self.global.clone(), self.run(&callee.clone())?),
@@ -157,62 +276,42 @@ impl Executor for Interpreter {
                 for arg in args.iter() {
                     v_args.push(self.run(arg)?);
                 }
-                match *func {
-                    ValueData::Function(ref func) => match *func.borrow() {
-                        Function::NativeFunc(ref ntv) => {
-                            let func = ntv.data;
-                            func(this, self.run(callee)?, v_args)
-                        }
-                        Function::RegularFunc(ref data) => {
-                            let scope = self.make_scope(this);
-                            let scope_vars_ptr = scope.vars.borrow();
-                            for i in 0..data.args.len() {
-                                let name = data.args.get(i).unwrap();
-                                let expr = v_args.get(i).unwrap();
-                                scope_vars_ptr.set_field(name.clone(), expr.clone());
-                            }
-                            let result = self.run(&data.expr);
-                            self.destroy_scope();
-                            result
-                        }
-                    },
-                    _ => Err(Gc::new(ValueData::Undefined)),
-                }
+                self.call(&func, this, v_args)
             }
             ExprDef::WhileLoopExpr(ref cond, ref expr) => {
-                let mut result = Gc::new(ValueData::Undefined);
+                let mut result = Value::undefined();
                 while self.run(cond)?.borrow().is_true() {
                     result = self.run(expr)?;
                 }
                 Ok(result)
             }
             ExprDef::IfExpr(ref cond, ref expr, None) => {
-                Ok(if r#try!(self.run(cond)).borrow().is_true() {
-                    r#try!(self.run(expr))
+                Ok(if self.run(cond)?.borrow().is_true() {
+                    self.run(expr)?
                 } else {
-                    Gc::new(ValueData::Undefined)
+                    Value::undefined()
                 })
             }
             ExprDef::IfExpr(ref cond, ref expr, Some(ref else_e)) => {
-                Ok(if r#try!(self.run(cond)).borrow().is_true() {
-                    r#try!(self.run(expr))
+                Ok(if self.run(cond)?.borrow().is_true() {
+                    self.run(expr)?
                 } else {
-                    r#try!(self.run(else_e))
+                    self.run(else_e)?
                 })
             }
             ExprDef::SwitchExpr(ref val_e, ref vals, ref default) => {
-                let val = r#try!(self.run(val_e)).clone();
-                let mut result = Gc::new(ValueData::Null);
+                let val = self.run(val_e)?.clone();
+                let mut result = Value::null();
                 let mut matched = false;
                 for tup in vals.iter() {
                     let tup: &(Expr, Vec<Expr>) = tup;
                     let cond = &tup.0;
                     let block = &tup.1;
-                    if val == r#try!(self.run(cond)) {
+                    if val == self.run(cond)? {
                         matched = true;
                         let last_expr = block.last().unwrap();
                         for expr in block.iter() {
-                            let e_result = r#try!(self.run(expr));
+                            let e_result = self.run(expr)?;
                             if expr == last_expr {
                                 result = e_result;
                             }
@@ -220,27 +319,27 @@ impl Executor for Interpreter {
                     }
                 }
                 if !matched && default.is_some() {
-                    result = r#try!(self.run(default.as_ref().unwrap()));
+                    result = self.run(default.as_ref().unwrap())?;
                 }
                 Ok(result)
             }
             ExprDef::ObjectDeclExpr(ref map) => {
                 let obj = ValueData::new_obj(Some(self.global.clone()));
                 for (key, val) in map.iter() {
-                    obj.borrow().set_field(key.clone(), r#try!(self.run(val)));
+                    obj.borrow().set_field(key.clone(), self.run(val)?);
                 }
                 Ok(obj)
             }
             ExprDef::ArrayDeclExpr(ref arr) => {
                 let arr_map = ValueData::new_obj(Some(self.global.clone()));
+                arr_map.set_internal_slot(ARRAY_DATA, to_value(true));
                 let mut index: i32 = 0;
                 for val in arr.iter() {
-                    let val = r#try!(self.run(val));
+                    let val = self.run(val)?;
                     arr_map.borrow().set_field(index.to_string(), val);
                     index += 1;
                 }
-                arr_map.borrow().set_field_slice(
-                    INSTANCE_PROTOTYPE,
+                arr_map.borrow().set_prototype(
                     self.get_global("Array".to_string())
                         .borrow()
                         .get_field_slice(PROTOTYPE),
@@ -251,7 +350,8 @@ impl Executor for Interpreter {
             ExprDef::FunctionDeclExpr(ref name, ref args, ref expr) => {
                 let function =
                     Function::RegularFunc(RegularFunction::new(*expr.clone(), args.clone()));
-                let val = Gc::new(ValueData::Function(GcCell::new(function)));
+                let val = Value::new(ValueData::Function(GcCell::new(function)));
+                val.set_field_slice("name", to_value(name.clone().unwrap_or_default()));
                 if name.is_some() {
                     self.global
                         .borrow()
@@ -262,47 +362,49 @@ impl Executor for Interpreter {
             ExprDef::ArrowFunctionDeclExpr(ref args, ref expr) => {
                 let function =
                     Function::RegularFunc(RegularFunction::new(*expr.clone(), args.clone()));
-                Ok(Gc::new(ValueData::Function(GcCell::new(function))))
+                Ok(Value::new(ValueData::Function(GcCell::new(function))))
             }
             ExprDef::BinOpExpr(BinOp::Num(ref op), ref a, ref b) => {
-                let v_r_a = r#try!(self.run(a));
-                let v_r_b = r#try!(self.run(b));
-                let v_a = (*v_r_a).clone();
-                let v_b = (*v_r_b).clone();
-                Ok(Gc::new(match *op {
+                let v_r_a = self.run(a)?;
+                let v_r_b = self.run(b)?;
+                let v_a = v_r_a.data().clone();
+                let v_b = v_r_b.data().clone();
+                let result = match *op {
                     NumOp::Add => v_a + v_b,
                     NumOp::Sub => v_a - v_b,
                     NumOp::Mul => v_a * v_b,
                     NumOp::Div => v_a / v_b,
                     NumOp::Mod => v_a % v_b,
-                }))
+                };
+                result.map_err(|e| e.into_error(self))
             }
             ExprDef::UnaryOpExpr(ref op, ref a) => {
-                let v_r_a = r#try!(self.run(a));
-                let v_a = (*v_r_a).clone();
+                let v_r_a = self.run(a)?;
+                let v_a = v_r_a.data().clone();
                 Ok(match *op {
-                    UnaryOp::Minus => to_value(-v_a.to_num()),
-                    UnaryOp::Plus => to_value(v_a.to_num()),
-                    UnaryOp::Not => Gc::new(!v_a),
+                    UnaryOp::Minus => Value::new(-v_a),
+                    UnaryOp::Plus => to_value(v_a.to_number().map_err(|e| e.into_error(self))?),
+                    UnaryOp::Not => Value::new(!v_a),
                     _ => unreachable!(),
                 })
             }
             ExprDef::BinOpExpr(BinOp::Bit(ref op), ref a, ref b) => {
-                let v_r_a = r#try!(self.run(a));
-                let v_r_b = r#try!(self.run(b));
-                let v_a = (*v_r_a).clone();
-                let v_b = (*v_r_b).clone();
-                Ok(Gc::new(match *op {
+                let v_r_a = self.run(a)?;
+                let v_r_b = self.run(b)?;
+                let v_a = v_r_a.data().clone();
+                let v_b = v_r_b.data().clone();
+                let result = match *op {
                     BitOp::And => v_a & v_b,
                     BitOp::Or => v_a | v_b,
                     BitOp::Xor => v_a ^ v_b,
                     BitOp::Shl => v_a << v_b,
                     BitOp::Shr => v_a >> v_b,
-                }))
+                };
+                result.map_err(|e| e.into_error(self))
             }
             ExprDef::BinOpExpr(BinOp::Comp(ref op), ref a, ref b) => {
-                let v_r_a = r#try!(self.run(a));
-                let v_r_b = r#try!(self.run(b));
+                let v_r_a = self.run(a)?;
+                let v_r_b = self.run(b)?;
                 let v_a = v_r_a.borrow();
                 let v_b = v_r_b.borrow();
                 Ok(to_value(match *op {
@@ -310,19 +412,17 @@ impl Executor for Interpreter {
                     CompOp::Equal => v_a == v_b,
                     CompOp::NotEqual if v_a.is_object() => v_r_a != v_r_b,
                     CompOp::NotEqual => v_a != v_b,
-                    CompOp::StrictEqual if v_a.is_object() => v_r_a == v_r_b,
-                    CompOp::StrictEqual => v_a == v_b,
-                    CompOp::StrictNotEqual if v_a.is_object() => v_r_a != v_r_b,
-                    CompOp::StrictNotEqual => v_a != v_b,
-                    CompOp::GreaterThan => v_a.to_num() > v_b.to_num(),
-                    CompOp::GreaterThanOrEqual => v_a.to_num() >= v_b.to_num(),
-                    CompOp::LessThan => v_a.to_num() < v_b.to_num(),
-                    CompOp::LessThanOrEqual => v_a.to_num() <= v_b.to_num(),
+                    CompOp::StrictEqual => v_a.strict_equals(v_b),
+                    CompOp::StrictNotEqual => !v_a.strict_equals(v_b),
+                    CompOp::GreaterThan => v_b.less_than(v_a) == Some(true),
+                    CompOp::GreaterThanOrEqual => v_a.less_than(v_b) == Some(false),
+                    CompOp::LessThan => v_a.less_than(v_b) == Some(true),
+                    CompOp::LessThanOrEqual => v_b.less_than(v_a) == Some(false),
                 }))
             }
             ExprDef::BinOpExpr(BinOp::Log(ref op), ref a, ref b) => {
-                let v_a = from_value::<bool>(r#try!(self.run(a))).unwrap();
-                let v_b = from_value::<bool>(r#try!(self.run(b))).unwrap();
+                let v_a = from_value::<bool>(self.run(a)?).unwrap();
+                let v_b = from_value::<bool>(self.run(b)?).unwrap();
                 Ok(match *op {
                     LogOp::And => to_value(v_a && v_b),
                     LogOp::Or => to_value(v_a || v_b),
@@ -332,41 +432,17 @@ impl Executor for Interpreter {
                 let func = self.run(callee)?;
                 let mut v_args = Vec::with_capacity(args.len());
                 for arg in args.iter() {
-                    v_args.push(r#try!(self.run(arg)));
-                }
-                let this = Gc::new(ValueData::Object(GcCell::new(HashMap::new())));
-                this.borrow()
-                    .set_field_slice(INSTANCE_PROTOTYPE, func.borrow().get_field_slice(PROTOTYPE));
-                match *func {
-                    ValueData::Function(ref func) => match func.clone().into_inner() {
-                        Function::NativeFunc(ref ntv) => {
-                            let func = ntv.data;
-                            println!("{:#?}", this);
-                            func(this, self.run(callee)?, v_args)
-                        }
-                        Function::RegularFunc(ref data) => {
-                            let scope = self.make_scope(this);
-                            let scope_vars_ptr = scope.vars.borrow();
-                            for i in 0..data.args.len() {
-                                let name = data.args.get(i).unwrap();
-                                let expr = v_args.get(i).unwrap();
-                                scope_vars_ptr.set_field(name.clone(), (*expr).clone());
-                            }
-                            let result = self.run(&data.expr);
-                            self.destroy_scope();
-                            result
-                        }
-                    },
-                    _ => Ok(Gc::new(ValueData::Undefined)),
+                    v_args.push(self.run(arg)?);
                 }
+                self.construct(&func, v_args)
             }
             ExprDef::ReturnExpr(ref ret) => match *ret {
                 Some(ref v) => self.run(v),
-                None => Ok(Gc::new(ValueData::Undefined)),
+                None => Ok(Value::undefined()),
             },
-            ExprDef::ThrowExpr(ref ex) => Err(r#try!(self.run(ex))),
+            ExprDef::ThrowExpr(ref ex) => Err(self.run(ex)?),
             ExprDef::AssignExpr(ref ref_e, ref val_e) => {
-                let val = r#try!(self.run(val_e));
+                let val = self.run(val_e)?;
                 match ref_e.def {
                     ExprDef::LocalExpr(ref name) => {
                         self.scope()
@@ -375,7 +451,7 @@ impl Executor for Interpreter {
                             .set_field(name.clone(), val.clone());
                     }
                     ExprDef::GetConstFieldExpr(ref obj, ref field) => {
-                        let val_obj = r#try!(self.run(obj));
+                        let val_obj = self.run(obj)?;
                         val_obj.borrow().set_field(field.clone(), val.clone());
                     }
                     _ => (),
@@ -388,21 +464,26 @@ impl Executor for Interpreter {
                 for var in vars.iter() {
                     let (name, value) = var.clone();
                     let val = match value {
-                        Some(v) => r#try!(self.run(&v)),
-                        None => Gc::new(ValueData::Null),
+                        Some(v) => self.run(&v)?,
+                        None => Value::null(),
                     };
                     scope_vars_ptr.set_field(name.clone(), val);
                 }
-                Ok(Gc::new(ValueData::Undefined))
+                Ok(Value::undefined())
             }
             ExprDef::TypeOfExpr(ref val_e) => {
-                let val = r#try!(self.run(val_e));
-                Ok(to_value(match *val {
+                let val = self.run(val_e)?;
+                Ok(to_value(match *val.data() {
                     ValueData::Undefined => "undefined",
-                    ValueData::Null | ValueData::Object(_) => "object",
+                    ValueData::Null
+                    | ValueData::Object(_)
+                    | ValueData::Bytes(_)
+                    | ValueData::Host(_)
+                    | ValueData::Slots(_) => "object",
                     ValueData::Boolean(_) => "boolean",
                     ValueData::Number(_) | ValueData::Integer(_) => "number",
                     ValueData::String(_) => "string",
+                    ValueData::BigInt(_) => "bigint",
                     ValueData::Function(_) => "function",
                 }))
             }
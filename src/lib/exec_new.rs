@@ -1,18 +1,185 @@
-use crate::js::value::{to_value, Value, ValueData};
-use crate::js::{array, console, function, json, math, object, string};
-use gc::Gc;
-use std::borrow::Borrow;
-use ratel::ast::Pattern;
+use crate::js::value::{allocated_bytes, to_value, NativeError, ResultValue, Value, ValueData};
+use crate::js::array::array_create;
+use crate::js::atom::{atom_table_size, sweep_atoms, Atom, ToAtom};
+use crate::js::class::Class;
+use crate::js::console::{ConsoleLevel, ConsoleSink, StdioSink};
+use crate::js::date::TimeZone;
+use crate::js::error::new_error;
+use crate::js::function::{
+    bound_target, make_arguments, new_function_value, Caller, Function, FunctionCode, NativeBody,
+    NewRegularFunction,
+};
+use crate::js::js_string::JsString;
+use crate::js::number::string_to_number;
+use crate::js::object::PROTOTYPE;
+use crate::js::serde_value::{self, Serializer};
+use crate::js::array_buffer::SharedBytes;
+use crate::js::typed_array::set_buffer_field;
+use crate::js::{array_buffer, object};
+use crate::gc::{collect, heap_stats, GcCell};
+use serde::Serialize;
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use ratel::ast::{Pattern, PatternList, PropertyKey, StatementList};
 use ratel::ast::operator::*;
-use ratel::ast::function::MandatoryName;
 
 extern crate ratel;
 use ratel::ast::expression::*;
 pub use ratel::ast::literal::Literal;
 use ratel::ast::Statement;
+use ratel::lexer::Token;
+use ratel::Module;
+use ratel_transformer::scope::{resolve, Resolution};
+
+pub mod bytecode;
+mod compiler;
+pub mod event_loop;
+pub mod host;
+pub mod jit;
+pub mod realm;
+pub mod snapshot;
+mod vm;
+
+use self::bytecode::Chunk;
+use self::compiler::{compile_expression, compile_function, compile_script, compile_statement};
+use self::jit::{FunctionProfile, FunctionStats};
+use self::host::Permissions;
+use self::realm::Realm;
+
+/// The variables of the scope a scope is nested in
+pub const OUTER_ENV: &str = "[[OuterEnv]]";
+/// Marks the variables of a block, like the parameter of a `catch` clause, which `var` declarations skip over
+const BLOCK_ENV: &str = "[[BlockEnv]]";
+/// The steps between measurements of the heap, when it's limited
+const HEAP_CHECK_INTERVAL: u64 = 4096;
+
+/// A parsed script, which keeps its source text alive for the functions it makes
+#[derive(Debug)]
+pub struct Script {
+    /// The syntax tree, which lives in an arena of its own holding a copy of the source text
+    module: Module<'static>,
+    /// What the identifiers in the syntax tree refer to, keyed by where their text is in it
+    resolution: Resolution,
+    /// The source text
+    source: Box<str>,
+    /// The name of the file the script came from, as stack traces show it
+    name: String,
+}
+
+impl Script {
+    /// Parse the source text of a script that didn't come from a file
+    pub fn parse(source: &str) -> Result<Rc<Script>, Value> {
+        Script::parse_with_name("<anonymous>", source)
+    }
+
+    /// Parse the source text of a script, naming the file it came from
+    pub fn parse_with_name(name: &str, source: &str) -> Result<Rc<Script>, Value> {
+        Script::parse_in_mode(name, source, false)
+    }
+
+    /// Parse the source text of a script, as strict mode code from the start if `strict` is set
+    fn parse_in_mode(name: &str, source: &str, strict: bool) -> Result<Rc<Script>, Value> {
+        let parsed = if strict {
+            ratel::parse_strict(source)
+        } else {
+            ratel::parse(source)
+        };
+        match parsed {
+            Ok(module) => {
+                // Resolving borrows the syntax tree only while it runs, and keeps nothing from it
+                let resolution = resolve(unsafe { &*(&module as *const Module<'static>) });
+                Ok(Rc::new(Script {
+                    module,
+                    resolution,
+                    source: source.into(),
+                    name: name.to_string(),
+                }))
+            }
+            Err(errors) => Err(to_value(match (errors[0].message, errors[0].token) {
+                (Some(message), _) => format!("{} at position {}", message, errors[0].start),
+                (None, Token::EndOfProgram) => "Unexpected end of input".to_string(),
+                (None, _) => format!(
+                    "Unexpected token {} at position {}",
+                    errors[0].raw, errors[0].start
+                ),
+            })),
+        }
+    }
+
+    /// The source text of the script
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The name of the file the script came from
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The line and column, both counted from one, of a position in the source text
+    pub fn line_and_column(&self, position: u32) -> (usize, usize) {
+        let before = &self.source[..(position as usize).min(self.source.len())];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+
+    /// The statements of the script
+    pub fn body(&self) -> StatementList<'static> {
+        self.module.body()
+    }
+
+    /// What the identifiers in the functions of the script refer to, where that's known before they run
+    fn resolution(&self) -> &Resolution {
+        &self.resolution
+    }
+}
+
+/// If a list of statements starts with a `"use strict"` directive
+fn has_use_strict(body: &StatementList) -> bool {
+    for statement in body {
+        match statement.item {
+            Statement::Expression(ref e) => match e.item {
+                // A directive is written without escapes, so its source text is compared
+                Expression::Literal(Literal::String(raw)) => {
+                    if &raw[1..raw.len() - 1] == "use strict" {
+                        return true;
+                    }
+                }
+                _ => return false,
+            },
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// The number of arguments a function expects, which is its parameters up to the first with a default or rest
+fn expected_argument_count(params: &PatternList) -> usize {
+    params
+        .iter()
+        .take_while(|param| matches!(param.item, Pattern::Identifier(_)))
+        .count()
+}
+
+/// The name a parameter binds, if it's not destructured
+fn param_name<'ast>(param: &Pattern<'ast>) -> Option<&'ast str> {
+    match *param {
+        Pattern::Identifier(name) => Some(name),
+        Pattern::AssignmentPattern { ref left, .. } => param_name(&left.item),
+        Pattern::RestElement { ref argument } => Some(argument.item),
+        _ => None,
+    }
+}
 
 /// A variable scope
-#[derive(Trace, Finalize, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Scope {
     /// The value of `this` in the scope
     pub this: Value,
@@ -24,248 +191,1136 @@ pub struct Scope {
 pub trait Executor {
     /// Make a new execution engine
     fn new() -> Self;
+    /// Make a new execution engine which runs scripts in a realm, like one restored from a snapshot
+    fn with_realm(realm: Realm) -> Self;
     /// Set a global variable called `name` with the value `val`
     fn set_global(&mut self, name: String, val: Value) -> Value;
     /// Resolve the global variable `name`
     fn get_global(&self, name: String) -> Value;
+    /// The realm scripts run in
+    fn realm(&self) -> Realm;
+    /// Run the scripts run from now on in a realm, returning the realm they ran in before
+    fn enter_realm(&mut self, realm: Realm) -> Realm;
+    /// Run a script in a realm, going back to the realm scripts ran in before once it's done
+    fn run_script_in(&mut self, realm: &Realm, script: &Rc<Script>) -> ResultValue {
+        let outer = self.enter_realm(realm.clone());
+        let result = self.run_script(script);
+        self.enter_realm(outer);
+        result
+    }
     /// Create a new scope and return it
     fn make_scope(&mut self, this: Value) -> Scope;
     /// Destroy the current scope
     fn destroy_scope(&mut self) -> Scope;
-    /// Run an expression
-    fn run(&mut self, expr: &Statement) -> ();
+    /// Set the local time zone `Date` uses, in place of the host's
+    fn set_time_zone(&mut self, tz: TimeZone);
+    /// Make an `ArrayBuffer` holding a copy of some bytes
+    fn copy_array_buffer(&self, bytes: &[u8]) -> Value {
+        self.share_array_buffer(Rc::new(RefCell::new(bytes.to_vec())))
+    }
+    /// Make an `ArrayBuffer` over bytes the host keeps a handle to, so it sees what the script writes and the other way round
+    fn share_array_buffer(&self, bytes: SharedBytes) -> Value {
+        array_buffer::new_array_buffer(&self.realm().intrinsic("ArrayBuffer"), bytes)
+    }
+    /// Allow or forbid `eval` and the `Function` constructor running strings as code, which they throw an `EvalError` for when forbidden
+    fn allow_code_generation(&mut self, allowed: bool);
+    /// Limit the steps a script run with `run_script` can take, or lift the limit with `None`
+    fn set_step_limit(&mut self, steps: Option<u64>);
+    /// Limit the calls that can be in progress at once, past which a call throws a `RangeError`
+    fn set_call_depth_limit(&mut self, depth: usize);
+    /// Limit the bytes of native stack a script can use, past which it throws a `RangeError`
+    fn set_stack_size_limit(&mut self, bytes: usize);
+    /// Limit the bytes the values a script can reach and the atom table take up, or lift the limit with `None`
+    fn set_heap_limit(&mut self, bytes: Option<usize>);
+    /// Set what the host functions installed with `host::install` are allowed to do, which is nothing until this is called
+    fn set_permissions(&mut self, permissions: Permissions);
+    /// Send the messages scripts write to the `console` to a sink, in place of the standard output and error streams
+    fn set_console_sink(&mut self, sink: Rc<dyn ConsoleSink>);
+    /// Get a handle another thread can stop the running script with
+    fn interrupt_handle(&self) -> InterruptHandle;
+    /// Why the last script run was stopped before it finished, if it was
+    fn termination(&self) -> Option<Termination>;
+    /// Make the constructor of a class of Rust objects, and set a global variable with its name to it
+    fn register_class<T: Any>(&mut self, class: Class<T>) -> Value;
+    /// Convert Rust data to a Javascript value, whose objects and arrays have the usual prototypes
+    fn to_js_value<T: Serialize + ?Sized>(&self, value: &T) -> Result<Value, serde_value::Error>;
+    /// Allow or forbid compiling hot functions to machine code, which only builds with the `jit` feature do
+    fn set_jit(&mut self, enabled: bool);
+    /// How often each script function that has been called was called, and how it was run
+    fn function_stats(&self) -> Vec<FunctionStats>;
+    /// Run a statement, returning its completion value
+    fn run(&mut self, stmt: &Statement) -> ResultValue;
+    /// Evaluate an expression
+    fn run_expr(&mut self, expr: &Expression) -> ResultValue;
+    /// Run a script, returning the completion value of its last statement
+    ///
+    /// Unlike statements given to `run`, a script can make functions, as it keeps their code alive
+    fn run_script(&mut self, script: &Rc<Script>) -> ResultValue;
+}
+
+/// What calling a function runs
+enum Callable {
+    /// A native function
+    Native(NativeBody),
+    /// The code of a script function, with the variables it was made in, the `this` it was made with
+    /// and the global object of its realm
+    Script(Rc<FunctionCode>, Value, Value, Value),
+}
+
+/// A call in progress, which a stack trace shows a line for
+struct Frame {
+    /// The name of the function called, or empty for the script itself
+    name: String,
+    /// The script the code running is part of
+    script: Rc<Script>,
+    /// Where in the script's source text the code running is up to
+    position: u32,
+}
+
+/// Why a script was stopped before it finished, which, unlike an error it throws, it can't catch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// Its interpreter's `InterruptHandle` was used
+    Interrupted,
+    /// It took more steps than the step limit allows
+    StepLimit,
+    /// The values it could reach took up more bytes than the heap limit allows
+    HeapLimit,
+    /// It called `process.exit` with an exit code
+    Exit(i32),
+}
 
-    fn run_expr(&mut self, expr: &Expression) -> Value;
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Termination::Interrupted => "Script execution was interrupted",
+            Termination::StepLimit => "Script took more steps than allowed",
+            Termination::HeapLimit => "Script used more memory than allowed",
+            Termination::Exit(code) => return write!(f, "Script exited with code {}", code),
+        })
+    }
+}
+
+/// A handle to an interpreter, which can be sent to another thread to stop the script running on it
+///
+/// If no script is running, the next one is stopped as soon as it starts
+#[derive(Debug, Clone)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    /// Stop the script running on the interpreter
+    pub fn interrupt(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Where the native stack is up to
+#[inline(always)]
+fn stack_position() -> usize {
+    let marker = 0u8;
+    &marker as *const u8 as usize
 }
 
 /// A Javascript intepreter
 pub struct Interpreter {
-    /// An object representing the global object
-    global: Value,
+    /// The realm the running code belongs to, whose global object and built-in objects it uses
+    realm: Realm,
     /// The scopes
     pub scopes: Vec<Scope>,
-    pub decls: std::collections::HashMap<String, Value> 
+    pub decls: std::collections::HashMap<String, Value>,
+    /// The script whose code is running, if any
+    script: Option<Rc<Script>>,
+    /// If the running code is strict mode code
+    strict: bool,
+    /// The calls in progress, outermost first
+    frames: Vec<Frame>,
+    /// If `eval` and the `Function` constructor can run strings as code
+    code_generation: bool,
+    /// The most steps a script run with `run_script` can take, if limited
+    step_limit: Option<u64>,
+    /// The steps the running script has taken
+    steps: u64,
+    /// The most calls that can be in progress at once
+    call_depth_limit: usize,
+    /// The most bytes of native stack a script can use
+    stack_size_limit: usize,
+    /// Where the native stack was when the running script started
+    stack_base: Option<usize>,
+    /// The most bytes the values a script can reach can take up, if limited
+    heap_limit: Option<usize>,
+    /// The bytes allocated on this thread when the heap was last measured
+    heap_measured_at: usize,
+    /// The steps the running script will have taken when the heap is next measured
+    heap_check_at: u64,
+    /// Set by an `InterruptHandle` to stop the running script
+    interrupt: Arc<AtomicBool>,
+    /// Why the running or last script was stopped, if it was
+    termination: Option<Termination>,
+    /// If hot functions are compiled to machine code, where that's supported
+    #[cfg_attr(
+        not(all(feature = "jit", target_arch = "x86_64", target_os = "linux")),
+        allow(dead_code)
+    )]
+    jit: bool,
+    /// The script functions that have been called, by name
+    profiled: Vec<(String, Weak<FunctionCode>)>,
+    /// Where the messages scripts write to the `console` go
+    console: Rc<dyn ConsoleSink>,
+    /// What the host functions are allowed to do
+    permissions: Permissions,
+    /// The local time zone `Date` uses
+    time_zone: Rc<TimeZone>,
+}
 
+/// Read the source text of a number literal, such as `0x1F` or `017`
+fn parse_number_literal(raw: &str) -> f64 {
+    let bytes = raw.as_bytes();
+    // Legacy octal literals are a `0` followed only by octal digits
+    if bytes.len() > 1 && bytes[0] == b'0' && bytes.iter().all(|b| b'0' <= *b && *b <= b'7') {
+        return bytes.iter().fold(0.0, |n, b| n * 8.0 + (b - b'0') as f64);
+    }
+    string_to_number(raw)
+}
+
+/// Read the hexadecimal digits of an escape sequence as a code point
+fn hex_escape(digits: &str) -> Option<u32> {
+    u32::from_str_radix(digits, 16)
+        .ok()
+        .filter(|&code| code <= 0x10FFFF)
+}
+
+/// Replace the escape sequences in the source text of a string with the characters they stand for
+///
+/// The string is built of UTF-16 code units, since a `\uD800` escape can stand for half a surrogate pair.
+fn unescape(raw: &str) -> JsString {
+    let mut result = Vec::with_capacity(raw.len());
+    let mut chars = raw.char_indices().peekable();
+    let push = |result: &mut Vec<u16>, c: char| result.extend_from_slice(c.encode_utf16(&mut [0; 2]));
+    while let Some((_, c)) = chars.next() {
+        if c != '\\' {
+            push(&mut result, c);
+            continue;
+        }
+        let (i, c) = match chars.next() {
+            Some(next) => next,
+            None => break,
+        };
+        match c {
+            'n' => push(&mut result, '\n'),
+            't' => push(&mut result, '\t'),
+            'r' => push(&mut result, '\r'),
+            'b' => push(&mut result, '\u{8}'),
+            'f' => push(&mut result, '\u{c}'),
+            'v' => push(&mut result, '\u{b}'),
+            '0' => push(&mut result, '\0'),
+            // A line continuation
+            '\r' | '\n' | '\u{2028}' | '\u{2029}' => {
+                if c == '\r' && chars.peek().map(|&(_, c)| c) == Some('\n') {
+                    chars.next();
+                }
+            }
+            'x' | 'u' => {
+                let rest = &raw[i + 1..];
+                let digits = if c == 'x' {
+                    rest.get(..2)
+                } else if rest.starts_with('{') {
+                    rest.find('}').map(|end| &rest[1..end])
+                } else {
+                    rest.get(..4)
+                };
+                match digits.and_then(hex_escape) {
+                    Some(code) => {
+                        let len = digits.unwrap().len() + if rest.starts_with('{') { 2 } else { 0 };
+                        for _ in 0..len {
+                            chars.next();
+                        }
+                        match std::char::from_u32(code) {
+                            Some(ch) => push(&mut result, ch),
+                            // A lone surrogate, which a string can hold though a `char` can't
+                            None => result.push(code as u16),
+                        }
+                    }
+                    None => push(&mut result, c),
+                }
+            }
+            _ => push(&mut result, c),
+        }
+    }
+    JsString::from_utf16(result)
+}
+
+/// The property name a property key in an object literal stands for
+fn property_key_name(key: &PropertyKey) -> Option<String> {
+    match *key {
+        PropertyKey::Literal(raw) if raw.starts_with('"') || raw.starts_with('\'') => {
+            Some(unescape(&raw[1..raw.len() - 1]).to_string())
+        }
+        PropertyKey::Literal(raw) if raw.starts_with(|c: char| c.is_ascii_digit() || c == '.') => {
+            Some(to_value(parse_number_literal(raw)).to_string())
+        }
+        PropertyKey::Literal(raw) => Some(raw.to_string()),
+        PropertyKey::Binary(raw) => Some(to_value(string_to_number(raw)).to_string()),
+        PropertyKey::Computed(_) => None,
+    }
+}
+
+/// Apply a binary operator, or the operator of a compound assignment, to two values
+fn binary_op(op: OperatorKind, a: &Value, b: &Value) -> Result<Value, NativeError> {
+    let (v_a, v_b) = (a.data().clone(), b.data().clone());
+    Ok(match op {
+        OperatorKind::Addition | OperatorKind::AddAssign => return v_a + v_b,
+        OperatorKind::Subtraction | OperatorKind::SubtractAssign => return v_a - v_b,
+        OperatorKind::Multiplication | OperatorKind::MultiplyAssign => return v_a * v_b,
+        OperatorKind::Division | OperatorKind::DivideAssign => return v_a / v_b,
+        OperatorKind::Remainder | OperatorKind::RemainderAssign => return v_a % v_b,
+        OperatorKind::Exponent | OperatorKind::ExponentAssign => return v_a.pow(&v_b),
+        OperatorKind::BitwiseAnd | OperatorKind::BitAndAssign => return v_a & v_b,
+        OperatorKind::BitwiseOr | OperatorKind::BitOrAssign => return v_a | v_b,
+        OperatorKind::BitwiseXor | OperatorKind::BitXorAssign => return v_a ^ v_b,
+        OperatorKind::BitShiftLeft | OperatorKind::BSLAssign => return v_a << v_b,
+        OperatorKind::BitShiftRight | OperatorKind::BSRAssign => return v_a >> v_b,
+        OperatorKind::UBitShiftRight | OperatorKind::UBSRAssign => return v_a.unsigned_shr(&v_b),
+        OperatorKind::Equality => to_value(*a.data() == *b.data()),
+        OperatorKind::Inequality => to_value(*a.data() != *b.data()),
+        OperatorKind::StrictEquality => to_value(a.strict_equals(b)),
+        OperatorKind::StrictInequality => to_value(!a.strict_equals(b)),
+        OperatorKind::Lesser => to_value(v_a.less_than(&v_b) == Some(true)),
+        OperatorKind::LesserEquals => to_value(v_b.less_than(&v_a) == Some(false)),
+        OperatorKind::Greater => to_value(v_b.less_than(&v_a) == Some(true)),
+        OperatorKind::GreaterEquals => to_value(v_a.less_than(&v_b) == Some(false)),
+        OperatorKind::In => match *b.data() {
+            ValueData::Object(_) | ValueData::Function(_) => {
+                to_value(b.get_prop(a.to_string()).is_some())
+            }
+            _ => {
+                return Err(NativeError::type_error(
+                    "Cannot use 'in' operator to search in a primitive",
+                ))
+            }
+        },
+        OperatorKind::Instanceof => {
+            let proto = match *b.data() {
+                ValueData::Function(_) => b.get_field_slice(PROTOTYPE),
+                _ => {
+                    return Err(NativeError::type_error(
+                        "Right-hand side of 'instanceof' is not callable",
+                    ))
+                }
+            };
+            let mut obj = a.get_prototype();
+            while obj.is_object() && !obj.strict_equals(&proto) {
+                obj = obj.get_prototype();
+            }
+            to_value(obj.is_object())
+        }
+        _ => {
+            return Err(NativeError::type_error(&format!(
+                "Unsupported operator {:?}",
+                op
+            )))
+        }
+    })
+}
+
+/// The scope around the variables of a scope
+fn outer_env(env: &Value) -> Value {
+    match *env.data() {
+        ValueData::Slots(ref slots) => slots.borrow()[0].clone(),
+        _ => env.get_internal_slot(OUTER_ENV),
+    }
+}
+
+/// The type of a value, as `typeof` gives it
+fn type_of(val: &Value) -> &'static str {
+    match *val.data() {
+        ValueData::Undefined => "undefined",
+        ValueData::Null
+        | ValueData::Object(_)
+        | ValueData::Bytes(_)
+        | ValueData::Host(_)
+        | ValueData::Slots(_) => "object",
+        ValueData::Boolean(_) => "boolean",
+        ValueData::Number(_) | ValueData::Integer(_) => "number",
+        ValueData::String(_) => "string",
+        ValueData::BigInt(_) => "bigint",
+        ValueData::Function(_) => "function",
+    }
 }
 
 impl Interpreter {
     #[inline(always)]
     /// Get the current scope
     pub fn scope(&self) -> &Scope {
-        self.scopes.get(self.scopes.len() - 1).unwrap()
+        self.scopes.last().unwrap()
+    }
+
+    /// Get a field of a value, reading the fields of a primitive from its wrapper's prototype,
+    /// and those a function doesn't have from `Function.prototype`
+    fn get_field<K: ToAtom>(&mut self, obj: &Value, field: K) -> ResultValue {
+        let wrapper = match *obj.data() {
+            ValueData::Number(_) | ValueData::Integer(_) => Some("Number"),
+            ValueData::Boolean(_) => Some("Boolean"),
+            ValueData::BigInt(_) => Some("BigInt"),
+            ValueData::Function(_) => Some("Function"),
+            _ => None,
+        };
+        let mut prop = obj.get_prop(&field);
+        if let (None, Some(wrapper)) = (&prop, wrapper) {
+            prop = self.realm.prototype(wrapper).get_prop(&field);
+        }
+        match prop {
+            // A getter is called with the value the property was read from as `this`
+            Some(ref prop) if prop.get.is_function() => self.call(&prop.get, obj.clone(), Vec::new()),
+            Some(prop) => Ok(prop.value),
+            None => Ok(Value::undefined()),
+        }
+    }
+
+    /// The variables of the scope the variable `name` is declared in, if any
+    fn env_of(&self, name: Atom) -> Option<Value> {
+        let mut env = self.scope().vars.clone();
+        loop {
+            match *env.data() {
+                // The variables in slots are only reached through them
+                ValueData::Slots(_) => (),
+                ValueData::Object(_) if env.get_prop(name).is_some() => return Some(env),
+                ValueData::Object(_) => (),
+                _ => return None,
+            }
+            env = outer_env(&env);
+        }
+    }
+
+    /// Make the variables of a scope nested in the current one
+    fn new_env(&self) -> Value {
+        let env = ValueData::new_obj(None);
+        env.set_internal_slot(OUTER_ENV, self.scope().vars.clone());
+        env
+    }
+
+    /// The variables `var` declarations in the running code declare, skipping those of the blocks it's in
+    fn var_env(&self) -> Value {
+        let mut env = self.scope().vars.clone();
+        while env.has_internal_slot(BLOCK_ENV) {
+            env = env.get_internal_slot(OUTER_ENV);
+        }
+        env
+    }
+
+    /// Make an array out of some values
+    fn new_array(&self, items: Vec<Value>) -> Value {
+        let len = items.len();
+        let arr = array_create(self.realm.prototype("Array"));
+        for (i, item) in items.into_iter().enumerate() {
+            arr.set_field(i, item);
+        }
+        arr.set_field_slice("length", to_value(len as i32));
+        arr
+    }
+
+    /// Make an error of one of the native kinds, like `TypeError`, for the interpreter to throw
+    fn error(&self, kind: &str, message: String) -> Value {
+        let prototype = self.realm.prototype(kind);
+        new_error(prototype, to_value(message), self)
+    }
+
+    /// Stop the running script, for good
+    fn terminate(&mut self, termination: Termination) -> Value {
+        self.termination = Some(termination);
+        to_value(termination.to_string())
+    }
+
+    /// Count a step of the running script, stopping it if it's been interrupted or gone over a limit
+    fn step(&mut self) -> Result<(), Value> {
+        self.advance(1)
+    }
+
+    /// Count some steps of the running script at once, as machine code does, checking what `step` does
+    fn advance(&mut self, steps: u64) -> Result<(), Value> {
+        if let Some(termination) = self.termination {
+            return Err(to_value(termination.to_string()));
+        }
+        if self.interrupt.swap(false, Ordering::SeqCst) {
+            return Err(self.terminate(Termination::Interrupted));
+        }
+        self.steps += steps;
+        if self.step_limit.is_some_and(|limit| self.steps > limit) {
+            return Err(self.terminate(Termination::StepLimit));
+        }
+        if let Some(limit) = self.heap_limit {
+            // The heap is measured by collecting it, every so often and as soon as a lot has been
+            // allocated since it last was. The bigger it is the longer it's left between
+            // measurements, so they don't take longer in all than running the script
+            let allocated = allocated_bytes().wrapping_sub(self.heap_measured_at);
+            if self.steps >= self.heap_check_at || allocated > limit / 4 {
+                collect();
+                let stats = heap_stats();
+                self.heap_measured_at = allocated_bytes();
+                self.heap_check_at = self.steps + HEAP_CHECK_INTERVAL.max(stats.objects as u64);
+                // The names objects have been keyed by take up memory too, until they're swept
+                if stats.bytes + atom_table_size() > limit {
+                    return Err(self.terminate(Termination::HeapLimit));
+                }
+            }
+        }
+        if let Some(base) = self.stack_base {
+            let here = stack_position();
+            let used = base.abs_diff(here);
+            if used > self.stack_size_limit {
+                return Err(self.error("RangeError", "Maximum call stack size exceeded".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Record where the innermost call is up to, for stack traces
+    fn set_position(&mut self, position: u32) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.position = position;
+        }
+    }
+
+    /// Run some code as part of a script, or of no script when it's `None`, with or without strict mode
+    fn in_script<T>(
+        &mut self,
+        script: Option<Rc<Script>>,
+        strict: bool,
+        run: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        let outer_script = std::mem::replace(&mut self.script, script);
+        let outer_strict = std::mem::replace(&mut self.strict, strict);
+        let result = run(self);
+        self.script = outer_script;
+        self.strict = outer_strict;
+        result
+    }
+
+    /// Make a function out of a function or arrow function written in the running script
+    fn make_function<'ast>(
+        &mut self,
+        name: &str,
+        params: PatternList<'ast>,
+        body: ArrowBody<'ast>,
+        loc: (u32, u32),
+        arrow: bool,
+        env: Value,
+    ) -> ResultValue {
+        let script = match self.script {
+            Some(ref script) => script.clone(),
+            None => return Err(to_value("Functions can only be made by scripts run with run_script")),
+        };
+        let strict = self.strict
+            || match body {
+                ArrowBody::Block(ref b) => has_use_strict(&b.body),
+                ArrowBody::Expression(_) => false,
+            };
+        // Only the running script's code is run while it's set, and the function keeps it alive
+        let (params, body) = unsafe {
+            (
+                std::mem::transmute::<PatternList<'ast>, PatternList<'static>>(params),
+                std::mem::transmute::<ArrowBody<'ast>, ArrowBody<'static>>(body),
+            )
+        };
+        let code = Rc::new(FunctionCode {
+            script,
+            params,
+            body,
+            start: loc.0,
+            end: loc.1,
+            arrow,
+            strict,
+            chunk: RefCell::new(None),
+            profile: FunctionProfile::default(),
+        });
+        let this = if arrow {
+            self.scope().this.clone()
+        } else {
+            Value::undefined()
+        };
+        let func = new_function_value(Function::NewRegularFunc(NewRegularFunction::new(
+            code,
+            env,
+            this,
+            self.realm.global().clone(),
+        )));
+        func.set_prototype(self.realm.prototype("Function"));
+        func.set_prop_slice("name", object::Property::read_only(to_value(name)));
+        let length = expected_argument_count(&params) as i32;
+        func.set_prop_slice("length", object::Property::read_only(to_value(length)));
+        if !arrow {
+            let prototype = self.realm.new_object();
+            prototype.set_field_slice("constructor", func.clone());
+            func.set_field_slice(PROTOTYPE, prototype);
+        }
+        Ok(func)
+    }
+
+    /// Call a script function
+    fn call_code(
+        &mut self,
+        func: &Value,
+        code: Rc<FunctionCode>,
+        outer: Value,
+        this: Value,
+        args: Vec<Value>,
+    ) -> ResultValue {
+        if self.frames.len() >= self.call_depth_limit {
+            return Err(self.error("RangeError", "Maximum call stack size exceeded".to_string()));
+        }
+        let cached = code.chunk.borrow().clone();
+        let chunk = match cached {
+            Some(chunk) => chunk,
+            None => {
+                let chunk = Rc::new(compile_function(
+                    &code.params,
+                    &code.body,
+                    code.script.resolution(),
+                ));
+                *code.chunk.borrow_mut() = Some(chunk.clone());
+                let name = func.get_field_slice("name").to_string();
+                self.profiled.push((name, Rc::downgrade(&code)));
+                chunk
+            }
+        };
+        // A function whose variables are all in slots only needs a scope of its own for those closures share
+        let env = if !chunk.resolved {
+            let env = ValueData::new_obj(None);
+            env.set_internal_slot(OUTER_ENV, outer);
+            env
+        } else if chunk.captured.is_empty() {
+            outer
+        } else {
+            let mut slots = vec![outer];
+            slots.resize(chunk.captured.len() + 1, Value::undefined());
+            Value::new(ValueData::Slots(GcCell::new(slots)))
+        };
+        // Sloppy mode functions called without a `this` get the global object instead
+        let this = if code.strict || !this.is_null_or_undefined() {
+            this
+        } else {
+            self.realm.global().clone()
+        };
+        self.scopes.push(Scope {
+            this,
+            vars: env.clone(),
+        });
+        self.frames.push(Frame {
+            name: func.get_field_slice("name").to_string(),
+            script: code.script.clone(),
+            position: code.start,
+        });
+        let result = self.in_script(Some(code.script.clone()), code.strict, |engine| {
+            engine.run_function_body(func, &code, &chunk, &env, args)
+        });
+        self.frames.pop();
+        self.scopes.pop();
+        result
+    }
+
+    /// Bind the parameters and `arguments` of a call to a script function, then run its body
+    fn run_function_body(
+        &mut self,
+        func: &Value,
+        code: &FunctionCode,
+        chunk: &Chunk,
+        env: &Value,
+        args: Vec<Value>,
+    ) -> ResultValue {
+        let mut names = Vec::new();
+        for param in &code.params {
+            match param_name(&param.item) {
+                Some(name) => names.push(name.to_string()),
+                None => return Err(to_value("Destructuring is not supported yet")),
+            }
+        }
+        // The variables of a function whose body resolved them all to slots can't be reached through `arguments`
+        if !code.arrow && !chunk.resolved && !names.iter().any(|name| name == "arguments") {
+            // Only sloppy mode functions with plain parameters have their parameters and arguments linked
+            let mapping = if code.strict || !code.has_simple_params() {
+                None
+            } else {
+                Some((&names[..], env))
+            };
+            let arguments = make_arguments(self.realm.global(), func, &args, mapping);
+            env.set_field_slice("arguments", arguments);
+        }
+        self.run_function_chunk(code, chunk, &args)
+    }
+
+    /// Run a list of statements at the top level of a script or eval code, returning the completion value of the last
+    fn run_body(&mut self, body: &StatementList) -> ResultValue {
+        self.run_chunk(&compile_script(body), &[])
+    }
+
+    /// The error to throw when running strings as code is forbidden
+    fn check_code_generation(&self) -> Result<(), Value> {
+        if self.code_generation {
+            Ok(())
+        } else {
+            let message = "Code generation from strings disallowed for this context".to_string();
+            Err(self.error("EvalError", message))
+        }
+    }
+
+    /// Run the source text given to `eval`, in the scope of the call for a direct eval, or the global scope otherwise
+    /// [[SPEC] - PerformEval](https://tc39.es/ecma262/#sec-performeval)
+    fn run_eval(&mut self, source: &str, direct: bool) -> ResultValue {
+        self.check_code_generation()?;
+        let strict = direct && self.strict;
+        let script = match Script::parse_in_mode("<anonymous>", source, strict) {
+            Ok(script) => script,
+            Err(message) => return Err(self.error("SyntaxError", message.to_string())),
+        };
+        let body = script.body();
+        let strict = strict || has_use_strict(&body);
+        let mut scope = if direct {
+            self.scope().clone()
+        } else {
+            Scope {
+                this: self.realm.global().clone(),
+                vars: self.realm.global().clone(),
+            }
+        };
+        // Strict mode eval code declares its variables in a scope of its own, where
+        // sloppy mode eval code declares them in the scope it's run in
+        if strict {
+            let env = ValueData::new_obj(None);
+            env.set_internal_slot(OUTER_ENV, scope.vars.clone());
+            scope.vars = env;
+        }
+        self.scopes.push(scope);
+        self.frames.push(Frame {
+            name: "eval".to_string(),
+            script: script.clone(),
+            position: 0,
+        });
+        let result = self.in_script(Some(script.clone()), strict, |engine| engine.run_body(&body));
+        self.frames.pop();
+        self.scopes.pop();
+        result
+    }
+
+    /// Get the value of a variable, or throw if it isn't declared
+    fn get_binding(&self, name: Atom) -> ResultValue {
+        match self.env_of(name) {
+            Some(env) => Ok(env.get_field(name)),
+            None => Err(self.error("ReferenceError", format!("{} is not defined", name))),
+        }
+    }
+
+    /// Assign to a variable, creating a global if it isn't declared, which strict mode code throws for instead
+    fn put_binding(&mut self, name: Atom, val: Value) -> ResultValue {
+        let vars = match self.env_of(name) {
+            Some(env) => env,
+            None if self.strict => {
+                return Err(self.error("ReferenceError", format!("{} is not defined", name)))
+            }
+            None => self.realm.global().clone(),
+        };
+        self.write_property(&vars, name, val)
+    }
+
+    /// The atom of a property key a script computed, or a `RangeError` if the atom table is full
+    fn property_key(&self, key: &Value) -> Result<Atom, Value> {
+        key.try_to_atom()
+            .ok_or_else(|| self.error("RangeError", "Too many property names".to_string()))
+    }
+
+    /// Throw a `TypeError` for reading or setting a property of `null` or `undefined`, which have no properties
+    fn check_has_properties(&self, obj: &Value, field: &dyn fmt::Display, setting: bool) -> Result<(), Value> {
+        if !obj.is_null_or_undefined() {
+            return Ok(());
+        }
+        let (verb, noun) = if setting { ("set", "setting") } else { ("read", "reading") };
+        let message = format!("Cannot {} properties of {} ({} '{}')", verb, obj, noun, field);
+        Err(self.error("TypeError", message))
+    }
+
+    /// Assign to a property of a value, or throw if the value can't be stored in the element of a typed array it is
+    fn put_property(&mut self, obj: Value, field: Atom, val: Value) -> ResultValue {
+        match *obj.data() {
+            ValueData::Object(ref data) => {
+                if let Some(result) = set_buffer_field(&data.borrow(), field, &val) {
+                    return result.map(|_| val).map_err(|e| e.into_error(self));
+                }
+            }
+            ValueData::Function(_) => (),
+            // Primitives have no properties of their own to write to
+            _ if self.strict => {
+                let message = format!("Cannot create property '{}' on {} '{}'", field, obj.get_type(), obj);
+                return Err(self.error("TypeError", message));
+            }
+            _ => return Ok(val),
+        }
+        self.write_property(&obj, field, val)
+    }
+
+    /// Assign to a property of an object, unless it's read-only, which strict mode code throws for
+    ///
+    /// Assigning to an accessor property calls its setter instead, with the object as `this`
+    fn write_property(&mut self, obj: &Value, field: Atom, val: Value) -> ResultValue {
+        match obj.get_prop(field) {
+            Some(ref prop) if prop.set.is_function() => {
+                self.call(&prop.set, obj.clone(), vec![val.clone()])?;
+                Ok(val)
+            }
+            Some(ref prop) if prop.is_accessor() => {
+                if self.strict {
+                    let message = format!("Cannot set property {} of {} which has only a getter", field, obj.get_type());
+                    Err(self.error("TypeError", message))
+                } else {
+                    Ok(val)
+                }
+            }
+            Some(ref prop) if !prop.writable => {
+                if self.strict {
+                    let message = format!("Cannot assign to read only property '{}' of {}", field, obj.get_type());
+                    Err(self.error("TypeError", message))
+                } else {
+                    Ok(val)
+                }
+            }
+            _ => Ok(obj.set_field(field, val)),
+        }
+    }
+
+    /// Run a call with the stack limit counting from where it starts, if nothing is running yet
+    fn with_stack_base<T>(&mut self, run: impl FnOnce(&mut Self) -> T) -> T {
+        // Calls from the host, like the event loop's callbacks, get the limit as `run_script` does
+        let outermost = self.stack_base.is_none();
+        if outermost {
+            self.stack_base = Some(stack_position());
+        }
+        let result = run(self);
+        if outermost {
+            self.stack_base = None;
+        }
+        result
+    }
+
+    /// Call a function, as `call` does once the stack base is set
+    fn call_value(&mut self, func: &Value, this: Value, args: Vec<Value>) -> ResultValue {
+        let callable = match *func.data() {
+            ValueData::Function(ref inner) => match *inner.borrow() {
+                Function::NativeFunc(ref ntv) => Callable::Native(ntv.data.clone()),
+                Function::NewRegularFunc(ref f) => {
+                    let this = if f.code.arrow { f.this.clone() } else { this.clone() };
+                    Callable::Script(f.code.clone(), f.env.clone(), this, f.realm.clone())
+                }
+                Function::RegularFunc(_) => {
+                    return Err(to_value("Functions made by the old interpreter can't be called here"))
+                }
+            },
+            _ => {
+                let message = format!("{} is not a function", func.get_type());
+                return Err(self.error("TypeError", message));
+            }
+        };
+        match callable {
+            Callable::Native(data) => data.call(this, func.clone(), args, self),
+            Callable::Script(code, env, this, global) => {
+                // A function from another realm runs in that realm, and what it makes gets that realm's prototypes
+                if global.same_heap_cell(self.realm.global()) {
+                    return self.call_code(func, code, env, this, args);
+                }
+                let realm = Realm::from_global(&global).expect("functions are made in a realm");
+                let outer = std::mem::replace(&mut self.realm, realm);
+                let result = self.call_code(func, code, env, this, args);
+                self.realm = outer;
+                result
+            }
+        }
+    }
+
+    /// Construct an object with a function, as `construct` does once the stack base is set
+    fn construct_value(&mut self, func: &Value, args: Vec<Value>) -> ResultValue {
+        if let Some((target, mut bound_args)) = bound_target(func) {
+            bound_args.extend(args);
+            return self.construct_value(&target, bound_args);
+        }
+        let (is_constructor, is_script) = match *func.data() {
+            ValueData::Function(ref inner) => match *inner.borrow() {
+                Function::NewRegularFunc(ref f) => (!f.code.arrow, true),
+                _ => (true, false),
+            },
+            _ => (false, false),
+        };
+        if !is_constructor {
+            let name = match *func.get_field_slice("name").data() {
+                ValueData::String(ref name) if !name.is_empty() => name.to_string(),
+                _ => func.get_type().to_string(),
+            };
+            return Err(self.error("TypeError", format!("{} is not a constructor", name)));
+        }
+        let prototype = func.get_field_slice(PROTOTYPE);
+        let this = if prototype.is_object() {
+            ValueData::new_obj_from_prototype(prototype)
+        } else {
+            self.realm.new_object()
+        };
+        let result = self.call_value(func, this.clone(), args)?;
+        // A script function's own object is the result, unless it returns another object
+        if is_script && !result.is_object() && !result.is_function() {
+            Ok(this)
+        } else {
+            Ok(result)
+        }
+    }
+}
+
+impl Caller for Interpreter {
+    fn call(&mut self, func: &Value, this: Value, args: Vec<Value>) -> ResultValue {
+        self.with_stack_base(|engine| engine.call_value(func, this, args))
+    }
+
+    fn construct(&mut self, func: &Value, args: Vec<Value>) -> ResultValue {
+        self.with_stack_base(|engine| engine.construct_value(func, args))
+    }
+
+    fn stack_trace(&self) -> String {
+        let lines: Vec<String> = self
+            .frames
+            .iter()
+            .rev()
+            .map(|frame| {
+                let (line, column) = frame.script.line_and_column(frame.position);
+                let place = format!("{}:{}:{}", frame.script.name(), line, column);
+                if frame.name.is_empty() {
+                    format!("    at {}", place)
+                } else {
+                    format!("    at {} ({})", frame.name, place)
+                }
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    fn eval_code(&mut self, source: &str) -> ResultValue {
+        self.run_eval(source, false)
+    }
+
+    fn new_error(&self, kind: &str, message: String) -> Value {
+        self.error(kind, message)
+    }
+
+    fn console_write(&self, level: ConsoleLevel, message: &str) {
+        self.console.write(level, message)
+    }
+
+    fn permissions(&self) -> Option<&Permissions> {
+        Some(&self.permissions)
+    }
+
+    fn prototype(&self, constructor: &str) -> Value {
+        self.realm.prototype(constructor)
+    }
+
+    fn time_zone(&self) -> Rc<TimeZone> {
+        self.time_zone.clone()
+    }
+
+    fn exit(&mut self, code: i32) -> Value {
+        self.terminate(Termination::Exit(code))
+    }
+
+    fn compile_function(&mut self, params: &str, body: &str) -> ResultValue {
+        self.check_code_generation()?;
+        let source = format!("(function anonymous({}\n) {{\n{}\n}})", params, body);
+        let script = match Script::parse(&source) {
+            Ok(script) => script,
+            Err(message) => return Err(self.error("SyntaxError", message.to_string())),
+        };
+        let statements: Vec<_> = script.body().iter().collect();
+        // The parameters and body are only valid if they make up a single function, and don't end it early
+        let node = match statements[..] {
+            [statement] => match statement.item {
+                Statement::Expression(ref e) => *e,
+                _ => return Err(self.error("SyntaxError", "Invalid function source".to_string())),
+            },
+            _ => return Err(self.error("SyntaxError", "Invalid function source".to_string())),
+        };
+        let f = match node.item {
+            Expression::Function(ref f) if node.end as usize + 1 == source.len() => f,
+            _ => return Err(self.error("SyntaxError", "Invalid function source".to_string())),
+        };
+        if f.generator {
+            return Err(to_value("Generators are not supported yet"));
+        }
+        let global = self.realm.global().clone();
+        let loc = (node.start, node.end);
+        self.in_script(Some(script.clone()), false, |engine| {
+            engine.make_function("anonymous", f.params, ArrowBody::Block(f.body), loc, false, global)
+        })
     }
 }
 
 impl Executor for Interpreter {
     fn new() -> Interpreter {
-        let global = ValueData::new_obj(None);
-        object::init(global.clone());
-        console::init(global.clone());
-        math::init(global.clone());
-        array::init(global.clone());
-        function::init(global.clone());
-        json::init(global.clone());
-        string::init(global.clone());
+        Interpreter::with_realm(Realm::new())
+    }
+
+    fn with_realm(realm: Realm) -> Interpreter {
+        let global = realm.global().clone();
         Interpreter {
-            global: global.clone(),
-            decls: std::collections::HashMap::new().clone(),
+            code_generation: true,
+            step_limit: None,
+            steps: 0,
+            call_depth_limit: 10_000,
+            stack_size_limit: 1 << 20,
+            stack_base: None,
+            heap_limit: None,
+            heap_measured_at: 0,
+            heap_check_at: 0,
+            interrupt: Arc::new(AtomicBool::new(false)),
+            termination: None,
+            jit: true,
+            profiled: Vec::new(),
+            console: Rc::new(StdioSink),
+            permissions: Permissions::new(),
+            time_zone: Rc::new(TimeZone::System),
+            realm,
+            decls: std::collections::HashMap::new(),
             scopes: vec![Scope {
                 this: global.clone(),
                 vars: global.clone()
             }],
+            script: None,
+            strict: false,
+            frames: Vec::new(),
         }
     }
 
     fn set_global(&mut self, name: String, val: Value) -> Value {
-        self.global.borrow().set_field(name, val)
+        self.realm.global().set_field(name, val)
     }
 
     fn get_global(&self, name: String) -> Value {
-        self.global.borrow().get_field(name)
+        self.realm.global().get_field(name)
     }
 
-    fn make_scope(&mut self, this: Value) -> Scope {
-        let scope = Scope {
-            this: this,
-            vars: ValueData::new_obj(None)
+    fn realm(&self) -> Realm {
+        self.realm.clone()
+    }
+
+    fn enter_realm(&mut self, realm: Realm) -> Realm {
+        // The outermost scope is the global scope of the realm
+        self.scopes[0] = Scope {
+            this: realm.global().clone(),
+            vars: realm.global().clone(),
         };
-        self.scopes.push(scope.clone());
-        scope
+        std::mem::replace(&mut self.realm, realm)
     }
 
-    fn destroy_scope(&mut self) -> Scope {
-        self.scopes.pop().unwrap()
+    fn set_time_zone(&mut self, tz: TimeZone) {
+        self.time_zone = Rc::new(tz);
     }
 
-    fn run_expr(&mut self, expr: &Expression) -> Value {
-        match expr {
-            Expression::Literal(i) => match i {
-                Literal::Number(num) => to_value(num.parse::<f64>().ok()),
-                Literal::Binary(num) => to_value(num.parse::<f64>().ok()),
-                Literal::String(str_val) => to_value(format!("{}", (str_val.replace("\"", "")))),
-                Literal::RegEx(_regex) => to_value(None::<()>),
-                Literal::False => to_value(false),
-                Literal::True => to_value(true),
-                Literal::Null => to_value(None::<()>),
-                Literal::Undefined => Gc::new(ValueData::Undefined),
-            },
-            Expression::Call(_e) => {
-                // let args = e.arguments;
-                // let name = self.run_expr(&e.callee.item);
-                to_value(3)
-            },
-            Expression::Identifier(id) => {let scope_vars = self.scope().vars.borrow(); scope_vars.get_field(id.to_string())},
-            Expression::Function(_e) => {to_value(3)},
-            Expression::Binary(e) => {
-                match e.operator {
-                    OperatorKind::Addition => {
-                        let left  =  self.run_expr(&e.left.item);
-                        let right =  self.run_expr(&e.right.item);
-
-                        if left.is_double() && right.is_double() {
-                            to_value(left.to_num() + right.to_num())
-                        } 
-                        else if left.is_string() && right.is_double() {
-                            let owned_string = format!("{}{}", left, right.to_num());
-                            to_value(format!("{}", owned_string))
-                        }
-                        else {
-                            Gc::new(ValueData::Undefined)
-                        }
-                    },
-                    OperatorKind::Subtraction => {
-                        let left  =  self.run_expr(&e.left.item);
-                        let right =  self.run_expr(&e.right.item);
-
-                        if left.is_double() && right.is_double() {
-                            to_value(left.to_num() - right.to_num())
-                        } 
-                        else {
-                            Gc::new(ValueData::Undefined)
-                        }
-                    },
-                    OperatorKind::Division => {
-                        let left  =  self.run_expr(&e.left.item);
-                        let right =  self.run_expr(&e.right.item);
-
-                        if left.is_double() && right.is_double() {
-                            if right.to_num() == 0.0 {
-                                panic!("Error! Division By Zero!");
-                            }
-                            to_value(left.to_num() / right.to_num())
-                        } 
-                        else {
-                            Gc::new(ValueData::Undefined)
-                        }
-                    },
-                    OperatorKind::Multiplication => {
-                        let left  =  self.run_expr(&e.left.item);
-                        let right =  self.run_expr(&e.right.item);
-
-                        if left.is_double() && right.is_double() {
-                            to_value(left.to_num() * right.to_num())
-                        } 
-                        else {
-                            Gc::new(ValueData::Undefined)
-                        }
-                    }
-                    _ => panic!("unhandled case"),    
-                }
-            },
-            _ => panic!("unhandled case"),
-        }
-    }
-
-    fn run(&mut self, stmt: &Statement) -> () {
-        match stmt {
-            Statement::Expression(e) => println!("{}", self.run_expr(&e.item)),
-            Statement::Declaration(v) => {
-                let decl = v.declarators;
-                let _kind = v.kind;
-                let scope_vars = self.scope().vars.clone();
-                let scope_vars_ptr = scope_vars.borrow();
-                 for i in &decl {
-                    let id = i.item.id.item;
-                    let init = i.item.init;
-                    let val = match init {
-                         Some(l) => self.run_expr(&l.item),
-                         _ => panic!("unhandled case"),
-                    };
-
-                    let varname = match id { 
-                        Pattern::Identifier(name) => name,
-                         _ => panic!("wrong var name"),
-                     };
-                     
-                     scope_vars_ptr.set_field(varname.to_string(), val.clone());
-                     self.set_global(varname.to_string(), val.clone());
-                     &self.decls.insert(varname.to_string(), val.clone());
-                 }
-            },
-            Statement::Block(b) => {
-                let body = b.body;
-                 for i in &body {
-                     self.run(&i.item);
-                 }
-                //  body.last().unwrap().clone();
-            },
-            Statement::If(exp) => {
-                let _cond = self.run_expr(&exp.test.item);
-                let _cons = exp.consequent;
-                let _alternate = exp.alternate;
+    fn allow_code_generation(&mut self, allowed: bool) {
+        self.code_generation = allowed;
+    }
 
-                if _cond.is_true() {
-                    self.run(&_cons.item);
-                }
-                else {
-                    match _alternate {
-                        Some(e) => self.run(&e.item),
-                        _ => (),
-                    }
-                }
-            },
-            Statement::While(exp) => {
-                let _cond = self.run_expr(&exp.test.item);
-                let _cons = exp.body;
-                while _cond.is_true() {
-                    self.run(&_cons.item);
-                }
-                println!("{:?}",exp);
-            },
-            Statement::Function(e) => {
-                let _params    = e.params;
-                let _body      = e.body.item.body;
+    fn set_step_limit(&mut self, steps: Option<u64>) {
+        self.step_limit = steps;
+    }
 
-                let _func_name = match e.name {
-                    MandatoryName(name) => name.item,
-                };
+    fn set_call_depth_limit(&mut self, depth: usize) {
+        self.call_depth_limit = depth;
+    }
 
-                // self.run(&_body.item.body);
-                let mut v = Vec::new();
-                for i in &_body {
-                    self.run(&i);
-                    v.push(i);
-                }
+    fn set_stack_size_limit(&mut self, bytes: usize) {
+        self.stack_size_limit = bytes;
+    }
 
-                let mut memory = std::collections::HashMap::new();
-                // scope_vars.set_field(_func_name.to_string(), v);
+    fn set_heap_limit(&mut self, bytes: Option<usize>) {
+        self.heap_limit = bytes;
+    }
 
-                memory.insert(
-                    _func_name.to_string(),
-                    _body,
-                );
+    fn set_permissions(&mut self, permissions: Permissions) {
+        self.permissions = permissions;
+    }
 
+    fn set_console_sink(&mut self, sink: Rc<dyn ConsoleSink>) {
+        self.console = sink;
+    }
 
+    fn interrupt_handle(&self) -> InterruptHandle {
+        InterruptHandle(self.interrupt.clone())
+    }
 
-                
-                // let val = crate::js::function::NewRegularFunction::new(Statement::Function(e), new Vec<string)();
-                // self.global
-                //     .borrow()
-                //     .set_field(_func_name.to_owned().clone(), val.clone());
-            },
-            Statement::Empty => (),
-            _ => panic!("unhandled case"),
+    fn termination(&self) -> Option<Termination> {
+        self.termination
+    }
+
+    fn register_class<T: Any>(&mut self, class: Class<T>) -> Value {
+        let name = class.name().to_string();
+        let ctor = class.build(self.realm.global());
+        self.realm.global().set_field(name, ctor.clone());
+        ctor
+    }
+
+    fn to_js_value<T: Serialize + ?Sized>(&self, value: &T) -> Result<Value, serde_value::Error> {
+        value.serialize(Serializer::new(Some(self.realm.global().clone())))
+    }
+
+    fn set_jit(&mut self, enabled: bool) {
+        self.jit = enabled;
+    }
+
+    fn function_stats(&self) -> Vec<FunctionStats> {
+        self.profiled
+            .iter()
+            .filter_map(|(name, code)| code.upgrade().map(|code| FunctionStats::new(name, &code)))
+            .collect()
+    }
+
+    fn make_scope(&mut self, this: Value) -> Scope {
+        let scope = Scope {
+            this,
+            vars: self.new_env(),
         };
-        ()
+        self.scopes.push(scope.clone());
+        scope
+    }
+
+    fn destroy_scope(&mut self) -> Scope {
+        self.scopes.pop().unwrap()
+    }
+
+    fn run_expr(&mut self, expr: &Expression) -> ResultValue {
+        let chunk = compile_expression(expr);
+        self.in_script(None, false, |engine| engine.run_chunk(&chunk, &[]))
+    }
+
+    fn run(&mut self, stmt: &Statement) -> ResultValue {
+        let chunk = compile_statement(stmt);
+        self.in_script(None, false, |engine| engine.run_chunk(&chunk, &[]))
+    }
+
+    fn run_script(&mut self, script: &Rc<Script>) -> ResultValue {
+        let body = script.body();
+        let strict = has_use_strict(&body);
+        // The limits count from the start of the outermost script running
+        let outermost = self.stack_base.is_none();
+        if outermost {
+            self.stack_base = Some(stack_position());
+            self.steps = 0;
+            self.heap_measured_at = allocated_bytes();
+            self.heap_check_at = HEAP_CHECK_INTERVAL;
+            self.termination = None;
+        }
+        self.frames.push(Frame {
+            name: String::new(),
+            script: script.clone(),
+            position: 0,
+        });
+        let result = self.in_script(Some(script.clone()), strict, |engine| engine.run_body(&body));
+        self.frames.pop();
+        if outermost {
+            self.stack_base = None;
+        }
+        if self.frames.is_empty() {
+            sweep_atoms();
+        }
+        result
     }
 }
@@ -0,0 +1,240 @@
+//! The instructions scripts and functions are compiled to, and a disassembler to read them with
+
+use crate::exec_new::compiler::{compile_function, compile_script};
+use crate::exec_new::Script;
+use crate::js::atom::Atom;
+use crate::js::value::ValueData;
+use ratel::ast::expression::ArrowBody;
+use ratel::ast::operator::OperatorKind;
+use ratel::ast::PatternList;
+use std::fmt::Write;
+
+/// An instruction, which works on a stack of values
+///
+/// The operands are indices into the pools of the chunk the instruction is part of,
+/// local slots, counts, or the indices of the instructions jumps go to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    /// Push `undefined`
+    Undefined,
+    /// Push a constant
+    Const(u32),
+    /// Push the value of `this`
+    This,
+    /// Push an argument of the call, or `undefined` if there aren't that many
+    Arg(u32),
+    /// Push an array of the arguments of the call from the one given on
+    RestArgs(u32),
+    /// Pop a value
+    Pop,
+    /// Push the value on top again
+    Dup,
+    /// Push the two values on top again
+    Dup2,
+    /// Move the value on top under the two below it
+    Rot3,
+    /// Move the value on top under the three below it
+    Rot4,
+    /// Push the value of a local slot
+    GetLocal(u32),
+    /// Store the value on top in a local slot, leaving it on top
+    SetLocal(u32),
+    /// Push the value of a slot closures share, of the call that many calls with such slots out from the running one
+    GetCaptured(u32, u32),
+    /// Store the value on top in a slot closures share, as `GetCaptured` finds it, leaving it on top
+    SetCaptured(u32, u32),
+    /// Push the value of a variable, found by name in the scope chain
+    GetName(u32),
+    /// Store the value on top in a variable found by name, leaving it on top
+    SetName(u32),
+    /// Push the type of a variable found by name, which is `"undefined"` if it isn't declared
+    TypeofName(u32),
+    /// Pop a value and store it in a variable `var` declares
+    DeclareVar(u32),
+    /// Declare a variable as `var` does, leaving it alone if it's declared already
+    DeclareVarUndefined(u32),
+    /// Pop a function and store it in a variable, as a function declaration does
+    DeclareFunction(u32),
+    /// Pop a value and store it in a variable of the innermost scope, as a parameter
+    SetParam(u32),
+    /// Pop an object and push one of its properties
+    GetField(u32),
+    /// Pop a property key and an object and push the property
+    GetComputed,
+    /// Pop a value and an object, store the value in a property of the object and push it
+    SetField(u32),
+    /// Pop a value, a property key and an object, store the value in the property and push it
+    SetComputed,
+    /// Pop an object, delete one of its properties and push `true`
+    DeleteField(u32),
+    /// Pop a property key and an object, delete the property and push `true`
+    DeleteComputed,
+    /// Convert the value on top to a property key
+    ToPropertyKey,
+    /// Push a new object
+    NewObject,
+    /// Pop a value and give the object on top a property holding it
+    DefineField(u32),
+    /// Pop a value and a property key and give the object on top a property holding it
+    DefineComputed,
+    /// Name the function on top after the property key below it
+    NameFunction,
+    /// Push a new array
+    NewArray,
+    /// Pop a value and store it at an index of the array on top
+    ArraySet(u32),
+    /// Set the length of the array on top
+    ArrayLength(u32),
+    /// Make a function and push it
+    MakeFunction(u32),
+    /// Pop two values and push the result of a binary operator
+    Binary(OperatorKind),
+    /// Replace the value on top with the result of `!`
+    Not,
+    /// Replace the value on top with the result of `~`
+    BitNot,
+    /// Replace the value on top with the result of unary `+`
+    Plus,
+    /// Replace the value on top with the result of unary `-`
+    Neg,
+    /// Replace the value on top with its type
+    Typeof,
+    /// Convert the value on top to a number, unless it's a `BigInt`
+    ToNumeric,
+    /// Add one to the value on top
+    Inc,
+    /// Subtract one from the value on top
+    Dec,
+    /// Pop some values and push the concatenation of their string values
+    Concat(u32),
+    /// Jump to an instruction
+    Jump(u32),
+    /// Pop a value and jump if it's falsy
+    JumpIfFalse(u32),
+    /// Jump if the value on top is falsy, or pop it otherwise
+    JumpIfFalseOrPop(u32),
+    /// Jump if the value on top is truthy, or pop it otherwise
+    JumpIfTrueOrPop(u32),
+    /// Jump if the value on top isn't `undefined`
+    JumpIfNotUndefined(u32),
+    /// Pop some arguments, a function and a value for `this`, and push the result of calling it
+    Call(u32),
+    /// Like `Call`, but a call to the original `eval` runs the code in the scope of the call
+    CallEval(u32),
+    /// Pop some arguments and a function, and push the result of calling it as a constructor
+    New(u32),
+    /// Pop a value and throw it
+    Throw,
+    /// Pop a value and return it
+    Return,
+    /// Catch what the instructions up to the matching `LeaveTry` throw, jumping to an instruction with it on top
+    EnterTry(u32),
+    /// Stop catching what's thrown, as the matching `EnterTry` did
+    LeaveTry,
+    /// Pop a value and bind it to the parameter of a `catch` clause, in a scope of its own
+    EnterCatch(u32),
+    /// Leave the scope of a `catch` clause
+    LeaveCatch,
+    /// Throw a constant, for code the interpreter doesn't support
+    Fail(u32),
+}
+
+/// A function written in the compiled code, which `MakeFunction` makes a function value out of
+#[derive(Debug)]
+pub struct FunctionTemplate {
+    /// The name of the function
+    pub name: String,
+    /// If the function is a named function expression, whose name is a variable in a scope of its own
+    pub own_name: bool,
+    /// The parameters
+    pub params: PatternList<'static>,
+    /// The body
+    pub body: ArrowBody<'static>,
+    /// Where the function starts and ends in the source text
+    pub loc: (u32, u32),
+    /// If this is an arrow function
+    pub arrow: bool,
+}
+
+/// Compiled code: a script, the code given to `eval`, or the body of a function
+#[derive(Debug, Default)]
+pub struct Chunk {
+    /// The instructions
+    pub code: Vec<Op>,
+    /// Where in the source text each instruction comes from
+    pub positions: Vec<u32>,
+    /// The constants instructions push, which are all primitives
+    pub constants: Vec<ValueData>,
+    /// The names of variables and properties instructions use
+    pub names: Vec<Atom>,
+    /// The functions instructions make
+    pub functions: Vec<FunctionTemplate>,
+    /// The names of the local slots, whose number is the number of slots
+    pub locals: Vec<String>,
+    /// The names of the slots of a function call that closures made in it share
+    pub captured: Vec<String>,
+    /// If the variables of a function body are all in slots, so it has no use for an `arguments` object
+    pub resolved: bool,
+}
+
+impl Chunk {
+    /// Describe an instruction, with the name or constant its operand stands for
+    fn describe(&self, op: Op) -> String {
+        match op {
+            Op::Const(i) | Op::Fail(i) => format!("{:?}\t; {}", op, self.constants[i as usize]),
+            Op::GetLocal(i) | Op::SetLocal(i) => format!("{:?}\t; {}", op, self.locals[i as usize]),
+            // Only the slots of the call itself have their names in the chunk
+            Op::GetCaptured(0, i) | Op::SetCaptured(0, i) if !self.captured.is_empty() => {
+                format!("{:?}\t; {}", op, self.captured[i as usize])
+            }
+            Op::GetName(i)
+            | Op::SetName(i)
+            | Op::TypeofName(i)
+            | Op::DeclareVar(i)
+            | Op::DeclareVarUndefined(i)
+            | Op::DeclareFunction(i)
+            | Op::SetParam(i)
+            | Op::GetField(i)
+            | Op::SetField(i)
+            | Op::DeleteField(i)
+            | Op::DefineField(i)
+            | Op::EnterCatch(i) => format!("{:?}\t; {}", op, self.names[i as usize]),
+            Op::MakeFunction(i) => format!("{:?}\t; {}", op, self.functions[i as usize].name),
+            _ => format!("{:?}", op),
+        }
+    }
+
+    /// Write out the instructions, with the line and column each comes from
+    fn disassemble(&self, title: &str, script: &Script, out: &mut String) {
+        writeln!(out, "== {} ==", title).unwrap();
+        for (pc, op) in self.code.iter().enumerate() {
+            let (line, column) = script.line_and_column(self.positions[pc]);
+            writeln!(
+                out,
+                "{:04} {:>4}:{:<4} {}",
+                pc,
+                line,
+                column,
+                self.describe(*op)
+            )
+            .unwrap();
+        }
+        for function in &self.functions {
+            let chunk = compile_function(&function.params, &function.body, script.resolution());
+            let name = if function.name.is_empty() {
+                "<anonymous>"
+            } else {
+                &function.name
+            };
+            out.push('\n');
+            chunk.disassemble(name, script, out);
+        }
+    }
+}
+
+/// Disassemble a script, and the functions written in it
+pub fn disassemble(script: &Script) -> String {
+    let mut out = String::new();
+    compile_script(&script.body()).disassemble(script.name(), script, &mut out);
+    out
+}
@@ -0,0 +1,1033 @@
+//! Compiles the syntax tree of a script or function to bytecode
+
+use crate::exec_new::bytecode::{Chunk, FunctionTemplate, Op};
+use crate::exec_new::{param_name, parse_number_literal, property_key_name, unescape};
+use crate::js::atom::Atom;
+use crate::js::bigint::parse_bigint_literal;
+use crate::js::number::string_to_number;
+use crate::js::value::{to_value, Value, ValueData};
+use ratel::ast::expression::*;
+use ratel::ast::operator::OperatorKind;
+use ratel::ast::statement::{CatchClause, TryStatement};
+use ratel::ast::{
+    BlockNode, ExpressionNode, Literal, Pattern, PatternList, Property, PropertyKey, Statement,
+    StatementList, StatementNode,
+};
+use ratel_transformer::scope::{Binding, Resolution};
+use std::collections::HashMap;
+
+/// A statement the code being compiled is inside of, which a `return` has to leave first
+#[derive(Clone, Copy)]
+enum Context<'ast> {
+    /// A `try` block, or a `catch` clause with a `finally` block after it
+    Try,
+    /// A `catch` clause, which has a scope of its own
+    Catch,
+    /// A `try` statement with a `finally` block, which runs on the way out
+    Finally(BlockNode<'ast, Statement<'ast>>),
+}
+
+/// Compiles statements and expressions into a chunk
+struct Compiler<'ast, 'r> {
+    /// The chunk being compiled
+    chunk: Chunk,
+    /// What the identifiers in the body of a function whose variables are all in slots refer to
+    resolution: Option<&'r Resolution>,
+    /// The slot the completion value is kept in, for scripts and eval code
+    completion: Option<u32>,
+    /// If function declarations are made before the code runs, which they are in a script or function
+    hoisted: bool,
+    /// The statements the code being compiled is inside of, innermost last
+    contexts: Vec<Context<'ast>>,
+    /// Where the code being compiled is in the source text
+    position: u32,
+    /// The index of each name in the chunk's names
+    name_indices: HashMap<Atom, u32>,
+}
+
+/// Compile a script or the code given to `eval`, whose completion value it returns
+pub fn compile_script<'ast>(body: &StatementList<'ast>) -> Chunk {
+    let mut compiler = Compiler::new(true);
+    compiler.hoist(body);
+    for statement in body {
+        compiler.statement(statement, true);
+    }
+    compiler.finish_with_completion()
+}
+
+/// Compile a statement run on its own, whose completion value it returns, and which can't declare functions
+pub fn compile_statement<'ast>(statement: &Statement<'ast>) -> Chunk {
+    let mut compiler = Compiler::new(false);
+    compiler.completion = Some(compiler.local("<completion>"));
+    compiler.statement_item(statement, true);
+    compiler.finish_with_completion()
+}
+
+/// Compile an expression evaluated on its own, which it returns the value of
+pub fn compile_expression<'ast>(expr: &Expression<'ast>) -> Chunk {
+    let mut compiler = Compiler::new(false);
+    compiler.plain_expression(expr, 0);
+    compiler.emit(Op::Return);
+    compiler.chunk
+}
+
+/// Compile the body of a function, including binding its parameters to the arguments it's called with
+pub fn compile_function<'ast>(
+    params: &PatternList<'ast>,
+    body: &ArrowBody<'ast>,
+    resolution: &Resolution,
+) -> Chunk {
+    let mut compiler = Compiler::new(true);
+    let body_start = match *body {
+        ArrowBody::Expression(ref e) => e.start,
+        ArrowBody::Block(ref block) => block.start,
+    };
+    // Functions whose variables aren't all in slots look every variable up by name
+    if let Some(layout) = resolution.layout(body_start) {
+        compiler.resolution = Some(resolution);
+        compiler.chunk.locals = layout.locals.clone();
+        compiler.chunk.captured = layout.captured.clone();
+        compiler.chunk.resolved = true;
+    }
+    for (i, param) in params.iter().enumerate() {
+        compiler.position = param.start;
+        match param.item {
+            Pattern::Identifier(name) => {
+                compiler.emit(Op::Arg(i as u32));
+                compiler.bind_param(name);
+            }
+            Pattern::AssignmentPattern {
+                ref left,
+                ref right,
+            } => {
+                let name = param_name(&left.item).unwrap_or("");
+                compiler.emit(Op::Arg(i as u32));
+                let skip = compiler.jump(Op::JumpIfNotUndefined(0));
+                compiler.emit(Op::Pop);
+                compiler.expression(right, name);
+                compiler.patch(skip);
+                compiler.bind_param(name);
+            }
+            Pattern::RestElement { ref argument } => {
+                compiler.emit(Op::RestArgs(i as u32));
+                compiler.bind_param(argument.item);
+            }
+            // The interpreter throws for destructured parameters before the body runs
+            _ => (),
+        }
+    }
+    match *body {
+        ArrowBody::Expression(ref e) => {
+            compiler.expression(e, "");
+            compiler.emit(Op::Return);
+        }
+        ArrowBody::Block(ref block) => {
+            compiler.hoist(&block.body);
+            for statement in &block.body {
+                compiler.statement(statement, false);
+            }
+            compiler.emit(Op::Undefined);
+            compiler.emit(Op::Return);
+        }
+    }
+    compiler.chunk
+}
+
+/// Collect the names `var` declarations declare in a list of statements, and the blocks in it
+fn declared_vars<'ast>(body: &StatementList<'ast>, vars: &mut Vec<&'ast str>) {
+    for statement in body {
+        statement_vars(statement, vars);
+    }
+}
+
+/// Collect the names `var` declarations declare in a statement, and the blocks in it
+fn statement_vars<'ast>(statement: &StatementNode<'ast>, vars: &mut Vec<&'ast str>) {
+    match statement.item {
+        Statement::Declaration(ref d) => {
+            for declarator in &d.declarators {
+                if let Pattern::Identifier(name) = declarator.item.id.item {
+                    if !vars.contains(&name) {
+                        vars.push(name);
+                    }
+                }
+            }
+        }
+        Statement::Block(ref b) => declared_vars(&b.body, vars),
+        Statement::If(ref s) => {
+            statement_vars(&s.consequent, vars);
+            if let Some(ref alternate) = s.alternate {
+                statement_vars(alternate, vars);
+            }
+        }
+        Statement::While(ref s) => statement_vars(&s.body, vars),
+        Statement::Try(ref s) => {
+            declared_vars(&s.block.body, vars);
+            if let Some(ref handler) = s.handler {
+                declared_vars(&handler.body.body, vars);
+            }
+            if let Some(ref finalizer) = s.finalizer {
+                declared_vars(&finalizer.body, vars);
+            }
+        }
+        _ => (),
+    }
+}
+
+impl<'ast, 'r> Compiler<'ast, 'r> {
+    /// Make a compiler, for code whose function declarations are hoisted or not
+    fn new(hoisted: bool) -> Compiler<'ast, 'r> {
+        Compiler {
+            chunk: Chunk::default(),
+            resolution: None,
+            completion: None,
+            hoisted,
+            contexts: Vec::new(),
+            position: 0,
+            name_indices: HashMap::new(),
+        }
+    }
+
+    /// Return the completion value at the end of the code
+    fn finish_with_completion(mut self) -> Chunk {
+        let completion = self.completion();
+        self.emit(Op::GetLocal(completion));
+        self.emit(Op::Return);
+        self.chunk
+    }
+
+    /// Add an instruction, from where the code being compiled is in the source text
+    fn emit(&mut self, op: Op) {
+        let position = self.position;
+        self.emit_at(op, position);
+    }
+
+    /// Add an instruction, from a given place in the source text
+    fn emit_at(&mut self, op: Op, position: u32) {
+        self.chunk.code.push(op);
+        self.chunk.positions.push(position);
+    }
+
+    /// Add a jump, whose target is filled in by `patch`
+    fn jump(&mut self, op: Op) -> usize {
+        self.emit(op);
+        self.chunk.code.len() - 1
+    }
+
+    /// Point a jump at the next instruction added
+    fn patch(&mut self, at: usize) {
+        let target = self.chunk.code.len() as u32;
+        self.chunk.code[at] = match self.chunk.code[at] {
+            Op::Jump(_) => Op::Jump(target),
+            Op::JumpIfFalse(_) => Op::JumpIfFalse(target),
+            Op::JumpIfFalseOrPop(_) => Op::JumpIfFalseOrPop(target),
+            Op::JumpIfTrueOrPop(_) => Op::JumpIfTrueOrPop(target),
+            Op::JumpIfNotUndefined(_) => Op::JumpIfNotUndefined(target),
+            Op::EnterTry(_) => Op::EnterTry(target),
+            op => unreachable!("{:?} isn't a jump", op),
+        };
+    }
+
+    /// Add a constant
+    fn constant(&mut self, val: Value) -> u32 {
+        self.chunk.constants.push(val.data().clone());
+        (self.chunk.constants.len() - 1) as u32
+    }
+
+    /// Push a constant
+    fn push_constant(&mut self, val: Value) {
+        let index = self.constant(val);
+        self.emit(Op::Const(index));
+    }
+
+    /// The index of a name
+    fn name(&mut self, name: &str) -> u32 {
+        let atom = Atom::new(name);
+        if let Some(&index) = self.name_indices.get(&atom) {
+            return index;
+        }
+        let index = self.chunk.names.len() as u32;
+        self.chunk.names.push(atom);
+        self.name_indices.insert(atom, index);
+        index
+    }
+
+    /// Add a local slot
+    fn local(&mut self, name: &str) -> u32 {
+        self.chunk.locals.push(name.to_string());
+        (self.chunk.locals.len() - 1) as u32
+    }
+
+    /// The slot an identifier refers to, if it's in one
+    fn binding(&self, name: &'ast str) -> Option<Binding> {
+        self.resolution
+            .and_then(|resolution| resolution.binding(name))
+    }
+
+    /// Push the value of a slot
+    fn load(&mut self, binding: Binding, position: u32) {
+        match binding {
+            Binding::Local(slot) => self.emit_at(Op::GetLocal(slot), position),
+            Binding::Captured { depth, index } => {
+                self.emit_at(Op::GetCaptured(depth, index), position)
+            }
+        }
+    }
+
+    /// Store the value on top in a slot, leaving it on top
+    fn store(&mut self, binding: Binding) {
+        match binding {
+            Binding::Local(slot) => self.emit(Op::SetLocal(slot)),
+            Binding::Captured { depth, index } => self.emit(Op::SetCaptured(depth, index)),
+        }
+    }
+
+    /// Pop a value and bind a parameter to it
+    fn bind_param(&mut self, name: &'ast str) {
+        match self.binding(name) {
+            Some(binding) => {
+                self.store(binding);
+                self.emit(Op::Pop);
+            }
+            None => {
+                let name = self.name(name);
+                self.emit(Op::SetParam(name));
+            }
+        }
+    }
+
+    /// The slot of the completion value
+    fn completion(&mut self) -> u32 {
+        match self.completion {
+            Some(slot) => slot,
+            None => {
+                let slot = self.local("<completion>");
+                self.completion = Some(slot);
+                slot
+            }
+        }
+    }
+
+    /// Throw an error for code the interpreter doesn't support
+    fn fail(&mut self, message: &str) {
+        let index = self.constant(to_value(message));
+        self.emit(Op::Fail(index));
+    }
+
+    /// Declare the variables and make the functions a list of statements declares, before it runs
+    fn hoist(&mut self, body: &StatementList<'ast>) {
+        let mut vars = Vec::new();
+        declared_vars(body, &mut vars);
+        for var in vars {
+            if self.binding(var).is_none() {
+                let name = self.name(var);
+                self.emit(Op::DeclareVarUndefined(name));
+            }
+        }
+        for statement in body {
+            self.hoist_function(statement);
+        }
+    }
+
+    /// Make the functions a statement declares, or the blocks in it do
+    fn hoist_function(&mut self, statement: &StatementNode<'ast>) {
+        self.position = statement.start;
+        match statement.item {
+            Statement::Function(ref f) => {
+                if f.generator {
+                    return self.fail("Generators are not supported yet");
+                }
+                let name = f.name.0.item;
+                let loc = (statement.start, statement.end);
+                self.function(name, false, f.params, ArrowBody::Block(f.body), loc, false);
+                match self.binding(name) {
+                    Some(binding) => {
+                        self.store(binding);
+                        self.emit(Op::Pop);
+                    }
+                    None => {
+                        let name = self.name(name);
+                        self.emit(Op::DeclareFunction(name));
+                    }
+                }
+            }
+            Statement::Block(ref b) => {
+                for statement in &b.body {
+                    self.hoist_function(statement);
+                }
+            }
+            Statement::If(ref s) => {
+                self.hoist_function(&s.consequent);
+                if let Some(ref alternate) = s.alternate {
+                    self.hoist_function(alternate);
+                }
+            }
+            Statement::While(ref s) => self.hoist_function(&s.body),
+            Statement::Try(ref s) => {
+                for statement in &s.block.body {
+                    self.hoist_function(statement);
+                }
+                if let Some(ref handler) = s.handler {
+                    for statement in &handler.body.body {
+                        self.hoist_function(statement);
+                    }
+                }
+                if let Some(ref finalizer) = s.finalizer {
+                    for statement in &finalizer.body {
+                        self.hoist_function(statement);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Make a function, which keeps hold of the syntax tree of its parameters and body
+    fn function(
+        &mut self,
+        name: &str,
+        own_name: bool,
+        params: PatternList<'ast>,
+        body: ArrowBody<'ast>,
+        loc: (u32, u32),
+        arrow: bool,
+    ) {
+        // Chunks are only run while the script they're compiled from is alive, and functions made
+        // from them keep it alive
+        let (params, body) = unsafe {
+            (
+                std::mem::transmute::<PatternList<'ast>, PatternList<'static>>(params),
+                std::mem::transmute::<ArrowBody<'ast>, ArrowBody<'static>>(body),
+            )
+        };
+        self.chunk.functions.push(FunctionTemplate {
+            name: name.to_string(),
+            own_name,
+            params,
+            body,
+            loc,
+            arrow,
+        });
+        let index = (self.chunk.functions.len() - 1) as u32;
+        self.emit(Op::MakeFunction(index));
+    }
+
+    /// Store the value on top as the completion value, or drop it
+    fn complete(&mut self, completion: bool) {
+        if completion {
+            let slot = self.completion();
+            self.emit(Op::SetLocal(slot));
+        }
+        self.emit(Op::Pop);
+    }
+
+    /// Reset the completion value to `undefined`, as statements that complete with no value of their own do
+    fn complete_undefined(&mut self, completion: bool) {
+        if completion {
+            self.emit(Op::Undefined);
+            self.complete(true);
+        }
+    }
+
+    /// Compile a statement, keeping its completion value if `completion` is set
+    fn statement(&mut self, node: &StatementNode<'ast>, completion: bool) {
+        let outer = self.position;
+        self.position = node.start;
+        self.statement_item(&node.item, completion);
+        self.position = outer;
+    }
+
+    /// Compile a statement, from where the code being compiled is in the source text
+    fn statement_item(&mut self, statement: &Statement<'ast>, completion: bool) {
+        match *statement {
+            Statement::Expression(ref e) => {
+                self.expression(e, "");
+                self.complete(completion);
+            }
+            Statement::Declaration(ref d) => {
+                for declarator in &d.declarators {
+                    let name = match declarator.item.id.item {
+                        Pattern::Identifier(name) => name,
+                        _ => return self.fail("Destructuring is not supported yet"),
+                    };
+                    match (declarator.item.init, self.binding(name)) {
+                        (Some(ref init), Some(binding)) => {
+                            self.expression(init, name);
+                            self.store(binding);
+                            self.emit(Op::Pop);
+                        }
+                        (Some(ref init), None) => {
+                            self.expression(init, name);
+                            let name = self.name(name);
+                            self.emit(Op::DeclareVar(name));
+                        }
+                        (None, Some(_)) => (),
+                        (None, None) => {
+                            let name = self.name(name);
+                            self.emit(Op::DeclareVarUndefined(name));
+                        }
+                    }
+                }
+            }
+            Statement::Block(ref b) => self.block(&b.body, completion),
+            Statement::If(ref s) => {
+                self.expression(&s.test, "");
+                let otherwise = self.jump(Op::JumpIfFalse(0));
+                self.statement(&s.consequent, completion);
+                let end = self.jump(Op::Jump(0));
+                self.patch(otherwise);
+                match s.alternate {
+                    Some(ref alternate) => self.statement(alternate, completion),
+                    None => self.complete_undefined(completion),
+                }
+                self.patch(end);
+            }
+            Statement::While(ref s) => {
+                self.complete_undefined(completion);
+                let top = self.chunk.code.len() as u32;
+                self.expression(&s.test, "");
+                let end = self.jump(Op::JumpIfFalse(0));
+                self.statement(&s.body, completion);
+                self.emit(Op::Jump(top));
+                self.patch(end);
+            }
+            Statement::Throw(ref t) => {
+                self.expression(&t.value, "");
+                self.emit(Op::Throw);
+            }
+            Statement::Try(ref t) => self.try_statement(t, completion),
+            Statement::Return(ref r) => {
+                match r.value {
+                    Some(ref val) => self.expression(val, ""),
+                    None => self.emit(Op::Undefined),
+                }
+                self.unwind();
+                self.emit(Op::Return);
+            }
+            // Function declarations are made before the code around them runs
+            Statement::Function(_) if self.hoisted => (),
+            Statement::Function(_) => {
+                self.fail("Functions can only be made by scripts run with run_script")
+            }
+            Statement::Empty => (),
+            _ => self.fail("Unsupported statement"),
+        }
+    }
+
+    /// Compile the statements of a block, whose completion value starts out `undefined`
+    fn block(&mut self, body: &StatementList<'ast>, completion: bool) {
+        self.complete_undefined(completion);
+        for statement in body {
+            self.statement(statement, completion);
+        }
+    }
+
+    /// Compile a `try` statement
+    fn try_statement(&mut self, t: &TryStatement<'ast>, completion: bool) {
+        if let Some(finalizer) = t.finalizer {
+            self.contexts.push(Context::Finally(finalizer));
+        }
+        let enter = self.jump(Op::EnterTry(0));
+        self.contexts.push(Context::Try);
+        self.block(&t.block.body, completion);
+        self.contexts.pop();
+        self.emit(Op::LeaveTry);
+        let mut ends = vec![self.jump(Op::Jump(0))];
+        // What the `try` block throws arrives on top of the stack, at the `catch` clause if there is one
+        self.patch(enter);
+        if let Some(handler) = t.handler {
+            let enter_finally = match t.finalizer {
+                Some(_) => {
+                    let enter = self.jump(Op::EnterTry(0));
+                    self.contexts.push(Context::Try);
+                    Some(enter)
+                }
+                None => None,
+            };
+            self.catch_clause(&handler.item, completion);
+            if let Some(enter) = enter_finally {
+                self.contexts.pop();
+                self.emit(Op::LeaveTry);
+                ends.push(self.jump(Op::Jump(0)));
+                self.patch(enter);
+            } else {
+                ends.push(self.jump(Op::Jump(0)));
+            }
+        }
+        if let Some(finalizer) = t.finalizer {
+            self.contexts.pop();
+            // Run the `finally` block then throw again what was thrown
+            self.block(&finalizer.body, false);
+            self.emit(Op::Throw);
+            for end in ends {
+                self.patch(end);
+            }
+            self.block(&finalizer.body, false);
+        } else {
+            for end in ends {
+                self.patch(end);
+            }
+        }
+    }
+
+    /// Compile a `catch` clause, which finds what was thrown on top of the stack
+    fn catch_clause(&mut self, handler: &CatchClause<'ast>, completion: bool) {
+        let name = match handler.param.item {
+            Pattern::Identifier(name) => name,
+            _ => {
+                self.emit(Op::Pop);
+                return self.fail("Destructuring is not supported yet");
+            }
+        };
+        // A parameter in a slot needs no scope of its own
+        if let Some(binding) = self.binding(name) {
+            self.store(binding);
+            self.emit(Op::Pop);
+            return self.block(&handler.body.body, completion);
+        }
+        let name = self.name(name);
+        self.emit(Op::EnterCatch(name));
+        self.contexts.push(Context::Catch);
+        self.block(&handler.body.body, completion);
+        self.contexts.pop();
+        self.emit(Op::LeaveCatch);
+    }
+
+    /// Leave the statements the code being compiled is inside of, running their `finally` blocks, before a `return`
+    fn unwind(&mut self) {
+        for i in (0..self.contexts.len()).rev() {
+            match self.contexts[i] {
+                Context::Try => self.emit(Op::LeaveTry),
+                Context::Catch => self.emit(Op::LeaveCatch),
+                Context::Finally(finalizer) => {
+                    // A `return` in the `finally` block only has the statements outside it to leave
+                    let inner = self.contexts.split_off(i);
+                    self.block(&finalizer.body, false);
+                    self.contexts.extend(inner);
+                }
+            }
+        }
+    }
+
+    /// Push the value of a variable
+    fn get_variable(&mut self, name: &'ast str, position: u32) {
+        match self.binding(name) {
+            Some(binding) => self.load(binding, position),
+            None => {
+                let name = self.name(name);
+                self.emit_at(Op::GetName(name), position);
+            }
+        }
+    }
+
+    /// Store the value on top in a variable, leaving it on top
+    fn set_variable(&mut self, name: &'ast str) {
+        match self.binding(name) {
+            Some(binding) => self.store(binding),
+            None => {
+                let name = self.name(name);
+                self.emit(Op::SetName(name));
+            }
+        }
+    }
+
+    /// Compile an expression, naming the anonymous function it makes, if it does
+    fn expression(&mut self, node: &ExpressionNode<'ast>, name: &str) {
+        // What comes after the expression is from where the code around it is
+        let outer = self.position;
+        self.position = node.start;
+        let loc = (node.start, node.end);
+        match node.item {
+            Expression::Function(ref f) if f.generator => {
+                self.fail("Generators are not supported yet")
+            }
+            Expression::Function(ref f) => {
+                let body = ArrowBody::Block(f.body);
+                match f.name.0 {
+                    Some(own_name) => {
+                        self.function(own_name.item, true, f.params, body, loc, false)
+                    }
+                    None => self.function(name, false, f.params, body, loc, false),
+                }
+            }
+            Expression::Arrow(ref f) => self.function(name, false, f.params, f.body, loc, true),
+            ref expr => self.plain_expression(expr, node.start),
+        }
+        self.position = outer;
+    }
+
+    /// If an expression makes an anonymous function, which is named after what it's assigned to
+    fn is_anonymous_function(node: &ExpressionNode<'ast>) -> bool {
+        match node.item {
+            Expression::Function(ref f) => f.name.0.is_none(),
+            Expression::Arrow(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Compile an expression that doesn't make a function, which starts at `start` in the source text
+    fn plain_expression(&mut self, expr: &Expression<'ast>, start: u32) {
+        self.position = start;
+        match *expr {
+            Expression::Void => self.emit(Op::Undefined),
+            Expression::This(_) => self.emit(Op::This),
+            Expression::Literal(literal) => match literal {
+                Literal::Number(num) => self.push_constant(to_value(parse_number_literal(num))),
+                Literal::Binary(num) => self.push_constant(to_value(string_to_number(num))),
+                Literal::BigInt(num) => match parse_bigint_literal(num) {
+                    Some(n) => self.push_constant(to_value(n)),
+                    None => self.fail(&format!("Invalid BigInt literal {}", num)),
+                },
+                Literal::String(raw) => self.push_constant(Value::new(ValueData::String(
+                    unescape(&raw[1..raw.len() - 1]),
+                ))),
+                Literal::RegEx(_) | Literal::Null => self.push_constant(to_value(None::<()>)),
+                Literal::False => self.push_constant(to_value(false)),
+                Literal::True => self.push_constant(to_value(true)),
+                Literal::Undefined => self.emit(Op::Undefined),
+            },
+            Expression::Identifier(id) => self.get_variable(id, start),
+            Expression::Sequence(ref e) => {
+                if e.body.is_empty() {
+                    self.emit(Op::Undefined);
+                }
+                for (i, item) in e.body.iter().enumerate() {
+                    if i > 0 {
+                        self.emit(Op::Pop);
+                    }
+                    self.expression(item, "");
+                }
+            }
+            Expression::Array(ref e) => {
+                self.emit(Op::NewArray);
+                let mut index = 0;
+                for item in &e.body {
+                    // Holes are left out of the array, but still count towards its length
+                    if item.item != Expression::Void {
+                        self.expression(item, "");
+                        self.emit(Op::ArraySet(index));
+                    }
+                    index += 1;
+                }
+                self.emit(Op::ArrayLength(index));
+            }
+            Expression::Object(ref e) => {
+                self.emit(Op::NewObject);
+                for property in &e.body {
+                    self.position = property.start;
+                    match property.item {
+                        Property::Shorthand(name) => {
+                            self.get_variable(name, property.start);
+                            let name = self.name(name);
+                            self.emit(Op::DefineField(name));
+                        }
+                        Property::Literal { key, value } => match property_key_name(&key.item) {
+                            Some(name) => {
+                                self.expression(&value, &name);
+                                let name = self.name(&name);
+                                self.emit(Op::DefineField(name));
+                            }
+                            None => {
+                                self.computed_key(&key.item);
+                                self.expression(&value, "");
+                                if Compiler::is_anonymous_function(&value) {
+                                    self.emit(Op::NameFunction);
+                                }
+                                self.emit(Op::DefineComputed);
+                            }
+                        },
+                        Property::Method { key, value } => {
+                            if value.generator {
+                                return self.fail("Generators are not supported yet");
+                            }
+                            let body = ArrowBody::Block(value.body);
+                            let loc = (key.start, value.end);
+                            match property_key_name(&key.item) {
+                                Some(name) => {
+                                    self.function(&name, false, value.params, body, loc, false);
+                                    let name = self.name(&name);
+                                    self.emit(Op::DefineField(name));
+                                }
+                                None => {
+                                    self.computed_key(&key.item);
+                                    self.function("", false, value.params, body, loc, false);
+                                    self.emit(Op::NameFunction);
+                                    self.emit(Op::DefineComputed);
+                                }
+                            }
+                        }
+                        _ => return self.fail("Unsupported object literal property"),
+                    }
+                }
+            }
+            Expression::Member(ref e) => {
+                self.expression(&e.object, "");
+                let name = self.name(e.property.item);
+                self.emit(Op::GetField(name));
+            }
+            Expression::ComputedMember(ref e) => {
+                self.expression(&e.object, "");
+                self.expression(&e.property, "");
+                self.emit(Op::GetComputed);
+            }
+            Expression::Call(ref e) => {
+                match e.callee.item {
+                    Expression::Member(ref m) => {
+                        self.expression(&m.object, "");
+                        self.emit(Op::Dup);
+                        let name = self.name(m.property.item);
+                        self.emit(Op::GetField(name));
+                    }
+                    Expression::ComputedMember(ref m) => {
+                        self.expression(&m.object, "");
+                        self.emit(Op::Dup);
+                        self.expression(&m.property, "");
+                        self.emit(Op::GetComputed);
+                    }
+                    _ => {
+                        self.emit(Op::Undefined);
+                        self.expression(&e.callee, "");
+                    }
+                }
+                for arg in &e.arguments {
+                    self.expression(arg, "");
+                }
+                let argc = e.arguments.iter().count() as u32;
+                match e.callee.item {
+                    Expression::Identifier("eval") => {
+                        self.emit_at(Op::CallEval(argc), e.callee.start)
+                    }
+                    _ => self.emit_at(Op::Call(argc), e.callee.start),
+                }
+            }
+            Expression::Binary(ref e) => match e.operator {
+                OperatorKind::LogicalAnd | OperatorKind::LogicalOr => {
+                    self.expression(&e.left, "");
+                    let end = if e.operator == OperatorKind::LogicalAnd {
+                        self.jump(Op::JumpIfFalseOrPop(0))
+                    } else {
+                        self.jump(Op::JumpIfTrueOrPop(0))
+                    };
+                    self.expression(&e.right, "");
+                    self.patch(end);
+                }
+                OperatorKind::Assign => match e.left.item {
+                    Expression::Identifier(name) => {
+                        self.expression(&e.right, name);
+                        self.set_variable(name);
+                    }
+                    Expression::Member(ref m) => {
+                        self.expression(&m.object, "");
+                        self.expression(&e.right, "");
+                        let name = self.name(m.property.item);
+                        self.emit(Op::SetField(name));
+                    }
+                    Expression::ComputedMember(ref m) => {
+                        self.expression(&m.object, "");
+                        self.expression(&m.property, "");
+                        self.emit(Op::ToPropertyKey);
+                        self.expression(&e.right, "");
+                        self.emit(Op::SetComputed);
+                    }
+                    _ => self.fail("Invalid left-hand side in assignment"),
+                },
+                OperatorKind::AddAssign
+                | OperatorKind::SubtractAssign
+                | OperatorKind::ExponentAssign
+                | OperatorKind::MultiplyAssign
+                | OperatorKind::DivideAssign
+                | OperatorKind::RemainderAssign
+                | OperatorKind::BSLAssign
+                | OperatorKind::BSRAssign
+                | OperatorKind::UBSRAssign
+                | OperatorKind::BitAndAssign
+                | OperatorKind::BitXorAssign
+                | OperatorKind::BitOrAssign => match e.left.item {
+                    Expression::Identifier(name) => {
+                        self.get_variable(name, e.left.start);
+                        self.expression(&e.right, "");
+                        self.emit(Op::Binary(e.operator));
+                        self.set_variable(name);
+                    }
+                    Expression::Member(ref m) => {
+                        self.expression(&m.object, "");
+                        self.emit(Op::Dup);
+                        let name = self.name(m.property.item);
+                        self.emit(Op::GetField(name));
+                        self.expression(&e.right, "");
+                        self.emit(Op::Binary(e.operator));
+                        self.emit(Op::SetField(name));
+                    }
+                    Expression::ComputedMember(ref m) => {
+                        self.expression(&m.object, "");
+                        self.expression(&m.property, "");
+                        self.emit(Op::ToPropertyKey);
+                        self.emit(Op::Dup2);
+                        self.emit(Op::GetComputed);
+                        self.expression(&e.right, "");
+                        self.emit(Op::Binary(e.operator));
+                        self.emit(Op::SetComputed);
+                    }
+                    _ => self.fail("Invalid left-hand side in assignment"),
+                },
+                op => {
+                    self.expression(&e.left, "");
+                    self.expression(&e.right, "");
+                    self.emit(Op::Binary(op));
+                }
+            },
+            Expression::Prefix(ref e) => match e.operator {
+                OperatorKind::New => {
+                    let argc = match e.operand.item {
+                        Expression::Call(ref call) => {
+                            self.expression(&call.callee, "");
+                            for arg in &call.arguments {
+                                self.expression(arg, "");
+                            }
+                            call.arguments.iter().count() as u32
+                        }
+                        _ => {
+                            self.expression(&e.operand, "");
+                            0
+                        }
+                    };
+                    self.emit_at(Op::New(argc), start);
+                }
+                OperatorKind::Increment => self.update(&e.operand, true, true),
+                OperatorKind::Decrement => self.update(&e.operand, false, true),
+                OperatorKind::Delete => match e.operand.item {
+                    Expression::Member(ref m) => {
+                        self.expression(&m.object, "");
+                        let name = self.name(m.property.item);
+                        self.emit(Op::DeleteField(name));
+                    }
+                    Expression::ComputedMember(ref m) => {
+                        self.expression(&m.object, "");
+                        self.expression(&m.property, "");
+                        self.emit(Op::DeleteComputed);
+                    }
+                    _ => self.push_constant(to_value(true)),
+                },
+                // `typeof` is the one thing a variable that isn't declared can be used with
+                OperatorKind::Typeof if !self.is_in_slot(&e.operand) => match e.operand.item {
+                    Expression::Identifier(id) => {
+                        let name = self.name(id);
+                        self.emit_at(Op::TypeofName(name), e.operand.start);
+                    }
+                    _ => {
+                        self.expression(&e.operand, "");
+                        self.emit(Op::Typeof);
+                    }
+                },
+                op => {
+                    self.expression(&e.operand, "");
+                    self.position = start;
+                    match op {
+                        OperatorKind::LogicalNot => self.emit(Op::Not),
+                        OperatorKind::BitwiseNot => self.emit(Op::BitNot),
+                        OperatorKind::Addition => self.emit(Op::Plus),
+                        OperatorKind::Subtraction => self.emit(Op::Neg),
+                        OperatorKind::Typeof => self.emit(Op::Typeof),
+                        OperatorKind::Void => {
+                            self.emit(Op::Pop);
+                            self.emit(Op::Undefined);
+                        }
+                        _ => {
+                            self.emit(Op::Pop);
+                            self.fail(&format!("Unsupported operator {:?}", op));
+                        }
+                    }
+                }
+            },
+            Expression::Postfix(ref e) => {
+                self.update(&e.operand, e.operator == OperatorKind::Increment, false)
+            }
+            Expression::Conditional(ref e) => {
+                self.expression(&e.test, "");
+                let otherwise = self.jump(Op::JumpIfFalse(0));
+                self.expression(&e.consequent, "");
+                let end = self.jump(Op::Jump(0));
+                self.patch(otherwise);
+                self.expression(&e.alternate, "");
+                self.patch(end);
+            }
+            Expression::Template(ref e) => {
+                let mut expressions = e.expressions.iter();
+                let mut parts = 0;
+                for quasi in &e.quasis {
+                    self.push_constant(Value::new(ValueData::String(unescape(quasi.item))));
+                    parts += 1;
+                    if let Some(expression) = expressions.next() {
+                        self.expression(expression, "");
+                        parts += 1;
+                    }
+                }
+                self.emit(Op::Concat(parts));
+            }
+            _ => self.fail("Unsupported expression"),
+        }
+    }
+
+    /// If an expression is a variable in a slot
+    fn is_in_slot(&self, node: &ExpressionNode<'ast>) -> bool {
+        match node.item {
+            Expression::Identifier(id) => self.binding(id).is_some(),
+            _ => false,
+        }
+    }
+
+    /// Push the property key of a computed property name
+    fn computed_key(&mut self, key: &PropertyKey<'ast>) {
+        if let PropertyKey::Computed(ref e) = *key {
+            self.expression(e, "");
+            self.emit(Op::ToPropertyKey);
+        }
+    }
+
+    /// Add or subtract one from a variable or property, as the `++` and `--` operators do
+    fn update(&mut self, operand: &ExpressionNode<'ast>, increment: bool, prefix: bool) {
+        let step = if increment { Op::Inc } else { Op::Dec };
+        match operand.item {
+            Expression::Identifier(name) => {
+                self.get_variable(name, operand.start);
+                self.emit(Op::ToNumeric);
+                if !prefix {
+                    self.emit(Op::Dup);
+                }
+                self.emit(step);
+                self.set_variable(name);
+            }
+            Expression::Member(ref m) => {
+                self.expression(&m.object, "");
+                self.emit(Op::Dup);
+                let name = self.name(m.property.item);
+                self.emit(Op::GetField(name));
+                self.emit(Op::ToNumeric);
+                if !prefix {
+                    self.emit(Op::Dup);
+                    self.emit(Op::Rot3);
+                }
+                self.emit(step);
+                self.emit(Op::SetField(name));
+            }
+            Expression::ComputedMember(ref m) => {
+                self.expression(&m.object, "");
+                self.expression(&m.property, "");
+                self.emit(Op::ToPropertyKey);
+                self.emit(Op::Dup2);
+                self.emit(Op::GetComputed);
+                self.emit(Op::ToNumeric);
+                if !prefix {
+                    self.emit(Op::Dup);
+                    self.emit(Op::Rot4);
+                }
+                self.emit(step);
+                self.emit(Op::SetComputed);
+            }
+            _ => return self.fail("Invalid left-hand side in assignment"),
+        }
+        // A postfix operator leaves the old value, which is under the new one
+        if !prefix {
+            self.emit(Op::Pop);
+        }
+    }
+}
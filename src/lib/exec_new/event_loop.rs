@@ -0,0 +1,348 @@
+//! An event loop for hosts, giving scripts `setTimeout`, `setInterval` and `queueMicrotask`
+//!
+//! The loop keeps the timers scripts set in a heap ordered by when they're due, and runs them one
+//! at a time, each as its own task. After the script that set them, and after each task, the
+//! microtasks queued so far run, including the ones they queue in turn, before anything else.
+//! The loop can follow the real clock, sleeping until the next timer is due, or a fake clock
+//! which only moves when the loop runs out of work or the host moves it, so tests run instantly
+//! and always see timers fire in the same order.
+
+use crate::exec_new::realm::Realm;
+use crate::js::function::{new_native_closure, Caller};
+use crate::js::object::Property;
+use crate::js::value::{to_value, Value};
+use std::cell::RefCell;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Where the time the loop goes by comes from
+enum Clock {
+    /// The real clock, counting from when the loop was made
+    Real(Instant),
+    /// A clock only the loop and its host move, holding the milliseconds it's at
+    Fake(f64),
+}
+
+/// What a timer runs when it's due
+enum Callback {
+    /// A function, called with the arguments given when the timer was set
+    Function(Value, Vec<Value>),
+    /// Source text, run in the global scope as an indirect `eval` would
+    Code(String),
+}
+
+/// A timer set with `setTimeout` or `setInterval`
+struct Timer {
+    /// What it runs
+    callback: Rc<Callback>,
+    /// How often it repeats, in milliseconds, if it was set with `setInterval`
+    interval: Option<f64>,
+}
+
+/// What the loop and the functions it gives scripts share
+struct LoopState {
+    clock: Clock,
+    /// The timers which haven't been cleared, by their ids
+    timers: HashMap<u32, Timer>,
+    /// When each timer is next due, soonest first, then in the order they were set
+    ///
+    /// Cleared timers are left in the heap, and skipped when they come up.
+    due: BinaryHeap<Reverse<(Millis, u64, u32)>>,
+    /// The functions queued with `queueMicrotask`, oldest first
+    microtasks: VecDeque<Value>,
+    /// The id the next timer gets
+    next_id: u32,
+    /// How many timers have been scheduled, which orders the ones due at the same time
+    scheduled: u64,
+}
+
+/// A time in milliseconds, totally ordered so timers can be kept in a heap
+#[derive(Debug, Clone, Copy)]
+struct Millis(f64);
+
+impl PartialEq for Millis {
+    fn eq(&self, other: &Millis) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Millis {}
+
+impl PartialOrd for Millis {
+    fn partial_cmp(&self, other: &Millis) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Millis {
+    fn cmp(&self, other: &Millis) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl LoopState {
+    /// The milliseconds since the loop started
+    fn now(&self) -> f64 {
+        match self.clock {
+            Clock::Real(start) => {
+                let elapsed = start.elapsed();
+                elapsed.as_secs() as f64 * 1e3 + elapsed.subsec_nanos() as f64 / 1e6
+            }
+            Clock::Fake(now) => now,
+        }
+    }
+
+    /// Put a timer in the heap, to be due after a delay
+    fn schedule(&mut self, id: u32, delay: f64) {
+        let at = self.now() + delay;
+        self.scheduled += 1;
+        self.due.push(Reverse((Millis(at), self.scheduled, id)));
+    }
+
+    /// Set a timer, returning its id
+    fn add_timer(&mut self, callback: Callback, delay: f64, repeat: bool) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let timer = Timer {
+            callback: Rc::new(callback),
+            interval: if repeat { Some(delay) } else { None },
+        };
+        self.timers.insert(id, timer);
+        self.schedule(id, delay);
+        id
+    }
+
+    /// When the next timer which hasn't been cleared is due, dropping the cleared ones in front of it
+    fn next_due(&mut self) -> Option<f64> {
+        while let Some(&Reverse((Millis(at), _, id))) = self.due.peek() {
+            if self.timers.contains_key(&id) {
+                return Some(at);
+            }
+            self.due.pop();
+        }
+        None
+    }
+}
+
+/// The longest a timer can wait, as node's delays are 32-bit integers
+const MAX_TIMER_DELAY: f64 = 2147483647.0;
+
+/// How long a timer waits, from the delay a script gave, which like node's is at least 1ms, so
+/// an interval can't keep the loop from moving on
+///
+/// Delays too long to wait, infinite ones included, are 1ms too, so no timer can hang the loop.
+fn timer_delay(args: &[Value]) -> f64 {
+    let delay = args.get(1).map_or(1.0, |delay| delay.to_num());
+    if (1.0..=MAX_TIMER_DELAY).contains(&delay) {
+        delay
+    } else {
+        1.0
+    }
+}
+
+/// An event loop running the timers and microtasks the scripts of the realms it's installed in queue
+///
+/// ```ignore
+/// let event_loop = EventLoop::new();
+/// event_loop.install(&engine.realm());
+/// engine.run_script(&script)?;
+/// event_loop.run(&mut engine)?;
+/// ```
+///
+/// Cloning a loop gives another handle to the same loop. The callbacks waiting to run are kept
+/// alive by the loop until they run or are cleared.
+#[derive(Clone)]
+pub struct EventLoop {
+    state: Rc<RefCell<LoopState>>,
+}
+
+impl EventLoop {
+    /// Make an event loop following the real clock, which sleeps until the next timer is due
+    pub fn new() -> EventLoop {
+        EventLoop::with_clock(Clock::Real(Instant::now()))
+    }
+
+    /// Make an event loop with a fake clock starting at 0, which jumps to the next timer rather than sleeping
+    pub fn with_fake_clock() -> EventLoop {
+        EventLoop::with_clock(Clock::Fake(0.0))
+    }
+
+    fn with_clock(clock: Clock) -> EventLoop {
+        EventLoop {
+            state: Rc::new(RefCell::new(LoopState {
+                clock,
+                timers: HashMap::new(),
+                due: BinaryHeap::new(),
+                microtasks: VecDeque::new(),
+                next_id: 1,
+                scheduled: 0,
+            })),
+        }
+    }
+
+    /// Give the global object of a realm `setTimeout`, `setInterval`, `clearTimeout`,
+    /// `clearInterval` and `queueMicrotask`, which queue work on this loop
+    ///
+    /// These are native closures, so a realm they're installed in can't be snapshotted.
+    pub fn install(&self, realm: &Realm) {
+        let function_prototype = realm.prototype("Function");
+        let global = realm.global();
+        let define = |name: &str, length: i32, func: Value| {
+            func.set_prototype(function_prototype.clone());
+            func.set_prop_slice("name", Property::read_only(to_value(name)));
+            func.set_prop_slice("length", Property::read_only(to_value(length)));
+            global.set_field_slice(name, func);
+        };
+        for &(name, repeat) in &[("setTimeout", false), ("setInterval", true)] {
+            let state = self.state.clone();
+            let set = new_native_closure(move |_, _, args, caller| {
+                let callback = match args.first() {
+                    Some(func) if func.is_function() => {
+                        Callback::Function(func.clone(), args.iter().skip(2).cloned().collect())
+                    }
+                    Some(code) if code.is_string() => Callback::Code(code.to_string()),
+                    _ => {
+                        let message = "The callback of a timer must be a function".to_string();
+                        return Err(caller.new_error("TypeError", message));
+                    }
+                };
+                let id = state
+                    .borrow_mut()
+                    .add_timer(callback, timer_delay(&args), repeat);
+                Ok(to_value(id as f64))
+            });
+            define(name, 1, set);
+        }
+        for name in &["clearTimeout", "clearInterval"] {
+            let state = self.state.clone();
+            let clear = new_native_closure(move |_, _, args, _| {
+                if let Some(id) = args.first() {
+                    let id = id.to_num();
+                    if id >= 1.0 && id <= u32::MAX as f64 {
+                        state.borrow_mut().timers.remove(&(id as u32));
+                    }
+                }
+                Ok(Value::undefined())
+            });
+            define(name, 0, clear);
+        }
+        let state = self.state.clone();
+        let queue = new_native_closure(move |_, _, args, caller| match args.first() {
+            Some(func) if func.is_function() => {
+                state.borrow_mut().microtasks.push_back(func.clone());
+                Ok(Value::undefined())
+            }
+            _ => {
+                let message = "The callback of queueMicrotask must be a function".to_string();
+                Err(caller.new_error("TypeError", message))
+            }
+        });
+        define("queueMicrotask", 1, queue);
+    }
+
+    /// The milliseconds the loop's clock has counted since it started
+    pub fn now(&self) -> f64 {
+        self.state.borrow().now()
+    }
+
+    /// If there are no timers or microtasks waiting to run
+    pub fn is_idle(&self) -> bool {
+        let mut state = self.state.borrow_mut();
+        state.microtasks.is_empty() && state.next_due().is_none()
+    }
+
+    /// Run the queued microtasks, and the ones they queue, until there are none left
+    ///
+    /// If one throws, the rest are left in the queue and the error is returned.
+    pub fn run_microtasks(&self, engine: &mut dyn Caller) -> Result<(), Value> {
+        loop {
+            let task = self.state.borrow_mut().microtasks.pop_front();
+            match task {
+                Some(task) => {
+                    engine.call(&task, Value::undefined(), Vec::new())?;
+                }
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Run the timer due soonest, if it's due by `until`, followed by the microtasks it queues,
+    /// returning whether there was one
+    fn run_timer(&self, engine: &mut dyn Caller, until: Option<f64>) -> Result<bool, Value> {
+        let callback = {
+            let mut state = self.state.borrow_mut();
+            let at = match state.next_due() {
+                Some(at) if until.is_none_or(|until| at <= until) => at,
+                _ => return Ok(false),
+            };
+            let wait = match state.clock {
+                Clock::Fake(ref mut now) => {
+                    *now = now.max(at);
+                    0.0
+                }
+                Clock::Real(_) => at - state.now(),
+            };
+            if wait > 0.0 {
+                thread::sleep(Duration::from_micros((wait * 1e3).ceil() as u64));
+            }
+            let Reverse((_, _, id)) = state.due.pop().expect("a timer is due");
+            let timer = &state.timers[&id];
+            let (callback, interval) = (timer.callback.clone(), timer.interval);
+            // An interval is due again a period after it runs, and a timeout is done with
+            match interval {
+                Some(interval) => state.schedule(id, interval),
+                None => {
+                    state.timers.remove(&id);
+                }
+            }
+            callback
+        };
+        match *callback {
+            Callback::Function(ref func, ref args) => {
+                engine.call(func, Value::undefined(), args.clone())?;
+            }
+            Callback::Code(ref source) => {
+                engine.eval_code(source)?;
+            }
+        }
+        self.run_microtasks(engine)?;
+        Ok(true)
+    }
+
+    /// Run the next task: the timer due soonest, waiting for it if it isn't due yet, followed by
+    /// the microtasks it queues. Returns `false`, having run nothing, if the loop is idle.
+    pub fn run_once(&self, engine: &mut dyn Caller) -> Result<bool, Value> {
+        self.run_microtasks(engine)?;
+        self.run_timer(engine, None)
+    }
+
+    /// Run tasks until none are left, as a host does once its main script has run
+    ///
+    /// If a task throws, the loop stops with its error, and can be run again to carry on with the rest.
+    pub fn run(&self, engine: &mut dyn Caller) -> Result<(), Value> {
+        while self.run_once(engine)? {}
+        Ok(())
+    }
+
+    /// Move a fake clock forward, running the timers due by the time it gets to, in order
+    ///
+    /// With the real clock, this runs the timers due in the next `ms` milliseconds, waiting for them.
+    pub fn advance(&self, engine: &mut dyn Caller, ms: f64) -> Result<(), Value> {
+        let until = self.now() + ms.max(0.0);
+        self.run_microtasks(engine)?;
+        while self.run_timer(engine, Some(until))? {}
+        if let Clock::Fake(ref mut now) = self.state.borrow_mut().clock {
+            *now = now.max(until);
+        }
+        Ok(())
+    }
+}
+
+impl Default for EventLoop {
+    fn default() -> EventLoop {
+        EventLoop::new()
+    }
+}
@@ -0,0 +1,393 @@
+//! Host functions for scripts run as tools: files, environment variables, the arguments and exit
+//! code of the process, and the standard input stream
+//!
+//! Installing them in a realm gives it `fs` and `process` objects, but what they can do is up to
+//! the `Permissions` of the interpreter calling them, which allow nothing unless told otherwise.
+//! Using one without permission throws an `Error` whose `code` is `ERR_ACCESS_DENIED`.
+
+use crate::exec_new::realm::Realm;
+use crate::js::array::array_create;
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::value::{to_value, ResultValue, Value};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, Read};
+use std::path::{Component, Path, PathBuf};
+
+/// What is allowed of a kind of access: only some things, or everything
+#[derive(Debug, Clone)]
+enum Grant<T> {
+    Only(Vec<T>),
+    All,
+}
+
+impl<T> Grant<T> {
+    fn add(&mut self, item: T) {
+        if let Grant::Only(ref mut items) = *self {
+            items.push(item);
+        }
+    }
+
+    fn allows<F: Fn(&T) -> bool>(&self, matches: F) -> bool {
+        match *self {
+            Grant::Only(ref items) => items.iter().any(matches),
+            Grant::All => true,
+        }
+    }
+}
+
+/// What the host functions are allowed to do, which is nothing by default
+///
+/// ```ignore
+/// let mut permissions = Permissions::new();
+/// permissions.allow_read("data").allow_env("HOME");
+/// engine.set_permissions(permissions);
+/// ```
+///
+/// Access to a directory is access to everything in it. Paths are compared once symbolic links
+/// are followed, so a link can't lead a script out of what it's allowed.
+#[derive(Debug, Clone)]
+pub struct Permissions {
+    read: Grant<PathBuf>,
+    write: Grant<PathBuf>,
+    env: Grant<String>,
+    stdin: bool,
+    exit: bool,
+}
+
+impl Default for Permissions {
+    fn default() -> Permissions {
+        Permissions::new()
+    }
+}
+
+impl Permissions {
+    /// Permissions allowing nothing
+    pub fn new() -> Permissions {
+        Permissions {
+            read: Grant::Only(Vec::new()),
+            write: Grant::Only(Vec::new()),
+            env: Grant::Only(Vec::new()),
+            stdin: false,
+            exit: false,
+        }
+    }
+
+    /// Permissions allowing everything
+    pub fn all() -> Permissions {
+        Permissions {
+            read: Grant::All,
+            write: Grant::All,
+            env: Grant::All,
+            stdin: true,
+            exit: true,
+        }
+    }
+
+    /// Allow reading a file, or anything in a directory, and listing directories
+    pub fn allow_read<P: AsRef<Path>>(&mut self, path: P) -> &mut Permissions {
+        self.read.add(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Allow reading any file
+    pub fn allow_read_all(&mut self) -> &mut Permissions {
+        self.read = Grant::All;
+        self
+    }
+
+    /// Allow writing a file, or anything in a directory
+    pub fn allow_write<P: AsRef<Path>>(&mut self, path: P) -> &mut Permissions {
+        self.write.add(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Allow writing any file
+    pub fn allow_write_all(&mut self) -> &mut Permissions {
+        self.write = Grant::All;
+        self
+    }
+
+    /// Allow reading an environment variable
+    pub fn allow_env(&mut self, name: &str) -> &mut Permissions {
+        self.env.add(name.to_string());
+        self
+    }
+
+    /// Allow reading any environment variable
+    pub fn allow_env_all(&mut self) -> &mut Permissions {
+        self.env = Grant::All;
+        self
+    }
+
+    /// Allow reading the standard input stream
+    pub fn allow_stdin(&mut self) -> &mut Permissions {
+        self.stdin = true;
+        self
+    }
+
+    /// Allow `process.exit` to stop the script with an exit code for the host
+    pub fn allow_exit(&mut self) -> &mut Permissions {
+        self.exit = true;
+        self
+    }
+
+    /// If a file or directory can be read
+    pub fn can_read(&self, path: &Path) -> bool {
+        can_access(&self.read, path)
+    }
+
+    /// If a file can be written
+    pub fn can_write(&self, path: &Path) -> bool {
+        can_access(&self.write, path)
+    }
+
+    /// If an environment variable can be read
+    pub fn can_read_env(&self, name: &str) -> bool {
+        self.env.allows(|allowed| allowed == name)
+    }
+
+    /// If the standard input stream can be read
+    pub fn can_read_stdin(&self) -> bool {
+        self.stdin
+    }
+
+    /// If a script can stop itself with `process.exit`
+    pub fn can_exit(&self) -> bool {
+        self.exit
+    }
+}
+
+/// Make a path absolute and follow the symbolic links along it, as far as it exists
+///
+/// Returns `None` for a path that goes up out of a directory that doesn't exist, which can't be
+/// opened, and for one through a symbolic link to something that doesn't exist, which can't be
+/// followed to where it leads.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().ok()?.join(path)
+    };
+    let components: Vec<Component> = absolute.components().collect();
+    for split in (0..=components.len()).rev() {
+        let existing: PathBuf = components[..split].iter().collect();
+        let mut resolved = match existing.canonicalize() {
+            Ok(resolved) => resolved,
+            // What's there but can't be canonicalized is a link to something missing, which could be
+            // written through
+            Err(_) if fs::symlink_metadata(&existing).is_ok() => return None,
+            Err(_) => continue,
+        };
+        for component in &components[split..] {
+            match *component {
+                Component::CurDir => (),
+                Component::ParentDir => return None,
+                ref other => resolved.push(other),
+            }
+        }
+        return Some(resolved);
+    }
+    None
+}
+
+/// If a grant of paths covers a path
+fn can_access(grant: &Grant<PathBuf>, path: &Path) -> bool {
+    covers(grant, resolve(path).as_deref())
+}
+
+/// If a grant of paths covers a path which has been resolved, if it could be
+fn covers(grant: &Grant<PathBuf>, resolved: Option<&Path>) -> bool {
+    if let Grant::All = *grant {
+        return true;
+    }
+    match resolved {
+        Some(path) => grant.allows(|allowed| resolve(allowed).is_some_and(|a| path.starts_with(a))),
+        None => false,
+    }
+}
+
+/// The error thrown when the permissions don't allow something
+fn access_denied(caller: &dyn Caller, what: String) -> Value {
+    let error = caller.new_error("Error", format!("{} is not allowed", what));
+    error.set_field_slice("code", to_value("ERR_ACCESS_DENIED"));
+    error
+}
+
+/// The error thrown when the host fails to do something, with a `code` like node's for the common failures
+fn io_error(caller: &dyn Caller, error: io::Error, path: &str) -> Value {
+    let code = match error.kind() {
+        io::ErrorKind::NotFound => "ENOENT",
+        io::ErrorKind::PermissionDenied => "EACCES",
+        io::ErrorKind::AlreadyExists => "EEXIST",
+        io::ErrorKind::InvalidData => "EILSEQ",
+        _ => "EIO",
+    };
+    let message = if path.is_empty() {
+        error.to_string()
+    } else {
+        format!("{}: {}", error, path)
+    };
+    let error = caller.new_error("Error", message);
+    error.set_field_slice("code", to_value(code));
+    error
+}
+
+/// The path argument of a file function, checked for reading or writing, and the path to open
+///
+/// What's opened is the path that was checked, with its links followed already, rather than the
+/// one the script gave. A file is never written through a link.
+fn checked_path(
+    args: &[Value],
+    caller: &dyn Caller,
+    write: bool,
+) -> Result<(String, PathBuf), Value> {
+    let path = match args.first() {
+        Some(path) if path.is_string() => path.to_string(),
+        _ => return Err(caller.new_error("TypeError", "The path must be a string".to_string())),
+    };
+    let resolved = resolve(Path::new(&path));
+    let allowed = caller.permissions().is_some_and(|p| {
+        let grant = if write { &p.write } else { &p.read };
+        covers(grant, resolved.as_deref())
+    });
+    let open = resolved.unwrap_or_else(|| PathBuf::from(&path));
+    let is_link = fs::symlink_metadata(&open).is_ok_and(|m| m.file_type().is_symlink());
+    if allowed && !(write && is_link) {
+        Ok((path, open))
+    } else {
+        let access = if write { "Write" } else { "Read" };
+        Err(access_denied(
+            caller,
+            format!("{} access to \"{}\"", access, path),
+        ))
+    }
+}
+
+/// Read a file as UTF-8 text
+pub fn read_file(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let (path, open) = checked_path(&args, caller, false)?;
+    match fs::read_to_string(&open) {
+        Ok(text) => Ok(to_value(text)),
+        Err(error) => Err(io_error(caller, error, &path)),
+    }
+}
+
+/// Write text to a file, replacing what it held
+pub fn write_file(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let (path, open) = checked_path(&args, caller, true)?;
+    let text = args.get(1).map_or(String::new(), |text| text.to_string());
+    match fs::write(&open, text) {
+        Ok(()) => Ok(Value::undefined()),
+        Err(error) => Err(io_error(caller, error, &path)),
+    }
+}
+
+/// List the names of what's in a directory, in order
+pub fn read_dir(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let (path, open) = checked_path(&args, caller, false)?;
+    let entries = fs::read_dir(&open).and_then(|entries| {
+        entries
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<io::Result<Vec<String>>>()
+    });
+    let mut names = entries.map_err(|error| io_error(caller, error, &path))?;
+    names.sort();
+    let array = array_create(caller.prototype("Array"));
+    for (i, name) in names.iter().enumerate() {
+        array.set_field(i.to_string(), to_value(name.as_str()));
+    }
+    array.set_field_slice("length", to_value(names.len() as i32));
+    Ok(array)
+}
+
+/// Get an environment variable, or `undefined` if it isn't set
+pub fn get_env(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let name = args.first().map_or(String::new(), |name| name.to_string());
+    if !caller.permissions().is_some_and(|p| p.can_read_env(&name)) {
+        let what = format!("Access to the environment variable \"{}\"", name);
+        return Err(access_denied(caller, what));
+    }
+    Ok(match env::var(&name) {
+        Ok(value) => to_value(value),
+        Err(_) => Value::undefined(),
+    })
+}
+
+/// Check the standard input stream can be read
+fn check_stdin(caller: &dyn Caller) -> Result<(), Value> {
+    if caller
+        .permissions()
+        .is_some_and(Permissions::can_read_stdin)
+    {
+        Ok(())
+    } else {
+        Err(access_denied(
+            caller,
+            "Reading the standard input stream".to_string(),
+        ))
+    }
+}
+
+/// Read the rest of the standard input stream as text
+pub fn read_stdin(_: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    check_stdin(caller)?;
+    let mut text = String::new();
+    match io::stdin().read_to_string(&mut text) {
+        Ok(_) => Ok(to_value(text)),
+        Err(error) => Err(io_error(caller, error, "")),
+    }
+}
+
+/// Read a line of the standard input stream, without its line ending, or `null` at the end of the stream
+pub fn read_line(_: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    check_stdin(caller)?;
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) => Ok(Value::null()),
+        Ok(_) => {
+            let end = line.trim_end_matches(['\n', '\r']).len();
+            line.truncate(end);
+            Ok(to_value(line))
+        }
+        Err(error) => Err(io_error(caller, error, "")),
+    }
+}
+
+/// Stop the script, leaving an exit code for the host
+pub fn exit(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if !caller.permissions().is_some_and(Permissions::can_exit) {
+        return Err(access_denied(caller, "Exiting the process".to_string()));
+    }
+    let code = args.first().map_or(0, |code| code.to_int());
+    Err(caller.exit(code))
+}
+
+/// Give the global object of a realm `fs` and `process` objects, with `argv` as the arguments of the process
+///
+/// The functions aren't built in, so a realm they're installed in can't be snapshotted.
+pub fn install(realm: &Realm, argv: Vec<String>) {
+    let global = realm.global();
+    let fs_object = realm.new_object();
+    fs_object.set_field_slice("readFile", to_value(read_file as NativeFunctionData));
+    fs_object.set_field_slice("writeFile", to_value(write_file as NativeFunctionData));
+    fs_object.set_field_slice("readDir", to_value(read_dir as NativeFunctionData));
+    global.set_field_slice("fs", fs_object);
+
+    let process = realm.new_object();
+    let args = array_create(realm.prototype("Array"));
+    for (i, arg) in argv.iter().enumerate() {
+        args.set_field(i.to_string(), to_value(arg.as_str()));
+    }
+    args.set_field_slice("length", to_value(argv.len() as i32));
+    process.set_field_slice("argv", args);
+    let env = realm.new_object();
+    env.set_field_slice("get", to_value(get_env as NativeFunctionData));
+    process.set_field_slice("env", env);
+    let stdin = realm.new_object();
+    stdin.set_field_slice("read", to_value(read_stdin as NativeFunctionData));
+    stdin.set_field_slice("readLine", to_value(read_line as NativeFunctionData));
+    process.set_field_slice("stdin", stdin);
+    process.set_field_slice("exit", to_value(exit as NativeFunctionData));
+    global.set_field_slice("process", process);
+}
@@ -0,0 +1,179 @@
+//! Counting the calls of script functions, and compiling the hot ones to machine code
+//!
+//! Every script function keeps a profile of how it has been called. With the `jit` feature on
+//! x86-64 Linux, a function called `JIT_THRESHOLD` times is compiled to machine code, which runs
+//! the arithmetic, comparisons and jumps of its body on numbers held in its local slots. Anything
+//! else, or a value that isn't a number where one was expected, sends the call back to the
+//! interpreter at the instruction it was up to, with the same stack and slots.
+
+use crate::exec_new::bytecode::Chunk;
+use crate::exec_new::Interpreter;
+use crate::js::function::FunctionCode;
+use crate::js::value::{ResultValue, Value};
+use std::cell::Cell;
+#[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+use std::cell::RefCell;
+use std::fmt;
+#[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+use std::rc::Rc;
+
+#[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+mod x64;
+
+/// The calls a function takes before it's compiled to machine code
+pub const JIT_THRESHOLD: u32 = 10;
+/// The times machine code can go back to the interpreter part way through before it's thrown away
+#[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+const DEOPT_LIMIT: u32 = 10;
+
+/// If this build can compile functions to machine code
+pub fn jit_supported() -> bool {
+    cfg!(all(
+        feature = "jit",
+        target_arch = "x86_64",
+        target_os = "linux"
+    ))
+}
+
+/// How a function's body is run
+#[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+#[derive(Debug)]
+enum Tier {
+    /// As machine code
+    Compiled(Rc<x64::CompiledChunk>),
+    /// By the interpreter for good, as the machine code couldn't be made or kept going back to it
+    Abandoned,
+}
+
+/// The counters of a script function which decide when it's compiled
+#[derive(Debug, Default)]
+pub struct FunctionProfile {
+    /// How many times the function has been called
+    pub calls: Cell<u32>,
+    /// How many of those calls started in machine code
+    pub compiled_calls: Cell<u32>,
+    /// How many times machine code went back to the interpreter part way through a call
+    pub deopts: Cell<u32>,
+    /// How the function is run once it's hot, or `None` while it's interpreted
+    #[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+    tier: RefCell<Option<Tier>>,
+}
+
+impl FunctionProfile {
+    /// If the function has machine code which calls start in
+    pub fn is_compiled(&self) -> bool {
+        #[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+        {
+            if let Some(Tier::Compiled(_)) = *self.tier.borrow() {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// How often a script function has been called and how it was run, for diagnostics
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionStats {
+    /// The name of the function
+    pub name: String,
+    /// How many times it has been called
+    pub calls: u32,
+    /// If it has been compiled to machine code, and not thrown it away
+    pub compiled: bool,
+    /// How many calls started in machine code
+    pub compiled_calls: u32,
+    /// How many times machine code went back to the interpreter part way through a call
+    pub deopts: u32,
+}
+
+impl FunctionStats {
+    /// The statistics of a function
+    pub(super) fn new(name: &str, code: &FunctionCode) -> FunctionStats {
+        let profile = &code.profile;
+        FunctionStats {
+            name: name.to_string(),
+            calls: profile.calls.get(),
+            compiled: profile.is_compiled(),
+            compiled_calls: profile.compiled_calls.get(),
+            deopts: profile.deopts.get(),
+        }
+    }
+}
+
+impl fmt::Display for FunctionStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = if self.name.is_empty() {
+            "<anonymous>"
+        } else {
+            &self.name
+        };
+        write!(
+            f,
+            "{}: {} calls, {} in machine code, {} deoptimizations{}",
+            name,
+            self.calls,
+            self.compiled_calls,
+            self.deopts,
+            if self.compiled { "" } else { " (interpreted)" }
+        )
+    }
+}
+
+impl Interpreter {
+    /// Run the body of a script function, in machine code once it's hot
+    pub(super) fn run_function_chunk(
+        &mut self,
+        code: &FunctionCode,
+        chunk: &Chunk,
+        args: &[Value],
+    ) -> ResultValue {
+        let profile = &code.profile;
+        profile.calls.set(profile.calls.get().saturating_add(1));
+        #[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+        {
+            if let Some(compiled) = self.machine_code(profile, chunk) {
+                profile.compiled_calls.set(profile.compiled_calls.get() + 1);
+                match compiled.run(self, args) {
+                    x64::Outcome::Returned(val) => return Ok(val),
+                    x64::Outcome::Thrown(err) => return Err(err),
+                    x64::Outcome::Deopt { pc, stack, locals } => {
+                        profile.deopts.set(profile.deopts.get() + 1);
+                        if profile.deopts.get() >= DEOPT_LIMIT {
+                            *profile.tier.borrow_mut() = Some(Tier::Abandoned);
+                        }
+                        return self.resume_chunk(chunk, args, pc, stack, locals);
+                    }
+                }
+            }
+        }
+        self.run_chunk(chunk, args)
+    }
+
+    /// The machine code calls to a function start in, compiling it if it has just got hot
+    #[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+    fn machine_code(
+        &self,
+        profile: &FunctionProfile,
+        chunk: &Chunk,
+    ) -> Option<Rc<x64::CompiledChunk>> {
+        if !self.jit {
+            return None;
+        }
+        let mut tier = profile.tier.borrow_mut();
+        match *tier {
+            Some(Tier::Compiled(ref compiled)) => return Some(compiled.clone()),
+            Some(Tier::Abandoned) => return None,
+            _ if profile.calls.get() < JIT_THRESHOLD => return None,
+            _ => (),
+        }
+        *tier = Some(match x64::compile(chunk) {
+            Some(compiled) => Tier::Compiled(Rc::new(compiled)),
+            None => Tier::Abandoned,
+        });
+        match *tier {
+            Some(Tier::Compiled(ref compiled)) => Some(compiled.clone()),
+            _ => None,
+        }
+    }
+}
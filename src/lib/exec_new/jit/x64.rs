@@ -0,0 +1,867 @@
+//! A baseline compiler from bytecode to x86-64 machine code
+//!
+//! Each instruction is compiled on its own, to code which works on the same stack and local slots
+//! the interpreter would, so wherever the machine code stops the interpreter can carry on. The
+//! height of the stack at each instruction is worked out ahead of time, which puts every stack
+//! entry at a fixed place. Only values which aren't on the heap are ever put on that stack or in
+//! those slots, so they can be copied as bits; an argument on the heap, or a constant that is,
+//! stops the machine code before it's used.
+//!
+//! Registers while the code runs:
+//! - `rbx` holds the `Context`
+//! - `r12` holds the stack and `r14` the local slots
+//! - `r15` holds the arguments
+//! - `r13` counts the instructions run since the interpreter was last told
+
+use crate::exec_new::bytecode::{Chunk, Op};
+use crate::exec_new::Interpreter;
+use crate::js::value::{
+    Value, ValueData, CANONICAL_NAN, TAG_BOOLEAN, TAG_HEAP, TAG_NULL, TAG_UNDEFINED,
+};
+use ratel::ast::operator::OperatorKind;
+use std::fmt;
+use std::mem;
+use std::ptr;
+
+/// The most instructions machine code runs before it checks for interrupts and limits
+const POLL_INTERVAL: u64 = 4096;
+
+/// What machine code left the interpreter to do
+pub enum Outcome {
+    /// The call returned a value
+    Returned(Value),
+    /// The call was stopped, by an interrupt or a limit
+    Thrown(Value),
+    /// The interpreter has to carry on from an instruction, with this stack and these slots
+    Deopt {
+        pc: usize,
+        stack: Vec<Value>,
+        locals: Vec<Value>,
+    },
+}
+
+/// How machine code stopped, which it returns
+const STATUS_RETURNED: u64 = 0;
+const STATUS_DEOPT: u64 = 1;
+const STATUS_THROWN: u64 = 2;
+
+/// What machine code is given to work on, at the offsets below
+#[repr(C)]
+struct Context {
+    stack: *mut u64,
+    locals: *mut u64,
+    args: *const Value,
+    argc: u64,
+    /// The instructions run since the interpreter was last told, when the code stops
+    steps: u64,
+    /// The instructions the code can run before it has to tell the interpreter
+    poll_every: u64,
+    /// The instruction the interpreter carries on from
+    exit_pc: u64,
+    /// The height of the stack the interpreter carries on with
+    exit_depth: u64,
+    /// The value returned
+    result: u64,
+    interpreter: *mut Interpreter,
+    /// What stopped the code, when the interpreter did
+    error: Option<Value>,
+}
+
+const CTX_STACK: i32 = 0;
+const CTX_LOCALS: i32 = 8;
+const CTX_ARGS: i32 = 16;
+const CTX_ARGC: i32 = 24;
+const CTX_STEPS: i32 = 32;
+const CTX_POLL_EVERY: i32 = 40;
+const CTX_EXIT_PC: i32 = 48;
+const CTX_EXIT_DEPTH: i32 = 56;
+const CTX_RESULT: i32 = 64;
+
+/// Tell the interpreter about the instructions machine code has run, returning nonzero if it has to stop
+extern "sysv64" fn poll(ctx: *mut Context, steps: u64) -> u64 {
+    let ctx = unsafe { &mut *ctx };
+    let interpreter = unsafe { &mut *ctx.interpreter };
+    match interpreter.advance(steps) {
+        Ok(()) => {
+            ctx.poll_every = poll_interval(interpreter);
+            0
+        }
+        Err(err) => {
+            ctx.error = Some(err);
+            1
+        }
+    }
+}
+
+/// The instructions machine code can run before it has to tell the interpreter, so it stops close to the step limit
+fn poll_interval(interpreter: &Interpreter) -> u64 {
+    match interpreter.step_limit {
+        Some(limit) => (limit.saturating_sub(interpreter.steps) + 1).min(POLL_INTERVAL),
+        None => POLL_INTERVAL,
+    }
+}
+
+/// The registers used
+const RAX: u8 = 0;
+const RCX: u8 = 1;
+const RDX: u8 = 2;
+const RBX: u8 = 3;
+const RSI: u8 = 6;
+const RDI: u8 = 7;
+const R12: u8 = 12;
+const R13: u8 = 13;
+const R14: u8 = 14;
+const R15: u8 = 15;
+
+/// The conditions of conditional jumps and `setcc`
+const CC_B: u8 = 0x2;
+const CC_AE: u8 = 0x3;
+const CC_E: u8 = 0x4;
+const CC_NE: u8 = 0x5;
+const CC_BE: u8 = 0x6;
+const CC_A: u8 = 0x7;
+const CC_P: u8 = 0xA;
+const CC_NP: u8 = 0xB;
+
+/// A place in the code jumps can go to, which may not be known yet
+#[derive(Clone, Copy)]
+struct Label(usize);
+
+/// Writes machine code, filling in jumps once the places they go to are known
+struct Assembler {
+    code: Vec<u8>,
+    labels: Vec<Option<usize>>,
+    /// The offsets of jumps to fill in, and the labels they go to
+    fixups: Vec<(usize, Label)>,
+}
+
+impl Assembler {
+    fn new() -> Assembler {
+        Assembler {
+            code: Vec::new(),
+            labels: Vec::new(),
+            fixups: Vec::new(),
+        }
+    }
+
+    fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    fn bind(&mut self, label: Label) {
+        self.labels[label.0] = Some(self.code.len());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.code.extend_from_slice(bytes);
+    }
+
+    fn imm32(&mut self, imm: i32) {
+        self.code.extend_from_slice(&imm.to_le_bytes());
+    }
+
+    /// A 64-bit operation's prefix, for a register in the `reg` field and one in the `rm` field
+    fn rex_w(&mut self, reg: u8, rm: u8) {
+        self.code.push(0x48 | ((reg >> 3) << 2) | (rm >> 3));
+    }
+
+    /// The operand `[base + disp]`, with `reg` in the `reg` field
+    fn mem_operand(&mut self, reg: u8, base: u8, disp: i32) {
+        self.code.push(0x80 | ((reg & 7) << 3) | (base & 7));
+        if base & 7 == 4 {
+            // `rsp` and `r12` as a base need an SIB byte
+            self.code.push(0x24);
+        }
+        self.imm32(disp);
+    }
+
+    /// `mov dst, [base + disp]`
+    fn load(&mut self, dst: u8, base: u8, disp: i32) {
+        self.rex_w(dst, base);
+        self.code.push(0x8B);
+        self.mem_operand(dst, base, disp);
+    }
+
+    /// `mov [base + disp], src`
+    fn store(&mut self, base: u8, disp: i32, src: u8) {
+        self.rex_w(src, base);
+        self.code.push(0x89);
+        self.mem_operand(src, base, disp);
+    }
+
+    /// `cmp reg, [base + disp]`
+    fn cmp_mem(&mut self, reg: u8, base: u8, disp: i32) {
+        self.rex_w(reg, base);
+        self.code.push(0x3B);
+        self.mem_operand(reg, base, disp);
+    }
+
+    /// `mov dst, imm`
+    fn mov_imm(&mut self, dst: u8, imm: u64) {
+        self.rex_w(0, dst);
+        self.code.push(0xB8 + (dst & 7));
+        self.code.extend_from_slice(&imm.to_le_bytes());
+    }
+
+    /// `mov dst, src`
+    fn mov(&mut self, dst: u8, src: u8) {
+        self.rex_w(src, dst);
+        self.code.push(0x89);
+        self.code.push(0xC0 | ((src & 7) << 3) | (dst & 7));
+    }
+
+    /// `cmp a, b`
+    fn cmp(&mut self, a: u8, b: u8) {
+        self.rex_w(b, a);
+        self.code.push(0x39);
+        self.code.push(0xC0 | ((b & 7) << 3) | (a & 7));
+    }
+
+    /// `or dst, src`
+    fn or(&mut self, dst: u8, src: u8) {
+        self.rex_w(src, dst);
+        self.code.push(0x09);
+        self.code.push(0xC0 | ((src & 7) << 3) | (dst & 7));
+    }
+
+    /// `xor dst, src`
+    fn xor(&mut self, dst: u8, src: u8) {
+        self.rex_w(src, dst);
+        self.code.push(0x31);
+        self.code.push(0xC0 | ((src & 7) << 3) | (dst & 7));
+    }
+
+    /// `movq xmm, reg`
+    fn movq_to_xmm(&mut self, xmm: u8, reg: u8) {
+        self.code.push(0x66);
+        self.rex_w(xmm, reg);
+        self.bytes(&[0x0F, 0x6E, 0xC0 | (xmm << 3) | (reg & 7)]);
+    }
+
+    /// `movq reg, xmm`
+    fn movq_from_xmm(&mut self, reg: u8, xmm: u8) {
+        self.code.push(0x66);
+        self.rex_w(xmm, reg);
+        self.bytes(&[0x0F, 0x7E, 0xC0 | (xmm << 3) | (reg & 7)]);
+    }
+
+    /// A scalar double operation such as `addsd a, b`
+    fn sse(&mut self, opcode: u8, a: u8, b: u8) {
+        self.bytes(&[0xF2, 0x0F, opcode, 0xC0 | (a << 3) | b]);
+    }
+
+    /// `ucomisd a, b`
+    fn ucomisd(&mut self, a: u8, b: u8) {
+        self.bytes(&[0x66, 0x0F, 0x2E, 0xC0 | (a << 3) | b]);
+    }
+
+    /// `setcc` into `al` or `cl`
+    fn setcc(&mut self, cc: u8, reg: u8) {
+        self.bytes(&[0x0F, 0x90 + cc, 0xC0 | reg]);
+    }
+
+    fn jmp(&mut self, label: Label) {
+        self.code.push(0xE9);
+        self.fixups.push((self.code.len(), label));
+        self.imm32(0);
+    }
+
+    fn jcc(&mut self, cc: u8, label: Label) {
+        self.bytes(&[0x0F, 0x80 + cc]);
+        self.fixups.push((self.code.len(), label));
+        self.imm32(0);
+    }
+
+    /// Fill in the jumps, and return the code
+    fn finish(mut self) -> Vec<u8> {
+        for &(offset, label) in &self.fixups {
+            let target = self.labels[label.0].expect("jump to a label that wasn't bound");
+            let rel = target as i64 - (offset as i64 + 4);
+            self.code[offset..offset + 4].copy_from_slice(&(rel as i32).to_le_bytes());
+        }
+        self.code
+    }
+}
+
+/// Memory holding machine code, which can be run but not written
+struct ExecutableMemory {
+    ptr: *mut u8,
+    len: usize,
+}
+
+extern "C" {
+    fn mmap(addr: *mut u8, len: usize, prot: i32, flags: i32, fd: i32, offset: i64) -> *mut u8;
+    fn mprotect(addr: *mut u8, len: usize, prot: i32) -> i32;
+    fn munmap(addr: *mut u8, len: usize) -> i32;
+}
+
+const PROT_READ: i32 = 1;
+const PROT_WRITE: i32 = 2;
+const PROT_EXEC: i32 = 4;
+const MAP_PRIVATE: i32 = 2;
+const MAP_ANONYMOUS: i32 = 0x20;
+
+impl ExecutableMemory {
+    /// Copy code into memory of its own and make it executable
+    fn new(code: &[u8]) -> Option<ExecutableMemory> {
+        let len = code.len().max(1);
+        unsafe {
+            let ptr = mmap(
+                ptr::null_mut(),
+                len,
+                PROT_READ | PROT_WRITE,
+                MAP_PRIVATE | MAP_ANONYMOUS,
+                -1,
+                0,
+            );
+            if ptr as isize == -1 {
+                return None;
+            }
+            ptr::copy_nonoverlapping(code.as_ptr(), ptr, code.len());
+            if mprotect(ptr, len, PROT_READ | PROT_EXEC) != 0 {
+                munmap(ptr, len);
+                return None;
+            }
+            Some(ExecutableMemory { ptr, len })
+        }
+    }
+}
+
+impl Drop for ExecutableMemory {
+    fn drop(&mut self) {
+        unsafe {
+            munmap(self.ptr, self.len);
+        }
+    }
+}
+
+/// A chunk compiled to machine code
+pub struct CompiledChunk {
+    memory: ExecutableMemory,
+    /// The most values the stack holds at once
+    max_depth: usize,
+    /// The number of local slots
+    locals: usize,
+}
+
+impl fmt::Debug for CompiledChunk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CompiledChunk({} bytes)", self.memory.len)
+    }
+}
+
+impl CompiledChunk {
+    /// Run a call of the chunk with some arguments
+    pub fn run(&self, interpreter: &mut Interpreter, args: &[Value]) -> Outcome {
+        let mut stack = vec![TAG_UNDEFINED; self.max_depth.max(1)];
+        let mut locals = vec![TAG_UNDEFINED; self.locals.max(1)];
+        let mut ctx = Context {
+            stack: stack.as_mut_ptr(),
+            locals: locals.as_mut_ptr(),
+            args: args.as_ptr(),
+            argc: args.len() as u64,
+            steps: 0,
+            poll_every: poll_interval(interpreter),
+            exit_pc: 0,
+            exit_depth: 0,
+            result: TAG_UNDEFINED,
+            interpreter: interpreter as *mut Interpreter,
+            error: None,
+        };
+        let status = unsafe {
+            let entry: extern "sysv64" fn(*mut Context) -> u64 = mem::transmute(self.memory.ptr);
+            entry(&mut ctx)
+        };
+        if status == STATUS_THROWN {
+            return Outcome::Thrown(
+                ctx.error
+                    .take()
+                    .expect("machine code stopped without an error"),
+            );
+        }
+        if let Err(err) = interpreter.advance(ctx.steps) {
+            return Outcome::Thrown(err);
+        }
+        if status == STATUS_RETURNED {
+            return Outcome::Returned(Value::from_immediate_bits(ctx.result));
+        }
+        stack.truncate(ctx.exit_depth as usize);
+        locals.truncate(self.locals);
+        Outcome::Deopt {
+            pc: ctx.exit_pc as usize,
+            stack: stack.into_iter().map(Value::from_immediate_bits).collect(),
+            locals: locals.into_iter().map(Value::from_immediate_bits).collect(),
+        }
+    }
+}
+
+/// The binary operators compiled, and how
+enum BinaryKind {
+    /// An arithmetic operator, as the opcode of its SSE instruction
+    Arithmetic(u8),
+    /// A comparison, as which operand goes first in `ucomisd` and the condition that's true
+    Compare { swap: bool, cc: u8 },
+    /// `==` or `===` on numbers
+    Equal,
+    /// `!=` or `!==` on numbers
+    NotEqual,
+}
+
+/// How a binary operator is compiled, if it is
+fn binary_kind(op: OperatorKind) -> Option<BinaryKind> {
+    Some(match op {
+        OperatorKind::Addition | OperatorKind::AddAssign => BinaryKind::Arithmetic(0x58),
+        OperatorKind::Subtraction | OperatorKind::SubtractAssign => BinaryKind::Arithmetic(0x5C),
+        OperatorKind::Multiplication | OperatorKind::MultiplyAssign => BinaryKind::Arithmetic(0x59),
+        OperatorKind::Division | OperatorKind::DivideAssign => BinaryKind::Arithmetic(0x5E),
+        // `ucomisd` sets the flags as an unsigned comparison would, and as "below" when either is `NaN`
+        OperatorKind::Lesser => BinaryKind::Compare {
+            swap: true,
+            cc: CC_A,
+        },
+        OperatorKind::LesserEquals => BinaryKind::Compare {
+            swap: true,
+            cc: CC_AE,
+        },
+        OperatorKind::Greater => BinaryKind::Compare {
+            swap: false,
+            cc: CC_A,
+        },
+        OperatorKind::GreaterEquals => BinaryKind::Compare {
+            swap: false,
+            cc: CC_AE,
+        },
+        OperatorKind::Equality | OperatorKind::StrictEquality => BinaryKind::Equal,
+        OperatorKind::Inequality | OperatorKind::StrictInequality => BinaryKind::NotEqual,
+        _ => return None,
+    })
+}
+
+/// The bits of a constant, if it's compiled
+fn constant_bits(constant: &ValueData) -> Option<u64> {
+    Value::new(constant.clone()).immediate_bits()
+}
+
+/// The values an instruction pops and pushes and the instructions that can run after it, if it's compiled
+fn stack_effect(chunk: &Chunk, pc: usize) -> Option<(u32, u32, Vec<usize>)> {
+    let next = vec![pc + 1];
+    Some(match chunk.code[pc] {
+        Op::Undefined | Op::Arg(_) | Op::GetLocal(_) => (0, 1, next),
+        Op::Const(i) => {
+            constant_bits(&chunk.constants[i as usize])?;
+            (0, 1, next)
+        }
+        Op::Pop => (1, 0, next),
+        Op::Dup => (1, 2, next),
+        Op::Dup2 => (2, 4, next),
+        Op::Rot3 => (3, 3, next),
+        Op::Rot4 => (4, 4, next),
+        Op::SetLocal(_) => (1, 1, next),
+        Op::Binary(op) => {
+            binary_kind(op)?;
+            (2, 1, next)
+        }
+        Op::Not | Op::Neg | Op::Plus | Op::ToNumeric | Op::Inc | Op::Dec => (1, 1, next),
+        Op::Jump(target) => (0, 0, vec![target as usize]),
+        Op::JumpIfFalse(target) => (1, 0, vec![pc + 1, target as usize]),
+        // These pop the value only when they don't jump, which `compile` allows for
+        Op::JumpIfFalseOrPop(target) | Op::JumpIfTrueOrPop(target) => {
+            (1, 0, vec![pc + 1, target as usize])
+        }
+        Op::JumpIfNotUndefined(target) => (1, 1, vec![pc + 1, target as usize]),
+        Op::Return => (1, 0, Vec::new()),
+        _ => return None,
+    })
+}
+
+/// The height of the stack before each instruction that can be reached without leaving machine code
+fn stack_depths(chunk: &Chunk) -> Option<Vec<Option<u32>>> {
+    let mut depths = vec![None; chunk.code.len()];
+    let mut pending = vec![(0, 0)];
+    while let Some((pc, depth)) = pending.pop() {
+        if pc >= chunk.code.len() {
+            return None;
+        }
+        match depths[pc] {
+            Some(known) if known == depth => continue,
+            // The stack has to be the same height however an instruction is reached
+            Some(_) => return None,
+            None => depths[pc] = Some(depth),
+        }
+        let (pops, pushes, next) = match stack_effect(chunk, pc) {
+            Some(effect) => effect,
+            None => continue,
+        };
+        if depth < pops {
+            return None;
+        }
+        let after = depth - pops + pushes;
+        for target in next {
+            let kept = match chunk.code[pc] {
+                Op::JumpIfFalseOrPop(t) | Op::JumpIfTrueOrPop(t) if target == t as usize => depth,
+                _ => after,
+            };
+            pending.push((target, kept));
+        }
+    }
+    Some(depths)
+}
+
+/// The place of a stack entry
+fn slot(index: u32) -> i32 {
+    8 * index as i32
+}
+
+/// Compiles the instructions of a chunk
+struct Compiler<'a> {
+    chunk: &'a Chunk,
+    asm: Assembler,
+    /// The code of each instruction
+    labels: Vec<Label>,
+    /// Where code goes back to the interpreter before each instruction: a guard's exit, which
+    /// takes back the count of the instruction, and the exit of an instruction that isn't compiled
+    exits: Vec<(Label, Label)>,
+    epilogue: Label,
+    thrown: Label,
+}
+
+impl<'a> Compiler<'a> {
+    /// Jump to the interpreter, for the instruction at `pc`, if `reg` isn't a number
+    fn guard_number(&mut self, pc: usize, reg: u8) {
+        self.asm.mov_imm(RDX, TAG_UNDEFINED);
+        self.asm.cmp(reg, RDX);
+        self.asm.jcc(CC_AE, self.exits[pc].0);
+    }
+
+    /// Replace a `NaN` in `xmm0` with the canonical one, leaving the number in `rax`
+    fn canonical_result(&mut self) {
+        let done = self.asm.label();
+        self.asm.ucomisd(0, 0);
+        self.asm.movq_from_xmm(RAX, 0);
+        self.asm.jcc(CC_NP, done);
+        self.asm.mov_imm(RAX, CANONICAL_NAN);
+        self.asm.bind(done);
+    }
+
+    /// Turn `al` into a boolean value in `rax`
+    fn boolean_result(&mut self) {
+        self.asm.bytes(&[0x0F, 0xB6, 0xC0]); // movzx eax, al
+        self.asm.mov_imm(RDX, TAG_BOOLEAN);
+        self.asm.or(RAX, RDX);
+    }
+
+    /// Set `al` to whether the value in `rax` is truthy, or go to the interpreter if it isn't a primitive
+    fn truthiness(&mut self, pc: usize) {
+        let (truthy, falsy, done) = (self.asm.label(), self.asm.label(), self.asm.label());
+        self.asm.mov_imm(RDX, TAG_BOOLEAN | 1);
+        self.asm.cmp(RAX, RDX);
+        self.asm.jcc(CC_E, truthy);
+        for &bits in &[TAG_BOOLEAN, TAG_UNDEFINED, TAG_NULL] {
+            self.asm.mov_imm(RDX, bits);
+            self.asm.cmp(RAX, RDX);
+            self.asm.jcc(CC_E, falsy);
+        }
+        self.guard_number(pc, RAX);
+        // `0`, `-0` and `NaN` compare as equal to zero
+        self.asm.movq_to_xmm(0, RAX);
+        self.asm.bytes(&[0x66, 0x0F, 0x57, 0xC9]); // xorpd xmm1, xmm1
+        self.asm.ucomisd(0, 1);
+        self.asm.setcc(CC_NE, RAX);
+        self.asm.jmp(done);
+        self.asm.bind(truthy);
+        self.asm.bytes(&[0xB0, 0x01]); // mov al, 1
+        self.asm.jmp(done);
+        self.asm.bind(falsy);
+        self.asm.bytes(&[0x31, 0xC0]); // xor eax, eax
+        self.asm.bind(done);
+    }
+
+    /// Tell the interpreter about the instructions run, if it's time to, stopping if it says to
+    fn poll(&mut self) {
+        let skip = self.asm.label();
+        self.asm.cmp_mem(R13, RBX, CTX_POLL_EVERY);
+        self.asm.jcc(CC_B, skip);
+        self.asm.mov(RDI, RBX);
+        self.asm.mov(RSI, R13);
+        let poll: extern "sysv64" fn(*mut Context, u64) -> u64 = poll;
+        self.asm.mov_imm(RAX, poll as usize as u64);
+        self.asm.bytes(&[0xFF, 0xD0]); // call rax
+        self.asm.bytes(&[0x45, 0x31, 0xED]); // xor r13d, r13d
+        self.asm.bytes(&[0x48, 0x85, 0xC0]); // test rax, rax
+        self.asm.jcc(CC_NE, self.thrown);
+        self.asm.bind(skip);
+    }
+
+    /// Compile an instruction, with the stack `depth` high before it
+    fn instruction(&mut self, pc: usize, depth: u32) {
+        let op = self.chunk.code[pc];
+        let exit = self.exits[pc].1;
+        if stack_effect(self.chunk, pc).is_none() {
+            self.asm.jmp(exit);
+            return;
+        }
+        match op {
+            // Loops check for interrupts and limits on the way back, before the jump is counted
+            Op::Jump(target)
+            | Op::JumpIfFalse(target)
+            | Op::JumpIfFalseOrPop(target)
+            | Op::JumpIfTrueOrPop(target)
+            | Op::JumpIfNotUndefined(target)
+                if target as usize <= pc =>
+            {
+                self.poll()
+            }
+            _ => (),
+        }
+        self.asm.bytes(&[0x49, 0xFF, 0xC5]); // inc r13
+        let top = depth.wrapping_sub(1);
+        match op {
+            Op::Undefined => {
+                self.asm.mov_imm(RAX, TAG_UNDEFINED);
+                self.asm.store(R12, slot(depth), RAX);
+            }
+            Op::Const(i) => {
+                let bits = constant_bits(&self.chunk.constants[i as usize]).unwrap();
+                self.asm.mov_imm(RAX, bits);
+                self.asm.store(R12, slot(depth), RAX);
+            }
+            Op::Arg(i) => {
+                let (missing, done) = (self.asm.label(), self.asm.label());
+                self.asm.load(RAX, RBX, CTX_ARGC);
+                self.asm.code.extend_from_slice(&[0x48, 0x3D]); // cmp rax, imm32
+                self.asm.imm32(i as i32);
+                self.asm.jcc(CC_BE, missing);
+                self.asm.load(RAX, R15, slot(i));
+                // An argument on the heap would need its handle counted
+                self.asm.mov(RCX, RAX);
+                self.asm.bytes(&[0x48, 0xC1, 0xE9, 0x30]); // shr rcx, 48
+                self.asm.bytes(&[0x81, 0xF9]); // cmp ecx, imm32
+                self.asm.imm32((TAG_HEAP >> 48) as i32);
+                self.asm.jcc(CC_E, self.exits[pc].0);
+                self.asm.jmp(done);
+                self.asm.bind(missing);
+                self.asm.mov_imm(RAX, TAG_UNDEFINED);
+                self.asm.bind(done);
+                self.asm.store(R12, slot(depth), RAX);
+            }
+            Op::Pop => (),
+            Op::Dup => {
+                self.asm.load(RAX, R12, slot(top));
+                self.asm.store(R12, slot(depth), RAX);
+            }
+            Op::Dup2 => {
+                self.asm.load(RAX, R12, slot(depth - 2));
+                self.asm.load(RCX, R12, slot(depth - 1));
+                self.asm.store(R12, slot(depth), RAX);
+                self.asm.store(R12, slot(depth + 1), RCX);
+            }
+            Op::Rot3 => {
+                self.asm.load(RAX, R12, slot(depth - 3));
+                self.asm.load(RCX, R12, slot(depth - 2));
+                self.asm.load(RDX, R12, slot(depth - 1));
+                self.asm.store(R12, slot(depth - 3), RDX);
+                self.asm.store(R12, slot(depth - 2), RAX);
+                self.asm.store(R12, slot(depth - 1), RCX);
+            }
+            Op::Rot4 => {
+                self.asm.load(RAX, R12, slot(depth - 4));
+                self.asm.load(RCX, R12, slot(depth - 3));
+                self.asm.load(RDX, R12, slot(depth - 2));
+                self.asm.load(RSI, R12, slot(depth - 1));
+                self.asm.store(R12, slot(depth - 4), RSI);
+                self.asm.store(R12, slot(depth - 3), RAX);
+                self.asm.store(R12, slot(depth - 2), RCX);
+                self.asm.store(R12, slot(depth - 1), RDX);
+            }
+            Op::GetLocal(i) => {
+                self.asm.load(RAX, R14, slot(i));
+                self.asm.store(R12, slot(depth), RAX);
+            }
+            Op::SetLocal(i) => {
+                self.asm.load(RAX, R12, slot(top));
+                self.asm.store(R14, slot(i), RAX);
+            }
+            Op::Binary(op) => {
+                self.asm.load(RAX, R12, slot(depth - 2));
+                self.asm.load(RCX, R12, slot(depth - 1));
+                self.guard_number(pc, RAX);
+                self.guard_number(pc, RCX);
+                self.asm.movq_to_xmm(0, RAX);
+                self.asm.movq_to_xmm(1, RCX);
+                match binary_kind(op).unwrap() {
+                    BinaryKind::Arithmetic(opcode) => {
+                        self.asm.sse(opcode, 0, 1);
+                        self.canonical_result();
+                    }
+                    BinaryKind::Compare { swap, cc } => {
+                        if swap {
+                            self.asm.ucomisd(1, 0);
+                        } else {
+                            self.asm.ucomisd(0, 1);
+                        }
+                        self.asm.setcc(cc, RAX);
+                        self.boolean_result();
+                    }
+                    BinaryKind::Equal => {
+                        self.asm.ucomisd(0, 1);
+                        self.asm.setcc(CC_E, RAX);
+                        self.asm.setcc(CC_NP, RCX);
+                        self.asm.bytes(&[0x20, 0xC8]); // and al, cl
+                        self.boolean_result();
+                    }
+                    BinaryKind::NotEqual => {
+                        self.asm.ucomisd(0, 1);
+                        self.asm.setcc(CC_NE, RAX);
+                        self.asm.setcc(CC_P, RCX);
+                        self.asm.bytes(&[0x08, 0xC8]); // or al, cl
+                        self.boolean_result();
+                    }
+                }
+                self.asm.store(R12, slot(depth - 2), RAX);
+            }
+            Op::Not => {
+                self.asm.load(RAX, R12, slot(top));
+                self.truthiness(pc);
+                self.asm.bytes(&[0x34, 0x01]); // xor al, 1
+                self.boolean_result();
+                self.asm.store(R12, slot(top), RAX);
+            }
+            Op::Neg => {
+                self.asm.load(RAX, R12, slot(top));
+                self.guard_number(pc, RAX);
+                self.asm.mov_imm(RDX, 1 << 63);
+                self.asm.xor(RAX, RDX);
+                self.asm.movq_to_xmm(0, RAX);
+                self.canonical_result();
+                self.asm.store(R12, slot(top), RAX);
+            }
+            Op::Plus | Op::ToNumeric => {
+                self.asm.load(RAX, R12, slot(top));
+                self.guard_number(pc, RAX);
+            }
+            Op::Inc | Op::Dec => {
+                self.asm.load(RAX, R12, slot(top));
+                self.guard_number(pc, RAX);
+                self.asm.movq_to_xmm(0, RAX);
+                self.asm.mov_imm(RDX, 1.0f64.to_bits());
+                self.asm.movq_to_xmm(1, RDX);
+                self.asm.sse(if op == Op::Inc { 0x58 } else { 0x5C }, 0, 1);
+                self.canonical_result();
+                self.asm.store(R12, slot(top), RAX);
+            }
+            Op::Jump(target) => {
+                self.asm.jmp(self.labels[target as usize]);
+            }
+            Op::JumpIfFalse(target)
+            | Op::JumpIfFalseOrPop(target)
+            | Op::JumpIfTrueOrPop(target) => {
+                self.asm.load(RAX, R12, slot(top));
+                self.truthiness(pc);
+                self.asm.bytes(&[0x84, 0xC0]); // test al, al
+                let cc = match op {
+                    Op::JumpIfTrueOrPop(_) => CC_NE,
+                    _ => CC_E,
+                };
+                self.asm.jcc(cc, self.labels[target as usize]);
+            }
+            Op::JumpIfNotUndefined(target) => {
+                self.asm.load(RAX, R12, slot(top));
+                self.asm.mov_imm(RDX, TAG_UNDEFINED);
+                self.asm.cmp(RAX, RDX);
+                self.asm.jcc(CC_NE, self.labels[target as usize]);
+            }
+            Op::Return => {
+                self.asm.load(RAX, R12, slot(top));
+                self.asm.store(RBX, CTX_RESULT, RAX);
+                self.asm.store(RBX, CTX_STEPS, R13);
+                self.asm.mov_imm(RAX, STATUS_RETURNED);
+                self.asm.jmp(self.epilogue);
+            }
+            _ => unreachable!("instruction {:?} isn't compiled", op),
+        }
+    }
+
+    /// The code going back to the interpreter before an instruction
+    fn exit(&mut self, pc: usize, depth: u32) {
+        let (guard, exit) = self.exits[pc];
+        self.asm.bind(guard);
+        self.asm.bytes(&[0x49, 0xFF, 0xCD]); // dec r13
+        self.asm.bind(exit);
+        self.asm.mov_imm(RAX, pc as u64);
+        self.asm.store(RBX, CTX_EXIT_PC, RAX);
+        self.asm.mov_imm(RAX, u64::from(depth));
+        self.asm.store(RBX, CTX_EXIT_DEPTH, RAX);
+        self.asm.store(RBX, CTX_STEPS, R13);
+        self.asm.mov_imm(RAX, STATUS_DEOPT);
+        self.asm.jmp(self.epilogue);
+    }
+}
+
+/// Compile a chunk to machine code, if its first instruction is one that's compiled
+pub fn compile(chunk: &Chunk) -> Option<CompiledChunk> {
+    if chunk.code.is_empty() || stack_effect(chunk, 0).is_none() {
+        return None;
+    }
+    let depths = stack_depths(chunk)?;
+    let max_depth = chunk
+        .code
+        .iter()
+        .enumerate()
+        .filter_map(|(pc, _)| {
+            let depth = depths[pc]?;
+            let (pops, pushes, _) = stack_effect(chunk, pc).unwrap_or((0, 0, Vec::new()));
+            Some(depth.max(depth - pops + pushes))
+        })
+        .max()
+        .unwrap_or(0) as usize;
+    let mut asm = Assembler::new();
+    let labels = (0..chunk.code.len()).map(|_| asm.label()).collect();
+    let exits = (0..chunk.code.len())
+        .map(|_| (asm.label(), asm.label()))
+        .collect();
+    let (epilogue, thrown) = (asm.label(), asm.label());
+    let mut compiler = Compiler {
+        chunk,
+        asm,
+        labels,
+        exits,
+        epilogue,
+        thrown,
+    };
+    // Save the registers the calling convention says to keep, and load the context
+    compiler
+        .asm
+        .bytes(&[0x53, 0x41, 0x54, 0x41, 0x55, 0x41, 0x56, 0x41, 0x57]);
+    compiler.asm.mov(RBX, RDI);
+    compiler.asm.load(R12, RBX, CTX_STACK);
+    compiler.asm.load(R14, RBX, CTX_LOCALS);
+    compiler.asm.load(R15, RBX, CTX_ARGS);
+    compiler.asm.bytes(&[0x45, 0x31, 0xED]); // xor r13d, r13d
+    for (pc, depth) in depths.iter().enumerate() {
+        if let Some(depth) = *depth {
+            let label = compiler.labels[pc];
+            compiler.asm.bind(label);
+            compiler.instruction(pc, depth);
+        }
+    }
+    for (pc, depth) in depths.iter().enumerate() {
+        if let Some(depth) = *depth {
+            compiler.exit(pc, depth);
+        }
+    }
+    let thrown = compiler.thrown;
+    compiler.asm.bind(thrown);
+    compiler.asm.mov_imm(RAX, STATUS_THROWN);
+    let epilogue = compiler.epilogue;
+    compiler.asm.bind(epilogue);
+    compiler
+        .asm
+        .bytes(&[0x41, 0x5F, 0x41, 0x5E, 0x41, 0x5D, 0x41, 0x5C, 0x5B, 0xC3]);
+    let memory = ExecutableMemory::new(&compiler.asm.finish())?;
+    Some(CompiledChunk {
+        memory,
+        max_depth,
+        locals: chunk.locals.len(),
+    })
+}
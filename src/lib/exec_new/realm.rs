@@ -0,0 +1,117 @@
+use crate::js::object::PROTOTYPE;
+use crate::js::value::{Value, ValueData};
+use crate::js::{
+    array, array_buffer, bigint, boolean, console, data_view, date, error, function, json, math,
+    number, object, string, typed_array,
+};
+#[cfg(feature = "web")]
+use crate::js::{encoding, uri, url};
+
+/// The internal slot of a global object holding the built-in objects its realm was made with, each
+/// in an internal slot of its own, so neither the holder nor its contents can be reached by scripts
+static INTRINSICS: &str = "[[Intrinsics]]";
+
+/// A global object and the built-in objects made along with it, which the code run in it uses
+///
+/// Every realm made on a thread shares its heap, so objects can be passed from one realm to
+/// another. An object keeps the prototypes of the realm it was made in, and a script function
+/// runs in the realm it was made in, whichever realm it's called from. Native functions run in
+/// the realm of the code calling them.
+///
+/// A realm is a handle to its global object, so cloning one gives another handle to the same
+/// realm, and it lives as long as anything can reach its global object.
+#[derive(Clone, Debug)]
+pub struct Realm {
+    global: Value,
+}
+
+impl Default for Realm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Realm {
+    /// Make a realm with a new global object and built-in objects of its own
+    pub fn new() -> Realm {
+        let global = ValueData::new_obj(None);
+        object::init(global.clone());
+        math::init(global.clone());
+        number::init(global.clone());
+        array::init(global.clone());
+        array_buffer::init(global.clone());
+        data_view::init(global.clone());
+        typed_array::init(global.clone());
+        bigint::init(global.clone());
+        boolean::init(global.clone());
+        date::init(global.clone());
+        error::init(global.clone());
+        function::init(global.clone());
+        json::init(global.clone());
+        #[cfg(feature = "web")]
+        {
+            encoding::init(global.clone());
+            uri::init(global.clone());
+            url::init(global.clone());
+        }
+        console::init(global.clone());
+        string::init(global.clone());
+        global.set_field_slice("globalThis", global.clone());
+        // Scripts can replace the built-ins on the global object, but not the ones the engine uses
+        let intrinsics = ValueData::new_obj(None);
+        if let ValueData::Object(ref obj) = *global.data() {
+            for (key, prop) in obj.borrow().iter() {
+                let value = &prop.value;
+                if !value.is_object() && !value.is_function() {
+                    continue;
+                }
+                intrinsics.set_internal_slot(&key.to_string(), value.clone());
+                let prototype = value.get_field_slice(PROTOTYPE);
+                if prototype.is_object() {
+                    intrinsics.set_internal_slot(&format!("{}.prototype", key), prototype);
+                }
+            }
+        }
+        global.set_internal_slot(INTRINSICS, intrinsics);
+        Realm { global }
+    }
+
+    /// The realm a global object belongs to, or `None` if the value isn't the global object of a realm
+    pub fn from_global(global: &Value) -> Option<Realm> {
+        if global.get_internal_slot(INTRINSICS).is_object() {
+            Some(Realm {
+                global: global.clone(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The global object
+    pub fn global(&self) -> &Value {
+        &self.global
+    }
+
+    /// A built-in object as the realm was made with it, like `Array` or `Array.prototype`, or
+    /// `undefined` if there's no built-in by that name
+    pub fn intrinsic(&self, name: &str) -> Value {
+        self.global
+            .get_internal_slot(INTRINSICS)
+            .get_internal_slot(name)
+    }
+
+    /// The prototype of the objects a built-in constructor makes, like `Array.prototype` for `Array`
+    pub fn prototype(&self, constructor: &str) -> Value {
+        self.intrinsic(&format!("{}.prototype", constructor))
+    }
+
+    /// Make an object inheriting from the realm's `Object.prototype`
+    pub fn new_object(&self) -> Value {
+        ValueData::new_obj_from_prototype(self.prototype("Object"))
+    }
+
+    /// If this is the same realm as another
+    pub fn same_realm(&self, other: &Realm) -> bool {
+        self.global.same_heap_cell(&other.global)
+    }
+}
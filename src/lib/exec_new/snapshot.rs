@@ -0,0 +1,773 @@
+//! Snapshots of the heap of a realm, to start interpreters from without running the scripts that made it
+//!
+//! A snapshot holds every object, function and closure the global object of a realm reaches,
+//! along with the source text of the scripts its functions were written in. Restoring one runs
+//! nothing, but it does parse those scripts again and compile every function written in them.
+//!
+//! Compiled code isn't written to snapshots, as functions keep pointing into the syntax trees of
+//! their scripts. So unlike the objects, which are only read back, the functions cost time to
+//! restore in proportion to the source text of their scripts: a realm whose scripts are large
+//! but quick to run saves less by being snapshotted than one whose scripts do a lot of work.
+//!
+//! Native functions are written as their place among the built-in ones, so a snapshot can only
+//! be restored by a build of the engine with the same built-in objects, which is checked along
+//! with the version of the engine and the format of the snapshot.
+
+use crate::exec_new::bytecode::Chunk;
+use crate::exec_new::compiler::{compile_function, compile_script};
+use crate::exec_new::realm::Realm;
+use crate::exec_new::Script;
+use crate::gc::GcCell;
+use crate::js::atom::{Atom, ToAtom};
+use crate::js::function::{
+    call_bound, new_function_value, Function, FunctionCode, NativeBody, NativeFunction,
+    NativeFunctionData, NewRegularFunction,
+};
+use crate::js::js_string::JsString;
+use crate::js::object::{ObjectData, Property};
+use crate::js::value::{to_value, Value, ValueData};
+use num_bigint::BigInt;
+use ratel::ast::expression::ArrowBody;
+use ratel::ast::PatternList;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error;
+use std::fmt;
+use std::rc::Rc;
+
+/// The bytes every snapshot starts with
+const MAGIC: &[u8; 8] = b"BOASNAP\0";
+/// The version of the format snapshots are written in, which changes whenever the format does
+const FORMAT_VERSION: u32 = 2;
+/// The version of the engine, as snapshots record it
+const ENGINE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Why a realm couldn't be written to a snapshot, or a snapshot couldn't be restored
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnapshotError {
+    /// The realm reaches something a snapshot can't hold, like a native closure
+    Unsupported(String),
+    /// The bytes aren't a snapshot
+    NotASnapshot,
+    /// The snapshot was made by another version of the engine, or one with other built-in objects
+    VersionMismatch(String),
+    /// The snapshot is damaged or cut short
+    Corrupt(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SnapshotError::Unsupported(ref what) => write!(f, "Can't snapshot {}", what),
+            SnapshotError::NotASnapshot => write!(f, "Not a snapshot"),
+            SnapshotError::VersionMismatch(ref why) => write!(f, "Mismatched snapshot: {}", why),
+            SnapshotError::Corrupt(ref why) => write!(f, "Corrupt snapshot: {}", why),
+        }
+    }
+}
+
+impl error::Error for SnapshotError {}
+
+/// Hash some bytes with FNV-1a, carrying on from an earlier hash
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100_0000_01b3)
+    })
+}
+
+/// Where FNV-1a hashes start
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Copy something out of the object data of an object or function
+fn object_data<T: Default>(value: &Value, copy: impl Fn(&ObjectData) -> T) -> T {
+    match *value.data() {
+        ValueData::Object(ref obj) => copy(&obj.borrow()),
+        ValueData::Function(ref func) => match *func.borrow() {
+            Function::NativeFunc(ref f) => copy(&f.object),
+            Function::RegularFunc(ref f) => copy(&f.object),
+            Function::NewRegularFunc(ref f) => copy(&f.object),
+        },
+        _ => T::default(),
+    }
+}
+
+/// The properties of an object or function
+fn properties(value: &Value) -> Vec<(Atom, Property)> {
+    object_data(value, |obj| {
+        obj.iter().map(|(key, prop)| (*key, prop.clone())).collect()
+    })
+}
+
+/// The internal slots of an object or function
+fn internal_slots(value: &Value) -> Vec<(Atom, Value)> {
+    object_data(value, |obj| {
+        obj.internal_slots()
+            .map(|(key, slot)| (key, slot.clone()))
+            .collect()
+    })
+}
+
+/// The native functions snapshots refer to by number, in an order every build with the same built-in objects agrees on
+struct Natives {
+    functions: Vec<NativeFunctionData>,
+    /// The number of each function, by its address
+    numbers: HashMap<usize, u32>,
+    /// A hash of where each function is found, which differs between builds with other built-in objects
+    fingerprint: u64,
+}
+
+impl Natives {
+    /// Find the native functions of a new realm, in the order its properties reach them
+    fn find() -> Natives {
+        let mut natives = Natives {
+            functions: Vec::new(),
+            numbers: HashMap::new(),
+            fingerprint: FNV_OFFSET,
+        };
+        let realm = Realm::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back((realm.global().clone(), "globalThis".to_string()));
+        while let Some((value, path)) = queue.pop_front() {
+            if !seen.insert(value.heap_address()) {
+                continue;
+            }
+            if let ValueData::Function(ref func) = *value.data() {
+                if let Function::NativeFunc(NativeFunction {
+                    data: NativeBody::Fn(data),
+                    ..
+                }) = *func.borrow()
+                {
+                    natives.add(data, &path);
+                }
+            }
+            for (key, prop) in properties(&value) {
+                for child in &[prop.value, prop.get, prop.set] {
+                    if child.is_object() || child.is_function() {
+                        queue.push_back((child.clone(), format!("{}.{}", path, key)));
+                    }
+                }
+            }
+            for (key, slot) in internal_slots(&value) {
+                if slot.is_object() || slot.is_function() {
+                    queue.push_back((slot, format!("{}.{}", path, key)));
+                }
+            }
+        }
+        // Functions made as scripts run, rather than with the realm
+        natives.add(call_bound, "[[BoundFunction]]");
+        natives
+    }
+
+    /// Add a function, found at a path of property keys
+    fn add(&mut self, data: NativeFunctionData, path: &str) {
+        let address = data as usize;
+        if !self.numbers.contains_key(&address) {
+            self.numbers.insert(address, self.functions.len() as u32);
+            self.functions.push(data);
+            self.fingerprint = fnv1a(self.fingerprint, path.as_bytes());
+            self.fingerprint = fnv1a(self.fingerprint, b"\n");
+        }
+    }
+}
+
+thread_local! {
+    /// The native functions, found the first time a snapshot is made or restored on the thread
+    static NATIVES: Rc<Natives> = Rc::new(Natives::find());
+}
+
+/// The kinds of things on the heap a snapshot holds
+const OBJECT: u8 = 0;
+const NATIVE_FUNCTION: u8 = 1;
+const SCRIPT_FUNCTION: u8 = 2;
+const SLOTS: u8 = 3;
+const BYTES: u8 = 4;
+
+/// The kinds of values a snapshot holds
+const UNDEFINED: u8 = 0;
+const NULL: u8 = 1;
+const FALSE: u8 = 2;
+const TRUE: u8 = 3;
+const NUMBER: u8 = 4;
+const STRING: u8 = 5;
+const BIGINT: u8 = 6;
+const CELL: u8 = 7;
+
+/// The flags of a script function
+const ARROW: u8 = 1;
+const STRICT: u8 = 2;
+const COMPILED: u8 = 4;
+
+/// The flags of a property
+const CONFIGURABLE: u8 = 1;
+const ENUMERABLE: u8 = 2;
+const WRITABLE: u8 = 4;
+
+/// The heap of a realm being written to a snapshot
+struct Writer {
+    out: Vec<u8>,
+    natives: Rc<Natives>,
+    /// The objects, functions and other things on the heap found so far, in the order they're written
+    cells: Vec<Value>,
+    /// The number of each cell, by its address on the heap
+    numbers: HashMap<usize, u32>,
+    /// The scripts the functions found so far were written in
+    scripts: Vec<Rc<Script>>,
+    /// The number of each script, by its address
+    script_numbers: HashMap<usize, u32>,
+}
+
+impl Writer {
+    /// Find a value on the heap, if it's something that's written as a cell, giving it a number
+    fn find(&mut self, value: &Value) {
+        match *value.data() {
+            ValueData::Object(_)
+            | ValueData::Function(_)
+            | ValueData::Slots(_)
+            | ValueData::Bytes(_)
+            | ValueData::Host(_) => (),
+            _ => return,
+        }
+        let address = value.heap_address().expect("cells are on the heap");
+        if !self.numbers.contains_key(&address) {
+            self.numbers.insert(address, self.cells.len() as u32);
+            self.cells.push(value.clone());
+        }
+    }
+
+    /// Give a script a number
+    fn find_script(&mut self, script: &Rc<Script>) -> u32 {
+        let address = &**script as *const Script as usize;
+        if let Some(number) = self.script_numbers.get(&address) {
+            return *number;
+        }
+        let number = self.scripts.len() as u32;
+        self.script_numbers.insert(address, number);
+        self.scripts.push(script.clone());
+        number
+    }
+
+    /// The values a cell holds, failing if it can't be written
+    fn children(&mut self, cell: &Value) -> Result<Vec<Value>, SnapshotError> {
+        let mut children = Vec::new();
+        match *cell.data() {
+            ValueData::Function(ref func) => match *func.borrow() {
+                Function::NativeFunc(ref f) => match f.data {
+                    NativeBody::Fn(data) if self.natives.numbers.contains_key(&(data as usize)) => {
+                    }
+                    NativeBody::Fn(_) => {
+                        return Err(SnapshotError::Unsupported(
+                            "native functions that aren't built in".to_string(),
+                        ))
+                    }
+                    NativeBody::Closure(_) => {
+                        return Err(SnapshotError::Unsupported(
+                            "native closures, like the methods of host classes".to_string(),
+                        ))
+                    }
+                },
+                Function::RegularFunc(_) => {
+                    return Err(SnapshotError::Unsupported(
+                        "functions made by the old interpreter".to_string(),
+                    ))
+                }
+                Function::NewRegularFunc(ref f) => {
+                    self.find_script(&f.code.script);
+                    children.push(f.env.clone());
+                    children.push(f.this.clone());
+                    children.push(f.realm.clone());
+                }
+            },
+            ValueData::Slots(ref slots) => children.extend(slots.borrow().iter().cloned()),
+            ValueData::Host(_) => {
+                return Err(SnapshotError::Unsupported(
+                    "the Rust data of objects made by host classes".to_string(),
+                ))
+            }
+            _ => (),
+        }
+        for (_, prop) in properties(cell) {
+            children.push(prop.value);
+            children.push(prop.get);
+            children.push(prop.set);
+        }
+        children.extend(internal_slots(cell).into_iter().map(|(_, slot)| slot));
+        Ok(children)
+    }
+
+    fn u8(&mut self, n: u8) {
+        self.out.push(n);
+    }
+
+    fn u32(&mut self, n: u32) {
+        self.out.extend_from_slice(&n.to_le_bytes());
+    }
+
+    fn u64(&mut self, n: u64) {
+        self.out.extend_from_slice(&n.to_le_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len() as u32);
+        self.out.extend_from_slice(bytes);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.bytes(s.as_bytes());
+    }
+
+    /// Write a value, as itself if it's a primitive or the number of its cell otherwise
+    fn value(&mut self, value: &Value) {
+        match *value.data() {
+            ValueData::Undefined => self.u8(UNDEFINED),
+            ValueData::Null => self.u8(NULL),
+            ValueData::Boolean(false) => self.u8(FALSE),
+            ValueData::Boolean(true) => self.u8(TRUE),
+            ValueData::Number(n) => {
+                self.u8(NUMBER);
+                self.u64(n.to_bits());
+            }
+            ValueData::Integer(n) => {
+                self.u8(NUMBER);
+                self.u64(f64::from(n).to_bits());
+            }
+            ValueData::String(ref s) => {
+                self.u8(STRING);
+                self.u32(s.len() as u32);
+                for unit in s.code_units() {
+                    self.out.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            ValueData::BigInt(ref n) => {
+                self.u8(BIGINT);
+                self.bytes(&n.to_signed_bytes_le());
+            }
+            _ => {
+                self.u8(CELL);
+                let address = value.heap_address().expect("cells are on the heap");
+                let number = self.numbers[&address];
+                self.u32(number);
+            }
+        }
+    }
+
+    /// Write the properties of an object or function, then its internal slots
+    fn properties(&mut self, cell: &Value) {
+        let props = properties(cell);
+        self.u32(props.len() as u32);
+        for (key, prop) in props {
+            match key.as_index() {
+                Some(index) => {
+                    self.u8(1);
+                    self.u32(index);
+                }
+                None => {
+                    self.u8(0);
+                    self.str(&key.as_str());
+                }
+            }
+            let flags = (if prop.configurable { CONFIGURABLE } else { 0 })
+                | (if prop.enumerable { ENUMERABLE } else { 0 })
+                | (if prop.writable { WRITABLE } else { 0 });
+            self.u8(flags);
+            self.value(&prop.value);
+            self.value(&prop.get);
+            self.value(&prop.set);
+        }
+        let slots = internal_slots(cell);
+        self.u32(slots.len() as u32);
+        for (key, slot) in slots {
+            self.str(&key.as_str());
+            self.value(&slot);
+        }
+    }
+
+    /// Write what a cell is, which is all that's needed to make it before what it holds is known
+    fn kind(&mut self, cell: &Value) {
+        match *cell.data() {
+            ValueData::Function(ref func) => match *func.borrow() {
+                Function::NativeFunc(NativeFunction {
+                    data: NativeBody::Fn(data),
+                    ..
+                }) => {
+                    self.u8(NATIVE_FUNCTION);
+                    let number = self.natives.numbers[&(data as usize)];
+                    self.u32(number);
+                }
+                Function::NewRegularFunc(ref f) => {
+                    self.u8(SCRIPT_FUNCTION);
+                    let script = self.find_script(&f.code.script);
+                    self.u32(script);
+                    self.u32(f.code.start);
+                    self.u32(f.code.end);
+                    let flags = (if f.code.arrow { ARROW } else { 0 })
+                        | (if f.code.strict { STRICT } else { 0 })
+                        | (if f.code.chunk.borrow().is_some() {
+                            COMPILED
+                        } else {
+                            0
+                        });
+                    self.u8(flags);
+                }
+                _ => unreachable!("unsupported functions fail when they're found"),
+            },
+            ValueData::Slots(_) => self.u8(SLOTS),
+            ValueData::Bytes(ref bytes) => {
+                self.u8(BYTES);
+                self.bytes(&bytes.borrow());
+            }
+            _ => self.u8(OBJECT),
+        }
+    }
+
+    /// Write what a cell holds
+    fn contents(&mut self, cell: &Value) {
+        match *cell.data() {
+            ValueData::Function(ref func) => {
+                if let Function::NewRegularFunc(ref f) = *func.borrow() {
+                    self.value(&f.env);
+                    self.value(&f.this);
+                    self.value(&f.realm);
+                }
+            }
+            ValueData::Slots(ref slots) => {
+                let slots = slots.borrow();
+                self.u32(slots.len() as u32);
+                for slot in slots.iter() {
+                    self.value(slot);
+                }
+            }
+            ValueData::Bytes(_) => return,
+            _ => (),
+        }
+        self.properties(cell);
+    }
+}
+
+/// Reads a snapshot, failing if it ends early
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.bytes.len() - self.pos < len {
+            return Err(SnapshotError::Corrupt("it ends early".to_string()));
+        }
+        let taken = &self.bytes[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], SnapshotError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, SnapshotError> {
+        String::from_utf8(self.bytes()?.to_vec())
+            .map_err(|_| SnapshotError::Corrupt("a name isn't UTF-8".to_string()))
+    }
+
+    /// Read a value, given the cells it can be one of
+    fn value(&mut self, cells: &[Value]) -> Result<Value, SnapshotError> {
+        Ok(match self.u8()? {
+            UNDEFINED => Value::undefined(),
+            NULL => Value::null(),
+            FALSE => Value::boolean(false),
+            TRUE => Value::boolean(true),
+            NUMBER => Value::number(f64::from_bits(self.u64()?)),
+            STRING => {
+                let len = self.u32()? as usize;
+                let units = self
+                    .take(len * 2)?
+                    .chunks(2)
+                    .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                    .collect();
+                Value::new(ValueData::String(JsString::from_utf16(units)))
+            }
+            BIGINT => to_value(BigInt::from_signed_bytes_le(self.bytes()?)),
+            CELL => {
+                let number = self.u32()? as usize;
+                cells
+                    .get(number)
+                    .cloned()
+                    .ok_or_else(|| SnapshotError::Corrupt("a value isn't in it".to_string()))?
+            }
+            kind => {
+                return Err(SnapshotError::Corrupt(format!(
+                    "unknown value kind {}",
+                    kind
+                )))
+            }
+        })
+    }
+
+    /// Read the properties of an object or function into it, then its internal slots
+    fn properties(&mut self, cell: &Value, cells: &[Value]) -> Result<(), SnapshotError> {
+        for _ in 0..self.u32()? {
+            let key = match self.u8()? {
+                0 => self.string()?.to_atom(),
+                _ => self.u32()?.to_atom(),
+            };
+            let flags = self.u8()?;
+            let prop = Property {
+                configurable: flags & CONFIGURABLE != 0,
+                enumerable: flags & ENUMERABLE != 0,
+                writable: flags & WRITABLE != 0,
+                value: self.value(cells)?,
+                get: self.value(cells)?,
+                set: self.value(cells)?,
+            };
+            cell.set_prop(key, prop);
+        }
+        for _ in 0..self.u32()? {
+            let name = self.string()?;
+            let slot = self.value(cells)?;
+            cell.set_internal_slot(&name, slot);
+        }
+        Ok(())
+    }
+}
+
+/// A function written in a script, compiled
+struct CompiledFunction {
+    params: PatternList<'static>,
+    body: ArrowBody<'static>,
+    chunk: Rc<Chunk>,
+}
+
+/// Compile the functions written in some compiled code, and those written in them, by where they start
+fn compile_functions(chunk: &Chunk, script: &Script, found: &mut HashMap<u32, CompiledFunction>) {
+    for template in &chunk.functions {
+        let compiled = compile_function(&template.params, &template.body, script.resolution());
+        compile_functions(&compiled, script, found);
+        found.insert(
+            template.loc.0,
+            CompiledFunction {
+                params: template.params,
+                body: template.body,
+                chunk: Rc::new(compiled),
+            },
+        );
+    }
+}
+
+impl Realm {
+    /// Write everything the global object reaches to a snapshot, which `from_snapshot` makes a realm from
+    ///
+    /// Native closures, like the methods of host classes, and the Rust data of objects made by
+    /// them can't be written, so the realm mustn't reach any.
+    pub fn snapshot(&self) -> Result<Vec<u8>, SnapshotError> {
+        let mut writer = Writer {
+            out: Vec::new(),
+            natives: NATIVES.with(|natives| natives.clone()),
+            cells: Vec::new(),
+            numbers: HashMap::new(),
+            scripts: Vec::new(),
+            script_numbers: HashMap::new(),
+        };
+        writer.find(self.global());
+        let mut next = 0;
+        while next < writer.cells.len() {
+            let cell = writer.cells[next].clone();
+            for child in writer.children(&cell)? {
+                writer.find(&child);
+            }
+            next += 1;
+        }
+        let scripts = writer.scripts.clone();
+        writer.u32(scripts.len() as u32);
+        for script in &scripts {
+            writer.str(script.name());
+            writer.str(script.source());
+        }
+        let cells = writer.cells.clone();
+        writer.u32(cells.len() as u32);
+        for cell in &cells {
+            writer.kind(cell);
+        }
+        for cell in &cells {
+            writer.contents(cell);
+        }
+        writer.value(self.global());
+        let body = writer.out;
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&(ENGINE_VERSION.len() as u32).to_le_bytes());
+        out.extend_from_slice(ENGINE_VERSION.as_bytes());
+        out.extend_from_slice(&writer.natives.fingerprint.to_le_bytes());
+        out.extend_from_slice(&fnv1a(FNV_OFFSET, &body).to_le_bytes());
+        out.extend_from_slice(&body);
+        Ok(out)
+    }
+
+    /// Make a realm from a snapshot `snapshot` wrote, without running any scripts
+    ///
+    /// Fails if the snapshot was made by another version of the engine or one with other
+    /// built-in objects, or if it's been damaged.
+    ///
+    /// Snapshots don't hold compiled code, so the scripts the functions of the realm were written
+    /// in are parsed and compiled again, and restoring takes longer the more source text they have.
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Realm, SnapshotError> {
+        if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let mut reader = Reader {
+            bytes,
+            pos: MAGIC.len(),
+        };
+        let format = reader.u32()?;
+        if format != FORMAT_VERSION {
+            return Err(SnapshotError::VersionMismatch(format!(
+                "it's in format {}, not {}",
+                format, FORMAT_VERSION
+            )));
+        }
+        let version = reader.string()?;
+        if version != ENGINE_VERSION {
+            return Err(SnapshotError::VersionMismatch(format!(
+                "it was made by version {} of the engine, not {}",
+                version, ENGINE_VERSION
+            )));
+        }
+        let natives = NATIVES.with(|natives| natives.clone());
+        if reader.u64()? != natives.fingerprint {
+            return Err(SnapshotError::VersionMismatch(
+                "it was made with other built-in objects".to_string(),
+            ));
+        }
+        let checksum = reader.u64()?;
+        if fnv1a(FNV_OFFSET, &bytes[reader.pos..]) != checksum {
+            return Err(SnapshotError::Corrupt(
+                "its checksum doesn't match".to_string(),
+            ));
+        }
+
+        let mut scripts = Vec::new();
+        for _ in 0..reader.u32()? {
+            let name = reader.string()?;
+            let source = reader.string()?;
+            let script = Script::parse_with_name(&name, &source).map_err(|err| {
+                SnapshotError::Corrupt(format!("the script {} doesn't parse: {}", name, err))
+            })?;
+            scripts.push((script, None));
+        }
+
+        // Every cell is made before any are filled in, as they can hold each other
+        let mut cells = Vec::new();
+        for _ in 0..reader.u32()? {
+            let cell = match reader.u8()? {
+                OBJECT => Value::new(ValueData::Object(GcCell::new(ObjectData::new()))),
+                NATIVE_FUNCTION => {
+                    let number = reader.u32()? as usize;
+                    let data = *natives.functions.get(number).ok_or_else(|| {
+                        SnapshotError::Corrupt(format!("unknown native function {}", number))
+                    })?;
+                    new_function_value(Function::NativeFunc(NativeFunction::new(data)))
+                }
+                SCRIPT_FUNCTION => {
+                    let number = reader.u32()? as usize;
+                    let (start, end, flags) = (reader.u32()?, reader.u32()?, reader.u8()?);
+                    let &mut (ref script, ref mut functions) =
+                        scripts.get_mut(number).ok_or_else(|| {
+                            SnapshotError::Corrupt(format!("unknown script {}", number))
+                        })?;
+                    let functions = functions.get_or_insert_with(|| {
+                        let mut found = HashMap::new();
+                        compile_functions(&compile_script(&script.body()), script, &mut found);
+                        found
+                    });
+                    let function = functions.get(&start).ok_or_else(|| {
+                        SnapshotError::Corrupt(format!(
+                            "no function starts at {} in {}",
+                            start,
+                            script.name()
+                        ))
+                    })?;
+                    let chunk = if flags & COMPILED != 0 {
+                        Some(function.chunk.clone())
+                    } else {
+                        None
+                    };
+                    let code = Rc::new(FunctionCode {
+                        script: script.clone(),
+                        params: function.params,
+                        body: function.body,
+                        start,
+                        end,
+                        arrow: flags & ARROW != 0,
+                        strict: flags & STRICT != 0,
+                        chunk: RefCell::new(chunk),
+                        profile: Default::default(),
+                    });
+                    new_function_value(Function::NewRegularFunc(NewRegularFunction::new(
+                        code,
+                        Value::undefined(),
+                        Value::undefined(),
+                        Value::undefined(),
+                    )))
+                }
+                SLOTS => Value::new(ValueData::Slots(GcCell::new(Vec::new()))),
+                BYTES => {
+                    let bytes = reader.bytes()?.to_vec();
+                    Value::new(ValueData::Bytes(Rc::new(RefCell::new(bytes))))
+                }
+                kind => {
+                    return Err(SnapshotError::Corrupt(format!(
+                        "unknown cell kind {}",
+                        kind
+                    )))
+                }
+            };
+            cells.push(cell);
+        }
+        for cell in &cells {
+            match *cell.data() {
+                ValueData::Function(ref func) => {
+                    if let Function::NewRegularFunc(ref mut f) = *func.borrow_mut() {
+                        f.env = reader.value(&cells)?;
+                        f.this = reader.value(&cells)?;
+                        f.realm = reader.value(&cells)?;
+                    }
+                }
+                ValueData::Slots(ref slots) => {
+                    let len = reader.u32()?;
+                    let mut values = Vec::new();
+                    for _ in 0..len {
+                        values.push(reader.value(&cells)?);
+                    }
+                    *slots.borrow_mut() = values;
+                }
+                ValueData::Bytes(_) => continue,
+                _ => (),
+            }
+            reader.properties(cell, &cells)?;
+        }
+        let global = reader.value(&cells)?;
+        if reader.pos != bytes.len() {
+            return Err(SnapshotError::Corrupt(
+                "there's more after its end".to_string(),
+            ));
+        }
+        Realm::from_global(&global)
+            .ok_or_else(|| SnapshotError::Corrupt("it doesn't hold a global object".to_string()))
+    }
+}
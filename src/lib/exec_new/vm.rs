@@ -0,0 +1,477 @@
+//! Runs compiled code, one instruction at a time
+
+use crate::exec_new::bytecode::{Chunk, Op};
+use crate::exec_new::{binary_op, outer_env, type_of, Interpreter, BLOCK_ENV};
+use crate::js::array::array_create;
+use crate::js::atom::{Atom, ToAtom};
+use crate::js::function::{unmap_argument, Caller};
+use crate::js::object;
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
+use num_bigint::BigInt;
+
+/// A `try` block being run, and the state to go back to when it throws
+struct Handler {
+    /// The instruction to jump to with what was thrown
+    target: usize,
+    /// The height of the stack when the block was entered
+    stack_len: usize,
+    /// The `catch` scopes entered when the block was
+    envs_len: usize,
+    /// The variables of the scope when the block was entered
+    vars: Value,
+}
+
+/// The state of a chunk being run
+struct Activation<'a> {
+    /// The compiled code
+    chunk: &'a Chunk,
+    /// The arguments of the call, for a function body
+    args: &'a [Value],
+    /// The instruction to run next
+    pc: usize,
+    /// The values being worked on
+    stack: Vec<Value>,
+    /// The local slots
+    locals: Vec<Value>,
+    /// The `try` blocks being run, innermost last
+    handlers: Vec<Handler>,
+    /// The variables of the scope outside each `catch` clause being run, innermost last
+    envs: Vec<Value>,
+}
+
+impl<'a> Activation<'a> {
+    /// Pop the value on top
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("The stack of a chunk ran out")
+    }
+
+    /// The value on top
+    fn top(&self) -> &Value {
+        self.stack.last().expect("The stack of a chunk ran out")
+    }
+
+    /// Pop the arguments of a call
+    fn pop_args(&mut self, argc: u32) -> Vec<Value> {
+        let len = self.stack.len() - argc as usize;
+        self.stack.split_off(len)
+    }
+}
+
+impl Interpreter {
+    /// Run a chunk, with the arguments of the call if it's a function body, returning the value it returns
+    pub(super) fn run_chunk(&mut self, chunk: &Chunk, args: &[Value]) -> ResultValue {
+        self.run_activation(Activation {
+            chunk,
+            args,
+            pc: 0,
+            stack: Vec::new(),
+            locals: vec![Value::undefined(); chunk.locals.len()],
+            handlers: Vec::new(),
+            envs: Vec::new(),
+        })
+    }
+
+    /// Carry on running a function body from where machine code left it
+    #[cfg(all(feature = "jit", target_arch = "x86_64", target_os = "linux"))]
+    pub(super) fn resume_chunk(
+        &mut self,
+        chunk: &Chunk,
+        args: &[Value],
+        pc: usize,
+        stack: Vec<Value>,
+        locals: Vec<Value>,
+    ) -> ResultValue {
+        self.run_activation(Activation {
+            chunk,
+            args,
+            pc,
+            stack,
+            locals,
+            handlers: Vec::new(),
+            envs: Vec::new(),
+        })
+    }
+
+    /// Run a chunk from where an activation of it is up to
+    fn run_activation(&mut self, mut activation: Activation) -> ResultValue {
+        loop {
+            let err = match self.dispatch(&mut activation) {
+                Ok(val) => return Ok(val),
+                Err(err) => err,
+            };
+            // A script that's been stopped can't catch it, or run `finally` blocks
+            let handler = match self.termination {
+                None => activation.handlers.pop(),
+                Some(_) => None,
+            };
+            match handler {
+                Some(handler) => {
+                    activation.stack.truncate(handler.stack_len);
+                    activation.envs.truncate(handler.envs_len);
+                    self.scopes.last_mut().unwrap().vars = handler.vars;
+                    activation.stack.push(err);
+                    activation.pc = handler.target;
+                }
+                None => {
+                    if let Some(vars) = activation.envs.into_iter().next() {
+                        self.scopes.last_mut().unwrap().vars = vars;
+                    }
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    /// Work on the slots closures share of a call, `depth` calls with such slots out from the running one
+    fn captured_slots<T>(&self, depth: u32, work: impl FnOnce(&mut Vec<Value>) -> T) -> T {
+        let mut env = self.scope().vars.clone();
+        let mut depth = depth;
+        loop {
+            if let ValueData::Slots(ref slots) = *env.data() {
+                if depth == 0 {
+                    return work(&mut slots.borrow_mut());
+                }
+                depth -= 1;
+            }
+            env = outer_env(&env);
+        }
+    }
+
+    /// Run instructions until the chunk returns or throws
+    fn dispatch(&mut self, a: &mut Activation) -> ResultValue {
+        let chunk = a.chunk;
+        loop {
+            self.step()?;
+            let op = chunk.code[a.pc];
+            let position = chunk.positions[a.pc];
+            a.pc += 1;
+            match op {
+                Op::Undefined => a.stack.push(Value::undefined()),
+                Op::Const(i) => a.stack.push(Value::new(chunk.constants[i as usize].clone())),
+                Op::This => a.stack.push(self.scope().this.clone()),
+                Op::Arg(i) => {
+                    let arg = a.args.get(i as usize).cloned();
+                    a.stack
+                        .push(arg.unwrap_or_else(Value::undefined));
+                }
+                Op::RestArgs(i) => {
+                    let rest = self.new_array(a.args.iter().skip(i as usize).cloned().collect());
+                    a.stack.push(rest);
+                }
+                Op::Pop => {
+                    a.pop();
+                }
+                Op::Dup => {
+                    let top = a.top().clone();
+                    a.stack.push(top);
+                }
+                Op::Dup2 => {
+                    let len = a.stack.len();
+                    let (first, second) = (a.stack[len - 2].clone(), a.stack[len - 1].clone());
+                    a.stack.push(first);
+                    a.stack.push(second);
+                }
+                Op::Rot3 => {
+                    let top = a.pop();
+                    let len = a.stack.len();
+                    a.stack.insert(len - 2, top);
+                }
+                Op::Rot4 => {
+                    let top = a.pop();
+                    let len = a.stack.len();
+                    a.stack.insert(len - 3, top);
+                }
+                Op::GetLocal(i) => a.stack.push(a.locals[i as usize].clone()),
+                Op::SetLocal(i) => a.locals[i as usize] = a.top().clone(),
+                Op::GetCaptured(depth, i) => {
+                    let val = self.captured_slots(depth, |slots| slots[i as usize + 1].clone());
+                    a.stack.push(val);
+                }
+                Op::SetCaptured(depth, i) => {
+                    let val = a.top().clone();
+                    self.captured_slots(depth, |slots| slots[i as usize + 1] = val);
+                }
+                Op::GetName(i) => {
+                    self.set_position(position);
+                    let val = self.get_binding(chunk.names[i as usize])?;
+                    a.stack.push(val);
+                }
+                Op::SetName(i) => {
+                    let val = a.pop();
+                    let val = self.put_binding(chunk.names[i as usize], val)?;
+                    a.stack.push(val);
+                }
+                Op::TypeofName(i) => {
+                    self.set_position(position);
+                    let name = chunk.names[i as usize];
+                    // `typeof` is the one thing a variable that isn't declared can be used with
+                    let kind = match self.env_of(name) {
+                        Some(_) => type_of(&self.get_binding(name)?),
+                        None => "undefined",
+                    };
+                    a.stack.push(to_value(kind));
+                }
+                Op::DeclareVar(i) => {
+                    let val = a.pop();
+                    let name = chunk.names[i as usize];
+                    self.var_env().set_field(name, val);
+                }
+                Op::DeclareVarUndefined(i) => {
+                    let name = chunk.names[i as usize];
+                    let env = self.var_env();
+                    // Declaring a variable again without a value keeps its old one
+                    if env.get_prop(name).is_none() {
+                        env.set_field(name, Value::undefined());
+                    }
+                }
+                Op::DeclareFunction(i) => {
+                    let func = a.pop();
+                    self.var_env()
+                        .set_field(chunk.names[i as usize], func);
+                }
+                Op::SetParam(i) => {
+                    let val = a.pop();
+                    self.scope()
+                        .vars
+                        .set_field(chunk.names[i as usize], val);
+                }
+                Op::GetField(i) => {
+                    let obj = a.pop();
+                    self.set_position(position);
+                    self.check_has_properties(&obj, &chunk.names[i as usize], false)?;
+                    let val = self.get_field(&obj, chunk.names[i as usize])?;
+                    a.stack.push(val);
+                }
+                Op::GetComputed => {
+                    let field = a.pop();
+                    let obj = a.pop();
+                    self.set_position(position);
+                    self.check_has_properties(&obj, &field, false)?;
+                    let val = self.get_field(&obj, field)?;
+                    a.stack.push(val);
+                }
+                Op::SetField(i) => {
+                    let val = a.pop();
+                    let obj = a.pop();
+                    self.set_position(position);
+                    self.check_has_properties(&obj, &chunk.names[i as usize], true)?;
+                    let val = self.put_property(obj, chunk.names[i as usize], val)?;
+                    a.stack.push(val);
+                }
+                Op::SetComputed => {
+                    let val = a.pop();
+                    let field = a.pop();
+                    let obj = a.pop();
+                    self.set_position(position);
+                    self.check_has_properties(&obj, &field, true)?;
+                    let field = self.property_key(&field)?;
+                    let val = self.put_property(obj, field, val)?;
+                    a.stack.push(val);
+                }
+                Op::DeleteField(i) => {
+                    let obj = a.pop();
+                    delete_property(&obj, chunk.names[i as usize]);
+                    a.stack.push(to_value(true));
+                }
+                Op::DeleteComputed => {
+                    let field = a.pop();
+                    let obj = a.pop();
+                    // A key no object has been given needn't be made to delete it
+                    if let Some(field) = field.find_atom() {
+                        delete_property(&obj, field);
+                    }
+                    a.stack.push(to_value(true));
+                }
+                Op::ToPropertyKey => {
+                    let key = a.pop();
+                    a.stack.push(to_value(key.to_string()));
+                }
+                Op::NewObject => a.stack.push(self.realm.new_object()),
+                Op::DefineField(i) => {
+                    let val = a.pop();
+                    a.top().set_field(chunk.names[i as usize], val);
+                }
+                Op::DefineComputed => {
+                    let val = a.pop();
+                    let key = a.pop();
+                    let key = self.property_key(&key)?;
+                    a.top().set_field(key, val);
+                }
+                Op::NameFunction => {
+                    let key = a.stack[a.stack.len() - 2].to_string();
+                    a.top()
+                        .set_prop_slice("name", object::Property::read_only(to_value(key)));
+                }
+                Op::NewArray => {
+                    let prototype = self.realm.prototype("Array");
+                    a.stack.push(array_create(prototype));
+                }
+                Op::ArraySet(i) => {
+                    let val = a.pop();
+                    a.top().set_field(i, val);
+                }
+                Op::ArrayLength(len) => {
+                    a.top().set_field_slice("length", to_value(len as i32));
+                }
+                Op::MakeFunction(i) => {
+                    let template = &chunk.functions[i as usize];
+                    let (params, body, loc) = (template.params, template.body, template.loc);
+                    let func = if template.own_name {
+                        // The name of a function expression is a variable in a scope of its own
+                        let env = self.new_env();
+                        let func = self.make_function(
+                            &template.name,
+                            params,
+                            body,
+                            loc,
+                            false,
+                            env.clone(),
+                        )?;
+                        env.set_field(template.name.clone(), func.clone());
+                        func
+                    } else {
+                        let env = self.scope().vars.clone();
+                        self.make_function(&template.name, params, body, loc, template.arrow, env)?
+                    };
+                    a.stack.push(func);
+                }
+                Op::Binary(op) => {
+                    let right = a.pop();
+                    let left = a.pop();
+                    let result = binary_op(op, &left, &right).map_err(|e| e.into_error(self))?;
+                    a.stack.push(result);
+                }
+                Op::Not => {
+                    let val = a.pop();
+                    a.stack.push(to_value(!val.is_true()));
+                }
+                Op::BitNot => {
+                    let val = a.pop();
+                    a.stack.push(Value::new(val.bitwise_not()));
+                }
+                Op::Plus => {
+                    let val = a.pop();
+                    let n = val.to_number().map_err(|e| e.into_error(self))?;
+                    a.stack.push(to_value(n));
+                }
+                Op::Neg => {
+                    let val = a.pop();
+                    a.stack.push(Value::new(-val.data().clone()));
+                }
+                Op::Typeof => {
+                    let val = a.pop();
+                    a.stack.push(to_value(type_of(&val)));
+                }
+                Op::ToNumeric => {
+                    let val = a.pop();
+                    a.stack.push(match *val.data() {
+                        ValueData::BigInt(_) => val.clone(),
+                        _ => to_value(val.to_num()),
+                    });
+                }
+                Op::Inc | Op::Dec => {
+                    let val = a.pop();
+                    let one = match *val.data() {
+                        ValueData::BigInt(_) => to_value(BigInt::from(1)),
+                        _ => to_value(1.0),
+                    };
+                    let result = if op == Op::Inc {
+                        val.data().clone() + one.data().clone()
+                    } else {
+                        val.data().clone() - one.data().clone()
+                    };
+                    a.stack.push(result.map_err(|e| e.into_error(self))?);
+                }
+                Op::Concat(count) => {
+                    let parts = a.pop_args(count);
+                    let result: String = parts.iter().map(|part| part.to_string()).collect();
+                    a.stack.push(to_value(result));
+                }
+                Op::Jump(target) => a.pc = target as usize,
+                Op::JumpIfFalse(target) => {
+                    if !a.pop().is_true() {
+                        a.pc = target as usize;
+                    }
+                }
+                Op::JumpIfFalseOrPop(target) => {
+                    if a.top().is_true() {
+                        a.pop();
+                    } else {
+                        a.pc = target as usize;
+                    }
+                }
+                Op::JumpIfTrueOrPop(target) => {
+                    if a.top().is_true() {
+                        a.pc = target as usize;
+                    } else {
+                        a.pop();
+                    }
+                }
+                Op::JumpIfNotUndefined(target) => {
+                    if !a.top().is_undefined() {
+                        a.pc = target as usize;
+                    }
+                }
+                Op::Call(argc) | Op::CallEval(argc) => {
+                    let args = a.pop_args(argc);
+                    let func = a.pop();
+                    let this = a.pop();
+                    self.set_position(position);
+                    let result = match op {
+                        // Calling the original `eval` by name runs the code in the scope of the call
+                        Op::CallEval(_) if func.strict_equals(&self.realm.intrinsic("eval")) => match args.first() {
+                            Some(source) if source.is_string() => {
+                                self.run_eval(&source.to_string(), true)?
+                            }
+                            Some(val) => val.clone(),
+                            None => Value::undefined(),
+                        },
+                        _ => self.call(&func, this, args)?,
+                    };
+                    a.stack.push(result);
+                }
+                Op::New(argc) => {
+                    let args = a.pop_args(argc);
+                    let func = a.pop();
+                    self.set_position(position);
+                    a.stack.push(self.construct(&func, args)?);
+                }
+                Op::Throw => return Err(a.pop()),
+                Op::Return => return Ok(a.pop()),
+                Op::EnterTry(target) => a.handlers.push(Handler {
+                    target: target as usize,
+                    stack_len: a.stack.len(),
+                    envs_len: a.envs.len(),
+                    vars: self.scope().vars.clone(),
+                }),
+                Op::LeaveTry => {
+                    a.handlers.pop();
+                }
+                Op::EnterCatch(i) => {
+                    let thrown = a.pop();
+                    let env = self.new_env();
+                    env.set_internal_slot(BLOCK_ENV, to_value(true));
+                    env.set_field(chunk.names[i as usize], thrown);
+                    let outer = std::mem::replace(&mut self.scopes.last_mut().unwrap().vars, env);
+                    a.envs.push(outer);
+                }
+                Op::LeaveCatch => {
+                    let outer = a
+                        .envs
+                        .pop()
+                        .expect("Left a catch clause that wasn't entered");
+                    self.scopes.last_mut().unwrap().vars = outer;
+                }
+                Op::Fail(i) => return Err(Value::new(chunk.constants[i as usize].clone())),
+            }
+        }
+    }
+}
+
+/// Delete a property of an object, as the `delete` operator does
+fn delete_property(obj: &Value, field: Atom) {
+    if let ValueData::Object(ref obj) = *obj.data() {
+        unmap_argument(&obj.borrow(), field);
+        obj.borrow_mut().shift_remove(&field);
+    }
+}
@@ -0,0 +1,592 @@
+//! The garbage collector the engine's values live in
+//!
+//! Every `Gc` handle counts towards its allocation, wherever the handle is kept. A collection
+//! traces each allocation on the heap and takes away the handles it finds inside them, so any
+//! allocation with handles left over is held from outside the heap: by a Rust local, an
+//! embedder's struct or anything else that isn't traced. Those allocations are the roots, and
+//! everything that can't be reached from them is freed, cycles included. An embedder registers a
+//! root just by holding a handle, and unregisters it by dropping it.
+//!
+//! Collections happen when enough has been allocated since the last one, as `GcConfig` sets, or
+//! when `collect` is called. Each thread has its own heap, and handles mustn't leave their thread.
+
+use crate::js::atom::AtomMarks;
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// Types which can hold handles to the heap, and can tell the collector about each of them
+///
+/// # Safety
+/// `trace` must pass every handle the value owns to the tracer exactly once, or none of them.
+/// Handles shared through an `Rc` mustn't be traced, as each owner would count them again. A
+/// handle which isn't traced is treated as a root, so leaving one out only keeps it alive.
+pub unsafe trait Trace {
+    /// Pass each handle in the value to the tracer
+    fn trace(&self, tracer: &mut Tracer);
+
+    /// Mark the atoms the value keeps, leaving those of the allocations it has handles to, which
+    /// are each looked at on their own
+    fn mark_atoms(&self, _marks: &mut AtomMarks) {}
+
+    /// The bytes the value keeps outside its allocation, like the characters of a string, which
+    /// count towards the size of the heap
+    fn extra_size(&self) -> usize {
+        0
+    }
+}
+
+/// What a collection is doing when it traces the heap
+#[derive(Clone, Copy, PartialEq)]
+enum Phase {
+    /// Taking away the handles found inside the heap from the counts of their allocations
+    Count,
+    /// Marking the allocations reachable from the roots
+    Mark,
+}
+
+/// The collector's view of the handles a value holds
+pub struct Tracer {
+    phase: Phase,
+    /// Allocations which have been marked, but whose handles haven't been traced yet
+    pending: Vec<NonNull<GcBox<dyn Trace>>>,
+}
+
+impl Tracer {
+    /// Tell the collector about a handle
+    pub fn edge<T: Trace + 'static>(&mut self, gc: &Gc<T>) {
+        let header = gc.header();
+        match self.phase {
+            Phase::Count => header.scratch.set(header.scratch.get() - 1),
+            Phase::Mark => {
+                if !header.marked.get() {
+                    header.marked.set(true);
+                    self.pending.push(gc.ptr);
+                }
+            }
+        }
+    }
+}
+
+/// The bookkeeping of an allocation
+struct Header {
+    /// The handles to the allocation
+    refs: Cell<usize>,
+    /// The handles to the allocation from outside the heap, while a collection runs
+    scratch: Cell<usize>,
+    /// If a collection has found the allocation reachable
+    marked: Cell<bool>,
+}
+
+/// An allocation on the heap
+pub struct GcBox<T: ?Sized + 'static> {
+    header: Header,
+    value: T,
+}
+
+/// When the heap is collected without being asked to be
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GcConfig {
+    /// The bytes the heap can grow to before it's first collected
+    pub threshold: usize,
+    /// How many times the bytes still in use after a collection the heap can grow to before the next one
+    pub growth: f64,
+}
+
+impl Default for GcConfig {
+    fn default() -> GcConfig {
+        GcConfig {
+            threshold: 1 << 20,
+            growth: 2.0,
+        }
+    }
+}
+
+/// How big the heap is and what the collector has done, for diagnostics
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct HeapStats {
+    /// The allocations on the heap
+    pub objects: usize,
+    /// The bytes of the allocations on the heap
+    pub bytes: usize,
+    /// The bytes the heap can grow to before it's next collected
+    pub threshold: usize,
+    /// How many times the heap has been collected
+    pub collections: usize,
+    /// How many allocations collections have freed in all
+    pub collected: usize,
+    /// How many allocations were held from outside the heap when it was last collected
+    pub roots: usize,
+}
+
+impl fmt::Display for HeapStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} objects ({} bytes, collected at {}), {} collections freed {} objects, {} roots",
+            self.objects, self.bytes, self.threshold, self.collections, self.collected, self.roots
+        )
+    }
+}
+
+/// The allocations made on a thread
+struct Heap {
+    boxes: Vec<NonNull<GcBox<dyn Trace>>>,
+    config: GcConfig,
+    stats: HeapStats,
+    /// If a collection is running, which allocating mustn't start another of
+    collecting: bool,
+}
+
+thread_local! {
+    /// The heap of this thread, which is leaked when the thread ends as handles may still be dropped after it
+    static HEAP: RefCell<Heap> = RefCell::new(Heap {
+        boxes: Vec::new(),
+        config: GcConfig::default(),
+        stats: HeapStats {
+            threshold: GcConfig::default().threshold,
+            ..HeapStats::default()
+        },
+        collecting: false,
+    });
+}
+
+/// The bytes an allocation takes up, with those its value keeps outside it
+fn box_size(ptr: NonNull<GcBox<dyn Trace>>) -> usize {
+    let gc_box = unsafe { ptr.as_ref() };
+    mem::size_of_val(gc_box) + gc_box.value.extra_size()
+}
+
+/// Collect the garbage on this thread's heap
+pub fn collect() {
+    let boxes = HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        if heap.collecting {
+            return None;
+        }
+        heap.collecting = true;
+        Some((mem::take(&mut heap.boxes), heap.stats.bytes))
+    });
+    let (mut boxes, bytes_before) = match boxes {
+        Some(boxes) => boxes,
+        None => return,
+    };
+    let (roots, garbage) = unsafe { find_garbage(&mut boxes) };
+    let freed = garbage.len();
+    // The allocations kept are counted again, as what their values keep outside them changes
+    let kept_bytes: usize = boxes.iter().map(|&ptr| box_size(ptr)).sum();
+    // The handles in the garbage are dropped while all of it is still allocated, since they count
+    // towards each other, and it's only freed after that
+    for &ptr in &garbage {
+        unsafe { std::ptr::drop_in_place(&mut (*ptr.as_ptr()).value) };
+    }
+    for ptr in garbage {
+        unsafe { dealloc_box(ptr) };
+    }
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        // Anything allocated by the values dropped goes after the allocations kept
+        boxes.append(&mut heap.boxes);
+        heap.boxes = boxes;
+        heap.collecting = false;
+        heap.stats.objects -= freed;
+        heap.stats.bytes = kept_bytes + (heap.stats.bytes - bytes_before);
+        heap.stats.collections += 1;
+        heap.stats.collected += freed;
+        heap.stats.roots = roots;
+        let grown = (heap.stats.bytes as f64 * heap.config.growth) as usize;
+        heap.stats.threshold = grown.max(heap.config.threshold);
+    });
+}
+
+/// Take the allocations which can't be reached out of the heap, returning them and how many roots there were
+unsafe fn find_garbage(
+    boxes: &mut Vec<NonNull<GcBox<dyn Trace>>>,
+) -> (usize, Vec<NonNull<GcBox<dyn Trace>>>) {
+    for ptr in boxes.iter() {
+        let header = &ptr.as_ref().header;
+        header.scratch.set(header.refs.get());
+        header.marked.set(false);
+    }
+    let mut tracer = Tracer {
+        phase: Phase::Count,
+        pending: Vec::new(),
+    };
+    for ptr in boxes.iter() {
+        ptr.as_ref().value.trace(&mut tracer);
+    }
+    tracer.phase = Phase::Mark;
+    let mut roots = 0;
+    for ptr in boxes.iter() {
+        let header = &ptr.as_ref().header;
+        if header.scratch.get() > 0 && !header.marked.get() {
+            roots += 1;
+            header.marked.set(true);
+            tracer.pending.push(*ptr);
+        }
+        // Marking goes depth-first from each root in turn, without recursing
+        while let Some(next) = tracer.pending.pop() {
+            next.as_ref().value.trace(&mut tracer);
+        }
+    }
+    let mut garbage = Vec::new();
+    boxes.retain(|ptr| {
+        if ptr.as_ref().header.marked.get() {
+            true
+        } else {
+            garbage.push(*ptr);
+            false
+        }
+    });
+    (roots, garbage)
+}
+
+/// Free an allocation whose value has been dropped already
+unsafe fn dealloc_box(ptr: NonNull<GcBox<dyn Trace>>) {
+    let layout = std::alloc::Layout::for_value(ptr.as_ref());
+    std::alloc::dealloc(ptr.as_ptr() as *mut u8, layout);
+}
+
+/// Pass the value of each allocation on this thread's heap to `f`, whether it can be reached or not
+///
+/// `f` mustn't allocate, nor drop handles, while the heap is being walked.
+pub(crate) fn for_each_value<F: FnMut(&dyn Trace)>(mut f: F) {
+    HEAP.with(|heap| {
+        let heap = heap.borrow();
+        for ptr in &heap.boxes {
+            f(unsafe { &ptr.as_ref().value });
+        }
+    });
+}
+
+/// Statistics about this thread's heap
+pub fn heap_stats() -> HeapStats {
+    HEAP.with(|heap| heap.borrow().stats)
+}
+
+/// Change when this thread's heap is collected
+pub fn configure(config: GcConfig) {
+    HEAP.with(|heap| {
+        let mut heap = heap.borrow_mut();
+        heap.config = config;
+        let grown = (heap.stats.bytes as f64 * config.growth) as usize;
+        heap.stats.threshold = grown.max(config.threshold);
+    });
+}
+
+/// A handle to a value on the heap, which is freed once no root can reach it
+pub struct Gc<T: Trace + 'static> {
+    ptr: NonNull<GcBox<T>>,
+    marker: PhantomData<GcBox<T>>,
+}
+
+impl<T: Trace + 'static> Gc<T> {
+    /// Put a value on the heap, collecting it first if it has grown enough
+    pub fn new(value: T) -> Gc<T> {
+        let due = HEAP.with(|heap| {
+            let heap = heap.borrow();
+            !heap.collecting && heap.stats.bytes > heap.stats.threshold
+        });
+        if due {
+            collect();
+        }
+        let extra = value.extra_size();
+        let ptr = NonNull::from(Box::leak(Box::new(GcBox {
+            header: Header {
+                refs: Cell::new(1),
+                scratch: Cell::new(0),
+                marked: Cell::new(false),
+            },
+            value,
+        })));
+        HEAP.with(|heap| {
+            let mut heap = heap.borrow_mut();
+            heap.stats.objects += 1;
+            heap.stats.bytes += mem::size_of::<GcBox<T>>() + extra;
+            heap.boxes.push(ptr);
+        });
+        Gc {
+            ptr,
+            marker: PhantomData,
+        }
+    }
+
+    /// Whether two handles are to the same allocation
+    pub fn ptr_eq(a: &Gc<T>, b: &Gc<T>) -> bool {
+        a.ptr == b.ptr
+    }
+
+    /// Turn a handle into a pointer, which keeps the allocation alive until it's made a handle again
+    pub fn into_raw(this: Gc<T>) -> *const GcBox<T> {
+        let ptr = this.ptr.as_ptr();
+        mem::forget(this);
+        ptr
+    }
+
+    /// Turn a pointer from `into_raw` back into the handle
+    ///
+    /// # Safety
+    /// The pointer must have come from `into_raw`, and be made a handle again only once.
+    pub unsafe fn from_raw(ptr: *const GcBox<T>) -> Gc<T> {
+        Gc {
+            ptr: NonNull::new_unchecked(ptr as *mut GcBox<T>),
+            marker: PhantomData,
+        }
+    }
+
+    fn header(&self) -> &Header {
+        unsafe { &self.ptr.as_ref().header }
+    }
+}
+
+impl<T: Trace + 'static> Deref for Gc<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &self.ptr.as_ref().value }
+    }
+}
+
+impl<T: Trace + 'static> Clone for Gc<T> {
+    fn clone(&self) -> Gc<T> {
+        let header = self.header();
+        header.refs.set(header.refs.get() + 1);
+        Gc {
+            ptr: self.ptr,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Trace + 'static> Drop for Gc<T> {
+    fn drop(&mut self) {
+        // The allocation stays where it is until a collection finds it unreachable
+        let header = self.header();
+        header.refs.set(header.refs.get() - 1);
+    }
+}
+
+unsafe impl<T: Trace + 'static> Trace for Gc<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.edge(self);
+    }
+}
+
+impl<T: Trace + fmt::Debug + 'static> fmt::Debug for Gc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+/// How a `GcCell` is borrowed: `WRITING` when mutably, and otherwise the number of shared borrows
+type BorrowFlag = usize;
+const UNUSED: BorrowFlag = 0;
+const WRITING: BorrowFlag = !0;
+
+/// A mutable value on the heap, which checks its borrows at runtime like `RefCell`
+///
+/// Collections can run while the value is borrowed mutably. The handles in it are left out of the
+/// collection then, which keeps them alive until the next one.
+pub struct GcCell<T: Trace> {
+    flag: Cell<BorrowFlag>,
+    value: UnsafeCell<T>,
+}
+
+/// The error of borrowing a `GcCell` in a way it's already borrowed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BorrowError {
+    /// If the cell is borrowed mutably, rather than shared
+    pub mutably: bool,
+}
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.mutably {
+            write!(f, "value is being changed")
+        } else {
+            write!(f, "value is being read")
+        }
+    }
+}
+
+impl<T: Trace> GcCell<T> {
+    /// Make a cell holding a value
+    pub fn new(value: T) -> GcCell<T> {
+        GcCell {
+            flag: Cell::new(UNUSED),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Take the value out of the cell
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Borrow the value, or fail if it's borrowed mutably
+    pub fn try_borrow(&self) -> Result<GcCellRef<'_, T>, BorrowError> {
+        match self.flag.get() {
+            WRITING => Err(BorrowError { mutably: true }),
+            n => {
+                self.flag.set(n + 1);
+                Ok(GcCellRef { cell: self })
+            }
+        }
+    }
+
+    /// Borrow the value mutably, or fail if it's borrowed at all
+    pub fn try_borrow_mut(&self) -> Result<GcCellRefMut<'_, T>, BorrowError> {
+        match self.flag.get() {
+            UNUSED => {
+                self.flag.set(WRITING);
+                Ok(GcCellRefMut { cell: self })
+            }
+            WRITING => Err(BorrowError { mutably: true }),
+            _ => Err(BorrowError { mutably: false }),
+        }
+    }
+
+    /// Borrow the value
+    ///
+    /// # Panics
+    /// If the value is borrowed mutably
+    pub fn borrow(&self) -> GcCellRef<'_, T> {
+        match self.try_borrow() {
+            Ok(value) => value,
+            Err(e) => panic!("can't read a value on the heap: {}", e),
+        }
+    }
+
+    /// Borrow the value mutably
+    ///
+    /// # Panics
+    /// If the value is borrowed at all
+    pub fn borrow_mut(&self) -> GcCellRefMut<'_, T> {
+        match self.try_borrow_mut() {
+            Ok(value) => value,
+            Err(e) => panic!("can't change a value on the heap: {}", e),
+        }
+    }
+}
+
+unsafe impl<T: Trace> Trace for GcCell<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        if self.flag.get() != WRITING {
+            unsafe { (*self.value.get()).trace(tracer) }
+        }
+    }
+
+    fn mark_atoms(&self, marks: &mut AtomMarks) {
+        if self.flag.get() == WRITING {
+            marks.incomplete();
+        } else {
+            unsafe { (*self.value.get()).mark_atoms(marks) }
+        }
+    }
+
+    fn extra_size(&self) -> usize {
+        if self.flag.get() == WRITING {
+            0
+        } else {
+            unsafe { (*self.value.get()).extra_size() }
+        }
+    }
+}
+
+impl<T: Trace + Clone> Clone for GcCell<T> {
+    fn clone(&self) -> GcCell<T> {
+        GcCell::new(self.borrow().clone())
+    }
+}
+
+impl<T: Trace + fmt::Debug> fmt::Debug for GcCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_borrow() {
+            Ok(value) => f.debug_struct("GcCell").field("value", &*value).finish(),
+            Err(_) => f
+                .debug_struct("GcCell")
+                .field("value", &"<borrowed>")
+                .finish(),
+        }
+    }
+}
+
+/// A borrow of the value in a `GcCell`
+pub struct GcCellRef<'a, T: Trace> {
+    cell: &'a GcCell<T>,
+}
+
+impl<'a, T: Trace> Deref for GcCellRef<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<'a, T: Trace> Drop for GcCellRef<'a, T> {
+    fn drop(&mut self) {
+        self.cell.flag.set(self.cell.flag.get() - 1);
+    }
+}
+
+/// A mutable borrow of the value in a `GcCell`
+pub struct GcCellRefMut<'a, T: Trace> {
+    cell: &'a GcCell<T>,
+}
+
+impl<'a, T: Trace> Deref for GcCellRefMut<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<'a, T: Trace> DerefMut for GcCellRefMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.cell.value.get() }
+    }
+}
+
+impl<'a, T: Trace> Drop for GcCellRefMut<'a, T> {
+    fn drop(&mut self) {
+        self.cell.flag.set(UNUSED);
+    }
+}
+
+unsafe impl<T: Trace> Trace for Vec<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        for value in self {
+            value.trace(tracer);
+        }
+    }
+
+    fn mark_atoms(&self, marks: &mut AtomMarks) {
+        for value in self {
+            value.mark_atoms(marks);
+        }
+    }
+
+    fn extra_size(&self) -> usize {
+        self.len() * mem::size_of::<T>() + self.iter().map(Trace::extra_size).sum::<usize>()
+    }
+}
+
+unsafe impl<T: Trace> Trace for Option<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(ref value) = *self {
+            value.trace(tracer);
+        }
+    }
+
+    fn mark_atoms(&self, marks: &mut AtomMarks) {
+        if let Some(ref value) = *self {
+            value.mark_atoms(marks);
+        }
+    }
+
+    fn extra_size(&self) -> usize {
+        self.as_ref().map_or(0, Trace::extra_size)
+    }
+}
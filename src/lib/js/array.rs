@@ -1,19 +1,43 @@
-use gc::Gc;
-use crate::js::function::NativeFunctionData;
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::PROTOTYPE;
 use crate::js::value::{to_value, ResultValue, Value, ValueData};
 
+/// The internal slot marking an object as an array, which only the engine can give an object
+pub static ARRAY_DATA: &str = "[[ArrayData]]";
+
+/// Make an empty array inheriting from `proto`
+/// [[SPEC] - ArrayCreate](https://tc39.github.io/ecma262/#sec-arraycreate)
+pub fn array_create(proto: Value) -> Value {
+    let array = ValueData::new_obj_from_prototype(proto);
+    array.set_internal_slot(ARRAY_DATA, to_value(true));
+    array
+}
+
+/// Whether a value is an array, from whichever realm it was made in
+/// [[SPEC] - IsArray](https://tc39.github.io/ecma262/#sec-isarray)
+pub fn is_array(value: &Value) -> bool {
+    match *value.data() {
+        ValueData::Object(ref obj) => obj.borrow().has_internal_slot(ARRAY_DATA),
+        _ => false,
+    }
+}
+
 /// Create a new array
-pub fn make_array(this: Value, _: Value, _: Vec<Value>) -> ResultValue {
+pub fn make_array(this: Value, _: Value, _: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
     let this_ptr = this.clone();
+    this_ptr.set_internal_slot(ARRAY_DATA, to_value(true));
     this_ptr.set_field_slice("length", to_value(0i32));
-    Ok(Gc::new(ValueData::Undefined))
+    Ok(Value::undefined())
 }
 /// Create a new `Array` object
-pub fn _create() -> Value {
+pub fn _create(global: Value) -> Value {
     let array = to_value(make_array as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global));
+    proto.set_internal_slot(ARRAY_DATA, to_value(true));
+    array.set_field_slice(PROTOTYPE, proto);
     array
 }
 /// Initialise the global object with the `Array` object
 pub fn init(global: Value) {
-    global.set_field_slice("Array", _create());
+    global.set_field_slice("Array", _create(global.clone()));
 }
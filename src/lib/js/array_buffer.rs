@@ -0,0 +1,203 @@
+use crate::js::data_view::DATA_VIEW_BYTE_LENGTH;
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::{is_construct, PROTOTYPE};
+use crate::js::typed_array::TYPED_ARRAY_NAME;
+use crate::js::value::{note_allocation, to_value, ResultValue, Value, ValueData};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The internal slot of an `ArrayBuffer` holding its bytes
+pub static ARRAY_BUFFER_DATA: &str = "[[ArrayBufferData]]";
+/// The internal slot of a resizable `ArrayBuffer` holding the most bytes it can grow to
+pub static ARRAY_BUFFER_MAX_BYTE_LENGTH: &str = "[[ArrayBufferMaxByteLength]]";
+
+/// The bytes of an `ArrayBuffer`, which the views on it and the host can share
+pub type SharedBytes = Rc<RefCell<Vec<u8>>>;
+
+/// The bytes behind an `ArrayBuffer`, or `None` if the value isn't one
+pub fn buffer_bytes(buffer: &Value) -> Option<SharedBytes> {
+    if let ValueData::Object(ref obj) = *buffer.data() {
+        if let Some(prop) = obj.borrow().get_internal_slot(ARRAY_BUFFER_DATA) {
+            if let ValueData::Bytes(ref bytes) = *prop.data() {
+                return Some(bytes.clone());
+            }
+        }
+    }
+    None
+}
+
+/// Make a new `ArrayBuffer` over some bytes, given the `ArrayBuffer` constructor
+/// The host keeps seeing the bytes the script writes for as long as it holds on to `bytes`
+pub fn new_array_buffer(array_buffer: &Value, bytes: SharedBytes) -> Value {
+    make_buffer(array_buffer.get_field_slice(PROTOTYPE), bytes, None)
+}
+
+/// Make a new `ArrayBuffer` object with a prototype, bytes and optionally a maximum length to grow to
+pub fn make_buffer(proto: Value, bytes: SharedBytes, max_byte_length: Option<usize>) -> Value {
+    let buffer = ValueData::new_obj_from_prototype(proto);
+    buffer.set_internal_slot(ARRAY_BUFFER_DATA, Value::new(ValueData::Bytes(bytes)));
+    if let Some(max) = max_byte_length {
+        buffer.set_internal_slot(ARRAY_BUFFER_MAX_BYTE_LENGTH, to_value(max as f64));
+    }
+    buffer
+}
+
+/// Allocate `len` zeroed bytes, or throw a `RangeError` if there isn't enough memory
+pub fn allocate(len: usize, caller: &dyn Caller) -> Result<SharedBytes, Value> {
+    let mut bytes = Vec::new();
+    if bytes.try_reserve_exact(len).is_err() {
+        return Err(caller.new_error("RangeError", "Array buffer allocation failed".to_string()));
+    }
+    bytes.resize(len, 0);
+    note_allocation(len);
+    Ok(Rc::new(RefCell::new(bytes)))
+}
+
+/// Convert a value to a length or offset
+/// [[SPEC] - ToIndex](https://tc39.github.io/ecma262/#sec-toindex)
+pub fn to_index(value: Option<&Value>, error: &str, caller: &dyn Caller) -> Result<usize, Value> {
+    let n = match value {
+        None => return Ok(0),
+        Some(v) if v.is_undefined() => return Ok(0),
+        Some(v) => v.to_number().map_err(|e| e.into_error(caller))?,
+    };
+    let n = if n.is_nan() { 0.0 } else { n.trunc() };
+    if !(0.0..=9_007_199_254_740_991.0).contains(&n) {
+        return Err(caller.new_error("RangeError", error.to_string()));
+    }
+    Ok(n as usize)
+}
+
+/// The most bytes a resizable `ArrayBuffer` can grow to, or `None` if it can't
+pub fn max_byte_length(buffer: &Value) -> Option<usize> {
+    if let ValueData::Object(ref obj) = *buffer.data() {
+        if let Some(prop) = obj.borrow().get_internal_slot(ARRAY_BUFFER_MAX_BYTE_LENGTH) {
+            return Some(prop.to_num() as usize);
+        }
+    }
+    None
+}
+
+/// Get the bytes `this` holds, or throw if it isn't an `ArrayBuffer`
+fn this_buffer_bytes(this: &Value, caller: &dyn Caller) -> Result<SharedBytes, Value> {
+    buffer_bytes(this)
+        .ok_or_else(|| caller.new_error("TypeError", "this is not an ArrayBuffer.".to_string()))
+}
+
+/// Create a new `ArrayBuffer` of zeroed bytes
+/// [[SPEC] - The ArrayBuffer Constructor](https://tc39.github.io/ecma262/#sec-arraybuffer-constructor)
+pub fn make_array_buffer(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if !is_construct(&this, &callee) {
+        return Err(caller.new_error(
+            "TypeError",
+            "Constructor ArrayBuffer requires 'new'".to_string(),
+        ));
+    }
+    let len = to_index(args.first(), "Invalid array buffer length", caller)?;
+    let max = match args.get(1) {
+        Some(options) if options.is_object() => {
+            let max = options.get_field_slice("maxByteLength");
+            if max.is_undefined() {
+                None
+            } else {
+                Some(to_index(Some(&max), "Invalid array buffer max length", caller)?)
+            }
+        }
+        _ => None,
+    };
+    if max.is_some_and(|max| len > max) {
+        return Err(caller.new_error("RangeError", "Invalid array buffer max length".to_string()));
+    }
+    let bytes = allocate(len, caller)?;
+    Ok(make_buffer(
+        this.get_prototype(),
+        bytes,
+        max,
+    ))
+}
+/// Whether a value is a typed array or a `DataView`
+pub fn is_view(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(match args.first().map(Value::data).as_deref() {
+        Some(ValueData::Object(ref obj)) => {
+            let obj = obj.borrow();
+            obj.has_internal_slot(TYPED_ARRAY_NAME) || obj.has_internal_slot(DATA_VIEW_BYTE_LENGTH)
+        }
+        _ => false,
+    }))
+}
+/// Change the length of a resizable `ArrayBuffer`, zeroing any new bytes
+/// [[SPEC] - ArrayBuffer.prototype.resize](https://tc39.es/ecma262/#sec-arraybuffer.prototype.resize)
+pub fn resize(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let bytes = this_buffer_bytes(&this, caller)?;
+    let max = match max_byte_length(&this) {
+        Some(max) => max,
+        None => {
+            return Err(caller.new_error("TypeError", "ArrayBuffer is not resizable".to_string()))
+        }
+    };
+    let len = to_index(args.first(), "Invalid array buffer length", caller)?;
+    if len > max {
+        return Err(caller.new_error("RangeError", "Invalid array buffer length".to_string()));
+    }
+    let mut bytes = bytes.borrow_mut();
+    let old_len = bytes.len();
+    if len > old_len && bytes.try_reserve_exact(len - old_len).is_err() {
+        return Err(caller.new_error("RangeError", "Array buffer allocation failed".to_string()));
+    }
+    bytes.resize(len, 0);
+    Ok(Value::undefined())
+}
+/// Copy some of the bytes of an `ArrayBuffer` to a new one
+/// [[SPEC] - ArrayBuffer.prototype.slice](https://tc39.github.io/ecma262/#sec-arraybuffer.prototype.slice)
+pub fn slice(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let bytes = this_buffer_bytes(&this, caller)?;
+    let bytes = bytes.borrow();
+    let len = bytes.len();
+    let start = relative_index(args.first(), len, 0, caller)?;
+    let end = relative_index(args.get(1), len, len, caller)?;
+    let copy = if start < end {
+        bytes[start..end].to_vec()
+    } else {
+        Vec::new()
+    };
+    Ok(make_buffer(
+        this.get_prototype(),
+        Rc::new(RefCell::new(copy)),
+        None,
+    ))
+}
+
+/// Resolve an index relative to the end when negative and clamp it to `0..=len`
+pub fn relative_index(
+    value: Option<&Value>,
+    len: usize,
+    default: usize,
+    caller: &dyn Caller,
+) -> Result<usize, Value> {
+    let n = match value {
+        None => return Ok(default),
+        Some(v) if v.is_undefined() => return Ok(default),
+        Some(v) => v.to_number().map_err(|e| e.into_error(caller))?,
+    };
+    let n = if n.is_nan() { 0.0 } else { n.trunc() };
+    Ok(if n < 0.0 {
+        (len as f64 + n).max(0.0) as usize
+    } else {
+        n.min(len as f64) as usize
+    })
+}
+
+/// Create a new `ArrayBuffer` object
+pub fn _create(global: Value) -> Value {
+    let array_buffer = to_value(make_array_buffer as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global));
+    proto.set_field_slice("resize", to_value(resize as NativeFunctionData));
+    proto.set_field_slice("slice", to_value(slice as NativeFunctionData));
+    array_buffer.set_field_slice(PROTOTYPE, proto);
+    array_buffer.set_field_slice("isView", to_value(is_view as NativeFunctionData));
+    array_buffer
+}
+/// Initialise the global object with the `ArrayBuffer` object
+pub fn init(global: Value) {
+    global.set_field_slice("ArrayBuffer", _create(global.clone()));
+}
@@ -0,0 +1,407 @@
+use crate::gc;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+/// The bit of an atom which says it holds an array index rather than a place in the table
+const INDEX_BIT: u32 = 1 << 31;
+
+/// An interned identifier or property key, which compares and hashes as a number
+///
+/// Array indices below 2^31 are held in the atom itself, so indexing an array never touches the
+/// table. Every other name is looked up in a table on the current thread, which keeps each name
+/// once, so atoms mustn't be sent to other threads.
+///
+/// Atoms made with `Atom::new`, like the names written in code, stay in the table for as long as
+/// the thread runs. Those made from property keys with `ToAtom` only stay while an object on the
+/// heap uses them: the new interpreter frees the rest once no script is running, and their numbers
+/// are given out again. A host keeping such an atom anywhere else should make it with `Atom::new`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Atom(u32);
+
+/// The names made into atoms before any others, so the atoms of the keys looked up most are constants
+const WELL_KNOWN: [&str; 4] = ["length", "prototype", "__proto__", "[[Prototype]]"];
+/// The atom of `length`
+pub const LENGTH: Atom = Atom(0);
+/// The atom of `prototype`
+pub const PROTOTYPE: Atom = Atom(1);
+/// The atom of `__proto__`, which scripts get and set the prototype of an object through
+pub const INSTANCE_PROTOTYPE: Atom = Atom(2);
+/// The atom of the internal slot holding the prototype of an object
+pub const PROTOTYPE_SLOT: Atom = Atom(3);
+
+/// How many names the atom table holds and how it has been used, for diagnostics
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AtomStats {
+    /// The names in the table
+    pub atoms: usize,
+    /// The bytes of the names in the table
+    pub bytes: usize,
+    /// How many times a name was turned into an atom, whether or not it was new
+    pub lookups: usize,
+    /// How many of those lookups found an array index, which skips the table
+    pub indices: usize,
+    /// How many atoms have been freed because no object used them
+    pub collected: usize,
+}
+
+/// How many atoms that aren't kept for good the table holds before it's first swept
+const SWEEP_THRESHOLD: usize = 4096;
+
+/// The names atoms stand for and the atoms of the names
+struct AtomTable {
+    /// The name of each atom, or `None` where one was freed
+    names: Vec<Option<Rc<str>>>,
+    /// Whether each atom is kept for as long as the thread runs
+    pinned: Vec<bool>,
+    atoms: HashMap<Rc<str>, Atom>,
+    /// The atoms which were freed, whose numbers are given out again
+    free: Vec<u32>,
+    /// How many atoms the table holds which aren't kept for good
+    collectable: usize,
+    /// How many of those the table can hold before it's next swept
+    sweep_at: usize,
+    stats: AtomStats,
+}
+
+impl AtomTable {
+    /// Make a table holding the well-known names
+    fn new() -> AtomTable {
+        let mut table = AtomTable {
+            names: Vec::new(),
+            pinned: Vec::new(),
+            atoms: HashMap::new(),
+            free: Vec::new(),
+            collectable: 0,
+            sweep_at: SWEEP_THRESHOLD,
+            stats: AtomStats::default(),
+        };
+        for name in WELL_KNOWN.iter() {
+            table.intern(name, true);
+        }
+        table
+    }
+
+    /// The atom of a name, adding it if there's room, and keeping it for good if `pin` is set
+    fn intern(&mut self, name: &str, pin: bool) -> Option<Atom> {
+        self.stats.lookups += 1;
+        if let Some(index) = parse_index(name) {
+            self.stats.indices += 1;
+            return Some(Atom(index | INDEX_BIT));
+        }
+        if let Some(&atom) = self.atoms.get(name) {
+            if pin && !self.pinned[atom.0 as usize] {
+                self.pinned[atom.0 as usize] = true;
+                self.collectable -= 1;
+            }
+            return Some(atom);
+        }
+        let atom = match self.free.pop() {
+            Some(number) => Atom(number),
+            None if self.names.len() < INDEX_BIT as usize => {
+                self.names.push(None);
+                self.pinned.push(false);
+                Atom(self.names.len() as u32 - 1)
+            }
+            None => return None,
+        };
+        let name: Rc<str> = Rc::from(name);
+        self.stats.atoms += 1;
+        self.stats.bytes += name.len();
+        self.names[atom.0 as usize] = Some(name.clone());
+        self.pinned[atom.0 as usize] = pin;
+        if !pin {
+            self.collectable += 1;
+        }
+        self.atoms.insert(name, atom);
+        Some(atom)
+    }
+
+    /// The bytes the table takes up, roughly
+    fn size(&self) -> usize {
+        // Each name is kept once, behind the counts of its `Rc`, and pointed to from both maps
+        let entry = mem::size_of::<Option<Rc<str>>>()
+            + mem::size_of::<bool>()
+            + mem::size_of::<(Rc<str>, Atom)>()
+            + 2 * mem::size_of::<usize>();
+        self.stats.bytes + self.names.len() * entry + self.free.len() * mem::size_of::<u32>()
+    }
+}
+
+/// The atoms the values on the heap use, found before the atom table is swept
+pub struct AtomMarks {
+    used: Vec<bool>,
+    /// If every value could be looked at, which a value being changed stops
+    complete: bool,
+}
+
+impl AtomMarks {
+    /// Record that a value uses an atom
+    pub fn mark(&mut self, atom: Atom) {
+        if atom.as_index().is_none() {
+            if let Some(used) = self.used.get_mut(atom.0 as usize) {
+                *used = true;
+            }
+        }
+    }
+
+    /// Record that a value couldn't be looked at, so no atom can be freed
+    pub fn incomplete(&mut self) {
+        self.complete = false;
+    }
+}
+
+/// Free the atoms no value on the heap uses, apart from those kept for good, once enough have
+/// been made since the table was last swept
+///
+/// Atoms held outside the heap aren't seen, so this is only done when no script is running.
+pub(crate) fn sweep_atoms() {
+    let (due, len) = ATOMS.with(|table| {
+        let table = table.borrow();
+        (table.collectable > table.sweep_at, table.names.len())
+    });
+    if !due {
+        return;
+    }
+    // Values which can't be reached would otherwise keep their keys
+    gc::collect();
+    let mut marks = AtomMarks {
+        used: vec![false; len],
+        complete: true,
+    };
+    gc::for_each_value(|value| value.mark_atoms(&mut marks));
+    if !marks.complete {
+        return;
+    }
+    ATOMS.with(|table| {
+        let table = &mut *table.borrow_mut();
+        for (number, used) in marks.used.into_iter().enumerate() {
+            if used || table.pinned[number] {
+                continue;
+            }
+            if let Some(name) = table.names[number].take() {
+                table.atoms.remove(&name);
+                table.free.push(number as u32);
+                table.collectable -= 1;
+                table.stats.atoms -= 1;
+                table.stats.bytes -= name.len();
+                table.stats.collected += 1;
+            }
+        }
+        table.sweep_at = (table.collectable * 2).max(SWEEP_THRESHOLD);
+    });
+}
+
+/// The bytes the atom table on this thread takes up, roughly, which count towards the heap limit
+pub fn atom_table_size() -> usize {
+    ATOMS.with(|table| table.borrow().size())
+}
+
+thread_local! {
+    /// The atoms made on this thread so far
+    static ATOMS: RefCell<AtomTable> = RefCell::new(AtomTable::new());
+}
+
+/// The array index a name is the canonical form of, if it is one an atom can hold
+fn parse_index(name: &str) -> Option<u32> {
+    let bytes = name.as_bytes();
+    if bytes.is_empty() || bytes.len() > 10 || (bytes[0] == b'0' && bytes.len() > 1) {
+        return None;
+    }
+    let mut n: u64 = 0;
+    for &b in bytes {
+        if !b.is_ascii_digit() {
+            return None;
+        }
+        n = n * 10 + u64::from(b - b'0');
+    }
+    if n < u64::from(INDEX_BIT) {
+        Some(n as u32)
+    } else {
+        None
+    }
+}
+
+impl Atom {
+    /// The atom of a name, adding it to the table if it isn't there yet and keeping it for good
+    ///
+    /// # Panics
+    /// If the table holds 2^31 names already
+    pub fn new(name: &str) -> Atom {
+        ATOMS
+            .with(|table| table.borrow_mut().intern(name, true))
+            .expect("too many atoms")
+    }
+
+    /// The atom of a property key, adding it to the table for as long as an object uses it, or
+    /// `None` if the table holds 2^31 names already
+    pub fn try_new(name: &str) -> Option<Atom> {
+        ATOMS.with(|table| table.borrow_mut().intern(name, false))
+    }
+
+    /// The atom of a name if one has been made already, which no property could be keyed by otherwise
+    pub fn lookup(name: &str) -> Option<Atom> {
+        if let Some(index) = parse_index(name) {
+            return Some(Atom(index | INDEX_BIT));
+        }
+        ATOMS.with(|table| table.borrow().atoms.get(name).cloned())
+    }
+
+    /// The atom of an array index
+    pub fn index(index: u32) -> Atom {
+        if index < INDEX_BIT {
+            Atom(index | INDEX_BIT)
+        } else {
+            index.to_string().to_atom()
+        }
+    }
+
+    /// The array index this atom is, if it is one below 2^31
+    pub fn as_index(self) -> Option<u32> {
+        if self.0 & INDEX_BIT != 0 {
+            Some(self.0 & !INDEX_BIT)
+        } else {
+            None
+        }
+    }
+
+    /// The name this atom stands for
+    pub fn as_str(self) -> Rc<str> {
+        match self.as_index() {
+            Some(index) => Rc::from(index.to_string()),
+            None => ATOMS.with(|table| {
+                table.borrow().names[self.0 as usize]
+                    .clone()
+                    .expect("atoms are only freed when nothing uses them")
+            }),
+        }
+    }
+}
+
+/// Statistics about the atoms made on this thread so far
+pub fn atom_stats() -> AtomStats {
+    ATOMS.with(|table| table.borrow().stats)
+}
+
+impl fmt::Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.as_index() {
+            Some(index) => write!(f, "{}", index),
+            None => write!(f, "{}", self.as_str()),
+        }
+    }
+}
+
+impl fmt::Display for AtomStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} atoms ({} bytes), {} lookups ({} array indices), {} collected",
+            self.atoms, self.bytes, self.lookups, self.indices, self.collected
+        )
+    }
+}
+
+impl fmt::Debug for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Atom({:?})", self.to_string())
+    }
+}
+
+impl PartialEq<str> for Atom {
+    fn eq(&self, other: &str) -> bool {
+        Atom::lookup(other) == Some(*self)
+    }
+}
+
+impl<'a> PartialEq<&'a str> for Atom {
+    fn eq(&self, other: &&'a str) -> bool {
+        *self == **other
+    }
+}
+
+/// Conversion to atoms from the ways a property key can be written
+pub trait ToAtom {
+    /// The atom of this key, adding it to the table if needed
+    ///
+    /// # Panics
+    /// If the key is a new name and the table holds 2^31 names already
+    fn to_atom(&self) -> Atom {
+        self.try_to_atom().expect("too many atoms")
+    }
+    /// The atom of this key, or `None` if it's a new name and the table has no room for it
+    fn try_to_atom(&self) -> Option<Atom>;
+    /// The atom of this key if there is one yet, for looking up keys without adding them
+    fn find_atom(&self) -> Option<Atom> {
+        Some(self.to_atom())
+    }
+}
+
+impl ToAtom for Atom {
+    fn try_to_atom(&self) -> Option<Atom> {
+        Some(*self)
+    }
+}
+
+impl ToAtom for str {
+    fn try_to_atom(&self) -> Option<Atom> {
+        Atom::try_new(self)
+    }
+    fn find_atom(&self) -> Option<Atom> {
+        Atom::lookup(self)
+    }
+}
+
+impl ToAtom for String {
+    fn try_to_atom(&self) -> Option<Atom> {
+        Atom::try_new(self)
+    }
+    fn find_atom(&self) -> Option<Atom> {
+        Atom::lookup(self)
+    }
+}
+
+impl ToAtom for u32 {
+    fn try_to_atom(&self) -> Option<Atom> {
+        if *self < INDEX_BIT {
+            Some(Atom::index(*self))
+        } else {
+            self.to_string().try_to_atom()
+        }
+    }
+    fn find_atom(&self) -> Option<Atom> {
+        if *self < INDEX_BIT {
+            Some(Atom::index(*self))
+        } else {
+            Atom::lookup(&self.to_string())
+        }
+    }
+}
+
+impl ToAtom for usize {
+    fn try_to_atom(&self) -> Option<Atom> {
+        if *self < INDEX_BIT as usize {
+            Some(Atom::index(*self as u32))
+        } else {
+            self.to_string().try_to_atom()
+        }
+    }
+    fn find_atom(&self) -> Option<Atom> {
+        if *self < INDEX_BIT as usize {
+            Some(Atom::index(*self as u32))
+        } else {
+            Atom::lookup(&self.to_string())
+        }
+    }
+}
+
+impl<K: ToAtom + ?Sized> ToAtom for &K {
+    fn try_to_atom(&self) -> Option<Atom> {
+        (**self).try_to_atom()
+    }
+    fn find_atom(&self) -> Option<Atom> {
+        (**self).find_atom()
+    }
+}
@@ -0,0 +1,238 @@
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::number::is_white_space;
+use crate::js::object::{is_construct, PROTOTYPE};
+use crate::js::value::{to_value, NativeError, ResultValue, Value, ValueData};
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, Num, One, Signed, Zero};
+use std::cmp::Ordering;
+
+/// The internal slot of a `BigInt` wrapper object holding its primitive value
+pub static BIGINT_DATA: &str = "[[BigIntData]]";
+
+/// The most bits a `BigInt` may have, as in V8
+pub const MAX_BIGINT_BITS: usize = 1 << 30;
+
+/// Read a string as an integer, or `None` if it isn't one
+/// [[SPEC] - StringToBigInt](https://tc39.github.io/ecma262/#sec-stringtobigint)
+pub fn string_to_bigint(s: &str) -> Option<BigInt> {
+    let s = s.trim_matches(is_white_space);
+    if s.is_empty() {
+        return Some(BigInt::zero());
+    }
+    let (radix, digits, negative) = match s.get(..2) {
+        Some("0x") | Some("0X") => (16, &s[2..], false),
+        Some("0o") | Some("0O") => (8, &s[2..], false),
+        Some("0b") | Some("0B") => (2, &s[2..], false),
+        _ if s.starts_with('-') => (10, &s[1..], true),
+        _ if s.starts_with('+') => (10, &s[1..], false),
+        _ => (10, s, false),
+    };
+    // `from_str_radix` also allows `_` separators and a sign, which Javascript doesn't
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let n = BigInt::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -n } else { n })
+}
+
+/// Read the source text of a `BigInt` literal, such as `0x1Fn`
+pub fn parse_bigint_literal(raw: &str) -> Option<BigInt> {
+    string_to_bigint(raw.trim_end_matches('n'))
+}
+
+/// The number closest to a `BigInt`
+pub fn bigint_to_number(n: &BigInt) -> f64 {
+    // Reading the decimal digits back rounds to nearest, which `to_f64` doesn't
+    n.to_string().parse().unwrap()
+}
+
+/// The `BigInt` with the same value as a number, or `None` if the number isn't an integer
+fn number_to_bigint(n: f64) -> Option<BigInt> {
+    if n.is_finite() && n.trunc() == n {
+        BigInt::from_f64(n)
+    } else {
+        None
+    }
+}
+
+/// Compare a `BigInt` with a number, or `None` if the number is `NaN`
+pub fn compare_with_number(a: &BigInt, n: f64) -> Option<Ordering> {
+    if n.is_nan() {
+        None
+    } else if n == f64::INFINITY {
+        Some(Ordering::Less)
+    } else if n == f64::NEG_INFINITY {
+        Some(Ordering::Greater)
+    } else {
+        let floor = n.floor();
+        match a.cmp(&BigInt::from_f64(floor).unwrap()) {
+            Ordering::Equal if n > floor => Some(Ordering::Less),
+            ordering => Some(ordering),
+        }
+    }
+}
+
+/// Convert a value to a `BigInt`
+/// [[SPEC] - ToBigInt](https://tc39.github.io/ecma262/#sec-tobigint)
+pub fn to_bigint(value: &Value) -> Result<BigInt, NativeError> {
+    match *value.data() {
+        ValueData::BigInt(ref n) => Ok(n.clone()),
+        ValueData::Boolean(b) => Ok(if b { BigInt::one() } else { BigInt::zero() }),
+        ValueData::String(ref s) => string_to_bigint(&s.to_string()).ok_or_else(|| NativeError {
+            kind: "SyntaxError",
+            message: format!("Cannot convert {} to a BigInt", s),
+        }),
+        ValueData::Object(ref obj) => match obj.borrow().get_internal_slot(BIGINT_DATA) {
+            Some(prop) => to_bigint(prop),
+            None => Err(NativeError::type_error("Cannot convert object to a BigInt")),
+        },
+        _ => Err(NativeError::type_error(&format!(
+            "Cannot convert {} to a BigInt",
+            value
+        ))),
+    }
+}
+
+/// The `BigInt` primitive `this` is or wraps
+/// [[SPEC] - thisBigIntValue](https://tc39.github.io/ecma262/#sec-thisbigintvalue)
+fn this_bigint_value(value: &Value, caller: &dyn Caller) -> Result<BigInt, Value> {
+    match *value.data() {
+        ValueData::BigInt(ref n) => Ok(n.clone()),
+        ValueData::Object(ref obj) => match obj.borrow().get_internal_slot(BIGINT_DATA) {
+            Some(prop) => this_bigint_value(prop, caller),
+            None => Err(caller.new_error("TypeError", "this is not a BigInt.".to_string())),
+        },
+        _ => Err(caller.new_error("TypeError", "this is not a BigInt.".to_string())),
+    }
+}
+
+/// The bit count argument of `asIntN` and `asUintN`
+/// [[SPEC] - ToIndex](https://tc39.github.io/ecma262/#sec-toindex)
+fn to_bits(value: Option<&Value>, caller: &dyn Caller) -> Result<usize, Value> {
+    let bits = value.map_or(0.0, |v| v.to_num());
+    let bits = if bits.is_nan() { 0.0 } else { bits.trunc() };
+    if !(0.0..=9_007_199_254_740_991.0).contains(&bits) {
+        return Err(caller.new_error(
+            "RangeError",
+            "Invalid value: not (convertible to) a safe integer".to_string(),
+        ));
+    }
+    Ok(bits as usize)
+}
+
+/// `n` modulo `2^bits`, in the range `0 <= n < 2^bits`
+fn wrap_unsigned(n: BigInt, bits: usize, caller: &dyn Caller) -> Result<BigInt, Value> {
+    if !n.is_negative() && n.bits() <= bits {
+        return Ok(n);
+    }
+    if bits > MAX_BIGINT_BITS {
+        return Err(caller.new_error("RangeError", "Maximum BigInt size exceeded".to_string()));
+    }
+    let modulus = BigInt::one() << bits;
+    let n = n % &modulus;
+    Ok(if n.is_negative() { n + modulus } else { n })
+}
+
+/// Convert a value to a `BigInt`, or an integral number to one when called as a function
+/// [[SPEC] - The BigInt Constructor](https://tc39.github.io/ecma262/#sec-bigint-constructor)
+pub fn make_bigint(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if is_construct(&this, &callee) {
+        return Err(caller.new_error("TypeError", "BigInt is not a constructor".to_string()));
+    }
+    let value = args.first().cloned().unwrap_or_else(Value::undefined);
+    let n = match *value.data() {
+        ValueData::Number(_) | ValueData::Integer(_) => number_to_bigint(value.to_num())
+            .ok_or_else(|| {
+                caller.new_error(
+                    "RangeError",
+                    format!(
+                        "The number {} cannot be converted to a BigInt because it is not an integer",
+                        value
+                    ),
+                )
+            })?,
+        _ => to_bigint(&value).map_err(|e| e.into_error(caller))?,
+    };
+    Ok(to_value(n))
+}
+/// Wrap a `BigInt` to a signed integer of some number of bits
+/// [[SPEC] - BigInt.asIntN](https://tc39.github.io/ecma262/#sec-bigint.asintn)
+pub fn as_int_n(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let bits = to_bits(args.first(), caller)?;
+    let n = to_bigint(
+        &args
+            .get(1)
+            .cloned()
+            .unwrap_or_else(Value::undefined),
+    )
+    .map_err(|e| e.into_error(caller))?;
+    if bits == 0 {
+        return Ok(to_value(BigInt::zero()));
+    }
+    // Values that fit in `bits - 1` bits plus a sign are already wrapped
+    if n.bits() < bits {
+        return Ok(to_value(n));
+    }
+    let n = wrap_unsigned(n, bits, caller)?;
+    Ok(to_value(if n.bits() == bits {
+        n - (BigInt::one() << bits)
+    } else {
+        n
+    }))
+}
+/// Wrap a `BigInt` to an unsigned integer of some number of bits
+/// [[SPEC] - BigInt.asUintN](https://tc39.github.io/ecma262/#sec-bigint.asuintn)
+pub fn as_uint_n(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let bits = to_bits(args.first(), caller)?;
+    let n = to_bigint(
+        &args
+            .get(1)
+            .cloned()
+            .unwrap_or_else(Value::undefined),
+    )
+    .map_err(|e| e.into_error(caller))?;
+    Ok(to_value(wrap_unsigned(n, bits, caller)?))
+}
+/// Get the decimal digits of a `BigInt`
+pub fn to_locale_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(this_bigint_value(&this, caller)?.to_string()))
+}
+/// Write a `BigInt` in a radix between 2 and 36
+/// [[SPEC] - BigInt.prototype.toString](https://tc39.github.io/ecma262/#sec-bigint.prototype.tostring)
+pub fn to_string(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let n = this_bigint_value(&this, caller)?;
+    let radix = match args.first().map(Value::data).as_deref() {
+        None | Some(ValueData::Undefined) => 10.0,
+        Some(v) => v.to_num().trunc(),
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(caller.new_error(
+            "RangeError",
+            "toString() radix must be between 2 and 36".to_string(),
+        ));
+    }
+    Ok(to_value(n.to_str_radix(radix as u32)))
+}
+/// Get the `BigInt` primitive of a `BigInt`
+pub fn value_of(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(this_bigint_value(&this, caller)?))
+}
+/// Create a new `BigInt` object
+pub fn _create(global: Value) -> Value {
+    let bigint = to_value(make_bigint as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global));
+    proto.set_field_slice(
+        "toLocaleString",
+        to_value(to_locale_string as NativeFunctionData),
+    );
+    proto.set_field_slice("toString", to_value(to_string as NativeFunctionData));
+    proto.set_field_slice("valueOf", to_value(value_of as NativeFunctionData));
+    bigint.set_field_slice(PROTOTYPE, proto);
+    bigint.set_field_slice("asIntN", to_value(as_int_n as NativeFunctionData));
+    bigint.set_field_slice("asUintN", to_value(as_uint_n as NativeFunctionData));
+    bigint
+}
+/// Initialise the global object with the `BigInt` object
+pub fn init(global: Value) {
+    global.set_field_slice("BigInt", _create(global.clone()));
+}
@@ -1,17 +1,56 @@
-use gc::Gc;
-use js::value::{to_value, ResultValue, Value, ValueData};
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::{is_construct, PROTOTYPE};
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
 
-/// Create a new boolean
-pub fn make_boolean(_: Vec<Value>, _: Value, _: Value, this: Value) -> ResultValue {
-    Ok(Gc::new(ValueData::Undefined))
+/// The internal slot of a `Boolean` object holding its primitive value
+pub static BOOLEAN_DATA: &str = "[[BooleanData]]";
+
+/// The boolean primitive `this` is or wraps
+/// [[SPEC] - thisBooleanValue](https://tc39.github.io/ecma262/#sec-thisbooleanvalue)
+fn this_boolean_value(value: &Value, caller: &dyn Caller) -> Result<bool, Value> {
+    match *value.data() {
+        ValueData::Boolean(v) => Ok(v),
+        ValueData::Object(ref obj) => match obj.borrow().get_internal_slot(BOOLEAN_DATA) {
+            Some(prop) => Ok(prop.is_true()),
+            None => Err(caller.new_error(
+                "TypeError",
+                "this is not a Boolean object.".to_string(),
+            )),
+        },
+        _ => Err(caller.new_error("TypeError", "this is not a Boolean object.".to_string())),
+    }
+}
+
+/// Create a new boolean, or convert a value to one when called as a function
+/// [[SPEC] - The Boolean Constructor](https://tc39.github.io/ecma262/#sec-boolean-constructor)
+pub fn make_boolean(this: Value, callee: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let data = to_value(args.first().is_some_and(|v| v.is_true()));
+    if is_construct(&this, &callee) {
+        this.set_internal_slot(BOOLEAN_DATA, data);
+        Ok(this)
+    } else {
+        Ok(data)
+    }
+}
+/// Get the string `"true"` or `"false"`
+pub fn to_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(this_boolean_value(&this, caller)?.to_string()))
+}
+/// Get the boolean primitive of a boolean
+pub fn value_of(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(this_boolean_value(&this, caller)?))
 }
 /// Create a new `Boolean` object
 pub fn _create(global: Value) -> Value {
-    let boolean = to_value(make_boolean);
+    let boolean = to_value(make_boolean as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global));
+    proto.set_internal_slot(BOOLEAN_DATA, to_value(false));
+    proto.set_field_slice("toString", to_value(to_string as NativeFunctionData));
+    proto.set_field_slice("valueOf", to_value(value_of as NativeFunctionData));
+    boolean.set_field_slice(PROTOTYPE, proto);
     boolean
 }
-/// Initialise the global object with the `Error` object
+/// Initialise the global object with the `Boolean` object
 pub fn init(global: Value) {
-    let global_ptr = global.borrow();
-    global_ptr.set_field_slice("Boolean", _create(global));
+    global.set_field_slice("Boolean", _create(global.clone()));
 }
@@ -0,0 +1,310 @@
+use crate::js::function::{new_native_closure, Caller};
+use crate::js::object::{is_construct, Property, PROTOTYPE};
+use crate::js::value::{from_value, to_value, FromValue, ResultValue, Value, ValueData};
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The internal slot of an object made by a host class holding its Rust data
+pub static HOST_DATA: &str = "[[HostData]]";
+
+/// The Rust data of an object made by a host class, which is a `RefCell` of the class's type
+pub type HostData = Rc<dyn Any>;
+
+/// Makes the Rust data of a new object of a class from the arguments given to its constructor
+type Constructor<T> = Rc<dyn Fn(&[Value], &dyn Caller) -> Result<T, Value>>;
+
+/// The Rust data of an object made by a class for `T`, or `None` if the value isn't one
+///
+/// The data is shared with the object, so changes made through it show up in the script
+pub fn host_data<T: Any>(obj: &Value) -> Option<Rc<RefCell<T>>> {
+    if let ValueData::Object(ref data) = *obj.data() {
+        if let Some(prop) = data.borrow().get_internal_slot(HOST_DATA) {
+            if let ValueData::Host(ref host) = *prop.data() {
+                return host.clone().downcast::<RefCell<T>>().ok();
+            }
+        }
+    }
+    None
+}
+
+/// Make an object with a prototype which carries some Rust data
+pub fn new_host_object<T: Any>(prototype: Value, data: T) -> Value {
+    let obj = ValueData::new_obj_from_prototype(prototype);
+    set_host_data(&obj, data);
+    obj
+}
+
+/// Give an object some Rust data
+fn set_host_data<T: Any>(obj: &Value, data: T) {
+    let host: HostData = Rc::new(RefCell::new(data));
+    obj.set_internal_slot(HOST_DATA, Value::new(ValueData::Host(host)));
+}
+
+impl<T: Any> FromValue for Rc<RefCell<T>> {
+    fn from_value(v: Value) -> Result<Rc<RefCell<T>>, &'static str> {
+        host_data(&v).ok_or("Value is not an object of the expected class")
+    }
+}
+
+/// Conversion of the arguments of a call to Rust values, each with `FromValue`
+///
+/// Missing arguments are converted from `undefined`, and extra ones are left out
+pub trait FromArgs: Sized {
+    /// How many arguments are converted, which is the `length` of functions taking them
+    const LENGTH: usize;
+    /// Convert the arguments
+    fn from_args(args: &[Value]) -> Result<Self, &'static str>;
+}
+
+impl FromArgs for Vec<Value> {
+    const LENGTH: usize = 0;
+    fn from_args(args: &[Value]) -> Result<Vec<Value>, &'static str> {
+        Ok(args.to_vec())
+    }
+}
+
+macro_rules! tuple_from_args {
+    ($length:expr; $($name:ident: $index:expr),*) => {
+        impl<$($name: FromValue),*> FromArgs for ($($name,)*) {
+            const LENGTH: usize = $length;
+            fn from_args(_args: &[Value]) -> Result<Self, &'static str> {
+                Ok(($(from_value::<$name>(
+                    _args.get($index).cloned().unwrap_or_else(Value::undefined),
+                )?,)*))
+            }
+        }
+    };
+}
+
+tuple_from_args!(0;);
+tuple_from_args!(1; A: 0);
+tuple_from_args!(2; A: 0, B: 1);
+tuple_from_args!(3; A: 0, B: 1, C: 2);
+tuple_from_args!(4; A: 0, B: 1, C: 2, D: 3);
+tuple_from_args!(5; A: 0, B: 1, C: 2, D: 3, E: 4);
+
+/// Convert the arguments of a call, throwing a `TypeError` if one can't be
+pub fn convert_args<A: FromArgs>(args: &[Value], caller: &dyn Caller) -> Result<A, Value> {
+    A::from_args(args).map_err(|message| caller.new_error("TypeError", message.to_string()))
+}
+
+/// The Rust data `this` carries, for a method of a class for `T`, or a `TypeError` if it has none
+fn this_data<T: Any>(
+    this: &Value,
+    class: &str,
+    member: &str,
+    caller: &dyn Caller,
+) -> Result<Rc<RefCell<T>>, Value> {
+    host_data(this).ok_or_else(|| {
+        let message = format!(
+            "{}.prototype.{} called on incompatible receiver",
+            class, member
+        );
+        caller.new_error("TypeError", message)
+    })
+}
+
+/// The error thrown when a method is called on an object whose data a method further out is using
+fn in_use(class: &str, caller: &dyn Caller) -> Value {
+    caller.new_error("TypeError", format!("{} object is already in use", class))
+}
+
+/// A Rust type exposed to scripts as a class, whose objects each carry a `T`
+///
+/// ```ignore
+/// let point = Class::new("Point", |(x, y): (f64, f64)| Ok(Point { x, y }))
+///     .method("norm", |p: &mut Point, (): ()| Ok(to_value(p.x.hypot(p.y))))
+///     .getter("x", |p: &Point| to_value(p.x))
+///     .setter("x", |p: &mut Point, x: f64| Ok(p.x = x));
+/// engine.register_class(point);
+/// ```
+///
+/// The constructor, methods and setters convert the arguments they're called with to the Rust
+/// types they take with `FromValue`, throwing a `TypeError` if one can't be converted. Values the
+/// Rust data holds on to keep what they reach alive, but aren't seen by the collector, so a cycle
+/// through them is never collected.
+pub struct Class<T> {
+    /// The name of the class
+    name: String,
+    /// How many arguments the constructor takes
+    length: usize,
+    /// Makes the data of a new object from the arguments given to the constructor
+    constructor: Constructor<T>,
+    /// The methods, getters and setters of the prototype
+    members: Vec<(String, Member)>,
+    /// The functions on the constructor itself, and how many arguments they take
+    statics: Vec<(String, Value, usize)>,
+}
+
+/// Something on the prototype of a class
+enum Member {
+    /// A method, and how many arguments it takes
+    Method(Value, usize),
+    /// A getter, a setter or both, either of which can be `undefined`
+    Accessor(Value, Value),
+}
+
+impl<T: Any> Class<T> {
+    /// Start a class, which makes its objects' data by calling `constructor` with the converted arguments
+    pub fn new<A, F>(name: &str, constructor: F) -> Class<T>
+    where
+        A: FromArgs,
+        F: Fn(A) -> Result<T, Value> + 'static,
+    {
+        Class {
+            name: name.to_string(),
+            length: A::LENGTH,
+            constructor: Rc::new(move |args, caller| constructor(convert_args(args, caller)?)),
+            members: Vec::new(),
+            statics: Vec::new(),
+        }
+    }
+
+    /// The name of the class
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Add a method to the prototype, which is given the data of `this` and the converted arguments
+    pub fn method<A, F>(mut self, name: &str, method: F) -> Class<T>
+    where
+        A: FromArgs,
+        F: Fn(&mut T, A) -> ResultValue + 'static,
+    {
+        let (class, member) = (self.name.clone(), name.to_string());
+        let method = new_native_closure(move |this, _, args, caller| {
+            let data = this_data::<T>(&this, &class, &member, caller)?;
+            let args = convert_args(&args, caller)?;
+            let mut data = data.try_borrow_mut().map_err(|_| in_use(&class, caller))?;
+            method(&mut data, args)
+        });
+        self.members
+            .push((name.to_string(), Member::Method(method, A::LENGTH)));
+        self
+    }
+
+    /// Add a getter to the prototype, which is given the data of `this`
+    pub fn getter<F>(mut self, name: &str, getter: F) -> Class<T>
+    where
+        F: Fn(&T) -> Value + 'static,
+    {
+        let (class, member) = (self.name.clone(), name.to_string());
+        let get = new_native_closure(move |this, _, _, caller| {
+            let data = this_data::<T>(&this, &class, &member, caller)?;
+            let data = data.try_borrow().map_err(|_| in_use(&class, caller))?;
+            Ok(getter(&data))
+        });
+        self.accessor(name, Some(get), None);
+        self
+    }
+
+    /// Add a setter to the prototype, which is given the data of `this` and the converted value assigned
+    pub fn setter<A, F>(mut self, name: &str, setter: F) -> Class<T>
+    where
+        A: FromValue,
+        F: Fn(&mut T, A) -> Result<(), Value> + 'static,
+    {
+        let (class, member) = (self.name.clone(), name.to_string());
+        let set = new_native_closure(move |this, _, args, caller| {
+            let data = this_data::<T>(&this, &class, &member, caller)?;
+            let (val,) = convert_args::<(A,)>(&args, caller)?;
+            let mut data = data.try_borrow_mut().map_err(|_| in_use(&class, caller))?;
+            setter(&mut data, val)?;
+            Ok(Value::undefined())
+        });
+        self.accessor(name, None, Some(set));
+        self
+    }
+
+    /// Add a function to the constructor, which is given the converted arguments
+    pub fn static_method<A, F>(mut self, name: &str, function: F) -> Class<T>
+    where
+        A: FromArgs,
+        F: Fn(A) -> ResultValue + 'static,
+    {
+        let function =
+            new_native_closure(move |_, _, args, caller| function(convert_args(&args, caller)?));
+        self.statics.push((name.to_string(), function, A::LENGTH));
+        self
+    }
+
+    /// Give a getter or setter to an accessor of the prototype, making it if there isn't one by that name
+    fn accessor(&mut self, name: &str, get: Option<Value>, set: Option<Value>) {
+        let existing = self
+            .members
+            .iter_mut()
+            .find_map(|(key, member)| match *member {
+                Member::Accessor(ref mut get, ref mut set) if key == name => Some((get, set)),
+                _ => None,
+            });
+        match existing {
+            Some((old_get, old_set)) => {
+                if let Some(get) = get {
+                    *old_get = get;
+                }
+                if let Some(set) = set {
+                    *old_set = set;
+                }
+            }
+            None => {
+                let get = get.unwrap_or_else(Value::undefined);
+                let set = set.unwrap_or_else(Value::undefined);
+                self.members
+                    .push((name.to_string(), Member::Accessor(get, set)));
+            }
+        }
+    }
+
+    /// Make the constructor of the class, given the global object
+    ///
+    /// Calling the constructor without `new` throws a `TypeError`
+    pub fn build(self, global: &Value) -> Value {
+        let Class {
+            name,
+            length,
+            constructor,
+            members,
+            statics,
+        } = self;
+        let class = name.clone();
+        let ctor = new_native_closure(move |this, callee, args, caller| {
+            if !is_construct(&this, &callee) {
+                let message = format!(
+                    "Class constructor {} cannot be invoked without 'new'",
+                    class
+                );
+                return Err(caller.new_error("TypeError", message));
+            }
+            set_host_data(&this, constructor(&args, caller)?);
+            Ok(this)
+        });
+        let function_prototype = global
+            .get_field_slice("Function")
+            .get_field_slice(PROTOTYPE);
+        let named = |func: Value, name: &str, length: usize| {
+            func.set_prototype(function_prototype.clone());
+            func.set_prop_slice("name", Property::read_only(to_value(name)));
+            func.set_prop_slice("length", Property::read_only(to_value(length as i32)));
+            func
+        };
+        let ctor = named(ctor, &name, length);
+        let prototype = ValueData::new_obj(Some(global.clone()));
+        prototype.set_field_slice("constructor", ctor.clone());
+        for (key, member) in members {
+            match member {
+                Member::Method(method, length) => {
+                    prototype.set_field(key.clone(), named(method, &key, length));
+                }
+                Member::Accessor(get, set) => {
+                    prototype.set_prop(key, Property::accessor(get, set));
+                }
+            }
+        }
+        ctor.set_prop_slice(PROTOTYPE, Property::read_only(prototype));
+        for (key, function, length) in statics {
+            ctor.set_field(key.clone(), named(function, &key, length));
+        }
+        ctor
+    }
+}
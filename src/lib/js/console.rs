@@ -1,35 +1,745 @@
-use gc::Gc;
-use crate::js::function::NativeFunctionData;
-use crate::js::value::{from_value, to_value, ResultValue, Value, ValueData};
-use std::iter::FromIterator;
-use time::{now, strftime};
-/// Print a javascript value to the standard output stream
-pub fn log(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    let args: Vec<String> = FromIterator::from_iter(
-        args.iter()
-            .map(|x| from_value::<String>(x.clone()).unwrap()),
+use crate::js::array::ARRAY_DATA;
+use crate::js::bigint::BIGINT_DATA;
+use crate::js::boolean::BOOLEAN_DATA;
+use crate::js::error::{error_to_string, ERROR_DATA};
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::number::{number_to_string, NUMBER_DATA};
+use crate::js::object::{own_property_keys, Property};
+use crate::js::typed_array::TypedArray;
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
+use std::fmt;
+use time::precise_time_ns;
+
+/// The internal slot of each `console` method holding the counters, timers and group depth they share
+static CONSOLE_STATE: &str = "[[ConsoleState]]";
+/// How deep into nested objects the printer goes before it abbreviates them, as node's does
+const DEFAULT_DEPTH: usize = 2;
+/// How long a printed object can be before it's broken over several lines
+const LINE_WIDTH: usize = 72;
+/// The most elements of an array the printer shows
+const MAX_ARRAY_ITEMS: usize = 100;
+
+/// How severe a message written to the console is, which decides where the default sink writes it
+/// [[SPEC] - Logger](https://console.spec.whatwg.org/#logger)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLevel {
+    /// `console.log` and the methods printing what they're asked to, like `console.table`
+    Log,
+    /// `console.info` and `console.count`
+    Info,
+    /// `console.debug`
+    Debug,
+    /// `console.warn` and misuse of counters and timers
+    Warn,
+    /// `console.error` and failed assertions
+    Error,
+    /// `console.trace`
+    Trace,
+}
+
+impl fmt::Display for ConsoleLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ConsoleLevel::Log => "log",
+            ConsoleLevel::Info => "info",
+            ConsoleLevel::Debug => "debug",
+            ConsoleLevel::Warn => "warn",
+            ConsoleLevel::Error => "error",
+            ConsoleLevel::Trace => "trace",
+        })
+    }
+}
+
+/// Where the messages a script writes to the `console` go, which a host can set to capture them
+pub trait ConsoleSink {
+    /// Write a message, already formatted and indented for the groups it's in
+    fn write(&self, level: ConsoleLevel, message: &str);
+}
+
+/// The sink writing logs to the standard output stream, and warnings, errors and traces to the standard error stream
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdioSink;
+
+impl ConsoleSink for StdioSink {
+    fn write(&self, level: ConsoleLevel, message: &str) {
+        match level {
+            ConsoleLevel::Log | ConsoleLevel::Info | ConsoleLevel::Debug => println!("{}", message),
+            ConsoleLevel::Warn | ConsoleLevel::Error | ConsoleLevel::Trace => {
+                eprintln!("{}", message)
+            }
+        }
+    }
+}
+
+/// Whether a key can be printed without quotes
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    match chars.next() {
+        Some(c) if c.is_alphabetic() || c == '_' || c == '$' => {
+            chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        }
+        _ => false,
+    }
+}
+
+/// Quote a string the way node does, with single quotes unless the string has some in it
+fn quote(text: &str) -> String {
+    let quote = if !text.contains('\'') {
+        '\''
+    } else if !text.contains('"') {
+        '"'
+    } else if !text.contains('`') {
+        '`'
+    } else {
+        '\''
+    };
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push(quote);
+    for c in text.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\\' => out.push_str("\\\\"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if (c as u32) < 0x20 || c as u32 == 0x7f => {
+                out.push_str(&format!("\\x{:02X}", c as u32))
+            }
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
+
+/// Print a number, keeping the sign of negative zero
+fn format_number(n: f64) -> String {
+    if n == 0.0 && n.is_sign_negative() {
+        "-0".to_string()
+    } else {
+        number_to_string(n)
+    }
+}
+
+/// The name of the constructor of an object, found through its prototypes
+fn constructor_name(value: &Value) -> Option<String> {
+    match *value
+        .get_field_slice("constructor")
+        .get_field_slice("name")
+        .data()
+    {
+        ValueData::String(ref name) if !name.is_empty() => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Prints values for people to read, the way node's `util.inspect` does
+struct Inspector {
+    /// How deep into nested objects to go, or `None` to print all of them
+    depth: Option<usize>,
+    /// The objects being printed, outermost first, for spotting the ones that contain themselves
+    seen: Vec<usize>,
+}
+
+impl Inspector {
+    /// Make a printer going `depth` objects deep
+    fn new(depth: Option<usize>) -> Inspector {
+        Inspector {
+            depth,
+            seen: Vec::new(),
+        }
+    }
+
+    /// Whether a value is an array or a typed array, whose elements are printed without their indices
+    fn is_array(&self, value: &Value) -> bool {
+        match *value.data() {
+            ValueData::Object(ref obj) => {
+                let obj = obj.borrow();
+                TypedArray::from_object(&obj).is_some() || obj.has_internal_slot(ARRAY_DATA)
+            }
+            _ => false,
+        }
+    }
+
+    /// Print a value nested `level` objects deep
+    fn inspect(&mut self, value: &Value, level: usize) -> String {
+        match *value.data() {
+            ValueData::Null => "null".to_string(),
+            ValueData::Undefined => "undefined".to_string(),
+            ValueData::Boolean(b) => b.to_string(),
+            ValueData::String(ref s) => quote(&s.to_string()),
+            ValueData::Number(n) => format_number(n),
+            ValueData::Integer(n) => n.to_string(),
+            ValueData::BigInt(ref n) => format!("{}n", n),
+            ValueData::Function(_) => match *value.get_field_slice("name").data() {
+                ValueData::String(ref name) if !name.is_empty() => format!("[Function: {}]", name),
+                _ => "[Function (anonymous)]".to_string(),
+            },
+            ValueData::Object(_) => self.inspect_object(value, level),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Print an object, with its own properties
+    fn inspect_object(&mut self, value: &Value, level: usize) -> String {
+        let (keys, props) = match *value.data() {
+            ValueData::Object(ref obj) => {
+                let obj = obj.borrow();
+                if obj.has_internal_slot(ERROR_DATA) {
+                    return match *value.get_field_slice("stack").data() {
+                        ValueData::String(ref stack) => stack.to_string(),
+                        _ => format!("[{}]", error_to_string(&value.data())),
+                    };
+                }
+                for (slot, name) in &[
+                    (NUMBER_DATA, "Number"),
+                    (BOOLEAN_DATA, "Boolean"),
+                    (BIGINT_DATA, "BigInt"),
+                ] {
+                    if let Some(prop) = obj.get(*slot) {
+                        return format!("[{}: {}]", name, self.inspect(&prop.value, level));
+                    }
+                }
+                let keys = own_property_keys(&obj);
+                let props: Vec<Option<Property>> = keys
+                    .iter()
+                    .map(|key| obj.get(key.as_str()).cloned())
+                    .collect();
+                (keys, props)
+            }
+            _ => return value.to_string(),
+        };
+        let address = value.heap_address().unwrap_or(0);
+        if self.seen.contains(&address) {
+            return "[Circular]".to_string();
+        }
+        let typed_array = TypedArray::from_value(value);
+        let name = constructor_name(value);
+        let is_array = self.is_array(value);
+        let prefix = match (&typed_array, &name) {
+            (Some(array), _) => format!("{}({}) ", array.kind.name(), array.len()),
+            (None, Some(name)) if name != "Object" && name != "Array" => format!("{} ", name),
+            _ => String::new(),
+        };
+        if self.depth.is_some_and(|depth| level > depth) {
+            return if is_array {
+                "[Array]".to_string()
+            } else {
+                format!("[{}]", name.unwrap_or_else(|| "Object".to_string()))
+            };
+        }
+        self.seen.push(address);
+        let mut entries = Vec::new();
+        let mut shown = 0;
+        for (key, prop) in keys.iter().zip(props) {
+            if is_array && key == "length" {
+                continue;
+            }
+            let is_index = key.parse::<u32>().is_ok();
+            if is_array && is_index {
+                shown += 1;
+                if shown > MAX_ARRAY_ITEMS {
+                    continue;
+                }
+            }
+            let printed = match prop {
+                Some(ref prop) if prop.is_accessor() => {
+                    match (prop.get.is_function(), prop.set.is_function()) {
+                        (true, true) => "[Getter/Setter]".to_string(),
+                        (true, false) => "[Getter]".to_string(),
+                        _ => "[Setter]".to_string(),
+                    }
+                }
+                _ => self.inspect(&value.get_field_slice(key), level + 1),
+            };
+            if is_array && is_index {
+                entries.push(printed);
+            } else if is_identifier(key) {
+                entries.push(format!("{}: {}", key, printed));
+            } else {
+                entries.push(format!("{}: {}", quote(key), printed));
+            }
+        }
+        self.seen.pop();
+        if shown > MAX_ARRAY_ITEMS {
+            let more = shown - MAX_ARRAY_ITEMS;
+            entries.push(format!(
+                "... {} more item{}",
+                more,
+                if more == 1 { "" } else { "s" }
+            ));
+        }
+        let (open, close) = if is_array { ("[", "]") } else { ("{", "}") };
+        if entries.is_empty() {
+            return format!("{}{}{}", prefix, open, close);
+        }
+        let width = prefix.len() + entries.iter().map(|e| e.len() + 2).sum::<usize>() + 2;
+        if width <= LINE_WIDTH && !entries.iter().any(|e| e.contains('\n')) {
+            format!("{}{} {} {}", prefix, open, entries.join(", "), close)
+        } else {
+            let lines: Vec<String> = entries
+                .iter()
+                .map(|e| format!("  {}", e.replace('\n', "\n  ")))
+                .collect();
+            format!("{}{}\n{}\n{}", prefix, open, lines.join(",\n"), close)
+        }
+    }
+}
+
+/// Print a value the way `console.dir` and the `%o` and `%O` format specifiers do
+fn inspect(value: &Value) -> String {
+    Inspector::new(Some(DEFAULT_DEPTH)).inspect(value, 0)
+}
+
+/// Print a value as part of a message, where strings appear as they are
+fn display(value: &Value) -> String {
+    match *value.data() {
+        ValueData::String(ref s) => s.to_string(),
+        _ => inspect(value),
+    }
+}
+
+/// Join the arguments of a logging method into a message, filling in the format specifiers of the first
+/// [[SPEC] - Formatter](https://console.spec.whatwg.org/#formatter)
+fn format_args(args: &[Value]) -> String {
+    let format = match args.first().map(|first| first.data()) {
+        Some(ref data) if data.is_string() => data.to_string(),
+        _ => {
+            let parts: Vec<String> = args.iter().map(display).collect();
+            return parts.join(" ");
+        }
+    };
+    let mut rest = args[1..].iter();
+    let mut out = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let spec = match chars.peek() {
+            Some(&spec) => spec,
+            None => {
+                out.push(c);
+                break;
+            }
+        };
+        if spec == '%' {
+            chars.next();
+            out.push('%');
+            continue;
+        }
+        if !"sdifoOc".contains(spec) {
+            out.push(c);
+            continue;
+        }
+        let arg = match rest.next() {
+            Some(arg) => arg,
+            None => {
+                out.push(c);
+                continue;
+            }
+        };
+        chars.next();
+        match spec {
+            's' => out.push_str(&display(arg)),
+            'd' | 'i' => match *arg.data() {
+                ValueData::BigInt(ref n) => out.push_str(&format!("{}n", n)),
+                ValueData::Object(_) | ValueData::Function(_) => out.push_str("NaN"),
+                ref data => out.push_str(&format_number(data.to_num().trunc())),
+            },
+            'f' => match *arg.data() {
+                ValueData::Object(_) | ValueData::Function(_) => out.push_str("NaN"),
+                ref data => out.push_str(&format_number(data.to_num())),
+            },
+            'o' | 'O' => out.push_str(&inspect(arg)),
+            // Styles only mean something to a browser's console
+            _ => (),
+        }
+    }
+    for arg in rest {
+        out.push(' ');
+        out.push_str(&display(arg));
+    }
+    out
+}
+
+/// The object a console's methods keep their counters, timers and group depth in
+fn state(callee: &Value) -> Value {
+    callee.get_internal_slot(CONSOLE_STATE)
+}
+
+/// Write a message to the console's sink, indented for the groups it's in
+fn emit(callee: &Value, caller: &mut dyn Caller, level: ConsoleLevel, message: String) {
+    let depth = state(callee)
+        .get_field_slice("groupDepth")
+        .to_num()
+        .max(0.0) as usize;
+    if depth == 0 {
+        return caller.console_write(level, &message);
+    }
+    let indent = "  ".repeat(depth);
+    let message = format!(
+        "{}{}",
+        indent,
+        message.replace('\n', &format!("\n{}", indent))
     );
-    println!("{}: {}", strftime("%X", &now()).unwrap(), args.join(" "));
-    Ok(Gc::new(ValueData::Undefined))
-}
-/// Print a javascript value to the standard error stream
-pub fn error(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    let args: Vec<String> = FromIterator::from_iter(
-        args.iter()
-            .map(|x| from_value::<String>(x.clone()).unwrap()),
+    caller.console_write(level, &message);
+}
+
+/// The label a counter or timer method is called with, which is `default` if it's left out
+fn label(args: &[Value]) -> String {
+    match args.first() {
+        Some(label) if !label.is_undefined() => label.to_string(),
+        _ => "default".to_string(),
+    }
+}
+
+/// The milliseconds since some fixed point in the past, for measuring how long timers run
+fn now_ms() -> f64 {
+    precise_time_ns() as f64 / 1e6
+}
+
+/// Write a message to the standard output stream
+pub fn log(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    emit(&callee, caller, ConsoleLevel::Log, format_args(&args));
+    Ok(Value::undefined())
+}
+
+/// Write an informative message
+pub fn info(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    emit(&callee, caller, ConsoleLevel::Info, format_args(&args));
+    Ok(Value::undefined())
+}
+
+/// Write a debugging message
+pub fn debug(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    emit(&callee, caller, ConsoleLevel::Debug, format_args(&args));
+    Ok(Value::undefined())
+}
+
+/// Write a warning
+pub fn warn(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    emit(&callee, caller, ConsoleLevel::Warn, format_args(&args));
+    Ok(Value::undefined())
+}
+
+/// Write an error
+pub fn error(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    emit(&callee, caller, ConsoleLevel::Error, format_args(&args));
+    Ok(Value::undefined())
+}
+
+/// Write a message followed by the functions being called
+/// [[SPEC] - console.trace](https://console.spec.whatwg.org/#trace)
+pub fn trace(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let mut message = if args.is_empty() {
+        "Trace".to_string()
+    } else {
+        format!("Trace: {}", format_args(&args))
+    };
+    let stack = caller.stack_trace();
+    if !stack.is_empty() {
+        message.push('\n');
+        message.push_str(&stack);
+    }
+    emit(&callee, caller, ConsoleLevel::Trace, message);
+    Ok(Value::undefined())
+}
+
+/// Write an error if a condition is false
+/// [[SPEC] - console.assert](https://console.spec.whatwg.org/#assert)
+pub fn assert(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if args.first().is_some_and(|condition| condition.is_true()) {
+        return Ok(Value::undefined());
+    }
+    let data = if args.is_empty() { &[][..] } else { &args[1..] };
+    let message = match data.first() {
+        None => "Assertion failed".to_string(),
+        Some(first) if first.is_string() => {
+            let mut data = data.to_vec();
+            data[0] = to_value(format!("Assertion failed: {}", first));
+            format_args(&data)
+        }
+        Some(_) => format!("Assertion failed {}", format_args(data)),
+    };
+    emit(&callee, caller, ConsoleLevel::Error, message);
+    Ok(Value::undefined())
+}
+
+/// Count the calls with a label, and write how many there have been
+/// [[SPEC] - console.count](https://console.spec.whatwg.org/#count)
+pub fn count(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let label = label(&args);
+    let counts = state(&callee).get_field_slice("counts");
+    let previous = counts.get_field_slice(&label);
+    let count = if previous.is_undefined() {
+        1.0
+    } else {
+        previous.to_num() + 1.0
+    };
+    counts.set_field(label.clone(), to_value(count));
+    emit(
+        &callee,
+        caller,
+        ConsoleLevel::Info,
+        format!("{}: {}", label, count),
     );
-    eprintln!("{}: {}", strftime("%X", &now()).unwrap(), args.join(" "));
-    Ok(Gc::new(ValueData::Undefined))
+    Ok(Value::undefined())
+}
+
+/// Start counting the calls with a label from zero again
+/// [[SPEC] - console.countReset](https://console.spec.whatwg.org/#countreset)
+pub fn count_reset(
+    _: Value,
+    callee: Value,
+    args: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    let label = label(&args);
+    let counts = state(&callee).get_field_slice("counts");
+    if counts.get_field_slice(&label).is_undefined() {
+        let message = format!("Count for '{}' does not exist", label);
+        emit(&callee, caller, ConsoleLevel::Warn, message);
+    } else {
+        counts.set_field(label, to_value(0));
+    }
+    Ok(Value::undefined())
+}
+
+/// Write a message, if there is one, and indent the messages after it until `groupEnd` is called
+/// [[SPEC] - console.group](https://console.spec.whatwg.org/#group)
+pub fn group(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if !args.is_empty() {
+        emit(&callee, caller, ConsoleLevel::Log, format_args(&args));
+    }
+    let state = state(&callee);
+    let depth = state.get_field_slice("groupDepth").to_num();
+    state.set_field_slice("groupDepth", to_value(depth + 1.0));
+    Ok(Value::undefined())
+}
+
+/// Stop indenting the messages of the group started last
+/// [[SPEC] - console.groupEnd](https://console.spec.whatwg.org/#groupend)
+pub fn group_end(_: Value, callee: Value, _: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let state = state(&callee);
+    let depth = state.get_field_slice("groupDepth").to_num();
+    state.set_field_slice("groupDepth", to_value((depth - 1.0).max(0.0)));
+    Ok(Value::undefined())
+}
+
+/// Start a timer with a label
+/// [[SPEC] - console.time](https://console.spec.whatwg.org/#time)
+pub fn time(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let label = label(&args);
+    let timers = state(&callee).get_field_slice("timers");
+    if timers.get_field_slice(&label).is_undefined() {
+        timers.set_field(label, to_value(now_ms()));
+    } else {
+        let message = format!("Timer '{}' already exists", label);
+        emit(&callee, caller, ConsoleLevel::Warn, message);
+    }
+    Ok(Value::undefined())
+}
+
+/// Write how long a timer has been running, followed by any other arguments, and stop it if `end` is set
+fn report_timer(callee: &Value, args: &[Value], caller: &mut dyn Caller, end: bool) {
+    let label = label(args);
+    let timers = state(callee).get_field_slice("timers");
+    let start = timers.get_field_slice(&label);
+    if start.is_undefined() {
+        let message = format!("Timer '{}' does not exist", label);
+        return emit(callee, caller, ConsoleLevel::Warn, message);
+    }
+    let mut message = format!("{}: {:.3}ms", label, now_ms() - start.to_num());
+    if !end && args.len() > 1 {
+        let data: Vec<String> = args[1..].iter().map(display).collect();
+        message.push(' ');
+        message.push_str(&data.join(" "));
+    }
+    if end {
+        if let ValueData::Object(ref obj) = *timers.data() {
+            obj.borrow_mut().shift_remove(label.as_str());
+        }
+    }
+    emit(callee, caller, ConsoleLevel::Info, message);
+}
+
+/// Write how long a timer has been running
+/// [[SPEC] - console.timeLog](https://console.spec.whatwg.org/#timelog)
+pub fn time_log(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    report_timer(&callee, &args, caller, false);
+    Ok(Value::undefined())
+}
+
+/// Write how long a timer ran for and stop it
+/// [[SPEC] - console.timeEnd](https://console.spec.whatwg.org/#timeend)
+pub fn time_end(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    report_timer(&callee, &args, caller, true);
+    Ok(Value::undefined())
+}
+
+/// Write an object with its properties, without treating strings as format strings
+/// [[SPEC] - console.dir](https://console.spec.whatwg.org/#dir)
+pub fn dir(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let value = args.first().cloned().unwrap_or_else(Value::undefined);
+    let options = args.get(1).cloned().unwrap_or_else(Value::undefined);
+    let depth = match *options.get_field_slice("depth").data() {
+        ValueData::Null => None,
+        ValueData::Number(n) if n == f64::INFINITY => None,
+        ValueData::Number(n) if n >= 0.0 => Some(n as usize),
+        ValueData::Integer(n) if n >= 0 => Some(n as usize),
+        _ => Some(DEFAULT_DEPTH),
+    };
+    let message = Inspector::new(depth).inspect(&value, 0);
+    emit(&callee, caller, ConsoleLevel::Log, message);
+    Ok(Value::undefined())
+}
+
+/// Pad a cell of a table with spaces to a width
+fn pad(cell: &str, width: usize) -> String {
+    format!("{}{}", cell, " ".repeat(width - cell.chars().count()))
+}
+
+/// Write an object as a table, with a row for each property, and a column for each property of those
+/// [[SPEC] - console.table](https://console.spec.whatwg.org/#table)
+pub fn table(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let data = args.first().cloned().unwrap_or_else(Value::undefined);
+    let keys = match *data.data() {
+        ValueData::Object(ref obj) => own_property_keys(&obj.borrow()),
+        _ => return log(this, callee, args, caller),
+    };
+    let mut inspector = Inspector::new(Some(0));
+    let is_array = inspector.is_array(&data);
+    let mut columns: Vec<String> = Vec::new();
+    let mut has_values = false;
+    let mut rows = Vec::new();
+    for key in keys {
+        if is_array && key == "length" {
+            continue;
+        }
+        let row = data.get_field_slice(&key);
+        let mut cells = Vec::new();
+        match *row.data() {
+            ValueData::Object(ref obj) => {
+                let row_is_array = inspector.is_array(&row);
+                for column in own_property_keys(&obj.borrow()) {
+                    if row_is_array && column == "length" {
+                        continue;
+                    }
+                    if !columns.contains(&column) {
+                        columns.push(column.clone());
+                    }
+                    let cell = inspector.inspect(&row.get_field_slice(&column), 1);
+                    cells.push((column, cell));
+                }
+            }
+            _ => {
+                has_values = true;
+                cells.push((String::new(), inspector.inspect(&row, 1)));
+            }
+        }
+        rows.push((key, cells));
+    }
+    // A list of properties picks the columns, and their order
+    if let Some(properties) = args.get(1) {
+        if let ValueData::Object(ref obj) = *properties.data() {
+            columns = own_property_keys(&obj.borrow())
+                .iter()
+                .filter(|key| *key != "length")
+                .map(|key| properties.get_field_slice(key).to_string())
+                .collect();
+        }
+    }
+    let mut header = vec!["(index)".to_string()];
+    header.extend(columns.iter().cloned());
+    if has_values {
+        header.push("Values".to_string());
+    }
+    let grid: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|(key, cells)| {
+            let mut line = vec![key];
+            for column in &columns {
+                let cell = cells.iter().find(|(c, _)| c == column && !c.is_empty());
+                line.push(cell.map_or(String::new(), |(_, cell)| cell.clone()));
+            }
+            if has_values {
+                let cell = cells.iter().find(|(c, _)| c.is_empty());
+                line.push(cell.map_or(String::new(), |(_, cell)| cell.clone()));
+            }
+            line
+        })
+        .collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            grid.iter()
+                .map(|line| line[i].chars().count())
+                .chain(std::iter::once(header[i].chars().count()))
+                .max()
+                .unwrap_or(0)
+                + 2
+        })
+        .collect();
+    let rule = |left: &str, middle: &str, right: &str| {
+        let parts: Vec<String> = widths.iter().map(|w| "─".repeat(*w)).collect();
+        format!("{}{}{}", left, parts.join(middle), right)
+    };
+    let row = |line: &[String]| {
+        let parts: Vec<String> = line
+            .iter()
+            .zip(&widths)
+            .map(|(cell, w)| format!(" {}", pad(cell, w - 1)))
+            .collect();
+        format!("│{}│", parts.join("│"))
+    };
+    let mut lines = vec![rule("┌", "┬", "┐"), row(&header), rule("├", "┼", "┤")];
+    lines.extend(grid.iter().map(|line| row(line)));
+    lines.push(rule("└", "┴", "┘"));
+    emit(&callee, caller, ConsoleLevel::Log, lines.join("\n"));
+    Ok(Value::undefined())
 }
+
 /// Create a new `console` object
 pub fn _create(global: Value) -> Value {
     let console = ValueData::new_obj(Some(global));
-    console.set_field_slice("log", to_value(log as NativeFunctionData));
-    console.set_field_slice("error", to_value(error as NativeFunctionData));
-    console.set_field_slice("exception", to_value(error as NativeFunctionData));
+    let state = ValueData::new_obj(None);
+    state.set_field_slice("counts", ValueData::new_obj(None));
+    state.set_field_slice("timers", ValueData::new_obj(None));
+    state.set_field_slice("groupDepth", to_value(0));
+    let methods: &[(&str, NativeFunctionData)] = &[
+        ("log", log),
+        ("info", info),
+        ("debug", debug),
+        ("warn", warn),
+        ("error", error),
+        ("exception", error),
+        ("trace", trace),
+        ("assert", assert),
+        ("count", count),
+        ("countReset", count_reset),
+        ("group", group),
+        ("groupCollapsed", group),
+        ("groupEnd", group_end),
+        ("time", time),
+        ("timeLog", time_log),
+        ("timeEnd", time_end),
+        ("table", table),
+        ("dir", dir),
+        ("dirxml", log),
+    ];
+    for (name, method) in methods {
+        let method = to_value(*method);
+        method.set_internal_slot(CONSOLE_STATE, state.clone());
+        console.set_field_slice(name, method);
+    }
     console
 }
-/// Initialise the global object with the `console` object
+
+/// Initialise the global object with the `console` object, which needs `Array` made first
 pub fn init(global: Value) {
     global.set_field_slice("console", _create(global.clone()));
 }
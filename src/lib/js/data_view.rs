@@ -0,0 +1,297 @@
+use crate::js::array_buffer::{buffer_bytes, max_byte_length, to_index, SharedBytes};
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::{is_construct, ObjectData, PROTOTYPE};
+use crate::js::typed_array::{TypedArrayKind, BYTE_OFFSET, VIEWED_ARRAY_BUFFER};
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
+
+/// The internal slot of a `DataView` holding its length in bytes, or `undefined` if it tracks the length of its buffer
+pub static DATA_VIEW_BYTE_LENGTH: &str = "[[ByteLength]]";
+
+/// The bytes of the buffer of a `DataView`, where its view starts, and how many bytes it sees,
+/// or `None` for the length if a buffer that shrank no longer holds all of them
+fn view_bounds(obj: &ObjectData) -> Option<(Value, SharedBytes, usize, Option<usize>)> {
+    let buffer = obj.get_internal_slot(VIEWED_ARRAY_BUFFER)?.clone();
+    let bytes = buffer_bytes(&buffer)?;
+    let offset = obj.get_internal_slot(BYTE_OFFSET)?.to_num() as usize;
+    let byte_length = &obj.get_internal_slot(DATA_VIEW_BYTE_LENGTH)?;
+    let buffer_len = bytes.borrow().len();
+    let len = if byte_length.is_undefined() {
+        if offset <= buffer_len {
+            Some(buffer_len - offset)
+        } else {
+            None
+        }
+    } else {
+        let len = byte_length.to_num() as usize;
+        if offset + len <= buffer_len {
+            Some(len)
+        } else {
+            None
+        }
+    };
+    Some((buffer, bytes, offset, len))
+}
+
+/// Resolve a field of a `DataView` whose value depends on its buffer, or `None` if the field is an ordinary property
+pub fn get_view_field(obj: &ObjectData, field: &str) -> Option<Value> {
+    let (buffer, _, offset, len) = view_bounds(obj)?;
+    match field {
+        "buffer" => Some(buffer),
+        "byteLength" => Some(to_value(len.unwrap_or(0) as f64)),
+        "byteOffset" => Some(to_value(len.map_or(0.0, |_| offset as f64))),
+        _ => None,
+    }
+}
+
+/// The bytes `this` sees and where they start, or throw if it isn't a `DataView` in bounds of its buffer
+fn this_view_bounds(
+    this: &Value,
+    caller: &dyn Caller,
+) -> Result<(SharedBytes, usize, usize), Value> {
+    let bounds = match *this.data() {
+        ValueData::Object(ref obj) => match obj.borrow().get_internal_slot(DATA_VIEW_BYTE_LENGTH) {
+            Some(_) => view_bounds(&obj.borrow()),
+            None => None,
+        },
+        _ => None,
+    };
+    match bounds {
+        Some((_, bytes, offset, Some(len))) => Ok((bytes, offset, len)),
+        Some(_) => Err(caller.new_error(
+            "TypeError",
+            "DataView is out of bounds of its buffer".to_string(),
+        )),
+        None => Err(caller.new_error("TypeError", "this is not a DataView.".to_string())),
+    }
+}
+
+/// Read a value of some type at a byte offset of a `DataView`, big-endian unless told otherwise
+/// [[SPEC] - GetViewValue](https://tc39.github.io/ecma262/#sec-getviewvalue)
+fn get_view_value(
+    this: &Value,
+    args: &[Value],
+    kind: TypedArrayKind,
+    caller: &dyn Caller,
+) -> ResultValue {
+    let index = to_index(
+        args.first(),
+        "Offset is outside the bounds of the DataView",
+        caller,
+    )?;
+    let little_endian = args.get(1).is_some_and(|v| v.is_true());
+    let (bytes, offset, len) = this_view_bounds(this, caller)?;
+    let size = kind.element_size();
+    if index + size > len {
+        return Err(caller.new_error(
+            "RangeError",
+            "Offset is outside the bounds of the DataView".to_string(),
+        ));
+    }
+    let start = offset + index;
+    let bytes = bytes.borrow();
+    Ok(kind.decode(&bytes[start..start + size], little_endian))
+}
+
+/// Write a value of some type at a byte offset of a `DataView`, big-endian unless told otherwise
+/// [[SPEC] - SetViewValue](https://tc39.github.io/ecma262/#sec-setviewvalue)
+fn set_view_value(
+    this: &Value,
+    args: &[Value],
+    kind: TypedArrayKind,
+    caller: &dyn Caller,
+) -> ResultValue {
+    let index = to_index(
+        args.first(),
+        "Offset is outside the bounds of the DataView",
+        caller,
+    )?;
+    let value = args
+        .get(1)
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    let little_endian = args.get(2).is_some_and(|v| v.is_true());
+    let element = kind
+        .encode(&value, little_endian)
+        .map_err(|e| e.into_error(caller))?;
+    let (bytes, offset, len) = this_view_bounds(this, caller)?;
+    if index + element.len() > len {
+        return Err(caller.new_error(
+            "RangeError",
+            "Offset is outside the bounds of the DataView".to_string(),
+        ));
+    }
+    let start = offset + index;
+    bytes.borrow_mut()[start..start + element.len()].copy_from_slice(&element);
+    Ok(Value::undefined())
+}
+
+/// Create a new `DataView` over some of the bytes of an `ArrayBuffer`
+/// [[SPEC] - The DataView Constructor](https://tc39.github.io/ecma262/#sec-dataview-constructor)
+pub fn make_data_view(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if !is_construct(&this, &callee) {
+        return Err(caller.new_error(
+            "TypeError",
+            "Constructor DataView requires 'new'".to_string(),
+        ));
+    }
+    let buffer = args
+        .first()
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    let bytes = match buffer_bytes(&buffer) {
+        Some(bytes) => bytes,
+        None => {
+            return Err(caller.new_error(
+                "TypeError",
+                "First argument to DataView constructor must be an ArrayBuffer".to_string(),
+            ));
+        }
+    };
+    let offset = to_index(
+        args.get(1),
+        "Start offset is outside the bounds of the buffer",
+        caller,
+    )?;
+    let buffer_len = bytes.borrow().len();
+    if offset > buffer_len {
+        return Err(caller.new_error("RangeError", format!(
+            "Start offset {} is outside the bounds of the buffer",
+            offset
+        )));
+    }
+    let byte_length = match args.get(2) {
+        Some(len) if !len.is_undefined() => {
+            let len = to_index(Some(len), "Invalid DataView length", caller)?;
+            if offset + len > buffer_len {
+                return Err(
+                    caller.new_error("RangeError", format!("Invalid DataView length {}", len))
+                );
+            }
+            to_value(len as f64)
+        }
+        // A view on a resizable buffer without a length follows the length of the buffer
+        _ if max_byte_length(&buffer).is_some() => Value::undefined(),
+        _ => to_value((buffer_len - offset) as f64),
+    };
+    this.set_internal_slot(VIEWED_ARRAY_BUFFER, buffer);
+    this.set_internal_slot(BYTE_OFFSET, to_value(offset as f64));
+    this.set_internal_slot(DATA_VIEW_BYTE_LENGTH, byte_length);
+    Ok(this)
+}
+/// Read a signed 8-bit integer
+pub fn get_int8(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::Int8, caller)
+}
+/// Read an unsigned 8-bit integer
+pub fn get_uint8(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::Uint8, caller)
+}
+/// Read a signed 16-bit integer
+pub fn get_int16(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::Int16, caller)
+}
+/// Read an unsigned 16-bit integer
+pub fn get_uint16(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::Uint16, caller)
+}
+/// Read a signed 32-bit integer
+pub fn get_int32(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::Int32, caller)
+}
+/// Read an unsigned 32-bit integer
+pub fn get_uint32(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::Uint32, caller)
+}
+/// Read a 32-bit float
+pub fn get_float32(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::Float32, caller)
+}
+/// Read a 64-bit float
+pub fn get_float64(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::Float64, caller)
+}
+/// Read a signed 64-bit integer as a `BigInt`
+pub fn get_big_int64(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::BigInt64, caller)
+}
+/// Read an unsigned 64-bit integer as a `BigInt`
+pub fn get_big_uint64(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_view_value(&this, &args, TypedArrayKind::BigUint64, caller)
+}
+/// Write a signed 8-bit integer
+pub fn set_int8(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::Int8, caller)
+}
+/// Write an unsigned 8-bit integer
+pub fn set_uint8(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::Uint8, caller)
+}
+/// Write a signed 16-bit integer
+pub fn set_int16(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::Int16, caller)
+}
+/// Write an unsigned 16-bit integer
+pub fn set_uint16(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::Uint16, caller)
+}
+/// Write a signed 32-bit integer
+pub fn set_int32(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::Int32, caller)
+}
+/// Write an unsigned 32-bit integer
+pub fn set_uint32(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::Uint32, caller)
+}
+/// Write a 32-bit float
+pub fn set_float32(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::Float32, caller)
+}
+/// Write a 64-bit float
+pub fn set_float64(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::Float64, caller)
+}
+/// Write a `BigInt` as a signed 64-bit integer
+pub fn set_big_int64(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::BigInt64, caller)
+}
+/// Write a `BigInt` as an unsigned 64-bit integer
+pub fn set_big_uint64(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_view_value(&this, &args, TypedArrayKind::BigUint64, caller)
+}
+/// Create a new `DataView` object
+pub fn _create(global: Value) -> Value {
+    let data_view = to_value(make_data_view as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global));
+    proto.set_field_slice("getInt8", to_value(get_int8 as NativeFunctionData));
+    proto.set_field_slice("getUint8", to_value(get_uint8 as NativeFunctionData));
+    proto.set_field_slice("getInt16", to_value(get_int16 as NativeFunctionData));
+    proto.set_field_slice("getUint16", to_value(get_uint16 as NativeFunctionData));
+    proto.set_field_slice("getInt32", to_value(get_int32 as NativeFunctionData));
+    proto.set_field_slice("getUint32", to_value(get_uint32 as NativeFunctionData));
+    proto.set_field_slice("getFloat32", to_value(get_float32 as NativeFunctionData));
+    proto.set_field_slice("getFloat64", to_value(get_float64 as NativeFunctionData));
+    proto.set_field_slice("getBigInt64", to_value(get_big_int64 as NativeFunctionData));
+    proto.set_field_slice(
+        "getBigUint64",
+        to_value(get_big_uint64 as NativeFunctionData),
+    );
+    proto.set_field_slice("setInt8", to_value(set_int8 as NativeFunctionData));
+    proto.set_field_slice("setUint8", to_value(set_uint8 as NativeFunctionData));
+    proto.set_field_slice("setInt16", to_value(set_int16 as NativeFunctionData));
+    proto.set_field_slice("setUint16", to_value(set_uint16 as NativeFunctionData));
+    proto.set_field_slice("setInt32", to_value(set_int32 as NativeFunctionData));
+    proto.set_field_slice("setUint32", to_value(set_uint32 as NativeFunctionData));
+    proto.set_field_slice("setFloat32", to_value(set_float32 as NativeFunctionData));
+    proto.set_field_slice("setFloat64", to_value(set_float64 as NativeFunctionData));
+    proto.set_field_slice("setBigInt64", to_value(set_big_int64 as NativeFunctionData));
+    proto.set_field_slice(
+        "setBigUint64",
+        to_value(set_big_uint64 as NativeFunctionData),
+    );
+    data_view.set_field_slice(PROTOTYPE, proto);
+    data_view
+}
+/// Initialise the global object with the `DataView` object
+pub fn init(global: Value) {
+    global.set_field_slice("DataView", _create(global.clone()));
+}
@@ -0,0 +1,1350 @@
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::{is_construct, PROTOTYPE};
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
+use std::fmt;
+use std::str::FromStr;
+use time::{self, at, Timespec};
+
+/// The internal slot of a `Date` object holding its time value
+pub static DATE_VALUE: &str = "[[DateValue]]";
+
+const MS_PER_SECOND: f64 = 1000.0;
+const MS_PER_MINUTE: f64 = 60_000.0;
+const MS_PER_HOUR: f64 = 3_600_000.0;
+const MS_PER_DAY: f64 = 86_400_000.0;
+/// Time values are limited to 100,000,000 days either side of the epoch
+/// [[SPEC] - Time Values and Time Range](https://tc39.github.io/ecma262/#sec-time-values-and-time-range)
+const MAX_TIME: f64 = 8.64e15;
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+/// The day within a non-leap year on which each month starts
+const MONTH_START: [f64; 13] = [
+    0.0, 31.0, 59.0, 90.0, 120.0, 151.0, 181.0, 212.0, 243.0, 273.0, 304.0, 334.0, 365.0,
+];
+
+/// [[SPEC] - Day Number and Time within Day](https://tc39.github.io/ecma262/#sec-day-number-and-time-within-day)
+fn day(t: f64) -> f64 {
+    (t / MS_PER_DAY).floor()
+}
+
+/// [[SPEC] - Year Number](https://tc39.github.io/ecma262/#sec-year-number)
+fn days_in_year(y: f64) -> f64 {
+    if y % 4.0 != 0.0 {
+        365.0
+    } else if y % 100.0 != 0.0 {
+        366.0
+    } else if y % 400.0 != 0.0 {
+        365.0
+    } else {
+        366.0
+    }
+}
+
+fn day_from_year(y: f64) -> f64 {
+    365.0 * (y - 1970.0) + ((y - 1969.0) / 4.0).floor() - ((y - 1901.0) / 100.0).floor()
+        + ((y - 1601.0) / 400.0).floor()
+}
+
+fn time_from_year(y: f64) -> f64 {
+    MS_PER_DAY * day_from_year(y)
+}
+
+fn year_from_time(t: f64) -> f64 {
+    let mut y = (t / (MS_PER_DAY * 365.2425)).floor() + 1970.0;
+    while time_from_year(y) > t {
+        y -= 1.0;
+    }
+    while time_from_year(y + 1.0) <= t {
+        y += 1.0;
+    }
+    y
+}
+
+fn in_leap_year(t: f64) -> bool {
+    days_in_year(year_from_time(t)) == 366.0
+}
+
+/// [[SPEC] - Month Number](https://tc39.github.io/ecma262/#sec-month-number)
+fn month_start(month: usize, leap: bool) -> f64 {
+    MONTH_START[month] + if leap && month >= 2 { 1.0 } else { 0.0 }
+}
+
+fn day_within_year(t: f64) -> f64 {
+    day(t) - day_from_year(year_from_time(t))
+}
+
+fn month_from_time(t: f64) -> f64 {
+    let day = day_within_year(t);
+    let leap = in_leap_year(t);
+    let mut month = 0;
+    while month < 11 && day >= month_start(month + 1, leap) {
+        month += 1;
+    }
+    month as f64
+}
+
+/// [[SPEC] - Date Number](https://tc39.github.io/ecma262/#sec-date-number)
+fn date_from_time(t: f64) -> f64 {
+    day_within_year(t) - month_start(month_from_time(t) as usize, in_leap_year(t)) + 1.0
+}
+
+/// [[SPEC] - Week Day](https://tc39.github.io/ecma262/#sec-week-day)
+fn week_day(t: f64) -> f64 {
+    (day(t) + 4.0).rem_euclid(7.0)
+}
+
+/// [[SPEC] - Hours, Minutes, Second, and Milliseconds](https://tc39.github.io/ecma262/#sec-hours-minutes-second-and-milliseconds)
+fn hour_from_time(t: f64) -> f64 {
+    (t / MS_PER_HOUR).floor().rem_euclid(24.0)
+}
+
+fn min_from_time(t: f64) -> f64 {
+    (t / MS_PER_MINUTE).floor().rem_euclid(60.0)
+}
+
+fn sec_from_time(t: f64) -> f64 {
+    (t / MS_PER_SECOND).floor().rem_euclid(60.0)
+}
+
+fn ms_from_time(t: f64) -> f64 {
+    t.rem_euclid(MS_PER_SECOND)
+}
+
+/// [[SPEC] - MakeTime](https://tc39.github.io/ecma262/#sec-maketime)
+fn make_time(hour: f64, min: f64, sec: f64, ms: f64) -> f64 {
+    if !(hour.is_finite() && min.is_finite() && sec.is_finite() && ms.is_finite()) {
+        return f64::NAN;
+    }
+    hour.trunc() * MS_PER_HOUR
+        + min.trunc() * MS_PER_MINUTE
+        + sec.trunc() * MS_PER_SECOND
+        + ms.trunc()
+}
+
+/// [[SPEC] - MakeDay](https://tc39.github.io/ecma262/#sec-makeday)
+fn make_day(year: f64, month: f64, date: f64) -> f64 {
+    if !(year.is_finite() && month.is_finite() && date.is_finite()) {
+        return f64::NAN;
+    }
+    let (y, m, dt) = (year.trunc(), month.trunc(), date.trunc());
+    let ym = y + (m / 12.0).floor();
+    // Anything further out than this is clipped by TimeClip anyway
+    if ym.abs() > 400_000.0 {
+        return f64::NAN;
+    }
+    let mn = m.rem_euclid(12.0) as usize;
+    day_from_year(ym) + month_start(mn, days_in_year(ym) == 366.0) + dt - 1.0
+}
+
+/// [[SPEC] - MakeDate](https://tc39.github.io/ecma262/#sec-makedate)
+fn make_date(day: f64, time: f64) -> f64 {
+    if !(day.is_finite() && time.is_finite()) {
+        return f64::NAN;
+    }
+    day * MS_PER_DAY + time
+}
+
+/// [[SPEC] - TimeClip](https://tc39.github.io/ecma262/#sec-timeclip)
+fn time_clip(time: f64) -> f64 {
+    if !time.is_finite() || time.abs() > MAX_TIME {
+        f64::NAN
+    } else {
+        time.trunc() + 0.0
+    }
+}
+
+/// Maps the two-digit years `0` to `99` onto `1900` to `1999`, as the constructor and `Date.UTC` do
+fn full_year(year: f64) -> f64 {
+    let y = year.trunc();
+    if year.is_finite() && (0.0..=99.0).contains(&y) {
+        1900.0 + y
+    } else {
+        year
+    }
+}
+
+/// The current time value
+fn now() -> f64 {
+    let Timespec { sec, nsec } = time::get_time();
+    sec as f64 * MS_PER_SECOND + (nsec / 1_000_000) as f64
+}
+
+/// A date in the year on which a daylight saving time transition happens
+#[derive(Clone, Debug, PartialEq)]
+enum TransitionDate {
+    /// `Jn` - The Julian day `n` (1 to 365), never counting February 29th
+    Julian(u16),
+    /// `n` - The zero-based day `n` (0 to 365), counting February 29th in leap years
+    Zero(u16),
+    /// `Mm.w.d` - Day `d` (0 is Sunday) of week `w` (5 means the last) of month `m`
+    MonthWeekDay(u8, u8, u8),
+}
+
+impl TransitionDate {
+    /// The day number of this date in the given year
+    fn day_in(&self, year: f64) -> f64 {
+        let first = day_from_year(year);
+        match *self {
+            TransitionDate::Julian(n) => {
+                let leap = days_in_year(year) == 366.0;
+                first + n as f64 - 1.0 + if leap && n >= 60 { 1.0 } else { 0.0 }
+            }
+            TransitionDate::Zero(n) => first + n as f64,
+            TransitionDate::MonthWeekDay(m, w, d) => {
+                let month_first = make_day(year, m as f64 - 1.0, 1.0);
+                let month_len = make_day(year, m as f64, 1.0) - month_first;
+                let first_weekday = week_day(month_first * MS_PER_DAY);
+                let mut day = month_first
+                    + (d as f64 - first_weekday).rem_euclid(7.0)
+                    + 7.0 * (w as f64 - 1.0);
+                while day - month_first >= month_len {
+                    day -= 7.0;
+                }
+                day
+            }
+        }
+    }
+}
+
+/// The daylight saving time part of a POSIX `TZ` rule
+#[derive(Clone, Debug, PartialEq)]
+struct DaylightRule {
+    name: String,
+    /// Seconds east of UTC while daylight saving time is in effect
+    offset: i64,
+    start: TransitionDate,
+    /// Seconds after local midnight, in standard time, at which daylight saving time starts
+    start_time: i64,
+    end: TransitionDate,
+    /// Seconds after local midnight, in daylight saving time, at which it ends
+    end_time: i64,
+}
+
+/// A time zone described by a POSIX `TZ` rule, such as `CET-1CEST,M3.5.0,M10.5.0/3`
+/// [[POSIX] - TZ](https://pubs.opengroup.org/onlinepubs/9699919799/basedefs/V1_chap08.html)
+#[derive(Clone, Debug, PartialEq)]
+pub struct PosixTimeZone {
+    /// The rule this time zone was parsed from
+    source: String,
+    name: String,
+    /// Seconds east of UTC during standard time
+    offset: i64,
+    daylight: Option<DaylightRule>,
+}
+
+impl PosixTimeZone {
+    /// The offset in milliseconds east of UTC in effect at the time value `t`
+    fn offset_at(&self, t: f64) -> f64 {
+        match self.daylight_at(t) {
+            Some(rule) => rule.offset as f64 * MS_PER_SECOND,
+            None => self.offset as f64 * MS_PER_SECOND,
+        }
+    }
+
+    /// The daylight saving rule if it is in effect at the time value `t`
+    fn daylight_at(&self, t: f64) -> Option<&DaylightRule> {
+        let rule = self.daylight.as_ref()?;
+        if !t.is_finite() {
+            return None;
+        }
+        let year = year_from_time(t + self.offset as f64 * MS_PER_SECOND);
+        let start = rule.start.day_in(year) * MS_PER_DAY
+            + (rule.start_time - self.offset) as f64 * MS_PER_SECOND;
+        let end = rule.end.day_in(year) * MS_PER_DAY
+            + (rule.end_time - rule.offset) as f64 * MS_PER_SECOND;
+        let in_daylight = if start < end {
+            t >= start && t < end
+        } else {
+            !(t >= end && t < start)
+        };
+        if in_daylight {
+            Some(rule)
+        } else {
+            None
+        }
+    }
+}
+
+/// A cursor over a POSIX `TZ` rule
+struct RuleReader<'a> {
+    rule: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> RuleReader<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.rule.get(self.pos).cloned()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return None;
+        }
+        std::str::from_utf8(&self.rule[start..self.pos])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    /// A zone abbreviation, either alphabetic (`CET`) or quoted (`<+0530>`)
+    fn name(&mut self) -> Option<String> {
+        let start = self.pos;
+        if self.eat(b'<') {
+            while self.peek().is_some_and(|b| b != b'>') {
+                self.pos += 1;
+            }
+            let name = String::from_utf8(self.rule[start + 1..self.pos].to_vec()).ok()?;
+            return if self.eat(b'>') && name.len() >= 3 {
+                Some(name)
+            } else {
+                None
+            };
+        }
+        while self.peek().is_some_and(|b| b.is_ascii_alphabetic()) {
+            self.pos += 1;
+        }
+        if self.pos - start < 3 {
+            return None;
+        }
+        String::from_utf8(self.rule[start..self.pos].to_vec()).ok()
+    }
+
+    /// A signed `hh[:mm[:ss]]` duration, in seconds
+    fn duration(&mut self) -> Option<i64> {
+        let sign = if self.eat(b'-') {
+            -1
+        } else {
+            self.eat(b'+');
+            1
+        };
+        let mut secs = self.number()? * 3600;
+        if self.eat(b':') {
+            secs += self.number()? * 60;
+            if self.eat(b':') {
+                secs += self.number()?;
+            }
+        }
+        Some(sign * secs)
+    }
+
+    /// A transition date with its optional `/time`, which defaults to 02:00
+    fn transition(&mut self) -> Option<(TransitionDate, i64)> {
+        let date = if self.eat(b'J') {
+            match self.number()? {
+                n @ 1..=365 => TransitionDate::Julian(n as u16),
+                _ => return None,
+            }
+        } else if self.eat(b'M') {
+            let month = self.number()?;
+            if !self.eat(b'.') {
+                return None;
+            }
+            let week = self.number()?;
+            if !self.eat(b'.') {
+                return None;
+            }
+            let weekday = self.number()?;
+            if !(1..=12).contains(&month) || !(1..=5).contains(&week) || weekday > 6 {
+                return None;
+            }
+            TransitionDate::MonthWeekDay(month as u8, week as u8, weekday as u8)
+        } else {
+            match self.number()? {
+                n @ 0..=365 => TransitionDate::Zero(n as u16),
+                _ => return None,
+            }
+        };
+        let time = if self.eat(b'/') {
+            self.duration()?
+        } else {
+            7200
+        };
+        Some((date, time))
+    }
+}
+
+impl FromStr for PosixTimeZone {
+    type Err = &'static str;
+    fn from_str(rule: &str) -> Result<PosixTimeZone, &'static str> {
+        let invalid = "Invalid time zone rule";
+        let mut reader = RuleReader {
+            rule: rule.as_bytes(),
+            pos: 0,
+        };
+        let name = reader.name().ok_or(invalid)?;
+        // POSIX offsets count hours west of UTC, the opposite of ECMAScript's
+        let offset = -reader.duration().ok_or(invalid)?;
+        let daylight = if reader.peek().is_some() {
+            let dst_name = reader.name().ok_or(invalid)?;
+            let dst_offset = match reader.peek() {
+                Some(b',') | None => offset + 3600,
+                _ => -reader.duration().ok_or(invalid)?,
+            };
+            let ((start, start_time), (end, end_time)) = if reader.eat(b',') {
+                let start = reader.transition().ok_or(invalid)?;
+                if !reader.eat(b',') {
+                    return Err(invalid);
+                }
+                (start, reader.transition().ok_or(invalid)?)
+            } else {
+                // Without explicit rules POSIX leaves it to the implementation, so follow the US rules
+                (
+                    (TransitionDate::MonthWeekDay(3, 2, 0), 7200),
+                    (TransitionDate::MonthWeekDay(11, 1, 0), 7200),
+                )
+            };
+            Some(DaylightRule {
+                name: dst_name,
+                offset: dst_offset,
+                start,
+                start_time,
+                end,
+                end_time,
+            })
+        } else {
+            None
+        };
+        if reader.peek().is_some() {
+            return Err(invalid);
+        }
+        Ok(PosixTimeZone {
+            source: rule.to_string(),
+            name,
+            offset,
+            daylight,
+        })
+    }
+}
+
+/// The local time zone `Date` uses to convert between UTC and local time
+/// [[SPEC] - LocalTZA](https://tc39.github.io/ecma262/#sec-local-time-zone-adjustment)
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimeZone {
+    /// The time zone of the host, as reported by the C library
+    System,
+    /// A time zone following a POSIX `TZ` rule
+    Posix(PosixTimeZone),
+}
+
+impl TimeZone {
+    /// Coordinated Universal Time
+    pub fn utc() -> TimeZone {
+        TimeZone::fixed(0)
+    }
+
+    /// A time zone without daylight saving time, `offset` minutes east of UTC
+    pub fn fixed(offset: i32) -> TimeZone {
+        let rule = if offset == 0 {
+            "UTC0".to_string()
+        } else {
+            let abs = offset.abs();
+            let (sign, posix_sign) = if offset < 0 { ('-', '+') } else { ('+', '-') };
+            format!(
+                "<{}{:02}{:02}>{}{}:{:02}",
+                sign,
+                abs / 60,
+                abs % 60,
+                posix_sign,
+                abs / 60,
+                abs % 60
+            )
+        };
+        TimeZone::from_str(&rule).unwrap()
+    }
+
+    /// The offset in milliseconds east of UTC in effect at the time value `t`
+    pub fn offset_at(&self, t: f64) -> f64 {
+        match *self {
+            TimeZone::Posix(ref tz) => tz.offset_at(t),
+            TimeZone::System => {
+                if !t.is_finite() {
+                    return 0.0;
+                }
+                let secs = (t.clamp(-MAX_TIME, MAX_TIME) / MS_PER_SECOND).floor() as i64;
+                at(Timespec::new(secs, 0)).tm_utcoff as f64 * MS_PER_SECOND
+            }
+        }
+    }
+
+    /// The offset in milliseconds east of UTC to apply to the local time `t`.
+    /// Local times skipped by a transition, or repeated by one, are interpreted using the offset from before it.
+    pub fn offset_for_local(&self, t: f64) -> f64 {
+        let before = self.offset_at(t - MS_PER_DAY);
+        let after = self.offset_at(t + MS_PER_DAY);
+        if self.offset_at(t - before) == before {
+            before
+        } else if self.offset_at(t - after) == after {
+            after
+        } else {
+            before
+        }
+    }
+
+    /// The abbreviated name of the zone at the time value `t`, when known
+    pub fn name_at(&self, t: f64) -> Option<String> {
+        match *self {
+            TimeZone::Posix(ref tz) => Some(match tz.daylight_at(t) {
+                Some(rule) => rule.name.clone(),
+                None => tz.name.clone(),
+            }),
+            TimeZone::System => None,
+        }
+    }
+
+    /// [[SPEC] - LocalTime](https://tc39.github.io/ecma262/#sec-localtime)
+    fn local_time(&self, t: f64) -> f64 {
+        t + self.offset_at(t)
+    }
+
+    /// [[SPEC] - UTC](https://tc39.github.io/ecma262/#sec-utc-t)
+    fn utc_time(&self, t: f64) -> f64 {
+        if !t.is_finite() {
+            return f64::NAN;
+        }
+        t - self.offset_for_local(t)
+    }
+}
+
+impl FromStr for TimeZone {
+    type Err = &'static str;
+    fn from_str(tz: &str) -> Result<TimeZone, &'static str> {
+        match tz {
+            "" | "System" => Ok(TimeZone::System),
+            "UTC" | "GMT" | "Z" => Ok(TimeZone::utc()),
+            rule => Ok(TimeZone::Posix(PosixTimeZone::from_str(rule)?)),
+        }
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TimeZone::System => write!(f, "System"),
+            TimeZone::Posix(ref tz) => write!(f, "{}", tz.source),
+        }
+    }
+}
+
+/// [[SPEC] - thisTimeValue](https://tc39.github.io/ecma262/#sec-thistimevalue)
+fn this_time_value(value: &Value, caller: &dyn Caller) -> Result<f64, Value> {
+    if let ValueData::Object(ref obj) = *value.data() {
+        if let Some(prop) = obj.borrow().get_internal_slot(DATE_VALUE) {
+            return Ok(prop.to_num());
+        }
+    }
+    Err(caller.new_error("TypeError", "this is not a Date object.".to_string()))
+}
+
+fn set_time_value(this: &Value, t: f64) -> ResultValue {
+    this.set_internal_slot(DATE_VALUE, to_value(t));
+    Ok(to_value(t))
+}
+
+fn arg(args: &[Value], index: usize, default: f64) -> f64 {
+    match args.get(index) {
+        Some(value) => value.to_num(),
+        None => default,
+    }
+}
+
+/// Formats the year the way `toString` and `toUTCString` do
+fn year_string(year: f64) -> String {
+    if year >= 0.0 {
+        format!("{:04}", year)
+    } else {
+        format!("-{:04}", -year)
+    }
+}
+
+/// [[SPEC] - DateString](https://tc39.github.io/ecma262/#sec-datestring)
+fn date_string(tv: f64) -> String {
+    format!(
+        "{} {} {:02} {}",
+        WEEKDAYS[week_day(tv) as usize],
+        MONTHS[month_from_time(tv) as usize],
+        date_from_time(tv),
+        year_string(year_from_time(tv))
+    )
+}
+
+/// [[SPEC] - TimeString](https://tc39.github.io/ecma262/#sec-timestring)
+fn time_string(tv: f64) -> String {
+    format!(
+        "{:02}:{:02}:{:02} GMT",
+        hour_from_time(tv),
+        min_from_time(tv),
+        sec_from_time(tv)
+    )
+}
+
+/// [[SPEC] - TimeZoneString](https://tc39.github.io/ecma262/#sec-timezoneestring)
+fn time_zone_string(tv: f64, tz: &TimeZone) -> String {
+    let offset = tz.offset_at(tv) / MS_PER_MINUTE;
+    let sign = if offset >= 0.0 { '+' } else { '-' };
+    let offset = offset.abs();
+    let numeric = format!("{}{:02}{:02}", sign, (offset / 60.0).floor(), offset % 60.0);
+    match tz.name_at(tv) {
+        Some(name) => format!("{} ({})", numeric, name),
+        None => numeric,
+    }
+}
+
+/// [[SPEC] - ToDateString](https://tc39.github.io/ecma262/#sec-todatestring)
+fn to_date_string_full(tv: f64, tz: &TimeZone) -> String {
+    if tv.is_nan() {
+        return "Invalid Date".to_string();
+    }
+    let t = tz.local_time(tv);
+    format!(
+        "{} {}{}",
+        date_string(t),
+        time_string(t),
+        time_zone_string(tv, tz)
+    )
+}
+
+/// Parses the Date Time String Format, a simplification of ISO 8601
+/// [[SPEC] - Date Time String Format](https://tc39.github.io/ecma262/#sec-date-time-string-format)
+fn parse_iso(s: &str, tz: &TimeZone) -> Option<f64> {
+    let bytes = s.as_bytes();
+    let mut pos = 0;
+    let digits = |count: usize, pos: &mut usize| -> Option<f64> {
+        let slice = bytes.get(*pos..*pos + count)?;
+        if !slice.iter().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        *pos += count;
+        std::str::from_utf8(slice).ok()?.parse().ok()
+    };
+    let year = match bytes.first()? {
+        b'+' | b'-' => {
+            let negative = bytes[0] == b'-';
+            pos += 1;
+            let year = digits(6, &mut pos)?;
+            // -000000 is not a valid extended year
+            if negative && year == 0.0 {
+                return None;
+            }
+            if negative {
+                -year
+            } else {
+                year
+            }
+        }
+        _ => digits(4, &mut pos)?,
+    };
+    let (mut month, mut date) = (1.0, 1.0);
+    if bytes.get(pos) == Some(&b'-') {
+        pos += 1;
+        month = digits(2, &mut pos)?;
+        if bytes.get(pos) == Some(&b'-') {
+            pos += 1;
+            date = digits(2, &mut pos)?;
+        }
+    }
+    if !(1.0..=12.0).contains(&month) || date < 1.0 {
+        return None;
+    }
+    let leap = days_in_year(year) == 366.0;
+    let month_len = month_start(month as usize, leap) - month_start(month as usize - 1, leap);
+    if date > month_len {
+        return None;
+    }
+    let (mut hour, mut min, mut sec, mut ms) = (0.0, 0.0, 0.0, 0.0);
+    // A space is accepted in place of the `T`, as other engines do
+    let has_time = matches!(bytes.get(pos), Some(b'T') | Some(b' '));
+    if has_time {
+        pos += 1;
+        hour = digits(2, &mut pos)?;
+        if bytes.get(pos) != Some(&b':') {
+            return None;
+        }
+        pos += 1;
+        min = digits(2, &mut pos)?;
+        if bytes.get(pos) == Some(&b':') {
+            pos += 1;
+            sec = digits(2, &mut pos)?;
+            if bytes.get(pos) == Some(&b'.') {
+                pos += 1;
+                let start = pos;
+                while bytes.get(pos).is_some_and(|b| b.is_ascii_digit()) {
+                    pos += 1;
+                }
+                if pos == start {
+                    return None;
+                }
+                // Only millisecond precision is kept
+                let fraction: f64 = format!("0.{}", &s[start..pos]).parse().ok()?;
+                ms = (fraction * 1000.0).floor();
+            }
+        }
+        if min > 59.0 || sec > 59.0 || hour > 24.0 {
+            return None;
+        }
+        if hour == 24.0 && (min != 0.0 || sec != 0.0 || ms != 0.0) {
+            return None;
+        }
+    }
+    let offset = match bytes.get(pos) {
+        Some(b'Z') if has_time => {
+            pos += 1;
+            Some(0.0)
+        }
+        Some(&sign) if has_time && (sign == b'+' || sign == b'-') => {
+            pos += 1;
+            let hours = digits(2, &mut pos)?;
+            if bytes.get(pos) != Some(&b':') {
+                return None;
+            }
+            pos += 1;
+            let minutes = digits(2, &mut pos)?;
+            if hours > 23.0 || minutes > 59.0 {
+                return None;
+            }
+            let offset = hours * MS_PER_HOUR + minutes * MS_PER_MINUTE;
+            Some(if sign == b'-' { -offset } else { offset })
+        }
+        _ => None,
+    };
+    if pos != bytes.len() {
+        return None;
+    }
+    let t = make_date(
+        make_day(year, month - 1.0, date),
+        make_time(hour, min, sec, ms),
+    );
+    Some(time_clip(match offset {
+        Some(offset) => t - offset,
+        // Date-only forms are UTC, date-time forms without an offset are local time
+        None if has_time => tz.utc_time(t),
+        None => t,
+    }))
+}
+
+/// The offset in minutes east of UTC of the zone names RFC 2822 allows
+fn zone_offset(name: &str) -> Option<f64> {
+    Some(match name {
+        "UT" | "UTC" | "GMT" | "Z" => 0.0,
+        "EDT" => -4.0 * 60.0,
+        "EST" | "CDT" => -5.0 * 60.0,
+        "CST" | "MDT" => -6.0 * 60.0,
+        "MST" | "PDT" => -7.0 * 60.0,
+        "PST" => -8.0 * 60.0,
+        _ => return None,
+    })
+}
+
+/// Parses `+hhmm` or `+hh:mm`
+fn numeric_offset(s: &str) -> Option<f64> {
+    let sign = match s.chars().next()? {
+        '+' => 1.0,
+        '-' => -1.0,
+        _ => return None,
+    };
+    let digits: String = s[1..].chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: f64 = digits[..2].parse().ok()?;
+    let minutes: f64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 60.0 + minutes))
+}
+
+/// Parses the formats produced by `toString` and `toUTCString`, RFC 2822 dates and a few common variations
+/// such as `Jan 15 2020 10:00` and `1/15/2020`
+fn parse_fallback(s: &str, tz: &TimeZone) -> Option<f64> {
+    let (mut year, mut month, mut date) = (None, None, None);
+    let (mut hour, mut min, mut sec) = (0.0, 0.0, 0.0);
+    let mut offset: Option<f64> = None;
+    let mut pm = None;
+    let mut comment_depth = 0;
+    for word in s.split(|c: char| c.is_whitespace() || c == ',') {
+        if word.is_empty() {
+            continue;
+        }
+        // RFC 2822 comments, and the zone name `toString` appends
+        if word.starts_with('(') || comment_depth > 0 {
+            comment_depth += word.matches('(').count();
+            comment_depth -= word.matches(')').count().min(comment_depth);
+            continue;
+        }
+        let upper = word.to_uppercase();
+        if upper.len() >= 3 && upper.chars().all(|c| c.is_ascii_alphabetic()) {
+            let prefix = &upper[..3];
+            if let Some(m) = MONTHS.iter().position(|m| m.to_uppercase() == prefix) {
+                month = Some(m as f64);
+                continue;
+            }
+            if WEEKDAYS.iter().any(|d| d.to_uppercase() == prefix) {
+                continue;
+            }
+        }
+        if upper == "AM" || upper == "PM" {
+            pm = Some(upper == "PM");
+            continue;
+        }
+        let zone_end = upper
+            .find(['+', '-'])
+            .filter(|i| *i > 0)
+            .unwrap_or(upper.len());
+        if let Some(zone) = zone_offset(&upper[..zone_end]) {
+            offset = Some(if zone_end < upper.len() {
+                zone + numeric_offset(&upper[zone_end..])?
+            } else {
+                zone
+            });
+            continue;
+        }
+        if word.starts_with('+') || (word.starts_with('-') && year.is_some()) {
+            offset = Some(numeric_offset(word)?);
+            continue;
+        }
+        if word.contains(':') {
+            let mut parts = word.split(':');
+            hour = parts.next()?.parse().ok()?;
+            min = parts.next()?.parse().ok()?;
+            sec = match parts.next() {
+                Some(s) => s.parse().ok()?,
+                None => 0.0,
+            };
+            if parts.next().is_some() {
+                return None;
+            }
+            continue;
+        }
+        if word.contains('/') {
+            let parts: Vec<&str> = word.split('/').collect();
+            if parts.len() != 3 {
+                return None;
+            }
+            month = Some(parts[0].parse::<f64>().ok()? - 1.0);
+            date = Some(parts[1].parse().ok()?);
+            year = Some(parts[2].parse().ok()?);
+            continue;
+        }
+        let number: f64 = word.parse().ok()?;
+        if word.starts_with('-') {
+            year = Some(number);
+        } else if date.is_none() && word.len() <= 2 {
+            date = Some(number);
+        } else if year.is_none() {
+            year = Some(if word.len() <= 2 {
+                // RFC 2822 obsolete two-digit years
+                if number < 50.0 {
+                    2000.0 + number
+                } else {
+                    1900.0 + number
+                }
+            } else {
+                number
+            });
+        } else {
+            return None;
+        }
+    }
+    let (year, month, date) = (year?, month?, date?);
+    match pm {
+        Some(true) if hour < 12.0 => hour += 12.0,
+        Some(false) if hour == 12.0 => hour = 0.0,
+        _ => (),
+    }
+    if hour > 24.0 || min > 59.0 || sec > 59.0 || !(1.0..=31.0).contains(&date) {
+        return None;
+    }
+    let t = make_date(make_day(year, month, date), make_time(hour, min, sec, 0.0));
+    Some(time_clip(match offset {
+        Some(offset) => t - offset * MS_PER_MINUTE,
+        None => tz.utc_time(t),
+    }))
+}
+
+/// [[SPEC] - Date.parse](https://tc39.github.io/ecma262/#sec-date.parse)
+fn parse_date(s: &str, tz: &TimeZone) -> f64 {
+    let s = s.trim();
+    parse_iso(s, tz)
+        .or_else(|| parse_fallback(s, tz))
+        .unwrap_or(f64::NAN)
+}
+
+/// Create a new date
+/// [[SPEC] - The Date Constructor](https://tc39.github.io/ecma262/#sec-date-constructor)
+pub fn make_date_object(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let tz = caller.time_zone();
+    if !is_construct(&this, &callee) {
+        return Ok(to_value(to_date_string_full(now(), &tz)));
+    }
+    let tv = match args.len() {
+        0 => now(),
+        1 => match this_time_value(&args[0], caller) {
+            Ok(tv) => tv,
+            Err(_) => time_clip(match *args[0].data() {
+                ValueData::String(ref s) => parse_date(&s.to_string(), &tz),
+                _ => args[0].to_num(),
+            }),
+        },
+        _ => {
+            let date = make_date(
+                make_day(
+                    full_year(arg(&args, 0, f64::NAN)),
+                    arg(&args, 1, f64::NAN),
+                    arg(&args, 2, 1.0),
+                ),
+                make_time(
+                    arg(&args, 3, 0.0),
+                    arg(&args, 4, 0.0),
+                    arg(&args, 5, 0.0),
+                    arg(&args, 6, 0.0),
+                ),
+            );
+            time_clip(tz.utc_time(date))
+        }
+    };
+    this.set_internal_slot(DATE_VALUE, to_value(tv));
+    Ok(this)
+}
+
+/// Get the current time value
+pub fn date_now(_: Value, _: Value, _: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(now()))
+}
+
+/// Parse a date string into a time value
+pub fn date_parse(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let tz = caller.time_zone();
+    let s = match args.first() {
+        Some(value) => value.to_string(),
+        None => return Ok(to_value(f64::NAN)),
+    };
+    Ok(to_value(parse_date(&s, &tz)))
+}
+
+/// Get the time value of a date given in UTC
+/// [[SPEC] - Date.UTC](https://tc39.github.io/ecma262/#sec-date.utc)
+pub fn date_utc(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let date = make_date(
+        make_day(
+            full_year(arg(&args, 0, f64::NAN)),
+            arg(&args, 1, 0.0),
+            arg(&args, 2, 1.0),
+        ),
+        make_time(
+            arg(&args, 3, 0.0),
+            arg(&args, 4, 0.0),
+            arg(&args, 5, 0.0),
+            arg(&args, 6, 0.0),
+        ),
+    );
+    Ok(to_value(time_clip(date)))
+}
+
+/// Reads one component of the date in local time, or in UTC
+fn get_component(
+    this: &Value,
+    local: bool,
+    component: fn(f64) -> f64,
+    caller: &dyn Caller,
+) -> ResultValue {
+    let t = this_time_value(this, caller)?;
+    if t.is_nan() {
+        return Ok(to_value(f64::NAN));
+    }
+    let t = if local {
+        caller.time_zone().local_time(t)
+    } else {
+        t
+    };
+    Ok(to_value(component(t)))
+}
+
+/// Replaces the date's components starting at `first` (0 is the year, 6 the milliseconds) with the arguments,
+/// up to the end of the date or time part it belongs to
+fn set_components(
+    this: &Value,
+    args: &[Value],
+    first: usize,
+    local: bool,
+    caller: &dyn Caller,
+) -> ResultValue {
+    let t = this_time_value(this, caller)?;
+    let tz = caller.time_zone();
+    let last = if first <= 2 { 2 } else { 6 };
+    let values: Vec<f64> = args
+        .iter()
+        .take(last - first + 1)
+        .map(|v| v.to_num())
+        .collect();
+    let base = if t.is_nan() {
+        // Only `setFullYear` can turn an invalid date into a valid one
+        if first != 0 {
+            return Ok(to_value(f64::NAN));
+        }
+        0.0
+    } else if local {
+        tz.local_time(t)
+    } else {
+        t
+    };
+    let mut c = [
+        year_from_time(base),
+        month_from_time(base),
+        date_from_time(base),
+        hour_from_time(base),
+        min_from_time(base),
+        sec_from_time(base),
+        ms_from_time(base),
+    ];
+    if values.is_empty() {
+        c[first] = f64::NAN;
+    }
+    for (i, value) in values.into_iter().enumerate() {
+        c[first + i] = value;
+    }
+    let date = make_date(
+        make_day(c[0], c[1], c[2]),
+        make_time(c[3], c[4], c[5], c[6]),
+    );
+    set_time_value(
+        this,
+        time_clip(if local { tz.utc_time(date) } else { date }),
+    )
+}
+
+/// Get the time value in milliseconds since the epoch
+pub fn get_time(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(this_time_value(&this, caller)?))
+}
+/// Get the year in local time
+pub fn get_full_year(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, true, year_from_time, caller)
+}
+/// Get the zero-based month in local time
+pub fn get_month(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, true, month_from_time, caller)
+}
+/// Get the day of the month in local time
+pub fn get_date(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, true, date_from_time, caller)
+}
+/// Get the day of the week in local time, where 0 is Sunday
+pub fn get_day(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, true, week_day, caller)
+}
+/// Get the hours in local time
+pub fn get_hours(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, true, hour_from_time, caller)
+}
+/// Get the minutes in local time
+pub fn get_minutes(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, true, min_from_time, caller)
+}
+/// Get the seconds in local time
+pub fn get_seconds(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, true, sec_from_time, caller)
+}
+/// Get the milliseconds in local time
+pub fn get_milliseconds(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, true, ms_from_time, caller)
+}
+/// Get the year in UTC
+pub fn get_utc_full_year(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, false, year_from_time, caller)
+}
+/// Get the zero-based month in UTC
+pub fn get_utc_month(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, false, month_from_time, caller)
+}
+/// Get the day of the month in UTC
+pub fn get_utc_date(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, false, date_from_time, caller)
+}
+/// Get the day of the week in UTC, where 0 is Sunday
+pub fn get_utc_day(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, false, week_day, caller)
+}
+/// Get the hours in UTC
+pub fn get_utc_hours(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, false, hour_from_time, caller)
+}
+/// Get the minutes in UTC
+pub fn get_utc_minutes(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, false, min_from_time, caller)
+}
+/// Get the seconds in UTC
+pub fn get_utc_seconds(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, false, sec_from_time, caller)
+}
+/// Get the milliseconds in UTC
+pub fn get_utc_milliseconds(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    get_component(&this, false, ms_from_time, caller)
+}
+/// Get the difference in minutes between UTC and local time
+pub fn get_timezone_offset(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let t = this_time_value(&this, caller)?;
+    if t.is_nan() {
+        return Ok(to_value(f64::NAN));
+    }
+    Ok(to_value(
+        -caller.time_zone().offset_at(t) / MS_PER_MINUTE + 0.0,
+    ))
+}
+
+/// Set the time value in milliseconds since the epoch
+pub fn set_time(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    this_time_value(&this, caller)?;
+    set_time_value(&this, time_clip(arg(&args, 0, f64::NAN)))
+}
+/// Set the year, and optionally the month and day, in local time
+pub fn set_full_year(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 0, true, caller)
+}
+/// Set the month, and optionally the day, in local time
+pub fn set_month(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 1, true, caller)
+}
+/// Set the day of the month in local time
+pub fn set_date(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 2, true, caller)
+}
+/// Set the hours, and optionally the minutes, seconds and milliseconds, in local time
+pub fn set_hours(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 3, true, caller)
+}
+/// Set the minutes, and optionally the seconds and milliseconds, in local time
+pub fn set_minutes(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 4, true, caller)
+}
+/// Set the seconds, and optionally the milliseconds, in local time
+pub fn set_seconds(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 5, true, caller)
+}
+/// Set the milliseconds in local time
+pub fn set_milliseconds(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 6, true, caller)
+}
+/// Set the year, and optionally the month and day, in UTC
+pub fn set_utc_full_year(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 0, false, caller)
+}
+/// Set the month, and optionally the day, in UTC
+pub fn set_utc_month(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 1, false, caller)
+}
+/// Set the day of the month in UTC
+pub fn set_utc_date(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 2, false, caller)
+}
+/// Set the hours, and optionally the minutes, seconds and milliseconds, in UTC
+pub fn set_utc_hours(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 3, false, caller)
+}
+/// Set the minutes, and optionally the seconds and milliseconds, in UTC
+pub fn set_utc_minutes(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 4, false, caller)
+}
+/// Set the seconds, and optionally the milliseconds, in UTC
+pub fn set_utc_seconds(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 5, false, caller)
+}
+/// Set the milliseconds in UTC
+pub fn set_utc_milliseconds(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    set_components(&this, &args, 6, false, caller)
+}
+
+/// Get the date and time in local time, e.g. `Wed Jan 15 2020 11:00:00 GMT+0100 (CET)`
+pub fn to_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let tv = this_time_value(&this, caller)?;
+    Ok(to_value(to_date_string_full(tv, &caller.time_zone())))
+}
+/// Get the date in local time, e.g. `Wed Jan 15 2020`
+pub fn to_date_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let tv = this_time_value(&this, caller)?;
+    if tv.is_nan() {
+        return Ok(to_value("Invalid Date"));
+    }
+    Ok(to_value(date_string(caller.time_zone().local_time(tv))))
+}
+/// Get the time in local time, e.g. `11:00:00 GMT+0100 (CET)`
+pub fn to_time_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let tv = this_time_value(&this, caller)?;
+    if tv.is_nan() {
+        return Ok(to_value("Invalid Date"));
+    }
+    let tz = caller.time_zone();
+    Ok(to_value(format!(
+        "{}{}",
+        time_string(tz.local_time(tv)),
+        time_zone_string(tv, &tz)
+    )))
+}
+/// Get the date and time in UTC, e.g. `Wed, 15 Jan 2020 10:00:00 GMT`
+/// [[SPEC] - Date.prototype.toUTCString](https://tc39.github.io/ecma262/#sec-date.prototype.toutcstring)
+pub fn to_utc_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let tv = this_time_value(&this, caller)?;
+    if tv.is_nan() {
+        return Ok(to_value("Invalid Date"));
+    }
+    Ok(to_value(format!(
+        "{}, {:02} {} {} {}",
+        WEEKDAYS[week_day(tv) as usize],
+        date_from_time(tv),
+        MONTHS[month_from_time(tv) as usize],
+        year_string(year_from_time(tv)),
+        time_string(tv)
+    )))
+}
+/// Get the date and time in UTC in the Date Time String Format, e.g. `2020-01-15T10:00:00.000Z`
+/// [[SPEC] - Date.prototype.toISOString](https://tc39.github.io/ecma262/#sec-date.prototype.toisostring)
+pub fn to_iso_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let tv = this_time_value(&this, caller)?;
+    if tv.is_nan() {
+        return Err(caller.new_error("RangeError", "Invalid time value".to_string()));
+    }
+    let year = year_from_time(tv);
+    let year = if (0.0..=9999.0).contains(&year) {
+        format!("{:04}", year)
+    } else if year < 0.0 {
+        format!("-{:06}", -year)
+    } else {
+        format!("+{:06}", year)
+    };
+    Ok(to_value(format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month_from_time(tv) + 1.0,
+        date_from_time(tv),
+        hour_from_time(tv),
+        min_from_time(tv),
+        sec_from_time(tv),
+        ms_from_time(tv)
+    )))
+}
+/// Get the JSON representation of the date, or `null` if it is invalid
+/// [[SPEC] - Date.prototype.toJSON](https://tc39.github.io/ecma262/#sec-date.prototype.tojson)
+pub fn to_json(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if this_time_value(&this, caller)?.is_finite() {
+        to_iso_string(this, callee, args, caller)
+    } else {
+        Ok(to_value(None::<()>))
+    }
+}
+
+/// Create a new `Date` object
+pub fn _create(global: Value) -> Value {
+    let date = to_value(make_date_object as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global));
+    proto.set_field_slice("getTime", to_value(get_time as NativeFunctionData));
+    proto.set_field_slice("valueOf", to_value(get_time as NativeFunctionData));
+    proto.set_field_slice("getFullYear", to_value(get_full_year as NativeFunctionData));
+    proto.set_field_slice("getMonth", to_value(get_month as NativeFunctionData));
+    proto.set_field_slice("getDate", to_value(get_date as NativeFunctionData));
+    proto.set_field_slice("getDay", to_value(get_day as NativeFunctionData));
+    proto.set_field_slice("getHours", to_value(get_hours as NativeFunctionData));
+    proto.set_field_slice("getMinutes", to_value(get_minutes as NativeFunctionData));
+    proto.set_field_slice("getSeconds", to_value(get_seconds as NativeFunctionData));
+    proto.set_field_slice(
+        "getMilliseconds",
+        to_value(get_milliseconds as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "getUTCFullYear",
+        to_value(get_utc_full_year as NativeFunctionData),
+    );
+    proto.set_field_slice("getUTCMonth", to_value(get_utc_month as NativeFunctionData));
+    proto.set_field_slice("getUTCDate", to_value(get_utc_date as NativeFunctionData));
+    proto.set_field_slice("getUTCDay", to_value(get_utc_day as NativeFunctionData));
+    proto.set_field_slice("getUTCHours", to_value(get_utc_hours as NativeFunctionData));
+    proto.set_field_slice(
+        "getUTCMinutes",
+        to_value(get_utc_minutes as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "getUTCSeconds",
+        to_value(get_utc_seconds as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "getUTCMilliseconds",
+        to_value(get_utc_milliseconds as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "getTimezoneOffset",
+        to_value(get_timezone_offset as NativeFunctionData),
+    );
+    proto.set_field_slice("setTime", to_value(set_time as NativeFunctionData));
+    proto.set_field_slice("setFullYear", to_value(set_full_year as NativeFunctionData));
+    proto.set_field_slice("setMonth", to_value(set_month as NativeFunctionData));
+    proto.set_field_slice("setDate", to_value(set_date as NativeFunctionData));
+    proto.set_field_slice("setHours", to_value(set_hours as NativeFunctionData));
+    proto.set_field_slice("setMinutes", to_value(set_minutes as NativeFunctionData));
+    proto.set_field_slice("setSeconds", to_value(set_seconds as NativeFunctionData));
+    proto.set_field_slice(
+        "setMilliseconds",
+        to_value(set_milliseconds as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "setUTCFullYear",
+        to_value(set_utc_full_year as NativeFunctionData),
+    );
+    proto.set_field_slice("setUTCMonth", to_value(set_utc_month as NativeFunctionData));
+    proto.set_field_slice("setUTCDate", to_value(set_utc_date as NativeFunctionData));
+    proto.set_field_slice("setUTCHours", to_value(set_utc_hours as NativeFunctionData));
+    proto.set_field_slice(
+        "setUTCMinutes",
+        to_value(set_utc_minutes as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "setUTCSeconds",
+        to_value(set_utc_seconds as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "setUTCMilliseconds",
+        to_value(set_utc_milliseconds as NativeFunctionData),
+    );
+    proto.set_field_slice("toString", to_value(to_string as NativeFunctionData));
+    proto.set_field_slice(
+        "toDateString",
+        to_value(to_date_string as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "toTimeString",
+        to_value(to_time_string as NativeFunctionData),
+    );
+    proto.set_field_slice("toLocaleString", to_value(to_string as NativeFunctionData));
+    proto.set_field_slice(
+        "toLocaleDateString",
+        to_value(to_date_string as NativeFunctionData),
+    );
+    proto.set_field_slice(
+        "toLocaleTimeString",
+        to_value(to_time_string as NativeFunctionData),
+    );
+    proto.set_field_slice("toUTCString", to_value(to_utc_string as NativeFunctionData));
+    proto.set_field_slice("toGMTString", to_value(to_utc_string as NativeFunctionData));
+    proto.set_field_slice("toISOString", to_value(to_iso_string as NativeFunctionData));
+    proto.set_field_slice("toJSON", to_value(to_json as NativeFunctionData));
+    date.set_field_slice(PROTOTYPE, proto);
+    date.set_field_slice("length", to_value(7i32));
+    date.set_field_slice("now", to_value(date_now as NativeFunctionData));
+    date.set_field_slice("parse", to_value(date_parse as NativeFunctionData));
+    date.set_field_slice("UTC", to_value(date_utc as NativeFunctionData));
+    date
+}
+/// Initialise the global object with the `Date` object
+pub fn init(global: Value) {
+    global.set_field_slice("Date", _create(global.clone()));
+}
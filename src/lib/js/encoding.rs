@@ -0,0 +1,457 @@
+use crate::js::array_buffer::buffer_bytes;
+use crate::js::data_view::{get_view_field, DATA_VIEW_BYTE_LENGTH};
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::{is_construct, Property, PROTOTYPE};
+use crate::js::typed_array::{new_typed_array, TypedArray, TypedArrayKind};
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
+
+/// The internal slot of a `TextDecoder` holding the name of its encoding, such as `"utf-8"`
+static ENCODING: &str = "[[Encoding]]";
+/// The internal slot of a `TextDecoder` holding whether it throws on malformed input
+static FATAL: &str = "[[Fatal]]";
+/// The internal slot of a `TextDecoder` holding whether it keeps a byte order mark in its output
+static IGNORE_BOM: &str = "[[IgnoreBOM]]";
+/// The internal slot of a `TextDecoder` holding whether the stream it's decoding has started
+static BOM_SEEN: &str = "[[BOMSeen]]";
+/// The internal slot of a `TextDecoder` holding the bytes of an unfinished character at the end
+/// of the last chunk of a stream, as a string with a character for each byte
+static PENDING: &str = "[[PendingBytes]]";
+/// The internal slot of `TextEncoder.prototype.encode` holding the `Uint8Array` constructor
+static UINT8_ARRAY: &str = "[[Uint8Array]]";
+/// The characters of base64, in the order of the values they stand for
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The name of the encoding a label stands for, of the ones a `TextDecoder` supports
+/// [[SPEC] - Get an encoding](https://encoding.spec.whatwg.org/#concept-encoding-get)
+fn encoding_for_label(label: &str) -> Option<&'static str> {
+    let label = label
+        .trim_matches(|c| c == '\t' || c == '\n' || c == '\x0C' || c == '\r' || c == ' ')
+        .to_ascii_lowercase();
+    match label.as_str() {
+        "unicode-1-1-utf-8" | "unicode11utf8" | "unicode20utf8" | "utf-8" | "utf8"
+        | "x-unicode20utf8" => Some("utf-8"),
+        "csunicode" | "iso-10646-ucs-2" | "ucs-2" | "unicode" | "unicodefeff" | "utf-16"
+        | "utf-16le" => Some("utf-16le"),
+        _ => None,
+    }
+}
+
+/// A copy of the bytes an `ArrayBuffer`, typed array or `DataView` sees, or `None` if the value isn't one
+/// [[SPEC] - BufferSource](https://webidl.spec.whatwg.org/#BufferSource)
+fn source_bytes(source: &Value) -> Option<Vec<u8>> {
+    if let Some(bytes) = buffer_bytes(source) {
+        return Some(bytes.borrow().clone());
+    }
+    if let Some(array) = TypedArray::from_value(source) {
+        let len = array.len() * array.kind.element_size();
+        if len == 0 {
+            return Some(Vec::new());
+        }
+        let bytes = array.bytes.borrow();
+        return Some(bytes[array.byte_offset..array.byte_offset + len].to_vec());
+    }
+    if let ValueData::Object(ref obj) = *source.data() {
+        let obj = obj.borrow();
+        if obj.get_internal_slot(DATA_VIEW_BYTE_LENGTH).is_some() {
+            let bytes = buffer_bytes(&get_view_field(&obj, "buffer")?)?;
+            let offset = get_view_field(&obj, "byteOffset")?.to_num() as usize;
+            let len = get_view_field(&obj, "byteLength")?.to_num() as usize;
+            let bytes = bytes.borrow();
+            return Some(bytes[offset..offset + len].to_vec());
+        }
+    }
+    None
+}
+
+/// The error thrown by a `TextDecoder` which is `fatal` for malformed input
+fn decoding_error(caller: &dyn Caller, encoding: &str) -> Value {
+    let message = format!("The encoded data was not valid for encoding {}", encoding);
+    caller.new_error("TypeError", message)
+}
+
+/// Decode UTF-8, returning the text and the bytes of an unfinished character at the end, which
+/// are only left over when `stream` is set, or `None` if `fatal` is set and the bytes are malformed
+/// [[SPEC] - UTF-8 decoder](https://encoding.spec.whatwg.org/#utf-8-decoder)
+fn decode_utf8(mut bytes: &[u8], stream: bool, fatal: bool) -> Option<(String, Vec<u8>)> {
+    let mut text = String::with_capacity(bytes.len());
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                text.push_str(valid);
+                return Some((text, Vec::new()));
+            }
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                text.push_str(std::str::from_utf8(valid).expect("the bytes are valid"));
+                match error.error_len() {
+                    // Like the spec, this replaces each maximal part of a malformed sequence
+                    Some(_) if fatal => return None,
+                    Some(len) => {
+                        text.push('\u{FFFD}');
+                        bytes = &rest[len..];
+                    }
+                    None if stream => return Some((text, rest.to_vec())),
+                    None if fatal => return None,
+                    None => {
+                        text.push('\u{FFFD}');
+                        return Some((text, Vec::new()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Decode UTF-16 in little-endian byte order, as `decode_utf8` decodes UTF-8
+/// [[SPEC] - UTF-16LE decoder](https://encoding.spec.whatwg.org/#utf-16le-decoder)
+fn decode_utf16le(bytes: &[u8], stream: bool, fatal: bool) -> Option<(String, Vec<u8>)> {
+    let mut units: Vec<u16> = bytes
+        .chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    let mut pending = Vec::new();
+    if stream {
+        // A lead surrogate at the end of a chunk may be followed by its trail in the next one
+        if let Some(&last) = units.last() {
+            if (0xD800..=0xDBFF).contains(&last) {
+                units.pop();
+                pending.extend_from_slice(&last.to_le_bytes());
+            }
+        }
+    }
+    if bytes.len() % 2 == 1 {
+        pending.push(bytes[bytes.len() - 1]);
+    }
+    let mut text = String::with_capacity(units.len());
+    for c in std::char::decode_utf16(units) {
+        match c {
+            Ok(c) => text.push(c),
+            Err(_) if fatal => return None,
+            Err(_) => text.push('\u{FFFD}'),
+        }
+    }
+    if !stream && !pending.is_empty() {
+        if fatal {
+            return None;
+        }
+        text.push('\u{FFFD}');
+        pending.clear();
+    }
+    Some((text, pending))
+}
+
+/// Create a new `TextEncoder`, which encodes strings as UTF-8
+/// [[SPEC] - TextEncoder](https://encoding.spec.whatwg.org/#interface-textencoder)
+pub fn make_text_encoder(
+    this: Value,
+    callee: Value,
+    _: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    if !is_construct(&this, &callee) {
+        let message = "Constructor TextEncoder requires 'new'".to_string();
+        return Err(caller.new_error("TypeError", message));
+    }
+    Ok(this)
+}
+/// The encoding of a `TextEncoder`, which is always UTF-8
+pub fn get_encoder_encoding(_: Value, _: Value, _: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value("utf-8"))
+}
+/// Encode a string as a new `Uint8Array` of UTF-8, replacing lone surrogates
+/// [[SPEC] - TextEncoder.prototype.encode](https://encoding.spec.whatwg.org/#dom-textencoder-encode)
+pub fn encode(_: Value, callee: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let text = args.first().map_or(String::new(), |input| {
+        if input.is_undefined() {
+            String::new()
+        } else {
+            input.to_string()
+        }
+    });
+    Ok(new_typed_array(
+        &callee.get_internal_slot(UINT8_ARRAY),
+        text.into_bytes(),
+    ))
+}
+/// Encode as much of a string as fits into a `Uint8Array`, without splitting characters,
+/// returning how many UTF-16 code units were read and how many bytes were written
+/// [[SPEC] - TextEncoder.prototype.encodeInto](https://encoding.spec.whatwg.org/#dom-textencoder-encodeinto)
+pub fn encode_into(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let source = args
+        .first()
+        .map_or_else(|| "undefined".into(), |source| source.to_js_string());
+    let dest = match args.get(1).and_then(TypedArray::from_value) {
+        Some(array) if array.kind == TypedArrayKind::Uint8 => array,
+        _ => {
+            let message = "The destination of encodeInto must be a Uint8Array".to_string();
+            return Err(caller.new_error("TypeError", message));
+        }
+    };
+    let capacity = dest.len();
+    let (mut read, mut written) = (0, 0);
+    {
+        let mut bytes = dest.bytes.borrow_mut();
+        let mut buf = [0u8; 4];
+        for c in std::char::decode_utf16(source.code_units()) {
+            let c = c.unwrap_or('\u{FFFD}');
+            let encoded = c.encode_utf8(&mut buf);
+            if written + encoded.len() > capacity {
+                break;
+            }
+            let start = dest.byte_offset + written;
+            bytes[start..start + encoded.len()].copy_from_slice(encoded.as_bytes());
+            read += c.len_utf16();
+            written += encoded.len();
+        }
+    }
+    let result = ValueData::new_obj_from_prototype(caller.prototype("Object"));
+    result.set_field_slice("read", to_value(read as f64));
+    result.set_field_slice("written", to_value(written as f64));
+    Ok(result)
+}
+/// Create a new `TextDecoder` for an encoding label, which can be told to throw on malformed
+/// input with `fatal`, and to keep a byte order mark with `ignoreBOM`
+/// [[SPEC] - TextDecoder](https://encoding.spec.whatwg.org/#interface-textdecoder)
+pub fn make_text_decoder(
+    this: Value,
+    callee: Value,
+    args: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    if !is_construct(&this, &callee) {
+        let message = "Constructor TextDecoder requires 'new'".to_string();
+        return Err(caller.new_error("TypeError", message));
+    }
+    let label = match args.first() {
+        Some(label) if !label.is_undefined() => label.to_string(),
+        _ => "utf-8".to_string(),
+    };
+    let encoding = match encoding_for_label(&label) {
+        Some(encoding) => encoding,
+        None => {
+            let message = format!("The encoding label provided ('{}') is invalid.", label);
+            return Err(caller.new_error("RangeError", message));
+        }
+    };
+    let options = args.get(1).cloned().unwrap_or_else(Value::undefined);
+    this.set_internal_slot(ENCODING, to_value(encoding));
+    this.set_internal_slot(FATAL, to_value(options.get_field_slice("fatal").is_true()));
+    this.set_internal_slot(
+        IGNORE_BOM,
+        to_value(options.get_field_slice("ignoreBOM").is_true()),
+    );
+    this.set_internal_slot(BOM_SEEN, to_value(false));
+    this.set_internal_slot(PENDING, to_value(""));
+    Ok(this)
+}
+
+/// Get an internal slot of the `TextDecoder` `this` is, or throw if it isn't one
+fn decoder_slot(this: &Value, slot: &str, caller: &dyn Caller) -> ResultValue {
+    if let ValueData::Object(ref obj) = *this.data() {
+        if let Some(value) = obj.borrow().get_internal_slot(slot) {
+            return Ok(value.clone());
+        }
+    }
+    Err(caller.new_error("TypeError", "this is not a TextDecoder".to_string()))
+}
+/// The name of the encoding of a `TextDecoder`
+pub fn get_decoder_encoding(
+    this: Value,
+    _: Value,
+    _: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    decoder_slot(&this, ENCODING, caller)
+}
+/// Whether a `TextDecoder` throws on malformed input
+pub fn get_fatal(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    decoder_slot(&this, FATAL, caller)
+}
+/// Whether a `TextDecoder` keeps a byte order mark in its output
+pub fn get_ignore_bom(
+    this: Value,
+    _: Value,
+    _: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    decoder_slot(&this, IGNORE_BOM, caller)
+}
+/// Decode the bytes of an `ArrayBuffer`, typed array or `DataView` as a string
+///
+/// With `{ stream: true }`, an unfinished character at the end is kept for the next call, for
+/// decoding a stream one chunk at a time, which ends with a call without it.
+/// [[SPEC] - TextDecoder.prototype.decode](https://encoding.spec.whatwg.org/#dom-textdecoder-decode)
+pub fn decode(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let encoding = decoder_slot(&this, ENCODING, caller)?.to_string();
+    let mut bytes: Vec<u8> = this
+        .get_internal_slot(PENDING)
+        .to_js_string()
+        .code_units()
+        .map(|unit| unit as u8)
+        .collect();
+    match args.first() {
+        Some(input) if !input.is_undefined() => match source_bytes(input) {
+            Some(input) => bytes.extend(input),
+            None => {
+                let message =
+                    "The input to decode must be an ArrayBuffer or a view on one".to_string();
+                return Err(caller.new_error("TypeError", message));
+            }
+        },
+        _ => {}
+    }
+    let stream = args
+        .get(1)
+        .is_some_and(|options| options.get_field_slice("stream").is_true());
+    let fatal = this.get_internal_slot(FATAL).is_true();
+    let decoded = if encoding == "utf-8" {
+        decode_utf8(&bytes, stream, fatal)
+    } else {
+        decode_utf16le(&bytes, stream, fatal)
+    };
+    let (mut text, pending) = match decoded {
+        Some(decoded) => decoded,
+        None => {
+            // A stream with an error in it is over, so the next call starts a new one
+            this.set_internal_slot(PENDING, to_value(""));
+            this.set_internal_slot(BOM_SEEN, to_value(false));
+            return Err(decoding_error(caller, &encoding));
+        }
+    };
+    // The byte order mark is left out once, at the start of a stream
+    if !this.get_internal_slot(IGNORE_BOM).is_true() && !this.get_internal_slot(BOM_SEEN).is_true()
+    {
+        if text.starts_with('\u{FEFF}') {
+            text.remove(0);
+            this.set_internal_slot(BOM_SEEN, to_value(true));
+        } else if !text.is_empty() {
+            this.set_internal_slot(BOM_SEEN, to_value(true));
+        }
+    }
+    let pending: String = pending.iter().map(|&byte| byte as char).collect();
+    this.set_internal_slot(PENDING, to_value(pending));
+    if !stream {
+        this.set_internal_slot(BOM_SEEN, to_value(false));
+    }
+    Ok(to_value(text))
+}
+/// The error thrown by `atob` and `btoa`, which is an `InvalidCharacterError` like the DOM's
+fn invalid_character(caller: &dyn Caller, message: &str) -> Value {
+    let error = caller.new_error("Error", message.to_string());
+    error.set_field_slice("name", to_value("InvalidCharacterError"));
+    error
+}
+/// Encode a string of bytes, each a character up to U+00FF, as base64
+/// [[SPEC] - btoa](https://html.spec.whatwg.org/multipage/webappapis.html#dom-btoa)
+pub fn btoa(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let data = args
+        .first()
+        .map_or_else(|| "undefined".into(), |data| data.to_js_string());
+    let mut bytes = Vec::with_capacity(data.len());
+    for unit in data.code_units() {
+        if unit > 0xFF {
+            let message =
+                "The string to be encoded contains characters outside of the Latin1 range.";
+            return Err(invalid_character(caller, message));
+        }
+        bytes.push(unit as u8);
+    }
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &byte)| n | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    Ok(to_value(out))
+}
+/// Decode base64 into a string of bytes, each a character up to U+00FF, ignoring whitespace
+/// [[SPEC] - forgiving-base64 decode](https://infra.spec.whatwg.org/#forgiving-base64-decode)
+pub fn atob(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let data = args
+        .first()
+        .map_or("undefined".to_string(), |data| data.to_string());
+    let mut data: Vec<u8> = data
+        .bytes()
+        .filter(|&byte| !b"\t\n\x0C\r ".contains(&byte))
+        .collect();
+    if data.len().is_multiple_of(4) {
+        for _ in 0..2 {
+            if data.last() == Some(&b'=') {
+                data.pop();
+            }
+        }
+    }
+    let invalid = "The string to be decoded is not correctly encoded.";
+    if data.len() % 4 == 1 {
+        return Err(invalid_character(caller, invalid));
+    }
+    let mut out = String::with_capacity(data.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in data {
+        let value = match BASE64.iter().position(|&c| c == byte) {
+            Some(value) => value as u32,
+            None => return Err(invalid_character(caller, invalid)),
+        };
+        buffer = buffer << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits & 0xFF) as u8 as char);
+        }
+    }
+    Ok(to_value(out))
+}
+/// Create a new `TextEncoder` object
+pub fn _create_text_encoder(global: &Value) -> Value {
+    let text_encoder = to_value(make_text_encoder as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global.clone()));
+    let encode = to_value(encode as NativeFunctionData);
+    encode.set_internal_slot(UINT8_ARRAY, global.get_field_slice("Uint8Array"));
+    proto.set_field_slice("encode", encode);
+    proto.set_field_slice("encodeInto", to_value(encode_into as NativeFunctionData));
+    proto.set_prop_slice(
+        "encoding",
+        Property::accessor(
+            to_value(get_encoder_encoding as NativeFunctionData),
+            Value::undefined(),
+        ),
+    );
+    text_encoder.set_field_slice(PROTOTYPE, proto);
+    text_encoder
+}
+/// Create a new `TextDecoder` object
+pub fn _create_text_decoder(global: &Value) -> Value {
+    let text_decoder = to_value(make_text_decoder as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global.clone()));
+    proto.set_field_slice("decode", to_value(decode as NativeFunctionData));
+    let getters: [(&str, NativeFunctionData); 3] = [
+        ("encoding", get_decoder_encoding),
+        ("fatal", get_fatal),
+        ("ignoreBOM", get_ignore_bom),
+    ];
+    for &(name, getter) in getters.iter() {
+        proto.set_prop_slice(
+            name,
+            Property::accessor(to_value(getter), Value::undefined()),
+        );
+    }
+    text_decoder.set_field_slice(PROTOTYPE, proto);
+    text_decoder
+}
+/// Initialise the global object with `TextEncoder`, `TextDecoder`, `atob` and `btoa`
+/// This needs the typed arrays to be there already
+pub fn init(global: Value) {
+    global.set_field_slice("TextEncoder", _create_text_encoder(&global));
+    global.set_field_slice("TextDecoder", _create_text_decoder(&global));
+    global.set_field_slice("atob", to_value(atob as NativeFunctionData));
+    global.set_field_slice("btoa", to_value(btoa as NativeFunctionData));
+}
@@ -1,32 +1,196 @@
-use gc::Gc;
-use crate::js::function::NativeFunctionData;
-use crate::js::object::PROTOTYPE;
+use crate::js::array::array_create;
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::{is_construct, PROTOTYPE};
 use crate::js::value::{to_value, ResultValue, Value, ValueData};
 
-/// Create a new error
-pub fn make_error(this: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    if args.len() >= 1 {
-        this.set_field_slice("message", to_value(args.get(0).unwrap().to_string()));
+/// The internal slot marking an object as an error instance
+pub static ERROR_DATA: &str = "[[ErrorData]]";
+
+/// The constructors of the native errors, which all inherit from `Error`
+/// [[SPEC] - Native Error Types](https://tc39.github.io/ecma262/#sec-native-error-types-used-in-this-standard)
+pub const NATIVE_ERRORS: [&str; 6] = [
+    "EvalError",
+    "RangeError",
+    "ReferenceError",
+    "SyntaxError",
+    "TypeError",
+    "URIError",
+];
+
+/// Make a new error instance inheriting from `prototype`, with a message and a stack trace
+pub fn new_error(prototype: Value, message: Value, caller: &dyn Caller) -> Value {
+    let error = ValueData::new_obj_from_prototype(prototype);
+    init_error(&error, message, caller);
+    error
+}
+
+/// Give an error instance its message, if it has one, and its stack trace
+fn init_error(error: &Value, message: Value, caller: &dyn Caller) {
+    error.set_internal_slot(ERROR_DATA, to_value(true));
+    if !message.is_undefined() {
+        error.set_field_slice("message", to_value(message.to_string()));
+    }
+    let trace = caller.stack_trace();
+    let header = error_to_string(&error.data());
+    error.set_field_slice(
+        "stack",
+        to_value(if trace.is_empty() {
+            header
+        } else {
+            format!("{}\n{}", header, trace)
+        }),
+    );
+}
+
+/// The object an error constructor fills in: `this` when called with `new`, or a fresh one otherwise
+fn error_object(this: Value, callee: &Value) -> Value {
+    if is_construct(&this, callee) {
+        this
+    } else {
+        ValueData::new_obj_from_prototype(callee.get_field_slice(PROTOTYPE))
+    }
+}
+
+/// Copy the `cause` of an options object onto an error, if it has one
+/// [[SPEC] - InstallErrorCause](https://tc39.github.io/ecma262/#sec-installerrorcause)
+fn install_cause(error: &Value, options: Option<&Value>) {
+    if let Some(options) = options {
+        if options.is_object() && options.get_prop("cause".to_string()).is_some() {
+            error.set_field_slice("cause", options.get_field_slice("cause"));
+        }
+    }
+}
+
+/// Create a new error, with or without `new`
+/// [[SPEC] - Error](https://tc39.github.io/ecma262/#sec-error-message)
+pub fn make_error(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let error = error_object(this, &callee);
+    let message = args
+        .first()
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    install_cause(&error, args.get(1));
+    init_error(&error, message, caller);
+    Ok(error)
+}
+
+/// Create a new error wrapping a list of errors, with or without `new`
+/// [[SPEC] - AggregateError](https://tc39.github.io/ecma262/#sec-aggregate-error)
+pub fn make_aggregate_error(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let error = error_object(this, &callee);
+    let message = args
+        .get(1)
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    install_cause(&error, args.get(2));
+    init_error(&error, message, caller);
+    let list = match args.first() {
+        Some(list) if list.is_object() || list.is_function() => list.clone(),
+        _ => {
+            return Err(caller.new_error(
+                "TypeError",
+                "AggregateError requires a list of errors".to_string(),
+            ))
+        }
+    };
+    let len = list.get_field_slice("length").to_num();
+    let len = if len > 0.0 {
+        len.min(u32::MAX as f64) as u32
+    } else {
+        0
+    };
+    let errors = array_create(caller.prototype("Array"));
+    for i in 0..len {
+        errors.set_field(i.to_string(), list.get_field(i.to_string()));
+    }
+    errors.set_field_slice("length", to_value(len as i32));
+    error.set_field_slice("errors", errors);
+    Ok(error)
+}
+
+/// The string an error converts to, as `Error.prototype.toString` makes it
+pub fn error_to_string(error: &ValueData) -> String {
+    let name = error.get_field_slice("name");
+    let name = if name.is_undefined() {
+        "Error".to_string()
+    } else {
+        name.to_string()
+    };
+    let message = error.get_field_slice("message");
+    let message = if message.is_undefined() {
+        String::new()
+    } else {
+        message.to_string()
+    };
+    if name.is_empty() {
+        message
+    } else if message.is_empty() {
+        name
+    } else {
+        format!("{}: {}", name, message)
     }
-    Ok(Gc::new(ValueData::Undefined))
 }
+
 /// Get the string representation of the error
-pub fn to_string(this: Value, _: Value, _: Vec<Value>) -> ResultValue {
-    let name = this.get_field_slice("name");
-    let message = this.get_field_slice("message");
-    Ok(to_value(format!("{}: {}", name, message).to_string()))
+/// [[SPEC] - Error.prototype.toString](https://tc39.github.io/ecma262/#sec-error.prototype.tostring)
+pub fn to_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if !this.is_object() && !this.is_function() {
+        return Err(caller.new_error(
+            "TypeError",
+            "Error.prototype.toString requires that 'this' be an Object".to_string(),
+        ));
+    }
+    Ok(to_value(error_to_string(&this.data())))
 }
+
+/// Make the constructor of a kind of error, whose prototype inherits from `parent`'s
+fn make_constructor(
+    global: &Value,
+    name: &str,
+    data: NativeFunctionData,
+    length: i32,
+    parent: Option<&Value>,
+) -> Value {
+    let prototype = match parent {
+        Some(parent) => ValueData::new_obj_from_prototype(parent.get_field_slice(PROTOTYPE)),
+        None => ValueData::new_obj(Some(global.clone())),
+    };
+    prototype.set_field_slice("message", to_value(""));
+    prototype.set_field_slice("name", to_value(name));
+    let constructor = to_value(data);
+    constructor.set_field_slice("name", to_value(name));
+    constructor.set_field_slice("length", to_value(length));
+    if let Some(parent) = parent {
+        constructor.set_prototype(parent.clone());
+    }
+    prototype.set_field_slice("constructor", constructor.clone());
+    constructor.set_field_slice(PROTOTYPE, prototype);
+    constructor
+}
+
 /// Create a new `Error` object
 pub fn _create(global: Value) -> Value {
-    let prototype = ValueData::new_obj(Some(global));
-    prototype.set_field_slice("message", to_value(""));
-    prototype.set_field_slice("name", to_value("Error"));
-    prototype.set_field_slice("toString", to_value(to_string as NativeFunctionData));
-    let error = to_value(make_error as NativeFunctionData);
-    error.set_field_slice(PROTOTYPE, prototype);
+    let error = make_constructor(&global, "Error", make_error, 1, None);
+    error
+        .get_field_slice(PROTOTYPE)
+        .set_field_slice("toString", to_value(to_string as NativeFunctionData));
     error
 }
-/// Initialise the global object with the `Error` object
+
+/// Initialise the global object with `Error` and the errors inheriting from it
 pub fn init(global: Value) {
-    global.set_field_slice("Error", _create(global.clone()));
+    let error = _create(global.clone());
+    for name in NATIVE_ERRORS.iter() {
+        let native = make_constructor(&global, name, make_error, 1, Some(&error));
+        global.set_field_slice(name, native);
+    }
+    let aggregate = make_constructor(
+        &global,
+        "AggregateError",
+        make_aggregate_error,
+        2,
+        Some(&error),
+    );
+    global.set_field_slice("AggregateError", aggregate);
+    global.set_field_slice("Error", error);
 }
@@ -1,54 +1,161 @@
-use gc::Gc;
-use crate::js::object::{ObjectData, Property};
+use crate::gc::{GcCell, Trace, Tracer};
+use crate::exec_new::bytecode::Chunk;
+use crate::exec_new::host::Permissions;
+use crate::exec_new::jit::FunctionProfile;
+use crate::exec_new::Script;
+use crate::js::atom::{Atom, AtomMarks};
+use crate::js::console::{ConsoleLevel, ConsoleSink, StdioSink};
+use crate::js::date::TimeZone;
+use crate::js::object::{ObjectData, Property, PROTOTYPE};
 use crate::js::value::{to_value, ResultValue, Value, ValueData};
-use std::collections::HashMap;
 use crate::syntax::ast::expr::Expr;
-use ratel::ast::Statement;
+use ratel::ast::expression::ArrowBody;
+use ratel::ast::PatternList;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
 
-/// fn(this, callee, arguments)
-pub type NativeFunctionData = fn(Value, Value, Vec<Value>) -> ResultValue;
+/// fn(this, callee, arguments, interpreter)
+pub type NativeFunctionData = fn(Value, Value, Vec<Value>, &mut dyn Caller) -> ResultValue;
+
+/// A native function which carries Rust state, called like a `NativeFunctionData`
+pub type NativeClosure = Rc<dyn Fn(Value, Value, Vec<Value>, &mut dyn Caller) -> ResultValue>;
+
+/// The interpreter running a native function, which it can call back into to run the functions it's given
+pub trait Caller {
+    /// Call a function with a value for `this`
+    fn call(&mut self, func: &Value, this: Value, args: Vec<Value>) -> ResultValue;
+    /// Call a function as a constructor, as `new` does
+    fn construct(&mut self, func: &Value, args: Vec<Value>) -> ResultValue;
+    /// The functions being called, innermost first, one per line, for the `stack` of an error
+    fn stack_trace(&self) -> String {
+        String::new()
+    }
+    /// Run source text in the global scope, as an indirect call to `eval` does
+    fn eval_code(&mut self, _source: &str) -> ResultValue {
+        Err(to_value("eval is not supported by this interpreter"))
+    }
+    /// Make a function in the global scope out of the source text of its parameters and body
+    fn compile_function(&mut self, _params: &str, _body: &str) -> ResultValue {
+        Err(to_value(
+            "Creating functions from strings is not supported by this interpreter",
+        ))
+    }
+    /// The prototype of the objects a built-in constructor makes in the realm of the calling code,
+    /// like `Array.prototype` for `Array`
+    fn prototype(&self, constructor: &str) -> Value;
+    /// Make an error of one of the native kinds, like `TypeError`, for a native function to throw
+    fn new_error(&self, _kind: &str, message: String) -> Value {
+        to_value(message)
+    }
+    /// The local time zone `Date` uses
+    fn time_zone(&self) -> Rc<TimeZone> {
+        Rc::new(TimeZone::System)
+    }
+    /// Write a message a script logged with `console` to wherever the host wants it
+    fn console_write(&self, level: ConsoleLevel, message: &str) {
+        StdioSink.write(level, message)
+    }
+    /// What the host functions are allowed to do, or `None` if they're allowed nothing
+    fn permissions(&self) -> Option<&Permissions> {
+        None
+    }
+    /// Stop the running script for good with an exit code for the host, returning what to throw to unwind it
+    fn exit(&mut self, _code: i32) -> Value {
+        to_value("Exiting is not supported by this interpreter")
+    }
+}
+
+/// The function a bound function calls
+pub const BOUND_TARGET_FUNCTION: &str = "[[BoundTargetFunction]]";
+/// The value of `this` a bound function calls its target with
+pub const BOUND_THIS: &str = "[[BoundThis]]";
+/// The arguments a bound function puts in front of the ones it's called with
+pub const BOUND_ARGUMENTS: &str = "[[BoundArguments]]";
+/// The parameter names the indices of a mapped `arguments` object stand for
+pub const PARAMETER_MAP: &str = "[[ParameterMap]]";
+/// The variables of the call a mapped `arguments` object belongs to
+pub const PARAMETER_ENV: &str = "[[ParameterEnv]]";
+
+/// The code of a function written in a script, shared by every function value made from it
+#[derive(Debug)]
+pub struct FunctionCode {
+    /// The script the function is written in, which keeps its syntax tree alive
+    pub script: Rc<Script>,
+    /// The parameters
+    pub params: PatternList<'static>,
+    /// The body, which is a single expression for some arrow functions
+    pub body: ArrowBody<'static>,
+    /// Where the function starts in the source text
+    pub start: u32,
+    /// Where the function ends in the source text
+    pub end: u32,
+    /// If this is an arrow function, which takes `this` and `arguments` from where it's written
+    pub arrow: bool,
+    /// If the function's code is strict mode code
+    pub strict: bool,
+    /// The body compiled to bytecode, once the function has been called
+    pub chunk: RefCell<Option<Rc<Chunk>>>,
+    /// How often the function has been called, and how it's run
+    pub profile: FunctionProfile,
+}
+
+impl FunctionCode {
+    /// The source text of the function
+    pub fn source_text(&self) -> &str {
+        &self.script.source()[self.start as usize..self.end as usize]
+    }
+
+    /// If every parameter is a plain name, without defaults, rest or destructuring
+    pub fn has_simple_params(&self) -> bool {
+        self.params.iter().all(|param| matches!(param.item, ratel::ast::Pattern::Identifier(_)))
+    }
+}
 
 /// A Javascript function
 /// A member of the Object type that may be invoked as a subroutine
 /// https://tc39.github.io/ecma262/#sec-terms-and-definitions-function
 /// In our implementation, Function is extending Object by holding an object field which some extra data
-
+#[derive(Debug, Clone)]
 pub struct NewRegularFunction {
     /// The fields associated with the function
     pub object: ObjectData,
-    /// This function's expression
-    pub expr: Statement<'static>,
-    /// The argument names of the function
-    pub args: Vec<String>,
+    /// This function's code
+    pub code: Rc<FunctionCode>,
+    /// The variables of the scope the function was made in
+    pub env: Value,
+    /// The value of `this` where an arrow function was made
+    pub this: Value,
+    /// The global object of the realm the function was made in, which it runs in
+    pub realm: Value,
 }
 
 impl NewRegularFunction {
     /// Make a new regular function
-    pub fn new(expr: Statement<'static>, args: Vec<String>) -> NewRegularFunction {
-        let mut obj = HashMap::new();
-        obj.insert(
-            "arguments".to_string(),
-            Property::new(Gc::new(ValueData::Integer(args.len() as i32))),
-        );
+    pub fn new(code: Rc<FunctionCode>, env: Value, this: Value, realm: Value) -> NewRegularFunction {
         NewRegularFunction {
-            object: obj,
-            expr: expr,
-            args: args,
+            object: ObjectData::new(),
+            code,
+            env,
+            this,
+            realm,
         }
     }
 }
 
 /// A Javascript function
-#[derive(Trace, Finalize, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub enum Function {
     /// A native javascript function
     NativeFunc(NativeFunction),
     /// A regular javascript function
     RegularFunc(RegularFunction),
+    /// A function written in a script run by the new interpreter
+    NewRegularFunc(NewRegularFunction),
 }
 
 /// Represents a regular javascript function in memory
-#[derive(Trace, Finalize, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct RegularFunction {
     /// The fields associated with the function
     pub object: ObjectData,
@@ -61,45 +168,346 @@ pub struct RegularFunction {
 impl RegularFunction {
     /// Make a new regular function
     pub fn new(expr: Expr, args: Vec<String>) -> RegularFunction {
-        let mut obj = HashMap::new();
+        let mut obj = ObjectData::new();
         obj.insert(
-            "arguments".to_string(),
-            Property::new(Gc::new(ValueData::Integer(args.len() as i32))),
+            "length".to_string(),
+            Property::new(Value::new(ValueData::Integer(args.len() as i32))),
         );
         RegularFunction {
             object: obj,
-            expr: expr,
-            args: args,
+            expr,
+            args,
         }
     }
 }
 
-#[derive(Trace, Finalize, Debug, Clone)]
+#[derive(Debug, Clone)]
 /// Represents a native javascript function in memory
 pub struct NativeFunction {
     /// The fields associated with the function
     pub object: ObjectData,
     /// The callable function data
-    pub data: NativeFunctionData,
+    pub data: NativeBody,
 }
 impl NativeFunction {
     /// Make a new native function with the given function data
     pub fn new(data: NativeFunctionData) -> NativeFunction {
-        let obj = HashMap::new();
+        let obj = ObjectData::new();
         NativeFunction {
             object: obj,
-            data: data,
+            data: NativeBody::Fn(data),
+        }
+    }
+
+    /// Make a new native function which runs a closure
+    pub fn from_closure(closure: NativeClosure) -> NativeFunction {
+        NativeFunction {
+            object: ObjectData::new(),
+            data: NativeBody::Closure(closure),
+        }
+    }
+}
+
+/// What a native function runs when it's called
+#[derive(Clone)]
+pub enum NativeBody {
+    /// A plain Rust function
+    Fn(NativeFunctionData),
+    /// A closure, with the state it captured
+    Closure(NativeClosure),
+}
+
+impl NativeBody {
+    /// Run the function with a value for `this`, itself, and the arguments of the call
+    pub fn call(
+        &self,
+        this: Value,
+        callee: Value,
+        args: Vec<Value>,
+        caller: &mut dyn Caller,
+    ) -> ResultValue {
+        match *self {
+            NativeBody::Fn(data) => data(this, callee, args, caller),
+            NativeBody::Closure(ref closure) => closure(this, callee, args, caller),
+        }
+    }
+}
+
+impl fmt::Debug for NativeBody {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NativeBody::Fn(data) => write!(f, "Fn({:p})", data as *const ()),
+            NativeBody::Closure(_) => write!(f, "Closure"),
+        }
+    }
+}
+
+/// Make a function value which runs a closure, so it can carry Rust state
+pub fn new_native_closure<F>(closure: F) -> Value
+where
+    F: Fn(Value, Value, Vec<Value>, &mut dyn Caller) -> ResultValue + 'static,
+{
+    new_function_value(Function::NativeFunc(NativeFunction::from_closure(Rc::new(
+        closure,
+    ))))
+}
+
+unsafe impl Trace for Function {
+    fn trace(&self, tracer: &mut Tracer) {
+        match *self {
+            Function::NativeFunc(ref f) => f.object.trace(tracer),
+            Function::RegularFunc(ref f) => f.object.trace(tracer),
+            Function::NewRegularFunc(ref f) => {
+                f.object.trace(tracer);
+                f.env.trace(tracer);
+                f.this.trace(tracer);
+                f.realm.trace(tracer);
+            }
+        }
+    }
+
+    fn mark_atoms(&self, marks: &mut AtomMarks) {
+        match *self {
+            Function::NativeFunc(ref f) => f.object.mark_atoms(marks),
+            Function::RegularFunc(ref f) => f.object.mark_atoms(marks),
+            Function::NewRegularFunc(ref f) => f.object.mark_atoms(marks),
+        }
+    }
+
+    fn extra_size(&self) -> usize {
+        match *self {
+            Function::NativeFunc(ref f) => f.object.extra_size(),
+            Function::RegularFunc(ref f) => f.object.extra_size(),
+            Function::NewRegularFunc(ref f) => f.object.extra_size(),
+        }
+    }
+}
+
+/// Make a function value out of a function
+pub fn new_function_value(function: Function) -> Value {
+    Value::new(ValueData::Function(GcCell::new(function)))
+}
+
+/// Create the `arguments` object of a call
+///
+/// When `mapping` holds the parameter names and the variables of the call, the object is mapped:
+/// its indices and the parameters they were passed as read and write the same variables
+pub fn make_arguments(
+    global: &Value,
+    callee: &Value,
+    args: &[Value],
+    mapping: Option<(&[String], &Value)>,
+) -> Value {
+    let arguments = ValueData::new_obj(Some(global.clone()));
+    for (i, arg) in args.iter().enumerate() {
+        arguments.set_field(i, arg.clone());
+    }
+    arguments.set_field_slice("length", to_value(args.len() as i32));
+    if let Some((params, env)) = mapping {
+        let map = ValueData::new_obj(None);
+        for (i, name) in params.iter().enumerate().take(args.len()) {
+            // Only the last of several parameters with the same name is mapped
+            if let ValueData::Object(ref map) = *map.data() {
+                map.borrow_mut()
+                    .retain(|_, prop| prop.value.to_string() != *name);
+            }
+            map.set_field(i, to_value(name.clone()));
+        }
+        arguments.set_internal_slot(PARAMETER_MAP, map);
+        arguments.set_internal_slot(PARAMETER_ENV, env.clone());
+        arguments.set_field_slice("callee", callee.clone());
+    }
+    arguments
+}
+
+/// The parameter a field of a mapped `arguments` object stands for, and the variables it's in
+fn mapped_parameter(obj: &ObjectData, field: Atom) -> Option<(String, Value)> {
+    let map = obj.get_internal_slot(PARAMETER_MAP)?.clone();
+    let name = map.get_prop(field)?.value.to_string();
+    Some((name, obj.get_internal_slot(PARAMETER_ENV)?.clone()))
+}
+
+/// Read an index of a mapped `arguments` object from the parameter it stands for
+pub fn get_mapped_argument(obj: &ObjectData, field: Atom) -> Option<Property> {
+    let (name, env) = mapped_parameter(obj, field)?;
+    Some(Property::new(env.get_field(name)))
+}
+
+/// Write the parameter an index of a mapped `arguments` object stands for, as well as the index
+pub fn set_mapped_argument(obj: &ObjectData, field: Atom, val: &Value) {
+    if let Some((name, env)) = mapped_parameter(obj, field) {
+        env.set_field(name, val.clone());
+    }
+}
+
+/// Stop an index of a mapped `arguments` object standing for a parameter, as deleting it does
+pub fn unmap_argument(obj: &ObjectData, field: Atom) {
+    if let Some(prop) = obj.get_internal_slot(PARAMETER_MAP) {
+        if let ValueData::Object(ref map) = *prop.data() {
+            map.borrow_mut().shift_remove(&field);
+        }
+    }
+}
+
+/// The function a bound function wraps, and the arguments it puts in front of the ones it's called with
+pub fn bound_target(func: &Value) -> Option<(Value, Vec<Value>)> {
+    let target = func.get_internal_slot(BOUND_TARGET_FUNCTION);
+    if !target.is_function() {
+        return None;
+    }
+    let list = func.get_internal_slot(BOUND_ARGUMENTS);
+    let len = list.get_field_slice("length").to_int();
+    let bound_args = (0..len).map(|i| list.get_field(i.to_string())).collect();
+    Some((target, bound_args))
+}
+
+/// The `Function` constructor, which makes a function out of the source text of its parameters and body
+/// <https://tc39.es/ecma262/#sec-function-p1-p2-pn-body>
+pub fn make_function(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let mut args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    let body = args.pop().unwrap_or_default();
+    caller.compile_function(&args.join(","), &body)
+}
+
+/// Run a string as a script, returning its completion value, or return anything else unchanged
+///
+/// Interpreters run a call to `eval` written in a script in the scope of the call themselves,
+/// so this is only called for indirect calls, which run in the global scope
+/// <https://tc39.es/ecma262/#sec-eval-x>
+pub fn eval(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    match args.first() {
+        Some(source) if source.is_string() => caller.eval_code(&source.to_string()),
+        Some(val) => Ok(val.clone()),
+        None => Ok(Value::undefined()),
+    }
+}
+
+/// Call a function with a value for `this` and the rest of the arguments
+/// <https://tc39.es/ecma262/#sec-function.prototype.call>
+pub fn call(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let mut args = args.into_iter();
+    let this_arg = args.next().unwrap_or_else(Value::undefined);
+    caller.call(&this, this_arg, args.collect())
+}
+
+/// Call a function with a value for `this` and the elements of an array-like as the arguments
+/// <https://tc39.es/ecma262/#sec-function.prototype.apply>
+pub fn apply(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let this_arg = args
+        .first()
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    let list = match args.get(1) {
+        None => Vec::new(),
+        Some(list) if list.is_null_or_undefined() => Vec::new(),
+        Some(list) if list.is_object() || list.is_function() => {
+            let len = list.get_field_slice("length").to_num();
+            let len = if len > 0.0 {
+                len.min(u32::MAX as f64) as u32
+            } else {
+                0
+            };
+            (0..len).map(|i| list.get_field(i.to_string())).collect()
         }
+        Some(_) => {
+            return Err(caller.new_error(
+                "TypeError",
+                "CreateListFromArrayLike called on non-object".to_string(),
+            ))
+        }
+    };
+    caller.call(&this, this_arg, list)
+}
+
+/// Make a bound function, which calls this function with a fixed `this` and leading arguments
+/// <https://tc39.es/ecma262/#sec-function.prototype.bind>
+pub fn bind(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    if !this.is_function() {
+        return Err(caller.new_error("TypeError", "Bind must be called on a function".to_string()));
+    }
+    let mut args = args.into_iter();
+    let bound_this = args.next().unwrap_or_else(Value::undefined);
+    let bound_args: Vec<Value> = args.collect();
+    let length = match *this.get_field_slice("length").data() {
+        ValueData::Number(n) => n,
+        ValueData::Integer(n) => n as f64,
+        _ => 0.0,
+    };
+    let length = (length - bound_args.len() as f64).max(0.0);
+    let name = match *this.get_field_slice("name").data() {
+        ValueData::String(ref name) => name.to_string(),
+        _ => String::new(),
+    };
+    let list = ValueData::new_obj(None);
+    for (i, arg) in bound_args.iter().enumerate() {
+        list.set_field(i.to_string(), arg.clone());
+    }
+    list.set_field_slice("length", to_value(bound_args.len() as i32));
+    let bound = to_value(call_bound as NativeFunctionData);
+    bound.set_internal_slot(BOUND_TARGET_FUNCTION, this.clone());
+    bound.set_internal_slot(BOUND_THIS, bound_this);
+    bound.set_internal_slot(BOUND_ARGUMENTS, list);
+    bound.set_field_slice("name", to_value(format!("bound {}", name)));
+    bound.set_field_slice("length", to_value(length));
+    let proto = this.get_prototype();
+    if !proto.is_undefined() {
+        bound.set_prototype(proto);
+    }
+    Ok(bound)
+}
+
+/// Call the target of a bound function
+pub(crate) fn call_bound(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    match bound_target(&callee) {
+        Some((target, mut bound_args)) => {
+            bound_args.extend(args);
+            caller.call(&target, callee.get_internal_slot(BOUND_THIS), bound_args)
+        }
+        None => Err(caller.new_error("TypeError", "Bound function has no target".to_string())),
     }
 }
 
+/// Get the source text of a function, or a stand-in for a native function's
+/// <https://tc39.es/ecma262/#sec-function.prototype.tostring>
+pub fn to_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let name = match *this.data() {
+        ValueData::Function(ref func) => match *func.borrow() {
+            Function::NativeFunc(ref native)
+                if !native.object.has_internal_slot(BOUND_TARGET_FUNCTION) =>
+            {
+                match native.object.get("name") {
+                    Some(prop) if prop.value.is_string() => prop.value.to_string(),
+                    _ => String::new(),
+                }
+            }
+            Function::NativeFunc(_) => String::new(),
+            _ => return Ok(to_value(this.to_string())),
+        },
+        _ => {
+            return Err(caller.new_error(
+                "TypeError",
+                "Function.prototype.toString requires that 'this' be a Function".to_string(),
+            ));
+        }
+    };
+    Ok(to_value(format!("function {}() {{ [native code] }}", name)))
+}
+
 /// Create a new `Function` object
-pub fn _create() -> Value {
-    let function: ObjectData = HashMap::new();
-    to_value(function)
+pub fn _create(global: Value) -> Value {
+    let function = to_value(make_function as NativeFunctionData);
+    let prototype = ValueData::new_obj(Some(global));
+    prototype.set_field_slice("call", to_value(call as NativeFunctionData));
+    prototype.set_field_slice("apply", to_value(apply as NativeFunctionData));
+    prototype.set_field_slice("bind", to_value(bind as NativeFunctionData));
+    prototype.set_field_slice("toString", to_value(to_string as NativeFunctionData));
+    prototype.set_field_slice("constructor", function.clone());
+    function.set_field_slice(PROTOTYPE, prototype);
+    function.set_field_slice("length", to_value(1i32));
+    function
 }
-/// Initialise the global object with the `Function` object
+/// Initialise the global object with the `Function` object and `eval`
 pub fn init(global: Value) {
-    let global_ptr = global;
-    global_ptr.set_field_slice("Function", _create());
+    global.set_field_slice("Function", _create(global.clone()));
+    global.set_field_slice("eval", to_value(eval as NativeFunctionData));
 }
@@ -0,0 +1,343 @@
+use std::cell::RefCell;
+use std::char::{decode_utf16, REPLACEMENT_CHARACTER};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::rc::Rc;
+
+/// Strings joined into one at most this long are copied, rather than kept as a rope
+const SHORT_STRING: usize = 32;
+
+/// The characters of a flat string, stored a byte each when they are all Latin-1
+enum Chars {
+    Latin1(Box<[u8]>),
+    Utf16(Box<[u16]>),
+}
+
+/// A run of the characters of a buffer, which the substrings of a string share
+#[derive(Clone)]
+struct Slice {
+    chars: Rc<Chars>,
+    start: usize,
+    len: usize,
+}
+
+impl Slice {
+    /// The code unit at an index, which must be in the slice
+    fn get(&self, index: usize) -> u16 {
+        match *self.chars {
+            Chars::Latin1(ref chars) => u16::from(chars[self.start + index]),
+            Chars::Utf16(ref chars) => chars[self.start + index],
+        }
+    }
+}
+
+/// How the characters of a string are stored
+enum Node {
+    /// No characters, which is also what a rope is left as while it's dropped
+    Empty,
+    /// A run of characters in a buffer
+    Flat(Slice),
+    /// Two strings joined, which are copied into a buffer of their own the first time the characters are read
+    Concat(JsString, JsString),
+}
+
+/// A string and its length, which is known without reading it
+struct Inner {
+    /// The number of UTF-16 code units
+    len: usize,
+    /// If every character is Latin-1, so flattening the string can store it a byte each
+    latin1: bool,
+    node: RefCell<Node>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Drop the strings of a rope one at a time, since a loop adding to a string makes it as deep as the loop is long
+        let mut pending = Vec::new();
+        if let Node::Concat(left, right) = mem::replace(self.node.get_mut(), Node::Empty) {
+            pending.push(left);
+            pending.push(right);
+        }
+        while let Some(string) = pending.pop() {
+            if let Ok(mut inner) = Rc::try_unwrap(string.0) {
+                if let Node::Concat(left, right) = mem::replace(inner.node.get_mut(), Node::Empty) {
+                    pending.push(left);
+                    pending.push(right);
+                }
+            }
+        }
+    }
+}
+
+/// A Javascript string, a sequence of UTF-16 code units
+///
+/// Joining strings makes a rope, which is flattened into one buffer the first time its characters
+/// are needed, so building a string a piece at a time takes linear time. Substrings share the
+/// buffer of the string they are taken from. Strings whose characters are all Latin-1 take a byte
+/// per character, and the others two, so the length and each character are found in constant time.
+#[derive(Clone)]
+pub struct JsString(Rc<Inner>);
+
+impl JsString {
+    /// Make a string out of UTF-16 code units
+    pub fn from_utf16(units: Vec<u16>) -> JsString {
+        if units.iter().all(|&unit| unit <= 0xFF) {
+            let chars = units
+                .into_iter()
+                .map(|unit| unit as u8)
+                .collect::<Vec<u8>>();
+            JsString::flat(Chars::Latin1(chars.into_boxed_slice()))
+        } else {
+            JsString::flat(Chars::Utf16(units.into_boxed_slice()))
+        }
+    }
+
+    /// Make a string out of a buffer of its own
+    fn flat(chars: Chars) -> JsString {
+        let (len, latin1) = match chars {
+            Chars::Latin1(ref chars) => (chars.len(), true),
+            Chars::Utf16(ref chars) => (chars.len(), false),
+        };
+        JsString(Rc::new(Inner {
+            len,
+            latin1,
+            node: RefCell::new(Node::Flat(Slice {
+                chars: Rc::new(chars),
+                start: 0,
+                len,
+            })),
+        }))
+    }
+
+    /// The number of UTF-16 code units in the string
+    pub fn len(&self) -> usize {
+        self.0.len
+    }
+
+    /// Returns true if the string has no characters
+    pub fn is_empty(&self) -> bool {
+        self.0.len == 0
+    }
+
+    /// An estimate of the bytes the characters take up
+    pub fn heap_size(&self) -> usize {
+        if self.0.latin1 {
+            self.len()
+        } else {
+            self.len() * 2
+        }
+    }
+
+    /// The UTF-16 code unit at an index, if the string is that long
+    pub fn char_code_at(&self, index: usize) -> Option<u16> {
+        if index < self.len() {
+            Some(self.slice().get(index))
+        } else {
+            None
+        }
+    }
+
+    /// The string from one code unit up to but not including another, sharing this string's characters
+    pub fn substring(&self, start: usize, end: usize) -> JsString {
+        let end = end.min(self.len());
+        let start = start.min(end);
+        if start == 0 && end == self.len() {
+            return self.clone();
+        }
+        let slice = self.slice();
+        let latin1 = match *slice.chars {
+            Chars::Latin1(_) => true,
+            Chars::Utf16(_) => false,
+        };
+        JsString(Rc::new(Inner {
+            len: end - start,
+            latin1,
+            node: RefCell::new(Node::Flat(Slice {
+                chars: slice.chars,
+                start: slice.start + start,
+                len: end - start,
+            })),
+        }))
+    }
+
+    /// This string followed by another, which only copies short strings
+    pub fn concat(&self, other: &JsString) -> JsString {
+        if other.is_empty() {
+            return self.clone();
+        }
+        if self.is_empty() {
+            return other.clone();
+        }
+        let len = self
+            .len()
+            .checked_add(other.len())
+            .expect("string too long");
+        let latin1 = self.0.latin1 && other.0.latin1;
+        if len <= SHORT_STRING {
+            let units = self.code_units().chain(other.code_units()).collect();
+            return JsString::from_utf16(units);
+        }
+        JsString(Rc::new(Inner {
+            len,
+            latin1,
+            node: RefCell::new(Node::Concat(self.clone(), other.clone())),
+        }))
+    }
+
+    /// The code units of the string
+    pub fn code_units(&self) -> CodeUnits {
+        CodeUnits {
+            slice: self.slice(),
+            index: 0,
+        }
+    }
+
+    /// The characters of the string in one buffer, flattening it if it's a rope
+    fn slice(&self) -> Slice {
+        match *self.0.node.borrow() {
+            Node::Empty => {
+                return Slice {
+                    chars: Rc::new(Chars::Latin1(Box::new([]))),
+                    start: 0,
+                    len: 0,
+                }
+            }
+            Node::Flat(ref slice) => return slice.clone(),
+            Node::Concat(..) => (),
+        }
+        let chars = if self.0.latin1 {
+            let mut chars = Vec::with_capacity(self.len());
+            self.each_unit(|unit| chars.push(unit as u8));
+            Chars::Latin1(chars.into_boxed_slice())
+        } else {
+            let mut chars = Vec::with_capacity(self.len());
+            self.each_unit(|unit| chars.push(unit));
+            Chars::Utf16(chars.into_boxed_slice())
+        };
+        let slice = Slice {
+            chars: Rc::new(chars),
+            start: 0,
+            len: self.len(),
+        };
+        *self.0.node.borrow_mut() = Node::Flat(slice.clone());
+        slice
+    }
+
+    /// Call a function with each code unit of a rope in turn, without recursing into it
+    fn each_unit<F: FnMut(u16)>(&self, mut f: F) {
+        let mut pending = vec![self.clone()];
+        while let Some(string) = pending.pop() {
+            let node = string.0.node.borrow();
+            match *node {
+                Node::Empty => (),
+                Node::Flat(ref slice) => (0..slice.len).for_each(|i| f(slice.get(i))),
+                Node::Concat(ref left, ref right) => {
+                    pending.push(right.clone());
+                    pending.push(left.clone());
+                }
+            }
+        }
+    }
+}
+
+/// The code units of a string, as `JsString::code_units` gives them
+pub struct CodeUnits {
+    slice: Slice,
+    index: usize,
+}
+
+impl Iterator for CodeUnits {
+    type Item = u16;
+    fn next(&mut self) -> Option<u16> {
+        if self.index < self.slice.len {
+            self.index += 1;
+            Some(self.slice.get(self.index - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = self.slice.len - self.index;
+        (left, Some(left))
+    }
+}
+
+impl Default for JsString {
+    fn default() -> JsString {
+        JsString(Rc::new(Inner {
+            len: 0,
+            latin1: true,
+            node: RefCell::new(Node::Empty),
+        }))
+    }
+}
+
+impl<'a> From<&'a str> for JsString {
+    fn from(s: &'a str) -> JsString {
+        if s.is_ascii() {
+            JsString::flat(Chars::Latin1(s.as_bytes().into()))
+        } else {
+            JsString::from_utf16(s.encode_utf16().collect())
+        }
+    }
+}
+
+impl From<String> for JsString {
+    fn from(s: String) -> JsString {
+        JsString::from(s.as_str())
+    }
+}
+
+impl fmt::Display for JsString {
+    /// Write the string as UTF-8, with any lone surrogates replaced
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let slice = self.slice();
+        if let Chars::Latin1(ref chars) = *slice.chars {
+            let chars = &chars[slice.start..slice.start + slice.len];
+            if chars.is_ascii() {
+                // ASCII is the same in UTF-8
+                return f.write_str(std::str::from_utf8(chars).unwrap());
+            }
+        }
+        let s: String = decode_utf16(self.code_units())
+            .map(|c| c.unwrap_or(REPLACEMENT_CHARACTER))
+            .collect();
+        f.write_str(&s)
+    }
+}
+
+impl fmt::Debug for JsString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.to_string())
+    }
+}
+
+impl PartialEq for JsString {
+    fn eq(&self, other: &JsString) -> bool {
+        self.len() == other.len() && self.code_units().eq(other.code_units())
+    }
+}
+
+impl Eq for JsString {}
+
+impl PartialOrd for JsString {
+    fn partial_cmp(&self, other: &JsString) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for JsString {
+    /// Compare the code units of the strings, as the `<` operator does
+    fn cmp(&self, other: &JsString) -> Ordering {
+        self.code_units().cmp(other.code_units())
+    }
+}
+
+impl Hash for JsString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.code_units().for_each(|unit| unit.hash(state));
+    }
+}
@@ -1,22 +1,548 @@
-use crate::js::function::NativeFunctionData;
+use crate::js::array::{array_create, is_array};
+use crate::js::bigint::BIGINT_DATA;
+use crate::js::boolean::BOOLEAN_DATA;
+use crate::js::function::{Caller, NativeFunctionData};
 /// The JSON Object
 /// https://tc39.github.io/ecma262/#sec-json-object
+use crate::js::number::{number_to_string, NUMBER_DATA};
+use crate::js::object::{own_property_keys, Property};
 use crate::js::value::{to_value, ResultValue, Value, ValueData};
-use serde_json::{self, to_string_pretty, Value as JSONValue};
+use std::ptr;
 
-/// Parse a JSON string into a Javascript object
-/// https://tc39.github.io/ecma262/#sec-json.parse
-pub fn parse(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    match serde_json::from_str::<JSONValue>(&args.get(0).unwrap().clone().to_string()) {
-        Ok(json) => Ok(to_value(json)),
-        Err(err) => Err(to_value(err.to_string())),
+/// The number of elements of an array-like object
+/// [[SPEC] - LengthOfArrayLike](https://tc39.github.io/ecma262/#sec-lengthofarraylike)
+fn length_of(value: &Value) -> usize {
+    let len = value.get_field_slice("length").to_num();
+    if len > 0.0 {
+        len.min(9_007_199_254_740_991.0) as usize
+    } else {
+        0
     }
 }
-/// Process a Javascript object into a JSON string
-pub fn stringify(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    let obj = args.get(0).unwrap();
-    let json = obj.to_json();
-    Ok(to_value(to_string_pretty(&json).unwrap()))
+
+/// Builds Javascript values straight from JSON text, one character at a time
+struct JsonParser<'a> {
+    /// The JSON text
+    text: &'a str,
+    /// The byte offset of the next character
+    pos: usize,
+    /// The prototype of the objects it makes
+    object_proto: Value,
+    /// The prototype of the arrays it makes
+    array_proto: Value,
+    /// The interpreter, for making the `SyntaxError`s it throws
+    caller: &'a dyn Caller,
+}
+
+impl<'a> JsonParser<'a> {
+    /// The next character, without consuming it
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    /// Skip the whitespace JSON allows between tokens
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// The error for the character at the current position
+    fn unexpected(&self) -> Value {
+        let message = match self.peek() {
+            Some(c) => format!("Unexpected token {} in JSON at position {}", c, self.pos),
+            None => "Unexpected end of JSON input".to_string(),
+        };
+        self.caller.new_error("SyntaxError", message)
+    }
+
+    /// Consume a character, or throw if it's a different one
+    fn expect(&mut self, expected: char) -> Result<(), Value> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Consume a keyword such as `true`, or throw if the text doesn't match it
+    fn keyword(&mut self, keyword: &str, value: Value) -> ResultValue {
+        for expected in keyword.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    /// Parse a whole JSON text, which can't have anything but whitespace after its value
+    fn parse_text(&mut self) -> ResultValue {
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+        if self.pos < self.text.len() {
+            return Err(self.unexpected());
+        }
+        Ok(value)
+    }
+
+    /// Parse a value and the whitespace around it
+    fn parse_value(&mut self) -> ResultValue {
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some('{') => self.parse_object()?,
+            Some('[') => self.parse_array()?,
+            Some('"') => to_value(self.parse_string()?),
+            Some('t') => self.keyword("true", to_value(true))?,
+            Some('f') => self.keyword("false", to_value(false))?,
+            Some('n') => self.keyword("null", Value::null())?,
+            Some('-') | Some('0'..='9') => self.parse_number()?,
+            _ => return Err(self.unexpected()),
+        };
+        self.skip_whitespace();
+        Ok(value)
+    }
+
+    /// Parse an object, keeping its members in the order they appear
+    fn parse_object(&mut self) -> ResultValue {
+        let obj = ValueData::new_obj_from_prototype(self.object_proto.clone());
+        self.expect('{')?;
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(obj);
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.unexpected());
+            }
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            // Members are own data properties, so `"__proto__"` doesn't set the prototype
+            obj.set_prop(key, Property::new(value));
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(obj);
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    /// Parse an array
+    fn parse_array(&mut self) -> ResultValue {
+        let arr = array_create(self.array_proto.clone());
+        self.expect('[')?;
+        self.skip_whitespace();
+        let mut len = 0;
+        if self.peek() == Some(']') {
+            self.pos += 1;
+        } else {
+            loop {
+                let value = self.parse_value()?;
+                arr.set_field(len.to_string(), value);
+                len += 1;
+                match self.peek() {
+                    Some(',') => self.pos += 1,
+                    Some(']') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(self.unexpected()),
+                }
+            }
+        }
+        arr.set_field_slice("length", to_value(len as f64));
+        Ok(arr)
+    }
+
+    /// Parse a string, unescaping it
+    fn parse_string(&mut self) -> Result<String, Value> {
+        self.expect('"')?;
+        let mut result = String::new();
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(result);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+                    let c = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.pos += 1;
+                            result.push(self.parse_unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.unexpected()),
+                    };
+                    self.pos += 1;
+                    result.push(c);
+                }
+                Some(c) if c >= ' ' => {
+                    self.pos += c.len_utf8();
+                    result.push(c);
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    /// Parse the four hex digits after `\u`, which can be the first half of a surrogate pair
+    /// A surrogate without its other half becomes U+FFFD, as a Rust string can't hold it
+    fn parse_unicode_escape(&mut self) -> Result<char, Value> {
+        let unit = self.parse_hex4()?;
+        if (0xD800..0xDC00).contains(&unit) && self.text[self.pos..].starts_with("\\u") {
+            let start = self.pos;
+            self.pos += 2;
+            let low = self.parse_hex4()?;
+            if (0xDC00..0xE000).contains(&low) {
+                let c = 0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00);
+                return Ok(std::char::from_u32(c).unwrap_or('\u{FFFD}'));
+            }
+            self.pos = start;
+        }
+        Ok(std::char::from_u32(unit).unwrap_or('\u{FFFD}'))
+    }
+
+    /// Parse four hex digits
+    fn parse_hex4(&mut self) -> Result<u32, Value> {
+        let mut unit = 0;
+        for _ in 0..4 {
+            match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => {
+                    unit = unit * 16 + digit;
+                    self.pos += 1;
+                }
+                None => return Err(self.unexpected()),
+            }
+        }
+        Ok(unit)
+    }
+
+    /// Skip a run of decimal digits, returning how many there were
+    fn skip_digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some('0'..='9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    /// Parse a number, which can't have a leading `+`, leading zeros or a bare `.`
+    fn parse_number(&mut self) -> ResultValue {
+        let start = self.pos;
+        if self.peek() == Some('-') {
+            self.pos += 1;
+        }
+        if self.peek() == Some('0') {
+            self.pos += 1;
+        } else if self.skip_digits() == 0 {
+            return Err(self.unexpected());
+        }
+        if self.peek() == Some('.') {
+            self.pos += 1;
+            if self.skip_digits() == 0 {
+                return Err(self.unexpected());
+            }
+        }
+        if let Some('e') | Some('E') = self.peek() {
+            self.pos += 1;
+            if let Some('+') | Some('-') = self.peek() {
+                self.pos += 1;
+            }
+            if self.skip_digits() == 0 {
+                return Err(self.unexpected());
+            }
+        }
+        Ok(to_value(self.text[start..self.pos].parse::<f64>().unwrap()))
+    }
+}
+
+/// Call the reviver on every value inside a parsed value from the innermost out, replacing or removing them
+/// [[SPEC] - InternalizeJSONProperty](https://tc39.github.io/ecma262/#sec-internalizejsonproperty)
+fn internalize(
+    holder: &Value,
+    name: String,
+    reviver: &Value,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    let val = holder.get_field(name.clone());
+    if let ValueData::Object(ref obj) = *val.data() {
+        let keys = if is_array(&val) {
+            (0..length_of(&val)).map(|i| i.to_string()).collect()
+        } else {
+            own_property_keys(&obj.borrow())
+        };
+        for key in keys {
+            let element = internalize(&val, key.clone(), reviver, caller)?;
+            if element.is_undefined() {
+                obj.borrow_mut().shift_remove(&key);
+            } else {
+                val.set_prop(key, Property::new(element));
+            }
+        }
+    }
+    caller.call(reviver, holder.clone(), vec![to_value(name), val])
+}
+
+/// Parse a JSON string into a Javascript value, optionally transforming it with a reviver
+/// [[SPEC] - JSON.parse](https://tc39.github.io/ecma262/#sec-json.parse)
+pub fn parse(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let text = match args.first() {
+        Some(text) => text.to_string(),
+        None => "undefined".to_string(),
+    };
+    let object_proto = caller.prototype("Object");
+    let unfiltered = JsonParser {
+        text: &text,
+        pos: 0,
+        object_proto: object_proto.clone(),
+        array_proto: caller.prototype("Array"),
+        caller,
+    }
+    .parse_text()?;
+    match args.get(1) {
+        Some(reviver) if reviver.is_function() => {
+            let root = ValueData::new_obj_from_prototype(object_proto);
+            root.set_field_slice("", unfiltered);
+            internalize(&root, String::new(), reviver, caller)
+        }
+        _ => Ok(unfiltered),
+    }
+}
+
+/// The state of a call to `JSON.stringify`
+struct JsonSerializer<'a> {
+    /// The objects being serialized, innermost last, for finding cycles
+    stack: Vec<Value>,
+    /// The indentation of the current line
+    indent: String,
+    /// The whitespace to indent each level by
+    gap: String,
+    /// The keys to serialize from every object, if the replacer is an array
+    property_list: Option<Vec<String>>,
+    /// The function to replace every value with its result, if the replacer is a function
+    replacer: Option<Value>,
+    /// The interpreter, for calling `toJSON` and the replacer
+    caller: &'a mut dyn Caller,
+}
+
+impl<'a> JsonSerializer<'a> {
+    /// Serialize a property of an object, or `None` for a value JSON leaves out such as `undefined`
+    /// [[SPEC] - SerializeJSONProperty](https://tc39.github.io/ecma262/#sec-serializejsonproperty)
+    fn serialize_property(&mut self, key: &str, holder: &Value) -> Result<Option<String>, Value> {
+        let mut value = holder.get_field_slice(key);
+        if value.is_object() {
+            let to_json = value.get_field_slice("toJSON");
+            if to_json.is_function() {
+                value = self
+                    .caller
+                    .call(&to_json, value.clone(), vec![to_value(key)])?;
+            }
+        }
+        if let Some(replacer) = self.replacer.clone() {
+            value = self
+                .caller
+                .call(&replacer, holder.clone(), vec![to_value(key), value])?;
+        }
+        // Unwrap `Number`, `Boolean` and `BigInt` objects to their primitive values
+        if let ValueData::Object(ref obj) = *value.clone().data() {
+            let obj = obj.borrow();
+            if let Some(prop) = obj
+                .get_internal_slot(NUMBER_DATA)
+                .or_else(|| obj.get_internal_slot(BOOLEAN_DATA))
+                .or_else(|| obj.get_internal_slot(BIGINT_DATA))
+            {
+                value = prop.clone();
+            }
+        }
+        Ok(Some(match *value.data() {
+            ValueData::Null => "null".to_string(),
+            ValueData::Boolean(b) => b.to_string(),
+            ValueData::String(ref s) => quote(&s.to_string()),
+            ValueData::Number(n) if n.is_finite() => number_to_string(n),
+            ValueData::Number(_) => "null".to_string(),
+            ValueData::Integer(n) => n.to_string(),
+            ValueData::BigInt(_) => {
+                return Err(self
+                    .caller
+                    .new_error("TypeError", "Do not know how to serialize a BigInt".to_string()))
+            }
+            ValueData::Object(_) if is_array(&value) => {
+                self.serialize_array(&value)?
+            }
+            ValueData::Object(_) => self.serialize_object(&value)?,
+            ValueData::Undefined
+            | ValueData::Function(_)
+            | ValueData::Bytes(_)
+            | ValueData::Host(_)
+            | ValueData::Slots(_) => return Ok(None),
+        }))
+    }
+
+    /// Start serializing an object or array, throwing if it's already being serialized further out
+    fn enter(&mut self, value: &Value) -> Result<String, Value> {
+        if self.stack.iter().any(|v| ptr::eq(&*v.data(), &*value.data())) {
+            return Err(self
+                .caller
+                .new_error("TypeError", "Converting circular structure to JSON".to_string()));
+        }
+        self.stack.push(value.clone());
+        let stepback = self.indent.clone();
+        self.indent.push_str(&self.gap);
+        Ok(stepback)
+    }
+
+    /// Finish serializing an object or array, joining its members inside a pair of brackets
+    fn leave(&mut self, stepback: String, partial: Vec<String>, open: char, close: char) -> String {
+        self.stack.pop();
+        let result = if partial.is_empty() {
+            format!("{}{}", open, close)
+        } else if self.gap.is_empty() {
+            format!("{}{}{}", open, partial.join(","), close)
+        } else {
+            let separator = format!(",\n{}", self.indent);
+            format!(
+                "{}\n{}{}\n{}{}",
+                open,
+                self.indent,
+                partial.join(&separator),
+                stepback,
+                close
+            )
+        };
+        self.indent = stepback;
+        result
+    }
+
+    /// Serialize the own properties of an object
+    /// [[SPEC] - SerializeJSONObject](https://tc39.github.io/ecma262/#sec-serializejsonobject)
+    fn serialize_object(&mut self, value: &Value) -> Result<String, Value> {
+        let stepback = self.enter(value)?;
+        let keys = match self.property_list {
+            Some(ref list) => list.clone(),
+            None => match *value.data() {
+                ValueData::Object(ref obj) => own_property_keys(&obj.borrow()),
+                _ => Vec::new(),
+            },
+        };
+        let mut partial = Vec::new();
+        for key in keys {
+            if let Some(str_p) = self.serialize_property(&key, value)? {
+                let colon = if self.gap.is_empty() { ":" } else { ": " };
+                partial.push(format!("{}{}{}", quote(&key), colon, str_p));
+            }
+        }
+        Ok(self.leave(stepback, partial, '{', '}'))
+    }
+
+    /// Serialize the elements of an array, writing `null` for the ones JSON leaves out
+    /// [[SPEC] - SerializeJSONArray](https://tc39.github.io/ecma262/#sec-serializejsonarray)
+    fn serialize_array(&mut self, value: &Value) -> Result<String, Value> {
+        let stepback = self.enter(value)?;
+        let mut partial = Vec::new();
+        for index in 0..length_of(value) {
+            let str_p = self.serialize_property(&index.to_string(), value)?;
+            partial.push(str_p.unwrap_or_else(|| "null".to_string()));
+        }
+        Ok(self.leave(stepback, partial, '[', ']'))
+    }
+}
+
+/// Wrap a string in double quotes, escaping the characters JSON can't hold as they are
+/// [[SPEC] - QuoteJSONString](https://tc39.github.io/ecma262/#sec-quotejsonstring)
+fn quote(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '\u{8}' => result.push_str("\\b"),
+            '\t' => result.push_str("\\t"),
+            '\n' => result.push_str("\\n"),
+            '\u{c}' => result.push_str("\\f"),
+            '\r' => result.push_str("\\r"),
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if c < ' ' => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Process a Javascript value into a JSON string, optionally filtering it with a replacer and indenting it
+/// [[SPEC] - JSON.stringify](https://tc39.github.io/ecma262/#sec-json.stringify)
+pub fn stringify(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let undefined = Value::undefined();
+    let value = args.first().cloned().unwrap_or_else(|| undefined.clone());
+    let replacer = args.get(1).cloned().unwrap_or_else(|| undefined.clone());
+    let mut property_list = None;
+    if is_array(&replacer) {
+        let mut list: Vec<String> = Vec::new();
+        for index in 0..length_of(&replacer) {
+            let mut item = replacer.get_field(index.to_string());
+            if let ValueData::Object(ref obj) = *item.clone().data() {
+                if let Some(prop) = obj.borrow().get_internal_slot(NUMBER_DATA) {
+                    item = prop.clone();
+                }
+            }
+            let item = match *item.data() {
+                ValueData::String(ref s) => s.to_string(),
+                ValueData::Number(_) | ValueData::Integer(_) => item.to_string(),
+                _ => continue,
+            };
+            if !list.contains(&item) {
+                list.push(item);
+            }
+        }
+        property_list = Some(list);
+    }
+    let mut space = args.get(2).cloned().unwrap_or(undefined);
+    if let ValueData::Object(ref obj) = *space.clone().data() {
+        if let Some(prop) = obj.borrow().get_internal_slot(NUMBER_DATA) {
+            space = prop.clone();
+        }
+    }
+    let gap = match *space.data() {
+        ValueData::Number(_) | ValueData::Integer(_) => {
+            let n = space.to_num();
+            let n = if n.is_nan() { 0.0 } else { n.trunc().min(10.0) };
+            " ".repeat(if n >= 1.0 { n as usize } else { 0 })
+        }
+        ValueData::String(ref s) => s.substring(0, 10).to_string(),
+        _ => String::new(),
+    };
+    let wrapper = ValueData::new_obj_from_prototype(caller.prototype("Object"));
+    wrapper.set_field_slice("", value);
+    let mut serializer = JsonSerializer {
+        stack: Vec::new(),
+        indent: String::new(),
+        gap,
+        property_list,
+        replacer: if replacer.is_function() {
+            Some(replacer)
+        } else {
+            None
+        },
+        caller,
+    };
+    Ok(match serializer.serialize_property("", &wrapper)? {
+        Some(json) => to_value(json),
+        None => Value::undefined(),
+    })
 }
 
 /// Create a new `JSON` object
@@ -1,12 +1,13 @@
-use crate::js::function::NativeFunctionData;
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::number::{to_int32, to_uint32};
 use crate::js::value::{from_value, to_value, ResultValue, Value, ValueData};
 use rand::random;
 use std::f64;
 
 /// Get the absolute value of a number
-pub fn abs(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn abs(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .abs()
     } else {
@@ -14,29 +15,49 @@ pub fn abs(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
     }))
 }
 /// Get the arccos of a number
-pub fn acos(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn acos(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .acos()
     } else {
         f64::NAN
     }))
 }
+/// Get the hyperbolic arccos of a number
+pub fn acosh(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .acosh()
+    } else {
+        f64::NAN
+    }))
+}
 /// Get the arcsine of a number
-pub fn asin(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn asin(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .asin()
     } else {
         f64::NAN
     }))
 }
+/// Get the hyperbolic arcsine of a number
+pub fn asinh(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .asinh()
+    } else {
+        f64::NAN
+    }))
+}
 /// Get the arctangent of a number
-pub fn atan(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn atan(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .atan()
     } else {
@@ -44,19 +65,29 @@ pub fn atan(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
     }))
 }
 /// Get the arctangent of a numbers
-pub fn atan2(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn atan2(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .atan2(args.get(1).unwrap().to_num())
     } else {
         f64::NAN
     }))
 }
+/// Get the hyperbolic arctangent of a number
+pub fn atanh(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .atanh()
+    } else {
+        f64::NAN
+    }))
+}
 /// Get the cubic root of a number
-pub fn cbrt(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn cbrt(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .cbrt()
     } else {
@@ -64,57 +95,136 @@ pub fn cbrt(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
     }))
 }
 /// Get lowest integer above a number
-pub fn ceil(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn ceil(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .ceil()
     } else {
         f64::NAN
     }))
 }
+/// Get the number of leading zero bits in the 32-bit representation of a number
+pub fn clz32(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let num = args.first().map_or(0.0, |x| x.to_num());
+    Ok(to_value(to_uint32(num).leading_zeros() as i32))
+}
 /// Get the cosine of a number
-pub fn cos(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn cos(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .cos()
     } else {
         f64::NAN
     }))
 }
+/// Get the hyperbolic cosine of a number
+pub fn cosh(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .cosh()
+    } else {
+        f64::NAN
+    }))
+}
 /// Get the power to raise the natural logarithm to get the number
-pub fn exp(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn exp(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .exp()
     } else {
         f64::NAN
     }))
 }
+/// Get the power to raise the natural logarithm to get the number, minus one
+pub fn expm1(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .exp_m1()
+    } else {
+        f64::NAN
+    }))
+}
 /// Get the highest integer below a number
-pub fn floor(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn floor(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .floor()
     } else {
         f64::NAN
     }))
 }
+/// Round a number to the nearest single precision float
+pub fn fround(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap() as f32 as f64
+    } else {
+        f64::NAN
+    }))
+}
+/// Get the square root of the sum of squares of several numbers
+pub fn hypot(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let nums: Vec<f64> = args.iter().map(|x| x.to_num()).collect();
+    Ok(to_value(if nums.iter().any(|x| x.is_infinite()) {
+        f64::INFINITY
+    } else {
+        nums.iter().fold(0.0, |acc: f64, x| acc.hypot(*x))
+    }))
+}
+/// Multiply two numbers as 32-bit integers, wrapping on overflow
+pub fn imul(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let a = to_int32(args.first().map_or(0.0, |x| x.to_num()));
+    let b = to_int32(args.get(1).map_or(0.0, |x| x.to_num()));
+    Ok(to_value(a.wrapping_mul(b)))
+}
 /// Get the natural logarithm of a number
-pub fn log(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn log(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .log(f64::consts::E)
     } else {
         f64::NAN
     }))
 }
+/// Get the base 10 logarithm of a number
+pub fn log10(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .log10()
+    } else {
+        f64::NAN
+    }))
+}
+/// Get the natural logarithm of one plus a number
+pub fn log1p(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .ln_1p()
+    } else {
+        f64::NAN
+    }))
+}
+/// Get the base 2 logarithm of a number
+pub fn log2(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .log2()
+    } else {
+        f64::NAN
+    }))
+}
 /// Get the maximum of several numbers
-pub fn max(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
+pub fn max(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
     let mut max = f64::NEG_INFINITY;
     for arg in args.iter() {
         let num = arg.to_num();
@@ -123,7 +233,7 @@ pub fn max(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
     Ok(to_value(max))
 }
 /// Get the minimum of several numbers
-pub fn min(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
+pub fn min(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
     let mut max = f64::INFINITY;
     for arg in args.iter() {
         let num = arg.to_num();
@@ -132,9 +242,9 @@ pub fn min(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
     Ok(to_value(max))
 }
 /// Raise a number to a power
-pub fn pow(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
+pub fn pow(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
     Ok(to_value(if args.len() >= 2 {
-        let num: f64 = from_value(args.get(0).unwrap().clone()).unwrap();
+        let num: f64 = from_value(args.first().unwrap().clone()).unwrap();
         let power: f64 = from_value(args.get(1).unwrap().clone()).unwrap();
         num.powf(power)
     } else {
@@ -142,33 +252,63 @@ pub fn pow(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
     }))
 }
 /// Generate a random floating-point number between 0 and 1
-pub fn _random(_: Value, _: Value, _args: Vec<Value>) -> ResultValue {
+pub fn _random(_: Value, _: Value, _args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
     Ok(to_value(random::<f64>()))
 }
-/// Round a number to the nearest integer
-pub fn round(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
-            .unwrap()
-            .round()
+/// Round a number to the nearest integer, with halves rounding up
+pub fn round(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        let num: f64 = from_value(args.first().unwrap().clone()).unwrap();
+        let floor = num.floor();
+        if num - floor < 0.5 {
+            floor
+        } else if floor == -1.0 {
+            // Numbers from -0.5 up to zero round to -0
+            -0.0
+        } else {
+            floor + 1.0
+        }
+    } else {
+        f64::NAN
+    }))
+}
+/// Get the sign of a number, as 1, -1 or zero
+pub fn sign(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        let num: f64 = from_value(args.first().unwrap().clone()).unwrap();
+        if num == 0.0 || num.is_nan() {
+            num
+        } else {
+            num.signum()
+        }
     } else {
         f64::NAN
     }))
 }
 /// Get the sine of a number
-pub fn sin(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn sin(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .sin()
     } else {
         f64::NAN
     }))
 }
+/// Get the hyperbolic sine of a number
+pub fn sinh(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .sinh()
+    } else {
+        f64::NAN
+    }))
+}
 /// Get the square root of a number
-pub fn sqrt(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn sqrt(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .sqrt()
     } else {
@@ -176,15 +316,35 @@ pub fn sqrt(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
     }))
 }
 /// Get the tangent of a number
-pub fn tan(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    Ok(to_value(if args.len() >= 1 {
-        from_value::<f64>(args.get(0).unwrap().clone())
+pub fn tan(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
             .unwrap()
             .tan()
     } else {
         f64::NAN
     }))
 }
+/// Get the hyperbolic tangent of a number
+pub fn tanh(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .tanh()
+    } else {
+        f64::NAN
+    }))
+}
+/// Get the integer part of a number
+pub fn trunc(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(if !args.is_empty() {
+        from_value::<f64>(args.first().unwrap().clone())
+            .unwrap()
+            .trunc()
+    } else {
+        f64::NAN
+    }))
+}
 /// Create a new `Math` object
 pub fn _create(global: Value) -> Value {
     let math = ValueData::new_obj(Some(global));
@@ -198,23 +358,39 @@ pub fn _create(global: Value) -> Value {
     math.set_field_slice("PI", to_value(f64::consts::PI));
     math.set_field_slice("abs", to_value(abs as NativeFunctionData));
     math.set_field_slice("acos", to_value(acos as NativeFunctionData));
+    math.set_field_slice("acosh", to_value(acosh as NativeFunctionData));
     math.set_field_slice("asin", to_value(asin as NativeFunctionData));
+    math.set_field_slice("asinh", to_value(asinh as NativeFunctionData));
     math.set_field_slice("atan", to_value(atan as NativeFunctionData));
     math.set_field_slice("atan2", to_value(atan2 as NativeFunctionData));
+    math.set_field_slice("atanh", to_value(atanh as NativeFunctionData));
     math.set_field_slice("cbrt", to_value(cbrt as NativeFunctionData));
     math.set_field_slice("ceil", to_value(ceil as NativeFunctionData));
+    math.set_field_slice("clz32", to_value(clz32 as NativeFunctionData));
     math.set_field_slice("cos", to_value(cos as NativeFunctionData));
+    math.set_field_slice("cosh", to_value(cosh as NativeFunctionData));
     math.set_field_slice("exp", to_value(exp as NativeFunctionData));
+    math.set_field_slice("expm1", to_value(expm1 as NativeFunctionData));
     math.set_field_slice("floor", to_value(floor as NativeFunctionData));
+    math.set_field_slice("fround", to_value(fround as NativeFunctionData));
+    math.set_field_slice("hypot", to_value(hypot as NativeFunctionData));
+    math.set_field_slice("imul", to_value(imul as NativeFunctionData));
     math.set_field_slice("log", to_value(log as NativeFunctionData));
+    math.set_field_slice("log10", to_value(log10 as NativeFunctionData));
+    math.set_field_slice("log1p", to_value(log1p as NativeFunctionData));
+    math.set_field_slice("log2", to_value(log2 as NativeFunctionData));
     math.set_field_slice("max", to_value(max as NativeFunctionData));
     math.set_field_slice("min", to_value(min as NativeFunctionData));
     math.set_field_slice("pow", to_value(pow as NativeFunctionData));
     math.set_field_slice("random", to_value(_random as NativeFunctionData));
     math.set_field_slice("round", to_value(round as NativeFunctionData));
+    math.set_field_slice("sign", to_value(sign as NativeFunctionData));
     math.set_field_slice("sin", to_value(sin as NativeFunctionData));
+    math.set_field_slice("sinh", to_value(sinh as NativeFunctionData));
     math.set_field_slice("sqrt", to_value(sqrt as NativeFunctionData));
     math.set_field_slice("tan", to_value(tan as NativeFunctionData));
+    math.set_field_slice("tanh", to_value(tanh as NativeFunctionData));
+    math.set_field_slice("trunc", to_value(trunc as NativeFunctionData));
     math
 }
 /// Initialise the `Math` object on the global object
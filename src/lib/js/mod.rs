@@ -1,18 +1,49 @@
 /// The global `Array` object
 pub mod array;
+/// Interned identifiers and property keys
+pub mod atom;
+/// The global `ArrayBuffer` object and the bytes shared with the host
+pub mod array_buffer;
+/// The global `BigInt` object
+pub mod bigint;
+/// The global `Boolean` object
+pub mod boolean;
+/// Rust types exposed to scripts as classes, whose objects carry Rust data
+pub mod class;
 /// The global `console` object
 pub mod console;
+/// The global `DataView` object
+pub mod data_view;
+/// The global `Date` object
+pub mod date;
+/// The global `TextEncoder` and `TextDecoder` objects and the global `atob` and `btoa` functions
+#[cfg(feature = "web")]
+pub mod encoding;
 /// The global `Error` object
 pub mod error;
 /// The global `Function` object and function value representations
 pub mod function;
+/// Javascript strings, stored as ropes of Latin-1 or UTF-16 characters
+pub mod js_string;
 /// The global `JSON` object
 pub mod json;
 /// The global `Math` object
 pub mod math;
+/// The global `Number` object and the global number functions
+pub mod number;
 /// The global `Object` object
 pub mod object;
+/// Conversion between Javascript values and Rust data with serde
+pub mod serde_value;
 /// The global `String` object
 pub mod string;
+/// The typed array objects, such as `Uint8Array`
+pub mod typed_array;
+/// The global URI functions, such as `encodeURIComponent`
+#[cfg(feature = "web")]
+pub mod uri;
+/// The global `URL` and `URLSearchParams` objects
+#[cfg(feature = "web")]
+pub mod url;
 /// Javascript values, utility methods and conversion between Javascript values and Rust values
 pub mod value;
@@ -0,0 +1,558 @@
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::{is_construct, Property, PROTOTYPE};
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
+
+/// The internal slot of a `Number` object holding its primitive value
+pub static NUMBER_DATA: &str = "[[NumberData]]";
+
+/// The digits used when writing numbers in radixes up to 36
+const RADIX_DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+/// The largest integer `n` such that `n` and `n + 1` are both exactly representable
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Whether a character is white space or a line terminator to Javascript
+pub fn is_white_space(c: char) -> bool {
+    c.is_whitespace() || c == '\u{FEFF}'
+}
+
+/// The length of the longest prefix of `s` that is a `StrUnsignedDecimalLiteral` other than `Infinity`
+fn decimal_literal_len(s: &[u8]) -> usize {
+    let digits = |from: usize| s[from..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let int_digits = digits(0);
+    let mut end = int_digits;
+    if s.get(end) == Some(&b'.') {
+        let frac_digits = digits(end + 1);
+        if int_digits == 0 && frac_digits == 0 {
+            return 0;
+        }
+        end += 1 + frac_digits;
+    } else if int_digits == 0 {
+        return 0;
+    }
+    if let Some(b'e') | Some(b'E') = s.get(end) {
+        let mut exp = end + 1;
+        if let Some(b'+') | Some(b'-') = s.get(exp) {
+            exp += 1;
+        }
+        let exp_digits = digits(exp);
+        if exp_digits > 0 {
+            end = exp + exp_digits;
+        }
+    }
+    end
+}
+
+/// Split a leading `+` or `-` off a string, returning the sign as a number
+fn split_sign(s: &str) -> (f64, &str) {
+    match s.as_bytes().first() {
+        Some(b'+') => (1.0, &s[1..]),
+        Some(b'-') => (-1.0, &s[1..]),
+        _ => (1.0, s),
+    }
+}
+
+/// Read a string of digits in a radix, or `None` if it has a character that isn't one
+fn digits_to_number(digits: &str, radix: u32) -> Option<f64> {
+    if radix == 10 {
+        return digits.parse().ok();
+    }
+    digits.chars().try_fold(0.0, |acc, c| {
+        c.to_digit(radix)
+            .map(|d| acc * f64::from(radix) + f64::from(d))
+    })
+}
+
+/// Convert a string to a number, giving `NaN` if the whole string isn't a numeric literal
+/// [[SPEC] - ToNumber Applied to the String Type](https://tc39.github.io/ecma262/#sec-tonumber-applied-to-the-string-type)
+pub fn string_to_number(s: &str) -> f64 {
+    let s = s.trim_matches(is_white_space);
+    if s.is_empty() {
+        return 0.0;
+    }
+    let radix = match s.get(..2) {
+        Some("0x") | Some("0X") => 16,
+        Some("0o") | Some("0O") => 8,
+        Some("0b") | Some("0B") => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        return match &s[2..] {
+            "" => f64::NAN,
+            digits => digits_to_number(digits, radix).unwrap_or(f64::NAN),
+        };
+    }
+    let (sign, unsigned) = split_sign(s);
+    if unsigned == "Infinity" {
+        return sign * f64::INFINITY;
+    }
+    if unsigned.is_empty() || decimal_literal_len(unsigned.as_bytes()) != unsigned.len() {
+        return f64::NAN;
+    }
+    sign * unsigned.parse::<f64>().unwrap_or(f64::NAN)
+}
+
+/// Convert a number to a 32-bit unsigned integer, wrapping modulo 2^32
+/// [[SPEC] - ToUint32](https://tc39.github.io/ecma262/#sec-touint32)
+pub fn to_uint32(x: f64) -> u32 {
+    if !x.is_finite() {
+        return 0;
+    }
+    let int = x.trunc() % 4_294_967_296.0;
+    (if int < 0.0 {
+        int + 4_294_967_296.0
+    } else {
+        int
+    }) as u32
+}
+
+/// Convert a number to a 32-bit signed integer, wrapping modulo 2^32
+/// [[SPEC] - ToInt32](https://tc39.github.io/ecma262/#sec-toint32)
+pub fn to_int32(x: f64) -> i32 {
+    to_uint32(x) as i32
+}
+
+/// Convert a value to a whole number, with `NaN` becoming zero
+/// [[SPEC] - ToInteger](https://tc39.github.io/ecma262/#sec-tointeger)
+fn to_integer(value: Option<&Value>) -> f64 {
+    let num = value.map_or(f64::NAN, |v| v.to_num());
+    if num.is_nan() {
+        0.0
+    } else {
+        num.trunc()
+    }
+}
+
+/// The shortest decimal digits `s` and exponent `n` such that `s × 10^(n - len(s))` reads back as `x`
+fn shortest_digits(x: f64) -> (Vec<u8>, i32) {
+    let sci = format!("{:e}", x);
+    let e_pos = sci.find('e').unwrap();
+    let digits = sci[..e_pos].bytes().filter(|b| *b != b'.').collect();
+    (digits, sci[e_pos + 1..].parse::<i32>().unwrap() + 1)
+}
+
+/// The exact decimal expansion of a finite, non-negative number, as its digits and the position of the decimal point
+fn exact_digits(x: f64) -> (Vec<u8>, usize) {
+    // 1074 fractional digits is enough to write the smallest subnormal exactly
+    let exact = format!("{:.1074}", x);
+    let point = exact.find('.').unwrap();
+    (exact.bytes().filter(|b| *b != b'.').collect(), point)
+}
+
+/// Keep the first `len` of `digits`, rounding half up.
+/// Returns the kept digits and whether the rounding carried into a new leading digit, in which case there are `len + 1` of them
+fn round_half_up(digits: &[u8], len: usize) -> (Vec<u8>, bool) {
+    let mut kept: Vec<u8> = digits.iter().take(len).cloned().collect();
+    kept.resize(len, b'0');
+    if digits.get(len).is_some_and(|d| *d >= b'5') {
+        for d in kept.iter_mut().rev() {
+            if *d == b'9' {
+                *d = b'0';
+            } else {
+                *d += 1;
+                return (kept, false);
+            }
+        }
+        kept.insert(0, b'1');
+        return (kept, true);
+    }
+    (kept, false)
+}
+
+/// The `precision` significant digits `s` and exponent `n` such that `s × 10^(n - precision)` is closest to `x`, picking the larger on a tie
+fn rounded_digits(x: f64, precision: usize) -> (Vec<u8>, i32) {
+    if x == 0.0 {
+        return (vec![b'0'; precision], 1);
+    }
+    let (digits, point) = exact_digits(x);
+    let first = digits.iter().position(|d| *d != b'0').unwrap();
+    let (mut rounded, carried) = round_half_up(&digits[first..], precision);
+    let mut n = point as i32 - first as i32;
+    if carried {
+        rounded.pop();
+        n += 1;
+    }
+    (rounded, n)
+}
+
+/// Write digits in exponential notation, with `e` the power of ten of the first digit
+fn exponential_notation(digits: &[u8], e: i32) -> String {
+    let mut s = String::from_utf8(digits[..1].to_vec()).unwrap();
+    if digits.len() > 1 {
+        s.push('.');
+        s.push_str(std::str::from_utf8(&digits[1..]).unwrap());
+    }
+    s.push('e');
+    s.push(if e < 0 { '-' } else { '+' });
+    s.push_str(&e.abs().to_string());
+    s
+}
+
+/// Convert a number to its shortest string form that reads back as the same number
+/// [[SPEC] - Number::toString](https://tc39.github.io/ecma262/#sec-tostring-applied-to-the-number-type)
+pub fn number_to_string(x: f64) -> String {
+    if x.is_nan() {
+        return "NaN".to_string();
+    }
+    if x == 0.0 {
+        return "0".to_string();
+    }
+    if x < 0.0 {
+        return format!("-{}", number_to_string(-x));
+    }
+    if x.is_infinite() {
+        return "Infinity".to_string();
+    }
+    let (s, n) = shortest_digits(x);
+    let k = s.len() as i32;
+    let digits = std::str::from_utf8(&s).unwrap();
+    if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        exponential_notation(&s, n - 1)
+    }
+}
+
+/// The value of a digit character in radixes up to 36
+fn radix_digit_value(c: u8) -> u32 {
+    (c as char).to_digit(36).unwrap()
+}
+
+/// Convert a number to a string in a radix other than 10, writing only as many fractional digits as its precision supports
+pub fn number_to_radix_string(x: f64, radix: u32) -> String {
+    if radix == 10 || !x.is_finite() || x == 0.0 {
+        return number_to_string(x);
+    }
+    if x < 0.0 {
+        return format!("-{}", number_to_radix_string(-x, radix));
+    }
+    let base = f64::from(radix);
+    let mut integer = x.floor();
+    let mut fraction = x - integer;
+    // Half the distance to the next number up is the finest detail worth writing
+    let next_up = f64::from_bits(x.to_bits() + 1);
+    let mut delta = (0.5 * (next_up - x)).max(f64::from_bits(1));
+    let mut fraction_digits = Vec::new();
+    if fraction >= delta {
+        loop {
+            fraction *= base;
+            delta *= base;
+            let digit = fraction as usize;
+            fraction_digits.push(RADIX_DIGITS[digit]);
+            fraction -= digit as f64;
+            // Round to even, carrying through the digits already written
+            if (fraction > 0.5 || (fraction == 0.5 && digit & 1 == 1)) && fraction + delta > 1.0 {
+                loop {
+                    match fraction_digits.pop() {
+                        Some(c) if radix_digit_value(c) + 1 < radix => {
+                            fraction_digits.push(RADIX_DIGITS[radix_digit_value(c) as usize + 1]);
+                            break;
+                        }
+                        Some(_) => (),
+                        None => {
+                            integer += 1.0;
+                            break;
+                        }
+                    }
+                }
+                break;
+            }
+            if fraction < delta {
+                break;
+            }
+        }
+    }
+    // Digits below the precision of the integer part are written as zeros
+    let mut integer_digits = Vec::new();
+    while integer / base >= MAX_SAFE_INTEGER + 1.0 {
+        integer /= base;
+        integer_digits.push(b'0');
+    }
+    loop {
+        let remainder = integer % base;
+        integer_digits.push(RADIX_DIGITS[remainder as usize]);
+        integer = (integer - remainder) / base;
+        if integer <= 0.0 {
+            break;
+        }
+    }
+    integer_digits.reverse();
+    let mut s = String::from_utf8(integer_digits).unwrap();
+    if !fraction_digits.is_empty() {
+        s.push('.');
+        s.push_str(std::str::from_utf8(&fraction_digits).unwrap());
+    }
+    s
+}
+
+/// The number a value holds if it is a number primitive
+fn as_number(value: Option<&Value>) -> Option<f64> {
+    value.filter(|v| v.is_double()).map(Value::to_num)
+}
+
+/// The number primitive `this` is or wraps
+/// [[SPEC] - thisNumberValue](https://tc39.github.io/ecma262/#sec-thisnumbervalue)
+fn this_number_value(value: &Value, caller: &dyn Caller) -> Result<f64, Value> {
+    if let Some(n) = as_number(Some(value)) {
+        return Ok(n);
+    }
+    if let ValueData::Object(ref obj) = *value.data() {
+        if let Some(prop) = obj.borrow().get_internal_slot(NUMBER_DATA) {
+            return Ok(prop.to_num());
+        }
+    }
+    Err(caller.new_error("TypeError", "this is not a Number object.".to_string()))
+}
+
+/// Create a new number, or convert a value to one when called as a function
+/// [[SPEC] - The Number Constructor](https://tc39.github.io/ecma262/#sec-number-constructor)
+pub fn make_number(this: Value, callee: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let data = to_value(args.first().map_or(0.0, |v| v.to_num()));
+    if is_construct(&this, &callee) {
+        this.set_internal_slot(NUMBER_DATA, data);
+        Ok(this)
+    } else {
+        Ok(data)
+    }
+}
+/// Check if a value is a finite number, without converting it
+pub fn is_finite(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(
+        as_number(args.first()).is_some_and(f64::is_finite),
+    ))
+}
+/// Check if a value is a whole number, without converting it
+pub fn is_integer(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(
+        as_number(args.first()).is_some_and(|n| n.is_finite() && n.trunc() == n),
+    ))
+}
+/// Check if a value is `NaN`, without converting it
+pub fn is_nan(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(as_number(args.first()).is_some_and(f64::is_nan)))
+}
+/// Check if a value is a whole number that can be represented exactly, along with its neighbours
+pub fn is_safe_integer(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(as_number(args.first()).is_some_and(|n| {
+        n.trunc() == n && n.abs() <= MAX_SAFE_INTEGER
+    })))
+}
+/// Read the longest prefix of a string that is a decimal literal as a number
+/// [[SPEC] - parseFloat](https://tc39.github.io/ecma262/#sec-parsefloat-string)
+pub fn parse_float(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let input = args
+        .first()
+        .map_or("undefined".to_string(), |v| v.to_string());
+    let (sign, unsigned) = split_sign(input.trim_start_matches(is_white_space));
+    if unsigned.starts_with("Infinity") {
+        return Ok(to_value(sign * f64::INFINITY));
+    }
+    Ok(to_value(match decimal_literal_len(unsigned.as_bytes()) {
+        0 => f64::NAN,
+        len => sign * unsigned[..len].parse::<f64>().unwrap_or(f64::NAN),
+    }))
+}
+/// Read the longest prefix of a string that is an integer in a radix as a number
+/// [[SPEC] - parseInt](https://tc39.github.io/ecma262/#sec-parseint-string-radix)
+pub fn parse_int(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let input = args
+        .first()
+        .map_or("undefined".to_string(), |v| v.to_string());
+    let (sign, mut s) = split_sign(input.trim_start_matches(is_white_space));
+    let mut radix = args.get(1).map_or(0, |v| v.to_int());
+    let mut strip_prefix = true;
+    if radix != 0 {
+        if !(2..=36).contains(&radix) {
+            return Ok(to_value(f64::NAN));
+        }
+        strip_prefix = radix == 16;
+    } else {
+        radix = 10;
+    }
+    if strip_prefix && (s.starts_with("0x") || s.starts_with("0X")) {
+        s = &s[2..];
+        radix = 16;
+    }
+    let radix = radix as u32;
+    let end = s.find(|c: char| !c.is_digit(radix)).unwrap_or(s.len());
+    Ok(to_value(match &s[..end] {
+        "" => f64::NAN,
+        digits => sign * digits_to_number(digits, radix).unwrap(),
+    }))
+}
+/// Check if a value is `NaN` once converted to a number
+pub fn global_is_nan(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(
+        args.first().map_or(f64::NAN, |v| v.to_num()).is_nan(),
+    ))
+}
+/// Check if a value is finite once converted to a number
+pub fn global_is_finite(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(
+        args.first().map_or(f64::NAN, |v| v.to_num()).is_finite(),
+    ))
+}
+/// Write a number in exponential notation with a number of digits after the point
+/// [[SPEC] - Number.prototype.toExponential](https://tc39.github.io/ecma262/#sec-number.prototype.toexponential)
+pub fn to_exponential(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let x = this_number_value(&this, caller)?;
+    let f = to_integer(args.first());
+    if !x.is_finite() {
+        return Ok(to_value(number_to_string(x)));
+    }
+    if !(0.0..=100.0).contains(&f) {
+        return Err(caller.new_error(
+            "RangeError",
+            "toExponential() argument must be between 0 and 100".to_string(),
+        ));
+    }
+    let sign = if x < 0.0 { "-" } else { "" };
+    let (digits, n) = match args.first().map(Value::data).as_deref() {
+        None | Some(ValueData::Undefined) if x != 0.0 => shortest_digits(x.abs()),
+        _ => rounded_digits(x.abs(), f as usize + 1),
+    };
+    Ok(to_value(format!(
+        "{}{}",
+        sign,
+        exponential_notation(&digits, n - 1)
+    )))
+}
+/// Write a number in fixed-point notation with a number of digits after the point
+/// [[SPEC] - Number.prototype.toFixed](https://tc39.github.io/ecma262/#sec-number.prototype.tofixed)
+pub fn to_fixed(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let x = this_number_value(&this, caller)?;
+    let f = to_integer(args.first());
+    if !(0.0..=100.0).contains(&f) {
+        return Err(caller.new_error(
+            "RangeError",
+            "toFixed() digits argument must be between 0 and 100".to_string(),
+        ));
+    }
+    if !x.is_finite() || x.abs() >= 1e21 {
+        return Ok(to_value(number_to_string(x)));
+    }
+    let f = f as usize;
+    let (digits, point) = exact_digits(x.abs());
+    let (mut rounded, carried) = round_half_up(&digits, point + f);
+    let point = if carried { point + 1 } else { point };
+    if f > 0 {
+        rounded.insert(point, b'.');
+    }
+    let s = String::from_utf8(rounded).unwrap();
+    Ok(to_value(if x < 0.0 { format!("-{}", s) } else { s }))
+}
+/// Write a number the way the current locale would
+pub fn to_locale_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(number_to_string(this_number_value(&this, caller)?)))
+}
+/// Write a number with a number of significant digits
+/// [[SPEC] - Number.prototype.toPrecision](https://tc39.github.io/ecma262/#sec-number.prototype.toprecision)
+pub fn to_precision(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let x = this_number_value(&this, caller)?;
+    match args.first().map(Value::data).as_deref() {
+        None | Some(ValueData::Undefined) => return Ok(to_value(number_to_string(x))),
+        _ => (),
+    }
+    let p = to_integer(args.first());
+    if !x.is_finite() {
+        return Ok(to_value(number_to_string(x)));
+    }
+    if !(1.0..=100.0).contains(&p) {
+        return Err(caller.new_error(
+            "RangeError",
+            "toPrecision() argument must be between 1 and 100".to_string(),
+        ));
+    }
+    let p = p as usize;
+    let sign = if x < 0.0 { "-" } else { "" };
+    let (digits, n) = rounded_digits(x.abs(), p);
+    let e = n - 1;
+    if e < -6 || e >= p as i32 {
+        return Ok(to_value(format!(
+            "{}{}",
+            sign,
+            exponential_notation(&digits, e)
+        )));
+    }
+    let digits = String::from_utf8(digits).unwrap();
+    Ok(to_value(if e == p as i32 - 1 {
+        format!("{}{}", sign, digits)
+    } else if e >= 0 {
+        let (int, frac) = digits.split_at(e as usize + 1);
+        format!("{}{}.{}", sign, int, frac)
+    } else {
+        format!("{}0.{}{}", sign, "0".repeat(-(e + 1) as usize), digits)
+    }))
+}
+/// Write a number in a radix between 2 and 36
+/// [[SPEC] - Number.prototype.toString](https://tc39.github.io/ecma262/#sec-number.prototype.tostring)
+pub fn to_string(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let x = this_number_value(&this, caller)?;
+    let radix = match args.first().map(Value::data).as_deref() {
+        None | Some(ValueData::Undefined) => 10.0,
+        _ => to_integer(args.first()),
+    };
+    if !(2.0..=36.0).contains(&radix) {
+        return Err(caller.new_error(
+            "RangeError",
+            "toString() radix must be between 2 and 36".to_string(),
+        ));
+    }
+    Ok(to_value(number_to_radix_string(x, radix as u32)))
+}
+/// Get the number primitive of a number
+pub fn value_of(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(this_number_value(&this, caller)?))
+}
+/// Create a new `Number` object
+pub fn _create(global: Value) -> Value {
+    let number = to_value(make_number as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global));
+    proto.set_internal_slot(NUMBER_DATA, to_value(0.0));
+    proto.set_field_slice(
+        "toExponential",
+        to_value(to_exponential as NativeFunctionData),
+    );
+    proto.set_field_slice("toFixed", to_value(to_fixed as NativeFunctionData));
+    proto.set_field_slice(
+        "toLocaleString",
+        to_value(to_locale_string as NativeFunctionData),
+    );
+    proto.set_field_slice("toPrecision", to_value(to_precision as NativeFunctionData));
+    proto.set_field_slice("toString", to_value(to_string as NativeFunctionData));
+    proto.set_field_slice("valueOf", to_value(value_of as NativeFunctionData));
+    number.set_field_slice(PROTOTYPE, proto);
+    number.set_field_slice("EPSILON", to_value(f64::EPSILON));
+    number.set_field_slice("MAX_SAFE_INTEGER", to_value(MAX_SAFE_INTEGER));
+    number.set_field_slice("MAX_VALUE", to_value(f64::MAX));
+    number.set_field_slice("MIN_SAFE_INTEGER", to_value(-MAX_SAFE_INTEGER));
+    number.set_field_slice("MIN_VALUE", to_value(f64::from_bits(1)));
+    number.set_field_slice("NaN", to_value(f64::NAN));
+    number.set_field_slice("NEGATIVE_INFINITY", to_value(-f64::INFINITY));
+    number.set_field_slice("POSITIVE_INFINITY", to_value(f64::INFINITY));
+    number.set_field_slice("isFinite", to_value(is_finite as NativeFunctionData));
+    number.set_field_slice("isInteger", to_value(is_integer as NativeFunctionData));
+    number.set_field_slice("isNaN", to_value(is_nan as NativeFunctionData));
+    number.set_field_slice(
+        "isSafeInteger",
+        to_value(is_safe_integer as NativeFunctionData),
+    );
+    number.set_field_slice("parseFloat", to_value(parse_float as NativeFunctionData));
+    number.set_field_slice("parseInt", to_value(parse_int as NativeFunctionData));
+    number
+}
+/// Initialise the global object with the `Number` object and the global number functions and values
+pub fn init(global: Value) {
+    let number = _create(global.clone());
+    // `parseFloat` and `parseInt` are the same functions on the global object and on `Number`
+    global.set_field_slice("parseFloat", number.get_field_slice("parseFloat"));
+    global.set_field_slice("parseInt", number.get_field_slice("parseInt"));
+    global.set_field_slice("isFinite", to_value(global_is_finite as NativeFunctionData));
+    global.set_field_slice("isNaN", to_value(global_is_nan as NativeFunctionData));
+    global.set_prop_slice("Infinity", Property::read_only(to_value(f64::INFINITY)));
+    global.set_prop_slice("NaN", Property::read_only(to_value(f64::NAN)));
+    global.set_field_slice("Number", number);
+}
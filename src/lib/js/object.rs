@@ -1,16 +1,141 @@
-use gc::Gc;
-use crate::js::function::NativeFunctionData;
+use crate::gc::{Trace, Tracer};
+use crate::js::atom::{self, Atom, AtomMarks, ToAtom};
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::typed_array::TypedArray;
 use crate::js::value::{from_value, to_value, FromValue, ResultValue, ToValue, Value, ValueData};
-use std::collections::HashMap;
-pub static PROTOTYPE: &'static str = "prototype";
-pub static INSTANCE_PROTOTYPE: &'static str = "__proto__";
+use indexmap::IndexMap;
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+pub static PROTOTYPE: &str = "prototype";
+pub static INSTANCE_PROTOTYPE: &str = "__proto__";
 
-pub type ObjectData = HashMap<String, Property>;
+/// The properties of an object, kept in the order they were created so enumerating them follows it,
+/// and its internal slots
+///
+/// Internal slots, like the `[[DateValue]]` of a `Date`, hold the engine's own state. They're kept
+/// apart from the properties, so no property key a script uses can reach them.
+#[derive(Clone, Debug, Default)]
+pub struct ObjectData {
+    properties: IndexMap<Atom, Property>,
+    slots: IndexMap<Atom, Value>,
+}
+
+impl ObjectData {
+    /// Make a new object without any properties
+    pub fn new() -> ObjectData {
+        ObjectData::default()
+    }
+
+    /// The property with a key, if the object has one
+    pub fn get<K: ToAtom + ?Sized>(&self, key: &K) -> Option<&Property> {
+        self.properties.get(&key.find_atom()?)
+    }
+
+    /// The property with a key, to change it in place
+    pub fn get_mut<K: ToAtom + ?Sized>(&mut self, key: &K) -> Option<&mut Property> {
+        self.properties.get_mut(&key.find_atom()?)
+    }
+
+    /// Whether the object has a property with a key
+    pub fn contains_key<K: ToAtom + ?Sized>(&self, key: &K) -> bool {
+        key.find_atom()
+            .is_some_and(|atom| self.properties.contains_key(&atom))
+    }
+
+    /// Set the property with a key, returning the one it replaced
+    pub fn insert<K: ToAtom>(&mut self, key: K, prop: Property) -> Option<Property> {
+        self.properties.insert(key.to_atom(), prop)
+    }
+
+    /// Remove the property with a key, keeping the others in order
+    pub fn shift_remove<K: ToAtom + ?Sized>(&mut self, key: &K) -> Option<Property> {
+        self.properties.shift_remove(&key.find_atom()?)
+    }
+
+    /// The value of an internal slot, if the object has it
+    pub fn get_internal_slot(&self, name: &str) -> Option<&Value> {
+        self.slots.get(&Atom::lookup(name)?)
+    }
+
+    /// Whether the object has an internal slot
+    pub fn has_internal_slot(&self, name: &str) -> bool {
+        self.get_internal_slot(name).is_some()
+    }
+
+    /// Set an internal slot, giving the object the slot if it doesn't have it yet
+    pub fn set_internal_slot(&mut self, name: &str, value: Value) {
+        self.slots.insert(Atom::new(name), value);
+    }
+
+    /// The prototype of the object, if it has one
+    pub fn prototype(&self) -> Option<&Value> {
+        self.slots.get(&atom::PROTOTYPE_SLOT)
+    }
+
+    /// Set the prototype of the object
+    pub fn set_prototype(&mut self, proto: Value) {
+        self.slots.insert(atom::PROTOTYPE_SLOT, proto);
+    }
+
+    /// The internal slots of the object, in the order they were made
+    pub fn internal_slots(&self) -> impl Iterator<Item = (Atom, &Value)> {
+        self.slots.iter().map(|(name, value)| (*name, value))
+    }
+}
+
+impl Deref for ObjectData {
+    type Target = IndexMap<Atom, Property>;
+    fn deref(&self) -> &IndexMap<Atom, Property> {
+        &self.properties
+    }
+}
+
+impl DerefMut for ObjectData {
+    fn deref_mut(&mut self) -> &mut IndexMap<Atom, Property> {
+        &mut self.properties
+    }
+}
+
+impl<K: ToAtom> FromIterator<(K, Property)> for ObjectData {
+    fn from_iter<I: IntoIterator<Item = (K, Property)>>(iter: I) -> ObjectData {
+        ObjectData {
+            properties: iter
+                .into_iter()
+                .map(|(key, prop)| (key.to_atom(), prop))
+                .collect(),
+            slots: IndexMap::new(),
+        }
+    }
+}
+
+unsafe impl Trace for ObjectData {
+    fn trace(&self, tracer: &mut Tracer) {
+        for prop in self.properties.values() {
+            prop.trace(tracer);
+        }
+        for value in self.slots.values() {
+            value.trace(tracer);
+        }
+    }
+
+    fn mark_atoms(&self, marks: &mut AtomMarks) {
+        for &key in self.properties.keys().chain(self.slots.keys()) {
+            marks.mark(key);
+        }
+    }
+
+    fn extra_size(&self) -> usize {
+        // Keys are atoms, whose names are kept once in the atom table
+        self.properties.len() * mem::size_of::<(Atom, Property)>()
+            + self.slots.len() * mem::size_of::<(Atom, Value)>()
+    }
+}
 
 /// A Javascript Property AKA The Property Descriptor   
 /// [[SPEC] - The Property Descriptor Specification Type](https://tc39.github.io/ecma262/#sec-property-descriptor-specification-type)   
 /// [[SPEC] - Default Attribute Values](https://tc39.github.io/ecma262/#table-4)
-#[derive(Trace, Finalize, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Property {
     /// If the type of this can be changed and this can be deleted
     pub configurable: bool,
@@ -26,18 +151,48 @@ pub struct Property {
     pub set: Value,
 }
 
+unsafe impl Trace for Property {
+    fn trace(&self, tracer: &mut Tracer) {
+        self.value.trace(tracer);
+        self.get.trace(tracer);
+        self.set.trace(tracer);
+    }
+}
+
 impl Property {
-    /// Make a new property with the given value
+    /// Make a new property with the given value, which can be assigned to
     pub fn new(value: Value) -> Property {
         Property {
             configurable: false,
             enumerable: false,
-            writable: false,
-            value: value,
-            get: Gc::new(ValueData::Undefined),
-            set: Gc::new(ValueData::Undefined),
+            writable: true,
+            value,
+            get: Value::undefined(),
+            set: Value::undefined(),
         }
     }
+
+    /// Make a new property with the given value, which assignments leave alone
+    pub fn read_only(value: Value) -> Property {
+        let mut prop = Property::new(value);
+        prop.writable = false;
+        prop
+    }
+
+    /// Make a new property which calls a getter when it's read and a setter when it's assigned to
+    ///
+    /// Either can be `undefined`, for a property without one
+    pub fn accessor(get: Value, set: Value) -> Property {
+        let mut prop = Property::new(Value::undefined());
+        prop.get = get;
+        prop.set = set;
+        prop
+    }
+
+    /// If the property has a getter or setter in place of a value
+    pub fn is_accessor(&self) -> bool {
+        self.get.is_function() || self.set.is_function()
+    }
 }
 
 impl ToValue for Property {
@@ -66,45 +221,88 @@ impl FromValue for Property {
     }
 }
 
+/// The keys of the own properties of an object that scripts can see, with array indices first in ascending order and then the other keys in the order they were created
+/// [[SPEC] - OrdinaryOwnPropertyKeys](https://tc39.github.io/ecma262/#sec-ordinaryownpropertykeys)
+pub fn own_property_keys(obj: &ObjectData) -> Vec<String> {
+    let mut indices: Vec<u32> = match TypedArray::from_object(obj) {
+        Some(array) => (0..array.len() as u32).collect(),
+        None => Vec::new(),
+    };
+    let mut keys = Vec::new();
+    for key in obj.keys() {
+        if let Some(index) = key.as_index() {
+            indices.push(index);
+            continue;
+        }
+        let name = key.as_str();
+        match name.parse::<u32>() {
+            // Only the canonical form of an integer below 2^32 - 1 is an array index
+            Ok(index) if index != u32::MAX && index.to_string() == *name => indices.push(index),
+            _ => keys.push(name.to_string()),
+        }
+    }
+    indices.sort();
+    indices
+        .iter()
+        .map(|index| index.to_string())
+        .chain(keys)
+        .collect()
+}
+
+/// Whether `this` is the fresh object `new` created for `callee`, rather than the receiver of a plain call
+pub fn is_construct(this: &Value, callee: &Value) -> bool {
+    if let ValueData::Object(ref obj) = *this.data() {
+        let obj = obj.borrow();
+        if !obj.is_empty() {
+            return false;
+        }
+        if let Some(proto) = obj.prototype() {
+            let expected = callee.get_field_slice(PROTOTYPE);
+            return proto.same_heap_cell(&expected);
+        }
+    }
+    false
+}
+
 /// Create a new object
-pub fn make_object(_: Value, _: Value, _args: Vec<Value>) -> ResultValue {
-    Ok(Gc::new(ValueData::Undefined))
+pub fn make_object(_: Value, _: Value, _args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    Ok(Value::undefined())
 }
 
 /// Get the prototype of an object
-pub fn get_proto_of(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    let obj = args.get(0).unwrap();
-    Ok(obj.get_field_slice(INSTANCE_PROTOTYPE))
+pub fn get_proto_of(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let obj = args.first().unwrap();
+    Ok(obj.get_prototype())
 }
 
 /// Set the prototype of an object
-pub fn set_proto_of(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    let obj = args.get(0).unwrap().clone();
+pub fn set_proto_of(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let obj = args.first().unwrap().clone();
     let proto = args.get(1).unwrap().clone();
-    obj.set_field_slice(INSTANCE_PROTOTYPE, proto);
+    obj.set_prototype(proto);
     Ok(obj)
 }
 
 /// Define a property in an object
-pub fn define_prop(_: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    let obj = args.get(0).unwrap();
+pub fn define_prop(_: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let obj = args.first().unwrap();
     let prop = from_value::<String>(args.get(1).unwrap().clone()).unwrap();
     let desc = from_value::<Property>(args.get(2).unwrap().clone()).unwrap();
     obj.set_prop(prop, desc);
-    Ok(Gc::new(ValueData::Undefined))
+    Ok(Value::undefined())
 }
 
 /// To string
-pub fn to_string(this: Value, _: Value, _: Vec<Value>) -> ResultValue {
+pub fn to_string(this: Value, _: Value, _: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
     Ok(to_value(this.to_string()))
 }
 
 /// Check if it has a property
-pub fn has_own_prop(this: Value, _: Value, args: Vec<Value>) -> ResultValue {
-    let prop = if args.len() == 0 {
+pub fn has_own_prop(this: Value, _: Value, args: Vec<Value>, _: &mut dyn Caller) -> ResultValue {
+    let prop = if args.is_empty() {
         None
     } else {
-        from_value::<String>(args.get(0).unwrap().clone()).ok()
+        from_value::<String>(args.first().unwrap().clone()).ok()
     };
     Ok(to_value(
         prop.is_some() && this.get_prop(prop.unwrap()).is_some(),
@@ -0,0 +1,768 @@
+use crate::js::array::ARRAY_DATA;
+use crate::js::array_buffer::buffer_bytes;
+use crate::js::object::{own_property_keys, PROTOTYPE};
+use crate::js::value::{to_value, Value, ValueData};
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use serde::ser::{self, Serialize};
+use std::cell::RefCell;
+use std::error;
+use std::fmt;
+use std::rc::Rc;
+
+/// The largest integer a number holds exactly, past which integers become `BigInt`s
+const MAX_SAFE_INTEGER: i128 = 9_007_199_254_740_991;
+
+/// An error converting between Javascript values and Rust data
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Error {
+        Error(msg.to_string())
+    }
+}
+
+/// Convert Rust data to a Javascript value, making objects and arrays without prototypes
+///
+/// Use a `Serializer` made with the global object for ones with the usual prototypes
+pub fn to_js_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, Error> {
+    value.serialize(Serializer::new(None))
+}
+
+/// Convert a Javascript value to Rust data
+pub fn from_js_value<T: DeserializeOwned>(value: &Value) -> Result<T, Error> {
+    T::deserialize(Deserializer::new(value.clone()))
+}
+
+/// Converts Rust data to Javascript values
+///
+/// Structs and maps become objects with their fields in order, sequences and tuples become
+/// arrays, and enums are tagged with an object holding the variant, as `serde_json` does. Unit
+/// values become `undefined` and `None` becomes `null`, while integers a number can't hold
+/// exactly become `BigInt`s.
+#[derive(Clone)]
+pub struct Serializer {
+    /// The global object, whose `Object` and `Array` prototypes objects and arrays are made with
+    global: Option<Value>,
+}
+
+impl Serializer {
+    /// Make a serializer, given the global object to take prototypes from if there is one
+    pub fn new(global: Option<Value>) -> Serializer {
+        Serializer { global }
+    }
+
+    /// Make an object
+    fn new_object(&self) -> Value {
+        ValueData::new_obj(self.global.clone())
+    }
+
+    /// Make an array of some values
+    fn new_array(&self, items: Vec<Value>) -> Value {
+        let arr = match self.global {
+            Some(ref global) => ValueData::new_obj_from_prototype(
+                global.get_field_slice("Array").get_field_slice(PROTOTYPE),
+            ),
+            None => ValueData::new_obj(None),
+        };
+        arr.set_internal_slot(ARRAY_DATA, to_value(true));
+        let len = items.len();
+        for (i, item) in items.into_iter().enumerate() {
+            arr.set_field(i, item);
+        }
+        arr.set_field_slice("length", to_value(len as i32));
+        arr
+    }
+
+    /// Make an object tagging a value with the variant of an enum it's the data of
+    fn tagged(&self, variant: &str, value: Value) -> Value {
+        let obj = self.new_object();
+        obj.set_field(variant, value);
+        obj
+    }
+}
+
+/// An integer, as a number if it can hold it exactly or a `BigInt` otherwise
+fn integer(n: i128) -> Value {
+    if n.abs() <= MAX_SAFE_INTEGER {
+        to_value(n as f64)
+    } else {
+        to_value(BigInt::from(n))
+    }
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(to_value(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        Ok(integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(integer(v.into()))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        if v <= MAX_SAFE_INTEGER as u128 {
+            Ok(to_value(v as f64))
+        } else {
+            Ok(to_value(BigInt::from(v)))
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        Ok(to_value(f64::from(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(to_value(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(to_value(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(to_value(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(self.new_array(v.iter().map(|b| to_value(f64::from(*b))).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::null())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Ok(Value::undefined())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Ok(Value::undefined())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(to_value(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        let value = value.serialize(self.clone())?;
+        Ok(self.tagged(variant, value))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+            ser: self,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len),
+            variant: Some(variant),
+            ser: self,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject {
+            object: self.new_object(),
+            key: None,
+            variant: None,
+            ser: self,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeObject, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject {
+            object: self.new_object(),
+            key: None,
+            variant: Some(variant),
+            ser: self,
+        })
+    }
+}
+
+/// Collects the elements of a sequence, tuple or tuple variant into an array
+pub struct SerializeArray {
+    ser: Serializer,
+    items: Vec<Value>,
+    /// The variant the array is the data of, for a tuple variant
+    variant: Option<&'static str>,
+}
+
+impl SerializeArray {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(self.ser.clone())?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        let arr = self.ser.new_array(self.items);
+        Ok(match self.variant {
+            Some(variant) => self.ser.tagged(variant, arr),
+            None => arr,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+/// Collects the entries of a map, struct or struct variant into an object
+pub struct SerializeObject {
+    ser: Serializer,
+    object: Value,
+    /// The key of the entry whose value is next
+    key: Option<String>,
+    /// The variant the object is the data of, for a struct variant
+    variant: Option<&'static str>,
+}
+
+impl SerializeObject {
+    fn finish(self) -> Result<Value, Error> {
+        Ok(match self.variant {
+            Some(variant) => self.ser.tagged(variant, self.object),
+            None => self.object,
+        })
+    }
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = key.serialize(self.ser.clone())?;
+        self.key = Some(match *key.data() {
+            ValueData::String(_)
+            | ValueData::Number(_)
+            | ValueData::Integer(_)
+            | ValueData::BigInt(_)
+            | ValueData::Boolean(_) => key.to_string(),
+            _ => {
+                return Err(Error(format!(
+                    "Keys of maps must be strings, numbers or booleans, not {}",
+                    key.get_type()
+                )))
+            }
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.object
+            .set_field(key, value.serialize(self.ser.clone())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.object
+            .set_field(key, value.serialize(self.ser.clone())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.object
+            .set_field(key, value.serialize(self.ser.clone())?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+/// Converts a Javascript value to Rust data
+///
+/// Objects are read as maps or structs, keeping the order of their keys, and objects with a
+/// `length` and only indices below it otherwise are read as sequences. Numbers read as integers
+/// when they're whole, `undefined` and `null` both read as `None`, and an enum is read from the
+/// name of a unit variant or an object holding the variant. Functions can't be read, and an
+/// object that contains itself is an error rather than read forever.
+pub struct Deserializer {
+    value: Value,
+    /// The objects being read, from the outermost in
+    path: Rc<RefCell<Vec<usize>>>,
+}
+
+impl Deserializer {
+    /// Make a deserializer reading a value
+    pub fn new(value: Value) -> Deserializer {
+        Deserializer {
+            value,
+            path: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// A deserializer for a value inside the one this reads
+    fn child(&self, value: Value) -> Deserializer {
+        Deserializer {
+            value,
+            path: self.path.clone(),
+        }
+    }
+
+    /// Read the object this reads with a visitor, failing if it's already being read further out
+    fn visit_object<'de, V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let address = self.value.heap_address().unwrap_or(0);
+        if self.path.borrow().contains(&address) {
+            return Err(Error(
+                "Can't convert an object that contains itself".to_string(),
+            ));
+        }
+        self.path.borrow_mut().push(address);
+        let result = match array_length(&self.value) {
+            Some(len) => visitor.visit_seq(ArrayAccess {
+                de: &self,
+                index: 0,
+                len,
+            }),
+            None => {
+                let keys = match *self.value.data() {
+                    ValueData::Object(ref obj) => own_property_keys(&obj.borrow()),
+                    _ => Vec::new(),
+                };
+                visitor.visit_map(ObjectAccess {
+                    de: &self,
+                    keys: keys.into_iter(),
+                    value: None,
+                })
+            }
+        };
+        self.path.borrow_mut().pop();
+        result
+    }
+}
+
+/// The length of an object that's an array, with a `length` and no keys but indices below it
+fn array_length(value: &Value) -> Option<u32> {
+    if let ValueData::Object(ref obj) = *value.data() {
+        let len = obj.borrow().get("length")?.value.to_num();
+        if len < 0.0 || len.fract() != 0.0 || len > f64::from(u32::MAX) {
+            return None;
+        }
+        let len = len as u32;
+        let keys = own_property_keys(&obj.borrow());
+        let elements = keys.iter().all(|key| match key.parse::<u32>() {
+            Ok(index) => index < len,
+            Err(_) => key == "length",
+        });
+        if elements {
+            return Some(len);
+        }
+    }
+    None
+}
+
+/// Visit a number as an integer if it's a whole one
+fn visit_number<'de, V: Visitor<'de>>(n: f64, visitor: V) -> Result<V::Value, Error> {
+    if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER as f64 {
+        if n >= 0.0 {
+            visitor.visit_u64(n as u64)
+        } else {
+            visitor.visit_i64(n as i64)
+        }
+    } else {
+        visitor.visit_f64(n)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let unsupported = match *self.value.data() {
+            ValueData::Undefined => return visitor.visit_unit(),
+            ValueData::Null => return visitor.visit_none(),
+            ValueData::Boolean(b) => return visitor.visit_bool(b),
+            ValueData::Number(n) => return visit_number(n, visitor),
+            ValueData::Integer(n) => return visitor.visit_i32(n),
+            ValueData::String(ref s) => return visitor.visit_string(s.to_string()),
+            ValueData::BigInt(ref n) => {
+                if let Some(n) = n.to_u64() {
+                    return visitor.visit_u64(n);
+                } else if let Some(n) = n.to_i64() {
+                    return visitor.visit_i64(n);
+                } else if let Some(n) = n.to_i128() {
+                    return visitor.visit_i128(n);
+                }
+                "a BigInt wider than 128 bits"
+            }
+            ValueData::Object(_) => "",
+            ValueData::Function(_) => "a function",
+            ValueData::Bytes(_) | ValueData::Host(_) | ValueData::Slots(_) => "an internal value",
+        };
+        if unsupported.is_empty() {
+            self.visit_object(visitor)
+        } else {
+            Err(Error(format!("Can't convert {} to Rust data", unsupported)))
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.value.is_null_or_undefined() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.value.is_null_or_undefined() {
+            visitor.visit_unit()
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match buffer_bytes(&self.value) {
+            Some(bytes) => visitor.visit_byte_buf(bytes.borrow().clone()),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if self.value.is_string() {
+            return visitor.visit_enum(self.value.to_string().into_deserializer());
+        }
+        let keys = match *self.value.data() {
+            ValueData::Object(ref obj) => own_property_keys(&obj.borrow()),
+            _ => Vec::new(),
+        };
+        if keys.len() != 1 {
+            return Err(Error(format!(
+                "Expected a string or an object with one key for the enum {}",
+                name
+            )));
+        }
+        let value = self.value.get_field(keys[0].as_str());
+        visitor.visit_enum(VariantDeserializer {
+            variant: keys[0].clone(),
+            de: self.child(value),
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Reads the elements of an array
+struct ArrayAccess<'a> {
+    de: &'a Deserializer,
+    index: u32,
+    len: u32,
+}
+
+impl<'a, 'de> SeqAccess<'de> for ArrayAccess<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let value = self.de.value.get_field(self.index);
+        self.index += 1;
+        seed.deserialize(self.de.child(value)).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some((self.len - self.index) as usize)
+    }
+}
+
+/// Reads the entries of an object
+struct ObjectAccess<'a> {
+    de: &'a Deserializer,
+    keys: ::std::vec::IntoIter<String>,
+    /// The value of the entry whose key was read last
+    value: Option<Value>,
+}
+
+impl<'a, 'de> MapAccess<'de> for ObjectAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.keys.next() {
+            Some(key) => {
+                self.value = Some(self.de.value.get_field(key.as_str()));
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(self.de.child(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.keys.len())
+    }
+}
+
+/// Reads the variant of an enum held in an object, and its data
+struct VariantDeserializer {
+    variant: String,
+    de: Deserializer,
+}
+
+impl<'de> EnumAccess<'de> for VariantDeserializer {
+    type Error = Error;
+    type Variant = Deserializer;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Deserializer), Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.de))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Deserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
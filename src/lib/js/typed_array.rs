@@ -0,0 +1,750 @@
+use crate::js::array_buffer::{
+    self, allocate, buffer_bytes, make_buffer, relative_index, to_index, SharedBytes,
+};
+use crate::js::atom::Atom;
+use crate::js::bigint::to_bigint;
+use crate::js::data_view::{self, DATA_VIEW_BYTE_LENGTH};
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::number::{number_to_string, string_to_number, to_int32, to_uint32};
+use crate::js::object::{is_construct, ObjectData, Property, PROTOTYPE};
+use crate::js::value::{to_value, NativeError, ResultValue, Value, ValueData};
+use num_bigint::BigInt;
+use num_traits::Signed;
+
+/// The internal slot of a typed array holding the name of its constructor, such as `"Uint8Array"`
+pub static TYPED_ARRAY_NAME: &str = "[[TypedArrayName]]";
+/// The internal slot of a typed array or `DataView` holding its `ArrayBuffer`
+pub static VIEWED_ARRAY_BUFFER: &str = "[[ViewedArrayBuffer]]";
+/// The internal slot of a typed array or `DataView` holding where it starts in its buffer
+pub static BYTE_OFFSET: &str = "[[ByteOffset]]";
+/// The internal slot of a typed array holding its length, or `undefined` if it tracks the length of its buffer
+pub static ARRAY_LENGTH: &str = "[[ArrayLength]]";
+/// The internal slot of a typed array constructor holding the prototype of the buffers it allocates
+static ARRAY_BUFFER_PROTOTYPE: &str = "[[ArrayBufferPrototype]]";
+
+/// The type of the elements of a typed array
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TypedArrayKind {
+    Int8,
+    Uint8,
+    Uint8Clamped,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Float32,
+    Float64,
+    BigInt64,
+    BigUint64,
+}
+
+impl TypedArrayKind {
+    /// Every kind of typed array
+    pub const ALL: [TypedArrayKind; 11] = [
+        TypedArrayKind::Int8,
+        TypedArrayKind::Uint8,
+        TypedArrayKind::Uint8Clamped,
+        TypedArrayKind::Int16,
+        TypedArrayKind::Uint16,
+        TypedArrayKind::Int32,
+        TypedArrayKind::Uint32,
+        TypedArrayKind::Float32,
+        TypedArrayKind::Float64,
+        TypedArrayKind::BigInt64,
+        TypedArrayKind::BigUint64,
+    ];
+
+    /// The name of the constructor for this kind
+    pub fn name(self) -> &'static str {
+        match self {
+            TypedArrayKind::Int8 => "Int8Array",
+            TypedArrayKind::Uint8 => "Uint8Array",
+            TypedArrayKind::Uint8Clamped => "Uint8ClampedArray",
+            TypedArrayKind::Int16 => "Int16Array",
+            TypedArrayKind::Uint16 => "Uint16Array",
+            TypedArrayKind::Int32 => "Int32Array",
+            TypedArrayKind::Uint32 => "Uint32Array",
+            TypedArrayKind::Float32 => "Float32Array",
+            TypedArrayKind::Float64 => "Float64Array",
+            TypedArrayKind::BigInt64 => "BigInt64Array",
+            TypedArrayKind::BigUint64 => "BigUint64Array",
+        }
+    }
+
+    /// The kind whose constructor is called `name`
+    pub fn from_name(name: &str) -> Option<TypedArrayKind> {
+        TypedArrayKind::ALL
+            .iter()
+            .cloned()
+            .find(|kind| kind.name() == name)
+    }
+
+    /// The number of bytes in an element
+    pub fn element_size(self) -> usize {
+        match self {
+            TypedArrayKind::Int8 | TypedArrayKind::Uint8 | TypedArrayKind::Uint8Clamped => 1,
+            TypedArrayKind::Int16 | TypedArrayKind::Uint16 => 2,
+            TypedArrayKind::Int32 | TypedArrayKind::Uint32 | TypedArrayKind::Float32 => 4,
+            TypedArrayKind::Float64 | TypedArrayKind::BigInt64 | TypedArrayKind::BigUint64 => 8,
+        }
+    }
+
+    /// Whether the elements are `BigInt`s rather than numbers
+    pub fn is_bigint(self) -> bool {
+        self == TypedArrayKind::BigInt64 || self == TypedArrayKind::BigUint64
+    }
+
+    /// Read an element from its bytes
+    /// [[SPEC] - RawBytesToNumeric](https://tc39.github.io/ecma262/#sec-rawbytestonumeric)
+    pub fn decode(self, bytes: &[u8], little_endian: bool) -> Value {
+        let size = self.element_size();
+        let mut buf = [0u8; 8];
+        buf[..size].copy_from_slice(&bytes[..size]);
+        if !little_endian {
+            buf[..size].reverse();
+        }
+        let (b2, b4) = ([buf[0], buf[1]], [buf[0], buf[1], buf[2], buf[3]]);
+        match self {
+            TypedArrayKind::Int8 => to_value(buf[0] as i8 as i32),
+            TypedArrayKind::Uint8 | TypedArrayKind::Uint8Clamped => to_value(buf[0] as i32),
+            TypedArrayKind::Int16 => to_value(i16::from_le_bytes(b2) as i32),
+            TypedArrayKind::Uint16 => to_value(u16::from_le_bytes(b2) as i32),
+            TypedArrayKind::Int32 => to_value(i32::from_le_bytes(b4)),
+            TypedArrayKind::Uint32 => to_value(u32::from_le_bytes(b4) as f64),
+            TypedArrayKind::Float32 => to_value(f32::from_le_bytes(b4) as f64),
+            TypedArrayKind::Float64 => to_value(f64::from_le_bytes(buf)),
+            TypedArrayKind::BigInt64 => to_value(BigInt::from(i64::from_le_bytes(buf))),
+            TypedArrayKind::BigUint64 => to_value(BigInt::from(u64::from_le_bytes(buf))),
+        }
+    }
+
+    /// Convert a value to the bytes of an element
+    /// [[SPEC] - NumericToRawBytes](https://tc39.github.io/ecma262/#sec-numerictorawbytes)
+    pub fn encode(self, value: &Value, little_endian: bool) -> Result<Vec<u8>, NativeError> {
+        let mut bytes = if self.is_bigint() {
+            bigint_low_bits(&to_bigint(value)?).to_le_bytes().to_vec()
+        } else {
+            let n = value.to_number()?;
+            match self {
+                TypedArrayKind::Int8 | TypedArrayKind::Uint8 => vec![to_int32(n) as u8],
+                TypedArrayKind::Uint8Clamped => vec![to_uint8_clamp(n)],
+                TypedArrayKind::Int16 | TypedArrayKind::Uint16 => {
+                    (to_int32(n) as u16).to_le_bytes().to_vec()
+                }
+                TypedArrayKind::Int32 | TypedArrayKind::Uint32 => {
+                    to_uint32(n).to_le_bytes().to_vec()
+                }
+                TypedArrayKind::Float32 => (n as f32).to_le_bytes().to_vec(),
+                _ => n.to_le_bytes().to_vec(),
+            }
+        };
+        if !little_endian {
+            bytes.reverse();
+        }
+        Ok(bytes)
+    }
+}
+
+/// The low 64 bits of a `BigInt` in two's complement
+fn bigint_low_bits(n: &BigInt) -> u64 {
+    let mut buf = [if n.is_negative() { 0xFF } else { 0 }; 8];
+    for (i, byte) in n.to_signed_bytes_le().into_iter().take(8).enumerate() {
+        buf[i] = byte;
+    }
+    u64::from_le_bytes(buf)
+}
+
+/// Clamp a number to a byte, rounding halves to even
+/// [[SPEC] - ToUint8Clamp](https://tc39.github.io/ecma262/#sec-touint8clamp)
+fn to_uint8_clamp(n: f64) -> u8 {
+    if n.is_nan() || n <= 0.0 {
+        return 0;
+    }
+    if n >= 255.0 {
+        return 255;
+    }
+    let f = n.floor();
+    if f + 0.5 < n || (f + 0.5 == n && f % 2.0 == 1.0) {
+        f as u8 + 1
+    } else {
+        f as u8
+    }
+}
+
+/// The number a property name stands for, if it's the canonical way of writing that number
+/// [[SPEC] - CanonicalNumericIndexString](https://tc39.github.io/ecma262/#sec-canonicalnumericindexstring)
+fn canonical_numeric_index(field: &str) -> Option<f64> {
+    if field == "-0" {
+        return Some(-0.0);
+    }
+    let n = string_to_number(field);
+    if number_to_string(n) == field {
+        Some(n)
+    } else {
+        None
+    }
+}
+
+/// The element of a typed array at a numeric index, or `None` if it's out of bounds
+fn array_element(array: &TypedArray, n: f64) -> Option<Property> {
+    valid_index(n, array.len())
+        .and_then(|i| array.get(i))
+        .map(Property::new)
+}
+
+/// Where the elements of a typed array are
+pub struct TypedArray {
+    /// The type of the elements
+    pub kind: TypedArrayKind,
+    /// The `ArrayBuffer` holding the elements
+    pub buffer: Value,
+    /// The bytes of the buffer
+    pub bytes: SharedBytes,
+    /// Where the elements start in the buffer
+    pub byte_offset: usize,
+    /// The number of elements, or `None` if the array tracks the length of its buffer
+    pub array_length: Option<usize>,
+}
+
+impl TypedArray {
+    /// Read the internal slots of a typed array, or `None` if the object isn't one
+    pub fn from_object(obj: &ObjectData) -> Option<TypedArray> {
+        let kind = TypedArrayKind::from_name(&obj.get_internal_slot(TYPED_ARRAY_NAME)?.to_string())?;
+        let buffer = obj.get_internal_slot(VIEWED_ARRAY_BUFFER)?.clone();
+        let bytes = buffer_bytes(&buffer)?;
+        let array_length = &obj.get_internal_slot(ARRAY_LENGTH)?;
+        Some(TypedArray {
+            kind,
+            buffer: buffer.clone(),
+            bytes,
+            byte_offset: obj.get_internal_slot(BYTE_OFFSET)?.to_num() as usize,
+            array_length: if array_length.is_undefined() {
+                None
+            } else {
+                Some(array_length.to_num() as usize)
+            },
+        })
+    }
+
+    /// Read the internal slots of a typed array value, or `None` if it isn't one
+    pub fn from_value(value: &Value) -> Option<TypedArray> {
+        match *value.data() {
+            ValueData::Object(ref obj) => TypedArray::from_object(&obj.borrow()),
+            _ => None,
+        }
+    }
+
+    /// The number of elements, or `None` if a buffer that shrank no longer holds all of them
+    /// [[SPEC] - IsTypedArrayOutOfBounds](https://tc39.es/ecma262/#sec-istypedarrayoutofbounds)
+    pub fn bounds(&self) -> Option<usize> {
+        let buffer_len = self.bytes.borrow().len();
+        let size = self.kind.element_size();
+        match self.array_length {
+            None if self.byte_offset <= buffer_len => Some((buffer_len - self.byte_offset) / size),
+            Some(len) if self.byte_offset + len * size <= buffer_len => Some(len),
+            _ => None,
+        }
+    }
+
+    /// The number of elements, which is 0 when out of bounds
+    pub fn len(&self) -> usize {
+        self.bounds().unwrap_or(0)
+    }
+
+    /// If there are no elements, as when out of bounds
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read the element at an index, or `None` if the index is out of bounds
+    pub fn get(&self, index: usize) -> Option<Value> {
+        if index >= self.len() {
+            return None;
+        }
+        let size = self.kind.element_size();
+        let start = self.byte_offset + index * size;
+        let bytes = self.bytes.borrow();
+        Some(
+            self.kind
+                .decode(&bytes[start..start + size], cfg!(target_endian = "little")),
+        )
+    }
+
+    /// Write the bytes of an element at an index, ignoring an index that is out of bounds
+    pub fn put(&self, index: usize, element: &[u8]) {
+        if index < self.len() {
+            let start = self.byte_offset + index * element.len();
+            self.bytes.borrow_mut()[start..start + element.len()].copy_from_slice(element);
+        }
+    }
+
+    /// Convert a value to the bytes of an element
+    pub fn encode(&self, value: &Value) -> Result<Vec<u8>, NativeError> {
+        self.kind.encode(value, cfg!(target_endian = "little"))
+    }
+
+    /// Read every element
+    pub fn elements(&self) -> Vec<Value> {
+        (0..self.len()).filter_map(|i| self.get(i)).collect()
+    }
+}
+
+/// The index of an element a property name stands for, if it's within `len`
+/// [[SPEC] - IsValidIntegerIndex](https://tc39.github.io/ecma262/#sec-isvalidintegerindex)
+fn valid_index(n: f64, len: usize) -> Option<usize> {
+    if n.trunc() != n || (n == 0.0 && n.is_sign_negative()) || n < 0.0 || n >= len as f64 {
+        None
+    } else {
+        Some(n as usize)
+    }
+}
+
+/// Resolve a field whose value lives in the buffer of a typed array, `DataView` or `ArrayBuffer`,
+/// such as an element or a length, or `None` if the field is an ordinary property
+/// [[SPEC] - Integer-Indexed Exotic Objects](https://tc39.github.io/ecma262/#sec-integer-indexed-exotic-objects)
+pub fn get_buffer_field(obj: &ObjectData, field: Atom) -> Option<Option<Property>> {
+    let value = if let Some(array) = TypedArray::from_object(obj) {
+        let size = array.kind.element_size() as f64;
+        if let Some(index) = field.as_index() {
+            return Some(array_element(&array, f64::from(index)));
+        }
+        match &*field.as_str() {
+            "buffer" => array.buffer.clone(),
+            "byteLength" => to_value(array.len() as f64 * size),
+            "byteOffset" => to_value(array.bounds().map_or(0.0, |_| array.byte_offset as f64)),
+            "length" => to_value(array.len() as f64),
+            name => return Some(array_element(&array, canonical_numeric_index(name)?)),
+        }
+    } else if obj.has_internal_slot(DATA_VIEW_BYTE_LENGTH) {
+        data_view::get_view_field(obj, &field.as_str())?
+    } else if let Some(prop) = obj.get_internal_slot(array_buffer::ARRAY_BUFFER_DATA) {
+        let len = match *prop.data() {
+            ValueData::Bytes(ref bytes) => bytes.borrow().len(),
+            _ => return None,
+        };
+        let max = obj.get_internal_slot(array_buffer::ARRAY_BUFFER_MAX_BYTE_LENGTH);
+        match &*field.as_str() {
+            "byteLength" => to_value(len as f64),
+            "maxByteLength" => max.map_or(to_value(len as f64), |prop| prop.clone()),
+            "resizable" => to_value(max.is_some()),
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    Some(Some(Property::new(value)))
+}
+
+/// Assign to a field whose value lives in the buffer of a typed array, `DataView` or `ArrayBuffer`,
+/// or `None` if the field is an ordinary property
+/// Elements out of bounds and lengths are left alone, but converting the value can fail
+pub fn set_buffer_field(
+    obj: &ObjectData,
+    field: Atom,
+    val: &Value,
+) -> Option<Result<(), NativeError>> {
+    if let Some(array) = TypedArray::from_object(obj) {
+        let n = match field.as_index() {
+            Some(index) => f64::from(index),
+            None => match &*field.as_str() {
+                "buffer" | "byteLength" | "byteOffset" | "length" => return Some(Ok(())),
+                name => canonical_numeric_index(name)?,
+            },
+        };
+        return Some(array.encode(val).map(|element| {
+            if let Some(index) = valid_index(n, array.len()) {
+                array.put(index, &element);
+            }
+        }));
+    }
+    match get_buffer_field(obj, field) {
+        Some(Some(_)) => Some(Ok(())),
+        _ => None,
+    }
+}
+
+/// Get the typed array `this` is, or throw if it isn't one
+fn this_typed_array(this: &Value, caller: &dyn Caller) -> Result<TypedArray, Value> {
+    TypedArray::from_value(this)
+        .ok_or_else(|| caller.new_error("TypeError", "this is not a typed array.".to_string()))
+}
+
+/// Set the internal slots of a new typed array
+fn init_typed_array(
+    this: &Value,
+    kind: TypedArrayKind,
+    buffer: Value,
+    byte_offset: usize,
+    array_length: Option<usize>,
+) {
+    this.set_internal_slot(TYPED_ARRAY_NAME, to_value(kind.name()));
+    this.set_internal_slot(VIEWED_ARRAY_BUFFER, buffer);
+    this.set_internal_slot(BYTE_OFFSET, to_value(byte_offset as f64));
+    this.set_internal_slot(
+        ARRAY_LENGTH,
+        match array_length {
+            Some(len) => to_value(len as f64),
+            None => Value::undefined(),
+        },
+    );
+}
+
+/// Make a new typed array with a new buffer holding some elements
+fn typed_array_from_elements(
+    proto: Value,
+    buffer_proto: Value,
+    kind: TypedArrayKind,
+    elements: &[Value],
+    caller: &dyn Caller,
+) -> ResultValue {
+    let this = ValueData::new_obj_from_prototype(proto);
+    fill_new_typed_array(&this, buffer_proto, kind, elements, caller)?;
+    Ok(this)
+}
+
+/// Give a new typed array a new buffer holding some elements
+fn fill_new_typed_array(
+    this: &Value,
+    buffer_proto: Value,
+    kind: TypedArrayKind,
+    elements: &[Value],
+    caller: &dyn Caller,
+) -> Result<(), Value> {
+    let size = kind.element_size();
+    let mut bytes = Vec::new();
+    if bytes.try_reserve_exact(elements.len() * size).is_err() {
+        return Err(caller.new_error("RangeError", "Array buffer allocation failed".to_string()));
+    }
+    for element in elements {
+        let element = kind
+            .encode(element, cfg!(target_endian = "little"))
+            .map_err(|e| e.into_error(caller))?;
+        bytes.extend(element);
+    }
+    let bytes = std::rc::Rc::new(std::cell::RefCell::new(bytes));
+    init_typed_array(
+        this,
+        kind,
+        make_buffer(buffer_proto, bytes, None),
+        0,
+        Some(elements.len()),
+    );
+    Ok(())
+}
+
+/// Make a new typed array with a new buffer holding some bytes, given its constructor, for the host
+/// to hand bytes to scripts, like a `Uint8Array` of encoded text
+pub fn new_typed_array(constructor: &Value, bytes: Vec<u8>) -> Value {
+    let kind = TypedArrayKind::from_name(&constructor.get_internal_slot(TYPED_ARRAY_NAME).to_string())
+        .expect("a typed array constructor");
+    let len = bytes.len() / kind.element_size();
+    let this = ValueData::new_obj_from_prototype(constructor.get_field_slice(PROTOTYPE));
+    let bytes = std::rc::Rc::new(std::cell::RefCell::new(bytes));
+    let buffer = make_buffer(constructor.get_internal_slot(ARRAY_BUFFER_PROTOTYPE), bytes, None);
+    init_typed_array(&this, kind, buffer, 0, Some(len));
+    this
+}
+
+/// Create a new typed array from a length, an array-like object, another typed array or a view on an `ArrayBuffer`
+/// [[SPEC] - The TypedArray Constructors](https://tc39.github.io/ecma262/#sec-typedarray-constructors)
+pub fn make_typed_array(this: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let kind = TypedArrayKind::from_name(&callee.get_internal_slot(TYPED_ARRAY_NAME).to_string())
+        .ok_or_else(|| {
+            caller.new_error("TypeError", "Not a typed array constructor".to_string())
+        })?;
+    if !is_construct(&this, &callee) {
+        return Err(caller.new_error("TypeError", format!(
+            "Constructor {} requires 'new'",
+            kind.name()
+        )));
+    }
+    let buffer_proto = callee.get_internal_slot(ARRAY_BUFFER_PROTOTYPE);
+    let size = kind.element_size();
+    let first = args
+        .first()
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    if let Some(bytes) = buffer_bytes(&first) {
+        let offset = to_index(args.get(1), "Start offset is out of bounds", caller)?;
+        if offset % size != 0 {
+            return Err(caller.new_error("RangeError", format!(
+                "start offset of {} should be a multiple of {}",
+                kind.name(),
+                size
+            )));
+        }
+        let buffer_len = bytes.borrow().len();
+        let length = match args.get(2) {
+            Some(len) if !len.is_undefined() => {
+                let len = to_index(Some(len), "Invalid typed array length", caller)?;
+                if offset + len * size > buffer_len {
+                    return Err(caller
+                        .new_error("RangeError", format!("Invalid typed array length: {}", len)));
+                }
+                Some(len)
+            }
+            _ if offset > buffer_len => {
+                return Err(caller.new_error("RangeError", format!(
+                    "Start offset {} is outside the bounds of the buffer",
+                    offset
+                )))
+            }
+            _ if array_buffer::max_byte_length(&first).is_some() => None,
+            _ if (buffer_len - offset) % size != 0 => {
+                return Err(caller.new_error("RangeError", format!(
+                    "byte length of {} should be a multiple of {}",
+                    kind.name(),
+                    size
+                )))
+            }
+            _ => Some((buffer_len - offset) / size),
+        };
+        init_typed_array(&this, kind, first, offset, length);
+        return Ok(this);
+    }
+    let elements = if let Some(source) = TypedArray::from_value(&first) {
+        if source.kind.is_bigint() != kind.is_bigint() {
+            return Err(caller.new_error(
+                "TypeError",
+                "Cannot mix BigInt and other types, use explicit conversions".to_string(),
+            ));
+        }
+        source.elements()
+    } else if first.is_object() {
+        let len = to_index(
+            Some(&first.get_field_slice("length")),
+            "Invalid typed array length",
+            caller,
+        )?;
+        (0..len).map(|i| first.get_field(i.to_string())).collect()
+    } else {
+        let len = to_index(
+            Some(&first),
+            &format!("Invalid typed array length: {}", first),
+            caller,
+        )?;
+        let bytes = allocate(len * size, caller)?;
+        init_typed_array(
+            &this,
+            kind,
+            make_buffer(buffer_proto, bytes, None),
+            0,
+            Some(len),
+        );
+        return Ok(this);
+    };
+    fill_new_typed_array(&this, buffer_proto, kind, &elements, caller)?;
+    Ok(this)
+}
+/// Create a new typed array holding the arguments
+/// [[SPEC] - %TypedArray%.of](https://tc39.github.io/ecma262/#sec-%typedarray%.of)
+pub fn of(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let kind = TypedArrayKind::from_name(&this.get_internal_slot(TYPED_ARRAY_NAME).to_string())
+        .ok_or_else(|| {
+            caller.new_error("TypeError", "this is not a typed array constructor.".to_string())
+        })?;
+    typed_array_from_elements(
+        this.get_field_slice(PROTOTYPE),
+        this.get_internal_slot(ARRAY_BUFFER_PROTOTYPE),
+        kind,
+        &args,
+        caller,
+    )
+}
+/// Write a value to every element between two indices
+/// [[SPEC] - %TypedArray%.prototype.fill](https://tc39.github.io/ecma262/#sec-%typedarray%.prototype.fill)
+pub fn fill(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let array = this_typed_array(&this, caller)?;
+    let element = array.encode(
+        &args
+            .first()
+            .cloned()
+            .unwrap_or_else(Value::undefined),
+    )
+    .map_err(|e| e.into_error(caller))?;
+    let len = array.len();
+    let start = relative_index(args.get(1), len, 0, caller)?;
+    let end = relative_index(args.get(2), len, len, caller)?;
+    for index in start..end {
+        array.put(index, &element);
+    }
+    Ok(this)
+}
+/// Whether an element is strictly equal to a value, or both are `NaN`
+/// [[SPEC] - %TypedArray%.prototype.includes](https://tc39.github.io/ecma262/#sec-%typedarray%.prototype.includes)
+pub fn includes(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let array = this_typed_array(&this, caller)?;
+    let search = args
+        .first()
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    let start = relative_index(args.get(1), array.len(), 0, caller)?;
+    let search_nan = search.is_double() && search.to_num().is_nan();
+    Ok(to_value(
+        (start..array.len())
+            .filter_map(|i| array.get(i))
+            .any(|element| {
+                element.strict_equals(&search) || (search_nan && element.to_num().is_nan())
+            }),
+    ))
+}
+/// Find the first index of an element strictly equal to a value, or -1
+/// [[SPEC] - %TypedArray%.prototype.indexOf](https://tc39.github.io/ecma262/#sec-%typedarray%.prototype.indexof)
+pub fn index_of(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let array = this_typed_array(&this, caller)?;
+    let search = args
+        .first()
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    let start = relative_index(args.get(1), array.len(), 0, caller)?;
+    Ok(to_value(
+        (start..array.len())
+            .find(|i| {
+                array
+                    .get(*i)
+                    .is_some_and(|element| element.strict_equals(&search))
+            })
+            .map_or(-1.0, |i| i as f64),
+    ))
+}
+/// Join the elements into a string with a separator, which is a comma by default
+/// [[SPEC] - %TypedArray%.prototype.join](https://tc39.github.io/ecma262/#sec-%typedarray%.prototype.join)
+pub fn join(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let array = this_typed_array(&this, caller)?;
+    let separator = match args.first() {
+        Some(sep) if !sep.is_undefined() => sep.to_string(),
+        _ => ",".to_string(),
+    };
+    let elements: Vec<String> = array.elements().iter().map(|v| v.to_string()).collect();
+    Ok(to_value(elements.join(&separator)))
+}
+/// Copy the elements of an array or typed array into this one, starting at an offset
+/// [[SPEC] - %TypedArray%.prototype.set](https://tc39.github.io/ecma262/#sec-%typedarray%.prototype.set)
+pub fn set(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let array = this_typed_array(&this, caller)?;
+    let source = args
+        .first()
+        .cloned()
+        .unwrap_or_else(Value::undefined);
+    let offset = match args.get(1) {
+        Some(offset) => offset.to_number().map_err(|e| e.into_error(caller))?,
+        None => 0.0,
+    };
+    let offset = if offset.is_nan() { 0.0 } else { offset.trunc() };
+    if offset < 0.0 {
+        return Err(caller.new_error("RangeError", "offset is out of bounds".to_string()));
+    }
+    // Reading every element before writing any keeps overlapping views on one buffer right
+    let elements = match TypedArray::from_value(&source) {
+        Some(source) => {
+            if source.kind.is_bigint() != array.kind.is_bigint() {
+                return Err(caller.new_error(
+                    "TypeError",
+                    "Cannot mix BigInt and other types, use explicit conversions".to_string(),
+                ));
+            }
+            source.elements()
+        }
+        None => {
+            let len = to_index(
+                Some(&source.get_field_slice("length")),
+                "Invalid array length",
+                caller,
+            )?;
+            (0..len).map(|i| source.get_field(i.to_string())).collect()
+        }
+    };
+    if offset + elements.len() as f64 > array.len() as f64 {
+        return Err(caller.new_error("RangeError", "offset is out of bounds".to_string()));
+    }
+    let mut encoded = Vec::with_capacity(elements.len());
+    for element in elements.iter() {
+        encoded.push(array.encode(element).map_err(|e| e.into_error(caller))?);
+    }
+    for (i, element) in encoded.iter().enumerate() {
+        array.put(offset as usize + i, element);
+    }
+    Ok(Value::undefined())
+}
+/// Copy the elements between two indices to a new typed array with a new buffer
+/// [[SPEC] - %TypedArray%.prototype.slice](https://tc39.github.io/ecma262/#sec-%typedarray%.prototype.slice)
+pub fn slice(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let array = this_typed_array(&this, caller)?;
+    let len = array.len();
+    let start = relative_index(args.first(), len, 0, caller)?;
+    let end = relative_index(args.get(1), len, len, caller)?;
+    let elements: Vec<Value> = (start..end.max(start))
+        .filter_map(|i| array.get(i))
+        .collect();
+    typed_array_from_elements(
+        this.get_prototype(),
+        array.buffer.get_prototype(),
+        array.kind,
+        &elements,
+        caller,
+    )
+}
+/// Make a new typed array viewing the elements between two indices of the same buffer
+/// [[SPEC] - %TypedArray%.prototype.subarray](https://tc39.github.io/ecma262/#sec-%typedarray%.prototype.subarray)
+pub fn subarray(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let array = this_typed_array(&this, caller)?;
+    let len = array.len();
+    let start = relative_index(args.first(), len, 0, caller)?;
+    let end_given = args.get(1).is_some_and(|end| !end.is_undefined());
+    let end = relative_index(args.get(1), len, len, caller)?;
+    let sub = ValueData::new_obj_from_prototype(this.get_prototype());
+    // A view tracking the length of its buffer stays that way unless given an end
+    let length = if array.array_length.is_none() && !end_given {
+        None
+    } else {
+        Some(end.saturating_sub(start))
+    };
+    init_typed_array(
+        &sub,
+        array.kind,
+        array.buffer.clone(),
+        array.byte_offset + start * array.kind.element_size(),
+        length,
+    );
+    Ok(sub)
+}
+/// Create the prototype shared by every typed array prototype
+fn create_typed_array_prototype(global: &Value) -> Value {
+    let proto = ValueData::new_obj(Some(global.clone()));
+    proto.set_field_slice("fill", to_value(fill as NativeFunctionData));
+    proto.set_field_slice("includes", to_value(includes as NativeFunctionData));
+    proto.set_field_slice("indexOf", to_value(index_of as NativeFunctionData));
+    proto.set_field_slice("join", to_value(join as NativeFunctionData));
+    proto.set_field_slice("set", to_value(set as NativeFunctionData));
+    proto.set_field_slice("slice", to_value(slice as NativeFunctionData));
+    proto.set_field_slice("subarray", to_value(subarray as NativeFunctionData));
+    proto.set_field_slice("toString", to_value(join as NativeFunctionData));
+    proto
+}
+/// Create a typed array constructor of some kind
+pub fn _create(global: &Value, typed_array_proto: &Value, kind: TypedArrayKind) -> Value {
+    let constructor = to_value(make_typed_array as NativeFunctionData);
+    let size = to_value(kind.element_size() as i32);
+    let proto = ValueData::new_obj_from_prototype(typed_array_proto.clone());
+    proto.set_field_slice("BYTES_PER_ELEMENT", size.clone());
+    proto.set_field_slice("constructor", constructor.clone());
+    constructor.set_field_slice(PROTOTYPE, proto);
+    constructor.set_field_slice("BYTES_PER_ELEMENT", size);
+    constructor.set_internal_slot(TYPED_ARRAY_NAME, to_value(kind.name()));
+    constructor.set_internal_slot(
+        ARRAY_BUFFER_PROTOTYPE,
+        global
+            .get_field_slice("ArrayBuffer")
+            .get_field_slice(PROTOTYPE),
+    );
+    constructor.set_field_slice("of", to_value(of as NativeFunctionData));
+    constructor
+}
+/// Initialise the global object with a constructor for each kind of typed array
+/// This needs the `ArrayBuffer` object to be there already
+pub fn init(global: Value) {
+    let typed_array_proto = create_typed_array_prototype(&global);
+    for kind in TypedArrayKind::ALL.iter() {
+        global.set_field_slice(kind.name(), _create(&global, &typed_array_proto, *kind));
+    }
+}
@@ -0,0 +1,162 @@
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::js_string::JsString;
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
+
+/// The characters `encodeURIComponent` leaves as they are
+/// [[SPEC] - uriUnreserved](https://tc39.github.io/ecma262/#prod-uriUnreserved)
+const URI_UNRESERVED_MARKS: &str = "-_.!~*'()";
+/// The characters with a meaning in a URI and `#`, which `encodeURI` leaves and `decodeURI` doesn't decode
+/// [[SPEC] - uriReserved](https://tc39.github.io/ecma262/#prod-uriReserved)
+const URI_RESERVED_AND_HASH: &str = ";/?:@&=+$,#";
+
+/// The error thrown for a string which can't be encoded or decoded
+fn uri_malformed(caller: &dyn Caller) -> Value {
+    caller.new_error("URIError", "URI malformed".to_string())
+}
+
+/// The argument of a URI function as a string
+fn uri_arg(args: &[Value]) -> JsString {
+    args.first()
+        .map_or_else(|| JsString::from("undefined"), |arg| arg.to_js_string())
+}
+
+/// Percent-encode the UTF-8 bytes of each character, apart from the ones `unescaped` keeps
+/// [[SPEC] - Encode](https://tc39.github.io/ecma262/#sec-encode)
+fn encode(string: &JsString, unescaped: &dyn Fn(u16) -> bool, caller: &dyn Caller) -> ResultValue {
+    let units: Vec<u16> = string.code_units().collect();
+    let mut out = String::with_capacity(units.len());
+    let mut buf = [0u8; 4];
+    for c in std::char::decode_utf16(units.iter().cloned()) {
+        let c = c.map_err(|_| uri_malformed(caller))?;
+        if (c as u32) < 0x80 && unescaped(c as u16) {
+            out.push(c);
+            continue;
+        }
+        for byte in c.encode_utf8(&mut buf).bytes() {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    Ok(to_value(out))
+}
+
+/// The value of a hexadecimal digit
+fn hex_value(unit: u16) -> Option<u8> {
+    match unit {
+        0x30..=0x39 => Some((unit - 0x30) as u8),
+        0x41..=0x46 => Some((unit - 0x41 + 10) as u8),
+        0x61..=0x66 => Some((unit - 0x61 + 10) as u8),
+        _ => None,
+    }
+}
+
+/// The byte a `%XX` escape at an index stands for
+fn escaped_byte(units: &[u16], at: usize) -> Option<u8> {
+    if units.get(at) != Some(&(b'%' as u16)) {
+        return None;
+    }
+    let high = hex_value(*units.get(at + 1)?)?;
+    let low = hex_value(*units.get(at + 2)?)?;
+    Some(high << 4 | low)
+}
+
+/// Replace each escape sequence with the character its UTF-8 bytes stand for, apart from the ones
+/// in `reserved`, whose escapes are kept
+/// [[SPEC] - Decode](https://tc39.github.io/ecma262/#sec-decode)
+fn decode(string: &JsString, reserved: &str, caller: &dyn Caller) -> ResultValue {
+    let units: Vec<u16> = string.code_units().collect();
+    let mut out = Vec::with_capacity(units.len());
+    let mut k = 0;
+    while k < units.len() {
+        if units[k] != b'%' as u16 {
+            out.push(units[k]);
+            k += 1;
+            continue;
+        }
+        let start = k;
+        let byte = escaped_byte(&units, k).ok_or_else(|| uri_malformed(caller))?;
+        k += 3;
+        if byte < 0x80 {
+            if reserved.as_bytes().contains(&byte) {
+                out.extend_from_slice(&units[start..k]);
+            } else {
+                out.push(byte as u16);
+            }
+            continue;
+        }
+        let n = match byte.leading_ones() {
+            2 => 2,
+            3 => 3,
+            4 => 4,
+            _ => return Err(uri_malformed(caller)),
+        };
+        let mut bytes = vec![byte];
+        for _ in 1..n {
+            match escaped_byte(&units, k) {
+                Some(byte) if byte & 0xC0 == 0x80 => bytes.push(byte),
+                _ => return Err(uri_malformed(caller)),
+            }
+            k += 3;
+        }
+        // This rejects overlong forms, surrogates and code points past U+10FFFF, as the spec asks
+        let c = std::str::from_utf8(&bytes)
+            .map_err(|_| uri_malformed(caller))?
+            .chars()
+            .next()
+            .expect("a character was decoded");
+        let mut buf = [0u16; 2];
+        out.extend_from_slice(c.encode_utf16(&mut buf));
+    }
+    Ok(Value::new(ValueData::String(JsString::from_utf16(out))))
+}
+
+/// Whether `encodeURIComponent` leaves an ASCII character as it is
+fn is_unreserved(unit: u16) -> bool {
+    let c = unit as u8 as char;
+    c.is_ascii_alphanumeric() || URI_UNRESERVED_MARKS.contains(c)
+}
+
+/// Encode a whole URI, leaving the characters with a meaning in it
+/// [[SPEC] - encodeURI](https://tc39.github.io/ecma262/#sec-encodeuri-uri)
+pub fn encode_uri(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let unescaped =
+        |unit: u16| is_unreserved(unit) || URI_RESERVED_AND_HASH.contains(unit as u8 as char);
+    encode(&uri_arg(&args), &unescaped, caller)
+}
+/// Encode a component of a URI, such as a query parameter
+/// [[SPEC] - encodeURIComponent](https://tc39.github.io/ecma262/#sec-encodeuricomponent-uricomponent)
+pub fn encode_uri_component(
+    _: Value,
+    _: Value,
+    args: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    encode(&uri_arg(&args), &is_unreserved, caller)
+}
+/// Decode a whole URI, keeping the escapes of the characters with a meaning in it
+/// [[SPEC] - decodeURI](https://tc39.github.io/ecma262/#sec-decodeuri-encodeduri)
+pub fn decode_uri(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    decode(&uri_arg(&args), URI_RESERVED_AND_HASH, caller)
+}
+/// Decode a component of a URI
+/// [[SPEC] - decodeURIComponent](https://tc39.github.io/ecma262/#sec-decodeuricomponent-encodeduricomponent)
+pub fn decode_uri_component(
+    _: Value,
+    _: Value,
+    args: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    decode(&uri_arg(&args), "", caller)
+}
+/// Initialise the global object with the URI functions
+pub fn init(global: Value) {
+    global.set_field_slice("encodeURI", to_value(encode_uri as NativeFunctionData));
+    global.set_field_slice(
+        "encodeURIComponent",
+        to_value(encode_uri_component as NativeFunctionData),
+    );
+    global.set_field_slice("decodeURI", to_value(decode_uri as NativeFunctionData));
+    global.set_field_slice(
+        "decodeURIComponent",
+        to_value(decode_uri_component as NativeFunctionData),
+    );
+}
@@ -0,0 +1,1639 @@
+use crate::js::array::{array_create, ARRAY_DATA};
+use crate::js::function::{Caller, NativeFunctionData};
+use crate::js::object::{is_construct, own_property_keys, Property, PROTOTYPE};
+use crate::js::value::{to_value, ResultValue, Value, ValueData};
+use std::fmt;
+
+/// The internal slot of a `URL`, and of its `URLSearchParams`, holding the object they share
+/// whose `[[Href]]` is the URL they stand for, so they don't need to refer to each other
+///
+/// This is `undefined` for a `URLSearchParams` with a list of its own.
+static URL_RECORD: &str = "[[UrlRecord]]";
+/// The internal slot of the object a `URL` and its `URLSearchParams` share holding the URL, serialized
+static HREF: &str = "[[Href]]";
+/// The internal slot of a `URL` holding its `URLSearchParams`
+static URL_SEARCH_PARAMS: &str = "[[SearchParams]]";
+/// The internal slot of a `URLSearchParams` without a `URL` holding its list, serialized as a query
+static PARAMS_LIST: &str = "[[List]]";
+/// The internal slot of the accessors of `URL.prototype` holding the name of the part of the URL
+/// they're for, and of the methods of `URLSearchParams.prototype` listing its pairs holding what they list
+static COMPONENT: &str = "[[UrlComponent]]";
+/// The internal slot of `URL.parse` holding `URL.prototype`, for the URLs it makes
+static URL_PROTOTYPE: &str = "[[UrlPrototype]]";
+/// The internal slot of `URL` and `URL.parse` holding `URLSearchParams.prototype`, for the
+/// `searchParams` of the URLs they make
+static SEARCH_PARAMS_PROTOTYPE: &str = "[[URLSearchParamsPrototype]]";
+
+/// The host of a URL
+#[derive(Clone, Debug, PartialEq)]
+enum Host {
+    /// A domain, in ASCII
+    Domain(String),
+    /// An IPv4 address
+    Ipv4(u32),
+    /// An IPv6 address, as its eight 16-bit pieces
+    Ipv6([u16; 8]),
+    /// The percent-encoded host of a URL whose scheme isn't special
+    Opaque(String),
+    /// An empty host, as `file:///` and `foo://` have
+    Empty,
+}
+
+impl fmt::Display for Host {
+    /// Write the host as it's serialized in a URL
+    /// [[SPEC] - Host serializer](https://url.spec.whatwg.org/#concept-host-serializer)
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Host::Domain(ref host) | Host::Opaque(ref host) => f.write_str(host),
+            Host::Ipv4(address) => {
+                let [a, b, c, d] = address.to_be_bytes();
+                write!(f, "{}.{}.{}.{}", a, b, c, d)
+            }
+            Host::Ipv6(ref pieces) => write!(f, "[{}]", serialize_ipv6(pieces)),
+            Host::Empty => Ok(()),
+        }
+    }
+}
+
+/// The path of a URL
+#[derive(Clone, Debug, PartialEq)]
+enum UrlPath {
+    /// The path of a URL like `mailto:someone@example.com`, which is a string after the scheme
+    Opaque(String),
+    /// A path made of segments, each written after a `/`
+    Segments(Vec<String>),
+}
+
+/// A URL, parsed as the WHATWG URL standard does
+/// [[SPEC] - URL record](https://url.spec.whatwg.org/#concept-url)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Url {
+    scheme: String,
+    username: String,
+    password: String,
+    host: Option<Host>,
+    port: Option<u16>,
+    path: UrlPath,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+/// The port a special scheme uses by default, or `None` if the scheme doesn't have one
+/// [[SPEC] - Default port](https://url.spec.whatwg.org/#default-port)
+fn default_port(scheme: &str) -> Option<u16> {
+    match scheme {
+        "ftp" => Some(21),
+        "http" | "ws" => Some(80),
+        "https" | "wss" => Some(443),
+        _ => None,
+    }
+}
+
+/// Whether a scheme is one of the special ones, whose URLs are parsed differently
+/// [[SPEC] - Special scheme](https://url.spec.whatwg.org/#special-scheme)
+fn is_special(scheme: &str) -> bool {
+    matches!(scheme, "ftp" | "file" | "http" | "https" | "ws" | "wss")
+}
+
+/// Whether a byte is percent-encoded everywhere it appears in a URL
+/// [[SPEC] - C0 control percent-encode set](https://url.spec.whatwg.org/#c0-control-percent-encode-set)
+fn c0_control_set(byte: u8) -> bool {
+    !(0x20..=0x7E).contains(&byte)
+}
+
+/// [[SPEC] - Fragment percent-encode set](https://url.spec.whatwg.org/#fragment-percent-encode-set)
+fn fragment_set(byte: u8) -> bool {
+    c0_control_set(byte) || b" \"<>`".contains(&byte)
+}
+
+/// [[SPEC] - Query percent-encode set](https://url.spec.whatwg.org/#query-percent-encode-set)
+fn query_set(byte: u8) -> bool {
+    c0_control_set(byte) || b" \"#<>".contains(&byte)
+}
+
+/// [[SPEC] - Special-query percent-encode set](https://url.spec.whatwg.org/#special-query-percent-encode-set)
+fn special_query_set(byte: u8) -> bool {
+    query_set(byte) || byte == b'\''
+}
+
+/// [[SPEC] - Path percent-encode set](https://url.spec.whatwg.org/#path-percent-encode-set)
+fn path_set(byte: u8) -> bool {
+    query_set(byte) || b"?^`{}".contains(&byte)
+}
+
+/// [[SPEC] - Userinfo percent-encode set](https://url.spec.whatwg.org/#userinfo-percent-encode-set)
+fn userinfo_set(byte: u8) -> bool {
+    path_set(byte) || b"/:;=@[\\]|".contains(&byte)
+}
+
+/// [[SPEC] - application/x-www-form-urlencoded percent-encode set](https://url.spec.whatwg.org/#application-x-www-form-urlencoded-percent-encode-set)
+fn form_set(byte: u8) -> bool {
+    userinfo_set(byte) || b"$%&+,!'()~".contains(&byte)
+}
+
+/// Percent-encode the UTF-8 bytes of a string which are in a percent-encode set
+/// [[SPEC] - UTF-8 percent-encode](https://url.spec.whatwg.org/#string-utf-8-percent-encode)
+fn percent_encode(input: &str, set: fn(u8) -> bool) -> String {
+    let mut out = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        if set(byte) {
+            out.push_str(&format!("%{:02X}", byte));
+        } else {
+            out.push(byte as char);
+        }
+    }
+    out
+}
+
+/// Replace each `%` followed by two hexadecimal digits with the byte they stand for
+/// [[SPEC] - Percent-decode](https://url.spec.whatwg.org/#percent-decode)
+fn percent_decode(input: &[u8]) -> Vec<u8> {
+    let hex = |byte: Option<&u8>| byte.and_then(|&byte| (byte as char).to_digit(16));
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        if input[i] == b'%' {
+            if let (Some(high), Some(low)) = (hex(input.get(i + 1)), hex(input.get(i + 2))) {
+                out.push((high << 4 | low) as u8);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(input[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Whether a character can't be in a host
+/// [[SPEC] - Forbidden host code point](https://url.spec.whatwg.org/#forbidden-host-code-point)
+fn is_forbidden_host(c: char) -> bool {
+    "\0\t\n\r #/:<>?@[\\]^|".contains(c)
+}
+
+/// Whether a character can't be in a domain
+/// [[SPEC] - Forbidden domain code point](https://url.spec.whatwg.org/#forbidden-domain-code-point)
+fn is_forbidden_domain(c: char) -> bool {
+    is_forbidden_host(c) || c <= '\x1F' || c == '%' || c == '\x7F'
+}
+
+/// Whether a string is a letter followed by `:` or `|`, as `C:` is
+/// [[SPEC] - Windows drive letter](https://url.spec.whatwg.org/#windows-drive-letter)
+fn is_windows_drive_letter(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && (bytes[1] == b':' || bytes[1] == b'|')
+}
+
+/// [[SPEC] - Normalized Windows drive letter](https://url.spec.whatwg.org/#normalized-windows-drive-letter)
+fn is_normalized_windows_drive_letter(s: &str) -> bool {
+    is_windows_drive_letter(s) && s.as_bytes()[1] == b':'
+}
+
+/// Whether a string starts with a Windows drive letter which is a whole path segment
+/// [[SPEC] - Starts with a Windows drive letter](https://url.spec.whatwg.org/#start-with-a-windows-drive-letter)
+fn starts_with_windows_drive_letter(s: &str) -> bool {
+    s.len() >= 2
+        && is_windows_drive_letter(&s[..2])
+        && (s.len() == 2 || "/\\?#".contains(s.as_bytes()[2] as char))
+}
+
+/// Whether a path segment is `.`, which stays where it is
+fn is_single_dot(segment: &str) -> bool {
+    segment == "." || segment.eq_ignore_ascii_case("%2e")
+}
+
+/// Whether a path segment is `..`, which goes up a segment
+fn is_double_dot(segment: &str) -> bool {
+    matches!(
+        segment.to_ascii_lowercase().as_str(),
+        ".." | ".%2e" | "%2e." | "%2e%2e"
+    )
+}
+
+/// Leave out tabs and newlines, which the parser ignores wherever they are
+fn strip_tab_and_newline(input: &str) -> String {
+    input
+        .chars()
+        .filter(|&c| c != '\t' && c != '\n' && c != '\r')
+        .collect()
+}
+
+/// Split a string into a scheme and what follows the `:` after it, or `None` if it doesn't start with a scheme
+/// [[SPEC] - Scheme state](https://url.spec.whatwg.org/#scheme-state)
+fn split_scheme(input: &str) -> Option<(&str, &str)> {
+    if !input.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let end = input
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '+' && c != '-' && c != '.')
+        .unwrap_or(input.len());
+    if input[end..].starts_with(':') {
+        Some((&input[..end], &input[end + 1..]))
+    } else {
+        None
+    }
+}
+
+/// Parse a number in a part of an IPv4 address, which can be hexadecimal after `0x` or octal after `0`
+/// [[SPEC] - IPv4 number parser](https://url.spec.whatwg.org/#ipv4-number-parser)
+fn parse_ipv4_number(mut input: &str) -> Option<u64> {
+    if input.is_empty() {
+        return None;
+    }
+    let mut radix = 10;
+    if input.len() >= 2 && (input.starts_with("0x") || input.starts_with("0X")) {
+        input = &input[2..];
+        radix = 16;
+    } else if input.len() >= 2 && input.starts_with('0') {
+        input = &input[1..];
+        radix = 8;
+    }
+    let mut n: u64 = 0;
+    for c in input.chars() {
+        // A number too big for an address only has to stay too big
+        let digit = c.to_digit(radix)?;
+        n = n.saturating_mul(radix as u64).saturating_add(digit as u64);
+    }
+    Some(n)
+}
+
+/// Whether a domain ends in a number, which makes it an IPv4 address
+/// [[SPEC] - Ends in a number checker](https://url.spec.whatwg.org/#ends-in-a-number-checker)
+fn ends_in_number(domain: &str) -> bool {
+    let mut parts: Vec<&str> = domain.split('.').collect();
+    if parts.last() == Some(&"") {
+        if parts.len() == 1 {
+            return false;
+        }
+        parts.pop();
+    }
+    let last = parts[parts.len() - 1];
+    (!last.is_empty() && last.bytes().all(|byte| byte.is_ascii_digit()))
+        || parse_ipv4_number(last).is_some()
+}
+
+/// Parse an IPv4 address, whose parts can be fewer than four, with the last filling the rest
+/// [[SPEC] - IPv4 parser](https://url.spec.whatwg.org/#concept-ipv4-parser)
+fn parse_ipv4(input: &str) -> Option<u32> {
+    let mut parts: Vec<&str> = input.split('.').collect();
+    if parts.len() > 1 && parts.last() == Some(&"") {
+        parts.pop();
+    }
+    if parts.len() > 4 {
+        return None;
+    }
+    let numbers = parts
+        .iter()
+        .map(|part| parse_ipv4_number(part))
+        .collect::<Option<Vec<u64>>>()?;
+    let (&last, rest) = numbers.split_last()?;
+    if rest.iter().any(|&n| n > 255) || last >= 256u64.pow(5 - numbers.len() as u32) {
+        return None;
+    }
+    let address = rest
+        .iter()
+        .enumerate()
+        .fold(last, |address, (i, &n)| address + (n << (8 * (3 - i))));
+    Some(address as u32)
+}
+
+/// Parse an IPv6 address, without the brackets around it
+/// [[SPEC] - IPv6 parser](https://url.spec.whatwg.org/#concept-ipv6-parser)
+fn parse_ipv6(input: &str) -> Option<[u16; 8]> {
+    let input: Vec<char> = input.chars().collect();
+    let at = |pointer: usize| input.get(pointer).cloned();
+    let mut address = [0u16; 8];
+    let mut piece_index = 0;
+    let mut compress = None;
+    let mut pointer = 0;
+    if at(0) == Some(':') {
+        if at(1) != Some(':') {
+            return None;
+        }
+        pointer += 2;
+        piece_index += 1;
+        compress = Some(piece_index);
+    }
+    while at(pointer).is_some() {
+        if piece_index == 8 {
+            return None;
+        }
+        if at(pointer) == Some(':') {
+            if compress.is_some() {
+                return None;
+            }
+            pointer += 1;
+            piece_index += 1;
+            compress = Some(piece_index);
+            continue;
+        }
+        let (mut value, mut length) = (0, 0);
+        while length < 4 {
+            match at(pointer).and_then(|c| c.to_digit(16)) {
+                Some(digit) => value = value * 0x10 + digit,
+                None => break,
+            }
+            pointer += 1;
+            length += 1;
+        }
+        if at(pointer) == Some('.') {
+            // The last 32 bits can be written as an IPv4 address
+            if length == 0 || piece_index > 6 {
+                return None;
+            }
+            pointer -= length;
+            let mut numbers_seen = 0;
+            while at(pointer).is_some() {
+                if numbers_seen > 0 {
+                    if at(pointer) == Some('.') && numbers_seen < 4 {
+                        pointer += 1;
+                    } else {
+                        return None;
+                    }
+                }
+                let mut ipv4_piece: Option<u32> = None;
+                while let Some(digit) = at(pointer).and_then(|c| c.to_digit(10)) {
+                    ipv4_piece = match ipv4_piece {
+                        None => Some(digit),
+                        Some(0) => return None,
+                        Some(piece) => Some(piece * 10 + digit),
+                    };
+                    if ipv4_piece > Some(255) {
+                        return None;
+                    }
+                    pointer += 1;
+                }
+                address[piece_index] = address[piece_index] * 0x100 + ipv4_piece? as u16;
+                numbers_seen += 1;
+                if numbers_seen == 2 || numbers_seen == 4 {
+                    piece_index += 1;
+                }
+            }
+            if numbers_seen != 4 {
+                return None;
+            }
+            break;
+        } else if at(pointer) == Some(':') {
+            pointer += 1;
+            at(pointer)?;
+        } else if at(pointer).is_some() {
+            return None;
+        }
+        address[piece_index] = value as u16;
+        piece_index += 1;
+    }
+    match compress {
+        Some(compress) => {
+            // Move the pieces after `::` to the end, leaving zeros in their place
+            let mut swaps = piece_index - compress;
+            piece_index = 7;
+            while piece_index != 0 && swaps > 0 {
+                address.swap(piece_index, compress + swaps - 1);
+                piece_index -= 1;
+                swaps -= 1;
+            }
+        }
+        None if piece_index != 8 => return None,
+        None => {}
+    }
+    Some(address)
+}
+
+/// Write an IPv6 address with its longest run of zero pieces left out, as `::`
+/// [[SPEC] - IPv6 serializer](https://url.spec.whatwg.org/#concept-ipv6-serializer)
+fn serialize_ipv6(address: &[u16; 8]) -> String {
+    let (mut compress, mut longest) = (None, 1);
+    let mut i = 0;
+    while i < 8 {
+        let start = i;
+        while i < 8 && address[i] == 0 {
+            i += 1;
+        }
+        if i - start > longest {
+            longest = i - start;
+            compress = Some(start);
+        }
+        i += 1;
+    }
+    let mut out = String::new();
+    let mut i = 0;
+    while i < 8 {
+        if compress == Some(i) {
+            out.push_str(if i == 0 { "::" } else { ":" });
+            i += longest;
+            continue;
+        }
+        out.push_str(&format!("{:x}", address[i]));
+        if i != 7 {
+            out.push(':');
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Encode a label of a domain with Punycode, the `xn--` form of labels which aren't ASCII
+/// [[SPEC] - Punycode](https://tools.ietf.org/html/rfc3492#section-6.3)
+fn punycode_encode(label: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+    fn adapt(delta: u32, points: u32, first: bool) -> u32 {
+        let mut delta = if first { delta / 700 } else { delta / 2 };
+        delta += delta / points;
+        let mut k = 0;
+        while delta > (BASE - T_MIN) * T_MAX / 2 {
+            delta /= BASE - T_MIN;
+            k += BASE;
+        }
+        k + (BASE - T_MIN + 1) * delta / (delta + 38)
+    }
+    fn digit(d: u32) -> char {
+        (if d < 26 {
+            b'a' + d as u8
+        } else {
+            b'0' + d as u8 - 26
+        }) as char
+    }
+    let code_points: Vec<u32> = label.chars().map(|c| c as u32).collect();
+    let mut out: String = label.chars().filter(char::is_ascii).collect();
+    let basic = out.len() as u32;
+    if basic > 0 {
+        out.push('-');
+    }
+    let (mut n, mut delta, mut bias, mut handled) = (0x80, 0u32, 72, basic);
+    while (handled as usize) < code_points.len() {
+        let m = code_points.iter().cloned().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+        for &c in &code_points {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = if k <= bias {
+                        T_MIN
+                    } else if k >= bias + T_MAX {
+                        T_MAX
+                    } else {
+                        k - bias
+                    };
+                    if q < t {
+                        break;
+                    }
+                    out.push(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                out.push(digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+    Some(out)
+}
+
+/// Turn a domain to ASCII, lowercasing it and encoding the labels which aren't ASCII with Punycode
+///
+/// This doesn't have the Unicode mapping tables, so it only lowercases and maps full stops.
+/// [[SPEC] - Domain to ASCII](https://url.spec.whatwg.org/#concept-domain-to-ascii)
+fn domain_to_ascii(domain: &str) -> Option<String> {
+    let domain: String = domain
+        .chars()
+        .map(|c| match c {
+            '\u{3002}' | '\u{FF0E}' | '\u{FF61}' => '.',
+            c => c,
+        })
+        .flat_map(char::to_lowercase)
+        .collect();
+    let labels = domain
+        .split('.')
+        .map(|label| {
+            if label.is_ascii() {
+                Some(label.to_string())
+            } else {
+                punycode_encode(label).map(|label| format!("xn--{}", label))
+            }
+        })
+        .collect::<Option<Vec<String>>>()?;
+    let ascii = labels.join(".");
+    if ascii.is_empty() || ascii.chars().any(is_forbidden_domain) {
+        None
+    } else {
+        Some(ascii)
+    }
+}
+
+/// Parse a host, which is opaque for URLs whose scheme isn't special
+/// [[SPEC] - Host parser](https://url.spec.whatwg.org/#concept-host-parser)
+fn parse_host(input: &str, opaque: bool) -> Option<Host> {
+    if input.starts_with('[') {
+        if input.len() < 2 || !input.ends_with(']') {
+            return None;
+        }
+        return parse_ipv6(&input[1..input.len() - 1]).map(Host::Ipv6);
+    }
+    if opaque {
+        if input.chars().any(|c| c != '%' && is_forbidden_host(c)) {
+            return None;
+        }
+        return Some(if input.is_empty() {
+            Host::Empty
+        } else {
+            Host::Opaque(percent_encode(input, c0_control_set))
+        });
+    }
+    let domain = String::from_utf8_lossy(&percent_decode(input.as_bytes())).into_owned();
+    let ascii = domain_to_ascii(&domain)?;
+    if ends_in_number(&ascii) {
+        parse_ipv4(&ascii).map(Host::Ipv4)
+    } else {
+        Some(Host::Domain(ascii))
+    }
+}
+
+/// Parse the digits a port starts with, or `None` if it doesn't start with any or they're too big
+fn parse_port_digits(input: &str) -> Option<u16> {
+    let end = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    if end == 0 {
+        return None;
+    }
+    let mut port: u32 = 0;
+    for digit in input[..end].bytes() {
+        port = port * 10 + (digit - b'0') as u32;
+        if port > 65535 {
+            return None;
+        }
+    }
+    Some(port as u16)
+}
+
+/// Parse the `name=value` pairs of a query, joined by `&`
+/// [[SPEC] - application/x-www-form-urlencoded parser](https://url.spec.whatwg.org/#concept-urlencoded-parser)
+fn parse_form(input: &str) -> Vec<(String, String)> {
+    let decode = |s: &str| {
+        let bytes: Vec<u8> = s
+            .bytes()
+            .map(|byte| if byte == b'+' { b' ' } else { byte })
+            .collect();
+        String::from_utf8_lossy(&percent_decode(&bytes)).into_owned()
+    };
+    input
+        .split('&')
+        .filter(|sequence| !sequence.is_empty())
+        .map(|sequence| match sequence.find('=') {
+            Some(eq) => (decode(&sequence[..eq]), decode(&sequence[eq + 1..])),
+            None => (decode(sequence), String::new()),
+        })
+        .collect()
+}
+
+/// Write a list of names and values as a query
+/// [[SPEC] - application/x-www-form-urlencoded serializer](https://url.spec.whatwg.org/#concept-urlencoded-serializer)
+fn serialize_form(list: &[(String, String)]) -> String {
+    let encode = |s: &str| {
+        s.split(' ')
+            .map(|part| percent_encode(part, form_set))
+            .collect::<Vec<String>>()
+            .join("+")
+    };
+    list.iter()
+        .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+impl Url {
+    /// Parse a URL, relative to a base URL if it's given, or `None` if it isn't valid
+    /// [[SPEC] - Basic URL parser](https://url.spec.whatwg.org/#concept-basic-url-parser)
+    pub fn parse(input: &str, base: Option<&Url>) -> Option<Url> {
+        let input = strip_tab_and_newline(input.trim_matches(|c: char| c <= ' '));
+        let input = input.as_str();
+        let mut url = Url {
+            scheme: String::new(),
+            username: String::new(),
+            password: String::new(),
+            host: None,
+            port: None,
+            path: UrlPath::Segments(Vec::new()),
+            query: None,
+            fragment: None,
+        };
+        if let Some((scheme, rest)) = split_scheme(input) {
+            url.scheme = scheme.to_ascii_lowercase();
+            if url.scheme == "file" {
+                let base = base.filter(|base| base.scheme == "file");
+                url.parse_file(rest, base)?;
+            } else if url.is_special() {
+                match base {
+                    Some(base) if base.scheme == url.scheme && !rest.starts_with("//") => {
+                        url.parse_relative(rest, base)?;
+                    }
+                    _ => url.parse_authority(rest.trim_start_matches(['/', '\\']))?,
+                }
+            } else if let Some(rest) = rest.strip_prefix("//") {
+                url.parse_authority(rest)?;
+            } else if let Some(rest) = rest.strip_prefix('/') {
+                url.parse_path(rest);
+            } else {
+                let end = rest.find(['?', '#']).unwrap_or(rest.len());
+                url.path = UrlPath::Opaque(percent_encode(&rest[..end], c0_control_set));
+                url.parse_query_and_fragment(&rest[end..]);
+            }
+            return Some(url);
+        }
+        let base = base?;
+        if let UrlPath::Opaque(_) = base.path {
+            if !input.starts_with('#') {
+                return None;
+            }
+            url = base.clone();
+            url.fragment = None;
+            url.parse_query_and_fragment(input);
+            return Some(url);
+        }
+        url.scheme = base.scheme.clone();
+        if url.scheme == "file" {
+            url.parse_file(input, Some(base))?;
+        } else {
+            url.parse_relative(input, base)?;
+        }
+        Some(url)
+    }
+
+    /// If the scheme is special
+    fn is_special(&self) -> bool {
+        is_special(&self.scheme)
+    }
+
+    /// Whether a character ends a path segment, which `\` does in URLs with special schemes
+    fn is_slash(&self, c: char) -> bool {
+        c == '/' || (c == '\\' && self.is_special())
+    }
+
+    /// The segments of the path, which an opaque path doesn't have
+    fn segments_mut(&mut self) -> &mut Vec<String> {
+        if let UrlPath::Opaque(_) = self.path {
+            self.path = UrlPath::Segments(Vec::new());
+        }
+        match self.path {
+            UrlPath::Segments(ref mut segments) => segments,
+            UrlPath::Opaque(_) => unreachable!(),
+        }
+    }
+
+    /// Whether the URL can't have credentials or a port
+    /// [[SPEC] - Cannot have a username/password/port](https://url.spec.whatwg.org/#cannot-have-a-username-password-port)
+    fn cannot_have_credentials(&self) -> bool {
+        match self.host {
+            None | Some(Host::Empty) => true,
+            _ => self.scheme == "file",
+        }
+    }
+
+    /// Parse a URL without a scheme, or with the scheme of its base, relative to its base
+    /// [[SPEC] - Relative state](https://url.spec.whatwg.org/#relative-state)
+    fn parse_relative(&mut self, input: &str, base: &Url) -> Option<()> {
+        if input.starts_with(|c| self.is_slash(c)) {
+            let after = &input[1..];
+            if self.is_special() && after.starts_with(['/', '\\']) {
+                return self.parse_authority(after.trim_start_matches(['/', '\\']));
+            }
+            if !self.is_special() && after.starts_with('/') {
+                return self.parse_authority(&after[1..]);
+            }
+            self.username = base.username.clone();
+            self.password = base.password.clone();
+            self.host = base.host.clone();
+            self.port = base.port;
+            self.parse_path(after);
+            return Some(());
+        }
+        self.username = base.username.clone();
+        self.password = base.password.clone();
+        self.host = base.host.clone();
+        self.port = base.port;
+        self.path = base.path.clone();
+        self.query = base.query.clone();
+        match input.chars().next() {
+            None => {}
+            Some('?') | Some('#') => self.parse_query_and_fragment(input),
+            Some(_) => {
+                self.query = None;
+                self.shorten_path();
+                self.parse_path(input);
+            }
+        }
+        Some(())
+    }
+
+    /// Parse the credentials, host and port of a URL and what comes after them
+    /// [[SPEC] - Authority state](https://url.spec.whatwg.org/#authority-state)
+    fn parse_authority(&mut self, input: &str) -> Option<()> {
+        let end = input
+            .find(|c| c == '?' || c == '#' || self.is_slash(c))
+            .unwrap_or(input.len());
+        let (authority, rest) = input.split_at(end);
+        let host = match authority.rfind('@') {
+            Some(at) => {
+                let userinfo = &authority[..at];
+                let (username, password) = match userinfo.find(':') {
+                    Some(colon) => (&userinfo[..colon], &userinfo[colon + 1..]),
+                    None => (userinfo, ""),
+                };
+                self.username = percent_encode(username, userinfo_set);
+                self.password = percent_encode(password, userinfo_set);
+                if at + 1 == authority.len() {
+                    return None;
+                }
+                &authority[at + 1..]
+            }
+            None => authority,
+        };
+        let (host, port) = split_host_and_port(host);
+        if host.is_empty() && (port.is_some() || self.is_special()) {
+            return None;
+        }
+        self.host = Some(parse_host(host, !self.is_special())?);
+        if let Some(port) = port {
+            if !port.is_empty() {
+                if !port.bytes().all(|byte| byte.is_ascii_digit()) {
+                    return None;
+                }
+                self.set_port_number(parse_port_digits(port)?);
+            }
+        }
+        self.parse_path_start(rest);
+        Some(())
+    }
+
+    /// Parse a URL with the `file` scheme, which can take its host and path from its base
+    /// [[SPEC] - File state](https://url.spec.whatwg.org/#file-state)
+    fn parse_file(&mut self, input: &str, base: Option<&Url>) -> Option<()> {
+        self.host = Some(Host::Empty);
+        let is_slash = |c| c == '/' || c == '\\';
+        if input.starts_with(is_slash) {
+            let after = &input[1..];
+            if after.starts_with(is_slash) {
+                let after = &after[1..];
+                let end = after
+                    .find(|c| is_slash(c) || c == '?' || c == '#')
+                    .unwrap_or(after.len());
+                let (host, rest) = after.split_at(end);
+                if is_windows_drive_letter(host) {
+                    // `file://C:/` has a drive letter rather than a host
+                    self.parse_path(after);
+                    return Some(());
+                }
+                if !host.is_empty() {
+                    self.host = match parse_host(host, false)? {
+                        Host::Domain(ref domain) if domain == "localhost" => Some(Host::Empty),
+                        host => Some(host),
+                    };
+                }
+                self.parse_path_start(rest);
+                return Some(());
+            }
+            if let Some(base) = base {
+                self.host = base.host.clone();
+                if let UrlPath::Segments(ref segments) = base.path {
+                    if !starts_with_windows_drive_letter(after)
+                        && segments
+                            .first()
+                            .is_some_and(|first| is_normalized_windows_drive_letter(first))
+                    {
+                        self.segments_mut().push(segments[0].clone());
+                    }
+                }
+            }
+            self.parse_path(after);
+            return Some(());
+        }
+        if let Some(base) = base {
+            self.host = base.host.clone();
+            self.path = base.path.clone();
+            self.query = base.query.clone();
+            match input.chars().next() {
+                None => {}
+                Some('?') | Some('#') => self.parse_query_and_fragment(input),
+                Some(_) => {
+                    self.query = None;
+                    if starts_with_windows_drive_letter(input) {
+                        self.path = UrlPath::Segments(Vec::new());
+                    } else {
+                        self.shorten_path();
+                    }
+                    self.parse_path(input);
+                }
+            }
+            return Some(());
+        }
+        self.parse_path(input);
+        Some(())
+    }
+
+    /// Parse the path after the host, with the `/` it starts with, and what comes after it
+    /// [[SPEC] - Path start state](https://url.spec.whatwg.org/#path-start-state)
+    fn parse_path_start(&mut self, input: &str) {
+        if self.is_special() {
+            if input.starts_with(|c| self.is_slash(c)) {
+                self.parse_path(&input[1..]);
+            } else {
+                self.parse_path(input);
+            }
+        } else if input.starts_with('?') || input.starts_with('#') {
+            self.parse_query_and_fragment(input);
+        } else if let Some(input) = input.strip_prefix('/') {
+            self.parse_path(input);
+        } else if !input.is_empty() {
+            self.parse_path(input);
+        }
+    }
+
+    /// Parse the path, after the `/` it starts with, and the query and fragment after it
+    /// [[SPEC] - Path state](https://url.spec.whatwg.org/#path-state)
+    fn parse_path(&mut self, input: &str) {
+        let end = input.find(['?', '#']).unwrap_or(input.len());
+        self.push_path(&input[..end]);
+        self.parse_query_and_fragment(&input[end..]);
+    }
+
+    /// Add the segments of a path to the path, resolving `.` and `..`
+    fn push_path(&mut self, path: &str) {
+        let special = self.is_special();
+        let segments: Vec<&str> = path.split(|c| c == '/' || (c == '\\' && special)).collect();
+        let last = segments.len() - 1;
+        for (i, segment) in segments.into_iter().enumerate() {
+            // A `.` or `..` at the end leaves the path ending in a slash
+            let at_end = i == last;
+            if is_double_dot(segment) {
+                self.shorten_path();
+                if at_end {
+                    self.segments_mut().push(String::new());
+                }
+            } else if is_single_dot(segment) {
+                if at_end {
+                    self.segments_mut().push(String::new());
+                }
+            } else {
+                let mut segment = percent_encode(segment, path_set);
+                if self.scheme == "file"
+                    && self.segments_mut().is_empty()
+                    && is_windows_drive_letter(&segment)
+                {
+                    segment.replace_range(1..2, ":");
+                }
+                self.segments_mut().push(segment);
+            }
+        }
+    }
+
+    /// Remove the last segment of the path, unless it's the drive letter of a `file` URL
+    /// [[SPEC] - Shorten a URL's path](https://url.spec.whatwg.org/#shorten-a-urls-path)
+    fn shorten_path(&mut self) {
+        let file = self.scheme == "file";
+        let segments = self.segments_mut();
+        if file && segments.len() == 1 && is_normalized_windows_drive_letter(&segments[0]) {
+            return;
+        }
+        segments.pop();
+    }
+
+    /// Parse a query after a `?` and a fragment after a `#`, whichever the input starts with
+    /// [[SPEC] - Query state](https://url.spec.whatwg.org/#query-state)
+    fn parse_query_and_fragment(&mut self, input: &str) {
+        let (query, fragment) = match input.find('#') {
+            Some(hash) => (&input[..hash], Some(&input[hash + 1..])),
+            None => (input, None),
+        };
+        if let Some(query) = query.strip_prefix('?') {
+            self.set_query_text(query);
+        }
+        if let Some(fragment) = fragment {
+            self.fragment = Some(percent_encode(fragment, fragment_set));
+        }
+    }
+
+    /// Set the query to some text, percent-encoding what it needs to
+    fn set_query_text(&mut self, query: &str) {
+        let set = if self.is_special() {
+            special_query_set
+        } else {
+            query_set
+        };
+        self.query = Some(percent_encode(query, set));
+    }
+
+    /// Set the port, leaving it out if it's the default port of the scheme
+    fn set_port_number(&mut self, port: u16) {
+        self.port = if default_port(&self.scheme) == Some(port) {
+            None
+        } else {
+            Some(port)
+        };
+    }
+
+    /// Leave out the spaces an opaque path ends in, which would be lost when it's parsed again
+    /// without a query or fragment after it
+    /// [[SPEC] - Potentially strip trailing spaces from an opaque path](https://url.spec.whatwg.org/#potentially-strip-trailing-spaces-from-an-opaque-path)
+    fn strip_trailing_spaces(&mut self) {
+        if self.fragment.is_some() || self.query.is_some() {
+            return;
+        }
+        if let UrlPath::Opaque(ref mut path) = self.path {
+            let len = path.trim_end_matches(' ').len();
+            path.truncate(len);
+        }
+    }
+
+    /// Write the URL as a string
+    /// [[SPEC] - URL serializer](https://url.spec.whatwg.org/#concept-url-serializer)
+    pub fn href(&self) -> String {
+        let mut out = format!("{}:", self.scheme);
+        match self.host {
+            Some(ref host) => {
+                out.push_str("//");
+                if !self.username.is_empty() || !self.password.is_empty() {
+                    out.push_str(&self.username);
+                    if !self.password.is_empty() {
+                        out.push(':');
+                        out.push_str(&self.password);
+                    }
+                    out.push('@');
+                }
+                out.push_str(&host.to_string());
+                if let Some(port) = self.port {
+                    out.push_str(&format!(":{}", port));
+                }
+            }
+            None => {
+                // A path starting with an empty segment would be read back as a host
+                if let UrlPath::Segments(ref segments) = self.path {
+                    if segments.len() > 1 && segments[0].is_empty() {
+                        out.push_str("/.");
+                    }
+                }
+            }
+        }
+        out.push_str(&self.pathname());
+        if let Some(ref query) = self.query {
+            out.push('?');
+            out.push_str(query);
+        }
+        if let Some(ref fragment) = self.fragment {
+            out.push('#');
+            out.push_str(fragment);
+        }
+        out
+    }
+
+    /// Write the path of the URL
+    /// [[SPEC] - URL path serializer](https://url.spec.whatwg.org/#url-path-serializer)
+    fn pathname(&self) -> String {
+        match self.path {
+            UrlPath::Opaque(ref path) => path.clone(),
+            UrlPath::Segments(ref segments) => segments.iter().map(|s| format!("/{}", s)).collect(),
+        }
+    }
+
+    /// The scheme, host and port of the URL, or `"null"` if it doesn't have an origin which can be written
+    /// [[SPEC] - Origin](https://url.spec.whatwg.org/#concept-url-origin)
+    fn origin(&self) -> String {
+        match self.scheme.as_str() {
+            "blob" => match Url::parse(&self.pathname(), None) {
+                Some(ref url) if url.scheme == "http" || url.scheme == "https" => url.origin(),
+                _ => "null".to_string(),
+            },
+            "ftp" | "http" | "https" | "ws" | "wss" => {
+                let host = self.host.as_ref().map_or(String::new(), Host::to_string);
+                match self.port {
+                    Some(port) => format!("{}://{}:{}", self.scheme, host, port),
+                    None => format!("{}://{}", self.scheme, host),
+                }
+            }
+            _ => "null".to_string(),
+        }
+    }
+
+    /// The part of the URL a getter of `URL.prototype` returns
+    fn component(&self, name: &str) -> String {
+        let prefixed = |prefix: &str, part: &Option<String>| match *part {
+            Some(ref part) if !part.is_empty() => format!("{}{}", prefix, part),
+            _ => String::new(),
+        };
+        match name {
+            "href" => self.href(),
+            "origin" => self.origin(),
+            "protocol" => format!("{}:", self.scheme),
+            "username" => self.username.clone(),
+            "password" => self.password.clone(),
+            "host" => match (&self.host, self.port) {
+                (Some(host), Some(port)) => format!("{}:{}", host, port),
+                (Some(host), None) => host.to_string(),
+                (None, _) => String::new(),
+            },
+            "hostname" => self.host.as_ref().map_or(String::new(), Host::to_string),
+            "port" => self.port.map_or(String::new(), |port| port.to_string()),
+            "pathname" => self.pathname(),
+            "search" => prefixed("?", &self.query),
+            "hash" => prefixed("#", &self.fragment),
+            _ => String::new(),
+        }
+    }
+
+    /// Change the scheme, unless that would change whether it's special
+    /// [[SPEC] - protocol setter](https://url.spec.whatwg.org/#dom-url-protocol)
+    fn set_protocol(&mut self, value: &str) {
+        let value = format!("{}:", strip_tab_and_newline(value));
+        let scheme = match split_scheme(&value) {
+            Some((scheme, _)) => scheme.to_ascii_lowercase(),
+            None => return,
+        };
+        let has_credentials = !self.username.is_empty() || !self.password.is_empty();
+        if is_special(&scheme) != self.is_special()
+            || (scheme == "file" && (has_credentials || self.port.is_some()))
+            || (self.scheme == "file" && self.host == Some(Host::Empty))
+        {
+            return;
+        }
+        self.scheme = scheme;
+        if self.port.is_some() && self.port == default_port(&self.scheme) {
+            self.port = None;
+        }
+    }
+
+    /// Change the host, and the port after it with `with_port`, leaving the URL as it was if the value isn't valid
+    /// [[SPEC] - Host state](https://url.spec.whatwg.org/#host-state)
+    fn set_host(&mut self, value: &str, with_port: bool) {
+        if let UrlPath::Opaque(_) = self.path {
+            return;
+        }
+        let value = strip_tab_and_newline(value);
+        let end = value
+            .find(|c| c == '?' || c == '#' || self.is_slash(c))
+            .unwrap_or(value.len());
+        let value = &value[..end];
+        if self.scheme == "file" {
+            self.host = match parse_host(value, false) {
+                _ if value.is_empty() => Some(Host::Empty),
+                Some(Host::Domain(ref domain)) if domain == "localhost" => Some(Host::Empty),
+                Some(host) => Some(host),
+                None => return,
+            };
+            return;
+        }
+        let (host, port) = split_host_and_port(value);
+        if port.is_some() && (host.is_empty() || !with_port) {
+            return;
+        }
+        let has_credentials = !self.username.is_empty() || !self.password.is_empty();
+        if host.is_empty() && (self.is_special() || has_credentials || self.port.is_some()) {
+            return;
+        }
+        match parse_host(host, !self.is_special()) {
+            Some(host) => self.host = Some(host),
+            None => return,
+        }
+        if let Some(port) = port.and_then(parse_port_digits) {
+            self.set_port_number(port);
+        }
+    }
+
+    /// Change the port to the digits the value starts with, or leave it out if the value is empty
+    /// [[SPEC] - port setter](https://url.spec.whatwg.org/#dom-url-port)
+    fn set_port(&mut self, value: &str) {
+        if self.cannot_have_credentials() {
+            return;
+        }
+        let value = strip_tab_and_newline(value);
+        if value.is_empty() {
+            self.port = None;
+        } else if let Some(port) = parse_port_digits(&value) {
+            self.set_port_number(port);
+        }
+    }
+
+    /// Replace the path, unless it's opaque
+    /// [[SPEC] - pathname setter](https://url.spec.whatwg.org/#dom-url-pathname)
+    fn set_pathname(&mut self, value: &str) {
+        if let UrlPath::Opaque(_) = self.path {
+            return;
+        }
+        self.path = UrlPath::Segments(Vec::new());
+        let value = strip_tab_and_newline(value);
+        if value.starts_with(|c| self.is_slash(c)) {
+            self.push_path(&value[1..]);
+        } else if !value.is_empty() || self.is_special() {
+            self.push_path(&value);
+        } else if self.host.is_none() {
+            self.segments_mut().push(String::new());
+        }
+    }
+
+    /// Replace the query, with or without the `?` it starts with, or leave it out if the value is empty
+    /// [[SPEC] - search setter](https://url.spec.whatwg.org/#dom-url-search)
+    fn set_search(&mut self, value: &str) {
+        let value = strip_tab_and_newline(value);
+        if value.is_empty() {
+            self.query = None;
+            self.strip_trailing_spaces();
+        } else {
+            let query = value.strip_prefix('?').unwrap_or(&value);
+            self.set_query_text(query);
+        }
+    }
+
+    /// Replace the fragment, with or without the `#` it starts with, or leave it out if the value is empty
+    /// [[SPEC] - hash setter](https://url.spec.whatwg.org/#dom-url-hash)
+    fn set_hash(&mut self, value: &str) {
+        let value = strip_tab_and_newline(value);
+        if value.is_empty() {
+            self.fragment = None;
+            self.strip_trailing_spaces();
+        } else {
+            let fragment = value.strip_prefix('#').unwrap_or(&value);
+            self.fragment = Some(percent_encode(fragment, fragment_set));
+        }
+    }
+}
+
+/// Split a host from the port after it, at a `:` which isn't in the brackets of an IPv6 address
+fn split_host_and_port(input: &str) -> (&str, Option<&str>) {
+    let mut in_brackets = false;
+    for (i, c) in input.char_indices() {
+        match c {
+            '[' => in_brackets = true,
+            ']' => in_brackets = false,
+            ':' if !in_brackets => return (&input[..i], Some(&input[i + 1..])),
+            _ => {}
+        }
+    }
+    (input, None)
+}
+
+/// The error thrown for a string which isn't a valid URL
+fn invalid_url(caller: &dyn Caller, input: &str) -> Value {
+    let error = caller.new_error("TypeError", format!("Invalid URL: {}", input));
+    error.set_field_slice("code", to_value("ERR_INVALID_URL"));
+    error
+}
+
+/// An argument as a string, which is `"undefined"` if it's missing
+fn string_arg(args: &[Value], index: usize) -> String {
+    args.get(index)
+        .map_or("undefined".to_string(), |arg| arg.to_string())
+}
+
+/// Parse the URL and optional base a `URL` function is given, or throw if either isn't valid
+fn parse_url_args(args: &[Value], caller: &dyn Caller) -> Result<Url, Value> {
+    let input = string_arg(args, 0);
+    let base = match args.get(1) {
+        Some(base) if !base.is_undefined() => {
+            let base = base.to_string();
+            Some(Url::parse(&base, None).ok_or_else(|| invalid_url(caller, &base))?)
+        }
+        _ => None,
+    };
+    Url::parse(&input, base.as_ref()).ok_or_else(|| invalid_url(caller, &input))
+}
+
+/// Give an object the internal slots of a `URL`, along with its `URLSearchParams`
+fn init_url(this: &Value, url: &Url, search_params_proto: Value) {
+    let record = ValueData::new_obj(None);
+    record.set_internal_slot(HREF, to_value(url.href()));
+    let params = ValueData::new_obj_from_prototype(search_params_proto);
+    params.set_internal_slot(URL_RECORD, record.clone());
+    params.set_internal_slot(PARAMS_LIST, to_value(""));
+    this.set_internal_slot(URL_RECORD, record);
+    this.set_internal_slot(URL_SEARCH_PARAMS, params);
+}
+
+/// The URL the object a `URL` and its `URLSearchParams` share stands for
+fn read_record(record: &Value) -> Url {
+    Url::parse(&record.get_internal_slot(HREF).to_string(), None).expect("a URL stays valid")
+}
+
+/// Get the object the `URL` `this` shares with its `URLSearchParams`, or throw if it isn't a `URL`
+fn this_url_record(this: &Value, caller: &dyn Caller) -> Result<Value, Value> {
+    if let ValueData::Object(ref obj) = *this.data() {
+        let obj = obj.borrow();
+        if let (Some(record), Some(_)) = (
+            obj.get_internal_slot(URL_RECORD),
+            obj.get_internal_slot(URL_SEARCH_PARAMS),
+        ) {
+            return Ok(record.clone());
+        }
+    }
+    Err(caller.new_error("TypeError", "this is not a URL".to_string()))
+}
+
+/// Get the URL the `URL` `this` stands for, or throw if it isn't one
+fn this_url(this: &Value, caller: &dyn Caller) -> Result<Url, Value> {
+    Ok(read_record(&this_url_record(this, caller)?))
+}
+
+/// Make an array of values, given `Array.prototype`
+fn new_array(array_proto: Value, items: Vec<Value>) -> Value {
+    let array = array_create(array_proto);
+    let len = items.len();
+    for (i, item) in items.into_iter().enumerate() {
+        array.set_field(i.to_string(), item);
+    }
+    array.set_field_slice("length", to_value(len as i32));
+    array
+}
+
+/// Create a new `URL`, parsing a URL relative to an optional base URL
+/// [[SPEC] - URL](https://url.spec.whatwg.org/#dom-url-url)
+pub fn make_url(
+    this: Value,
+    callee: Value,
+    args: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    if !is_construct(&this, &callee) {
+        let message = "Constructor URL requires 'new'".to_string();
+        return Err(caller.new_error("TypeError", message));
+    }
+    let url = parse_url_args(&args, caller)?;
+    init_url(
+        &this,
+        &url,
+        callee.get_internal_slot(SEARCH_PARAMS_PROTOTYPE),
+    );
+    Ok(this)
+}
+/// Parse a URL relative to an optional base URL, returning `null` rather than throwing if it isn't valid
+/// [[SPEC] - URL.parse](https://url.spec.whatwg.org/#dom-url-parse)
+pub fn parse(_: Value, callee: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    match parse_url_args(&args, caller) {
+        Ok(url) => {
+            let this = ValueData::new_obj_from_prototype(callee.get_internal_slot(URL_PROTOTYPE));
+            init_url(
+                &this,
+                &url,
+                callee.get_internal_slot(SEARCH_PARAMS_PROTOTYPE),
+            );
+            Ok(this)
+        }
+        Err(_) => Ok(Value::null()),
+    }
+}
+/// Whether a URL relative to an optional base URL is valid
+/// [[SPEC] - URL.canParse](https://url.spec.whatwg.org/#dom-url-canparse)
+pub fn can_parse(_: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(parse_url_args(&args, caller).is_ok()))
+}
+/// Get the part of a URL the getter is for
+pub fn get_component(
+    this: Value,
+    callee: Value,
+    _: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    let url = this_url(&this, caller)?;
+    let name = callee.get_internal_slot(COMPONENT).to_string();
+    Ok(to_value(url.component(&name)))
+}
+/// Set the part of a URL the setter is for, which leaves the URL as it was if the value isn't
+/// valid for it, apart from `href`, which throws
+pub fn set_component(
+    this: Value,
+    callee: Value,
+    args: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    let record = this_url_record(&this, caller)?;
+    let mut url = read_record(&record);
+    let value = string_arg(&args, 0);
+    match callee.get_internal_slot(COMPONENT).to_string().as_str() {
+        "href" => url = Url::parse(&value, None).ok_or_else(|| invalid_url(caller, &value))?,
+        "protocol" => url.set_protocol(&value),
+        "username" if !url.cannot_have_credentials() => {
+            url.username = percent_encode(&value, userinfo_set)
+        }
+        "password" if !url.cannot_have_credentials() => {
+            url.password = percent_encode(&value, userinfo_set)
+        }
+        "host" => url.set_host(&value, true),
+        "hostname" => url.set_host(&value, false),
+        "port" => url.set_port(&value),
+        "pathname" => url.set_pathname(&value),
+        "search" => url.set_search(&value),
+        "hash" => url.set_hash(&value),
+        _ => {}
+    }
+    record.set_internal_slot(HREF, to_value(url.href()));
+    Ok(Value::undefined())
+}
+/// Get the `URLSearchParams` of a URL, which stands for its query
+pub fn get_search_params(
+    this: Value,
+    _: Value,
+    _: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    this_url(&this, caller)?;
+    Ok(this.get_internal_slot(URL_SEARCH_PARAMS))
+}
+/// Write a URL as a string, as `href` does
+pub fn to_string(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(this_url(&this, caller)?.href()))
+}
+
+/// The names and values of the `URLSearchParams` `this` is, or throw if it isn't one
+fn this_params(this: &Value, caller: &dyn Caller) -> Result<Vec<(String, String)>, Value> {
+    if let ValueData::Object(ref obj) = *this.data() {
+        if let Some(record) = obj.borrow().get_internal_slot(URL_RECORD) {
+            let query = if record.is_object() {
+                read_record(record).query.unwrap_or_default()
+            } else {
+                this.get_internal_slot(PARAMS_LIST).to_string()
+            };
+            return Ok(parse_form(&query));
+        }
+    }
+    Err(caller.new_error("TypeError", "this is not a URLSearchParams".to_string()))
+}
+
+/// Replace the names and values of a `URLSearchParams`, which changes the query of its `URL` if it has one
+/// [[SPEC] - Update](https://url.spec.whatwg.org/#concept-urlsearchparams-update)
+fn update_params(this: &Value, list: &[(String, String)]) {
+    let query = serialize_form(list);
+    let record = this.get_internal_slot(URL_RECORD);
+    if record.is_object() {
+        let mut url = read_record(&record);
+        if query.is_empty() {
+            url.query = None;
+            url.strip_trailing_spaces();
+        } else {
+            url.query = Some(query);
+        }
+        record.set_internal_slot(HREF, to_value(url.href()));
+    } else {
+        this.set_internal_slot(PARAMS_LIST, to_value(query));
+    }
+}
+
+/// Create a new `URLSearchParams` from a query string, an array of name and value pairs, an
+/// object whose properties are the names and values, or another `URLSearchParams`
+/// [[SPEC] - URLSearchParams](https://url.spec.whatwg.org/#dom-urlsearchparams-urlsearchparams)
+pub fn make_search_params(
+    this: Value,
+    callee: Value,
+    args: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    if !is_construct(&this, &callee) {
+        let message = "Constructor URLSearchParams requires 'new'".to_string();
+        return Err(caller.new_error("TypeError", message));
+    }
+    let init = args.first().cloned().unwrap_or_else(Value::undefined);
+    let list = match *init.data() {
+        ValueData::Undefined => Vec::new(),
+        ValueData::Object(ref obj) => {
+            let is_array = obj.borrow().has_internal_slot(ARRAY_DATA);
+            if obj.borrow().get_internal_slot(URL_RECORD).is_some() {
+                this_params(&init, caller)?
+            } else if is_array {
+                let len = init.get_field_slice("length").to_num() as usize;
+                let mut list = Vec::with_capacity(len);
+                for i in 0..len {
+                    let pair = init.get_field(i.to_string());
+                    if !pair.is_object() || pair.get_field_slice("length").to_num() != 2.0 {
+                        let message =
+                            "Each query pair must be an array of a name and a value".to_string();
+                        return Err(caller.new_error("TypeError", message));
+                    }
+                    list.push((
+                        pair.get_field_slice("0").to_string(),
+                        pair.get_field_slice("1").to_string(),
+                    ));
+                }
+                list
+            } else {
+                let keys = own_property_keys(&obj.borrow());
+                keys.into_iter()
+                    .map(|key| {
+                        let value = init.get_field(key.clone()).to_string();
+                        (key, value)
+                    })
+                    .collect()
+            }
+        }
+        _ => {
+            let query = init.to_string();
+            let query = query.strip_prefix('?').unwrap_or(&query);
+            parse_form(query)
+        }
+    };
+    this.set_internal_slot(URL_RECORD, Value::undefined());
+    this.set_internal_slot(PARAMS_LIST, to_value(serialize_form(&list)));
+    Ok(this)
+}
+/// Add a name and value to the end
+pub fn append(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let mut list = this_params(&this, caller)?;
+    list.push((string_arg(&args, 0), string_arg(&args, 1)));
+    update_params(&this, &list);
+    Ok(Value::undefined())
+}
+/// Remove the pairs with a name, and if it's given, a value
+pub fn delete(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let mut list = this_params(&this, caller)?;
+    let name = string_arg(&args, 0);
+    let value = args
+        .get(1)
+        .filter(|value| !value.is_undefined())
+        .map(|value| value.to_string());
+    list.retain(|pair| pair.0 != name || value.as_ref().is_some_and(|value| pair.1 != *value));
+    update_params(&this, &list);
+    Ok(Value::undefined())
+}
+/// Get the first value with a name, or `null` if there isn't one
+pub fn get(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let name = string_arg(&args, 0);
+    Ok(this_params(&this, caller)?
+        .into_iter()
+        .find(|pair| pair.0 == name)
+        .map_or_else(Value::null, |pair| to_value(pair.1)))
+}
+/// Get an array of the values with a name
+pub fn get_all(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let name = string_arg(&args, 0);
+    let values = this_params(&this, caller)?
+        .into_iter()
+        .filter(|pair| pair.0 == name)
+        .map(|pair| to_value(pair.1))
+        .collect();
+    Ok(new_array(caller.prototype("Array"), values))
+}
+/// Whether there's a pair with a name, and if it's given, a value
+pub fn has(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let name = string_arg(&args, 0);
+    let value = args
+        .get(1)
+        .filter(|value| !value.is_undefined())
+        .map(|value| value.to_string());
+    Ok(to_value(this_params(&this, caller)?.iter().any(|pair| {
+        pair.0 == name && value.as_ref().is_none_or(|value| pair.1 == *value)
+    })))
+}
+/// Set the value of the first pair with a name, removing the others, or add a pair if there isn't one
+pub fn set(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let mut list = this_params(&this, caller)?;
+    let (name, value) = (string_arg(&args, 0), string_arg(&args, 1));
+    match list.iter().position(|pair| pair.0 == name) {
+        Some(first) => {
+            list[first].1 = value;
+            let mut i = 0;
+            list.retain(|pair| {
+                i += 1;
+                i - 1 <= first || pair.0 != name
+            });
+        }
+        None => list.push((name, value)),
+    }
+    update_params(&this, &list);
+    Ok(Value::undefined())
+}
+/// Sort the pairs by their names, comparing their UTF-16 code units, keeping pairs with the same name in order
+pub fn sort(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let mut list = this_params(&this, caller)?;
+    list.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+    update_params(&this, &list);
+    Ok(Value::undefined())
+}
+/// Call a function with each value, name and the `URLSearchParams`, seeing changes it makes as it goes
+pub fn for_each(this: Value, _: Value, args: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    let callback = args.first().cloned().unwrap_or_else(Value::undefined);
+    if !callback.is_function() {
+        let message = "The callback of forEach must be a function".to_string();
+        return Err(caller.new_error("TypeError", message));
+    }
+    let this_arg = args.get(1).cloned().unwrap_or_else(Value::undefined);
+    let mut i = 0;
+    loop {
+        let (name, value) = match this_params(&this, caller)?.into_iter().nth(i) {
+            Some(pair) => pair,
+            None => return Ok(Value::undefined()),
+        };
+        let args = vec![to_value(value), to_value(name), this.clone()];
+        caller.call(&callback, this_arg.clone(), args)?;
+        i += 1;
+    }
+}
+/// Get an array of the names, the values or `[name, value]` pairs, whichever the method is for
+///
+/// These are arrays rather than iterators, as there's no iteration protocol to follow yet.
+pub fn list_entries(
+    this: Value,
+    callee: Value,
+    _: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    let array_proto = caller.prototype("Array");
+    let kind = callee.get_internal_slot(COMPONENT).to_string();
+    let items = this_params(&this, caller)?
+        .into_iter()
+        .map(|(name, value)| match kind.as_str() {
+            "keys" => to_value(name),
+            "values" => to_value(value),
+            _ => new_array(array_proto.clone(), vec![to_value(name), to_value(value)]),
+        })
+        .collect();
+    Ok(new_array(array_proto, items))
+}
+/// Get the number of pairs
+pub fn get_size(this: Value, _: Value, _: Vec<Value>, caller: &mut dyn Caller) -> ResultValue {
+    Ok(to_value(this_params(&this, caller)?.len() as i32))
+}
+/// Write the pairs as a query, without the `?` before it
+pub fn params_to_string(
+    this: Value,
+    _: Value,
+    _: Vec<Value>,
+    caller: &mut dyn Caller,
+) -> ResultValue {
+    Ok(to_value(serialize_form(&this_params(&this, caller)?)))
+}
+
+/// Create a new `URLSearchParams` object
+pub fn _create_search_params(global: &Value) -> Value {
+    let search_params = to_value(make_search_params as NativeFunctionData);
+    let proto = ValueData::new_obj(Some(global.clone()));
+    proto.set_field_slice("append", to_value(append as NativeFunctionData));
+    proto.set_field_slice("delete", to_value(delete as NativeFunctionData));
+    proto.set_field_slice("get", to_value(get as NativeFunctionData));
+    proto.set_field_slice("getAll", to_value(get_all as NativeFunctionData));
+    proto.set_field_slice("has", to_value(has as NativeFunctionData));
+    proto.set_field_slice("set", to_value(set as NativeFunctionData));
+    proto.set_field_slice("sort", to_value(sort as NativeFunctionData));
+    proto.set_field_slice("forEach", to_value(for_each as NativeFunctionData));
+    for name in &["keys", "values", "entries"] {
+        let method = to_value(list_entries as NativeFunctionData);
+        method.set_internal_slot(COMPONENT, to_value(*name));
+        proto.set_field_slice(name, method);
+    }
+    proto.set_field_slice("toString", to_value(params_to_string as NativeFunctionData));
+    proto.set_prop_slice(
+        "size",
+        Property::accessor(to_value(get_size as NativeFunctionData), Value::undefined()),
+    );
+    search_params.set_field_slice(PROTOTYPE, proto);
+    search_params
+}
+/// Create a new `URL` object, given the `URLSearchParams` object
+pub fn _create_url(global: &Value, search_params: &Value) -> Value {
+    let url = to_value(make_url as NativeFunctionData);
+    let search_params_proto = search_params.get_field_slice(PROTOTYPE);
+    url.set_internal_slot(SEARCH_PARAMS_PROTOTYPE, search_params_proto.clone());
+    let proto = ValueData::new_obj(Some(global.clone()));
+    let components = [
+        "href", "origin", "protocol", "username", "password", "host", "hostname", "port",
+        "pathname", "search", "hash",
+    ];
+    for name in components.iter() {
+        let get = to_value(get_component as NativeFunctionData);
+        get.set_internal_slot(COMPONENT, to_value(*name));
+        let set = if *name == "origin" {
+            Value::undefined()
+        } else {
+            let set = to_value(set_component as NativeFunctionData);
+            set.set_internal_slot(COMPONENT, to_value(*name));
+            set
+        };
+        proto.set_prop_slice(name, Property::accessor(get, set));
+    }
+    proto.set_prop_slice(
+        "searchParams",
+        Property::accessor(
+            to_value(get_search_params as NativeFunctionData),
+            Value::undefined(),
+        ),
+    );
+    proto.set_field_slice("toString", to_value(to_string as NativeFunctionData));
+    proto.set_field_slice("toJSON", to_value(to_string as NativeFunctionData));
+    let parse = to_value(parse as NativeFunctionData);
+    parse.set_internal_slot(URL_PROTOTYPE, proto.clone());
+    parse.set_internal_slot(SEARCH_PARAMS_PROTOTYPE, search_params_proto);
+    url.set_field_slice("parse", parse);
+    url.set_field_slice("canParse", to_value(can_parse as NativeFunctionData));
+    url.set_field_slice(PROTOTYPE, proto);
+    url
+}
+/// Initialise the global object with `URL` and `URLSearchParams`
+pub fn init(global: Value) {
+    let search_params = _create_search_params(&global);
+    global.set_field_slice("URL", _create_url(&global, &search_params));
+    global.set_field_slice("URLSearchParams", search_params);
+}
@@ -1,27 +1,464 @@
-use gc::{Gc, GcCell};
-use crate::js::function::{Function, NativeFunction, NativeFunctionData};
-use crate::js::object::{ObjectData, Property, INSTANCE_PROTOTYPE, PROTOTYPE};
+use crate::gc::{Gc, GcCell, Trace, Tracer};
+use crate::js::array_buffer::SharedBytes;
+use crate::js::atom::{self, Atom, AtomMarks, ToAtom};
+use crate::js::class::HostData;
+use crate::js::bigint::{bigint_to_number, compare_with_number, string_to_bigint, MAX_BIGINT_BITS};
+use crate::js::error::{error_to_string, ERROR_DATA};
+use crate::js::js_string::JsString;
+use crate::js::function::{
+    get_mapped_argument, set_mapped_argument, Caller, Function, NativeBody, NativeFunction,
+    NativeFunctionData,
+};
+use crate::js::number::{number_to_string, string_to_number, to_int32, to_uint32};
+use crate::js::object::{ObjectData, Property, PROTOTYPE};
+use crate::js::typed_array::{get_buffer_field, set_buffer_field};
+use num_bigint::BigInt;
+use num_traits::{Signed, ToPrimitive, Zero};
 use serde_json::map::Map;
 use serde_json::Number as JSONNumber;
 use serde_json::Value as JSONValue;
-use std::collections::HashMap;
-use std::f64::NAN;
+use std::cell::Cell;
+use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Display;
 use std::iter::FromIterator;
+use std::mem;
+use std::mem::ManuallyDrop;
 use std::ops::Deref;
 use std::ops::DerefMut;
-use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Not, Rem, Shl, Shr, Sub};
-use std::str::FromStr;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub};
+use std::ptr;
 
-#[must_use]
 /// The result of a Javascript expression is represented like this so it can succeed (`Ok`) or fail (`Err`)
 pub type ResultValue = Result<Value, Value>;
-/// A Garbage-collected Javascript value as represented in the interpreter
-pub type Value = Gc<ValueData>;
+
+/// An error of one of the native kinds, like `TypeError`, made where the interpreter that throws it
+/// isn't at hand, such as in the operators
+#[derive(Clone, Debug, PartialEq)]
+pub struct NativeError {
+    /// The kind of native error, like `TypeError`
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl NativeError {
+    /// A `TypeError` with a message
+    pub fn type_error(message: &str) -> NativeError {
+        NativeError {
+            kind: "TypeError",
+            message: message.to_string(),
+        }
+    }
+
+    /// A `RangeError` with a message
+    pub fn range_error(message: &str) -> NativeError {
+        NativeError {
+            kind: "RangeError",
+            message: message.to_string(),
+        }
+    }
+
+    /// Make the error object to throw, with the interpreter it's thrown in
+    pub fn into_error(self, caller: &dyn Caller) -> Value {
+        caller.new_error(self.kind, self.message)
+    }
+}
+/// A Javascript value, packed into 64 bits
+///
+/// Numbers are stored as themselves, with every `NaN` stored as the same one. The other `NaN`s are
+/// free to hold `undefined`, `null` and booleans in their payload, and, for everything else, a
+/// pointer to a garbage-collected `ValueData`. Only those values live on the heap, so making a
+/// number or a boolean doesn't allocate.
+#[repr(transparent)]
+pub struct Value(Cell<u64>);
+
+/// The bits of a value which say what it holds, if it isn't a number
+const TAG_MASK: u64 = 0xFFFF_0000_0000_0000;
+/// The bits of a value which hold its boolean or its pointer
+const PAYLOAD_MASK: u64 = 0x0000_FFFF_FFFF_FFFF;
+/// The `NaN` all `NaN`s are stored as, which no tag below can be mistaken for
+pub(crate) const CANONICAL_NAN: u64 = 0x7FF8_0000_0000_0000;
+/// The tag of `undefined`, which is the lowest tag, so every number is below it
+pub(crate) const TAG_UNDEFINED: u64 = 0xFFF9_0000_0000_0000;
+/// The tag of `null`
+pub(crate) const TAG_NULL: u64 = 0xFFFA_0000_0000_0000;
+/// The tag of a boolean, which is its lowest bit
+pub(crate) const TAG_BOOLEAN: u64 = 0xFFFB_0000_0000_0000;
+/// The tag of a pointer to a garbage-collected `ValueData`
+pub(crate) const TAG_HEAP: u64 = 0xFFFC_0000_0000_0000;
+
+impl Value {
+    /// Make a value out of its data, putting it on the heap unless it fits in the value itself
+    pub fn new(data: ValueData) -> Value {
+        match data {
+            ValueData::Undefined => Value::undefined(),
+            ValueData::Null => Value::null(),
+            ValueData::Boolean(b) => Value::boolean(b),
+            ValueData::Number(n) => Value::number(n),
+            ValueData::Integer(n) => Value::number(f64::from(n)),
+            data => Value::from_gc(Gc::new(data)),
+        }
+    }
+
+    /// Returns `undefined`
+    pub fn undefined() -> Value {
+        Value(Cell::new(TAG_UNDEFINED))
+    }
+
+    /// Returns `null`
+    pub fn null() -> Value {
+        Value(Cell::new(TAG_NULL))
+    }
+
+    /// Returns a boolean
+    pub fn boolean(b: bool) -> Value {
+        Value(Cell::new(TAG_BOOLEAN | b as u64))
+    }
+
+    /// Returns a number
+    pub fn number(n: f64) -> Value {
+        Value(Cell::new(if n.is_nan() {
+            CANONICAL_NAN
+        } else {
+            n.to_bits()
+        }))
+    }
+
+    /// Wrap a handle to data on the heap, taking it over
+    fn from_gc(gc: Gc<ValueData>) -> Value {
+        let bits = Gc::into_raw(gc) as usize as u64;
+        // Pointers only use the low 48 bits on the platforms Rust supports
+        assert_eq!(bits & TAG_MASK, 0, "pointer doesn't fit in a value");
+        Value(Cell::new(TAG_HEAP | bits))
+    }
+
+    /// The pointer to the data on the heap, if this value has one, which mustn't be dropped
+    fn gc(&self) -> Option<ManuallyDrop<Gc<ValueData>>> {
+        let bits = self.0.get();
+        if bits & TAG_MASK == TAG_HEAP {
+            let gc = unsafe { Gc::from_raw((bits & PAYLOAD_MASK) as usize as *const _) };
+            Some(ManuallyDrop::new(gc))
+        } else {
+            None
+        }
+    }
+
+    /// The bits of a value which isn't on the heap, which can be copied without counting a handle
+    pub fn immediate_bits(&self) -> Option<u64> {
+        let bits = self.0.get();
+        if bits & TAG_MASK == TAG_HEAP {
+            None
+        } else {
+            Some(bits)
+        }
+    }
+
+    /// Make a value out of the bits `immediate_bits` gave
+    pub fn from_immediate_bits(bits: u64) -> Value {
+        assert_ne!(bits & TAG_MASK, TAG_HEAP, "value on the heap made from bits");
+        Value(Cell::new(bits))
+    }
+
+    /// The data of the value, which is either copied out of it or borrowed from the heap
+    pub fn data(&self) -> ValueRef<'_> {
+        match self.gc() {
+            Some(gc) => ValueRef::Heap(unsafe { &*(&**gc as *const ValueData) }),
+            None => {
+                let bits = self.0.get();
+                ValueRef::Immediate(match bits & TAG_MASK {
+                    TAG_UNDEFINED => ValueData::Undefined,
+                    TAG_NULL => ValueData::Null,
+                    TAG_BOOLEAN => ValueData::Boolean(bits & 1 == 1),
+                    _ => ValueData::Number(f64::from_bits(bits)),
+                })
+            }
+        }
+    }
+
+    /// Whether both values are the same value on the heap, such as the same object
+    pub fn same_heap_cell(&self, other: &Value) -> bool {
+        match (self.heap_address(), other.heap_address()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// The address of the data on the heap, if this value has some
+    pub(crate) fn heap_address(&self) -> Option<usize> {
+        self.gc().map(|gc| &**gc as *const ValueData as usize)
+    }
+
+    /// Returns true if the value is an object
+    pub fn is_object(&self) -> bool {
+        self.data().is_object()
+    }
+
+    /// Returns true if the value is a function, which can be called
+    pub fn is_function(&self) -> bool {
+        self.data().is_function()
+    }
+
+    /// Returns true if the value is undefined
+    pub fn is_undefined(&self) -> bool {
+        self.0.get() == TAG_UNDEFINED
+    }
+
+    /// Returns true if the value is null
+    pub fn is_null(&self) -> bool {
+        self.0.get() == TAG_NULL
+    }
+
+    /// Returns true if the value is null or undefined
+    pub fn is_null_or_undefined(&self) -> bool {
+        self.is_undefined() || self.is_null()
+    }
+
+    /// Returns true if the value is a number
+    pub fn is_double(&self) -> bool {
+        let bits = self.0.get();
+        bits == CANONICAL_NAN || bits & TAG_MASK < TAG_UNDEFINED
+    }
+
+    /// Returns true if the value is a string
+    pub fn is_string(&self) -> bool {
+        self.data().is_string()
+    }
+
+    /// Returns true if the value is true
+    /// [toBoolean](https://tc39.github.io/ecma262/#sec-toboolean)
+    pub fn is_true(&self) -> bool {
+        self.data().is_true()
+    }
+
+    /// Converts the value into a 64-bit floating point number
+    pub fn to_num(&self) -> f64 {
+        self.data().to_num()
+    }
+
+    /// Converts the value into a number for the unary `+` operator, which can't convert a `BigInt`
+    pub fn to_number(&self) -> Result<f64, NativeError> {
+        self.data().to_number()
+    }
+
+    /// Converts the value into a 32-bit integer
+    pub fn to_int(&self) -> i32 {
+        self.data().to_int()
+    }
+
+    /// Resolve the property in the object
+    /// Returns a copy of the Property
+    pub fn get_prop<K: ToAtom>(&self, field: K) -> Option<Property> {
+        self.data().get_prop(field)
+    }
+
+    /// Resolve the property in the object and get its value, or undefined if this is not an object or the field doesn't exist
+    pub fn get_field<K: ToAtom>(&self, field: K) -> Value {
+        self.data().get_field(field)
+    }
+
+    /// Resolve the property in the object and get its value, or undefined if this is not an object or the field doesn't exist
+    pub fn get_field_slice(&self, field: &str) -> Value {
+        self.data().get_field_slice(field)
+    }
+
+    /// Set the field in the value
+    pub fn set_field<K: ToAtom>(&self, field: K, val: Value) -> Value {
+        self.data().set_field(field, val)
+    }
+
+    /// Set the field in the value
+    pub fn set_field_slice(&self, field: &str, val: Value) -> Value {
+        self.data().set_field_slice(field, val)
+    }
+
+    /// Set the property in the value
+    pub fn set_prop<K: ToAtom>(&self, field: K, prop: Property) -> Property {
+        self.data().set_prop(field, prop)
+    }
+
+    /// Set the property in the value
+    pub fn set_prop_slice(&self, field: &str, prop: Property) -> Property {
+        self.data().set_prop_slice(field, prop)
+    }
+
+    /// The value of an internal slot of the object, or `undefined` if this isn't an object with the slot
+    pub fn get_internal_slot(&self, name: &str) -> Value {
+        self.data().get_internal_slot(name)
+    }
+
+    /// Whether this is an object with an internal slot
+    pub fn has_internal_slot(&self, name: &str) -> bool {
+        self.data().has_internal_slot(name)
+    }
+
+    /// Set an internal slot of the object, which does nothing if this isn't an object
+    pub fn set_internal_slot(&self, name: &str, val: Value) {
+        self.data().set_internal_slot(name, val)
+    }
+
+    /// The prototype of the object, or `undefined` if this isn't an object with one
+    pub fn get_prototype(&self) -> Value {
+        self.data().get_prototype()
+    }
+
+    /// Set the prototype of the object, which does nothing if this isn't an object
+    pub fn set_prototype(&self, proto: Value) {
+        self.data().set_prototype(proto)
+    }
+
+    /// Convert the value to a JSON value
+    pub fn to_json(&self) -> JSONValue {
+        self.data().to_json()
+    }
+
+    /// The string this value is, or converts to
+    pub fn to_js_string(&self) -> JsString {
+        self.data().to_js_string()
+    }
+
+    /// Get the type of the value
+    pub fn get_type(&self) -> &'static str {
+        self.data().get_type()
+    }
+
+    /// Whether two values are equal without converting either, as the `===` operator tests
+    pub fn strict_equals(&self, other: &Value) -> bool {
+        self.data().strict_equals(&other.data())
+    }
+
+    /// Whether this value is less than another, or `None` if they can't be compared because of a `NaN`
+    pub fn less_than(&self, other: &Value) -> Option<bool> {
+        self.data().less_than(&other.data())
+    }
+
+    /// Raise this value to the power of another, as the `**` operator does
+    pub fn pow(&self, other: &Value) -> Result<Value, NativeError> {
+        self.data().pow(&other.data())
+    }
+
+    /// Shift this value right, filling with zeros, as the `>>>` operator does
+    pub fn unsigned_shr(&self, other: &Value) -> Result<Value, NativeError> {
+        self.data().unsigned_shr(&other.data())
+    }
+
+    /// Flip the bits of this value, as the `~` operator does
+    pub fn bitwise_not(&self) -> ValueData {
+        self.data().bitwise_not()
+    }
+}
+
+impl Clone for Value {
+    fn clone(&self) -> Value {
+        match self.gc() {
+            Some(gc) => Value::from_gc((*gc).clone()),
+            None => Value(Cell::new(self.0.get())),
+        }
+    }
+}
+
+impl Drop for Value {
+    fn drop(&mut self) {
+        if let Some(gc) = self.gc() {
+            drop(ManuallyDrop::into_inner(gc));
+        }
+    }
+}
+
+unsafe impl Trace for Value {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(gc) = self.gc() {
+            gc.trace(tracer);
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&*self.data(), f)
+    }
+}
+
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.data(), f)
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        *self.data() == *other.data()
+    }
+}
+
+impl ToAtom for Value {
+    /// The property key a value stands for, which skips making a string for array indices
+    fn try_to_atom(&self) -> Option<Atom> {
+        match self.array_index() {
+            Some(index) => index.try_to_atom(),
+            None => Atom::try_new(&self.to_string()),
+        }
+    }
+
+    fn find_atom(&self) -> Option<Atom> {
+        match self.array_index() {
+            Some(index) => index.find_atom(),
+            None => Atom::lookup(&self.to_string()),
+        }
+    }
+}
+
+impl Value {
+    /// The array index this value is, if it's a number which is one
+    fn array_index(&self) -> Option<u32> {
+        if !self.is_double() {
+            return None;
+        }
+        let n = self.to_num();
+        if n >= 0.0 && n < f64::from(u32::MAX) && n.fract() == 0.0 {
+            Some(n as u32)
+        } else {
+            None
+        }
+    }
+}
+
+/// The data of a value, as `Value::data` gives it
+// Reading a value stored in itself mustn't allocate, so the copy isn't boxed
+#[allow(clippy::large_enum_variant)]
+pub enum ValueRef<'a> {
+    /// A copy of a value which is stored in the value itself
+    Immediate(ValueData),
+    /// A borrow of a value on the heap
+    Heap(&'a ValueData),
+}
+
+impl<'a> Deref for ValueRef<'a> {
+    type Target = ValueData;
+    fn deref(&self) -> &ValueData {
+        match *self {
+            ValueRef::Immediate(ref data) => data,
+            ValueRef::Heap(data) => data,
+        }
+    }
+}
+
+thread_local! {
+    /// The bytes of the objects, strings and array buffers made on this thread so far
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Count some bytes as allocated, so interpreters with a heap limit know when to measure their heaps
+pub fn note_allocation(bytes: usize) {
+    ALLOCATED.with(|allocated| allocated.set(allocated.get().wrapping_add(bytes)));
+}
+
+/// The bytes of the objects, strings and array buffers made on this thread so far, which wraps around on overflow
+pub fn allocated_bytes() -> usize {
+    ALLOCATED.with(|allocated| allocated.get())
+}
 
 /// A Javascript value
-#[derive(Trace, Finalize, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub enum ValueData {
     /// `null` - A null value, for when a value doesn't exist
     Null,
@@ -29,96 +466,128 @@ pub enum ValueData {
     Undefined,
     /// `boolean` - A `true` / `false` value, for if a certain criteria is met
     Boolean(bool),
-    /// `String` - A UTF-8 string, such as `"Hello, world"`
-    String(String),
+    /// `String` - A string of UTF-16 code units, such as `"Hello, world"`
+    String(JsString),
     /// `Number` - A 64-bit floating point number, such as `3.1415`
     Number(f64),
-    /// `Number` - A 32-bit integer, such as `42`
+    /// `Number` - A 32-bit integer, such as `42`, which a `Value` made from it holds as a `Number`
     Integer(i32),
+    /// `BigInt` - An integer of any size, such as `9007199254740993n`
+    BigInt(BigInt),
     /// `Object` - An object, such as `Math`, represented by a binary tree of string keys to Javascript values
     /// Some Objects will need an internal slot to hold private values, so our second ObjectData is for that
     Object(GcCell<ObjectData>),
     /// `Function` - A runnable block of code, such as `Math.sqrt`, which can take some variables and return a useful value or act upon an object
     Function(GcCell<Function>),
+    /// `Bytes` - The block of bytes behind an `ArrayBuffer`, which is only ever held in its internal slot
+    Bytes(SharedBytes),
+    /// `Host` - The Rust data of an object made by a class the host defined, which is only ever held in its internal slot
+    Host(HostData),
+    /// `Slots` - The variables of a function call that closures share, after the scope outside it, which is only ever held as a scope
+    Slots(GcCell<Vec<Value>>),
+}
+
+unsafe impl Trace for ValueData {
+    fn trace(&self, tracer: &mut Tracer) {
+        match *self {
+            ValueData::Object(ref obj) => obj.trace(tracer),
+            ValueData::Function(ref func) => func.trace(tracer),
+            ValueData::Slots(ref slots) => slots.trace(tracer),
+            _ => (),
+        }
+    }
+
+    fn mark_atoms(&self, marks: &mut AtomMarks) {
+        match *self {
+            ValueData::Object(ref obj) => obj.mark_atoms(marks),
+            ValueData::Function(ref func) => func.mark_atoms(marks),
+            _ => (),
+        }
+    }
+
+    fn extra_size(&self) -> usize {
+        match *self {
+            ValueData::String(ref s) => s.heap_size(),
+            ValueData::BigInt(ref n) => n.bits() / 8,
+            ValueData::Bytes(ref bytes) => bytes.try_borrow().map_or(0, |bytes| bytes.len()),
+            ValueData::Host(ref data) => mem::size_of_val(&**data),
+            ValueData::Object(ref obj) => obj.extra_size(),
+            ValueData::Function(ref func) => func.extra_size(),
+            ValueData::Slots(ref slots) => slots.extra_size(),
+            _ => 0,
+        }
+    }
 }
 
 impl ValueData {
     /// Returns a new empty object
     pub fn new_obj(global: Option<Value>) -> Value {
-        let mut obj: ObjectData = HashMap::new();
-        if global.is_some() {
+        let mut obj = ObjectData::new();
+        if let Some(global) = global {
             let obj_proto = global
-                .unwrap()
                 .get_field_slice("Object")
                 .get_field_slice(PROTOTYPE);
-            obj.insert(INSTANCE_PROTOTYPE.to_string(), Property::new(obj_proto));
+            obj.set_prototype(obj_proto);
         }
-        Gc::new(ValueData::Object(GcCell::new(obj)))
+        note_allocation(mem::size_of::<ValueData>());
+        Value::new(ValueData::Object(GcCell::new(obj)))
     }
 
     /// Similar to `new_obj`, but you can pass a prototype to create from
     pub fn new_obj_from_prototype(proto: Value) -> Value {
-        let mut obj: ObjectData = HashMap::new();
-        obj.insert(INSTANCE_PROTOTYPE.to_string(), Property::new(proto));
-        Gc::new(ValueData::Object(GcCell::new(obj)))
+        let mut obj = ObjectData::new();
+        obj.set_prototype(proto);
+        note_allocation(mem::size_of::<ValueData>());
+        Value::new(ValueData::Object(GcCell::new(obj)))
     }
 
     /// Returns true if the value is an object
     pub fn is_object(&self) -> bool {
-        match *self {
-            ValueData::Object(_) => true,
-            _ => false,
-        }
+        matches!(*self, ValueData::Object(_))
+    }
+
+    /// Returns true if the value is a function, which can be called
+    pub fn is_function(&self) -> bool {
+        matches!(*self, ValueData::Function(_))
     }
 
     /// Returns true if the value is undefined
     pub fn is_undefined(&self) -> bool {
-        match *self {
-            ValueData::Undefined => true,
-            _ => false,
-        }
+        matches!(*self, ValueData::Undefined)
     }
 
     /// Returns true if the value is null
     pub fn is_null(&self) -> bool {
-        match *self {
-            ValueData::Null => true,
-            _ => false,
-        }
+        matches!(*self, ValueData::Null)
     }
 
     /// Returns true if the value is null or undefined
     pub fn is_null_or_undefined(&self) -> bool {
-        match *self {
-            ValueData::Null | ValueData::Undefined => true,
-            _ => false,
-        }
+        matches!(*self, ValueData::Null | ValueData::Undefined)
     }
 
-    /// Returns true if the value is a 64-bit floating-point number
+    /// Returns true if the value is a number
     pub fn is_double(&self) -> bool {
-        match *self {
-            ValueData::Number(_) => true,
-            _ => false,
-        }
+        matches!(*self, ValueData::Number(_) | ValueData::Integer(_))
     }
 
     /// Returns true if the value is a string
     pub fn is_string(&self) -> bool {
-        match *self {
-            ValueData::String(_) => true,
-            _ => false,
-        }
+        matches!(*self, ValueData::String(_))
     }
 
     /// Returns true if the value is true
     /// [toBoolean](https://tc39.github.io/ecma262/#sec-toboolean)
     pub fn is_true(&self) -> bool {
         match *self {
-            ValueData::Object(_) => true,
+            ValueData::Object(_)
+            | ValueData::Bytes(_)
+            | ValueData::Host(_)
+            | ValueData::Slots(_) => true,
             ValueData::String(ref s) if !s.is_empty() => true,
-            ValueData::Number(n) if n >= 1.0 && n % 1.0 == 0.0 => true,
-            ValueData::Integer(n) if n > 1 => true,
+            ValueData::Number(n) if n != 0.0 && !n.is_nan() => true,
+            ValueData::Integer(n) if n != 0 => true,
+            ValueData::BigInt(ref n) => !n.is_zero(),
             ValueData::Boolean(v) => v,
             _ => false,
         }
@@ -127,15 +596,29 @@ impl ValueData {
     /// Converts the value into a 64-bit floating point number
     pub fn to_num(&self) -> f64 {
         match *self {
-            ValueData::Object(_) | ValueData::Undefined | ValueData::Function(_) => NAN,
-            ValueData::String(ref str) => match FromStr::from_str(str) {
-                Ok(num) => num,
-                Err(_) => NAN,
-            },
+            ValueData::Object(_)
+            | ValueData::Undefined
+            | ValueData::Function(_)
+            | ValueData::Bytes(_)
+            | ValueData::Host(_)
+            | ValueData::Slots(_) => f64::NAN,
+            ValueData::String(ref str) => string_to_number(&str.to_string()),
             ValueData::Number(num) => num,
             ValueData::Boolean(true) => 1.0,
             ValueData::Boolean(false) | ValueData::Null => 0.0,
             ValueData::Integer(num) => num as f64,
+            ValueData::BigInt(ref num) => bigint_to_number(num),
+        }
+    }
+
+    /// Converts the value into a number for the unary `+` operator, which can't convert a `BigInt`
+    /// [[SPEC] - ToNumber](https://tc39.github.io/ecma262/#sec-tonumber)
+    pub fn to_number(&self) -> Result<f64, NativeError> {
+        match *self {
+            ValueData::BigInt(_) => Err(NativeError::type_error(
+                "Cannot convert a BigInt value to a number",
+            )),
+            _ => Ok(self.to_num()),
         }
     }
 
@@ -146,25 +629,37 @@ impl ValueData {
             | ValueData::Undefined
             | ValueData::Null
             | ValueData::Boolean(false)
-            | ValueData::Function(_) => 0,
-            ValueData::String(ref str) => match FromStr::from_str(str) {
-                Ok(num) => num,
-                Err(_) => 0,
-            },
-            ValueData::Number(num) => num as i32,
+            | ValueData::Function(_)
+            | ValueData::Bytes(_)
+            | ValueData::Host(_)
+            | ValueData::Slots(_) => 0,
+            ValueData::String(ref str) => to_int32(string_to_number(&str.to_string())),
+            ValueData::Number(num) => to_int32(num),
             ValueData::Boolean(true) => 1,
             ValueData::Integer(num) => num,
+            ValueData::BigInt(ref num) => (num % (BigInt::from(1) << 32)).to_i64().unwrap() as i32,
         }
     }
 
     /// Resolve the property in the object
     /// Returns a copy of the Property
-    pub fn get_prop(&self, field: String) -> Option<Property> {
+    pub fn get_prop<K: ToAtom>(&self, field: K) -> Option<Property> {
+        // Only names which are atoms can be the keys of properties
+        let field = field.find_atom()?;
         // Spidermonkey has its own GetLengthProperty: https://searchfox.org/mozilla-central/source/js/src/vm/Interpreter-inl.h#154
         // This is only for primitive strings, String() objects have their lengths calculated in string.rs
-        if self.is_string() && field == "length" {
-            if let ValueData::String(ref s) = *self {
-                return Some(Property::new(to_value(s.len() as i32)))
+        if let ValueData::String(ref s) = *self {
+            if field == atom::LENGTH {
+                return Some(Property::new(to_value(s.len() as i32)));
+            }
+            // Each character of a string is a property, which shares the string's storage
+            if let Some(index) = field.as_index() {
+                let index = index as usize;
+                if index < s.len() {
+                    return Some(Property::read_only(Value::new(ValueData::String(
+                        s.substring(index, index + 1),
+                    ))));
+                }
             }
         }
 
@@ -176,46 +671,70 @@ impl ValueData {
             // Accesing .object on borrow() seems to automatically dereference it, so we don't need the *
             ValueData::Function(ref func) => match func.clone().into_inner() {
                 Function::NativeFunc(ref func) => func.object.clone(),
-                Function::RegularFunc(ref func) => func.object.clone()
+                Function::RegularFunc(ref func) => func.object.clone(),
+                Function::NewRegularFunc(ref func) => func.object.clone(),
             }
             _ => return None,
         };
+        if let Some(prop) = get_buffer_field(&obj, field) {
+            return prop;
+        }
+        if let Some(prop) = get_mapped_argument(&obj, field) {
+            return Some(prop);
+        }
         match obj.get(&field) {
             Some(val) => Some(val.clone()),
-            None => match obj.get(&PROTOTYPE.to_string()) {
-                Some(prop) => prop.value.get_prop(field),
+            // Every object gets and sets its prototype through `__proto__`, unless it has its own property with that key
+            None if field == atom::INSTANCE_PROTOTYPE => {
+                obj.prototype().cloned().map(Property::new)
+            }
+            None => match obj.prototype() {
+                Some(proto) => proto.get_prop(field),
                 None => None,
             },
         }
     }
 
     /// Resolve the property in the object and get its value, or undefined if this is not an object or the field doesn't exist
-    pub fn get_field(&self, field: String) -> Value {
+    pub fn get_field<K: ToAtom>(&self, field: K) -> Value {
         match self.get_prop(field) {
             Some(prop) => prop.value.clone(),
-            None => Gc::new(ValueData::Undefined),
+            None => Value::undefined(),
         }
     }
 
     /// Resolve the property in the object and get its value, or undefined if this is not an object or the field doesn't exist
-    pub fn get_field_slice<'a>(&self, field: &'a str) -> Value {
-        self.get_field(field.to_string())
+    pub fn get_field_slice(&self, field: &str) -> Value {
+        self.get_field(field)
     }
 
     /// Set the field in the value
-    pub fn set_field(&self, field: String, val: Value) -> Value {
+    pub fn set_field<K: ToAtom>(&self, field: K, val: Value) -> Value {
+        let field = field.to_atom();
+        if field == atom::INSTANCE_PROTOTYPE && !self.has_own_property(field) {
+            // Only objects and `null` can be prototypes, and setting anything else is ignored
+            if val.is_object() || val.is_function() || val.is_null() {
+                self.set_prototype(val.clone());
+            }
+            return val;
+        }
         match *self {
-            ValueData::Object(ref obj) => {
-                obj.borrow_mut()
-                    .insert(field.clone(), Property::new(val.clone()));
+            ValueData::Object(ref obj)
+                if set_buffer_field(&obj.borrow(), field, &val).is_none() =>
+            {
+                set_mapped_argument(&obj.borrow(), field, &val);
+                obj.borrow_mut().insert(field, Property::new(val.clone()));
             }
             ValueData::Function(ref func) => {
                 match *func.borrow_mut().deref_mut() {
                     Function::NativeFunc(ref mut f) => {
-                        f.object.insert(field.clone(), Property::new(val.clone()))
+                        f.object.insert(field, Property::new(val.clone()))
                     }
                     Function::RegularFunc(ref mut f) => {
-                        f.object.insert(field.clone(), Property::new(val.clone()))
+                        f.object.insert(field, Property::new(val.clone()))
+                    }
+                    Function::NewRegularFunc(ref mut f) => {
+                        f.object.insert(field, Property::new(val.clone()))
                     }
                 };
             }
@@ -225,21 +744,25 @@ impl ValueData {
     }
 
     /// Set the field in the value
-    pub fn set_field_slice<'a>(&self, field: &'a str, val: Value) -> Value {
-        self.set_field(field.to_string(), val)
+    pub fn set_field_slice(&self, field: &str, val: Value) -> Value {
+        self.set_field(field, val)
     }
 
     /// Set the property in the value
-    pub fn set_prop(&self, field: String, prop: Property) -> Property {
+    pub fn set_prop<K: ToAtom>(&self, field: K, prop: Property) -> Property {
+        let field = field.to_atom();
         match *self {
             ValueData::Object(ref obj) => {
-                obj.borrow_mut().insert(field.clone(), prop.clone());
+                obj.borrow_mut().insert(field, prop.clone());
             }
             ValueData::Function(ref func) => {
                 match *func.borrow_mut().deref_mut() {
-                    Function::NativeFunc(ref mut f) => f.object.insert(field.clone(), prop.clone()),
+                    Function::NativeFunc(ref mut f) => f.object.insert(field, prop.clone()),
                     Function::RegularFunc(ref mut f) => {
-                        f.object.insert(field.clone(), prop.clone())
+                        f.object.insert(field, prop.clone())
+                    }
+                    Function::NewRegularFunc(ref mut f) => {
+                        f.object.insert(field, prop.clone())
                     }
                 };
             }
@@ -249,15 +772,79 @@ impl ValueData {
     }
 
     /// Set the property in the value
-    pub fn set_prop_slice<'t>(&self, field: &'t str, prop: Property) -> Property {
-        self.set_prop(field.to_string(), prop)
+    pub fn set_prop_slice(&self, field: &str, prop: Property) -> Property {
+        self.set_prop(field, prop)
+    }
+
+    /// Run a function on the properties and internal slots of an object or function, or return
+    /// `None` if this isn't one
+    fn read_object<T, F: FnOnce(&ObjectData) -> T>(&self, f: F) -> Option<T> {
+        match *self {
+            ValueData::Object(ref obj) => Some(f(&obj.borrow())),
+            ValueData::Function(ref func) => Some(match *func.borrow() {
+                Function::NativeFunc(ref func) => f(&func.object),
+                Function::RegularFunc(ref func) => f(&func.object),
+                Function::NewRegularFunc(ref func) => f(&func.object),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Run a function to change the properties and internal slots of an object or function, or
+    /// return `None` if this isn't one
+    fn write_object<T, F: FnOnce(&mut ObjectData) -> T>(&self, f: F) -> Option<T> {
+        match *self {
+            ValueData::Object(ref obj) => Some(f(&mut obj.borrow_mut())),
+            ValueData::Function(ref func) => Some(match *func.borrow_mut().deref_mut() {
+                Function::NativeFunc(ref mut func) => f(&mut func.object),
+                Function::RegularFunc(ref mut func) => f(&mut func.object),
+                Function::NewRegularFunc(ref mut func) => f(&mut func.object),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The value of an internal slot of the object, or `undefined` if this isn't an object with the slot
+    pub fn get_internal_slot(&self, name: &str) -> Value {
+        self.read_object(|obj| obj.get_internal_slot(name).cloned())
+            .and_then(|slot| slot)
+            .unwrap_or_else(Value::undefined)
+    }
+
+    /// Whether this is an object with an internal slot
+    pub fn has_internal_slot(&self, name: &str) -> bool {
+        self.read_object(|obj| obj.has_internal_slot(name))
+            .unwrap_or(false)
+    }
+
+    /// Whether this is an object with its own property with a key, not counting those of its prototypes
+    fn has_own_property(&self, field: Atom) -> bool {
+        self.read_object(|obj| obj.contains_key(&field))
+            .unwrap_or(false)
+    }
+
+    /// The prototype of the object, or `undefined` if this isn't an object with one
+    pub fn get_prototype(&self) -> Value {
+        self.read_object(|obj| obj.prototype().cloned())
+            .and_then(|proto| proto)
+            .unwrap_or_else(Value::undefined)
+    }
+
+    /// Set the prototype of the object, which does nothing if this isn't an object
+    pub fn set_prototype(&self, proto: Value) {
+        self.write_object(|obj| obj.set_prototype(proto));
+    }
+
+    /// Set an internal slot of the object, which does nothing if this isn't an object
+    pub fn set_internal_slot(&self, name: &str, val: Value) {
+        self.write_object(|obj| obj.set_internal_slot(name, val));
     }
 
     /// Convert from a JSON value to a JS value
     pub fn from_json(json: JSONValue) -> ValueData {
         match json {
             JSONValue::Number(v) => ValueData::Number(v.as_f64().unwrap()),
-            JSONValue::String(v) => ValueData::String(v),
+            JSONValue::String(v) => ValueData::String(JsString::from(v)),
             JSONValue::Bool(v) => ValueData::Boolean(v),
             JSONValue::Array(vs) => {
                 let mut i = 0;
@@ -292,16 +879,32 @@ impl ValueData {
             ValueData::Object(ref obj) => {
                 let mut nobj = Map::new();
                 for (k, v) in obj.borrow().iter() {
-                    if k != INSTANCE_PROTOTYPE {
-                        nobj.insert(k.clone(), v.value.to_json());
-                    }
+                    nobj.insert(k.to_string(), v.value.to_json());
                 }
                 JSONValue::Object(nobj)
             }
-            ValueData::String(ref str) => JSONValue::String(str.clone()),
-            ValueData::Number(num) => JSONValue::Number(JSONNumber::from_f64(num).unwrap()),
+            ValueData::String(ref str) => JSONValue::String(str.to_string()),
+            // Whole numbers stay integers, since every number a `Value` holds is a `Number`
+            ValueData::Number(num) if num.fract() == 0.0 && num.abs() <= 9007199254740992.0 => {
+                JSONValue::Number(JSONNumber::from(num as i64))
+            }
+            ValueData::Number(num) => {
+                JSONNumber::from_f64(num).map_or(JSONValue::Null, JSONValue::Number)
+            }
             ValueData::Integer(val) => JSONValue::Number(JSONNumber::from(val)),
-            ValueData::Function(_) => JSONValue::Null,
+            ValueData::BigInt(_)
+            | ValueData::Function(_)
+            | ValueData::Bytes(_)
+            | ValueData::Host(_)
+            | ValueData::Slots(_) => JSONValue::Null,
+        }
+    }
+
+    /// The string this value is, or converts to
+    pub fn to_js_string(&self) -> JsString {
+        match *self {
+            ValueData::String(ref s) => s.clone(),
+            _ => JsString::from(self.to_string()),
         }
     }
 
@@ -311,11 +914,121 @@ impl ValueData {
             ValueData::Number(_) | ValueData::Integer(_) => "number",
             ValueData::String(_) => "string",
             ValueData::Boolean(_) => "boolean",
+            ValueData::BigInt(_) => "bigint",
             ValueData::Null => "null",
             ValueData::Undefined => "undefined",
             _ => "object",
         }
     }
+
+    /// Whether two values are equal without converting either, as the `===` operator tests
+    /// [[SPEC] - Strict Equality Comparison](https://tc39.github.io/ecma262/#sec-strict-equality-comparison)
+    pub fn strict_equals(&self, other: &ValueData) -> bool {
+        match (self, other) {
+            (ValueData::Null, ValueData::Null) | (ValueData::Undefined, ValueData::Undefined) => {
+                true
+            }
+            (ValueData::Number(_), ValueData::Number(_))
+            | (ValueData::Number(_), ValueData::Integer(_))
+            | (ValueData::Integer(_), ValueData::Number(_))
+            | (ValueData::Integer(_), ValueData::Integer(_)) => self.to_num() == other.to_num(),
+            (ValueData::String(a), ValueData::String(b)) => a == b,
+            (ValueData::Boolean(a), ValueData::Boolean(b)) => a == b,
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => a == b,
+            (ValueData::Object(_), ValueData::Object(_))
+            | (ValueData::Function(_), ValueData::Function(_)) => ptr::eq(self, other),
+            _ => false,
+        }
+    }
+
+    /// Whether this value is less than another, or `None` if they can't be compared because of a `NaN`
+    /// [[SPEC] - Abstract Relational Comparison](https://tc39.github.io/ecma262/#sec-abstract-relational-comparison)
+    pub fn less_than(&self, other: &ValueData) -> Option<bool> {
+        match (self, other) {
+            (ValueData::String(a), ValueData::String(b)) => Some(a < b),
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => Some(a < b),
+            (ValueData::BigInt(a), ValueData::String(b)) => {
+                string_to_bigint(&b.to_string()).map(|b| *a < b)
+            }
+            (ValueData::String(a), ValueData::BigInt(b)) => {
+                string_to_bigint(&a.to_string()).map(|a| a < *b)
+            }
+            (ValueData::BigInt(a), b) => {
+                compare_with_number(a, b.to_num()).map(|o| o == Ordering::Less)
+            }
+            (a, ValueData::BigInt(b)) => {
+                compare_with_number(b, a.to_num()).map(|o| o == Ordering::Greater)
+            }
+            (a, b) => {
+                let (a, b) = (a.to_num(), b.to_num());
+                if a.is_nan() || b.is_nan() {
+                    None
+                } else {
+                    Some(a < b)
+                }
+            }
+        }
+    }
+
+    /// Raise this value to the power of another, as the `**` operator does
+    /// [[SPEC] - Number::exponentiate](https://tc39.github.io/ecma262/#sec-numeric-types-number-exponentiate)
+    pub fn pow(&self, other: &ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => {
+                if b.is_negative() {
+                    return Err(NativeError::range_error("Exponent must be non-negative"));
+                }
+                match b.to_usize() {
+                    Some(0) => ValueData::BigInt(BigInt::from(1)),
+                    _ if a.is_zero() || *a == BigInt::from(1) => ValueData::BigInt(a.clone()),
+                    _ if *a == BigInt::from(-1) => ValueData::BigInt(if (b % BigInt::from(2)).is_zero() {
+                        -a.clone()
+                    } else {
+                        a.clone()
+                    }),
+                    Some(exp) if a.bits().saturating_mul(exp) <= MAX_BIGINT_BITS => {
+                        ValueData::BigInt(num_traits::pow(a.clone(), exp))
+                    }
+                    _ => return Err(NativeError::range_error("Maximum BigInt size exceeded")),
+                }
+            }
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => {
+                let (a, b) = (a.to_num(), b.to_num());
+                // `powf` gives 1 for `1 ** NaN` and `(-1) ** Infinity`, where Javascript gives NaN
+                if b.is_nan() || (a.abs() == 1.0 && b.is_infinite()) {
+                    ValueData::Number(f64::NAN)
+                } else {
+                    ValueData::Number(a.powf(b))
+                }
+            }
+        }))
+    }
+
+    /// Shift this value right, filling with zeros, as the `>>>` operator does
+    pub fn unsigned_shr(&self, other: &ValueData) -> Result<Value, NativeError> {
+        match (self, other) {
+            (ValueData::BigInt(_), ValueData::BigInt(_)) => {
+                Err(NativeError::type_error(
+                    "BigInts have no unsigned right shift, use >> instead",
+                ))
+            }
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => Err(mixed_bigint_error()),
+            (a, b) => Ok(to_value(
+                to_uint32(a.to_num()).wrapping_shr(to_uint32(b.to_num())) as f64,
+            )),
+        }
+    }
+
+    /// Flip the bits of this value, as the `~` operator does
+    pub fn bitwise_not(&self) -> ValueData {
+        match *self {
+            ValueData::BigInt(ref n) => ValueData::BigInt(!n.clone()),
+            _ => ValueData::Integer(!self.to_int()),
+        }
+    }
 }
 
 impl Display for ValueData {
@@ -325,37 +1038,33 @@ impl Display for ValueData {
             ValueData::Undefined => write!(f, "undefined"),
             ValueData::Boolean(v) => write!(f, "{}", v),
             ValueData::String(ref v) => write!(f, "{}", v),
-            ValueData::Number(v) => write!(
-                f,
-                "{}",
-                match v {
-                    _ if v.is_nan() => "NaN".to_string(),
-                    _ if v.is_infinite() && v.is_sign_negative() => "-Infinity".to_string(),
-                    _ if v.is_infinite() => "Infinity".to_string(),
-                    _ => v.to_string(),
-                }
-            ),
+            ValueData::Number(v) => write!(f, "{}", number_to_string(v)),
+            ValueData::Object(ref v) if v.borrow().has_internal_slot(ERROR_DATA) => {
+                write!(f, "{}", error_to_string(self))
+            }
             ValueData::Object(ref v) => {
-                r#try!(write!(f, "{}", "{"));
-                match v.borrow().iter().last() {
-                    Some((last_key, _)) => {
-                        for (key, val) in v.borrow().iter() {
-                            r#try!(write!(f, "{}: {}", key, val.value.clone()));
-                            if key != last_key {
-                                r#try!(write!(f, "{}", ", "));
-                            }
+                write!(f, "{{")?;
+                if let Some((last_key, _)) = v.borrow().iter().last() {
+                    for (key, val) in v.borrow().iter() {
+                        write!(f, "{}: {}", key, val.value.clone())?;
+                        if key != last_key {
+                            write!(f, ", ")?;
                         }
                     }
-                    None => (),
                 }
-                write!(f, "{}", "}")
+                write!(f, "}}")
             }
             ValueData::Integer(v) => write!(f, "{}", v),
+            ValueData::BigInt(ref v) => write!(f, "{}", v),
+            ValueData::Bytes(ref v) => write!(f, "<{} bytes>", v.borrow().len()),
+            ValueData::Host(_) => write!(f, "<host data>"),
+            ValueData::Slots(ref v) => write!(f, "<{} slots>", v.borrow().len() - 1),
             ValueData::Function(ref v) => match *v.borrow() {
-                Function::NativeFunc(_) => write!(f, "{}", "function() { [native code] }"),
+                Function::NativeFunc(_) => write!(f, "function() {{ [native code] }}"),
                 Function::RegularFunc(ref rf) => {
                     write!(f, "function({}){}", rf.args.join(", "), rf.expr)
                 }
+                Function::NewRegularFunc(ref rf) => write!(f, "{}", rf.code.source_text()),
             },
         }
     }
@@ -363,10 +1072,25 @@ impl Display for ValueData {
 
 impl PartialEq for ValueData {
     fn eq(&self, other: &ValueData) -> bool {
-        match (self.clone(), other.clone()) {
-            // TODO: fix this
-            // _ if self.ptr.to_inner() == &other.ptr.to_inner() => true,
+        match (self, other) {
+            (ValueData::Object(_), _) | (ValueData::Function(_), _) if ptr::eq(self, other) => true,
             _ if self.is_null_or_undefined() && other.is_null_or_undefined() => true,
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => a == b,
+            (ValueData::BigInt(a), ValueData::String(ref s))
+            | (ValueData::String(ref s), ValueData::BigInt(a)) => {
+                string_to_bigint(&s.to_string()).as_ref() == Some(a)
+            }
+            (ValueData::BigInt(a), ValueData::Number(_))
+            | (ValueData::BigInt(a), ValueData::Integer(_))
+            | (ValueData::BigInt(a), ValueData::Boolean(_)) => {
+                compare_with_number(a, other.to_num()) == Some(Ordering::Equal)
+            }
+            (ValueData::Number(_), ValueData::BigInt(a))
+            | (ValueData::Integer(_), ValueData::BigInt(a))
+            | (ValueData::Boolean(_), ValueData::BigInt(a)) => {
+                compare_with_number(a, self.to_num()) == Some(Ordering::Equal)
+            }
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => false,
             (ValueData::String(_), _) | (_, ValueData::String(_)) => {
                 self.to_string() == other.to_string()
             }
@@ -376,77 +1100,183 @@ impl PartialEq for ValueData {
             {
                 true
             }
-            (ValueData::Number(a), _) if a == other.to_num() => true,
-            (_, ValueData::Number(a)) if a == self.to_num() => true,
+            (ValueData::Number(a), _) if *a == other.to_num() => true,
+            (_, ValueData::Number(a)) if *a == self.to_num() => true,
             (ValueData::Integer(a), ValueData::Integer(b)) if a == b => true,
             _ => false,
         }
     }
 }
 
+/// The error for an arithmetic operator given a `BigInt` and a value of another type
+fn mixed_bigint_error() -> NativeError {
+    NativeError::type_error("Cannot mix BigInt and other types, use explicit conversions")
+}
+
+/// A `BigInt` shift amount, or an error if shifting left would exceed the size limit
+fn bigint_shift(n: &BigInt, amount: &BigInt, left: bool) -> Result<BigInt, NativeError> {
+    let left = left != amount.is_negative();
+    let amount = amount.abs().to_usize().unwrap_or(usize::MAX);
+    if left {
+        if n.is_zero() {
+            Ok(n.clone())
+        } else if amount > MAX_BIGINT_BITS || n.bits() + amount > MAX_BIGINT_BITS {
+            Err(NativeError::range_error("Maximum BigInt size exceeded"))
+        } else {
+            Ok(n.clone() << amount)
+        }
+    } else if amount >= n.bits() {
+        // Shifting right rounds towards negative infinity
+        Ok(BigInt::from(if n.is_negative() { -1 } else { 0 }))
+    } else {
+        Ok(n.clone() >> amount)
+    }
+}
+
 impl Add for ValueData {
-    type Output = ValueData;
-    fn add(self, other: ValueData) -> ValueData {
-        return match (self.clone(), other.clone()) {
-            (ValueData::String(ref s), ref other) | (ref other, ValueData::String(ref s)) => {
-                ValueData::String(s.clone() + &other.to_string())
+    type Output = Result<Value, NativeError>;
+    fn add(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::String(_), _) | (_, ValueData::String(_)) => {
+                let (a, b) = (self.to_js_string(), other.to_js_string());
+                // Joining makes a rope, which counts as the smaller half, so building a string a
+                // piece at a time isn't counted as quadratic but doubling one still is
+                note_allocation(mem::size_of::<ValueData>() + a.heap_size().min(b.heap_size()));
+                ValueData::String(a.concat(&b))
             }
-            (_, _) => ValueData::Number(self.to_num() + other.to_num()),
-        };
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => ValueData::BigInt(a + b),
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Number(a.to_num() + b.to_num()),
+        }))
     }
 }
 impl Sub for ValueData {
-    type Output = ValueData;
-    fn sub(self, other: ValueData) -> ValueData {
-        ValueData::Number(self.to_num() - other.to_num())
+    type Output = Result<Value, NativeError>;
+    fn sub(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => ValueData::BigInt(a - b),
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Number(a.to_num() - b.to_num()),
+        }))
     }
 }
 impl Mul for ValueData {
-    type Output = ValueData;
-    fn mul(self, other: ValueData) -> ValueData {
-        ValueData::Number(self.to_num() * other.to_num())
+    type Output = Result<Value, NativeError>;
+    fn mul(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => ValueData::BigInt(a * b),
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Number(a.to_num() * b.to_num()),
+        }))
     }
 }
 impl Div for ValueData {
-    type Output = ValueData;
-    fn div(self, other: ValueData) -> ValueData {
-        ValueData::Number(self.to_num() / other.to_num())
+    type Output = Result<Value, NativeError>;
+    fn div(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(_), ValueData::BigInt(ref b)) if b.is_zero() => {
+                return Err(NativeError::range_error("Division by zero"))
+            }
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => ValueData::BigInt(a / b),
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Number(a.to_num() / b.to_num()),
+        }))
     }
 }
 impl Rem for ValueData {
-    type Output = ValueData;
-    fn rem(self, other: ValueData) -> ValueData {
-        ValueData::Number(self.to_num() % other.to_num())
+    type Output = Result<Value, NativeError>;
+    fn rem(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(_), ValueData::BigInt(ref b)) if b.is_zero() => {
+                return Err(NativeError::range_error("Division by zero"))
+            }
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => ValueData::BigInt(a % b),
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Number(a.to_num() % b.to_num()),
+        }))
     }
 }
 impl BitAnd for ValueData {
-    type Output = ValueData;
-    fn bitand(self, other: ValueData) -> ValueData {
-        ValueData::Integer(self.to_int() & other.to_int())
+    type Output = Result<Value, NativeError>;
+    fn bitand(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => ValueData::BigInt(a & b),
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Integer(a.to_int() & b.to_int()),
+        }))
     }
 }
 impl BitOr for ValueData {
-    type Output = ValueData;
-    fn bitor(self, other: ValueData) -> ValueData {
-        ValueData::Integer(self.to_int() | other.to_int())
+    type Output = Result<Value, NativeError>;
+    fn bitor(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => ValueData::BigInt(a | b),
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Integer(a.to_int() | b.to_int()),
+        }))
     }
 }
 impl BitXor for ValueData {
-    type Output = ValueData;
-    fn bitxor(self, other: ValueData) -> ValueData {
-        ValueData::Integer(self.to_int() ^ other.to_int())
+    type Output = Result<Value, NativeError>;
+    fn bitxor(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(a), ValueData::BigInt(b)) => ValueData::BigInt(a ^ b),
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Integer(a.to_int() ^ b.to_int()),
+        }))
     }
 }
 impl Shl for ValueData {
-    type Output = ValueData;
-    fn shl(self, other: ValueData) -> ValueData {
-        ValueData::Integer(self.to_int() << other.to_int())
+    type Output = Result<Value, NativeError>;
+    fn shl(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(ref a), ValueData::BigInt(ref b)) => {
+                ValueData::BigInt(bigint_shift(a, b, true)?)
+            }
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Integer(a.to_int().wrapping_shl(to_uint32(b.to_num()))),
+        }))
     }
 }
 impl Shr for ValueData {
+    type Output = Result<Value, NativeError>;
+    fn shr(self, other: ValueData) -> Result<Value, NativeError> {
+        Ok(Value::new(match (&self, &other) {
+            (ValueData::BigInt(ref a), ValueData::BigInt(ref b)) => {
+                ValueData::BigInt(bigint_shift(a, b, false)?)
+            }
+            (ValueData::BigInt(_), _) | (_, ValueData::BigInt(_)) => {
+                return Err(mixed_bigint_error())
+            }
+            (a, b) => ValueData::Integer(a.to_int().wrapping_shr(to_uint32(b.to_num()))),
+        }))
+    }
+}
+impl Neg for ValueData {
     type Output = ValueData;
-    fn shr(self, other: ValueData) -> ValueData {
-        ValueData::Integer(self.to_int() >> other.to_int())
+    fn neg(self) -> ValueData {
+        match self {
+            ValueData::BigInt(ref n) => ValueData::BigInt(-n),
+            _ => ValueData::Number(-self.to_num()),
+        }
     }
 }
 impl Not for ValueData {
@@ -471,7 +1301,8 @@ pub trait FromValue {
 
 impl ToValue for String {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::String(self.clone()))
+        note_allocation(self.len());
+        Value::new(ValueData::String(JsString::from(self.as_str())))
     }
 }
 
@@ -481,15 +1312,15 @@ impl FromValue for String {
     }
 }
 
-impl<'s> ToValue for &'s str {
+impl ToValue for &str {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::String(String::from_str(*self).unwrap()))
+        Value::new(ValueData::String(JsString::from(*self)))
     }
 }
 
 impl ToValue for char {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::String(self.to_string()))
+        Value::new(ValueData::String(JsString::from(self.to_string())))
     }
 }
 impl FromValue for char {
@@ -500,7 +1331,7 @@ impl FromValue for char {
 
 impl ToValue for f64 {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::Number(*self))
+        Value::new(ValueData::Number(*self))
     }
 }
 impl FromValue for f64 {
@@ -511,7 +1342,7 @@ impl FromValue for f64 {
 
 impl ToValue for i32 {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::Integer(*self))
+        Value::new(ValueData::Integer(*self))
     }
 }
 impl FromValue for i32 {
@@ -520,9 +1351,15 @@ impl FromValue for i32 {
     }
 }
 
+impl ToValue for BigInt {
+    fn to_value(&self) -> Value {
+        Value::new(ValueData::BigInt(self.clone()))
+    }
+}
+
 impl ToValue for bool {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::Boolean(*self))
+        Value::new(ValueData::Boolean(*self))
     }
 }
 impl FromValue for bool {
@@ -531,24 +1368,20 @@ impl FromValue for bool {
     }
 }
 
-impl<'s, T: ToValue> ToValue for &'s [T] {
+impl<T: ToValue> ToValue for &[T] {
     fn to_value(&self) -> Value {
-        let mut arr = HashMap::new();
-        let mut i = 0;
-        for item in self.iter() {
+        let mut arr = ObjectData::new();
+        for (i, item) in self.iter().enumerate() {
             arr.insert(i.to_string(), Property::new(item.to_value()));
-            i += 1;
         }
         to_value(arr)
     }
 }
 impl<T: ToValue> ToValue for Vec<T> {
     fn to_value(&self) -> Value {
-        let mut arr = HashMap::new();
-        let mut i = 0;
-        for item in self.iter() {
+        let mut arr = ObjectData::new();
+        for (i, item) in self.iter().enumerate() {
             arr.insert(i.to_string(), Property::new(item.to_value()));
-            i += 1;
         }
         to_value(arr)
     }
@@ -559,7 +1392,7 @@ impl<T: FromValue> FromValue for Vec<T> {
         let len = v.get_field_slice("length").to_int();
         let mut vec = Vec::with_capacity(len as usize);
         for i in 0..len {
-            vec.push(r#try!(from_value(v.get_field(i.to_string()))))
+            vec.push(from_value(v.get_field(i.to_string()))?)
         }
         Ok(vec)
     }
@@ -567,17 +1400,18 @@ impl<T: FromValue> FromValue for Vec<T> {
 
 impl ToValue for ObjectData {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::Object(GcCell::new(self.clone())))
+        Value::new(ValueData::Object(GcCell::new(self.clone())))
     }
 }
 
 impl FromValue for ObjectData {
     fn from_value(v: Value) -> Result<ObjectData, &'static str> {
-        match *v {
+        match *v.data() {
             ValueData::Object(ref obj) => Ok(obj.clone().into_inner()),
             ValueData::Function(ref func) => Ok(match *func.borrow().deref() {
                 Function::NativeFunc(ref data) => data.object.clone(),
                 Function::RegularFunc(ref data) => data.object.clone(),
+                Function::NewRegularFunc(ref data) => data.object.clone(),
             }),
             _ => Err("Value is not a valid object"),
         }
@@ -586,7 +1420,7 @@ impl FromValue for ObjectData {
 
 impl ToValue for JSONValue {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::from_json(self.clone()))
+        Value::new(ValueData::from_json(self.clone()))
     }
 }
 
@@ -596,9 +1430,15 @@ impl FromValue for JSONValue {
     }
 }
 
+impl FromValue for Value {
+    fn from_value(v: Value) -> Result<Value, &'static str> {
+        Ok(v)
+    }
+}
+
 impl ToValue for () {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::Null)
+        Value::null()
     }
 }
 impl FromValue for () {
@@ -611,7 +1451,7 @@ impl<T: ToValue> ToValue for Option<T> {
     fn to_value(&self) -> Value {
         match *self {
             Some(ref v) => v.to_value(),
-            None => Gc::new(ValueData::Null),
+            None => Value::null(),
         }
     }
 }
@@ -620,23 +1460,26 @@ impl<T: FromValue> FromValue for Option<T> {
         Ok(if value.is_null_or_undefined() {
             None
         } else {
-            Some(r#try!(FromValue::from_value(value)))
+            Some(FromValue::from_value(value)?)
         })
     }
 }
 
 impl ToValue for NativeFunctionData {
     fn to_value(&self) -> Value {
-        Gc::new(ValueData::Function(GcCell::new(Function::NativeFunc(
+        Value::new(ValueData::Function(GcCell::new(Function::NativeFunc(
             NativeFunction::new(*self),
         ))))
     }
 }
 impl FromValue for NativeFunctionData {
     fn from_value(v: Value) -> Result<NativeFunctionData, &'static str> {
-        match *v {
+        match *v.data() {
             ValueData::Function(ref func) => match *func.borrow() {
-                Function::NativeFunc(ref data) => Ok(data.data),
+                Function::NativeFunc(NativeFunction {
+                    data: NativeBody::Fn(data),
+                    ..
+                }) => Ok(data),
                 _ => Err("Value is not a native function"),
             },
             _ => Err("Value is not a function"),
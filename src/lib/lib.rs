@@ -1,4 +1,6 @@
-extern crate gc;
+extern crate indexmap;
+extern crate num_bigint;
+extern crate num_traits;
 extern crate rand;
 extern crate serde_json;
 extern crate time;
@@ -6,10 +8,8 @@ extern crate ratel;
 // extern crate esprit;
 extern crate toolshed;
 
-#[macro_use]
-extern crate gc_derive;
-
 pub mod exec;
+pub mod gc;
 pub mod js;
 pub mod syntax;
 pub mod repl;
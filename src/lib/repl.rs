@@ -1,20 +1,25 @@
-use crate::exec_new::{Executor, Interpreter};
+use crate::exec_new::{Executor, Interpreter, Script};
 extern crate ratel;
 extern crate rustyline;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 extern crate toolshed;
-use ratel::parse;
 
 /// Starting prompt
-const DEFAULT_PROMPT: &'static str = "js => ";
-const MORE_PROMPT: &'static str = "...   ";
+const DEFAULT_PROMPT: &str = "js => ";
+const MORE_PROMPT: &str = "...   ";
 
 pub struct REPL {
     pub intr: Interpreter,
     pub editor: rustyline::Editor<()>,
 }
 
+impl Default for REPL {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl REPL {
     pub fn new() -> REPL {
         let rl = rustyline::Editor::<()>::new();
@@ -25,7 +30,7 @@ impl REPL {
         }
     }
 
-    pub fn run(&mut self) -> () {
+    pub fn run(&mut self) {
 
         let mut rl = Editor::<()>::new();
         if rl.load_history("history.txt").is_err() {
@@ -43,16 +48,13 @@ impl REPL {
                     last_command.push_str(&line);
 
                     let copy = last_command.clone();
-                    let r = parse(&copy);
+                    let r = Script::parse(&copy);
 
                     match r {
-                        Ok(module) => {
-                            let vec = module.body();
-
-                            for i in &vec {
-                                let x = i.item;
-                                
-                                engine.run(&x);
+                        Ok(script) => {
+                            match engine.run_script(&script) {
+                                Ok(v) => println!("{}", v),
+                                Err(v) => println!("Uncaught {}", v),
                             }
                             rl.save_history("history.txt").unwrap();
                             prompt = DEFAULT_PROMPT;
@@ -3,7 +3,7 @@ use std::fmt::{Display, Formatter, Result};
 use crate::syntax::ast::constant::Const;
 use crate::syntax::ast::op::{BinOp, Operator, UnaryOp};
 
-#[derive(Clone, Trace, Finalize, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Expr {
     /// The expression definition
     pub def: ExprDef,
@@ -12,7 +12,7 @@ pub struct Expr {
 impl Expr {
     /// Create a new expression with a starting and ending position
     pub fn new(def: ExprDef) -> Expr {
-        Expr { def: def }
+        Expr { def }
     }
 }
 
@@ -22,7 +22,7 @@ impl Display for Expr {
     }
 }
 
-#[derive(Clone, Debug, Trace, Finalize, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 /// A Javascript Expression
 pub enum ExprDef {
     /// Run a operation between 2 expressions
@@ -75,14 +75,14 @@ pub enum ExprDef {
 
 impl Operator for ExprDef {
     fn get_assoc(&self) -> bool {
-        match *self {
+        !matches!(
+            *self,
             ExprDef::ConstructExpr(_, _)
-            | ExprDef::UnaryOpExpr(_, _)
-            | ExprDef::TypeOfExpr(_)
-            | ExprDef::IfExpr(_, _, _)
-            | ExprDef::AssignExpr(_, _) => false,
-            _ => true,
-        }
+                | ExprDef::UnaryOpExpr(_, _)
+                | ExprDef::TypeOfExpr(_)
+                | ExprDef::IfExpr(_, _, _)
+                | ExprDef::AssignExpr(_, _)
+        )
     }
     fn get_precedence(&self) -> u64 {
         match self {
@@ -106,20 +106,20 @@ impl Operator for ExprDef {
 
 impl Display for ExprDef {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        return match *self {
+        match *self {
             ExprDef::ConstExpr(ref c) => write!(f, "{}", c),
             ExprDef::BlockExpr(ref block) => {
-                r#try!(write!(f, "{}", "{"));
+                write!(f, "{{")?;
                 for expr in block.iter() {
-                    r#try!(write!(f, "{};", expr));
+                    write!(f, "{};", expr)?;
                 }
-                write!(f, "{}", "}")
+                write!(f, "}}")
             }
             ExprDef::LocalExpr(ref s) => write!(f, "{}", s),
             ExprDef::GetConstFieldExpr(ref ex, ref field) => write!(f, "{}.{}", ex, field),
             ExprDef::GetFieldExpr(ref ex, ref field) => write!(f, "{}[{}]", ex, field),
             ExprDef::CallExpr(ref ex, ref args) => {
-                r#try!(write!(f, "{}(", ex));
+                write!(f, "{}(", ex)?;
                 let arg_strs: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
                 write!(f, "{})", arg_strs.join(","))
             }
@@ -183,7 +183,7 @@ impl Display for ExprDef {
             ExprDef::BinOpExpr(ref op, ref a, ref b) => write!(f, "{} {} {}", a, op, b),
             ExprDef::UnaryOpExpr(ref op, ref a) => write!(f, "{}{}", op, a),
             ExprDef::ReturnExpr(Some(ref ex)) => write!(f, "return {}", ex),
-            ExprDef::ReturnExpr(None) => write!(f, "{}", "return"),
+            ExprDef::ReturnExpr(None) => write!(f, "return"),
             ExprDef::ThrowExpr(ref ex) => write!(f, "throw {}", ex),
             ExprDef::AssignExpr(ref ref_e, ref val) => write!(f, "{} = {}", ref_e, val),
             ExprDef::VarDeclExpr(ref vars) => {
@@ -197,12 +197,12 @@ impl Display for ExprDef {
                 f.write_str("")
             }
             ExprDef::TypeOfExpr(ref e) => write!(f, "typeof {}", e),
-        };
+        }
     }
 }
 
 /// join_expr - Utility to join multiple Expressions into a single string
-fn join_expr(f: &mut Formatter, expr: &Vec<Expr>) -> Result {
+fn join_expr(f: &mut Formatter, expr: &[Expr]) -> Result {
     let mut first = true;
     for e in expr.iter() {
         if !first {
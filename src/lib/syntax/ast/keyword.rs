@@ -88,7 +88,7 @@ impl error::Error for KeywordError {
         "invalid token"
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn cause(&self) -> Option<&dyn error::Error> {
         // Generic error, underlying cause isn't tracked.
         None
     }
@@ -22,8 +22,8 @@ impl Position {
     /// * `column_number` - The column number the token starts at
     pub fn new(line_number: u64, column_number: u64) -> Position {
         Position {
-            line_number: line_number,
-            column_number: column_number,
+            line_number,
+            column_number,
         }
     }
 }
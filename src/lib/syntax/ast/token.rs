@@ -17,7 +17,7 @@ impl Token {
     /// Create a new detailed token from the token data, line number and column number
     pub fn new(data: TokenData, line_number: u64, column_number: u64) -> Token {
         Token {
-            data: data,
+            data,
             pos: Position::new(line_number, column_number),
         }
     }
@@ -83,7 +83,7 @@ impl error::Error for LexerError {
         &self.details
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn cause(&self) -> Option<&dyn error::Error> {
         // Generic error, underlying cause isn't tracked.
         None
     }
@@ -107,7 +107,7 @@ impl<'a> Lexer<'a> {
     /// # Arguments
     ///
     /// * `buffer` - A string slice that holds the source code.   
-    /// The buffer needs to have a lifetime as long as the Lexer instance itself
+    ///   The buffer needs to have a lifetime as long as the Lexer instance itself
     ///
     /// # Example
     ///
@@ -221,10 +221,7 @@ impl<'a> Lexer<'a> {
                                                 nums.push(self.next()?);
                                             }
                                             self.column_number += 2;
-                                            let as_num = match u64::from_str_radix(&nums, 16) {
-                                                Ok(v) => v,
-                                                Err(_) => 0,
-                                            };
+                                            let as_num = u64::from_str_radix(&nums, 16).unwrap_or_default();
                                             match from_u32(as_num as u32) {
                                                 Some(v) => v,
                                                 None => panic!(
@@ -239,10 +236,7 @@ impl<'a> Lexer<'a> {
                                                 nums.push(self.next()?);
                                             }
                                             self.column_number += 4;
-                                            let as_num = match u64::from_str_radix(&nums, 16) {
-                                                Ok(v) => v,
-                                                Err(_) => 0,
-                                            };
+                                            let as_num = u64::from_str_radix(&nums, 16).unwrap_or_default();
                                             match from_u32(as_num as u32) {
                                                 Some(v) => v,
                                                 None => panic!(
@@ -276,7 +270,7 @@ impl<'a> Lexer<'a> {
                         loop {
                             let ch = self.preview_next()?;
                             match ch {
-                                ch if ch.is_digit(16) => {
+                                ch if ch.is_ascii_hexdigit() => {
                                     buf.push(self.next()?);
                                 }
                                 _ => break,
@@ -302,7 +296,7 @@ impl<'a> Lexer<'a> {
                     };
                     self.push_token(TokenData::NumericLiteral(num as f64))
                 }
-                _ if ch.is_digit(10) => {
+                _ if ch.is_ascii_digit() => {
                     let mut buf = ch.to_string();
                     loop {
                         let ch = self.preview_next()?;
@@ -310,7 +304,7 @@ impl<'a> Lexer<'a> {
                             '.' => {
                                 buf.push(self.next()?);
                             }
-                            _ if ch.is_digit(10) => {
+                            _ if ch.is_ascii_digit() => {
                                 buf.push(self.next()?);
                             }
                             _ => break,
@@ -324,7 +318,7 @@ impl<'a> Lexer<'a> {
                     loop {
                         let ch = self.preview_next()?;
                         match ch {
-                            _ if ch.is_alphabetic() || ch.is_digit(10) || ch == '_' => {
+                            _ if ch.is_alphabetic() || ch.is_ascii_digit() || ch == '_' => {
                                 buf.push(self.next()?);
                             }
                             _ => {
@@ -0,0 +1,105 @@
+extern crate boa;
+mod common;
+use boa::exec_new::{Executor, Interpreter, Termination};
+use boa::js::atom::{self, atom_stats, Atom, ToAtom};
+use boa::js::value::{ToValue, ValueData};
+use common::{exec, exec_on};
+
+#[test]
+fn check_atoms_are_interned() {
+    let a = Atom::new("someProperty");
+    assert_eq!(Atom::new("someProperty"), a);
+    assert_eq!(Atom::lookup("someProperty"), Some(a));
+    assert_eq!(a.to_string(), "someProperty");
+    assert!(a == "someProperty");
+    assert_eq!(Atom::new("length"), atom::LENGTH);
+    assert_eq!(Atom::new("__proto__"), atom::INSTANCE_PROTOTYPE);
+}
+
+#[test]
+fn check_lookups_leave_the_table_alone() {
+    let before = atom_stats();
+    assert_eq!(Atom::lookup("aNameNothingUses"), None);
+    assert_eq!(atom_stats().atoms, before.atoms);
+    Atom::new("aNameNothingUses");
+    let after = atom_stats();
+    assert_eq!(after.atoms, before.atoms + 1);
+    assert_eq!(after.bytes, before.bytes + "aNameNothingUses".len());
+}
+
+#[test]
+fn check_array_index_atoms() {
+    let before = atom_stats();
+    assert_eq!(Atom::new("42"), Atom::index(42));
+    assert_eq!(Atom::index(42).as_index(), Some(42));
+    assert_eq!(Atom::index(42).to_string(), "42");
+    assert_eq!(atom_stats().atoms, before.atoms);
+    assert_eq!(atom_stats().indices, before.indices + 1);
+    // Only the canonical form of a number is an index
+    assert_eq!(Atom::new("042").as_index(), None);
+    assert_eq!(Atom::new("-1").as_index(), None);
+    assert_eq!(Atom::new("4294967294").as_index(), None);
+    assert_eq!(Atom::index(4294967294).to_string(), "4294967294");
+    assert_eq!(1.0.to_value().to_atom(), Atom::index(1));
+    assert_eq!((-0.0).to_value().to_atom(), Atom::index(0));
+    assert_eq!(1.5.to_value().to_atom(), Atom::new("1.5"));
+}
+
+#[test]
+fn check_object_keys_are_atoms() {
+    let obj = ValueData::new_obj(None);
+    obj.set_field_slice("x", 1.to_value());
+    obj.set_field(Atom::index(3), 2.to_value());
+    assert_eq!(obj.get_field(Atom::new("x")).to_string(), "1");
+    assert_eq!(obj.get_field_slice("3").to_string(), "2");
+    assert!(obj.get_field_slice("neverMadeIntoAnAtom").is_undefined());
+    assert_eq!(
+        exec("var o = {b: 1, 2: 1, a: 1, 1: 1}; o[2.0] = 3; JSON.stringify(o) + o['2']"),
+        "{\"1\":1,\"2\":3,\"b\":1,\"a\":1}3"
+    );
+}
+
+#[test]
+fn check_unused_atoms_are_freed() {
+    let kept = Atom::new("keptForGood");
+    let mut engine: Interpreter = Executor::new();
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "var o = {}, i = 0; while (i < 5000) { o['key' + i] = i; i++; } i"
+        ),
+        "5000"
+    );
+    let before = atom_stats();
+    assert!(Atom::lookup("key2500").is_some());
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "o = null; var p = {}; i = 0; while (i < 6000) { p['other' + i] = i; i++; } p.other0"
+        ),
+        "0"
+    );
+    let after = atom_stats();
+    assert!(after.collected >= before.collected + 5000);
+    assert!(after.atoms < before.atoms + 6000);
+    assert_eq!(Atom::lookup("key2500"), None);
+    assert_eq!(exec_on(&mut engine, "p.other3000"), "3000");
+    assert_eq!(Atom::lookup("keptForGood"), Some(kept));
+    assert_eq!(kept.to_string(), "keptForGood");
+}
+
+#[test]
+fn check_heap_limit_counts_atoms() {
+    let mut engine: Interpreter = Executor::new();
+    engine.set_heap_limit(Some(1 << 20));
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "var o = {}; var i = 0; while (i < 100000) { o['k' + i] = 1; delete o['k' + i]; i++; }"
+        ),
+        "Error: Script used more memory than allowed"
+    );
+    assert_eq!(engine.termination(), Some(Termination::HeapLimit));
+    // Deleting the keys left nothing using them once the script stopped
+    assert_eq!(Atom::lookup("k50"), None);
+}
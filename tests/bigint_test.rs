@@ -0,0 +1,125 @@
+extern crate boa;
+mod common;
+use boa::js::bigint::string_to_bigint;
+use common::exec_statements as exec;
+
+#[test]
+fn check_literals() {
+    assert_eq!(exec("123n"), "123");
+    assert_eq!(exec("0x1Fn"), "31");
+    assert_eq!(exec("0o17n"), "15");
+    assert_eq!(exec("0b101n"), "5");
+    assert_eq!(exec("typeof 1n"), "bigint");
+    assert_eq!(
+        exec("9007199254740993n * 1000000000000n"),
+        "9007199254740993000000000000"
+    );
+}
+
+#[test]
+fn check_arithmetic() {
+    assert_eq!(exec("7n / 2n"), "3");
+    assert_eq!(exec("-7n / 2n"), "-3");
+    assert_eq!(exec("-7n % 2n"), "-1");
+    assert_eq!(exec("2n ** 100n"), "1267650600228229401496703205376");
+    assert_eq!(exec("-(5n)"), "-5");
+    assert_eq!(exec("var a = 5n; a++; a"), "6");
+    assert_eq!(exec("var a = 5n; a -= 10n; a"), "-5");
+    assert_eq!(exec("1n / 0n"), "Error: RangeError: Division by zero");
+    assert_eq!(
+        exec("2n ** -1n"),
+        "Error: RangeError: Exponent must be non-negative"
+    );
+    assert_eq!(exec("'x' + 1n"), "x1");
+}
+
+#[test]
+fn check_bitwise() {
+    assert_eq!(exec("5n & 3n"), "1");
+    assert_eq!(exec("-5n | 2n"), "-5");
+    assert_eq!(exec("5n ^ -1n"), "-6");
+    assert_eq!(exec("~5n"), "-6");
+    assert_eq!(exec("1n << 70n"), "1180591620717411303424");
+    assert_eq!(exec("-9n >> 1n"), "-5");
+    assert_eq!(exec("8n << -2n"), "2");
+    assert_eq!(
+        exec("1n >>> 1n"),
+        "Error: TypeError: BigInts have no unsigned right shift, use >> instead"
+    );
+}
+
+#[test]
+fn check_mixing_types() {
+    let error = "Error: TypeError: Cannot mix BigInt and other types, use explicit conversions";
+    assert_eq!(exec("1n + 1"), error);
+    assert_eq!(exec("2 * 3n"), error);
+    assert_eq!(exec("1n << 2"), error);
+    assert_eq!(
+        exec("+1n"),
+        "Error: TypeError: Cannot convert a BigInt value to a number"
+    );
+    assert_eq!(exec("Number(2n ** 64n)"), "18446744073709552000");
+    assert_eq!(exec("BigInt(10) + 1n"), "11");
+}
+
+#[test]
+fn check_comparisons() {
+    assert_eq!(exec("1n == 1"), "true");
+    assert_eq!(exec("1n === 1"), "false");
+    assert_eq!(exec("2n === 2n"), "true");
+    assert_eq!(exec("10n == '10'"), "true");
+    assert_eq!(exec("1n < 1.5"), "true");
+    assert_eq!(exec("2n > 1.5"), "true");
+    assert_eq!(exec("1n < NaN"), "false");
+    assert_eq!(exec("9007199254740993n > 9007199254740992"), "true");
+    assert_eq!(exec("-1n < '0'"), "true");
+    assert_eq!(exec("0n ? 'yes' : 'no'"), "no");
+}
+
+#[test]
+fn check_constructor() {
+    assert_eq!(exec("BigInt('0x10')"), "16");
+    assert_eq!(exec("BigInt(' -42 ')"), "-42");
+    assert_eq!(exec("BigInt(true)"), "1");
+    assert_eq!(
+        exec("BigInt(1.5)"),
+        "Error: RangeError: The number 1.5 cannot be converted to a BigInt because it is not an integer"
+    );
+    assert_eq!(
+        exec("BigInt('1.5')"),
+        "Error: SyntaxError: Cannot convert 1.5 to a BigInt"
+    );
+    assert_eq!(
+        exec("new BigInt(1)"),
+        "Error: TypeError: BigInt is not a constructor"
+    );
+    assert_eq!(string_to_bigint("1_000"), None);
+    assert_eq!(string_to_bigint("-0x10"), None);
+}
+
+#[test]
+fn check_as_int_n() {
+    assert_eq!(exec("BigInt.asIntN(8, 255n)"), "-1");
+    assert_eq!(exec("BigInt.asIntN(8, 127n)"), "127");
+    assert_eq!(exec("BigInt.asIntN(8, -129n)"), "127");
+    assert_eq!(exec("BigInt.asIntN(64, 2n ** 63n)"), "-9223372036854775808");
+    assert_eq!(exec("BigInt.asUintN(8, -1n)"), "255");
+    assert_eq!(exec("BigInt.asUintN(64, -1n)"), "18446744073709551615");
+    assert_eq!(exec("BigInt.asUintN(0, 5n)"), "0");
+    assert_eq!(
+        exec("BigInt.asIntN(-1, 5n)"),
+        "Error: RangeError: Invalid value: not (convertible to) a safe integer"
+    );
+}
+
+#[test]
+fn check_to_string() {
+    assert_eq!(exec("(255n).toString(16)"), "ff");
+    assert_eq!(exec("(-255n).toString(2)"), "-11111111");
+    assert_eq!(exec("(2n ** 64n).toString(36)"), "3w5e11264sgsg");
+    assert_eq!(exec("(10n).valueOf() === 10n"), "true");
+    assert_eq!(
+        exec("(1n).toString(37)"),
+        "Error: RangeError: toString() radix must be between 2 and 36"
+    );
+}
@@ -0,0 +1,105 @@
+extern crate boa;
+mod common;
+use boa::exec_new::bytecode::disassemble;
+use boa::exec_new::Script;
+use common::exec;
+
+#[test]
+fn check_disassembly() {
+    let script = Script::parse("var x = 1;\nfunction f(a) { return a + x; }\nf(x)").unwrap();
+    assert_eq!(
+        disassemble(&script),
+        "== <anonymous> ==\n\
+         0000    1:1    DeclareVarUndefined(0)\t; x\n\
+         0001    2:1    MakeFunction(0)\t; f\n\
+         0002    2:1    DeclareFunction(1)\t; f\n\
+         0003    1:9    Const(0)\t; 1\n\
+         0004    1:1    DeclareVar(0)\t; x\n\
+         0005    3:2    Undefined\n\
+         0006    3:1    GetName(1)\t; f\n\
+         0007    3:3    GetName(0)\t; x\n\
+         0008    3:1    Call(1)\n\
+         0009    3:1    SetLocal(0)\t; <completion>\n\
+         0010    3:1    Pop\n\
+         0011    3:1    GetLocal(0)\t; <completion>\n\
+         0012    3:1    Return\n\
+         \n\
+         == f ==\n\
+         0000    2:12   Arg(0)\n\
+         0001    2:12   SetLocal(0)\t; a\n\
+         0002    2:12   Pop\n\
+         0003    2:24   GetLocal(0)\t; a\n\
+         0004    2:28   GetName(0)\t; x\n\
+         0005    2:24   Binary(Addition)\n\
+         0006    2:17   Return\n\
+         0007    2:17   Undefined\n\
+         0008    2:17   Return\n"
+    );
+}
+
+#[test]
+fn check_local_slots() {
+    assert_eq!(
+        exec("function f(a, b) { var c = a * b; return c + 1; } f(3, 4)"),
+        "13"
+    );
+    assert_eq!(
+        exec("function f(n) { var t = 0; while (n > 0) { t += n; n--; } return t; } f(100)"),
+        "5050"
+    );
+    assert_eq!(
+        exec("var x = 1; function f(x) { x = 2; return typeof y; } f(0) + x"),
+        "undefined1"
+    );
+}
+
+#[test]
+fn check_closures() {
+    assert_eq!(
+        exec("function counter() { var n = 0; return function() { n++; return n; }; } var c = counter(); c(); c()"),
+        "2"
+    );
+    assert_eq!(exec("function f(a) { return () => a * 2; } f(21)()"), "42");
+}
+
+#[test]
+fn check_try_finally_with_return() {
+    assert_eq!(
+        exec("var log; function f() { try { return 1; } finally { log = 'done'; } } f() + log"),
+        "1done"
+    );
+    assert_eq!(
+        exec("function f() { try { return 1; } finally { return 2; } } f()"),
+        "2"
+    );
+    assert_eq!(
+        exec("var s = ''; function f() { try { throw 'a'; } catch (e) { s += e; return s; } finally { s += 'f'; } } f() + s"),
+        "aaf"
+    );
+    assert_eq!(
+        exec("var e = 'outer'; function f() { try { throw 1; } catch (e) { return e; } } f() + e"),
+        "1outer"
+    );
+}
+
+#[test]
+fn check_completion_values() {
+    assert_eq!(exec("1; if (false) 2;"), "undefined");
+    assert_eq!(exec("3; var x = 4;"), "3");
+    assert_eq!(exec("var i = 0; while (i < 3) { i++; }"), "2");
+    assert_eq!(exec("5; try { 6; } finally { 7; }"), "6");
+    assert_eq!(exec("8; function g() {}"), "8");
+}
+
+#[test]
+fn check_var_hoisting() {
+    assert_eq!(
+        exec("function f() { x = 5; return x; var x; } f(); typeof x"),
+        "undefined"
+    );
+    assert_eq!(
+        exec("function g() { return y; var y = 1; } g()"),
+        "undefined"
+    );
+    assert_eq!(exec("typeof z; var z = 1; z"), "1");
+}
@@ -0,0 +1,142 @@
+extern crate boa;
+mod common;
+use boa::exec_new::{Executor, Interpreter, Script};
+use boa::js::class::{host_data, Class};
+use boa::js::function::new_native_closure;
+use boa::js::value::{to_value, Value};
+use common::exec_on;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+struct Point {
+    x: f64,
+    y: f64,
+}
+
+/// An engine with a `Point` class
+fn point_engine() -> Interpreter {
+    let mut engine: Interpreter = Executor::new();
+    let point = Class::new("Point", |(x, y): (f64, f64)| Ok(Point { x, y }))
+        .method("norm", |p: &mut Point, (): ()| Ok(to_value(p.x.hypot(p.y))))
+        .method("scale", |p: &mut Point, (k,): (f64,)| {
+            p.x *= k;
+            p.y *= k;
+            Ok(Value::undefined())
+        })
+        .method(
+            "distanceTo",
+            |p: &mut Point, (other,): (Rc<RefCell<Point>>,)| {
+                let other = other.borrow();
+                Ok(to_value((p.x - other.x).hypot(p.y - other.y)))
+            },
+        )
+        .getter("x", |p: &Point| to_value(p.x))
+        .setter("x", |p: &mut Point, x: f64| {
+            p.x = x;
+            Ok(())
+        })
+        .getter("y", |p: &Point| to_value(p.y))
+        .static_method("origin", |(): ()| Ok(to_value("(0, 0)")));
+    engine.register_class(point);
+    engine
+}
+
+#[test]
+fn check_class_methods() {
+    let mut engine = point_engine();
+    assert_eq!(
+        exec_on(&mut engine, "var p = new Point(3, 4); p.norm()"),
+        "5"
+    );
+    assert_eq!(exec_on(&mut engine, "p.scale(2); p.x + ',' + p.y"), "6,8");
+    assert_eq!(exec_on(&mut engine, "p.x = 0; p.x + ',' + p.norm()"), "0,8");
+    assert_eq!(exec_on(&mut engine, "p.y = 1; p.y"), "8");
+    assert_eq!(exec_on(&mut engine, "p.distanceTo(new Point(0, 5))"), "3");
+    assert_eq!(exec_on(&mut engine, "Point.origin()"), "(0, 0)");
+    assert_eq!(
+        exec_on(&mut engine, "Point.name + Point.length + p.norm.length"),
+        "Point20"
+    );
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "p.constructor === Point && Object.getPrototypeOf(p) === Point.prototype"
+        ),
+        "true"
+    );
+}
+
+#[test]
+fn check_class_type_errors() {
+    let mut engine = point_engine();
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "try { Point(1, 2) } catch (e) { e.name + ': ' + e.message }"
+        ),
+        "TypeError: Class constructor Point cannot be invoked without 'new'"
+    );
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "try { Point.prototype.norm.call({}) } catch (e) { e.name + ': ' + e.message }"
+        ),
+        "TypeError: Point.prototype.norm called on incompatible receiver"
+    );
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "var q = new Point(1, 2); try { q.distanceTo({}) } catch (e) { e.name + ': ' + e.message }"
+        ),
+        "TypeError: Value is not an object of the expected class"
+    );
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "'use strict'; var q = new Point(1, 2); try { q.y = 3 } catch (e) { e.name }"
+        ),
+        "TypeError"
+    );
+}
+
+#[test]
+fn check_host_data_from_this() {
+    let mut engine = point_engine();
+    let p = Script::parse("new Point(1, 2)")
+        .and_then(|script| engine.run_script(&script))
+        .unwrap();
+    let data = host_data::<Point>(&p).unwrap();
+    data.borrow_mut().y = 10.0;
+    assert!(host_data::<String>(&p).is_none());
+    engine.set_global("p".to_string(), p);
+    assert_eq!(exec_on(&mut engine, "p.y"), "10");
+}
+
+#[test]
+fn check_native_closures() {
+    let mut engine: Interpreter = Executor::new();
+    let count = Rc::new(Cell::new(0));
+    let counted = count.clone();
+    let counter = new_native_closure(move |_, _, args, _| {
+        counted.set(counted.get() + args.len());
+        Ok(to_value(counted.get() as i32))
+    });
+    engine.set_global("counter".to_string(), counter);
+    assert_eq!(exec_on(&mut engine, "counter(1, 2); counter(3)"), "3");
+    assert_eq!(count.get(), 3);
+}
+
+#[test]
+fn check_accessor_properties() {
+    let mut engine: Interpreter = Executor::new();
+    let src = r#"
+        var o = { v: 1 };
+        Object.defineProperty(o, "double", {
+            get: function () { return this.v * 2; },
+            set: function (x) { this.v = x / 2; }
+        });
+        o.double = 10;
+        o.v + "," + o.double
+    "#;
+    assert_eq!(exec_on(&mut engine, src), "5,10");
+}
@@ -0,0 +1,58 @@
+//! Ways of running source text shared by the integration tests, which each use some of them
+#![allow(dead_code)]
+
+use boa::exec::{self, Executor as _};
+use boa::exec_new::{Executor, Interpreter, Script};
+use boa::syntax::lexer::Lexer;
+use boa::syntax::parser::Parser;
+
+/// Run `src` as a script and return its completion value as a string
+pub fn exec(src: &str) -> String {
+    let mut engine: Interpreter = Executor::new();
+    exec_on(&mut engine, src)
+}
+
+/// Run `src` as a script on an engine and return its completion value as a string
+pub fn exec_on(engine: &mut Interpreter, src: &str) -> String {
+    match Script::parse(src).and_then(|script| engine.run_script(&script)) {
+        Ok(v) => v.to_string(),
+        Err(v) => format!("Error: {}", v),
+    }
+}
+
+/// Parse `src` with ratel, run it and return the completion value of the last statement as a string
+pub fn exec_statements(src: &str) -> String {
+    let mut engine: Interpreter = Executor::new();
+    exec_statements_on(&mut engine, src)
+}
+
+/// Parse `src` with ratel, run each statement on an engine and return the completion value of the
+/// last one as a string
+pub fn exec_statements_on(engine: &mut Interpreter, src: &str) -> String {
+    let module = ratel::parse(src).unwrap();
+    let mut result = String::new();
+    for statement in &module.body() {
+        match engine.run(&statement.item) {
+            Ok(v) => result = v.to_string(),
+            Err(v) => return format!("Error: {}", v),
+        }
+    }
+    result
+}
+
+/// Run `src` on the old interpreter and return the result as a string
+pub fn exec_old(src: &str) -> String {
+    let mut engine: exec::Interpreter = exec::Executor::new();
+    exec_old_on(&mut engine, src)
+}
+
+/// Run `src` on an engine of the old interpreter and return the result as a string
+pub fn exec_old_on(engine: &mut exec::Interpreter, src: &str) -> String {
+    let mut lexer = Lexer::new(src);
+    lexer.lex().unwrap();
+    let expr = Parser::new(lexer.tokens).parse_all().unwrap();
+    match engine.run(&expr) {
+        Ok(v) => v.to_string(),
+        Err(v) => format!("Error: {}", v),
+    }
+}
@@ -0,0 +1,178 @@
+extern crate boa;
+use boa::exec_new::{Executor, Interpreter, Script};
+use boa::js::console::{ConsoleLevel, ConsoleSink};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A sink keeping the messages written to it
+#[derive(Default)]
+struct Capture(RefCell<Vec<(ConsoleLevel, String)>>);
+
+impl ConsoleSink for Capture {
+    fn write(&self, level: ConsoleLevel, message: &str) {
+        self.0.borrow_mut().push((level, message.to_string()));
+    }
+}
+
+/// Run `src` on a new engine and return the messages it wrote to the console
+fn console_output(src: &str) -> Vec<(ConsoleLevel, String)> {
+    let mut engine: Interpreter = Executor::new();
+    let capture = Rc::new(Capture::default());
+    engine.set_console_sink(capture.clone());
+    Script::parse(src)
+        .and_then(|script| engine.run_script(&script))
+        .unwrap();
+    let messages = capture.0.borrow().clone();
+    messages
+}
+
+/// The messages written to the console, without their levels
+fn messages(src: &str) -> Vec<String> {
+    console_output(src).into_iter().map(|(_, m)| m).collect()
+}
+
+#[test]
+fn check_levels_and_format_specifiers() {
+    let output = console_output(
+        r#"
+        console.log("%s has %d items costing %f", "cart", 3.7, 1.5, "extra");
+        console.info("%i%% done %c", 42.9, "color: red");
+        console.debug("%o", [1, "two"]);
+        console.warn(undefined, null, -0, 10n);
+        console.error({ a: 1 });
+    "#,
+    );
+    assert_eq!(
+        output,
+        vec![
+            (
+                ConsoleLevel::Log,
+                "cart has 3 items costing 1.5 extra".to_string()
+            ),
+            (ConsoleLevel::Info, "42% done ".to_string()),
+            (ConsoleLevel::Debug, "[ 1, 'two' ]".to_string()),
+            (ConsoleLevel::Warn, "undefined null -0 10n".to_string()),
+            (ConsoleLevel::Error, "{ a: 1 }".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn check_inspect() {
+    let output = messages(
+        r#"
+        var o = { name: "it's", list: [1, [2, [3, [4]]]], "two words": true, f: function go() {} };
+        o.self = o;
+        console.log(o);
+        console.log({ a: { b: { c: { d: 1 } } } }, [], {});
+        var accessors = {};
+        Object.defineProperty(accessors, "x", { get: function () { return 1; } });
+        Object.defineProperty(accessors, "y", { set: function (v) {} });
+        console.log(accessors, function () {}, new Uint8Array([1, 2]));
+        function Point(x, y) { this.x = x; this.y = y; }
+        var e = new Error("bad");
+        console.log(new Point(1, 2), e.message);
+        console.dir("text");
+        console.dir({ a: { b: { c: { d: 1 } } } }, { depth: null });
+        console.log({ key: "a fairly long string value", other: "another fairly long string value" });
+    "#,
+    );
+    assert_eq!(
+        messages_slice(&output),
+        vec![
+            "{\n  name: \"it's\",\n  list: [ 1, [ 2, [Array] ] ],\n  'two words': true,\n  f: [Function: go],\n  self: [Circular]\n}",
+            "{ a: { b: { c: [Object] } } } [] {}",
+            "{ x: [Getter], y: [Setter] } [Function (anonymous)] Uint8Array(2) [ 1, 2 ]",
+            "Point { x: 1, y: 2 } bad",
+            "'text'",
+            "{ a: { b: { c: { d: 1 } } } }",
+            "{\n  key: 'a fairly long string value',\n  other: 'another fairly long string value'\n}",
+        ]
+    );
+}
+
+/// Borrow the messages as string slices, for comparing them with literals
+fn messages_slice(output: &[String]) -> Vec<&str> {
+    output.iter().map(|m| m.as_str()).collect()
+}
+
+#[test]
+fn check_counters_groups_and_assertions() {
+    let output = console_output(
+        r#"
+        console.count(); console.count(); console.count("x");
+        console.countReset(); console.count(); console.countReset("missing");
+        console.group("outer");
+        console.log("a\nb");
+        console.group();
+        console.assert(1 === 2, "expected %d", 2);
+        console.assert(true, "not shown");
+        console.groupEnd();
+        console.groupEnd();
+        console.groupEnd();
+        console.assert(false);
+    "#,
+    );
+    assert_eq!(
+        output,
+        vec![
+            (ConsoleLevel::Info, "default: 1".to_string()),
+            (ConsoleLevel::Info, "default: 2".to_string()),
+            (ConsoleLevel::Info, "x: 1".to_string()),
+            (ConsoleLevel::Info, "default: 1".to_string()),
+            (
+                ConsoleLevel::Warn,
+                "Count for 'missing' does not exist".to_string()
+            ),
+            (ConsoleLevel::Log, "outer".to_string()),
+            (ConsoleLevel::Log, "  a\n  b".to_string()),
+            (
+                ConsoleLevel::Error,
+                "    Assertion failed: expected 2".to_string()
+            ),
+            (ConsoleLevel::Error, "Assertion failed".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn check_timers_trace_and_table() {
+    let output = console_output(
+        r#"
+        console.time("t");
+        console.time("t");
+        console.timeLog("t", "step");
+        console.timeEnd("t");
+        console.timeEnd("t");
+        function inner() { console.trace("here"); }
+        inner();
+        console.table([{ a: 1, b: "Y" }, { a: 2, c: true }, 3]);
+    "#,
+    );
+    assert_eq!(
+        output[0],
+        (ConsoleLevel::Warn, "Timer 't' already exists".to_string())
+    );
+    assert_eq!(output[1].0, ConsoleLevel::Info);
+    assert!(output[1].1.starts_with("t: ") && output[1].1.ends_with("ms step"));
+    assert!(output[2].1.starts_with("t: ") && output[2].1.ends_with("ms"));
+    assert_eq!(
+        output[3],
+        (ConsoleLevel::Warn, "Timer 't' does not exist".to_string())
+    );
+    assert_eq!(output[4].0, ConsoleLevel::Trace);
+    assert!(output[4].1.starts_with("Trace: here\n    at inner ("));
+    assert_eq!(
+        output[5].1,
+        [
+            "┌─────────┬───┬─────┬──────┬────────┐",
+            "│ (index) │ a │ b   │ c    │ Values │",
+            "├─────────┼───┼─────┼──────┼────────┤",
+            "│ 0       │ 1 │ 'Y' │      │        │",
+            "│ 1       │ 2 │     │ true │        │",
+            "│ 2       │   │     │      │ 3      │",
+            "└─────────┴───┴─────┴──────┴────────┘",
+        ]
+        .join("\n")
+    );
+}
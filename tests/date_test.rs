@@ -0,0 +1,180 @@
+extern crate boa;
+mod common;
+use boa::exec::{Executor, Interpreter};
+use boa::js::date::TimeZone;
+use common::exec_old_on;
+use std::str::FromStr;
+
+/// Run `src` in an engine whose local time zone is `tz` and return the result as a string
+fn exec_in(tz: &str, src: &str) -> String {
+    let mut engine: Interpreter = Executor::new();
+    engine.set_time_zone(TimeZone::from_str(tz).unwrap());
+    exec_old_on(&mut engine, src)
+}
+
+#[test]
+fn check_iso_parsing() {
+    assert_eq!(
+        exec_in("UTC", "Date.parse('2020-01-15T10:30:00.250Z')"),
+        "1579084200250"
+    );
+    // Date-only forms are UTC, date-time forms without an offset are local
+    assert_eq!(exec_in("EST5", "Date.parse('2020-01-15')"), "1579046400000");
+    assert_eq!(
+        exec_in("EST5", "Date.parse('2020-01-15T00:00')"),
+        "1579064400000"
+    );
+    assert_eq!(
+        exec_in("UTC", "Date.parse('2020-01-15T10:30+01:00')"),
+        "1579080600000"
+    );
+    assert_eq!(
+        exec_in("UTC", "Date.parse('+002020-01-15T00:00:00Z')"),
+        "1579046400000"
+    );
+    assert_eq!(exec_in("UTC", "Date.parse('2020-02-30')"), "NaN");
+}
+
+#[test]
+fn check_rfc_2822_parsing() {
+    assert_eq!(
+        exec_in("UTC", "Date.parse('Wed, 15 Jan 2020 10:30:00 +0100')"),
+        "1579080600000"
+    );
+    assert_eq!(
+        exec_in("UTC", "Date.parse('15 Jan 2020 05:30:00 EST')"),
+        "1579084200000"
+    );
+    assert_eq!(
+        exec_in(
+            "UTC",
+            "Date.parse('Wed Jan 15 2020 11:30:00 GMT+0100 (CET)')"
+        ),
+        "1579084200000"
+    );
+}
+
+#[test]
+fn check_local_getters_follow_daylight_saving() {
+    let tz = "CET-1CEST,M3.5.0,M10.5.0/3";
+    let winter = "var d = new Date('2020-01-15T10:00:00Z');";
+    let summer = "var d = new Date('2020-07-15T10:00:00Z');";
+    assert_eq!(exec_in(tz, &format!("{} d.getHours()", winter)), "11");
+    assert_eq!(
+        exec_in(tz, &format!("{} d.getTimezoneOffset()", winter)),
+        "-60"
+    );
+    assert_eq!(exec_in(tz, &format!("{} d.getHours()", summer)), "12");
+    assert_eq!(exec_in(tz, &format!("{} d.getUTCHours()", summer)), "10");
+    assert_eq!(
+        exec_in(tz, &format!("{} d.toString()", summer)),
+        "Wed Jul 15 2020 12:00:00 GMT+0200 (CEST)"
+    );
+    // 02:30 on the last Sunday of March doesn't exist and is read with the winter offset
+    assert_eq!(
+        exec_in(tz, "var d = new Date(2020, 2, 29, 2, 30); d.toISOString()"),
+        "2020-03-29T01:30:00.000Z"
+    );
+}
+
+#[test]
+fn check_constructor_overloads() {
+    assert_eq!(
+        exec_in(
+            "UTC",
+            "var d = new Date(2020, 1, 15, 10, 30); d.toISOString()"
+        ),
+        "2020-02-15T10:30:00.000Z"
+    );
+    assert_eq!(
+        exec_in("UTC", "var d = new Date(99, 11); d.toISOString()"),
+        "1999-12-01T00:00:00.000Z"
+    );
+    assert_eq!(
+        exec_in(
+            "UTC",
+            "var d = new Date(1); var e = new Date(d); e.getTime()"
+        ),
+        "1"
+    );
+    assert_eq!(
+        exec_in("UTC", "var d = new Date('nonsense'); d.toString()"),
+        "Invalid Date"
+    );
+    assert_eq!(exec_in("UTC", "typeof Date()"), "string");
+}
+
+#[test]
+fn check_setters() {
+    let tz = "EST5EDT";
+    assert_eq!(
+        exec_in(
+            tz,
+            "var d = new Date(2020, 2, 31); d.setMonth(3); d.getDate()"
+        ),
+        "1"
+    );
+    assert_eq!(
+        exec_in(
+            tz,
+            "var d = new Date(2020, 1, 1); d.setHours(25, 30); d.toISOString()"
+        ),
+        "2020-02-02T06:30:00.000Z"
+    );
+    assert_eq!(
+        exec_in(
+            "UTC",
+            "var d = new Date(NaN); d.setFullYear(2021); d.toISOString()"
+        ),
+        "2021-01-01T00:00:00.000Z"
+    );
+}
+
+#[test]
+fn check_string_conversions() {
+    let src = "var d = new Date(Date.UTC(2020, 1, 15, 10, 1, 2, 7));";
+    assert_eq!(
+        exec_in("UTC", &format!("{} d.toUTCString()", src)),
+        "Sat, 15 Feb 2020 10:01:02 GMT"
+    );
+    assert_eq!(
+        exec_in("UTC", &format!("{} d.toJSON()", src)),
+        "2020-02-15T10:01:02.007Z"
+    );
+    assert_eq!(
+        exec_in("<+0530>-5:30", &format!("{} d.toDateString()", src)),
+        "Sat Feb 15 2020"
+    );
+    assert_eq!(
+        exec_in("UTC", "var d = new Date(1); d.setTime(NaN); d.toJSON()"),
+        "null"
+    );
+}
+
+#[test]
+fn check_time_zone_rules() {
+    assert_eq!(
+        TimeZone::fixed(330),
+        TimeZone::from_str("<+0530>-5:30").unwrap()
+    );
+    assert!(TimeZone::from_str("CET-1CEST,M3.5.0").is_err());
+    let sydney = TimeZone::from_str("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+    // 2020-01-15 and 2020-07-15 at noon UTC
+    assert_eq!(sydney.offset_at(1579089600000.0), 11.0 * 3_600_000.0);
+    assert_eq!(sydney.offset_at(1594814400000.0), 10.0 * 3_600_000.0);
+}
+
+#[test]
+fn check_internal_slots_are_hidden() {
+    assert_eq!(
+        exec_in(
+            "UTC",
+            "Date.prototype['[[TimeZone]]'] = 'EST5'; var d = new Date(1); d.getHours()"
+        ),
+        "0"
+    );
+    assert_eq!(
+        exec_in("UTC", "Date.prototype.getTime.call({'[[DateValue]]': 5})"),
+        "Error: this is not a Date object."
+    );
+}
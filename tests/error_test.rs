@@ -0,0 +1,195 @@
+extern crate boa;
+mod common;
+use boa::exec_new::{Executor, Interpreter, Script};
+use common::exec_old;
+
+/// Run `src` as a script and return its completion value as a string
+fn exec(src: &str) -> String {
+    let mut engine: Interpreter = Executor::new();
+    let result =
+        Script::parse_with_name("test.js", src).and_then(|script| engine.run_script(&script));
+    match result {
+        Ok(v) => v.to_string(),
+        Err(v) => format!("Error: {}", v),
+    }
+}
+
+#[test]
+fn check_hierarchy() {
+    for &(name, args) in &[
+        ("EvalError", "'m'"),
+        ("RangeError", "'m'"),
+        ("ReferenceError", "'m'"),
+        ("SyntaxError", "'m'"),
+        ("TypeError", "'m'"),
+        ("URIError", "'m'"),
+        ("AggregateError", "[], 'm'"),
+    ] {
+        let src = format!(
+            "var e = new {0}({1}); (e instanceof {0}) + ' ' + (e instanceof Error) + ' ' + e.name + ' ' + (Object.getPrototypeOf({0}) === Error)",
+            name, args
+        );
+        assert_eq!(exec(&src), format!("true true {} true", name));
+    }
+    assert_eq!(exec("(new RangeError('x')) instanceof TypeError"), "false");
+    assert_eq!(
+        exec("var e = TypeError('called'); (e instanceof TypeError) + e.message"),
+        "truecalled"
+    );
+    assert_eq!(
+        exec("TypeError.prototype.constructor === TypeError"),
+        "true"
+    );
+    assert_eq!(exec("Error.length + AggregateError.length"), "3");
+}
+
+#[test]
+fn check_message_and_cause() {
+    assert_eq!(exec("(new Error()).message === ''"), "true");
+    assert_eq!(exec("(new Error(42)).message === '42'"), "true");
+    assert_eq!(exec("(new Error('a', {cause: 'b'})).cause"), "b");
+    assert_eq!(
+        exec("(new Error('a', {})).hasOwnProperty('cause')"),
+        "false"
+    );
+    assert_eq!(exec("(new TypeError('a', {cause: 0})).cause"), "0");
+    assert_eq!(
+        exec("var e = new AggregateError([1, 2], 'both', {cause: 3}); e.errors.length + e.message + e.cause"),
+        "2both3"
+    );
+    assert_eq!(
+        exec("(new AggregateError([])).errors instanceof Array"),
+        "true"
+    );
+}
+
+#[test]
+fn check_to_string() {
+    assert_eq!(exec("(new TypeError('bad')).toString()"), "TypeError: bad");
+    assert_eq!(exec("(new Error()).toString()"), "Error");
+    assert_eq!(
+        exec("Error.prototype.toString.call({message: 'm'})"),
+        "Error: m"
+    );
+    assert_eq!(
+        exec("Error.prototype.toString.call({name: '', message: 'm'})"),
+        "m"
+    );
+    assert_eq!(exec("'' + new URIError('u')"), "URIError: u");
+    assert_eq!(exec("throw new RangeError('r')"), "Error: RangeError: r");
+}
+
+#[test]
+fn check_stack() {
+    assert_eq!(
+        exec("function outer() { return inner(); }\nfunction inner() {\n  return (new Error('boom')).stack;\n}\nouter()"),
+        "Error: boom\n    at inner (test.js:3:11)\n    at outer (test.js:1:27)\n    at test.js:5:1"
+    );
+    assert_eq!(
+        exec("(function() { return Error('e').stack; })()"),
+        "Error: e\n    at test.js:1:22\n    at test.js:1:2"
+    );
+}
+
+#[test]
+fn check_thrown_by_interpreter() {
+    assert_eq!(
+        exec("try { missing; } catch (e) { (e instanceof ReferenceError) + ' ' + e.message }"),
+        "true missing is not defined"
+    );
+    assert_eq!(exec("typeof missing"), "undefined");
+    assert_eq!(
+        exec("try { missing += 1; } catch (e) { e.name }"),
+        "ReferenceError"
+    );
+    assert_eq!(
+        exec("try { (1)(); } catch (e) { (e instanceof TypeError) + ' ' + e.message }"),
+        "true number is not a function"
+    );
+    assert_eq!(
+        exec("function f() {\n  undefined();\n}\ntry { f(); } catch (e) { e.stack }"),
+        "TypeError: undefined is not a function\n    at f (test.js:2:3)\n    at test.js:4:7"
+    );
+    assert_eq!(
+        exec("missing"),
+        "Error: ReferenceError: missing is not defined"
+    );
+    assert_eq!(
+        exec("try { null.x; } catch (e) { (e instanceof TypeError) + ' ' + e.message }"),
+        "true Cannot read properties of null (reading 'x')"
+    );
+    assert_eq!(
+        exec("function f(o) {\n  return o['y'];\n}\ntry { f(); } catch (e) { e.stack }"),
+        "TypeError: Cannot read properties of undefined (reading 'y')\n    at f (test.js:2:10)\n    at test.js:4:7"
+    );
+    assert_eq!(
+        exec("try { var u; u.z = 1; } catch (e) { e.message }"),
+        "Cannot set properties of undefined (setting 'z')"
+    );
+    assert_eq!(exec("try { 'a' in 1; } catch (e) { e.name }"), "TypeError");
+    assert_eq!(
+        exec("try { ({}) instanceof 1; } catch (e) { e.name }"),
+        "TypeError"
+    );
+    assert_eq!(
+        exec("try { 1n / 0n; } catch (e) { (e instanceof RangeError) + ' ' + e.message }"),
+        "true Division by zero"
+    );
+}
+
+#[test]
+fn check_try() {
+    assert_eq!(exec("try { throw 1; } catch (e) { e + 1 }"), "2");
+    assert_eq!(exec("try { throw 1; } catch (e) { var x = e; } x"), "1");
+    assert_eq!(
+        exec("var e = 'outer'; try { throw 1; } catch (e) {} e"),
+        "outer"
+    );
+    assert_eq!(
+        exec("function f() { try { return 1; } finally { return 2; } } f()"),
+        "2"
+    );
+    assert_eq!(
+        exec("function f() { try { throw 1; } finally { return 2; } } f()"),
+        "2"
+    );
+    assert_eq!(exec("var n = 0; try { n = 1; } finally { n += 1; } n"), "2");
+    assert_eq!(exec("try { throw 1; } finally { 2; }"), "Error: 1");
+    assert_eq!(
+        exec("try { try { throw 1; } catch (e) { throw e + 1; } } catch (e) { e }"),
+        "2"
+    );
+}
+
+#[test]
+fn check_old_interpreter() {
+    assert_eq!(
+        exec_old("var e = new TypeError('y'); e.toString()"),
+        "TypeError: y"
+    );
+    assert_eq!(exec_old("var e = Error('z'); e.toString()"), "Error: z");
+}
+
+#[test]
+fn check_thrown_by_natives() {
+    assert_eq!(
+        exec("try { (1).toFixed(101); } catch (e) { e.name }"),
+        "RangeError"
+    );
+    assert_eq!(
+        exec("try { new ArrayBuffer(-1); } catch (e) { e.name }"),
+        "RangeError"
+    );
+    assert_eq!(
+        exec("try { Date.prototype.getTime.call({}); } catch (e) { e.name }"),
+        "TypeError"
+    );
+    assert_eq!(
+        exec("try { Function.prototype.apply.call(function() {}, null, 1); } catch (e) { e.name }"),
+        "TypeError"
+    );
+    assert_eq!(
+        exec("try { new AggregateError(1); } catch (e) { e.name }"),
+        "TypeError"
+    );
+}
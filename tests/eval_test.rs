@@ -0,0 +1,147 @@
+extern crate boa;
+mod common;
+use boa::exec_new::{Executor, Interpreter};
+use common::{exec, exec_on};
+
+#[test]
+fn check_eval() {
+    assert_eq!(exec("eval('1 + 2')"), "3");
+    assert_eq!(exec("eval('if (true) { 4; } else { 5; }')"), "4");
+    assert_eq!(exec("eval(5)"), "5");
+    assert_eq!(exec("eval() === undefined"), "true");
+    assert_eq!(exec("eval('function g() { return 9; }'); g()"), "9");
+    assert_eq!(
+        exec("try { eval('1 +'); } catch (e) { e.name + ': ' + e.message }"),
+        "SyntaxError: Unexpected end of input"
+    );
+}
+
+#[test]
+fn check_direct_eval() {
+    assert_eq!(
+        exec("var x = 1; function f() { var x = 2; return eval('x'); } f()"),
+        "2"
+    );
+    assert_eq!(
+        exec("function f() { eval('var y = 5'); return y; } f()"),
+        "5"
+    );
+    assert_eq!(exec("function f(a) { eval('a = 3'); return a; } f(1)"), "3");
+    assert_eq!(
+        exec("function f() { return eval('this'); } f.call('t')"),
+        "t"
+    );
+    assert_eq!(
+        exec("function f() { return eval('arguments.length'); } f(1, 2, 3)"),
+        "3"
+    );
+    assert_eq!(
+        exec("try { throw 1; } catch (e) { eval('var z = e'); } z"),
+        "1"
+    );
+}
+
+#[test]
+fn check_strict_eval() {
+    assert_eq!(
+        exec("function f() { 'use strict'; eval('var y = 5'); return typeof y; } f()"),
+        "undefined"
+    );
+    assert_eq!(
+        exec("function f() { eval('\"use strict\"; var y = 5'); return typeof y; } f()"),
+        "undefined"
+    );
+    assert_eq!(exec("'use strict'; var x = 1; eval('x = 2'); x"), "2");
+    assert_eq!(
+        exec("'use strict'; try { eval('var implements'); } catch (e) { e.name }"),
+        "SyntaxError"
+    );
+    // Strict mode code is still a script, which can't export anything
+    assert_eq!(
+        exec("try { eval('\"use strict\"; export var x'); } catch (e) { e.name }"),
+        "SyntaxError"
+    );
+    assert_eq!(
+        exec("'use strict'; try { eval('export var x'); } catch (e) { e.name }"),
+        "SyntaxError"
+    );
+}
+
+#[test]
+fn check_indirect_eval() {
+    assert_eq!(
+        exec("var x = 1; function f() { var x = 2; var e = eval; return e('x'); } f()"),
+        "1"
+    );
+    assert_eq!(
+        exec("var x = 1; function f() { var x = 2; return (0, eval)('x'); } f()"),
+        "1"
+    );
+    assert_eq!(
+        exec("function f() { var e = eval; e('var w = 4'); } f(); w"),
+        "4"
+    );
+    assert_eq!(
+        exec("function f() { 'use strict'; return eval.call(null, 'this') === undefined; } f()"),
+        "false"
+    );
+}
+
+#[test]
+fn check_function_constructor() {
+    assert_eq!(exec("(new Function('a', 'b', 'return a + b'))(1, 2)"), "3");
+    assert_eq!(exec("Function('a, b', 'return a * b')(3, 4)"), "12");
+    assert_eq!(exec("Function('return this')() === this"), "true");
+    assert_eq!(
+        exec("var x = 1; function f() { var x = 2; return Function('return x')(); } f()"),
+        "1"
+    );
+    assert_eq!(exec("Function('a', 'return a').name"), "anonymous");
+    assert_eq!(
+        exec("Function('a', 'return a').toString()"),
+        "function anonymous(a\n) {\nreturn a\n}"
+    );
+    assert_eq!(
+        exec("Function().length + Function('a', 'b', '').length"),
+        "2"
+    );
+    assert_eq!(
+        exec("Function('a', 'return a') instanceof Function"),
+        "true"
+    );
+    assert_eq!(
+        exec("try { Function('}); (function() {'); } catch (e) { e.name }"),
+        "SyntaxError"
+    );
+    assert_eq!(
+        exec("try { Function('a', 'a', '\"use strict\"'); } catch (e) { e.name }"),
+        "SyntaxError"
+    );
+}
+
+#[test]
+fn check_code_generation_switch() {
+    let mut engine: Interpreter = Executor::new();
+    engine.allow_code_generation(false);
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "try { eval('1'); } catch (e) { (e instanceof EvalError) + ' ' + e.message }"
+        ),
+        "true Code generation from strings disallowed for this context"
+    );
+    assert_eq!(
+        exec_on(&mut engine, "try { (0, eval)('1'); } catch (e) { e.name }"),
+        "EvalError"
+    );
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "try { Function('return 1'); } catch (e) { e.name }"
+        ),
+        "EvalError"
+    );
+    assert_eq!(exec_on(&mut engine, "eval(2)"), "2");
+    engine.allow_code_generation(true);
+    assert_eq!(exec_on(&mut engine, "eval('1 + 1')"), "2");
+}
@@ -0,0 +1,104 @@
+extern crate boa;
+mod common;
+use boa::exec_new::event_loop::EventLoop;
+use boa::exec_new::{Executor, Interpreter};
+use common::exec_on;
+
+/// Make an engine with an event loop on a fake clock installed in its realm
+fn engine_with_loop() -> (Interpreter, EventLoop) {
+    let engine: Interpreter = Executor::new();
+    let event_loop = EventLoop::with_fake_clock();
+    event_loop.install(&engine.realm());
+    (engine, event_loop)
+}
+
+#[test]
+fn check_task_order() {
+    let (mut engine, event_loop) = engine_with_loop();
+    let src = r#"
+        var log = "";
+        setTimeout(function (a, b) { log += "t30" + a + b + " "; }, 30, 1, 2);
+        setTimeout(function () {
+            log += "t10 ";
+            queueMicrotask(function () { log += "m2 "; });
+        }, 10);
+        setTimeout(function () { log += "t10b "; }, 10);
+        queueMicrotask(function () {
+            log += "m1 ";
+            queueMicrotask(function () { log += "m1b "; });
+        });
+        log += "sync ";
+    "#;
+    exec_on(&mut engine, src);
+    event_loop.run(&mut engine).unwrap();
+    assert_eq!(
+        exec_on(&mut engine, "log"),
+        "sync m1 m1b t10 m2 t10b t3012 "
+    );
+    assert_eq!(event_loop.now(), 30.0);
+    assert!(event_loop.is_idle());
+}
+
+#[test]
+fn check_intervals_and_clearing() {
+    let (mut engine, event_loop) = engine_with_loop();
+    let src = r#"
+        var ticks = 0;
+        var never = setTimeout(function () { ticks = -100; }, 5);
+        clearTimeout(never);
+        var id = setInterval(function () {
+            ticks++;
+            if (ticks === 3) { clearInterval(id); }
+        }, 100);
+        typeof id
+    "#;
+    assert_eq!(exec_on(&mut engine, src), "number");
+    event_loop.advance(&mut engine, 250.0).unwrap();
+    assert_eq!(exec_on(&mut engine, "ticks"), "2");
+    assert_eq!(event_loop.now(), 250.0);
+    assert!(!event_loop.is_idle());
+    event_loop.run(&mut engine).unwrap();
+    assert_eq!(exec_on(&mut engine, "ticks"), "3");
+    assert_eq!(event_loop.now(), 300.0);
+}
+
+#[test]
+fn check_delays_out_of_range() {
+    let (mut engine, event_loop) = engine_with_loop();
+    let src = r#"
+        var log = "";
+        setTimeout(function () { log += "infinite "; }, Infinity);
+        setTimeout(function () { log += "too long "; }, 2147483648);
+        setTimeout(function () { log += "nan "; }, NaN);
+        setTimeout(function () { log += "longest "; }, 2147483647);
+    "#;
+    exec_on(&mut engine, src);
+    event_loop.advance(&mut engine, 1.0).unwrap();
+    assert_eq!(exec_on(&mut engine, "log"), "infinite too long nan ");
+    event_loop.run(&mut engine).unwrap();
+    assert_eq!(exec_on(&mut engine, "log"), "infinite too long nan longest ");
+    assert_eq!(event_loop.now(), 2147483647.0);
+}
+
+#[test]
+fn check_errors() {
+    let (mut engine, event_loop) = engine_with_loop();
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "try { queueMicrotask(1); } catch (e) { e.name }"
+        ),
+        "TypeError"
+    );
+    let src = r#"
+        var after = false;
+        setTimeout(function () { throw new Error("boom"); }, 1);
+        setTimeout(function () { after = true; }, 2);
+    "#;
+    exec_on(&mut engine, src);
+    let error = event_loop.run(&mut engine).unwrap_err();
+    assert_eq!(error.get_field_slice("message").to_string(), "boom");
+    assert_eq!(exec_on(&mut engine, "after"), "false");
+    event_loop.run(&mut engine).unwrap();
+    assert_eq!(exec_on(&mut engine, "after"), "true");
+}
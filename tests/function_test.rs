@@ -0,0 +1,188 @@
+extern crate boa;
+mod common;
+use common::{exec, exec_old};
+
+#[test]
+fn check_functions() {
+    assert_eq!(exec("function add(a, b) { return a + b; } add(1, 2)"), "3");
+    assert_eq!(exec("add(1, 2); function add(a, b) { return a + b; }"), "3");
+    assert_eq!(
+        exec("function counter() { var n = 0; return function() { n += 1; return n; }; } var c = counter(); c(); c()"),
+        "2"
+    );
+    assert_eq!(
+        exec("var f = function fact(n) { return n <= 1 ? 1 : n * fact(n - 1); }; f(5)"),
+        "120"
+    );
+    assert_eq!(
+        exec("var f = function(a, b = a * 2, ...rest) { return a + b + rest.length; }; f(1) + f(1, 1, 0, 0)"),
+        "7"
+    );
+    assert_eq!(exec("var f = (a, b = a + 1) => a * b; f(2)"), "6");
+    assert_eq!(
+        exec("function P(x) { this.x = x; } var p = new P(3); p.x"),
+        "3"
+    );
+    assert_eq!(
+        exec("function P() { this.x = 1; } (new P()) instanceof P"),
+        "true"
+    );
+    assert_eq!(
+        exec("function P() { this.m = function() { return 4; }; } new P().m() + new P()['m']()"),
+        "8"
+    );
+    assert_eq!(
+        exec("var o = {P: function(x) { this.x = x; }}; new o.P(5).x"),
+        "5"
+    );
+    assert_eq!(
+        exec("function F() { return function() { this.y = 6; }; } new new F()().y"),
+        "6"
+    );
+    assert_eq!(
+        exec("var o = {x: 2, f: function() { return () => this.x; }}; o.f()()"),
+        "2"
+    );
+    assert_eq!(
+        exec("var a = () => 1; new a()"),
+        "Error: TypeError: a is not a constructor"
+    );
+}
+
+#[test]
+fn check_name_and_length() {
+    assert_eq!(exec("function f(a, b) {} f.name + f.length"), "f2");
+    assert_eq!(exec("var g = function() {}; g.name"), "g");
+    assert_eq!(exec("var h = (a, b = 1, c) => a; h.name + h.length"), "h1");
+    assert_eq!(exec("var o = {m(x) {}}; o.m.name + o.m.length"), "m1");
+    assert_eq!(exec("(function(...a) {}).length"), "0");
+}
+
+#[test]
+fn check_call_and_apply() {
+    assert_eq!(
+        exec("function f(a) { return this.x + a; } f.call({x: 1}, 2)"),
+        "3"
+    );
+    assert_eq!(
+        exec("function f(a, b) { return this.x + a + b; } f.apply({x: 1}, [2, 3])"),
+        "6"
+    );
+    assert_eq!(
+        exec("function f() { return arguments.length; } f.apply(null)"),
+        "0"
+    );
+    assert_eq!(
+        exec("function f() {} f.apply(null, 1)"),
+        "Error: TypeError: CreateListFromArrayLike called on non-object"
+    );
+    assert_eq!(
+        exec("function f() { return this; } f.call(undefined) === this"),
+        "true"
+    );
+    assert_eq!(
+        exec("function f() { 'use strict'; return this; } f.call(undefined)"),
+        "undefined"
+    );
+    assert_eq!(exec("Math.max.apply(null, [1, 3, 2])"), "3");
+}
+
+#[test]
+fn check_bind() {
+    assert_eq!(
+        exec("function f(a, b) { return this.x + a + b; } f.bind({x: 1}, 2)(3)"),
+        "6"
+    );
+    assert_eq!(
+        exec("function f(a, b, c) {} var g = f.bind(null, 1); g.name + g.length"),
+        "bound f2"
+    );
+    assert_eq!(exec("function f(a) {} f.bind(null, 1, 2).length"), "0");
+    assert_eq!(
+        exec("function P(a, b) { this.sum = a + b; } var B = P.bind(null, 1); var p = new B(2); p.sum + ' ' + (p instanceof P)"),
+        "3 true"
+    );
+    assert_eq!(
+        exec("var o = {}; o.bind = Function.prototype.bind; o.bind()"),
+        "Error: TypeError: Bind must be called on a function"
+    );
+}
+
+#[test]
+fn check_to_string() {
+    assert_eq!(
+        exec("function  add(a, b) { return a + b; } add.toString()"),
+        "function  add(a, b) { return a + b; }"
+    );
+    assert_eq!(exec("var f = (a) => a * 2; f.toString()"), "(a) => a * 2");
+    assert_eq!(exec("Math.max.toString()"), "function () { [native code] }");
+    assert_eq!(
+        exec("function f() {} f.bind(null).toString()"),
+        "function () { [native code] }"
+    );
+    assert_eq!(
+        exec("Function.prototype.toString.call({})"),
+        "Error: TypeError: Function.prototype.toString requires that 'this' be a Function"
+    );
+}
+
+#[test]
+fn check_arguments() {
+    assert_eq!(
+        exec("function f() { return arguments.length; } f(1, 2, 3)"),
+        "3"
+    );
+    assert_eq!(
+        exec("function f(a) { arguments[0] = 9; return a; } f(1)"),
+        "9"
+    );
+    assert_eq!(
+        exec("function f(a) { a = 9; return arguments[0]; } f(1)"),
+        "9"
+    );
+    assert_eq!(
+        exec("function f(a) { 'use strict'; a = 9; return arguments[0]; } f(1)"),
+        "1"
+    );
+    assert_eq!(
+        exec("function f(a = 0) { a = 9; return arguments[0]; } f(1)"),
+        "1"
+    );
+    assert_eq!(
+        exec("function f(a) { arguments[1] = 9; return a; } f()"),
+        "undefined"
+    );
+    assert_eq!(
+        exec("function f(a) { delete arguments[0]; arguments[0] = 9; return a; } f(1)"),
+        "1"
+    );
+    assert_eq!(
+        exec("function f() { return arguments.callee === f; } f()"),
+        "true"
+    );
+    assert_eq!(
+        exec("function f() { return (() => arguments[0])(); } f(4)"),
+        "4"
+    );
+}
+
+#[test]
+fn check_old_interpreter() {
+    assert_eq!(
+        exec_old("function f(a) { return arguments.length; } f(1, 2)"),
+        "2"
+    );
+    assert_eq!(
+        exec_old("function f(a) { a = 5; return arguments['0']; } f(1)"),
+        "5"
+    );
+    assert_eq!(
+        exec_old("function f(a, b) { return b; } f.call(null, 1, 2)"),
+        "2"
+    );
+    assert_eq!(
+        exec_old("function f(a, b) { return a; } f.bind(null, 7)()"),
+        "7"
+    );
+    assert_eq!(exec_old("function f(a) { return a; } f()"), "undefined");
+}
@@ -0,0 +1,85 @@
+extern crate boa;
+mod common;
+use boa::exec_new::{Executor, Interpreter};
+use boa::gc::{self, Gc, GcCell, GcConfig};
+use boa::js::value::{ToValue, ValueData};
+use common::exec_on;
+
+#[test]
+fn check_cycles_are_collected() {
+    gc::collect();
+    let before = gc::heap_stats();
+    {
+        let a = ValueData::new_obj(None);
+        let b = ValueData::new_obj(None);
+        a.set_field_slice("other", b.clone());
+        b.set_field_slice("other", a.clone());
+        assert_eq!(gc::heap_stats().objects, before.objects + 2);
+    }
+    gc::collect();
+    let after = gc::heap_stats();
+    assert_eq!(after.objects, before.objects);
+    assert_eq!(after.collections, before.collections + 1);
+    assert_eq!(after.collected, before.collected + 2);
+}
+
+#[test]
+fn check_held_values_are_roots() {
+    let held = ValueData::new_obj(None);
+    let inner = ValueData::new_obj(None);
+    inner.set_field_slice("s", "kept".to_value());
+    held.set_field_slice("inner", inner);
+    held.set_field_slice("self", held.clone());
+    gc::collect();
+    assert_eq!(
+        held.get_field_slice("inner")
+            .get_field_slice("s")
+            .to_string(),
+        "kept"
+    );
+    assert!(gc::heap_stats().roots >= 1);
+}
+
+#[test]
+fn check_collection_while_borrowed() {
+    let cell = Gc::new(GcCell::new(vec![ValueData::new_obj(None)]));
+    {
+        let mut slots = cell.borrow_mut();
+        slots[0].set_field_slice("n", 1.to_value());
+        assert!(cell.try_borrow().is_err());
+        assert!(cell.try_borrow_mut().is_err());
+        slots.push(ValueData::new_obj(None));
+        gc::collect();
+    }
+    gc::collect();
+    assert_eq!(cell.borrow().len(), 2);
+    assert_eq!(cell.borrow()[0].get_field_slice("n").to_string(), "1");
+    assert!(cell.try_borrow_mut().is_ok());
+}
+
+#[test]
+fn check_collections_follow_the_threshold() {
+    gc::configure(GcConfig {
+        threshold: 16 * 1024,
+        growth: 1.5,
+    });
+    let mut engine: Interpreter = Executor::new();
+    let before = gc::heap_stats();
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "var i = 0; var last; while (i < 2000) { var o = {i: i}; o.self = o; last = o; i++; } last.self.i"
+        ),
+        "1999"
+    );
+    let after = gc::heap_stats();
+    assert!(after.collections > before.collections);
+    assert!(after.collected > 0);
+    assert!(after.threshold >= 16 * 1024);
+    gc::configure(GcConfig::default());
+    gc::collect();
+    assert_eq!(
+        exec_on(&mut engine, "last.self.i + Math.floor(1.5)"),
+        "2000"
+    );
+}
@@ -0,0 +1,156 @@
+extern crate boa;
+mod common;
+use boa::exec_new::host::{self, Permissions};
+use boa::exec_new::{Executor, Interpreter, Termination};
+use boa::js::value::to_value;
+use common::exec_on;
+use std::fs;
+use std::path::PathBuf;
+
+/// Make an engine with the host functions installed, and some permissions
+fn engine_with(permissions: Permissions) -> Interpreter {
+    let mut engine: Interpreter = Executor::new();
+    host::install(
+        &engine.realm(),
+        vec![
+            "boa".to_string(),
+            "tool.js".to_string(),
+            "--flag".to_string(),
+        ],
+    );
+    engine.set_permissions(permissions);
+    engine
+}
+
+/// Make an empty directory for a test to use
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("boa_host_test_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn check_denied_by_default() {
+    let dir = scratch_dir("denied");
+    fs::write(dir.join("a.txt"), "secret").unwrap();
+    let mut engine = engine_with(Permissions::new());
+    engine.set_global("dir".to_string(), to_value(dir.to_string_lossy().as_ref()));
+    let src = r#"
+        var codes = "";
+        function attempt(f) {
+            try { f(); codes += "ok "; } catch (e) { codes += e.code + " "; }
+        }
+        attempt(function () { fs.readFile(dir + "/a.txt"); });
+        attempt(function () { fs.writeFile(dir + "/b.txt", "x"); });
+        attempt(function () { fs.readDir(dir); });
+        attempt(function () { process.env.get("PATH"); });
+        attempt(function () { process.stdin.readLine(); });
+        attempt(function () { process.exit(1); });
+        codes + process.argv[2]
+    "#;
+    let denied = "ERR_ACCESS_DENIED ";
+    assert_eq!(
+        exec_on(&mut engine, src),
+        format!("{}--flag", denied.repeat(6))
+    );
+    assert!(!dir.join("b.txt").exists());
+    assert_eq!(engine.termination(), None);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn check_granted_paths() {
+    let dir = scratch_dir("granted");
+    let inside = dir.join("inside");
+    fs::create_dir_all(&inside).unwrap();
+    fs::write(dir.join("outside.txt"), "no").unwrap();
+    let mut permissions = Permissions::new();
+    permissions.allow_read(&inside).allow_write(&inside);
+    let mut engine = engine_with(permissions);
+    engine.set_global(
+        "dir".to_string(),
+        to_value(inside.to_string_lossy().as_ref()),
+    );
+    let src = r#"
+        fs.writeFile(dir + "/note.txt", "hello");
+        fs.writeFile(dir + "/other.txt", "");
+        var result = fs.readFile(dir + "/note.txt") + " " + fs.readDir(dir).length + fs.readDir(dir)[0];
+        try { fs.readFile(dir + "/../outside.txt"); } catch (e) { result += " " + e.code; }
+        try { fs.writeFile(dir + "/missing/../../escape.txt", "x"); } catch (e) { result += " " + e.code; }
+        try { fs.readFile(dir + "/absent.txt"); } catch (e) { result += " " + e.code; }
+        result
+    "#;
+    assert_eq!(
+        exec_on(&mut engine, src),
+        "hello 2note.txt ERR_ACCESS_DENIED ERR_ACCESS_DENIED ENOENT"
+    );
+    assert!(!dir.join("escape.txt").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[cfg(unix)]
+#[test]
+fn check_links_out_of_granted_paths() {
+    use std::os::unix::fs::symlink;
+    let dir = scratch_dir("links");
+    let inside = dir.join("inside");
+    fs::create_dir_all(&inside).unwrap();
+    fs::write(dir.join("secret.txt"), "secret").unwrap();
+    symlink(dir.join("secret.txt"), inside.join("link.txt")).unwrap();
+    symlink(dir.join("planted.txt"), inside.join("dangling.txt")).unwrap();
+    let mut permissions = Permissions::new();
+    permissions.allow_read(&inside).allow_write(&inside);
+    let mut engine = engine_with(permissions);
+    engine.set_global(
+        "dir".to_string(),
+        to_value(inside.to_string_lossy().as_ref()),
+    );
+    let src = r#"
+        var result = "";
+        try { fs.readFile(dir + "/link.txt"); } catch (e) { result += e.code; }
+        try { fs.writeFile(dir + "/link.txt", "x"); } catch (e) { result += " " + e.code; }
+        try { fs.writeFile(dir + "/dangling.txt", "x"); } catch (e) { result += " " + e.code; }
+        result
+    "#;
+    assert_eq!(
+        exec_on(&mut engine, src),
+        "ERR_ACCESS_DENIED ERR_ACCESS_DENIED ERR_ACCESS_DENIED"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("secret.txt")).unwrap(),
+        "secret"
+    );
+    assert!(!dir.join("planted.txt").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn check_environment_and_exit() {
+    std::env::set_var("BOA_HOST_TEST_VAR", "visible");
+    let mut permissions = Permissions::new();
+    permissions.allow_env("BOA_HOST_TEST_VAR").allow_exit();
+    let mut engine = engine_with(permissions);
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "process.env.get('BOA_HOST_TEST_VAR') + ' ' + process.env.get('BOA_HOST_TEST_UNSET')"
+        ),
+        "Error: Error: Access to the environment variable \"BOA_HOST_TEST_UNSET\" is not allowed"
+    );
+    assert_eq!(
+        exec_on(&mut engine, "process.env.get('BOA_HOST_TEST_VAR')"),
+        "visible"
+    );
+    let src = r#"
+        var after = false;
+        try { process.exit(7); } catch (e) {} finally { after = true; }
+        after = true;
+    "#;
+    assert_eq!(
+        exec_on(&mut engine, src),
+        "Error: Script exited with code 7"
+    );
+    assert_eq!(engine.termination(), Some(Termination::Exit(7)));
+    assert_eq!(exec_on(&mut engine, "after"), "false");
+}
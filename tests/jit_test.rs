@@ -0,0 +1,152 @@
+extern crate boa;
+mod common;
+use boa::exec_new::jit::JIT_THRESHOLD;
+#[cfg(feature = "jit")]
+use boa::exec_new::Termination;
+use boa::exec_new::{Executor, Interpreter};
+use common::exec_on;
+
+/// Run `src` on engines with and without the JIT, checking they agree, and return what they gave
+fn exec_both(src: &str) -> String {
+    let mut compiled: Interpreter = Executor::new();
+    let mut interpreted: Interpreter = Executor::new();
+    interpreted.set_jit(false);
+    let result = exec_on(&mut compiled, src);
+    assert_eq!(result, exec_on(&mut interpreted, src));
+    result
+}
+
+const SUM: &str = r#"
+    function sum(n) {
+        var total = 0;
+        var i = 0;
+        while (i < n) {
+            total = total + i * 2;
+            i++;
+        }
+        return total;
+    }
+"#;
+
+#[test]
+fn check_function_stats() {
+    let mut engine: Interpreter = Executor::new();
+    let src = format!(
+        "{} var k = 0; while (k < {}) {{ sum(k); k++; }} sum(10)",
+        SUM,
+        JIT_THRESHOLD * 2
+    );
+    assert_eq!(exec_on(&mut engine, &src), "90");
+    let stats = engine.function_stats();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].name, "sum");
+    assert_eq!(stats[0].calls, JIT_THRESHOLD * 2 + 1);
+    assert_eq!(stats[0].deopts, 0);
+    if boa::exec_new::jit::jit_supported() {
+        assert!(stats[0].compiled);
+        assert_eq!(stats[0].compiled_calls, JIT_THRESHOLD + 2);
+    } else {
+        assert!(!stats[0].compiled);
+        assert_eq!(stats[0].compiled_calls, 0);
+    }
+}
+
+#[test]
+fn check_no_jit() {
+    let mut engine: Interpreter = Executor::new();
+    engine.set_jit(false);
+    let src = format!(
+        "{} var k = 0; while (k < {}) {{ sum(k); k++; }} sum(5)",
+        SUM,
+        JIT_THRESHOLD * 2
+    );
+    assert_eq!(exec_on(&mut engine, &src), "20");
+    let stats = engine.function_stats();
+    assert_eq!(stats[0].calls, JIT_THRESHOLD * 2 + 1);
+    assert!(!stats[0].compiled);
+    assert_eq!(stats[0].compiled_calls, 0);
+}
+
+#[test]
+fn check_compiled_arithmetic() {
+    let src = r#"
+        function arith(a, b) { return a + b * 2 - a / b; }
+        function cmp(a, b) { return (a < b) * 1 + (a <= b) * 2 + (a > b) * 4 + (a >= b) * 8 + (a == b) * 16 + (a !== b) * 32; }
+        function neg(a) { var x = -a; return !x; }
+        function missing(a, b) { return b === undefined; }
+        var s = "";
+        var k = 0;
+        while (k < 30) {
+            s = s + arith(k, 3) + "," + arith(k, 0) + "," + cmp(k, 15) + "," + cmp(k, 0 / 0) + ",";
+            s = s + (1 / neg(k - 3)) + "," + missing(k) + ";";
+            k++;
+        }
+        s
+    "#;
+    let result = exec_both(src);
+    assert!(result.starts_with("6,NaN,35,32,Infinity,true;"));
+    assert!(result.contains(";8,-Infinity,35,32,1,true;"));
+    assert!(result.contains(";16,-Infinity,26,32,Infinity,true;"));
+    assert!(result.ends_with(";25.333333333333336,-Infinity,44,32,Infinity,true;"));
+}
+
+#[test]
+fn check_deopt() {
+    let src = r#"
+        function add(a, b) { var x = a + b; return x; }
+        var k = 0;
+        while (k < 20) { add(k, 1); k++; }
+        add("a", 1) + add(2, "b") + add(1.5, 2) + add(true, 1)
+    "#;
+    assert_eq!(exec_both(src), "a12b3.52");
+    let mut engine: Interpreter = Executor::new();
+    exec_on(&mut engine, src);
+    let stats = engine.function_stats();
+    if boa::exec_new::jit::jit_supported() {
+        assert_eq!(stats[0].deopts, 3);
+        assert!(stats[0].compiled);
+    }
+}
+
+#[cfg(feature = "jit")]
+#[test]
+fn check_compiled_step_limit() {
+    let mut engine: Interpreter = Executor::new();
+    exec_on(&mut engine, SUM);
+    let warm = format!(
+        "var k = 0; while (k < {}) {{ sum(k); k++; }}",
+        JIT_THRESHOLD
+    );
+    exec_on(&mut engine, &warm);
+    engine.set_step_limit(Some(100_000));
+    assert_eq!(
+        exec_on(&mut engine, "sum(1e9)"),
+        "Error: Script took more steps than allowed"
+    );
+    assert_eq!(engine.termination(), Some(Termination::StepLimit));
+    assert!(engine.function_stats()[0].compiled);
+    assert_eq!(exec_on(&mut engine, "sum(100)"), "9900");
+}
+
+#[cfg(feature = "jit")]
+#[test]
+fn check_compiled_interrupt() {
+    let mut engine: Interpreter = Executor::new();
+    exec_on(&mut engine, SUM);
+    let warm = format!(
+        "var k = 0; while (k < {}) {{ sum(k); k++; }}",
+        JIT_THRESHOLD
+    );
+    exec_on(&mut engine, &warm);
+    let handle = engine.interrupt_handle();
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        handle.interrupt();
+    });
+    assert_eq!(
+        exec_on(&mut engine, "sum(1e15)"),
+        "Error: Script execution was interrupted"
+    );
+    thread.join().unwrap();
+    assert_eq!(engine.termination(), Some(Termination::Interrupted));
+}
@@ -0,0 +1,199 @@
+extern crate boa;
+mod common;
+use common::{exec_old, exec_statements as exec};
+
+#[test]
+fn check_stringify() {
+    assert_eq!(
+        exec("JSON.stringify({a: 1, b: 'x', c: true, d: null})"),
+        r#"{"a":1,"b":"x","c":true,"d":null}"#
+    );
+    assert_eq!(exec("JSON.stringify([1, 'two', [3]])"), r#"[1,"two",[3]]"#);
+    assert_eq!(
+        exec("JSON.stringify({b: 1, a: 2, 1: 3, 0: 4})"),
+        r#"{"0":4,"1":3,"b":1,"a":2}"#
+    );
+    assert_eq!(
+        exec(r#"JSON.stringify('a"b\n\u0001')"#),
+        r#""a\"b\n\u0001""#
+    );
+    assert_eq!(exec("JSON.stringify(NaN)"), "null");
+    assert_eq!(
+        exec("JSON.stringify([NaN, Infinity, undefined])"),
+        "[null,null,null]"
+    );
+    assert_eq!(exec("JSON.stringify({a: undefined, b: 1})"), r#"{"b":1}"#);
+    assert_eq!(exec("JSON.stringify(undefined)"), "undefined");
+    assert_eq!(exec("JSON.stringify(new Number(3))"), "3");
+    assert_eq!(
+        exec("JSON.stringify(new Uint8Array([1, 2]))"),
+        r#"{"0":1,"1":2}"#
+    );
+    assert_eq!(
+        exec_old("JSON.stringify({a: function() {}, b: [function() {}]})"),
+        r#"{"b":[null]}"#
+    );
+}
+
+#[test]
+fn check_stringify_errors() {
+    assert_eq!(
+        exec("var a = {}; void (a.self = a); JSON.stringify(a)"),
+        "Error: TypeError: Converting circular structure to JSON"
+    );
+    assert_eq!(
+        exec("var a = [{}]; void (a[0].b = a); JSON.stringify(a)"),
+        "Error: TypeError: Converting circular structure to JSON"
+    );
+    assert_eq!(
+        exec("JSON.stringify({a: 1n})"),
+        "Error: TypeError: Do not know how to serialize a BigInt"
+    );
+    // The same object twice is fine as long as it doesn't contain itself
+    assert_eq!(exec("var a = {}; JSON.stringify([a, a])"), "[{},{}]");
+}
+
+#[test]
+fn check_stringify_space() {
+    assert_eq!(
+        exec("JSON.stringify({a: [1, 2], b: {}}, null, 2)"),
+        "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}"
+    );
+    assert_eq!(exec("JSON.stringify([1], null, '--')"), "[\n--1\n]");
+    assert_eq!(
+        exec("JSON.stringify([1], null, 20)"),
+        format!("[\n{}1\n]", " ".repeat(10))
+    );
+    assert_eq!(
+        exec("JSON.stringify([1], null, 'abcdefghijkl')"),
+        "[\nabcdefghij1\n]"
+    );
+    assert_eq!(exec("JSON.stringify([1], null, 0)"), "[1]");
+}
+
+#[test]
+fn check_stringify_replacer() {
+    assert_eq!(
+        exec("JSON.stringify({a: 1, b: 2, c: 3, 1: 4}, ['c', 'a', 1, 'c'])"),
+        r#"{"c":3,"a":1,"1":4}"#
+    );
+    assert_eq!(
+        exec_old("JSON.stringify({a: 1, b: [2]}, function(k, v) { return v; })"),
+        r#"{"a":1,"b":[2]}"#
+    );
+    assert_eq!(
+        exec_old("JSON.stringify({a: 1}, function(k, v) { return typeof v; })"),
+        r#""object""#
+    );
+    assert_eq!(
+        exec_old("JSON.stringify({a: {toJSON: function(k) { return k; }}})"),
+        r#"{"a":"a"}"#
+    );
+    assert_eq!(
+        exec("JSON.stringify({d: new Date(0)})"),
+        r#"{"d":"1970-01-01T00:00:00.000Z"}"#
+    );
+}
+
+#[test]
+fn check_parse() {
+    assert_eq!(
+        exec("JSON.parse('[1, 2.5, \"x\", true, null]').length"),
+        "5"
+    );
+    assert_eq!(
+        exec("JSON.parse(' {\"a\": {\"b\": [1e2]}} ').a.b[0]"),
+        "100"
+    );
+    assert_eq!(exec("JSON.parse('-0.5E-1')"), "-0.05");
+    assert_eq!(
+        exec(r#"JSON.parse('"\\u0041\\ud83d\\ude00\\n"')"#),
+        "A\u{1F600}\n"
+    );
+    assert_eq!(
+        exec("JSON.stringify(JSON.parse('{\"b\": 1, \"a\": 2, \"b\": 3}'))"),
+        r#"{"b":3,"a":2}"#
+    );
+    assert_eq!(exec("JSON.parse('[]').length"), "0");
+    assert_eq!(exec("JSON.parse('{}').hasOwnProperty('a')"), "false");
+}
+
+#[test]
+fn check_parse_errors() {
+    assert_eq!(
+        exec("var caught; try { JSON.parse('{') } catch (e) { caught = e instanceof SyntaxError } caught"),
+        "true"
+    );
+    assert_eq!(
+        exec("JSON.parse('{\"a\": 1,}')"),
+        "Error: SyntaxError: Unexpected token } in JSON at position 8"
+    );
+    assert_eq!(
+        exec("JSON.parse('[01]')"),
+        "Error: SyntaxError: Unexpected token 1 in JSON at position 2"
+    );
+    assert_eq!(
+        exec("JSON.parse('[1')"),
+        "Error: SyntaxError: Unexpected end of JSON input"
+    );
+    assert_eq!(
+        exec(r#"JSON.parse("'a'")"#),
+        "Error: SyntaxError: Unexpected token ' in JSON at position 0"
+    );
+    assert_eq!(
+        exec("JSON.parse('1 2')"),
+        "Error: SyntaxError: Unexpected token 2 in JSON at position 2"
+    );
+    assert_eq!(
+        exec("JSON.parse('.5')"),
+        "Error: SyntaxError: Unexpected token . in JSON at position 0"
+    );
+}
+
+#[test]
+fn check_parse_reviver() {
+    assert_eq!(
+        exec_old(
+            "JSON.stringify(JSON.parse('{\"a\": 1, \"b\": [2, \"x\"]}', function(k, v) { return v; }))"
+        ),
+        r#"{"a":1,"b":[2,"x"]}"#
+    );
+    assert_eq!(
+        exec_old("JSON.parse('{\"a\": 1}', function(k, v) { return typeof v; })"),
+        "object"
+    );
+}
+
+#[test]
+fn check_keys_like_internal_slots() {
+    assert_eq!(
+        exec(r#"JSON.stringify(JSON.parse('{"[[DateValue]]": 5, "y": 2}'))"#),
+        r#"{"[[DateValue]]":5,"y":2}"#
+    );
+    assert_eq!(
+        exec(r#"JSON.stringify({"[[x]]": 1, y: 2})"#),
+        r#"{"[[x]]":1,"y":2}"#
+    );
+}
+
+#[test]
+fn check_parse_proto_key() {
+    assert_eq!(
+        exec(r#"var o = JSON.parse('{"__proto__": {"polluted": 1}}'); typeof o.polluted"#),
+        "undefined"
+    );
+    assert_eq!(
+        exec(r#"JSON.stringify(JSON.parse('{"__proto__": {"a": 1}}'))"#),
+        r#"{"__proto__":{"a":1}}"#
+    );
+    assert_eq!(exec(r#"({}).polluted"#), "undefined");
+}
+
+#[test]
+fn check_arrays_are_told_apart_by_kind() {
+    assert_eq!(
+        exec("JSON.stringify(Object.setPrototypeOf({a: 1}, Array.prototype))"),
+        r#"{"a":1}"#
+    );
+    assert_eq!(exec(r#"JSON.stringify(JSON.parse('[1,[2]]'))"#), "[1,[2]]");
+}
@@ -0,0 +1,134 @@
+extern crate boa;
+mod common;
+use boa::exec_new::event_loop::EventLoop;
+use boa::exec_new::{Executor, Interpreter, Termination};
+use common::exec_on;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn check_step_limit() {
+    let mut engine: Interpreter = Executor::new();
+    engine.set_step_limit(Some(1000));
+    assert_eq!(
+        exec_on(&mut engine, "while (true) {}"),
+        "Error: Script took more steps than allowed"
+    );
+    assert_eq!(engine.termination(), Some(Termination::StepLimit));
+    assert_eq!(
+        exec_on(&mut engine, "var n = 0; while (true) { try { while (true) { n++; } } catch (e) {} finally { n = -1; } }"),
+        "Error: Script took more steps than allowed"
+    );
+    assert_eq!(exec_on(&mut engine, "n > 0"), "true");
+    assert_eq!(engine.termination(), None);
+    engine.set_step_limit(None);
+    assert_eq!(
+        exec_on(&mut engine, "var i = 0; while (i < 1000) { i++; } i"),
+        "1000"
+    );
+}
+
+#[test]
+fn check_call_depth_limit() {
+    let mut engine: Interpreter = Executor::new();
+    engine.set_call_depth_limit(20);
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "function f(n) { return n == 0 ? 0 : f(n - 1) + 1; } f(10)"
+        ),
+        "10"
+    );
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "try { f(30); } catch (e) { (e instanceof RangeError) + ' ' + e.message }"
+        ),
+        "true Maximum call stack size exceeded"
+    );
+    assert_eq!(engine.termination(), None);
+}
+
+#[test]
+fn check_stack_size_limit() {
+    let mut engine: Interpreter = Executor::new();
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "function f() { return f(); } try { f(); } catch (e) { e.name }"
+        ),
+        "RangeError"
+    );
+    engine.set_stack_size_limit(0);
+    assert_eq!(
+        exec_on(&mut engine, "1 + 1"),
+        "Error: RangeError: Maximum call stack size exceeded"
+    );
+}
+
+#[test]
+fn check_stack_limit_in_callbacks() {
+    let mut engine: Interpreter = Executor::new();
+    let event_loop = EventLoop::with_fake_clock();
+    event_loop.install(&engine.realm());
+    let src = r#"
+        var caught;
+        function f(n) { return f(n + 1); }
+        setTimeout(function () {
+            try { f(0); } catch (e) { caught = e.name + ': ' + e.message; }
+        }, 0);
+    "#;
+    exec_on(&mut engine, src);
+    event_loop.run(&mut engine).unwrap();
+    assert_eq!(
+        exec_on(&mut engine, "caught"),
+        "RangeError: Maximum call stack size exceeded"
+    );
+}
+
+#[test]
+fn check_heap_limit() {
+    let mut engine: Interpreter = Executor::new();
+    engine.set_heap_limit(Some(1 << 20));
+    assert_eq!(
+        exec_on(&mut engine, "var s = 'x'; while (true) { s = s + s; }"),
+        "Error: Script used more memory than allowed"
+    );
+    assert_eq!(engine.termination(), Some(Termination::HeapLimit));
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "s = ''; var a = []; var i = 0; while (true) { a[i] = {n: i}; i++; }"
+        ),
+        "Error: Script used more memory than allowed"
+    );
+    assert_eq!(
+        exec_on(&mut engine, "a = null; var total = 0; var j = 0; while (j < 1000) { total += ('' + j).length; j++; } total"),
+        "2890"
+    );
+}
+
+#[test]
+fn check_interrupt() {
+    let mut engine: Interpreter = Executor::new();
+    let handle = engine.interrupt_handle();
+    let interrupter = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        handle.interrupt();
+    });
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "while (true) { try { while (true) {} } catch (e) {} }"
+        ),
+        "Error: Script execution was interrupted"
+    );
+    assert_eq!(engine.termination(), Some(Termination::Interrupted));
+    interrupter.join().unwrap();
+    engine.interrupt_handle().interrupt();
+    assert_eq!(
+        exec_on(&mut engine, "1"),
+        "Error: Script execution was interrupted"
+    );
+    assert_eq!(exec_on(&mut engine, "1"), "1");
+}
@@ -0,0 +1,133 @@
+extern crate boa;
+mod common;
+use boa::js::number::number_to_string;
+use common::exec_old as exec;
+
+#[test]
+fn check_number_to_string() {
+    assert_eq!(number_to_string(0.1 + 0.2), "0.30000000000000004");
+    assert_eq!(number_to_string(1e21), "1e+21");
+    assert_eq!(number_to_string(123e18), "123000000000000000000");
+    assert_eq!(number_to_string(1.5e-7), "1.5e-7");
+    assert_eq!(number_to_string(0.000001), "0.000001");
+    assert_eq!(number_to_string(-0.0), "0");
+    assert_eq!(number_to_string(-1.0 / 0.0), "-Infinity");
+    assert_eq!(number_to_string(5e-324), "5e-324");
+    assert_eq!(exec("1 / 3;"), "0.3333333333333333");
+}
+
+#[test]
+fn check_radix_to_string() {
+    assert_eq!(exec("(255).toString(16)"), "ff");
+    assert_eq!(exec("(255).toString(2)"), "11111111");
+    assert_eq!(exec("Number('-255.5').toString(16)"), "-ff.8");
+    assert_eq!(
+        exec("Number('0.1').toString(2)"),
+        "0.0001100110011001100110011001100110011001100110011001101"
+    );
+    assert_eq!(exec("Number('1e21').toString(36)"), "5v1j4f4ds7c000");
+    assert_eq!(
+        exec("(10).toString(1)"),
+        "Error: toString() radix must be between 2 and 36"
+    );
+}
+
+#[test]
+fn check_to_fixed() {
+    assert_eq!(exec("(1.005).toFixed(2)"), "1.00");
+    assert_eq!(exec("(2.5).toFixed()"), "3");
+    assert_eq!(exec("(1.25).toFixed(1)"), "1.3");
+    assert_eq!(exec("(99.5).toFixed()"), "100");
+    assert_eq!(exec("Number('-1.5').toFixed(3)"), "-1.500");
+    assert_eq!(exec("Number('1e21').toFixed(2)"), "1e+21");
+    assert_eq!(
+        exec("(1).toFixed(101)"),
+        "Error: toFixed() digits argument must be between 0 and 100"
+    );
+}
+
+#[test]
+fn check_to_precision_and_exponential() {
+    assert_eq!(exec("(123.456).toPrecision(4)"), "123.5");
+    assert_eq!(exec("(123.456).toPrecision(2)"), "1.2e+2");
+    assert_eq!(exec("Number('0.00001234').toPrecision(2)"), "0.000012");
+    assert_eq!(exec("Number('1e-7').toPrecision(1)"), "1e-7");
+    assert_eq!(exec("(999.96).toPrecision(4)"), "1000");
+    assert_eq!(exec("(123456).toExponential(2)"), "1.23e+5");
+    assert_eq!(exec("(123456).toExponential()"), "1.23456e+5");
+    assert_eq!(exec("Number('0.00015').toExponential(1)"), "1.5e-4");
+    assert_eq!(exec("(NaN).toPrecision(3)"), "NaN");
+}
+
+#[test]
+fn check_string_to_number() {
+    assert_eq!(exec("Number(' 12.5 ')"), "12.5");
+    assert_eq!(exec("Number('')"), "0");
+    assert_eq!(exec("Number('0x1F')"), "31");
+    assert_eq!(exec("Number('0b101')"), "5");
+    assert_eq!(exec("Number('-Infinity')"), "-Infinity");
+    assert_eq!(exec("Number('infinity')"), "NaN");
+    assert_eq!(exec("Number('12px')"), "NaN");
+    assert_eq!(exec("Number('.5e1')"), "5");
+}
+
+#[test]
+fn check_parse_int_and_float() {
+    assert_eq!(exec("parseInt('  42px')"), "42");
+    assert_eq!(exec("parseInt('-0x1A')"), "-26");
+    assert_eq!(exec("parseInt('z', 36)"), "35");
+    assert_eq!(exec("parseInt('12', 1)"), "NaN");
+    assert_eq!(exec("parseInt('', 10)"), "NaN");
+    assert_eq!(exec("parseFloat('3.14abc')"), "3.14");
+    assert_eq!(exec("parseFloat('.5e')"), "0.5");
+    assert_eq!(exec("parseFloat('-Infinityx')"), "-Infinity");
+    assert_eq!(exec("parseFloat('e5')"), "NaN");
+    assert_eq!(exec("Number.parseFloat('1.5e3')"), "1500");
+}
+
+#[test]
+fn check_number_statics() {
+    assert_eq!(exec("Number.MAX_SAFE_INTEGER;"), "9007199254740991");
+    assert_eq!(exec("Number.EPSILON;"), "2.220446049250313e-16");
+    assert_eq!(exec("Number.MIN_VALUE;"), "5e-324");
+    assert_eq!(exec("Number.isInteger(5)"), "true");
+    assert_eq!(exec("Number.isInteger('5')"), "false");
+    assert_eq!(
+        exec("Number.isSafeInteger(Number.MAX_SAFE_INTEGER + 1)"),
+        "false"
+    );
+    assert_eq!(exec("Number.isNaN('abc')"), "false");
+    assert_eq!(exec("isNaN('abc')"), "true");
+    assert_eq!(exec("isFinite('12')"), "true");
+    assert_eq!(exec("Number.isFinite('12')"), "false");
+}
+
+#[test]
+fn check_wrappers() {
+    assert_eq!(exec("typeof new Number(5)"), "object");
+    assert_eq!(exec("var n = new Number(5); n.valueOf()"), "5");
+    assert_eq!(exec("typeof Number('5')"), "number");
+    assert_eq!(exec("Boolean('')"), "false");
+    assert_eq!(exec("Boolean(NaN)"), "false");
+    assert_eq!(exec("Boolean(Number('0.5'))"), "true");
+    assert_eq!(exec("var b = new Boolean(false); b.toString()"), "false");
+    assert_eq!(exec("true.toString()"), "true");
+    assert_eq!(exec("!new Boolean(false)"), "false");
+}
+
+#[test]
+fn check_math_additions() {
+    assert_eq!(exec("Math.sign(Number('-3'))"), "-1");
+    assert_eq!(exec("Math.trunc(Number('-4.7'))"), "-4");
+    assert_eq!(exec("Math.hypot(3, 4)"), "5");
+    assert_eq!(exec("Math.hypot(NaN, Infinity)"), "Infinity");
+    assert_eq!(exec("Math.log2(8)"), "3");
+    assert_eq!(exec("Math.log10(1000)"), "3");
+    assert_eq!(exec("Math.fround(5.5)"), "5.5");
+    assert_eq!(exec("Math.fround(5.05)"), "5.050000190734863");
+    assert_eq!(exec("Math.imul(4294967295, 5)"), "-5");
+    assert_eq!(exec("Math.clz32(1)"), "31");
+    assert_eq!(exec("Math.round(2.5)"), "3");
+    assert_eq!(exec("Math.round(Number('-2.5'))"), "-2");
+    assert_eq!(exec("Math.cosh(1)"), "1.5430806348152437");
+}
@@ -0,0 +1,130 @@
+extern crate boa;
+mod common;
+use boa::exec_new::realm::Realm;
+use boa::exec_new::{Executor, Interpreter, Script};
+use common::exec_on;
+
+/// Run `src` as a script in a realm and return its completion value as a string
+fn exec_in(engine: &mut Interpreter, realm: &Realm, src: &str) -> String {
+    match Script::parse(src).and_then(|script| engine.run_script_in(realm, &script)) {
+        Ok(v) => v.to_string(),
+        Err(v) => format!("Error: {}", v),
+    }
+}
+
+#[test]
+fn check_global_this() {
+    let mut engine: Interpreter = Executor::new();
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "var x = 1; globalThis === this && globalThis.x === x && globalThis.Array === Array"
+        ),
+        "true"
+    );
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "(function () { return this; })() === globalThis"
+        ),
+        "true"
+    );
+}
+
+#[test]
+fn check_intrinsics_outlive_replaced_globals() {
+    let mut engine: Interpreter = Executor::new();
+    let src = r#"
+        Array.prototype.marked = "yes";
+        Object.prototype.seen = "too";
+        Array = function () {};
+        Object = null;
+        [].marked + " " + ({}).seen
+    "#;
+    assert_eq!(exec_on(&mut engine, src), "yes too");
+    let realm = engine.realm();
+    assert!(realm.intrinsic("Array").is_function());
+    assert!(!realm
+        .intrinsic("Array")
+        .strict_equals(&engine.get_global("Array".to_string())));
+}
+
+#[test]
+fn check_realms_are_isolated() {
+    let mut engine: Interpreter = Executor::new();
+    let tenant = Realm::new();
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "var secret = 42; Array.prototype.extra = 1; secret"
+        ),
+        "42"
+    );
+    assert_eq!(
+        exec_in(&mut engine, &tenant, "typeof secret + ' ' + [].extra"),
+        "undefined undefined"
+    );
+    assert_eq!(
+        exec_in(&mut engine, &tenant, "var mine = 'tenant'; mine"),
+        "tenant"
+    );
+    assert_eq!(exec_on(&mut engine, "typeof mine"), "undefined");
+    assert!(engine
+        .realm()
+        .same_realm(&Realm::from_global(&engine.get_global("globalThis".to_string())).unwrap()));
+}
+
+#[test]
+fn check_cross_realm_functions() {
+    let mut engine: Interpreter = Executor::new();
+    let other = Realm::new();
+    exec_in(
+        &mut engine,
+        &other,
+        "var where = 'other'; function make() { return [where]; }",
+    );
+    engine.set_global("make".to_string(), other.global().get_field_slice("make"));
+    engine.set_global("otherArray".to_string(), other.intrinsic("Array"));
+    let src = r#"
+        var where = 'main';
+        var arr = make();
+        arr[0] + " " + (Object.getPrototypeOf(arr) === otherArray.prototype)
+            + " " + (Object.getPrototypeOf(arr) === Array.prototype) + " " + JSON.stringify(arr)
+    "#;
+    assert_eq!(exec_on(&mut engine, src), r#"other true false ["other"]"#);
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "var local = [1]; Object.getPrototypeOf(local) === Array.prototype"
+        ),
+        "true"
+    );
+}
+
+#[test]
+fn check_intrinsics_are_unreachable() {
+    let mut engine: Interpreter = Executor::new();
+    let src = r#"
+        var seen = typeof globalThis["[[Intrinsics]]"];
+        globalThis["[[Intrinsics]]"] = { Array: null };
+        delete globalThis["[[Intrinsics]]"];
+        seen + " " + JSON.stringify([1])
+    "#;
+    assert_eq!(exec_on(&mut engine, src), "undefined [1]");
+    let realm = engine.realm();
+    assert!(realm.intrinsic("Array").is_function());
+    assert!(Realm::from_global(realm.global()).is_some());
+}
+
+#[test]
+fn check_natives_use_the_calling_realm() {
+    let mut engine: Interpreter = Executor::new();
+    let other = Realm::new();
+    engine.set_global("otherJSON".to_string(), other.intrinsic("JSON"));
+    let src = r#"
+        var parsed = otherJSON.parse('{"list": [1]}');
+        (Object.getPrototypeOf(parsed) === Object.prototype)
+            + " " + (Object.getPrototypeOf(parsed.list) === Array.prototype)
+    "#;
+    assert_eq!(exec_on(&mut engine, src), "true true");
+}
@@ -0,0 +1,93 @@
+extern crate boa;
+mod common;
+use boa::exec_new::bytecode::disassemble;
+use boa::exec_new::Script;
+use common::exec;
+
+#[test]
+fn check_captured_slots_disassembly() {
+    let script = Script::parse("function f(a) {\n  var b = 1;\n  return () => a + b;\n}").unwrap();
+    assert_eq!(
+        disassemble(&script),
+        "== <anonymous> ==\n\
+         0000    1:1    MakeFunction(0)\t; f\n\
+         0001    1:1    DeclareFunction(0)\t; f\n\
+         0002    1:1    GetLocal(0)\t; <completion>\n\
+         0003    1:1    Return\n\
+         \n\
+         == f ==\n\
+         0000    1:12   Arg(0)\n\
+         0001    1:12   SetCaptured(0, 0)\t; a\n\
+         0002    1:12   Pop\n\
+         0003    2:11   Const(0)\t; 1\n\
+         0004    2:3    SetCaptured(0, 1)\t; b\n\
+         0005    2:3    Pop\n\
+         0006    3:10   MakeFunction(0)\t; \n\
+         0007    3:3    Return\n\
+         0008    3:3    Undefined\n\
+         0009    3:3    Return\n\
+         \n\
+         == <anonymous> ==\n\
+         0000    3:16   GetCaptured(0, 0)\n\
+         0001    3:20   GetCaptured(0, 1)\n\
+         0002    3:16   Binary(Addition)\n\
+         0003    1:1    Return\n"
+    );
+}
+
+#[test]
+fn check_closures_share_slots() {
+    assert_eq!(
+        exec("function pair() { var n = 0; return [function() { n++; }, function() { return n; }]; } var p = pair(); p[0](); p[0](); p[1]()"),
+        "2"
+    );
+    assert_eq!(
+        exec("function make(a) { var b = 2; return function() { var c = 3; return () => a + b + c; }; } make(1)()()"),
+        "6"
+    );
+    assert_eq!(
+        exec("function counter() { var n = 0; return function() { return ++n; }; } var a = counter(), b = counter(); a(); a(); b(); a() + b()"),
+        "5"
+    );
+}
+
+#[test]
+fn check_catch_parameter_slots() {
+    assert_eq!(
+        exec("function f() { var e = 'outer'; try { throw 'inner'; } catch (e) { var g = e; } return e + g; } f()"),
+        "outerinner"
+    );
+    assert_eq!(
+        exec("function f() { try { throw 1; } catch (e) { return () => e; } } f()()"),
+        "1"
+    );
+}
+
+#[test]
+fn check_function_expression_names() {
+    assert_eq!(
+        exec("function o() { var f = 1; var g = function f() { return typeof f; }; return g() + f; } o()"),
+        "function1"
+    );
+    assert_eq!(
+        exec("function o() { var n = 3; return function fact(k) { return k > 1 ? k * fact(k - 1) : n - 2; }; } o()(5)"),
+        "120"
+    );
+}
+
+#[test]
+fn check_eval_and_arguments_use_names() {
+    assert_eq!(exec("function f(a) { eval('a = 2'); return a; } f(1)"), "2");
+    assert_eq!(
+        exec("function f(a) { return () => eval('a + 1'); } f(1)()"),
+        "2"
+    );
+    assert_eq!(
+        exec("function f(a) { arguments[0] = 5; return a; } f(1)"),
+        "5"
+    );
+    assert_eq!(
+        exec("var x = 'global'; function f() { var x = 'local'; return eval('function g() { return x; } g()'); } f()"),
+        "local"
+    );
+}
@@ -0,0 +1,120 @@
+extern crate boa;
+extern crate serde;
+use boa::exec_new::{Executor, Interpreter, Script};
+use boa::js::object::own_property_keys;
+use boa::js::serde_value::{from_js_value, to_js_value};
+use boa::js::value::{Value, ValueData};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Run `src` as a script on an engine and return its completion value
+fn eval(engine: &mut Interpreter, src: &str) -> Value {
+    Script::parse(src)
+        .and_then(|script| engine.run_script(&script))
+        .unwrap()
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Shape {
+    Empty,
+    Circle(f64),
+    Rect { w: f64, h: f64 },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: String,
+    retries: u32,
+    ratio: f64,
+    tags: Vec<String>,
+    parent: Option<String>,
+    shapes: Vec<Shape>,
+    big: u64,
+}
+
+fn config() -> Config {
+    Config {
+        name: "main".to_string(),
+        retries: 3,
+        ratio: f64::INFINITY,
+        tags: vec!["a".to_string(), "b".to_string()],
+        parent: None,
+        shapes: vec![
+            Shape::Empty,
+            Shape::Circle(1.5),
+            Shape::Rect { w: 2.0, h: 3.0 },
+        ],
+        big: u64::MAX,
+    }
+}
+
+#[test]
+fn check_serialize_to_script() {
+    let mut engine: Interpreter = Executor::new();
+    let value = engine.to_js_value(&config()).unwrap();
+    let keys = match *value.data() {
+        ValueData::Object(ref obj) => own_property_keys(&obj.borrow()),
+        _ => Vec::new(),
+    };
+    assert_eq!(
+        keys,
+        vec!["name", "retries", "ratio", "tags", "parent", "shapes", "big"]
+    );
+    engine.set_global("config".to_string(), value);
+    let src = r#"
+        config.tags.length + config.tags[1] + " " + config.ratio + " " + config.parent + " "
+            + config.shapes[0] + " " + config.shapes[1].Circle + " " + config.shapes[2].Rect.h
+            + " " + typeof config.big
+            + " " + (Object.getPrototypeOf(config.tags) === Array.prototype)
+    "#;
+    assert_eq!(
+        eval(&mut engine, src).to_string(),
+        "2b Infinity null Empty 1.5 3 bigint true"
+    );
+}
+
+#[test]
+fn check_round_trip() {
+    let value = to_js_value(&config()).unwrap();
+    assert_eq!(from_js_value::<Config>(&value).unwrap(), config());
+    let nan = to_js_value(&f64::NAN).unwrap();
+    assert!(from_js_value::<f64>(&nan).unwrap().is_nan());
+    assert!(to_js_value(&()).unwrap().is_undefined());
+}
+
+#[test]
+fn check_deserialize_from_script() {
+    let mut engine: Interpreter = Executor::new();
+    let value = eval(
+        &mut engine,
+        "({ name: 'x', retries: 2, ratio: -0.5, tags: [], parent: undefined, \
+         shapes: ['Empty', { Circle: 2 }], big: 7n })",
+    );
+    let config: Config = from_js_value(&value).unwrap();
+    assert_eq!(config.retries, 2);
+    assert_eq!(config.parent, None);
+    assert_eq!(config.shapes, vec![Shape::Empty, Shape::Circle(2.0)]);
+    assert_eq!(config.big, 7);
+    let map: BTreeMap<String, i32> = from_js_value(&eval(&mut engine, "({ b: 2, a: 1 })")).unwrap();
+    assert_eq!(map.get("a"), Some(&1));
+    assert_eq!(map.get("b"), Some(&2));
+}
+
+#[test]
+fn check_deserialize_errors() {
+    let mut engine: Interpreter = Executor::new();
+    let cycle = eval(&mut engine, "var o = { a: 1 }; o.self = o; o");
+    assert_eq!(
+        from_js_value::<BTreeMap<String, i32>>(&cycle)
+            .unwrap_err()
+            .to_string(),
+        "Can't convert an object that contains itself"
+    );
+    let function = eval(&mut engine, "(function () {})");
+    assert_eq!(
+        from_js_value::<String>(&function).unwrap_err().to_string(),
+        "Can't convert a function to Rust data"
+    );
+    let wrong = eval(&mut engine, "'text'");
+    assert!(from_js_value::<u32>(&wrong).is_err());
+}
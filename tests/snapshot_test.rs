@@ -0,0 +1,103 @@
+extern crate boa;
+mod common;
+use boa::exec_new::realm::Realm;
+use boa::exec_new::snapshot::SnapshotError;
+use boa::exec_new::{Executor, Interpreter};
+use boa::js::class::Class;
+use boa::js::value::Value;
+use common::exec_on;
+
+/// Snapshot the realm of an engine after running a bootstrap script on it
+fn bootstrapped() -> Vec<u8> {
+    let mut engine: Interpreter = Executor::new();
+    let src = r#"
+        var config = { name: "app", limits: [1, 2, 3], big: 12345678901234567890n };
+        config.self = config;
+        function makeCounter(start) {
+            var count = start;
+            return function () { count += 1; return count; };
+        }
+        var counter = makeCounter(10);
+        counter();
+        var square = (x) => x * x;
+        var bound = function (a, b) { return this.base + a + b; }.bind({ base: 100 }, 1);
+        var bytes = new Uint8Array([7, 8, 9]);
+        Array.prototype.total = function () {
+            var sum = 0, i = 0;
+            while (i < this.length) { sum += this[i]; i++; }
+            return sum;
+        };
+    "#;
+    exec_on(&mut engine, src);
+    engine.realm().snapshot().unwrap()
+}
+
+#[test]
+fn check_snapshot_restores_state() {
+    let snapshot = bootstrapped();
+    let mut engine: Interpreter = Executor::with_realm(Realm::from_snapshot(&snapshot).unwrap());
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "config.name + config.limits.total() + (config.self === config) + typeof config.big"
+        ),
+        "app6truebigint"
+    );
+    assert_eq!(exec_on(&mut engine, "counter() + ',' + counter()"), "12,13");
+    assert_eq!(exec_on(&mut engine, "square(7) + ',' + bound(2)"), "49,103");
+    assert_eq!(exec_on(&mut engine, "bytes[1] + bytes.length"), "11");
+    assert_eq!(
+        exec_on(
+            &mut engine,
+            "globalThis === this && [4, 5].total() === 9 && makeCounter(1)() === 2"
+        ),
+        "true"
+    );
+}
+
+#[test]
+fn check_restored_realms_are_independent() {
+    let snapshot = bootstrapped();
+    let mut first: Interpreter = Executor::with_realm(Realm::from_snapshot(&snapshot).unwrap());
+    let mut second: Interpreter = Executor::with_realm(Realm::from_snapshot(&snapshot).unwrap());
+    assert_eq!(exec_on(&mut first, "counter(); counter()"), "13");
+    assert_eq!(exec_on(&mut second, "counter()"), "12");
+}
+
+#[test]
+fn check_snapshot_validation() {
+    let snapshot = bootstrapped();
+    assert_eq!(
+        Realm::from_snapshot(b"not a snapshot").unwrap_err(),
+        SnapshotError::NotASnapshot
+    );
+    let mut newer = snapshot.clone();
+    newer[8] = 99;
+    match Realm::from_snapshot(&newer) {
+        Err(SnapshotError::VersionMismatch(_)) => (),
+        other => panic!("expected a version mismatch, got {:?}", other.map(|_| ())),
+    }
+    let mut damaged = snapshot.clone();
+    let last = damaged.len() - 10;
+    damaged[last] ^= 0xff;
+    match Realm::from_snapshot(&damaged) {
+        Err(SnapshotError::Corrupt(_)) => (),
+        other => panic!("expected a corrupt snapshot, got {:?}", other.map(|_| ())),
+    }
+    let truncated = &snapshot[..snapshot.len() / 2];
+    assert!(Realm::from_snapshot(truncated).is_err());
+}
+
+#[test]
+fn check_unsupported_values() {
+    let mut engine: Interpreter = Executor::new();
+    engine.register_class(
+        Class::new("Thing", |(): ()| Ok(()))
+            .method("poke", |_: &mut (), (): ()| Ok(Value::undefined())),
+    );
+    let error = engine.realm().snapshot().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Can't snapshot native closures, like the methods of host classes"
+    );
+}
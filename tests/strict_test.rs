@@ -0,0 +1,89 @@
+extern crate boa;
+mod common;
+use common::exec;
+
+#[test]
+fn check_early_errors() {
+    assert_eq!(
+        exec("'use strict'; with (a) {}"),
+        "Error: Strict mode code may not include a with statement at position 14"
+    );
+    assert_eq!(
+        exec("'use strict'; 010"),
+        "Error: Octal literals are not allowed in strict mode. at position 14"
+    );
+    assert_eq!(
+        exec("function f(a, b) { 'use strict'; return a + b; } function g(a, a) { return a; } f(1, 2) + g(1, 2)"),
+        "5"
+    );
+    assert_eq!(exec("var implements = 1; implements"), "1");
+}
+
+#[test]
+fn check_this() {
+    assert_eq!(
+        exec("'use strict'; (function() { return typeof this; })()"),
+        "undefined"
+    );
+    assert_eq!(exec("(function() { return typeof this; })()"), "object");
+    assert_eq!(
+        exec("function f() { 'use strict'; return this; } f() === undefined"),
+        "true"
+    );
+}
+
+#[test]
+fn check_undeclared_assignment() {
+    assert_eq!(
+        exec("'use strict'; x = 1"),
+        "Error: ReferenceError: x is not defined"
+    );
+    assert_eq!(exec("x = 1; x"), "1");
+    assert_eq!(
+        exec("function f() { 'use strict'; y = 2; } try { f(); } catch (e) { e.name }"),
+        "ReferenceError"
+    );
+}
+
+#[test]
+fn check_read_only_assignment() {
+    let define = "var o = {}; Object.defineProperty(o, 'a', {value: 1, writable: false, enumerable: false, configurable: false});";
+    assert_eq!(exec(&format!("{} o.a = 2; o.a", define)), "1");
+    assert_eq!(
+        exec(&format!(
+            "'use strict'; {} try {{ o.a = 2; }} catch (e) {{ (e instanceof TypeError) + ' ' + o.a }}",
+            define
+        )),
+        "true 1"
+    );
+    assert_eq!(exec("NaN = 1; NaN"), "NaN");
+    assert_eq!(
+        exec("'use strict'; try { Infinity = 1; } catch (e) { e.name }"),
+        "TypeError"
+    );
+    assert_eq!(
+        exec("'use strict'; function f() {} try { f.name = 'g'; } catch (e) { e.name + ' ' + f.name }"),
+        "TypeError f"
+    );
+}
+
+#[test]
+fn check_primitive_assignment() {
+    assert_eq!(exec("var s = 'abc'; s.foo = 1; s.foo"), "undefined");
+    assert_eq!(
+        exec("'use strict'; try { 'abc'.foo = 1; } catch (e) { e.message }"),
+        "Cannot create property 'foo' on string 'abc'"
+    );
+}
+
+#[test]
+fn check_arguments() {
+    assert_eq!(
+        exec("function f(a) { a = 2; return arguments[0]; } f(1)"),
+        "2"
+    );
+    assert_eq!(
+        exec("function f(a) { 'use strict'; a = 2; return arguments[0]; } f(1)"),
+        "1"
+    );
+}
@@ -0,0 +1,64 @@
+extern crate boa;
+mod common;
+use boa::js::js_string::JsString;
+use common::exec;
+
+#[test]
+fn check_length_counts_code_units() {
+    assert_eq!(exec("'abc'.length"), "3");
+    assert_eq!(exec("'héllo'.length"), "5");
+    assert_eq!(exec("'😀'.length"), "2");
+    assert_eq!(JsString::from("😀x").len(), 3);
+    assert_eq!(JsString::from("😀x").to_string(), "😀x");
+}
+
+#[test]
+fn check_indexing() {
+    assert_eq!(exec("'hello'[1]"), "e");
+    assert_eq!(exec("'héllo'[1]"), "é");
+    assert_eq!(exec("'hello'[5] === undefined"), "true");
+    let s = JsString::from("añb");
+    assert_eq!(s.char_code_at(1), Some(0xF1));
+    assert_eq!(s.char_code_at(3), None);
+}
+
+#[test]
+fn check_building_long_strings() {
+    assert_eq!(
+        exec("var s = ''; var i = 0; while (i < 20000) { s += 'ab'; i++; } s.length"),
+        "40000"
+    );
+    assert_eq!(
+        exec("var s = ''; var i = 0; while (i < 1000) { s = s + i % 10; i++; } s[999] + s[500] + s.length"),
+        "901000"
+    );
+}
+
+#[test]
+fn check_substrings_and_concatenation() {
+    let s = JsString::from("the quick brown fox jumps over the lazy dog");
+    assert_eq!(s.substring(4, 9).to_string(), "quick");
+    assert_eq!(s.substring(40, 100).to_string(), "dog");
+    assert_eq!(s.substring(9, 4).len(), 0);
+    let long = s.concat(&JsString::from(" — again"));
+    assert_eq!(long.len(), s.len() + 8);
+    assert_eq!(long.substring(43, 51).to_string(), " — again");
+    assert_eq!(long.substring(0, 3), JsString::from("the"));
+}
+
+#[test]
+fn check_equality_across_storage() {
+    let latin1 = JsString::from("caf\u{e9}");
+    let wide = JsString::from("caf\u{e9}\u{2603}").substring(0, 4);
+    assert_eq!(latin1, wide);
+    assert!(JsString::from("a") < JsString::from("b"));
+    assert_eq!(exec("'ab' + 'c' === 'a' + 'bc'"), "true");
+}
+
+#[test]
+fn check_escapes_of_lone_surrogates() {
+    assert_eq!(exec("'\\uD800'.length"), "1");
+    assert_eq!(exec("'\\uD83D' + '\\uDE00' === '😀'"), "true");
+    assert_eq!(exec("'\\u{1F600}'.length + `\\uDC00x`.length"), "4");
+    assert_eq!(exec("'\\uD800' === '\\uDC00'"), "false");
+}
@@ -0,0 +1,160 @@
+extern crate boa;
+mod common;
+use boa::exec_new::{Executor, Interpreter};
+use common::{exec_statements as exec, exec_statements_on};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn check_elements() {
+    assert_eq!(
+        exec("var a = new Uint8Array(4); a[1] = 7; a.join()"),
+        "0,7,0,0"
+    );
+    assert_eq!(exec("var a = new Int16Array([1, 2, 3]); a.length"), "3");
+    assert_eq!(exec("var a = new Float64Array(2); a.byteLength"), "16");
+    assert_eq!(
+        exec("var a = new Uint8Array(2); a[5] = 1; a[5]"),
+        "undefined"
+    );
+    assert_eq!(
+        exec("var a = new Uint8Array(2); a['1.5'] = 1; a['1.5']"),
+        "undefined"
+    );
+    assert_eq!(exec("Int32Array.BYTES_PER_ELEMENT"), "4");
+    assert_eq!(exec("Uint16Array.of(1, 2, 3).toString()"), "1,2,3");
+    assert_eq!(
+        exec("Uint8Array(2)"),
+        "Error: TypeError: Constructor Uint8Array requires 'new'"
+    );
+}
+
+#[test]
+fn check_conversions() {
+    assert_eq!(exec("var a = new Uint8Array(1); a[0] = 257; a[0]"), "1");
+    assert_eq!(exec("var a = new Int8Array(1); a[0] = 255; a[0]"), "-1");
+    assert_eq!(
+        exec("var a = new Uint8ClampedArray(2); a[0] = 300; a[1] = 1.5; a.join()"),
+        "255,2"
+    );
+    assert_eq!(
+        exec("var a = new Uint32Array(1); a[0] = -1; a[0]"),
+        "4294967295"
+    );
+    assert_eq!(exec("var a = new Float32Array(1); a[0] = 0.5; a[0]"), "0.5");
+    assert_eq!(
+        exec("var a = new BigInt64Array(1); a[0] = 2n ** 63n; a[0]"),
+        "-9223372036854775808"
+    );
+    assert_eq!(
+        exec("var a = new BigUint64Array(1); a[0] = -1n; a[0]"),
+        "18446744073709551615"
+    );
+    assert_eq!(
+        exec("var a = new BigInt64Array(1); a[0] = 1; a[0]"),
+        "Error: TypeError: Cannot convert 1 to a BigInt"
+    );
+}
+
+#[test]
+fn check_shared_buffers() {
+    assert_eq!(
+        exec("var b = new ArrayBuffer(8); var a = new Uint8Array(b, 2, 4); var c = new Uint8Array(b); a[0] = 9; c[2]"),
+        "9"
+    );
+    assert_eq!(
+        exec("var a = new Uint8Array([1, 2, 3, 4]); var s = a.subarray(1, 3); s[0] = 5; a.join()"),
+        "1,5,3,4"
+    );
+    assert_eq!(
+        exec("var a = new Uint8Array([1, 2, 3, 4]); var s = a.slice(1, 3); s[0] = 5; a.join()"),
+        "1,2,3,4"
+    );
+    assert_eq!(
+        exec("ArrayBuffer.isView(new DataView(new ArrayBuffer(1)))"),
+        "true"
+    );
+    assert_eq!(
+        exec("new Uint16Array(new ArrayBuffer(3))"),
+        "Error: RangeError: byte length of Uint16Array should be a multiple of 2"
+    );
+}
+
+#[test]
+fn check_resizable_buffers() {
+    assert_eq!(
+        exec("var b = new ArrayBuffer(2, {maxByteLength: 8}); var a = new Uint8Array(b); b.resize(6); a.length"),
+        "6"
+    );
+    assert_eq!(
+        exec("var b = new ArrayBuffer(4, {maxByteLength: 8}); var a = new Uint8Array(b, 0, 4); b.resize(2); a.length"),
+        "0"
+    );
+    assert_eq!(
+        exec("var b = new ArrayBuffer(2, {maxByteLength: 8}); b.resizable"),
+        "true"
+    );
+    assert_eq!(exec("var b = new ArrayBuffer(2); b.maxByteLength"), "2");
+    assert_eq!(
+        exec("var b = new ArrayBuffer(2, {maxByteLength: 4}); b.resize(5)"),
+        "Error: RangeError: Invalid array buffer length"
+    );
+    assert_eq!(
+        exec("(new ArrayBuffer(2)).resize(1)"),
+        "Error: TypeError: ArrayBuffer is not resizable"
+    );
+}
+
+#[test]
+fn check_data_view() {
+    assert_eq!(
+        exec("var v = new DataView(new ArrayBuffer(4)); v.setUint16(0, 258); v.getUint8(0)"),
+        "1"
+    );
+    assert_eq!(
+        exec("var v = new DataView(new ArrayBuffer(4)); v.setUint16(0, 258, true); v.getUint8(0)"),
+        "2"
+    );
+    assert_eq!(
+        exec("var v = new DataView(new ArrayBuffer(8)); v.setFloat64(0, Math.PI); v.getFloat64(0)"),
+        "3.141592653589793"
+    );
+    assert_eq!(
+        exec("var v = new DataView(new ArrayBuffer(8)); v.setBigInt64(0, -2n, true); v.getBigUint64(0, true)"),
+        "18446744073709551614"
+    );
+    assert_eq!(
+        exec("var v = new DataView(new ArrayBuffer(4), 1); v.getInt32(0)"),
+        "Error: RangeError: Offset is outside the bounds of the DataView"
+    );
+    assert_eq!(
+        exec("new DataView(new ArrayBuffer(4), 5)"),
+        "Error: RangeError: Start offset 5 is outside the bounds of the buffer"
+    );
+    assert_eq!(
+        exec("var v = new DataView(new ArrayBuffer(4), 1, 2); v.byteLength"),
+        "2"
+    );
+}
+
+#[test]
+fn check_host_bytes() {
+    let mut engine: Interpreter = Executor::new();
+    let bytes = Rc::new(RefCell::new(vec![1, 2, 3]));
+    let buffer = engine.share_array_buffer(bytes.clone());
+    engine.set_global("shared".to_string(), buffer);
+    let copied = engine.copy_array_buffer(&[4, 5]);
+    engine.set_global("copied".to_string(), copied);
+    assert_eq!(
+        exec_statements_on(&mut engine, "(new Uint8Array(shared)).join()"),
+        "1,2,3"
+    );
+    exec_statements_on(&mut engine, "(new Uint8Array(shared))[0] = 42;");
+    assert_eq!(*bytes.borrow(), vec![42, 2, 3]);
+    bytes.borrow_mut()[1] = 7;
+    assert_eq!(
+        exec_statements_on(&mut engine, "(new Uint8Array(shared))[1]"),
+        "7"
+    );
+    assert_eq!(exec_statements_on(&mut engine, "copied.byteLength"), "2");
+}
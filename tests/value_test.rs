@@ -1,18 +1,18 @@
 extern crate boa;
-use boa::js::value::{ToValue, ValueData};
+use boa::js::value::{ToValue, Value, ValueData};
 
 #[test]
 fn check_is_object() {
     let val = ValueData::new_obj(None);
-    assert_eq!(val.is_object(), true);
+    assert!(val.is_object());
 }
 
 #[test]
 fn check_string_to_value() {
     let s = String::from("Hello");
     let v = s.to_value();
-    assert_eq!(v.is_string(), true);
-    assert_eq!(v.is_null(), false);
+    assert!(v.is_string());
+    assert!(!v.is_null());
 }
 
 #[test]
@@ -30,3 +30,46 @@ fn check_get_set_field() {
     obj.set_field_slice("foo", s);
     assert_eq!(obj.get_field_slice("foo").to_string(), "bar");
 }
+
+#[test]
+fn check_value_size() {
+    assert_eq!(std::mem::size_of::<Value>(), 8);
+}
+
+#[test]
+fn check_immediates() {
+    assert_eq!(Value::undefined().get_type(), "undefined");
+    assert_eq!(Value::null().to_string(), "null");
+    assert!(Value::boolean(true).is_true());
+    assert!(!Value::boolean(false).is_true());
+    assert!(Value::number(-0.0).to_num().is_sign_negative());
+    assert!(Value::number(f64::NAN).to_num().is_nan());
+    assert!(Value::number(-f64::NAN).is_double());
+    assert_eq!(Value::number(f64::INFINITY).to_string(), "Infinity");
+}
+
+#[test]
+fn check_integers_are_numbers() {
+    let v = 42.to_value();
+    assert!(v.is_double());
+    match *v.data() {
+        ValueData::Number(n) => assert_eq!(n, 42.0),
+        _ => panic!("expected a number"),
+    }
+    assert!(v.strict_equals(&42.0.to_value()));
+    assert!(ValueData::Integer(1).is_double());
+}
+
+#[test]
+fn check_heap_values_survive_collection() {
+    let obj = ValueData::new_obj(None);
+    obj.set_field_slice("s", "text".to_value());
+    obj.set_field_slice("n", 1.5.to_value());
+    let copy = obj.clone();
+    drop(obj);
+    boa::gc::collect();
+    assert_eq!(copy.get_field_slice("s").to_string(), "text");
+    assert_eq!(copy.get_field_slice("n").to_num(), 1.5);
+    assert!(copy.same_heap_cell(&copy.clone()));
+    assert!(!copy.same_heap_cell(&ValueData::new_obj(None)));
+}